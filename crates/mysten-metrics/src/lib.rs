@@ -633,9 +633,7 @@ pub fn start_prometheus_server(addr: SocketAddr) -> RegistryService {
         return registry_service;
     }
 
-    let app = Router::new()
-        .route(METRICS_ROUTE, get(metrics))
-        .layer(Extension(registry_service.clone()));
+    let app = metrics_router(registry_service.clone());
 
     tokio::spawn(async move {
         let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
@@ -647,6 +645,14 @@ pub fn start_prometheus_server(addr: SocketAddr) -> RegistryService {
     registry_service
 }
 
+/// A router that serves the metrics in `registry_service` on [METRICS_ROUTE], for servers other
+/// than the one started by [start_prometheus_server] to serve them.
+pub fn metrics_router(registry_service: RegistryService) -> Router {
+    Router::new()
+        .route(METRICS_ROUTE, get(metrics))
+        .layer(Extension(registry_service))
+}
+
 pub async fn metrics(
     Extension(registry_service): Extension<RegistryService>,
 ) -> (StatusCode, String) {
@@ -191,6 +191,8 @@ enum NextOp {
         num_commands: u16,
         /// Gas used in the executed transction
         gas_used: u64,
+        /// Whether the transaction failed because of an insufficient address balance
+        insufficient_balance: bool,
         /// The payload updated with the effects of the transaction
         payload: Box<dyn Payload>,
     },
@@ -398,6 +400,7 @@ impl Driver<(BenchmarkStats, StressStats)> for BenchDriver {
                 duration: Duration::ZERO,
                 num_error_txes: 0,
                 num_success_txes: 0,
+                num_insufficient_balance_txes: 0,
                 num_expected_error_txes: 0,
                 num_success_cmds: 0,
                 total_gas_used: 0,
@@ -728,6 +731,7 @@ async fn run_bench_worker(
     let mut num_error_txes = 0;
     let mut num_expected_error_txes = 0;
     let mut num_success_cmds = 0;
+    let mut num_insufficient_balance_txes = 0;
     let mut num_no_gas = 0;
     let mut num_in_flight: u64 = 0;
     let mut num_submitted = 0;
@@ -814,6 +818,7 @@ async fn run_bench_worker(
                     num_commands,
                     payload,
                     gas_used: effects.gas_used(),
+                    insufficient_balance: effects.is_insufficient_balance_for_withdraw(),
                 }
             }
             Err(err) => {
@@ -921,6 +926,7 @@ async fn run_bench_worker(
                             num_expected_error_txes,
                             num_success_txes,
                             num_success_cmds,
+                            num_insufficient_balance_txes,
                             latency_ms:HistogramWrapper{
                                 histogram:latency_histogram.clone()
                             },
@@ -935,6 +941,7 @@ async fn run_bench_worker(
                 num_error_txes = 0;
                 num_expected_error_txes = 0;
                 num_success_cmds = 0;
+                num_insufficient_balance_txes = 0;
                 num_no_gas = 0;
                 num_submitted = 0;
                 worker_gas_used = 0;
@@ -1014,9 +1021,10 @@ async fn run_bench_worker(
                             break;
                         }
                     }
-                    NextOp::Response { latency, num_commands, payload, gas_used } => {
+                    NextOp::Response { latency, num_commands, payload, gas_used, insufficient_balance } => {
                         num_success_txes += 1;
                         num_success_cmds += num_commands as u64;
+                        num_insufficient_balance_txes += insufficient_balance as u64;
                         num_in_flight -= 1;
                         worker_gas_used += gas_used;
                         free_pool.push_back(payload);
@@ -1047,6 +1055,7 @@ async fn run_bench_worker(
                 num_expected_error_txes,
                 num_success_txes,
                 num_success_cmds,
+                num_insufficient_balance_txes,
                 total_gas_used: worker_gas_used,
                 latency_ms: HistogramWrapper {
                     histogram: latency_histogram,
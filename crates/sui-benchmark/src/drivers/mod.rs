@@ -128,6 +128,11 @@ pub struct BenchmarkStats {
     pub num_expected_error_txes: u64,
     /// Number of transactions that were executed successfully
     pub num_success_txes: u64,
+    /// Number of executed transactions that failed because the address balance they withdraw from
+    /// was insufficient. These are also counted in `num_success_txes`, which counts every
+    /// transaction that produced effects.
+    #[serde(default)]
+    pub num_insufficient_balance_txes: u64,
     /// Total number of commands in transactions that executed successfully
     pub num_success_cmds: u64,
     /// Total gas used
@@ -141,6 +146,7 @@ impl BenchmarkStats {
         self.num_error_txes += sample_stat.num_error_txes;
         self.num_expected_error_txes += sample_stat.num_expected_error_txes;
         self.num_success_txes += sample_stat.num_success_txes;
+        self.num_insufficient_balance_txes += sample_stat.num_insufficient_balance_txes;
         self.num_success_cmds += sample_stat.num_success_cmds;
         self.total_gas_used += sample_stat.total_gas_used;
         self.latency_ms
@@ -159,6 +165,7 @@ impl BenchmarkStats {
                 "cps",
                 "error%",
                 "expected error%",
+                "insufficient balance%",
                 "latency (min)",
                 "latency (p50)",
                 "latency (p99)",
@@ -177,6 +184,9 @@ impl BenchmarkStats {
             (100 * self.num_expected_error_txes) as f32
                 / (self.num_expected_error_txes + self.num_success_txes) as f32,
         ));
+        row.add_cell(Cell::new(
+            (100 * self.num_insufficient_balance_txes) as f32 / self.num_success_txes as f32,
+        ));
         row.add_cell(Cell::new(self.latency_ms.histogram.min()));
         row.add_cell(Cell::new(self.latency_ms.histogram.value_at_quantile(0.5)));
         row.add_cell(Cell::new(self.latency_ms.histogram.value_at_quantile(0.99)));
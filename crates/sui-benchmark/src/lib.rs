@@ -184,6 +184,22 @@ impl ExecutionEffects {
         }
     }
 
+    pub fn is_insufficient_balance_for_withdraw(&self) -> bool {
+        match self {
+            ExecutionEffects::FinalizedTransactionEffects(effects, ..) => matches!(
+                effects.data().status(),
+                sui_types::execution_status::ExecutionStatus::Failure {
+                    error: ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+                    ..
+                }
+            ),
+            ExecutionEffects::SuiTransactionBlockEffects(sui_tx_effects) => {
+                let status = format!("{}", sui_tx_effects.status());
+                status.contains("InsufficientBalanceForWithdraw")
+            }
+        }
+    }
+
     pub fn status(&self) -> String {
         match self {
             ExecutionEffects::FinalizedTransactionEffects(effects, ..) => {
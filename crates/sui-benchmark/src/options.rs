@@ -193,6 +193,9 @@ pub enum RunSpec {
         // relative weight of party transactions in the benchmark workload
         #[clap(long, num_args(1..), value_delimiter = ',', default_values_t = [0])]
         party: Vec<u32>,
+        // relative weight of address balance deposit and withdraw transactions in the benchmark workload
+        #[clap(long, num_args(1..), value_delimiter = ',', default_values_t = [0])]
+        address_balance: Vec<u32>,
 
         // --- workload-specific options --- (TODO: use subcommands or similar)
         // 100 for max hotness i.e all requests target
@@ -226,6 +229,11 @@ pub enum RunSpec {
         // See `ExpectedFailureType` enum for `expected_failure_type`
         #[clap(long, num_args(1..), value_delimiter = ',', default_values_t = [0])]
         expected_failure_type: Vec<u32>,
+        // The number of addresses that address balance transactions deposit into and withdraw from.
+        // Fewer addresses means more withdraws contend for the same balance, 1 being the maximum
+        // contention.
+        #[clap(long, num_args(1..), value_delimiter = ',', default_values_t = [1])]
+        address_balance_num_accounts: Vec<u64>,
 
         // --- generic options ---
         // Target qps
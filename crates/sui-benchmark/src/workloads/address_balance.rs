@@ -0,0 +1,268 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use std::sync::Arc;
+use sui_test_transaction_builder::TestTransactionBuilder;
+use sui_types::{
+    base_types::SuiAddress,
+    crypto::{get_key_pair, AccountKeyPair},
+    gas_coin::GAS,
+    transaction::{Argument, BalanceWithdrawArg, Command, Transaction},
+    type_input::TypeInput,
+    SUI_FRAMEWORK_PACKAGE_ID,
+};
+use tracing::error;
+
+use crate::drivers::Interval;
+use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::payload::Payload;
+use crate::workloads::workload::{
+    ExpectedFailureType, Workload, WorkloadBuilder, ESTIMATED_COMPUTATION_COST, MAX_GAS_FOR_TESTING,
+};
+use crate::workloads::{Gas, GasCoinConfig, WorkloadBuilderInfo, WorkloadParams};
+use crate::ProgrammableTransactionBuilder;
+use crate::{ExecutionEffects, ValidatorProxy};
+
+/// Number of mist each payload deposits into, and then withdraws from, its address balance.
+const AMOUNT: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// Split `AMOUNT` off the gas coin and send it to the sender's address balance.
+    Deposit,
+    /// Withdraw `AMOUNT` from the sender's address balance and merge it back into the gas coin.
+    Withdraw,
+}
+
+/// A payload that alternates between depositing into and withdrawing from its sender's address
+/// balance. Many payloads share each sender, so their withdraws contend for the same balance:
+/// a withdraw is only scheduled once the balance it reserves against has settled, and fails with
+/// `InsufficientBalanceForWithdraw` if concurrent withdraws have already reserved the deposits it
+/// was counting on.
+#[derive(Debug)]
+pub struct AddressBalanceTestPayload {
+    gas: Gas,
+    op: Op,
+    system_state_observer: Arc<SystemStateObserver>,
+}
+
+impl Payload for AddressBalanceTestPayload {
+    fn make_new_payload(&mut self, effects: &ExecutionEffects) {
+        self.gas.0 = effects.gas_object().0;
+
+        // A failed withdraw is retried, to keep the number of deposits and withdraws balanced.
+        if effects.is_ok() {
+            self.op = match self.op {
+                Op::Deposit => Op::Withdraw,
+                Op::Withdraw => Op::Deposit,
+            };
+        } else if !effects.is_insufficient_balance_for_withdraw() {
+            effects.print_gas_summary();
+            error!(
+                "Address balance tx failed... Status: {:?}",
+                effects.status()
+            );
+        }
+    }
+
+    fn make_transaction(&mut self) -> Transaction {
+        let (gas, sender, keypair) = &self.gas;
+        let rgp = self
+            .system_state_observer
+            .state
+            .borrow()
+            .reference_gas_price;
+
+        let mut builder = ProgrammableTransactionBuilder::new();
+        match self.op {
+            Op::Deposit => {
+                let amount = builder.pure(AMOUNT).unwrap();
+                let recipient = builder.pure(*sender).unwrap();
+                let Argument::Result(coin) =
+                    builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]))
+                else {
+                    unreachable!("commands always produce results");
+                };
+
+                let balance = builder.programmable_move_call(
+                    SUI_FRAMEWORK_PACKAGE_ID,
+                    Identifier::new("coin").unwrap(),
+                    Identifier::new("into_balance").unwrap(),
+                    vec![GAS::type_tag()],
+                    vec![Argument::NestedResult(coin, 0)],
+                );
+
+                builder.programmable_move_call(
+                    SUI_FRAMEWORK_PACKAGE_ID,
+                    Identifier::new("balance").unwrap(),
+                    Identifier::new("send_to_account").unwrap(),
+                    vec![GAS::type_tag()],
+                    vec![balance, recipient],
+                );
+            }
+
+            Op::Withdraw => {
+                builder
+                    .balance_withdraw(BalanceWithdrawArg::new_with_amount(
+                        AMOUNT,
+                        TypeInput::from(GAS::type_tag()),
+                    ))
+                    .unwrap();
+
+                let amount = builder.pure(AMOUNT).unwrap();
+                let balance = builder.programmable_move_call(
+                    SUI_FRAMEWORK_PACKAGE_ID,
+                    Identifier::new("balance").unwrap(),
+                    Identifier::new("withdraw_from_account").unwrap(),
+                    vec![GAS::type_tag()],
+                    vec![amount],
+                );
+
+                let coin = builder.programmable_move_call(
+                    SUI_FRAMEWORK_PACKAGE_ID,
+                    Identifier::new("coin").unwrap(),
+                    Identifier::new("from_balance").unwrap(),
+                    vec![GAS::type_tag()],
+                    vec![balance],
+                );
+
+                builder.command(Command::MergeCoins(Argument::GasCoin, vec![coin]));
+            }
+        }
+
+        TestTransactionBuilder::new(*sender, *gas, rgp)
+            .programmable(builder.finish())
+            .build_and_sign(keypair.as_ref())
+    }
+
+    fn get_failure_type(&self) -> Option<ExpectedFailureType> {
+        None
+    }
+}
+
+impl std::fmt::Display for AddressBalanceTestPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "address_balance")
+    }
+}
+
+#[derive(Debug)]
+pub struct AddressBalanceWorkloadBuilder {
+    num_accounts: u64,
+    num_payloads: u64,
+}
+
+impl AddressBalanceWorkloadBuilder {
+    pub fn from(
+        workload_weight: f32,
+        target_qps: u64,
+        num_workers: u64,
+        in_flight_ratio: u64,
+        num_accounts: u64,
+        duration: Interval,
+        group: u32,
+    ) -> Option<WorkloadBuilderInfo> {
+        let target_qps = (workload_weight * target_qps as f32).ceil() as u64;
+        let num_workers = (workload_weight * num_workers as f32).ceil() as u64;
+        let max_ops = target_qps * in_flight_ratio;
+        if max_ops == 0 || num_workers == 0 {
+            None
+        } else {
+            let workload_params = WorkloadParams {
+                target_qps,
+                num_workers,
+                max_ops,
+                duration,
+                group,
+            };
+            let workload_builder = Box::<dyn WorkloadBuilder<dyn Payload>>::from(Box::new(
+                AddressBalanceWorkloadBuilder {
+                    num_accounts: num_accounts.clamp(1, max_ops),
+                    num_payloads: max_ops,
+                },
+            ));
+            let builder_info = WorkloadBuilderInfo {
+                workload_params,
+                workload_builder,
+            };
+            Some(builder_info)
+        }
+    }
+}
+
+#[async_trait]
+impl WorkloadBuilder<dyn Payload> for AddressBalanceWorkloadBuilder {
+    async fn generate_coin_config_for_init(&self) -> Vec<GasCoinConfig> {
+        vec![]
+    }
+
+    async fn generate_coin_config_for_payloads(&self) -> Vec<GasCoinConfig> {
+        let accounts: Vec<(SuiAddress, Arc<AccountKeyPair>)> = (0..self.num_accounts)
+            .map(|_| {
+                let (address, keypair) = get_key_pair();
+                (address, Arc::new(keypair))
+            })
+            .collect();
+
+        // Payloads are spread evenly across the accounts, each with its own gas coin, which also
+        // funds its deposits.
+        (0..self.num_payloads)
+            .map(|i| {
+                let (address, keypair) = &accounts[(i % self.num_accounts) as usize];
+                GasCoinConfig {
+                    amount: MAX_GAS_FOR_TESTING + ESTIMATED_COMPUTATION_COST,
+                    address: *address,
+                    keypair: keypair.clone(),
+                }
+            })
+            .collect()
+    }
+
+    async fn build(
+        &self,
+        _init_gas: Vec<Gas>,
+        payload_gas: Vec<Gas>,
+    ) -> Box<dyn Workload<dyn Payload>> {
+        Box::<dyn Workload<dyn Payload>>::from(Box::new(AddressBalanceWorkload { payload_gas }))
+    }
+}
+
+#[derive(Debug)]
+pub struct AddressBalanceWorkload {
+    payload_gas: Vec<Gas>,
+}
+
+#[async_trait]
+impl Workload<dyn Payload> for AddressBalanceWorkload {
+    async fn init(
+        &mut self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        _system_state_observer: Arc<SystemStateObserver>,
+    ) {
+        return;
+    }
+
+    async fn make_test_payloads(
+        &self,
+        _proxy: Arc<dyn ValidatorProxy + Sync + Send>,
+        system_state_observer: Arc<SystemStateObserver>,
+    ) -> Vec<Box<dyn Payload>> {
+        self.payload_gas
+            .iter()
+            .map(|gas| {
+                Box::new(AddressBalanceTestPayload {
+                    gas: gas.clone(),
+                    op: Op::Deposit,
+                    system_state_observer: system_state_observer.clone(),
+                })
+            })
+            .map(|b| Box::<dyn Payload>::from(b))
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        "AddressBalance"
+    }
+}
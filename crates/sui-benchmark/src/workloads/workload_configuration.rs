@@ -5,6 +5,7 @@ use crate::bank::BenchmarkBank;
 use crate::drivers::Interval;
 use crate::options::{Opts, RunSpec};
 use crate::system_state_observer::SystemStateObserver;
+use crate::workloads::address_balance::AddressBalanceWorkloadBuilder;
 use crate::workloads::batch_payment::BatchPaymentWorkloadBuilder;
 use crate::workloads::delegation::DelegationWorkloadBuilder;
 use crate::workloads::party::PartyWorkloadBuilder;
@@ -37,6 +38,7 @@ pub struct WorkloadWeights {
     pub randomized_transaction: u32,
     pub slow: u32,
     pub party: u32,
+    pub address_balance: u32,
 }
 
 pub struct WorkloadConfig {
@@ -50,6 +52,7 @@ pub struct WorkloadConfig {
     pub shared_counter_hotness_factor: u32,
     pub num_shared_counters: Option<u64>,
     pub shared_counter_max_tip: u64,
+    pub address_balance_num_accounts: u64,
     pub target_qps: u64,
     pub in_flight_ratio: u64,
     pub duration: Interval,
@@ -79,12 +82,14 @@ impl WorkloadConfiguration {
                 randomized_transaction,
                 slow,
                 party,
+                address_balance,
                 shared_counter_hotness_factor,
                 num_shared_counters,
                 shared_counter_max_tip,
                 batch_payment_size,
                 adversarial_cfg,
                 expected_failure_type,
+                address_balance_num_accounts,
                 target_qps,
                 num_workers,
                 in_flight_ratio,
@@ -115,6 +120,7 @@ impl WorkloadConfiguration {
                             randomized_transaction: randomized_transaction[i],
                             slow: slow[i],
                             party: party[i],
+                            address_balance: address_balance[i],
                         },
                         adversarial_cfg: AdversarialPayloadCfg::from_str(&adversarial_cfg[i])
                             .unwrap(),
@@ -126,6 +132,7 @@ impl WorkloadConfiguration {
                         shared_counter_hotness_factor: shared_counter_hotness_factor[i],
                         num_shared_counters: num_shared_counters.as_ref().map(|n| n[i]),
                         shared_counter_max_tip: shared_counter_max_tip[i],
+                        address_balance_num_accounts: address_balance_num_accounts[i],
                         target_qps: target_qps[i],
                         in_flight_ratio: in_flight_ratio[i],
                         duration: duration[i],
@@ -200,6 +207,7 @@ impl WorkloadConfiguration {
             shared_counter_hotness_factor,
             num_shared_counters,
             shared_counter_max_tip,
+            address_balance_num_accounts,
             target_qps,
             in_flight_ratio,
             duration,
@@ -223,7 +231,8 @@ impl WorkloadConfiguration {
             + weights.expected_failure
             + weights.randomized_transaction
             + weights.slow
-            + weights.party;
+            + weights.party
+            + weights.address_balance;
         let reference_gas_price = system_state_observer.state.borrow().reference_gas_price;
         let mut workload_builders = vec![];
         let shared_workload = SharedCounterWorkloadBuilder::from(
@@ -339,6 +348,16 @@ impl WorkloadConfiguration {
             group,
         );
         workload_builders.push(party_workload);
+        let address_balance_workload = AddressBalanceWorkloadBuilder::from(
+            weights.address_balance as f32 / total_weight as f32,
+            target_qps,
+            num_workers,
+            in_flight_ratio,
+            address_balance_num_accounts,
+            duration,
+            group,
+        );
+        workload_builders.push(address_balance_workload);
         workload_builders
     }
 }
@@ -1104,6 +1104,7 @@ mod test {
         expected_failure_weight: u32,
         expected_failure_config: ExpectedFailurePayloadCfg,
         party_weight: u32,
+        address_balance_weight: u32,
     }
 
     impl Default for SimulatedLoadConfig {
@@ -1129,6 +1130,7 @@ mod test {
                 },
                 // TODO: Set this to 1 once party object is enabled in mainnet protocol config.
                 party_weight: 0,
+                address_balance_weight: 0,
             }
         }
     }
@@ -1235,6 +1237,7 @@ mod test {
             randomized_transaction: config.randomized_transaction_weight,
             slow: config.slow_weight,
             party: config.party_weight,
+            address_balance: config.address_balance_weight,
         };
 
         let workload_config = WorkloadConfig {
@@ -1248,6 +1251,7 @@ mod test {
             shared_counter_hotness_factor: config.shared_counter_hotness_factor,
             num_shared_counters: config.num_shared_counters,
             shared_counter_max_tip,
+            address_balance_num_accounts: 1,
             target_qps,
             in_flight_ratio,
             duration,
@@ -0,0 +1,258 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Overrides for individual fields of a config file, for deployments where editing the file is
+//! awkward, e.g. when it is baked into a container image.
+//!
+//! An override sets the field at a path of serde field names (or sequence indices) to a value
+//! written in YAML. Overrides can come from:
+//!
+//! - Environment variables, named by a prefix followed by the path, with `__` between segments,
+//!   e.g. `SUI_NODE__DB_PATH=/opt/sui/db` or `SUI_NODE__P2P_CONFIG__LISTEN_ADDRESS=0.0.0.0:8084`.
+//!   Segments are matched against the field names already in the file case-insensitively, and
+//!   treating `-` and `_` as the same, so they also work for kebab-case fields.
+//! - Command line flags, as `path=value` with `.` between segments, e.g.
+//!   `--config-override db-path=/opt/sui/db`.
+//!
+//! Overrides are applied on top of the file, environment variables first, in order of name, and
+//! then flags, in the order they were given, so that flags take precedence over environment
+//! variables, which take precedence over the file. Values are parsed as YAML, so strings that
+//! would parse as something else (e.g. `0x...` IDs) need to be quoted.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+use serde_yaml::{Mapping, Value};
+
+/// Separates the segments of a path in the name of an environment variable.
+const ENV_SEPARATOR: &str = "__";
+
+/// Separates the segments of a path in a command line flag.
+const FLAG_SEPARATOR: char = '.';
+
+/// Sets the field at `path` to `value`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigOverride {
+    pub path: Vec<String>,
+    pub value: Value,
+}
+
+impl ConfigOverride {
+    /// Overrides from the process's environment variables whose names start with `prefix`.
+    pub fn from_env(prefix: &str) -> anyhow::Result<Vec<Self>> {
+        Self::from_env_vars(prefix, std::env::vars())
+    }
+
+    /// Overrides from the variables in `vars` whose names start with `prefix`, in order of name.
+    pub fn from_env_vars(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix(prefix)?.to_owned(), value)))
+            .collect();
+        vars.sort();
+
+        vars.into_iter()
+            .map(|(name, value)| {
+                let path = name
+                    .split(ENV_SEPARATOR)
+                    .map(|segment| segment.to_lowercase().replace('_', "-"))
+                    .collect();
+
+                Self::new(path, &value).with_context(|| format!("Invalid override {prefix}{name}"))
+            })
+            .collect()
+    }
+
+    fn new(path: Vec<String>, value: &str) -> anyhow::Result<Self> {
+        if path.iter().any(|segment| segment.is_empty()) {
+            bail!("Empty path segment");
+        }
+
+        let value = serde_yaml::from_str(value).context("Failed to parse value as YAML")?;
+        Ok(Self { path, value })
+    }
+
+    /// Set the field that this override is for in `config`, creating any fields along the way
+    /// that do not exist yet.
+    pub fn apply(&self, config: &mut Value) -> anyhow::Result<()> {
+        let mut current = config;
+        for (i, segment) in self.path.iter().enumerate() {
+            if current.is_null() {
+                *current = Value::Mapping(Mapping::new());
+            }
+
+            current = match current {
+                Value::Mapping(mapping) => {
+                    let key = matching_key(mapping, segment)
+                        .unwrap_or_else(|| Value::String(segment.clone()));
+                    mapping.entry(key).or_insert(Value::Null)
+                }
+
+                Value::Sequence(sequence) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| sequence.get_mut(index))
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "No element {segment} in the sequence at '{}'",
+                            self.path[..i].join(".")
+                        )
+                    })?,
+
+                _ => bail!(
+                    "Cannot override '{}', because '{}' is not a mapping",
+                    self.path.join("."),
+                    self.path[..i].join("."),
+                ),
+            };
+        }
+
+        *current = self.value.clone();
+        Ok(())
+    }
+}
+
+/// Apply `overrides` to `config` in order, so that later overrides take precedence over earlier
+/// ones.
+pub fn apply_overrides(config: &mut Value, overrides: &[ConfigOverride]) -> anyhow::Result<()> {
+    for config_override in overrides {
+        config_override.apply(config)?;
+    }
+
+    Ok(())
+}
+
+/// The key in `mapping` that `segment` refers to, if there is one, ignoring case and the
+/// difference between `-` and `_`.
+fn matching_key(mapping: &Mapping, segment: &str) -> Option<Value> {
+    let normalize = |s: &str| s.to_lowercase().replace('-', "_");
+    let segment = normalize(segment);
+    mapping
+        .keys()
+        .find(|key| key.as_str().is_some_and(|key| normalize(key) == segment))
+        .cloned()
+}
+
+impl FromStr for ConfigOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected an override of the form 'path=value'"))?;
+
+        let path = path.split(FLAG_SEPARATOR).map(str::to_owned).collect();
+        Self::new(path, value)
+    }
+}
+
+impl fmt::Display for ConfigOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = serde_yaml::to_string(&self.value).map_err(|_| fmt::Error)?;
+        write!(f, "{}={}", self.path.join("."), value.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+db-path: /var/sui/db
+p2p-config:
+  listen-address: 127.0.0.1:8084
+  seed-peers:
+    - address: /dns/peer-0/udp/8084
+    - address: /dns/peer-1/udp/8084
+metrics:
+  push_interval_seconds: 60
+"#;
+
+    fn config() -> Value {
+        serde_yaml::from_str(CONFIG).unwrap()
+    }
+
+    #[test]
+    fn test_flag_overrides() {
+        let mut config = config();
+        let overrides: Vec<ConfigOverride> = [
+            "db-path=/opt/sui/db",
+            "p2p-config.seed-peers.1.address=/dns/peer-2/udp/8084",
+            "metrics.push_interval_seconds=10",
+            "enable-index-processing=false",
+        ]
+        .into_iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+
+        apply_overrides(&mut config, &overrides).unwrap();
+        assert_eq!(config["db-path"], Value::from("/opt/sui/db"));
+        assert_eq!(
+            config["p2p-config"]["seed-peers"][1]["address"],
+            Value::from("/dns/peer-2/udp/8084"),
+        );
+        assert_eq!(config["metrics"]["push_interval_seconds"], Value::from(10));
+        assert_eq!(config["enable-index-processing"], Value::from(false));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = [
+            ("SUI_NODE__P2P_CONFIG__LISTEN_ADDRESS", "0.0.0.0:8084"),
+            ("SUI_NODE__METRICS__PUSH_INTERVAL_SECONDS", "30"),
+            (
+                "SUI_NODE__STATE_DEBUG_DUMP_CONFIG__DUMP_FILE_DIRECTORY",
+                "/tmp",
+            ),
+            ("OTHER__DB_PATH", "/ignored"),
+        ]
+        .map(|(name, value)| (name.to_owned(), value.to_owned()));
+
+        let overrides = ConfigOverride::from_env_vars("SUI_NODE__", vars).unwrap();
+        assert_eq!(overrides.len(), 3);
+
+        let mut config = config();
+        apply_overrides(&mut config, &overrides).unwrap();
+        assert_eq!(config["db-path"], Value::from("/var/sui/db"));
+        assert_eq!(
+            config["p2p-config"]["listen-address"],
+            Value::from("0.0.0.0:8084")
+        );
+
+        // Fields that are already in the file keep their names, and new fields are kebab-case.
+        assert_eq!(config["metrics"]["push_interval_seconds"], Value::from(30));
+        assert_eq!(
+            config["state-debug-dump-config"]["dump-file-directory"],
+            Value::from("/tmp"),
+        );
+    }
+
+    #[test]
+    fn test_flags_take_precedence_over_env() {
+        let vars = [("SUI_NODE__DB_PATH".to_owned(), "/env/db".to_owned())];
+        let mut overrides = ConfigOverride::from_env_vars("SUI_NODE__", vars).unwrap();
+        overrides.push("db-path=/flag/db".parse().unwrap());
+
+        let mut config = config();
+        apply_overrides(&mut config, &overrides).unwrap();
+        assert_eq!(config["db-path"], Value::from("/flag/db"));
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!("db-path".parse::<ConfigOverride>().is_err());
+        assert!("p2p-config..listen-address=x"
+            .parse::<ConfigOverride>()
+            .is_err());
+
+        let mut config = config();
+        let through_scalar: ConfigOverride = "db-path.nested=x".parse().unwrap();
+        assert!(through_scalar.apply(&mut config).is_err());
+
+        let out_of_bounds: ConfigOverride = "p2p-config.seed-peers.2.address=x".parse().unwrap();
+        assert!(out_of_bounds.apply(&mut config).is_err());
+    }
+}
@@ -9,15 +9,21 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::trace;
 
+use crate::config_overrides::{apply_overrides, ConfigOverride};
+
 pub mod certificate_deny_config;
+pub mod config_overrides;
 pub mod dynamic_transaction_signing_checks;
 pub mod genesis;
 pub mod local_ip_utils;
+pub mod network_profiles;
 pub mod node;
 pub mod node_config_metrics;
 pub mod object_storage_config;
 pub mod p2p;
 pub mod rpc_config;
+pub mod scrape_config;
+pub mod tls_config;
 pub mod transaction_deny_config;
 pub mod validator_client_monitor_config;
 pub mod verifier_signing_config;
@@ -25,16 +31,19 @@ pub mod verifier_signing_config;
 pub use node::{ConsensusConfig, ExecutionCacheConfig, NodeConfig};
 pub use rpc_config::{RpcConfig, RpcIndexInitConfig, RpcTlsConfig};
 use sui_types::multiaddr::Multiaddr;
+pub use tls_config::{TlsConfig, TlsService};
 
 const SUI_DIR: &str = ".sui";
 pub const SUI_CONFIG_DIR: &str = "sui_config";
 pub const SUI_NETWORK_CONFIG: &str = "network.yaml";
 pub const SUI_FULLNODE_CONFIG: &str = "fullnode.yaml";
 pub const SUI_CLIENT_CONFIG: &str = "client.yaml";
+pub const SUI_NETWORKS_CONFIG: &str = "networks.yaml";
 pub const SUI_KEYSTORE_FILENAME: &str = "sui.keystore";
 pub const SUI_KEYSTORE_ALIASES_FILENAME: &str = "sui.aliases";
 pub const SUI_BENCHMARK_GENESIS_GAS_KEYSTORE_FILENAME: &str = "benchmark.keystore";
 pub const SUI_GENESIS_FILENAME: &str = "genesis.blob";
+pub const SUI_PROMETHEUS_CONFIG: &str = "prometheus.yaml";
 pub const SUI_DEV_NET_URL: &str = "https://fullnode.devnet.sui.io:443";
 
 pub const AUTHORITIES_DB_NAME: &str = "authorities_db";
@@ -109,6 +118,35 @@ where
         Ok(serde_yaml::from_reader(reader)?)
     }
 
+    /// Load the config from `path`, with overrides from environment variables whose names start
+    /// with `env_prefix`, and then from `overrides`, taking precedence in that order. See
+    /// [config_overrides] for how overrides are written.
+    fn load_with_overrides<P: AsRef<Path>>(
+        path: P,
+        env_prefix: &str,
+        overrides: &[ConfigOverride],
+    ) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        trace!("Reading config from {}", path.display());
+        let reader = fs::File::open(path)
+            .with_context(|| format!("Unable to load config from {}", path.display()))?;
+        let mut config: serde_yaml::Value = serde_yaml::from_reader(reader)?;
+
+        let mut all_overrides = ConfigOverride::from_env(env_prefix)?;
+        all_overrides.extend_from_slice(overrides);
+        for config_override in &all_overrides {
+            trace!("Overriding config with {config_override}");
+        }
+
+        apply_overrides(&mut config, &all_overrides)?;
+        serde_yaml::from_value(config).with_context(|| {
+            format!(
+                "Invalid config after applying overrides to {}",
+                path.display()
+            )
+        })
+    }
+
     fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
         let path = path.as_ref();
         trace!("Writing config to {}", path.display());
@@ -3,11 +3,17 @@
 
 use std::net::SocketAddr;
 #[cfg(msim)]
-use std::sync::{atomic::{AtomicI16, Ordering}, Arc};
+use std::sync::{atomic::{AtomicI16, Ordering}, Arc, Mutex};
+#[cfg(msim)]
+use std::{collections::BTreeMap, fmt, panic::Location};
+#[cfg(msim)]
+use once_cell::sync::Lazy;
 use sui_types::multiaddr::Multiaddr;
 #[cfg(not(msim))]
 use tracing::{warn, error};
 
+use crate::node::PortConflictPolicy;
+
 /// Base IP address used for simulation environment.
 const BASE_IP: &str = "10.10.0";
 /// Starting port for simulation environment.
@@ -21,19 +27,45 @@ const MAX_IP_OFFSET: i16 = 255;
 pub struct SimAddressManager {
     next_ip_offset: AtomicI16,
     next_port: AtomicI16,
+    /// Allocations made since recording started, if it has.
+    allocations: Mutex<Option<Vec<AddressAllocation>>>,
+}
+
+/// An IP address or port handed out by the [`SimAddressManager`], and the component that asked
+/// for it, identified by the location of the call into this module.
+#[cfg(msim)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressAllocation {
+    pub component: String,
+    pub allocated: Allocated,
 }
 
+#[cfg(msim)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Allocated {
+    Ip(String),
+    Port(u16),
+}
+
+/// The allocations recorded by the first run of each test checked by
+/// [`check_allocation_determinism`], by test name.
+#[cfg(msim)]
+static FIRST_RUN_ALLOCATIONS: Lazy<Mutex<BTreeMap<String, Vec<AddressAllocation>>>> =
+    Lazy::new(Default::default);
+
 #[cfg(msim)]
 impl SimAddressManager {
     pub fn new() -> Self {
         Self {
             next_ip_offset: AtomicI16::new(1),
             next_port: AtomicI16::new(BASE_PORT),
+            allocations: Mutex::new(None),
         }
     }
 
     /// Generates the next unique IP address in the format `10.10.0.x`.
     /// Panics if the IP offset exceeds the maximum allowed value (255).
+    #[track_caller]
     pub fn get_next_ip(&self) -> String {
         let offset = self
             .next_ip_offset
@@ -41,12 +73,48 @@ impl SimAddressManager {
         if offset > MAX_IP_OFFSET {
             panic!("IP offset exceeded maximum value of {}", MAX_IP_OFFSET);
         }
-        format!("{}.{}", BASE_IP, offset)
+        let ip = format!("{}.{}", BASE_IP, offset);
+        self.record(Allocated::Ip(ip.clone()));
+        ip
     }
 
+    #[track_caller]
     pub fn get_next_available_port(&self) -> u16 {
-        self.next_port
-            .fetch_add(1, Ordering::SeqCst) as u16
+        let port = self.next_port
+            .fetch_add(1, Ordering::SeqCst) as u16;
+        self.record(Allocated::Port(port));
+        port
+    }
+
+    /// Start recording every allocation, discarding any that were recorded before.
+    pub fn start_recording(&self) {
+        *self.allocations.lock().unwrap() = Some(vec![]);
+    }
+
+    /// Stop recording, and return the allocations recorded since recording started, in the order
+    /// they were made.
+    pub fn take_recording(&self) -> Vec<AddressAllocation> {
+        self.allocations.lock().unwrap().take().unwrap_or_default()
+    }
+
+    #[track_caller]
+    fn record(&self, allocated: Allocated) {
+        if let Some(allocations) = self.allocations.lock().unwrap().as_mut() {
+            allocations.push(AddressAllocation {
+                component: Location::caller().to_string(),
+                allocated,
+            });
+        }
+    }
+}
+
+#[cfg(msim)]
+impl fmt::Display for AddressAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.allocated {
+            Allocated::Ip(ip) => write!(f, "ip {} for {}", ip, self.component),
+            Allocated::Port(port) => write!(f, "port {} for {}", port, self.component),
+        }
     }
 }
 
@@ -61,10 +129,54 @@ fn get_sim_address_manager() -> Arc<SimAddressManager> {
 
 /// In simtest, we generate a new unique IP each time this function is called.
 #[cfg(msim)]
+#[track_caller]
 pub fn get_new_ip() -> String {
     get_sim_address_manager().get_next_ip()
 }
 
+/// Start recording the IP addresses and ports allocated in this simulation, along with the
+/// components that requested them, to be checked by [`check_allocation_determinism`].
+#[cfg(msim)]
+pub fn start_recording_allocations() {
+    get_sim_address_manager().start_recording()
+}
+
+/// Stop recording allocations, and return the ones made since [`start_recording_allocations`].
+#[cfg(msim)]
+pub fn take_recorded_allocations() -> Vec<AddressAllocation> {
+    get_sim_address_manager().take_recording()
+}
+
+/// Stop recording allocations, and check that the ones made in this run of the test named
+/// `test_name` are the same, and were requested by the same components in the same order, as the
+/// ones made in the first run of that test in this process. Meant to be called at the end of tests
+/// that are run repeatedly with the same seed, e.g. by `#[sim_test(check_determinism)]`.
+///
+/// Panics on the first allocation that differs between runs.
+#[cfg(msim)]
+pub fn check_allocation_determinism(test_name: &str) {
+    let allocations = take_recorded_allocations();
+    let mut first_runs = FIRST_RUN_ALLOCATIONS.lock().unwrap();
+    let Some(expected) = first_runs.get(test_name) else {
+        first_runs.insert(test_name.to_string(), allocations);
+        return;
+    };
+
+    for (i, (expected, actual)) in expected.iter().zip(&allocations).enumerate() {
+        assert_eq!(
+            expected, actual,
+            "Allocation {} of {} differs between runs: expected {}, got {}",
+            i, test_name, expected, actual
+        );
+    }
+
+    assert_eq!(
+        expected.len(), allocations.len(),
+        "Number of allocations in {} differs between runs: expected {}, got {}",
+        test_name, expected.len(), allocations.len()
+    );
+}
+
 /// In non-simtest, we always only have one IP address which is localhost.
 #[cfg(not(msim))]
 pub fn get_new_ip() -> String {
@@ -79,10 +191,19 @@ pub fn localhost_for_testing() -> String {
 /// Returns an available port for the given host in simtest.
 /// We don't care about host because it's all managed by simulator. Just obtain a unique port.
 #[cfg(msim)]
+#[track_caller]
 pub fn get_available_port(_host: &str) -> u16 {
     get_sim_address_manager().get_next_available_port()
 }
 
+/// Returns an available UDP port for the given host in simtest. Ports are unique across
+/// protocols, just like for [`get_available_port`].
+#[cfg(msim)]
+#[track_caller]
+pub fn get_available_udp_port(_host: &str) -> u16 {
+    get_sim_address_manager().get_next_available_port()
+}
+
 /// Return an ephemeral, available port. On unix systems, the port returned will be in the
 /// TIME_WAIT state ensuring that the OS won't hand out this port for some grace period.
 /// Callers should be able to bind to this port given they use SO_REUSEADDR.
@@ -96,6 +217,25 @@ pub fn get_available_port(host: &str) -> u16 {
 /// Returns `None` if no port is found after the maximum retries.
 #[cfg(not(msim))]
 pub fn get_available_port_with_retries(host: &str, max_retries: u32) -> Option<u16> {
+    probe_port_with_retries(host, max_retries, get_ephemeral_port)
+}
+
+/// Return an ephemeral port that a UDP socket could be bound to. Unlike TCP ports, UDP ports
+/// can't be reserved by forcing them into the TIME_WAIT state, so the port is only known to have
+/// been free when this function returns.
+#[cfg(not(msim))]
+pub fn get_available_udp_port(host: &str) -> u16 {
+    probe_port_with_retries(host, 1000, get_ephemeral_udp_port)
+        .unwrap_or_else(|| panic!("Failed to find available UDP port on {} after maximum retries", host))
+}
+
+/// Calls `probe` until it finds an available port, backing off between attempts.
+#[cfg(not(msim))]
+fn probe_port_with_retries(
+    host: &str,
+    max_retries: u32,
+    probe: fn(&str) -> std::io::Result<u16>,
+) -> Option<u16> {
     use std::time::{Duration, Instant};
     
     if host.is_empty() {
@@ -112,7 +252,7 @@ pub fn get_available_port_with_retries(host: &str, max_retries: u32) -> Option<u
     let mut last_error = None;
 
     for attempt in 0..max_retries {
-        match get_ephemeral_port(host) {
+        match probe(host) {
             Ok(port) => return Some(port),
             Err(e) => {
                 last_error = Some(e);
@@ -162,7 +302,120 @@ fn get_ephemeral_port(host: &str) -> std::io::Result<u16> {
     Ok(addr.port())
 }
 
+#[cfg(not(msim))]
+fn get_ephemeral_udp_port(host: &str) -> std::io::Result<u16> {
+    use std::net::UdpSocket;
+
+    // Validate host
+    if host.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Host cannot be empty"));
+    }
+
+    // Request a random available port from the OS, which is released once the socket is dropped.
+    let socket = UdpSocket::bind((host, 0)).map_err(|e| {
+        error!(host = %host, error = %e, "Failed to bind UDP socket");
+        e
+    })?;
+
+    Ok(socket.local_addr()?.port())
+}
+
+/// How many ports after a taken port are tried under [`PortConflictPolicy::Increment`].
+pub const MAX_PORT_INCREMENTS: u16 = 100;
+
+/// The protocol a listener's port is bound for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    Tcp,
+    Udp,
+}
+
+/// A port claimed by [`claim_port`], which stays bound until this is dropped.
+pub struct ClaimedPort {
+    pub address: SocketAddr,
+    _socket: Option<ClaimedSocket>,
+}
+
+#[allow(dead_code)]
+enum ClaimedSocket {
+    Tcp(std::net::TcpListener),
+    Udp(std::net::UdpSocket),
+}
+
+/// Claim `addr` for a listener, or if it is taken, another port on the same host, chosen by
+/// `policy`. Fails under [`PortConflictPolicy::Fail`] if `addr` is taken, or if `policy` cannot
+/// find a free port.
+///
+/// The port is bound (with SO_REUSEADDR for TCP) until the claim is dropped, so claims can be
+/// made for several listeners without handing the same port out twice, before any of them
+/// start.
+#[cfg(not(msim))]
+pub fn claim_port(
+    addr: SocketAddr,
+    transport: Transport,
+    policy: PortConflictPolicy,
+) -> std::io::Result<ClaimedPort> {
+    use std::io::{Error, ErrorKind};
+
+    let err = match bind_port(addr, transport) {
+        Ok(port) => return Ok(port),
+        Err(e) if e.kind() == ErrorKind::AddrInUse => e,
+        Err(e) => return Err(e),
+    };
+
+    match policy {
+        PortConflictPolicy::Fail => Err(err),
+
+        PortConflictPolicy::Increment => (1..=MAX_PORT_INCREMENTS)
+            .filter_map(|i| addr.port().checked_add(i))
+            .find_map(|port| bind_port(SocketAddr::new(addr.ip(), port), transport).ok())
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("{addr} and the {MAX_PORT_INCREMENTS} ports after it are taken"),
+                )
+            }),
+
+        PortConflictPolicy::Ephemeral => {
+            let host = addr.ip().to_string();
+            let port = match transport {
+                Transport::Tcp => get_available_port_with_retries(&host, 10),
+                Transport::Udp => probe_port_with_retries(&host, 10, get_ephemeral_udp_port),
+            }
+            .ok_or_else(|| Error::new(ErrorKind::AddrInUse, format!("No free port on {host}")))?;
+            bind_port(SocketAddr::new(addr.ip(), port), transport)
+        }
+    }
+}
+
+/// In simtest, the simulator hands out unique ports, so there are no conflicts to resolve.
+#[cfg(msim)]
+pub fn claim_port(
+    addr: SocketAddr,
+    _transport: Transport,
+    _policy: PortConflictPolicy,
+) -> std::io::Result<ClaimedPort> {
+    Ok(ClaimedPort {
+        address: addr,
+        _socket: None,
+    })
+}
+
+#[cfg(not(msim))]
+fn bind_port(addr: SocketAddr, transport: Transport) -> std::io::Result<ClaimedPort> {
+    let socket = match transport {
+        Transport::Tcp => ClaimedSocket::Tcp(std::net::TcpListener::bind(addr)?),
+        Transport::Udp => ClaimedSocket::Udp(std::net::UdpSocket::bind(addr)?),
+    };
+
+    Ok(ClaimedPort {
+        address: addr,
+        _socket: Some(socket),
+    })
+}
+
 /// Returns a new unique TCP address for the given host, by finding a new available port.
+#[track_caller]
 pub fn new_tcp_address_for_testing(host: &str) -> Multiaddr {
     if host.is_empty() {
         panic!("Host cannot be empty");
@@ -174,23 +427,26 @@ pub fn new_tcp_address_for_testing(host: &str) -> Multiaddr {
 }
 
 /// Returns a new unique UDP address for the given host, by finding a new available port.
+#[track_caller]
 pub fn new_udp_address_for_testing(host: &str) -> Multiaddr {
     if host.is_empty() {
         panic!("Host cannot be empty");
     }
-    format!("/ip4/{}/udp/{}", host, get_available_port(host))
+    format!("/ip4/{}/udp/{}", host, get_available_udp_port(host))
         .parse()
         .map_err(|e| panic!("Failed to parse UDP Multiaddr for host {}: {}", host, e))
         .unwrap()
 }
 
 /// Returns a new unique TCP address in String format for localhost, by finding a new available port on localhost.
+#[track_caller]
 pub fn new_local_tcp_socket_for_testing_string() -> String {
     let localhost = localhost_for_testing();
     format!("{}:{}", localhost, get_available_port(&localhost))
 }
 
 /// Returns a new unique TCP address (SocketAddr) for localhost, by finding a new available port on localhost.
+#[track_caller]
 pub fn new_local_tcp_socket_for_testing() -> SocketAddr {
     new_local_tcp_socket_for_testing_string()
         .parse()
@@ -199,11 +455,13 @@ pub fn new_local_tcp_socket_for_testing() -> SocketAddr {
 }
 
 /// Returns a new unique TCP address (Multiaddr) for localhost, by finding a new available port on localhost.
+#[track_caller]
 pub fn new_local_tcp_address_for_testing() -> Multiaddr {
     new_tcp_address_for_testing(&localhost_for_testing())
 }
 
 /// Returns a new unique UDP address for localhost, by finding a new available port.
+#[track_caller]
 pub fn new_local_udp_address_for_testing() -> Multiaddr {
     new_udp_address_for_testing(&localhost_for_testing())
 }
@@ -226,4 +484,35 @@ pub fn new_deterministic_udp_address_for_testing(host: &str, port: u16) -> Multi
         .parse()
         .map_err(|e| panic!("Failed to parse deterministic UDP Multiaddr for host {}: {}", host, e))
         .unwrap()
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, not(msim)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_udp_port_is_bindable() {
+        let host = localhost_for_testing();
+        let port = get_available_udp_port(&host);
+        std::net::UdpSocket::bind((host.as_str(), port)).unwrap();
+    }
+
+    #[test]
+    fn test_claim_taken_port() {
+        let taken = std::net::TcpListener::bind((localhost_for_testing().as_str(), 0)).unwrap();
+        let addr = taken.local_addr().unwrap();
+
+        let err = claim_port(addr, Transport::Tcp, PortConflictPolicy::Fail)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+        let incremented = claim_port(addr, Transport::Tcp, PortConflictPolicy::Increment).unwrap();
+        assert!(incremented.address.port() > addr.port());
+
+        // The incremented port is still claimed, so the ephemeral one must differ from both.
+        let ephemeral = claim_port(addr, Transport::Tcp, PortConflictPolicy::Ephemeral).unwrap();
+        assert_ne!(ephemeral.address, addr);
+        assert_ne!(ephemeral.address, incremented.address);
+    }
+}
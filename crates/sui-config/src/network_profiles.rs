@@ -0,0 +1,168 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Config;
+
+/// Named network profiles, read from `networks.yaml` in the Sui config directory, that tools can
+/// select by name (e.g. with the CLI's `--network` flag) instead of configuring each endpoint
+/// separately.
+///
+/// ```yaml
+/// networks:
+///   custom:
+///     rpc: https://fullnode.custom.example.com:443
+///     graphql: https://graphql.custom.example.com/graphql
+///     faucet: https://faucet.custom.example.com/v2/gas
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkProfiles {
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkProfile>,
+}
+
+/// The endpoints of a single network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkProfile {
+    /// URL of the network's JSON-RPC endpoint.
+    pub rpc: String,
+
+    /// URL of the network's GraphQL endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<String>,
+
+    /// URL to request gas from the network's faucet, if it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<String>,
+
+    /// URL of the network's websocket endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ws: Option<String>,
+}
+
+impl Config for NetworkProfiles {}
+
+impl NetworkProfiles {
+    /// Profiles for the public networks, and for a network started locally with `sui start`.
+    pub fn builtin() -> Self {
+        let networks = [
+            (
+                "localnet",
+                NetworkProfile {
+                    rpc: "http://127.0.0.1:9000".to_string(),
+                    graphql: Some("http://127.0.0.1:9125/graphql".to_string()),
+                    faucet: Some("http://127.0.0.1:9123/v2/gas".to_string()),
+                    ws: None,
+                },
+            ),
+            (
+                "devnet",
+                NetworkProfile {
+                    rpc: "https://fullnode.devnet.sui.io:443".to_string(),
+                    graphql: Some("https://sui-devnet.mystenlabs.com/graphql".to_string()),
+                    faucet: Some("https://faucet.devnet.sui.io/v2/gas".to_string()),
+                    ws: None,
+                },
+            ),
+            (
+                "testnet",
+                NetworkProfile {
+                    rpc: "https://fullnode.testnet.sui.io:443".to_string(),
+                    graphql: Some("https://sui-testnet.mystenlabs.com/graphql".to_string()),
+                    // Testnet tokens are only available through the faucet's web UI.
+                    faucet: None,
+                    ws: None,
+                },
+            ),
+            (
+                "mainnet",
+                NetworkProfile {
+                    rpc: "https://fullnode.mainnet.sui.io:443".to_string(),
+                    graphql: Some("https://sui-mainnet.mystenlabs.com/graphql".to_string()),
+                    faucet: None,
+                    ws: None,
+                },
+            ),
+        ];
+
+        Self {
+            networks: networks
+                .into_iter()
+                .map(|(name, profile)| (name.to_string(), profile))
+                .collect(),
+        }
+    }
+
+    /// The built-in profiles, overridden and extended by the profiles in the file at `path`, if
+    /// it exists.
+    pub fn load_or_builtin(path: &Path) -> Result<Self, anyhow::Error> {
+        let mut profiles = Self::builtin();
+        if path.exists() {
+            profiles.networks.extend(Self::load(path)?.networks);
+        }
+
+        Ok(profiles)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NetworkProfile> {
+        self.networks.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_overrides_builtin_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(crate::SUI_NETWORKS_CONFIG);
+
+        std::fs::write(
+            &path,
+            r#"
+networks:
+  devnet:
+    rpc: http://devnet.example.com:9000
+  custom:
+    rpc: http://custom.example.com:9000
+    faucet: http://custom.example.com:9123/v2/gas
+"#,
+        )
+        .unwrap();
+
+        let profiles = NetworkProfiles::load_or_builtin(&path).unwrap();
+
+        let devnet = profiles.get("devnet").unwrap();
+        assert_eq!(devnet.rpc, "http://devnet.example.com:9000");
+        assert_eq!(devnet.faucet, None);
+
+        let custom = profiles.get("custom").unwrap();
+        assert_eq!(
+            custom.faucet.as_deref(),
+            Some("http://custom.example.com:9123/v2/gas")
+        );
+
+        assert_eq!(
+            profiles.get("mainnet"),
+            NetworkProfiles::builtin().get("mainnet")
+        );
+    }
+
+    #[test]
+    fn test_missing_file_uses_builtin_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(crate::SUI_NETWORKS_CONFIG);
+
+        assert_eq!(
+            NetworkProfiles::load_or_builtin(&path).unwrap(),
+            NetworkProfiles::builtin()
+        );
+    }
+}
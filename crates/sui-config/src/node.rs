@@ -8,7 +8,7 @@ use crate::transaction_deny_config::TransactionDenyConfig;
 use crate::validator_client_monitor_config::ValidatorClientMonitorConfig;
 use crate::verifier_signing_config::VerifierSigningConfig;
 use crate::Config;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use consensus_config::Parameters as ConsensusParameters;
 use mysten_common::fatal;
 use nonzero_ext::nonzero;
@@ -17,11 +17,15 @@ use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::collections::{BTreeMap, BTreeSet};
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use sui_keys::key_encryption::{
+    is_encrypted_key_file, read_encrypted_authority_keypair_from_file,
+    read_encrypted_keypair_from_file,
+};
 use sui_keys::keypair_file::{read_authority_keypair_from_file, read_keypair_from_file};
 use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::committee::EpochId;
@@ -46,6 +50,10 @@ pub const DEFAULT_VALIDATOR_GAS_PRICE: u64 = sui_types::transaction::DEFAULT_VAL
 /// Default commission rate of 2%
 pub const DEFAULT_COMMISSION_RATE: u64 = 200;
 
+/// Prefix of the environment variables that override fields of a [NodeConfig] when it is loaded
+/// by a node, see [crate::config_overrides].
+pub const NODE_CONFIG_ENV_PREFIX: &str = "SUI_NODE__";
+
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -68,11 +76,21 @@ pub struct NodeConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rpc: Option<crate::RpcConfig>,
 
+    /// TLS settings for the node's externally exposed servers. The RPC server only uses them if
+    /// `rpc.tls` is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<crate::TlsConfig>,
+
     #[serde(default = "default_metrics_address")]
     pub metrics_address: SocketAddr,
     #[serde(default = "default_admin_interface_port")]
     pub admin_interface_port: u16,
 
+    /// What to do when the port of one of the node's listeners is already taken when the node
+    /// starts. See [NodeConfig::resolve_port_conflicts] for the listeners this applies to.
+    #[serde(default, skip_serializing_if = "PortConflictPolicy::is_fail")]
+    pub port_conflict_policy: PortConflictPolicy,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub consensus_config: Option<ConsensusConfig>,
 
@@ -221,6 +239,81 @@ pub struct NodeConfig {
     /// Fork recovery configuration for handling validator equivocation after forks
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fork_recovery: Option<ForkRecoveryConfig>,
+
+    /// How to obtain the passphrase for key files that are encrypted at rest. Required if any
+    /// of the key files are encrypted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_encryption: Option<KeyEncryptionConfig>,
+
+    /// If set, a shadow copy of the balance withdraw scheduler runs alongside the live one, with
+    /// these parameters, to report how its decisions would differ on live traffic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_withdraw_scheduler: Option<ShadowWithdrawSchedulerConfig>,
+
+    /// The most transactions calling into each of these Move packages that can be executing at
+    /// once. Transactions that are ready to execute wait for a slot to free up once their
+    /// packages are at their limit, so that a congested package cannot take over every execution
+    /// slot. Packages that are not listed are not limited.
+    ///
+    /// These limits can be changed while the node is running, through the admin server.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub package_execution_limits: BTreeMap<ObjectID, NonZeroUsize>,
+}
+
+/// Source of the passphrase that unlocks encrypted key files when the node starts.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyEncryptionConfig {
+    /// Read the passphrase from an environment variable.
+    #[serde(rename_all = "kebab-case")]
+    Env { passphrase_env_var: String },
+    /// Decrypt the passphrase with an AWS KMS key.
+    #[serde(rename_all = "kebab-case")]
+    Kms {
+        key_id: String,
+        /// Base64 encoded ciphertext of the passphrase, as returned by KMS.
+        encrypted_passphrase: String,
+    },
+}
+
+/// What a node does when the port one of its listeners is configured with is already taken.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PortConflictPolicy {
+    /// Fail to start, as if no policy was configured.
+    #[default]
+    Fail,
+    /// Listen on the first free port after the configured one, trying up to
+    /// [crate::local_ip_utils::MAX_PORT_INCREMENTS] ports.
+    Increment,
+    /// Listen on a free port chosen by the OS.
+    Ephemeral,
+}
+
+impl PortConflictPolicy {
+    fn is_fail(&self) -> bool {
+        *self == Self::Fail
+    }
+}
+
+/// A listener that was moved off its configured address by the [PortConflictPolicy], because
+/// the address was already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortRebind {
+    pub listener: &'static str,
+    pub configured: SocketAddr,
+    pub bound: SocketAddr,
+}
+
+/// The addresses that the node's listeners are bound to, once any port conflicts are resolved.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListenAddresses {
+    pub network_address: Multiaddr,
+    pub json_rpc_address: SocketAddr,
+    pub metrics_address: SocketAddr,
+    pub admin_interface_address: SocketAddr,
+    pub p2p_listen_address: SocketAddr,
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
@@ -252,6 +345,32 @@ pub struct ForkRecoveryConfig {
     pub fork_crash_behavior: ForkCrashBehavior,
 }
 
+/// Alternative parameters for a shadow balance withdraw scheduler. The shadow schedules the same
+/// withdraws as the live scheduler, and its decisions are only compared against the live ones and
+/// reported as metrics, so they never affect execution.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShadowWithdrawSchedulerConfig {
+    /// Whether withdraws are scheduled ahead of settlement when their accounts are guaranteed to
+    /// have sufficient balance. If false, every withdraw waits for its accumulator version to be
+    /// settled.
+    ///
+    /// If unspecified, this will default to `true`, like the live scheduler.
+    pub schedule_ahead_of_settlement: Option<bool>,
+
+    /// The largest amount a transaction can reserve from a single account. Withdraws that reserve
+    /// more are rejected as having insufficient balance.
+    ///
+    /// If unspecified, reservations are not capped, like the live scheduler.
+    pub max_reservation_per_account: Option<u64>,
+}
+
+impl ShadowWithdrawSchedulerConfig {
+    pub fn schedule_ahead_of_settlement(&self) -> bool {
+        self.schedule_ahead_of_settlement.unwrap_or(true)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ExecutionTimeObserverConfig {
@@ -768,6 +887,16 @@ fn is_true(value: &bool) -> bool {
 impl Config for NodeConfig {}
 
 impl NodeConfig {
+    /// Decrypt the key files that are encrypted at rest with `passphrase`. Must be called
+    /// before any of the keypairs are accessed, which would otherwise fail to load.
+    pub fn unlock_key_pairs(&self, passphrase: &[u8]) -> Result<()> {
+        self.protocol_key_pair.unlock(passphrase)?;
+        self.worker_key_pair.unlock(passphrase)?;
+        self.account_key_pair.unlock(passphrase)?;
+        self.network_key_pair.unlock(passphrase)?;
+        Ok(())
+    }
+
     pub fn protocol_key_pair(&self) -> &AuthorityKeyPair {
         self.protocol_key_pair.authority_keypair()
     }
@@ -847,6 +976,71 @@ impl NodeConfig {
     pub fn rpc(&self) -> Option<&crate::RpcConfig> {
         self.rpc.as_ref()
     }
+
+    /// The TLS settings for `service`, if it is served over TLS.
+    pub fn tls(&self, service: crate::TlsService) -> Option<&crate::TlsConfig> {
+        self.tls.as_ref().filter(|tls| tls.serves(service))
+    }
+
+    /// The address of the admin server, which only listens on localhost.
+    pub fn admin_interface_address(&self) -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), self.admin_interface_port)
+    }
+
+    pub fn listen_addresses(&self) -> ListenAddresses {
+        ListenAddresses {
+            network_address: self.network_address.clone(),
+            json_rpc_address: self.json_rpc_address,
+            metrics_address: self.metrics_address,
+            admin_interface_address: self.admin_interface_address(),
+            p2p_listen_address: self.p2p_config.listen_address,
+        }
+    }
+
+    /// Move the metrics, JSON-RPC and admin listeners, and a full node's p2p listener, off
+    /// ports that are already taken, according to `port_conflict_policy`, before the node
+    /// starts. A validator's network and p2p addresses are published on chain, so they are
+    /// never moved.
+    ///
+    /// Returns the listeners that were moved. Under [PortConflictPolicy::Fail] nothing is
+    /// checked, and the node fails to start when it binds a taken port, as before.
+    pub fn resolve_port_conflicts(&mut self) -> std::io::Result<Vec<PortRebind>> {
+        use crate::local_ip_utils::{claim_port, Transport};
+
+        let policy = self.port_conflict_policy;
+        let mut rebinds = vec![];
+        if policy == PortConflictPolicy::Fail {
+            return Ok(rebinds);
+        }
+
+        // Ports are held until every listener has been resolved, so that two listeners are not
+        // moved onto the same port.
+        let mut claimed = vec![];
+        let mut resolve = |listener, configured, transport| {
+            let port = claim_port(configured, transport, policy)?;
+            let bound = port.address;
+            if bound != configured {
+                rebinds.push(PortRebind {
+                    listener,
+                    configured,
+                    bound,
+                });
+            }
+            claimed.push(port);
+            Ok::<_, std::io::Error>(bound)
+        };
+
+        self.metrics_address = resolve("metrics", self.metrics_address, Transport::Tcp)?;
+        self.json_rpc_address = resolve("json-rpc", self.json_rpc_address, Transport::Tcp)?;
+        self.admin_interface_port =
+            resolve("admin", self.admin_interface_address(), Transport::Tcp)?.port();
+        if self.consensus_config.is_none() {
+            self.p2p_config.listen_address =
+                resolve("p2p", self.p2p_config.listen_address, Transport::Udp)?;
+        }
+
+        Ok(rebinds)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1466,6 +1660,28 @@ impl KeyPairWithPath {
             })
             .as_ref()
     }
+
+    /// The file the keypair is stored in, unless it is stored in place in the config.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.location {
+            KeyPairLocation::InPlace { .. } => None,
+            KeyPairLocation::File { path } => Some(path),
+        }
+    }
+
+    /// Load the keypair from its file with `passphrase`, if the file is encrypted.
+    pub fn unlock(&self, passphrase: &[u8]) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        if self.keypair.get().is_some() || !is_encrypted_key_file(path)? {
+            return Ok(());
+        }
+        let keypair = read_encrypted_keypair_from_file(path, passphrase)
+            .with_context(|| format!("Failed to unlock keypair file at path {:?}", path))?;
+        let _ = self.keypair.set(Arc::new(keypair));
+        Ok(())
+    }
 }
 
 /// Wrapper struct for AuthorityKeyPair that can be deserialized from a file path.
@@ -1528,6 +1744,28 @@ impl AuthorityKeyPairWithPath {
             })
             .as_ref()
     }
+
+    /// The file the keypair is stored in, unless it is stored in place in the config.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.location {
+            AuthorityKeyPairLocation::InPlace { .. } => None,
+            AuthorityKeyPairLocation::File { path } => Some(path),
+        }
+    }
+
+    /// Load the keypair from its file with `passphrase`, if the file is encrypted.
+    pub fn unlock(&self, passphrase: &[u8]) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        if self.keypair.get().is_some() || !is_encrypted_key_file(path)? {
+            return Ok(());
+        }
+        let keypair = read_encrypted_authority_keypair_from_file(path, passphrase)
+            .with_context(|| format!("Failed to unlock authority keypair file {:?}", path))?;
+        let _ = self.keypair.set(Arc::new(keypair));
+        Ok(())
+    }
 }
 
 /// Configurations which determine how we dump state debug info.
@@ -1579,12 +1817,13 @@ where
 mod tests {
     use std::path::PathBuf;
 
-    use fastcrypto::traits::KeyPair;
+    use fastcrypto::traits::{EncodeDecodeBase64, KeyPair};
     use rand::{rngs::StdRng, SeedableRng};
+    use sui_keys::key_encryption::encrypt_key_file;
     use sui_keys::keypair_file::{write_authority_keypair_to_file, write_keypair_to_file};
     use sui_types::crypto::{get_key_pair_from_rng, AuthorityKeyPair, NetworkKeyPair, SuiKeyPair};
 
-    use super::{Genesis, StateArchiveConfig};
+    use super::{AuthorityKeyPairWithPath, Genesis, KeyPairWithPath, StateArchiveConfig};
     use crate::NodeConfig;
 
     #[test]
@@ -1649,6 +1888,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unlock_encrypted_key_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        let protocol_path = dir.path().join("protocol.key");
+        let network_path = dir.path().join("network.key");
+
+        let protocol_key_pair: AuthorityKeyPair =
+            get_key_pair_from_rng(&mut StdRng::from_seed([0; 32])).1;
+        let network_key_pair = SuiKeyPair::Ed25519(
+            get_key_pair_from_rng::<NetworkKeyPair, _>(&mut StdRng::from_seed([0; 32])).1,
+        );
+        write_authority_keypair_to_file(&protocol_key_pair, &protocol_path).unwrap();
+        write_keypair_to_file(&network_key_pair, &network_path).unwrap();
+        assert!(encrypt_key_file(&protocol_path, b"passphrase").unwrap());
+        assert!(encrypt_key_file(&network_path, b"passphrase").unwrap());
+        // Already encrypted files are left as they are.
+        assert!(!encrypt_key_file(&network_path, b"passphrase").unwrap());
+
+        let protocol: AuthorityKeyPairWithPath =
+            serde_yaml::from_str(&format!("path: {}", protocol_path.display())).unwrap();
+        let network: KeyPairWithPath =
+            serde_yaml::from_str(&format!("path: {}", network_path.display())).unwrap();
+
+        assert!(protocol.unlock(b"wrong passphrase").is_err());
+        protocol.unlock(b"passphrase").unwrap();
+        network.unlock(b"passphrase").unwrap();
+        assert_eq!(
+            protocol.authority_keypair().public(),
+            protocol_key_pair.public()
+        );
+        assert_eq!(
+            network.keypair().encode_base64(),
+            network_key_pair.encode_base64()
+        );
+    }
+
     #[test]
     fn test_remote_store_options_file_path_support() {
         // Create temporary credential files
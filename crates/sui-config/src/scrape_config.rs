@@ -0,0 +1,258 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prometheus configuration for scraping the metrics endpoints of a set of nodes, e.g. the
+//! validators and fullnodes of a local network, so that they can be monitored without listing
+//! each node's metrics address by hand.
+//!
+//! The same targets can be written out as:
+//!
+//! - A complete Prometheus config, with a static scrape config per kind of node.
+//! - A file for Prometheus' file-based service discovery (`file_sd_configs`), which Prometheus
+//!   re-reads when it changes, so nodes can be added without restarting it.
+//! - Labels for docker-compose services, following the `prometheus.io/*` convention that
+//!   Docker-based service discovery setups relabel on.
+
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, NodeConfig};
+
+/// The path that nodes serve their metrics on.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// How often Prometheus scrapes each target, unless configured otherwise.
+pub const DEFAULT_SCRAPE_INTERVAL: &str = "15s";
+
+/// The job that validators are scraped under.
+pub const VALIDATOR_JOB: &str = "sui-validator";
+
+/// The job that fullnodes are scraped under.
+pub const FULLNODE_JOB: &str = "sui-fullnode";
+
+/// The label that identifies which node a target is.
+const NODE_LABEL: &str = "node";
+
+/// A node's metrics endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScrapeTarget {
+    /// The job the node is scraped under, either [VALIDATOR_JOB] or [FULLNODE_JOB].
+    pub job: &'static str,
+    /// A name for the node that is unique among the targets, e.g. `validator-0`.
+    pub node: String,
+    /// Where to scrape the node's metrics from.
+    pub address: SocketAddr,
+}
+
+/// A Prometheus config that scrapes a set of targets.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrometheusConfig {
+    pub global: GlobalConfig,
+    pub scrape_configs: Vec<ScrapeConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    pub scrape_interval: String,
+}
+
+/// The targets scraped under a single job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeConfig {
+    pub job_name: String,
+    pub metrics_path: String,
+    pub static_configs: Vec<StaticConfig>,
+}
+
+/// A group of targets that share labels, in the format used both by `static_configs`, and by
+/// files for `file_sd_configs`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticConfig {
+    pub targets: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Config for PrometheusConfig {}
+
+impl ScrapeTarget {
+    /// The metrics endpoints of `configs`. Validators and fullnodes are named after their
+    /// position among the nodes of the same kind, e.g. `validator-0`, `fullnode-0`.
+    ///
+    /// Nodes whose metrics are served on an unspecified address (e.g. `0.0.0.0`) are scraped
+    /// through localhost, which matches how local networks allocate their addresses.
+    pub fn from_node_configs<'c>(configs: impl IntoIterator<Item = &'c NodeConfig>) -> Vec<Self> {
+        let mut validators = 0;
+        let mut fullnodes = 0;
+
+        configs
+            .into_iter()
+            .map(|config| {
+                let (job, node) = if config.consensus_config.is_some() {
+                    validators += 1;
+                    (VALIDATOR_JOB, format!("validator-{}", validators - 1))
+                } else {
+                    fullnodes += 1;
+                    (FULLNODE_JOB, format!("fullnode-{}", fullnodes - 1))
+                };
+
+                let mut address = config.metrics_address;
+                if address.ip().is_unspecified() {
+                    address.set_ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+                }
+
+                Self { job, node, address }
+            })
+            .collect()
+    }
+
+    /// Labels for a docker-compose service running this node, for Docker-based service discovery
+    /// to find its metrics endpoint by.
+    pub fn docker_compose_labels(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("prometheus.io/scrape".to_owned(), "true".to_owned()),
+            (
+                "prometheus.io/port".to_owned(),
+                self.address.port().to_string(),
+            ),
+            ("prometheus.io/path".to_owned(), METRICS_PATH.to_owned()),
+            ("prometheus.io/job".to_owned(), self.job.to_owned()),
+            (format!("prometheus.io/{NODE_LABEL}"), self.node.clone()),
+        ])
+    }
+
+    fn static_config(&self, job_label: bool) -> StaticConfig {
+        let mut labels = BTreeMap::from([(NODE_LABEL.to_owned(), self.node.clone())]);
+        if job_label {
+            labels.insert("job".to_owned(), self.job.to_owned());
+        }
+
+        StaticConfig {
+            targets: vec![self.address.to_string()],
+            labels,
+        }
+    }
+}
+
+impl PrometheusConfig {
+    /// A config that scrapes `targets`, with a scrape config for each job, in the order they
+    /// first appear in.
+    pub fn new(targets: &[ScrapeTarget]) -> Self {
+        let mut scrape_configs: Vec<ScrapeConfig> = vec![];
+        for target in targets {
+            let static_config = target.static_config(/* job_label */ false);
+            match scrape_configs.iter_mut().find(|c| c.job_name == target.job) {
+                Some(scrape_config) => scrape_config.static_configs.push(static_config),
+                None => scrape_configs.push(ScrapeConfig {
+                    job_name: target.job.to_owned(),
+                    metrics_path: METRICS_PATH.to_owned(),
+                    static_configs: vec![static_config],
+                }),
+            }
+        }
+
+        Self {
+            global: GlobalConfig {
+                scrape_interval: DEFAULT_SCRAPE_INTERVAL.to_owned(),
+            },
+            scrape_configs,
+        }
+    }
+}
+
+/// Target groups for `targets`, in the format of a file for Prometheus' file-based service
+/// discovery. Each group is labelled with its target's job, because file-based discovery shares
+/// the job of the scrape config that reads the file.
+pub fn file_sd_config(targets: &[ScrapeTarget]) -> Vec<StaticConfig> {
+    targets
+        .iter()
+        .map(|target| target.static_config(/* job_label */ true))
+        .collect()
+}
+
+/// Write the file-based service discovery file for `targets` to `path`, as JSON.
+pub fn save_file_sd_config(targets: &[ScrapeTarget], path: &Path) -> anyhow::Result<()> {
+    let contents = serde_json::to_string_pretty(&file_sd_config(targets))?;
+    std::fs::write(path, contents).with_context(|| {
+        format!(
+            "Unable to save service discovery file to {}",
+            path.display()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets() -> Vec<ScrapeTarget> {
+        vec![
+            ScrapeTarget {
+                job: VALIDATOR_JOB,
+                node: "validator-0".to_owned(),
+                address: "127.0.0.1:9184".parse().unwrap(),
+            },
+            ScrapeTarget {
+                job: FULLNODE_JOB,
+                node: "fullnode-0".to_owned(),
+                address: "127.0.0.1:9186".parse().unwrap(),
+            },
+            ScrapeTarget {
+                job: VALIDATOR_JOB,
+                node: "validator-1".to_owned(),
+                address: "127.0.0.1:9185".parse().unwrap(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_prometheus_config() {
+        let config = PrometheusConfig::new(&targets());
+
+        let jobs: Vec<_> = config
+            .scrape_configs
+            .iter()
+            .map(|c| (c.job_name.as_str(), c.static_configs.len()))
+            .collect();
+        assert_eq!(jobs, vec![(VALIDATOR_JOB, 2), (FULLNODE_JOB, 1)]);
+
+        let validator_1 = &config.scrape_configs[0].static_configs[1];
+        assert_eq!(validator_1.targets, vec!["127.0.0.1:9185".to_owned()]);
+        assert_eq!(
+            validator_1.labels,
+            BTreeMap::from([(NODE_LABEL.to_owned(), "validator-1".to_owned())]),
+        );
+
+        // The config survives a round trip through YAML, which is how Prometheus reads it.
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<PrometheusConfig>(&yaml).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_file_sd_config() {
+        let groups = file_sd_config(&targets());
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1].targets, vec!["127.0.0.1:9186".to_owned()]);
+        assert_eq!(groups[1].labels["job"], FULLNODE_JOB);
+        assert_eq!(groups[1].labels[NODE_LABEL], "fullnode-0");
+    }
+
+    #[test]
+    fn test_docker_compose_labels() {
+        let labels = targets()[0].docker_compose_labels();
+        assert_eq!(labels["prometheus.io/scrape"], "true");
+        assert_eq!(labels["prometheus.io/port"], "9184");
+        assert_eq!(labels["prometheus.io/path"], METRICS_PATH);
+        assert_eq!(labels["prometheus.io/job"], VALIDATOR_JOB);
+        assert_eq!(labels["prometheus.io/node"], "validator-0");
+    }
+}
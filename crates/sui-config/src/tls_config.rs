@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeSet, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+/// TLS settings shared by the node's externally exposed servers, so that operators can terminate
+/// TLS in the node itself, rather than in a sidecar.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TlsConfig {
+    /// File path to a PEM formatted TLS certificate chain.
+    pub cert: PathBuf,
+
+    /// File path to a PEM formatted TLS private key.
+    pub key: PathBuf,
+
+    /// The servers to serve over TLS.
+    ///
+    /// Defaults to all of them if not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<BTreeSet<TlsService>>,
+
+    /// File path to PEM formatted CA certificates that clients' certificates are checked against,
+    /// by the servers in `client-auth-services`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ca: Option<PathBuf>,
+
+    /// The servers that only accept clients presenting a certificate signed by one of the CAs in
+    /// `client-ca` (mutual TLS), e.g. RPC endpoints that are only meant for the operator's own
+    /// validators.
+    ///
+    /// Defaults to none of them if not specified.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub client_auth_services: BTreeSet<TlsService>,
+
+    /// How often to check the certificate and key files for changes. Changed files are reloaded,
+    /// so that rotated certificates are served without restarting the node. Set to `0` to never
+    /// reload them.
+    ///
+    /// Defaults to `60000` (1 minute) if not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reload_interval_ms: Option<u64>,
+}
+
+/// A server that the node can serve over TLS.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum TlsService {
+    /// The RPC server's https listener, see [crate::RpcConfig::https_address].
+    Rpc,
+    /// The Prometheus metrics endpoint.
+    Metrics,
+}
+
+impl TlsConfig {
+    /// Whether `service` is served over TLS.
+    pub fn serves(&self, service: TlsService) -> bool {
+        self.services
+            .as_ref()
+            .is_none_or(|services| services.contains(&service))
+    }
+
+    /// The CA certificates that `service` checks clients' certificates against, if it requires
+    /// them.
+    pub fn client_ca(&self, service: TlsService) -> Option<&PathBuf> {
+        if self.client_auth_services.contains(&service) {
+            self.client_ca.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// How often to check the certificate and key files for changes, or `None` if they are never
+    /// reloaded.
+    pub fn reload_interval(&self) -> Option<Duration> {
+        match self.reload_interval_ms.unwrap_or(60_000) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// Check that every server that requires client certificates has CAs to check them against,
+    /// and is served over TLS in the first place.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for service in &self.client_auth_services {
+            anyhow::ensure!(
+                self.client_ca.is_some(),
+                "TLS client auth is required for {service:?}, but no client-ca is configured",
+            );
+            anyhow::ensure!(
+                self.serves(*service),
+                "TLS client auth is required for {service:?}, but it is not served over TLS",
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> TlsConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let tls = config("cert: /etc/sui/cert.pem\nkey: /etc/sui/key.pem\n");
+
+        assert!(tls.serves(TlsService::Rpc));
+        assert!(tls.serves(TlsService::Metrics));
+        assert_eq!(tls.client_ca(TlsService::Rpc), None);
+        assert_eq!(tls.reload_interval(), Some(Duration::from_secs(60)));
+        tls.validate().unwrap();
+    }
+
+    #[test]
+    fn test_client_auth() {
+        let tls = config(
+            r#"
+            cert: /etc/sui/cert.pem
+            key: /etc/sui/key.pem
+            services: [rpc]
+            client-ca: /etc/sui/ca.pem
+            client-auth-services: [rpc]
+            reload-interval-ms: 0
+            "#,
+        );
+
+        assert!(tls.serves(TlsService::Rpc));
+        assert!(!tls.serves(TlsService::Metrics));
+        assert_eq!(
+            tls.client_ca(TlsService::Rpc),
+            Some(&PathBuf::from("/etc/sui/ca.pem"))
+        );
+        assert_eq!(tls.client_ca(TlsService::Metrics), None);
+        assert_eq!(tls.reload_interval(), None);
+        tls.validate().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_client_auth() {
+        let no_ca = config(
+            r#"
+            cert: /etc/sui/cert.pem
+            key: /etc/sui/key.pem
+            client-auth-services: [rpc]
+            "#,
+        );
+        no_ca.validate().unwrap_err();
+
+        let not_served = config(
+            r#"
+            cert: /etc/sui/cert.pem
+            key: /etc/sui/key.pem
+            services: [metrics]
+            client-ca: /etc/sui/ca.pem
+            client-auth-services: [rpc]
+            "#,
+        );
+        not_served.validate().unwrap_err();
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use mysten_common::fatal;
 use sui_types::accumulator_event::AccumulatorEvent;
@@ -11,6 +11,7 @@ use sui_types::accumulator_root::{
 };
 use sui_types::balance::{BALANCE_MODULE_NAME, BALANCE_STRUCT_NAME};
 use sui_types::base_types::ObjectID;
+use sui_types::digests::TransactionDigest;
 use sui_types::effects::{
     AccumulatorAddress, AccumulatorOperation, AccumulatorValue, AccumulatorWriteV1,
     TransactionEffects, TransactionEffectsAPI,
@@ -157,6 +158,8 @@ struct Update {
 pub(crate) struct AccumulatorSettlementTxBuilder {
     updates: HashMap<ObjectID, Update>,
     addresses: HashMap<ObjectID, AccumulatorAddress>,
+    /// The amount each transaction withdrew from each balance accumulator.
+    withdraws: BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>,
 }
 
 impl AccumulatorSettlementTxBuilder {
@@ -168,6 +171,8 @@ impl AccumulatorSettlementTxBuilder {
 
         let mut addresses = HashMap::<_, _>::new();
 
+        let mut withdraws = BTreeMap::<_, BTreeMap<_, u64>>::new();
+
         for effect in ckpt_effects {
             let tx = effect.transaction_digest();
             // TransactionEffectsAPI::accumulator_events() uses a linear scan of all
@@ -189,6 +194,20 @@ impl AccumulatorSettlementTxBuilder {
                     },
             } in events
             {
+                if let (
+                    AccumulatorOperation::Split,
+                    AccumulatorValue::Integer(amount),
+                    ClassifiedType::Balance,
+                ) = (&operation, &value, ClassifiedType::classify(&address.ty))
+                {
+                    let withdrawn = withdraws
+                        .entry(*tx)
+                        .or_default()
+                        .entry(accumulator_obj)
+                        .or_default();
+                    *withdrawn = withdrawn.saturating_add(*amount);
+                }
+
                 if let Some(prev) = addresses.insert(accumulator_obj, address.clone()) {
                     debug_assert_eq!(prev, address);
                 }
@@ -212,7 +231,11 @@ impl AccumulatorSettlementTxBuilder {
             }
         }
 
-        Self { updates, addresses }
+        Self {
+            updates,
+            addresses,
+            withdraws,
+        }
     }
 
     pub fn num_updates(&self) -> usize {
@@ -232,7 +255,10 @@ impl AccumulatorSettlementTxBuilder {
             })
             .collect();
 
-        BalanceSettlement { balance_changes }
+        BalanceSettlement {
+            balance_changes,
+            withdraws: self.withdraws.clone(),
+        }
     }
 
     // TODO(address-balances): This currently only creates a single accumulator update transaction.
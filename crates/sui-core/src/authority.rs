@@ -40,6 +40,7 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use shared_object_version_manager::AssignedVersions;
 use shared_object_version_manager::Schedulable;
+use shared_object_version_manager::WithdrawType;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
@@ -109,7 +110,9 @@ use sui_json_rpc_types::{
 use sui_macros::{fail_point, fail_point_arg, fail_point_async, fail_point_if};
 use sui_storage::key_value_store::{TransactionKeyValueStore, TransactionKeyValueStoreTrait};
 use sui_storage::key_value_store_metrics::KeyValueStoreMetrics;
+use sui_types::accumulator_root::{AccumulatorValue, U128};
 use sui_types::authenticator_state::get_authenticator_state;
+use sui_types::balance::Balance;
 use sui_types::committee::{EpochId, ProtocolVersion};
 use sui_types::crypto::{default_hash, AuthoritySignInfo, Signer};
 use sui_types::deny_list_v1::check_coin_deny_list_v1;
@@ -140,7 +143,8 @@ use sui_types::messages_grpc::{
 use sui_types::metrics::{BytecodeVerifierMetrics, LimitsMetrics};
 use sui_types::object::{MoveObject, Owner, PastObjectRead, OBJECT_START_VERSION};
 use sui_types::storage::{
-    BackingPackageStore, BackingStore, ObjectKey, ObjectOrTombstone, ObjectStore, WriteKind,
+    BackingPackageStore, BackingStore, ObjectKey, ObjectOrTombstone, ObjectStore, SpendableBalance,
+    WriteKind,
 };
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
 use sui_types::sui_system_state::SuiSystemStateTrait;
@@ -166,6 +170,7 @@ use crate::authority::authority_store_pruner::{
 };
 use crate::authority::epoch_start_configuration::EpochStartConfigTrait;
 use crate::authority::epoch_start_configuration::EpochStartConfiguration;
+use crate::authority::randomness_gate::RandomnessGate;
 use crate::checkpoints::CheckpointStore;
 use crate::epoch::committee_store::CommitteeStore;
 use crate::execution_cache::{
@@ -242,6 +247,7 @@ pub mod authority_store_types;
 pub mod consensus_tx_status_cache;
 pub mod epoch_start_configuration;
 pub mod execution_time_estimator;
+pub mod randomness_gate;
 pub mod shared_object_congestion_tracker;
 pub mod shared_object_version_manager;
 pub mod test_authority_builder;
@@ -286,6 +292,17 @@ pub struct AuthorityMetrics {
     pub(crate) transaction_manager_num_executing_certificates: IntGauge,
     pub(crate) transaction_manager_transaction_queue_age_s: Histogram,
 
+    pub(crate) balance_withdraw_shadow_schedule_results: IntCounterVec,
+    pub(crate) balance_withdraw_shadow_divergences: IntCounterVec,
+    pub(crate) balance_settlement_application_latency: Histogram,
+    pub(crate) balance_settlement_queue_latency: Histogram,
+    pub(crate) balance_settlements_queued_behind_unapplied: IntCounter,
+    pub(crate) balance_withdraws_long_settlement_wait: IntGauge,
+    pub(crate) balance_withdraw_reservation_wait: HistogramVec,
+    pub(crate) balance_read_cache_hits: IntCounter,
+    pub(crate) balance_read_cache_misses: IntCounter,
+    pub(crate) balance_read_cache_invalidations: IntCounter,
+
     pub(crate) execution_driver_executed_transactions: IntCounter,
     pub(crate) execution_driver_dispatch_queue: IntGauge,
     pub(crate) execution_queueing_delay_s: Histogram,
@@ -566,6 +583,72 @@ impl AuthorityMetrics {
                 registry,
             )
             .unwrap(),
+            balance_withdraw_shadow_schedule_results: register_int_counter_vec_with_registry!(
+                "balance_withdraw_shadow_schedule_results",
+                "Results of scheduling withdraws in the shadow balance withdraw scheduler, by status",
+                &["status"],
+                registry,
+            )
+            .unwrap(),
+            balance_withdraw_shadow_divergences: register_int_counter_vec_with_registry!(
+                "balance_withdraw_shadow_divergences",
+                "Number of transactions whose withdraws were scheduled differently by the shadow balance withdraw scheduler than by the live one",
+                &["live", "shadow"],
+                registry,
+            )
+            .unwrap(),
+            balance_settlement_application_latency: register_histogram_with_registry!(
+                "balance_settlement_application_latency",
+                "Time from the checkpoint builder producing the balance changes of a settlement to the balance withdraw scheduler applying it",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            balance_settlement_queue_latency: register_histogram_with_registry!(
+                "balance_settlement_queue_latency",
+                "Time that settlements wait in the balance withdraw scheduler before it starts applying them",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            balance_settlements_queued_behind_unapplied: register_int_counter_with_registry!(
+                "balance_settlements_queued_behind_unapplied",
+                "Number of settlements sent to the balance withdraw scheduler while an earlier settlement had not been applied yet, which they queued behind",
+                registry,
+            )
+            .unwrap(),
+            balance_withdraws_long_settlement_wait: register_int_gauge_with_registry!(
+                "balance_withdraws_long_settlement_wait",
+                "Number of withdraws that have been waiting for their accumulator version to be settled through at least the long wait threshold's number of settlements",
+                registry,
+            )
+            .unwrap(),
+            balance_withdraw_reservation_wait: register_histogram_vec_with_registry!(
+                "balance_withdraw_reservation_wait",
+                "Time from a withdraw being sent to the balance withdraw scheduler to it being scheduled, by status",
+                &["status"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            balance_read_cache_hits: register_int_counter_with_registry!(
+                "balance_read_cache_hits",
+                "Number of account balance reads by the balance withdraw scheduler and RPC that were served from the balance cache",
+                registry,
+            )
+            .unwrap(),
+            balance_read_cache_misses: register_int_counter_with_registry!(
+                "balance_read_cache_misses",
+                "Number of account balance reads by the balance withdraw scheduler and RPC that were read from the store",
+                registry,
+            )
+            .unwrap(),
+            balance_read_cache_invalidations: register_int_counter_with_registry!(
+                "balance_read_cache_invalidations",
+                "Number of cached account balances invalidated because a settlement changed them",
+                registry,
+            )
+            .unwrap(),
             transaction_overload_sources: register_int_counter_vec_with_registry!(
                 "transaction_overload_sources",
                 "Number of times each source indicates transaction overload.",
@@ -952,6 +1035,9 @@ pub struct AuthorityState {
 
     /// Fork recovery state for handling equivocation after forks
     fork_recovery_state: Option<ForkRecoveryState>,
+
+    /// Controls the release of new randomness rounds. Always open outside of tests.
+    randomness_gate: Arc<RandomnessGate>,
 }
 
 /// The authority state encapsulates all state, drives execution, and ensures safety.
@@ -981,6 +1067,10 @@ impl AuthorityState {
         &self.config.authority_overload_config
     }
 
+    pub fn randomness_gate(&self) -> &Arc<RandomnessGate> {
+        &self.randomness_gate
+    }
+
     pub fn get_epoch_state_commitments(
         &self,
         epoch: EpochId,
@@ -1554,6 +1644,7 @@ impl AuthorityState {
         }
 
         let scheduling_source = execution_env.scheduling_source;
+        let withdraw_type = execution_env.assigned_versions.withdraw_type;
         let mysticeti_fp_outputs = if epoch_store.protocol_config().mysticeti_fastpath() {
             tx_cache_reader.get_mysticeti_fastpath_outputs(tx_digest)
         } else {
@@ -1616,6 +1707,13 @@ impl AuthorityState {
                 tx_guard.release();
                 return Err(err);
             }
+
+            // Only transactions with withdraws know the accumulator version their deposits are
+            // settled after. Their effects are committed, so the deposits are final.
+            if let WithdrawType::Withdraw(accumulator_version) = withdraw_type {
+                self.execution_scheduler
+                    .record_balance_deposits(accumulator_version, &effects);
+            }
         }
 
         if let TransactionKind::AuthenticatorStateUpdate(auth_state) =
@@ -2254,12 +2352,12 @@ impl AuthorityState {
             .expect("Creating an executor should not fail here");
 
         let expensive_checks = false;
+        let balance_withdraw_status = self.get_balance_withdraw_status_for_dry_run(&transaction)?;
         let early_execution_error = get_early_execution_error(
             &transaction_digest,
             &checked_input_objects,
             self.config.certificate_deny_config.certificate_deny_set(),
-            // TODO(address-balances): Mimic withdraw scheduling and pass the result.
-            &BalanceWithdrawStatus::NoWithdraw,
+            &balance_withdraw_status,
         );
         let execution_params = match early_execution_error {
             Some(error) => ExecutionOrEarlyError::Err(error),
@@ -2459,13 +2557,13 @@ impl AuthorityState {
         )
         .expect("Creating an executor should not fail here");
 
+        let balance_withdraw_status = self.get_balance_withdraw_status_for_dry_run(&transaction)?;
         let (kind, signer, gas_data) = transaction.execution_parts();
         let early_execution_error = get_early_execution_error(
             &transaction.digest(),
             &checked_input_objects,
             self.config.certificate_deny_config.certificate_deny_set(),
-            // TODO(address-balances): Mimic withdraw scheduling and pass the result.
-            &BalanceWithdrawStatus::NoWithdraw,
+            &balance_withdraw_status,
         );
         let execution_params = match early_execution_error {
             Some(error) => ExecutionOrEarlyError::Err(error),
@@ -2647,6 +2745,7 @@ impl AuthorityState {
             }
         };
 
+        let balance_withdraw_status = self.get_balance_withdraw_status_for_dry_run(&transaction)?;
         let executor = sui_execution::executor(protocol_config, /* silent */ true)
             .expect("Creating an executor should not fail here");
         let gas_data = transaction.gas_data().clone();
@@ -2663,8 +2762,7 @@ impl AuthorityState {
             &transaction_digest,
             &checked_input_objects,
             self.config.certificate_deny_config.certificate_deny_set(),
-            // TODO(address-balances): Mimic withdraw scheduling and pass the result.
-            &BalanceWithdrawStatus::NoWithdraw,
+            &balance_withdraw_status,
         );
         let execution_params = match early_execution_error {
             Some(error) => ExecutionOrEarlyError::Err(error),
@@ -3385,8 +3483,10 @@ impl AuthorityState {
                 .clone(),
             tx_ready_certificates,
             &epoch_store,
+            config.shadow_withdraw_scheduler.as_ref(),
             metrics.clone(),
         ));
+        execution_scheduler.set_package_limits(&config.package_execution_limits);
         let (tx_execution_shutdown, rx_execution_shutdown) = oneshot::channel();
 
         let _authority_per_epoch_pruner = AuthorityPerEpochStorePruner::new(
@@ -3452,6 +3552,7 @@ impl AuthorityState {
             congestion_tracker: Arc::new(CongestionTracker::new()),
             traffic_controller,
             fork_recovery_state,
+            randomness_gate: Arc::new(RandomnessGate::default()),
         });
 
         let state_clone = Arc::downgrade(&state);
@@ -3547,6 +3648,72 @@ impl AuthorityState {
         &self.execution_scheduler
     }
 
+    /// Returns the address balance of `owner` for `coin_type`, along with the portion of it
+    /// that is still available to new withdraws after subtracting outstanding reservations
+    /// from the withdraw scheduler.
+    ///
+    /// Deposits by transactions that have executed but not been settled yet are counted as
+    /// pending, since the scheduler credits them to later withdraws. The balance is read as of the
+    /// last accumulator version that the scheduler has settled, so that it matches the pending
+    /// deposits and reservations.
+    pub fn get_spendable_balance(
+        &self,
+        owner: SuiAddress,
+        coin_type: TypeTag,
+    ) -> SuiResult<SpendableBalance> {
+        let balance_type = Balance::type_tag(coin_type);
+        let account_id = AccumulatorValue::get_field_id(owner, &balance_type)?;
+        self.get_spendable_balance_by_account_id(account_id)
+    }
+
+    fn get_spendable_balance_by_account_id(
+        &self,
+        account_id: ObjectID,
+    ) -> SuiResult<SpendableBalance> {
+        if let Some(balance) = self.execution_scheduler.get_spendable_balance(&account_id) {
+            return Ok(balance);
+        }
+
+        // Without accumulators, nothing is pending or reserved.
+        let settled = AccumulatorValue::load_by_id::<U128>(
+            self.get_child_object_resolver().as_ref(),
+            None,
+            account_id,
+        )?
+        .map_or(0, |U128 { value }| {
+            std::cmp::min(value, u64::MAX as u128) as u64
+        });
+        Ok(SpendableBalance::new(settled, 0, 0))
+    }
+
+    /// Mimics withdraw scheduling for a transaction that is dry-run or simulated, rather than
+    /// assuming the full settled balance of each account is spendable: every reservation is
+    /// checked against the account's settled balance minus what the withdraw scheduler has
+    /// already reserved for transactions that have not settled yet.
+    fn get_balance_withdraw_status_for_dry_run(
+        &self,
+        transaction: &TransactionData,
+    ) -> SuiResult<BalanceWithdrawStatus> {
+        if !transaction.has_balance_withdraws() {
+            return Ok(BalanceWithdrawStatus::NoWithdraw);
+        }
+
+        for (account_id, reservation) in transaction.process_balance_withdraws()? {
+            let spendable = self.get_spendable_balance_by_account_id(account_id)?;
+            if spendable.spendable < reservation {
+                debug!(
+                    ?account_id,
+                    reservation,
+                    ?spendable,
+                    "Dry run balance withdraw would be rejected"
+                );
+                return Ok(BalanceWithdrawStatus::InsufficientBalance);
+            }
+        }
+
+        Ok(BalanceWithdrawStatus::SufficientBalance)
+    }
+
     fn create_owner_index_if_empty(
         &self,
         genesis_objects: &[Object],
@@ -5835,6 +6002,7 @@ impl RandomnessRoundReceiver {
     #[instrument(level = "debug", skip_all, fields(?epoch, ?round))]
     async fn handle_new_randomness(&self, epoch: EpochId, round: RandomnessRound, bytes: Vec<u8>) {
         fail_point_async!("randomness-delay");
+        self.authority_state.randomness_gate.wait().await;
 
         let epoch_store = self.authority_state.load_epoch_store_one_call_per_task();
         if epoch_store.epoch() != epoch {
@@ -2329,6 +2329,37 @@ impl AuthorityPerEpochStore {
             .collect()
     }
 
+    /// The address balance accounts that transactions pending execution withdraw from: the
+    /// certificates and transactions submitted to consensus but not sequenced yet, and the
+    /// deferred transactions, which were sequenced but are only scheduled once their deferral
+    /// ends. After a restart, their withdraws are known before the balance withdraw scheduler
+    /// sees them.
+    pub fn pending_balance_withdraw_accounts(&self) -> BTreeSet<ObjectID> {
+        let submitted = self
+            .tables()
+            .and_then(|tables| tables.get_all_pending_consensus_transactions())
+            .unwrap_or_default();
+
+        let deferred = self.consensus_output_cache.deferred_transactions.lock();
+
+        let submitted = submitted.iter().filter_map(|tx| match &tx.kind {
+            ConsensusTransactionKind::CertifiedTransaction(cert) => Some(cert.data()),
+            ConsensusTransactionKind::UserTransaction(tx) => Some(tx.data()),
+            _ => None,
+        });
+
+        let deferred = deferred
+            .values()
+            .flatten()
+            .filter_map(|tx| tx.0.as_consensus_txn());
+
+        submitted
+            .chain(deferred)
+            .filter_map(|tx| tx.transaction_data().process_balance_withdraws().ok())
+            .flat_map(|withdraws| withdraws.into_keys())
+            .collect()
+    }
+
     fn should_defer(
         &self,
         tx_cost: Option<u64>,
@@ -3441,6 +3472,20 @@ impl AuthorityPerEpochStore {
             )
             .await?;
 
+        // The number of balance withdraws in each pending checkpoint tells the checkpoint builder
+        // how soon their balances should be settled.
+        let count_balance_withdraws = |transactions: &VecDeque<Schedulable>| {
+            transactions
+                .iter()
+                .filter_map(Schedulable::as_tx)
+                .filter(|tx| tx.transaction_data().has_balance_withdraws())
+                .count() as u64
+        };
+        let non_randomness_balance_withdraws =
+            count_balance_withdraws(&verified_non_randomness_transactions);
+        let randomness_balance_withdraws =
+            count_balance_withdraws(&verified_randomness_transactions);
+
         if self.accumulators_enabled() {
             // We insert settlement transactions to the end of the certificate queues.
             // This is important for shared object version assignment to work correctly.
@@ -3557,6 +3602,7 @@ impl AuthorityPerEpochStore {
                     timestamp_ms: consensus_commit_info.timestamp,
                     last_of_epoch: final_round && !should_write_random_checkpoint,
                     checkpoint_height,
+                    balance_withdraws: non_randomness_balance_withdraws,
                 },
             };
             self.write_pending_checkpoint(&mut output, &pending_checkpoint)?;
@@ -3568,6 +3614,7 @@ impl AuthorityPerEpochStore {
                         timestamp_ms: consensus_commit_info.timestamp,
                         last_of_epoch: final_round,
                         checkpoint_height: checkpoint_height + 1,
+                        balance_withdraws: randomness_balance_withdraws,
                     },
                 };
                 self.write_pending_checkpoint(&mut output, &pending_checkpoint)?;
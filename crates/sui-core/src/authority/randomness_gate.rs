@@ -0,0 +1,150 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// Controls when newly generated randomness is turned into `RandomnessStateUpdate` transactions
+/// by the `RandomnessRoundReceiver`. Used by tests that need to control which randomness rounds
+/// are observed by transactions that read on-chain randomness.
+///
+/// The gate is open by default. While it is paused, randomness rounds queue up in the receiver
+/// (in order) until they are released one at a time by [`RandomnessGate::step`], or all at once
+/// by [`RandomnessGate::resume`]. Transactions that use randomness cannot execute until the state
+/// update for their round has been released, and because checkpoints include randomness state
+/// updates, checkpoint creation (and therefore reconfiguration) stalls until pending rounds are
+/// released.
+///
+/// The gate only controls when randomness is delivered, not its value, which is still produced
+/// by the validators' threshold signature for the round.
+#[derive(Default)]
+pub struct RandomnessGate {
+    state: Mutex<GateState>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct GateState {
+    paused: bool,
+    /// Number of rounds that can be released while paused.
+    permits: u64,
+}
+
+impl RandomnessGate {
+    /// Stop releasing randomness rounds, until the gate is resumed or stepped.
+    pub fn pause(&self) {
+        let mut state = self.state.lock();
+        state.paused = true;
+        state.permits = 0;
+    }
+
+    /// Release all pending randomness rounds, and any future rounds as they are generated.
+    pub fn resume(&self) {
+        let mut state = self.state.lock();
+        state.paused = false;
+        state.permits = 0;
+        self.notify.notify_waiters();
+    }
+
+    /// Release the next `rounds` randomness rounds, leaving the gate paused afterwards. Has no
+    /// effect if the gate is not paused.
+    pub fn step(&self, rounds: u64) {
+        let mut state = self.state.lock();
+        if state.paused {
+            state.permits += rounds;
+            self.notify.notify_waiters();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().paused
+    }
+
+    /// Wait until the next randomness round can be released.
+    pub(crate) async fn wait(&self) {
+        loop {
+            // Register interest before checking the state, so that a notification sent between the
+            // check and the await is not missed.
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut state = self.state.lock();
+                if !state.paused {
+                    return;
+                }
+                if state.permits > 0 {
+                    state.permits -= 1;
+                    return;
+                }
+            }
+
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    async fn is_released(gate: &RandomnessGate) -> bool {
+        timeout(Duration::from_millis(100), gate.wait())
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_open_by_default() {
+        let gate = RandomnessGate::default();
+        assert!(!gate.is_paused());
+        assert!(is_released(&gate).await);
+        assert!(is_released(&gate).await);
+    }
+
+    #[tokio::test]
+    async fn test_pause_step_resume() {
+        let gate = RandomnessGate::default();
+        gate.pause();
+        assert!(!is_released(&gate).await);
+
+        gate.step(2);
+        assert!(is_released(&gate).await);
+        assert!(is_released(&gate).await);
+        assert!(!is_released(&gate).await);
+
+        gate.resume();
+        assert!(is_released(&gate).await);
+        assert!(is_released(&gate).await);
+
+        // Stepping an open gate does not accumulate permits for a later pause.
+        gate.step(1);
+        gate.pause();
+        assert!(!is_released(&gate).await);
+    }
+
+    #[tokio::test]
+    async fn test_step_wakes_waiter() {
+        let gate = Arc::new(RandomnessGate::default());
+        gate.pause();
+
+        let waiter = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait().await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        gate.step(1);
+        timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("waiter should be released")
+            .unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{net::SocketAddr, time::Duration};
+
+use clap::Parser;
+use sui_core::execution_scheduler::balance_withdraw_scheduler_bench::{
+    run_workload, Workload, WorkloadConfig,
+};
+
+/// Soak-test the balance withdraw scheduler with a synthetic workload, serving live Prometheus
+/// metrics while it runs.
+#[derive(Parser, Debug)]
+#[clap(rename_all = "kebab-case")]
+struct Args {
+    /// Address to serve Prometheus metrics on.
+    #[clap(long, default_value = "127.0.0.1:9184")]
+    metrics_address: SocketAddr,
+    /// Number of accounts that withdraws are drawn from.
+    #[clap(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    num_accounts: u64,
+    /// Balance every account starts with.
+    #[clap(long, default_value_t = 1_000_000)]
+    initial_balance: u64,
+    /// Number of hot accounts, receiving `hot-ratio` of all reservations.
+    #[clap(long, default_value_t = 10)]
+    hot_accounts: usize,
+    /// Probability (between 0 and 1) that a reservation goes to a hot account.
+    #[clap(long, default_value_t = 0.5)]
+    hot_ratio: f64,
+    /// Number of transactions scheduled against each accumulator version.
+    #[clap(long, default_value_t = 1_000, value_parser = clap::value_parser!(u64).range(1..))]
+    txs_per_version: u64,
+    /// Maximum number of accounts a single transaction withdraws from.
+    #[clap(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    max_accounts_per_tx: u64,
+    /// Maximum amount reserved by a single withdraw.
+    #[clap(long, default_value_t = 1_000, value_parser = clap::value_parser!(u64).range(1..))]
+    max_withdraw: u64,
+    /// Amount deposited into each account on every settlement.
+    #[clap(long, default_value_t = 100)]
+    deposit_per_settlement: u64,
+    /// Milliseconds between settlements.
+    #[clap(long, default_value_t = 250)]
+    settlement_interval_ms: u64,
+    /// Seconds to run the workload for.
+    #[clap(long, default_value_t = 600)]
+    duration_secs: u64,
+    /// Seed for the workload's random number generator.
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let _guard = telemetry_subscribers::TelemetryConfig::new()
+        .with_env()
+        .init();
+
+    assert!(
+        (0.0..=1.0).contains(&args.hot_ratio),
+        "--hot-ratio must be between 0 and 1"
+    );
+
+    let registry_service = mysten_metrics::start_prometheus_server(args.metrics_address);
+    let registry = registry_service.default_registry();
+
+    let workload = Workload::new()
+        .accounts(args.num_accounts as usize)
+        .initial_balances(1.0, args.initial_balance..=args.initial_balance)
+        .hot_accounts(args.hot_accounts, args.hot_ratio)
+        .settle_every(args.txs_per_version as usize)
+        .max_accounts_per_tx(args.max_accounts_per_tx as usize)
+        .max_withdraw(args.max_withdraw)
+        .seed(args.seed);
+
+    let config = WorkloadConfig {
+        workload,
+        deposit_per_settlement: args.deposit_per_settlement,
+        settlement_interval: Duration::from_millis(args.settlement_interval_ms),
+        duration: Duration::from_secs(args.duration_secs),
+    };
+
+    run_workload(config, &registry).await;
+}
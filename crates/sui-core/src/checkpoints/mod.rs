@@ -5,6 +5,7 @@ mod causal_order;
 pub mod checkpoint_executor;
 mod checkpoint_output;
 mod metrics;
+mod settlement_cadence;
 
 use crate::accumulators::AccumulatorSettlementTxBuilder;
 use crate::authority::AuthorityState;
@@ -15,6 +16,7 @@ pub use crate::checkpoints::checkpoint_output::{
     LogCheckpointOutput, SendCheckpointToStateSync, SubmitCheckpointToConsensus,
 };
 pub use crate::checkpoints::metrics::CheckpointMetrics;
+use crate::checkpoints::settlement_cadence::SettlementCadencePolicy;
 use crate::consensus_manager::ReplayWaiter;
 use crate::execution_cache::TransactionCacheRead;
 
@@ -75,7 +77,12 @@ use sui_types::sui_system_state::{SuiSystemState, SuiSystemStateTrait};
 use sui_types::transaction::{
     TransactionDataAPI, TransactionKey, TransactionKind, VerifiedTransaction,
 };
-use tokio::{sync::Notify, task::JoinSet, time::timeout};
+use sui_types::SUI_ACCUMULATOR_ROOT_OBJECT_ID;
+use tokio::{
+    sync::Notify,
+    task::JoinSet,
+    time::{timeout, Instant},
+};
 use tracing::{debug, error, info, instrument, trace, warn};
 use typed_store::DBMapUtils;
 use typed_store::Map;
@@ -101,6 +108,9 @@ pub struct PendingCheckpointInfo {
     // Computed in calculate_pending_checkpoint_height() from consensus round,
     // there is no guarantee that this is increasing per checkpoint, because of checkpoint splitting.
     pub checkpoint_height: CheckpointHeight,
+    // Number of balance withdraw transactions in the checkpoint, which the checkpoint builder
+    // uses to decide how soon their balances should be settled.
+    pub balance_withdraws: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -1223,12 +1233,10 @@ impl CheckpointBuilder {
         let mut last_height = summary.clone().and_then(|s| s.checkpoint_height);
         let mut last_timestamp = summary.map(|s| s.summary.timestamp_ms);
 
-        let min_checkpoint_interval_ms = self
-            .epoch_store
-            .protocol_config()
-            .min_checkpoint_interval_ms_as_option()
-            .unwrap_or_default();
+        let settlement_cadence =
+            SettlementCadencePolicy::from_protocol_config(self.epoch_store.protocol_config());
         let mut grouped_pending_checkpoints = Vec::new();
+        let mut pending_withdraws = 0;
         let mut checkpoints_iter = self
             .epoch_store
             .get_pending_checkpoints(last_height)
@@ -1238,7 +1246,11 @@ impl CheckpointBuilder {
         while let Some((height, pending)) = checkpoints_iter.next() {
             // Group PendingCheckpoints until:
             // - minimum interval has elapsed ...
+            // The minimum interval is shorter while many withdraws are waiting to be settled, and
+            // can be longer while none are.
             let current_timestamp = pending.details().timestamp_ms;
+            pending_withdraws += pending.details().balance_withdraws;
+            let min_checkpoint_interval_ms = settlement_cadence.min_interval_ms(pending_withdraws);
             let can_build = match last_timestamp {
                     Some(last_timestamp) => {
                         current_timestamp >= last_timestamp + min_checkpoint_interval_ms
@@ -1257,6 +1269,7 @@ impl CheckpointBuilder {
                     checkpoint_commit_height = height,
                     ?last_timestamp,
                     ?current_timestamp,
+                    pending_withdraws,
                     "waiting for more PendingCheckpoints: minimum interval not yet elapsed"
                 );
                 continue;
@@ -1265,6 +1278,7 @@ impl CheckpointBuilder {
             // Min interval has elapsed, we can now coalesce and build a checkpoint.
             last_height = Some(height);
             last_timestamp = Some(current_timestamp);
+            pending_withdraws = 0;
             debug!(
                 checkpoint_commit_height_from = grouped_pending_checkpoints
                     .first()
@@ -1356,6 +1370,7 @@ impl CheckpointBuilder {
         );
 
         let settlements = builder.get_balance_settlements();
+        let settlements_produced_at = Instant::now();
         let num_updates = builder.num_updates();
         let settlement_txns = builder.build_tx(&self.epoch_store, checkpoint_height);
 
@@ -1410,9 +1425,22 @@ impl CheckpointBuilder {
             );
         }
 
-        self.state
-            .execution_scheduler()
-            .settle_balances(settlements);
+        // After a restart, settlements of checkpoints that were built but not written before the
+        // restart are replayed here, and their effects are already executed. The version they
+        // advanced the accumulator root to tells the scheduler whether it has seen them.
+        let settled_version = settlement_effects
+            .iter()
+            .flat_map(|fx| fx.mutated())
+            .filter(|((id, _, _), _)| *id == SUI_ACCUMULATOR_ROOT_OBJECT_ID)
+            .map(|((_, version, _), _)| version)
+            .max()
+            .expect("settlement transactions must mutate the accumulator root");
+
+        self.state.execution_scheduler().settle_balances(
+            settled_version,
+            settlements,
+            settlements_produced_at,
+        );
 
         (tx_key, settlement_effects)
     }
@@ -3581,6 +3609,7 @@ mod tests {
                 timestamp_ms,
                 last_of_epoch: false,
                 checkpoint_height: i,
+                balance_withdraws: 0,
             },
         }
     }
@@ -0,0 +1,160 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Balances are settled once per checkpoint, so the interval between checkpoints is also the
+//! interval between balance settlements. While many withdraws are waiting to be settled, they are
+//! checked against balances that do not reflect recent deposits yet, and can be rejected with
+//! insufficient balance even though they would have succeeded a little later. The policy here
+//! shortens the interval in that case, and lets it return to the usual minimum interval once the
+//! pressure subsides. While no withdraws are waiting at all, there is nothing to settle, and the
+//! policy can lengthen the interval instead to produce fewer settlements.
+//!
+//! Withdraw pressure is measured from the balance withdraw transactions in the pending checkpoints
+//! that are waiting to be built. These are agreed through consensus, so that every validator
+//! coalesces the same commits into each checkpoint, and builds the same settlements.
+
+use sui_protocol_config::ProtocolConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SettlementCadencePolicy {
+    /// Minimum interval of commit timestamps between checkpoints when balances are not under
+    /// withdraw pressure.
+    min_interval_ms: u64,
+
+    /// Number of pending withdraws at which balances are under pressure, and the minimum interval
+    /// used then. Unset if the interval does not follow withdraw pressure.
+    pressure: Option<(u64, u64)>,
+
+    /// Minimum interval used while no withdraws are pending. Unset if the interval is not
+    /// lengthened when idle.
+    idle_interval_ms: Option<u64>,
+}
+
+impl SettlementCadencePolicy {
+    pub(crate) fn from_protocol_config(config: &ProtocolConfig) -> Self {
+        let min_interval_ms = config
+            .min_checkpoint_interval_ms_as_option()
+            .unwrap_or_default();
+        let pressure = config
+            .settlement_pressure_withdraw_threshold_as_option()
+            .zip(config.settlement_pressure_checkpoint_interval_ms_as_option());
+        let idle_interval_ms = config.settlement_idle_checkpoint_interval_ms_as_option();
+
+        Self::new(min_interval_ms, pressure, idle_interval_ms)
+    }
+
+    fn new(
+        min_interval_ms: u64,
+        pressure: Option<(u64, u64)>,
+        idle_interval_ms: Option<u64>,
+    ) -> Self {
+        Self {
+            min_interval_ms,
+            // Pressure can only shorten the interval.
+            pressure: pressure.map(|(threshold, interval_ms)| {
+                (threshold.max(1), interval_ms.min(min_interval_ms))
+            }),
+            // Idleness can only lengthen it.
+            idle_interval_ms: idle_interval_ms.map(|interval_ms| interval_ms.max(min_interval_ms)),
+        }
+    }
+
+    /// Minimum interval of commit timestamps since the last checkpoint before the next one can be
+    /// built, when `pending_withdraws` balance withdraw transactions are waiting to be included in
+    /// it.
+    pub(crate) fn min_interval_ms(&self, pending_withdraws: u64) -> u64 {
+        match (self.pressure, self.idle_interval_ms) {
+            (_, Some(interval_ms)) if pending_withdraws == 0 => interval_ms,
+            (Some((threshold, interval_ms)), _) if pending_withdraws >= threshold => interval_ms,
+            _ => self.min_interval_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_protocol_config::{Chain, ProtocolVersion};
+
+    use super::*;
+
+    #[test]
+    fn test_interval_without_pressure_config() {
+        let policy = SettlementCadencePolicy::new(200, None, None);
+        assert_eq!(policy.min_interval_ms(0), 200);
+        assert_eq!(policy.min_interval_ms(u64::MAX), 200);
+    }
+
+    #[test]
+    fn test_interval_follows_pressure() {
+        let policy = SettlementCadencePolicy::new(200, Some((10, 50)), None);
+
+        // Idle, and below the threshold.
+        assert_eq!(policy.min_interval_ms(0), 200);
+        assert_eq!(policy.min_interval_ms(9), 200);
+
+        // Under pressure.
+        assert_eq!(policy.min_interval_ms(10), 50);
+        assert_eq!(policy.min_interval_ms(1000), 50);
+
+        // Back to the usual interval once the withdraws have been settled.
+        assert_eq!(policy.min_interval_ms(0), 200);
+    }
+
+    #[test]
+    fn test_pressure_never_lengthens_interval() {
+        let policy = SettlementCadencePolicy::new(200, Some((10, 500)), None);
+        assert_eq!(policy.min_interval_ms(10), 200);
+    }
+
+    #[test]
+    fn test_zero_threshold_requires_a_withdraw() {
+        let policy = SettlementCadencePolicy::new(200, Some((0, 50)), None);
+        assert_eq!(policy.min_interval_ms(0), 200);
+        assert_eq!(policy.min_interval_ms(1), 50);
+    }
+
+    #[test]
+    fn test_interval_follows_idleness() {
+        let policy = SettlementCadencePolicy::new(200, Some((10, 50)), Some(1000));
+
+        // Idle.
+        assert_eq!(policy.min_interval_ms(0), 1000);
+
+        // Any pending withdraw returns to the usual interval, or shorter under pressure.
+        assert_eq!(policy.min_interval_ms(1), 200);
+        assert_eq!(policy.min_interval_ms(9), 200);
+        assert_eq!(policy.min_interval_ms(10), 50);
+
+        // Idle again once the withdraws have been settled.
+        assert_eq!(policy.min_interval_ms(0), 1000);
+    }
+
+    #[test]
+    fn test_idleness_never_shortens_interval() {
+        let policy = SettlementCadencePolicy::new(200, None, Some(100));
+        assert_eq!(policy.min_interval_ms(0), 200);
+        assert_eq!(policy.min_interval_ms(1), 200);
+    }
+
+    #[test]
+    fn test_from_protocol_config() {
+        let mut config = ProtocolConfig::get_for_version(ProtocolVersion::max(), Chain::Unknown);
+        config.set_min_checkpoint_interval_ms_for_testing(200);
+
+        // Both settings are needed to follow pressure.
+        config.set_settlement_pressure_withdraw_threshold_for_testing(10);
+        let policy = SettlementCadencePolicy::from_protocol_config(&config);
+        assert_eq!(policy.min_interval_ms(10), 200);
+
+        config.set_settlement_pressure_checkpoint_interval_ms_for_testing(50);
+        let policy = SettlementCadencePolicy::from_protocol_config(&config);
+        assert_eq!(policy.min_interval_ms(9), 200);
+        assert_eq!(policy.min_interval_ms(10), 50);
+
+        config.set_settlement_idle_checkpoint_interval_ms_for_testing(1000);
+        let policy = SettlementCadencePolicy::from_protocol_config(&config);
+        assert_eq!(policy.min_interval_ms(0), 1000);
+        assert_eq!(policy.min_interval_ms(1), 200);
+        assert_eq!(policy.min_interval_ms(10), 50);
+    }
+}
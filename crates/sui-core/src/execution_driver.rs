@@ -40,6 +40,7 @@ pub async fn execution_process(
         let execution_env;
         let txn_ready_time;
         let _executing_guard;
+        let package_permits;
         tokio::select! {
             result = rx_ready_certificates.recv() => {
                 if let Some(pending_cert) = result {
@@ -47,6 +48,7 @@ pub async fn execution_process(
                     execution_env = pending_cert.execution_env;
                     txn_ready_time = pending_cert.stats.ready_time.unwrap();
                     _executing_guard = pending_cert.executing_guard;
+                    package_permits = pending_cert.package_permits;
                 } else {
                     // Should only happen after the AuthorityState has shut down and tx_ready_certificate
                     // has been dropped by ExecutionScheduler.
@@ -111,6 +113,7 @@ pub async fn execution_process(
         spawn_monitored_task!(epoch_store.within_alive_epoch(async move {
             let _scope = monitored_scope("ExecutionDriver::task");
             let _guard = permit;
+            let _package_permits = package_permits;
             if authority.is_tx_already_executed(&digest) {
                 return;
             }
@@ -0,0 +1,397 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    num::NonZeroUsize,
+    sync::Arc,
+};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use prometheus::IntCounter;
+use sui_types::base_types::{ObjectID, SequenceNumber};
+
+use crate::execution_scheduler::balance_withdraw_scheduler::balance_read::AccountBalanceRead;
+
+/// Metrics for the [`CachedBalanceRead`], to track how often reads are served from the cache.
+#[derive(Clone)]
+pub(crate) struct BalanceReadCacheMetrics {
+    /// Reads served from the cache.
+    pub hits: IntCounter,
+    /// Reads that went to the underlying store.
+    pub misses: IntCounter,
+    /// Cached balances dropped because a settlement changed them.
+    pub invalidations: IntCounter,
+}
+
+/// A cache of account balances in front of another [`AccountBalanceRead`] (usually the store),
+/// shared between the withdraw scheduler and RPC reads of settled balances.
+///
+/// Each cached balance is the balance of an account at some accumulator version. Balances only
+/// change when a settlement changes them, so a balance cached at one version also answers reads
+/// at later settled versions, until a settlement changes the account, at which point it is
+/// invalidated. Reads at versions that are not settled yet, or older than the cached version,
+/// go to the store, and are not cached.
+///
+/// The cache must be told about every settlement, in order, once its balance changes can be
+/// read from the store, but before the scheduler reads at its version, see
+/// [`AccountBalanceRead::settle_balances`].
+///
+/// The balance changes of the most recent settlements are also retained, so that balances at
+/// those older versions can be read exactly, by undoing the changes settled since, whether or
+/// not the store still holds them.
+pub(crate) struct CachedBalanceRead {
+    inner: Arc<dyn AccountBalanceRead>,
+    state: Mutex<CacheState>,
+    /// How many of the most recent settlements' balance changes are retained.
+    retained_versions: usize,
+    metrics: Option<BalanceReadCacheMetrics>,
+}
+
+struct CacheState {
+    /// The last accumulator version settled, whose balances are known to be readable from the
+    /// store.
+    settled_version: SequenceNumber,
+    /// The balance of each account at the version it was read at, which is still its balance as
+    /// of `settled_version`.
+    entries: LruCache<ObjectID, CachedBalance>,
+    /// The balance changes of the most recent settlements, oldest first, each with the version
+    /// it settled. The last of them settled `settled_version`.
+    history: VecDeque<(SequenceNumber, BTreeMap<ObjectID, i128>)>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CachedBalance {
+    version: SequenceNumber,
+    balance: u64,
+}
+
+impl CachedBalanceRead {
+    pub fn new(
+        inner: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+        capacity: NonZeroUsize,
+        retained_versions: usize,
+        metrics: Option<BalanceReadCacheMetrics>,
+    ) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(CacheState {
+                settled_version: starting_accumulator_version,
+                entries: LruCache::new(capacity),
+                history: VecDeque::with_capacity(retained_versions),
+            }),
+            retained_versions,
+            metrics,
+        }
+    }
+
+    /// The last accumulator version that the cache was told was settled.
+    pub fn settled_version(&self) -> SequenceNumber {
+        self.state.lock().settled_version
+    }
+
+    /// The balance of an account as of the last settled accumulator version.
+    pub fn get_settled_balance(&self, account_id: &ObjectID) -> u64 {
+        let settled_version = self.settled_version();
+        self.get_account_balance(account_id, settled_version)
+    }
+
+    /// The balance of an account as of `accumulator_version`, which must have been settled. It is
+    /// exact for the versions whose settlements are retained, and read from the store otherwise.
+    pub fn get_balance_at(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> u64 {
+        self.get_account_balance_at(account_id, accumulator_version)
+            .unwrap_or_else(|| self.get_account_balance(account_id, accumulator_version))
+    }
+}
+
+impl AccountBalanceRead for CachedBalanceRead {
+    fn get_account_balance(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> u64 {
+        let settled_version = {
+            let mut state = self.state.lock();
+            let settled_version = state.settled_version;
+            if accumulator_version <= settled_version {
+                if let Some(cached) = state.entries.get(account_id) {
+                    if cached.version <= accumulator_version {
+                        if let Some(metrics) = &self.metrics {
+                            metrics.hits.inc();
+                        }
+                        return cached.balance;
+                    }
+                }
+            }
+            settled_version
+        };
+
+        if let Some(metrics) = &self.metrics {
+            metrics.misses.inc();
+        }
+
+        // Read without holding the lock, so that slow reads do not block hits.
+        let balance = self
+            .inner
+            .get_account_balance(account_id, accumulator_version);
+
+        // Only cache balances read at the settled version, and only if no settlement happened
+        // during the read, because it could have changed the balance without invalidating it.
+        let mut state = self.state.lock();
+        if accumulator_version == settled_version && state.settled_version == settled_version {
+            let newer = state
+                .entries
+                .peek(account_id)
+                .is_some_and(|cached| cached.version > accumulator_version);
+            if !newer {
+                state.entries.put(
+                    *account_id,
+                    CachedBalance {
+                        version: accumulator_version,
+                        balance,
+                    },
+                );
+            }
+        }
+
+        balance
+    }
+
+    /// Exact for the settled version, and the `retained_versions` versions before it (so long as
+    /// they were settled since the cache was created).
+    fn get_account_balance_at(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> Option<u64> {
+        let (settled_version, undo) = {
+            let state = self.state.lock();
+            if accumulator_version > state.settled_version {
+                return None;
+            }
+
+            // The settlements after `accumulator_version` must all be retained to undo them.
+            let since = state.settled_version.value() - accumulator_version.value();
+            if since > state.history.len() as u64 {
+                return None;
+            }
+
+            let undo: i128 = state
+                .history
+                .iter()
+                .filter(|(version, _)| *version > accumulator_version)
+                .filter_map(|(_, changes)| changes.get(account_id))
+                .sum();
+
+            (state.settled_version, undo)
+        };
+
+        // Reading at the settled version is exact even if another settlement happens meanwhile.
+        let balance = self.get_account_balance(account_id, settled_version) as i128 - undo;
+        Some(balance.clamp(0, u64::MAX as i128) as u64)
+    }
+
+    fn settle_balances(&self, balance_changes: &BTreeMap<ObjectID, i128>) {
+        let mut state = self.state.lock();
+        state.settled_version = state.settled_version.next();
+
+        if self.retained_versions > 0 {
+            if state.history.len() == self.retained_versions {
+                state.history.pop_front();
+            }
+            let settled_version = state.settled_version;
+            state
+                .history
+                .push_back((settled_version, balance_changes.clone()));
+        }
+
+        let mut invalidated = 0;
+        for account_id in balance_changes.keys() {
+            if state.entries.pop(account_id).is_some() {
+                invalidated += 1;
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.invalidations.inc_by(invalidated);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::execution_scheduler::balance_withdraw_scheduler::balance_read::MockBalanceRead;
+
+    /// Counts the reads that reach the store.
+    struct CountingBalanceRead {
+        inner: MockBalanceRead,
+        reads: AtomicUsize,
+    }
+
+    impl AccountBalanceRead for CountingBalanceRead {
+        fn get_account_balance(
+            &self,
+            account_id: &ObjectID,
+            accumulator_version: SequenceNumber,
+        ) -> u64 {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner
+                .get_account_balance(account_id, accumulator_version)
+        }
+    }
+
+    fn metrics() -> BalanceReadCacheMetrics {
+        let registry = Registry::new();
+        let counter = |name: &str| {
+            let counter = IntCounter::new(name, name).unwrap();
+            registry.register(Box::new(counter.clone())).unwrap();
+            counter
+        };
+
+        BalanceReadCacheMetrics {
+            hits: counter("hits"),
+            misses: counter("misses"),
+            invalidations: counter("invalidations"),
+        }
+    }
+
+    struct TestCache {
+        store: Arc<CountingBalanceRead>,
+        cache: CachedBalanceRead,
+        metrics: BalanceReadCacheMetrics,
+    }
+
+    impl TestCache {
+        fn new(capacity: usize, balances: BTreeMap<ObjectID, u64>) -> Self {
+            let store = Arc::new(CountingBalanceRead {
+                inner: MockBalanceRead::new(SequenceNumber::from_u64(0), balances),
+                reads: AtomicUsize::new(0),
+            });
+
+            let metrics = metrics();
+            let cache = CachedBalanceRead::new(
+                store.clone(),
+                SequenceNumber::from_u64(0),
+                NonZeroUsize::new(capacity).unwrap(),
+                2,
+                Some(metrics.clone()),
+            );
+
+            Self {
+                store,
+                cache,
+                metrics,
+            }
+        }
+
+        /// Settle the next version in the store, and then in the cache.
+        fn settle(&self, balance_changes: BTreeMap<ObjectID, i128>) {
+            self.store
+                .inner
+                .settle_balance_changes(balance_changes.clone());
+            self.cache.settle_balances(&balance_changes);
+        }
+
+        fn store_reads(&self) -> usize {
+            self.store.reads.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_hits_until_invalidated() {
+        let a = ObjectID::random();
+        let b = ObjectID::random();
+        let cache = TestCache::new(10, BTreeMap::from([(a, 100), (b, 200)]));
+        let v = SequenceNumber::from_u64;
+
+        assert_eq!(cache.cache.get_account_balance(&a, v(0)), 100);
+        assert_eq!(cache.cache.get_account_balance(&a, v(0)), 100);
+        assert_eq!(cache.store_reads(), 1);
+
+        // Settling a change to another account leaves the cached balance valid at the next
+        // version.
+        cache.settle(BTreeMap::from([(b, -50)]));
+        assert_eq!(cache.cache.get_account_balance(&a, v(1)), 100);
+        assert_eq!(cache.store_reads(), 1);
+        assert_eq!(cache.cache.get_settled_balance(&b), 150);
+        assert_eq!(cache.store_reads(), 2);
+
+        // Settling a change to the account invalidates it.
+        cache.settle(BTreeMap::from([(a, 25)]));
+        assert_eq!(cache.cache.get_account_balance(&a, v(2)), 125);
+        assert_eq!(cache.cache.get_account_balance(&a, v(2)), 125);
+        assert_eq!(cache.store_reads(), 3);
+
+        assert_eq!(cache.metrics.hits.get(), 3);
+        assert_eq!(cache.metrics.misses.get(), 3);
+        assert_eq!(cache.metrics.invalidations.get(), 1);
+    }
+
+    #[test]
+    fn test_unsettled_and_older_versions_are_not_cached() {
+        let a = ObjectID::random();
+        let cache = TestCache::new(10, BTreeMap::from([(a, 100)]));
+        let v = SequenceNumber::from_u64;
+
+        // Reads ahead of the settled version go to the store every time, because the balance
+        // could still change when the version is settled.
+        assert_eq!(cache.cache.get_account_balance(&a, v(1)), 100);
+        assert_eq!(cache.cache.get_account_balance(&a, v(1)), 100);
+        assert_eq!(cache.store_reads(), 2);
+
+        cache.settle(BTreeMap::from([(a, 10)]));
+        assert_eq!(cache.cache.get_account_balance(&a, v(1)), 110);
+
+        // The cached balance is newer than the version read, so it cannot answer it.
+        assert_eq!(cache.cache.get_account_balance(&a, v(0)), 100);
+        assert_eq!(cache.cache.get_account_balance(&a, v(1)), 110);
+        assert_eq!(cache.store_reads(), 4);
+    }
+
+    #[test]
+    fn test_historical_balances() {
+        let a = ObjectID::random();
+        let cache = TestCache::new(10, BTreeMap::from([(a, 100)]));
+        let v = SequenceNumber::from_u64;
+
+        cache.settle(BTreeMap::from([(a, -30)]));
+        cache.settle(BTreeMap::from([(a, 50)]));
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(2)), Some(120));
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(1)), Some(70));
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(0)), Some(100));
+
+        // Only the last two settlements are retained, and unsettled versions are not known.
+        cache.settle(BTreeMap::from([(a, 5)]));
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(1)), Some(70));
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(0)), None);
+        assert_eq!(cache.cache.get_account_balance_at(&a, v(4)), None);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let accounts: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+        let cache = TestCache::new(2, accounts.iter().map(|account| (*account, 100)).collect());
+
+        for account in &accounts {
+            cache.cache.get_settled_balance(account);
+        }
+        assert_eq!(cache.store_reads(), 3);
+
+        // The first account was evicted to make room for the third.
+        cache.cache.get_settled_balance(&accounts[2]);
+        assert_eq!(cache.store_reads(), 3);
+        cache.cache.get_settled_balance(&accounts[0]);
+        assert_eq!(cache.store_reads(), 4);
+    }
+}
@@ -1,9 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(test)]
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[cfg(test)]
 use parking_lot::RwLock;
@@ -21,6 +19,25 @@ pub(crate) trait AccountBalanceRead: Send + Sync {
         // bound the version when we look for child account objects.
         accumulator_version: SequenceNumber,
     ) -> u64;
+
+    /// The balance of `account_id` as of `accumulator_version`, an accumulator version that has
+    /// already been settled, or `None` if the implementation no longer knows it. Unlike
+    /// [`Self::get_account_balance`], which reads whatever the store still holds, this must be
+    /// exact: implementations that retain history guarantee it for a fixed number of the most
+    /// recently settled versions, and return `None` for older ones.
+    fn get_account_balance_at(
+        &self,
+        _account_id: &ObjectID,
+        _accumulator_version: SequenceNumber,
+    ) -> Option<u64> {
+        None
+    }
+
+    /// Called once for each accumulator version as it is settled, in order, with the balance
+    /// changes of its settlement. It is called once the changes can be read, and before the
+    /// scheduler reads any balances at that version. Implementations that cache balances use it
+    /// to invalidate them.
+    fn settle_balances(&self, _balance_changes: &BTreeMap<ObjectID, i128>) {}
 }
 
 impl AccountBalanceRead for Arc<dyn ChildObjectResolver + Send + Sync> {
@@ -124,4 +141,15 @@ impl AccountBalanceRead for MockBalanceRead {
         let inner = self.inner.read();
         inner.get_account_balance(account_id, accumulator_version)
     }
+
+    /// The mock retains every settled version.
+    fn get_account_balance_at(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> Option<u64> {
+        let inner = self.inner.read();
+        (accumulator_version <= inner.cur_version)
+            .then(|| inner.get_account_balance(account_id, accumulator_version))
+    }
 }
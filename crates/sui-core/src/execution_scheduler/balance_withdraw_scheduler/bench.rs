@@ -0,0 +1,237 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Synthetic workloads for soak-testing the balance withdraw scheduler, used by the
+//! `balance-scheduler-bench` binary.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::StreamExt;
+use parking_lot::RwLock;
+use prometheus::{
+    register_histogram_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_with_registry, Histogram, IntCounter,
+    IntCounterVec, IntGauge, Registry,
+};
+use sui_types::base_types::{ObjectID, SequenceNumber};
+use tracing::info;
+
+use crate::execution_scheduler::balance_withdraw_scheduler::{
+    balance_read::AccountBalanceRead, scheduler::BalanceWithdrawScheduler, BalanceSettlement,
+    ScheduleStatus,
+};
+
+pub use crate::execution_scheduler::balance_withdraw_scheduler::workload::Workload;
+
+const LATENCY_SEC_BUCKETS: &[f64] = &[0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1., 5.];
+
+/// Parameters of a synthetic workload.
+#[derive(Clone, Debug)]
+pub struct WorkloadConfig {
+    /// The withdraws to schedule, and the balances they are scheduled against.
+    pub workload: Workload,
+    /// Amount deposited into each account on every settlement, to keep balances topped up.
+    pub deposit_per_settlement: u64,
+    /// Time between settlements.
+    pub settlement_interval: Duration,
+    /// How long to run the workload for, unless it runs out of transactions first.
+    pub duration: Duration,
+}
+
+struct BenchMetrics {
+    scheduled_txs: IntCounterVec,
+    schedule_latency: Histogram,
+    settlements: IntCounter,
+    settled_version: IntGauge,
+    reserved_balance: IntGauge,
+}
+
+impl BenchMetrics {
+    fn new(registry: &Registry) -> Self {
+        Self {
+            scheduled_txs: register_int_counter_vec_with_registry!(
+                "balance_scheduler_bench_scheduled_txs",
+                "Number of transactions scheduled, by scheduling status",
+                &["status"],
+                registry,
+            )
+            .unwrap(),
+            schedule_latency: register_histogram_with_registry!(
+                "balance_scheduler_bench_schedule_latency",
+                "Time from submitting a batch of withdraws until all of them are scheduled",
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            settlements: register_int_counter_with_registry!(
+                "balance_scheduler_bench_settlements",
+                "Number of settlements sent to the scheduler",
+                registry,
+            )
+            .unwrap(),
+            settled_version: register_int_gauge_with_registry!(
+                "balance_scheduler_bench_settled_version",
+                "Latest accumulator version settled by the workload",
+                registry,
+            )
+            .unwrap(),
+            reserved_balance: register_int_gauge_with_registry!(
+                "balance_scheduler_bench_reserved_balance",
+                "Total balance reserved by scheduled but unsettled withdraws across hot accounts",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// In-memory account balances, keeping the balance of each account at every settled version,
+/// as the scheduler may read balances at versions behind the latest one.
+struct BenchBalanceRead {
+    balances: RwLock<BTreeMap<ObjectID, BTreeMap<SequenceNumber, u64>>>,
+}
+
+impl BenchBalanceRead {
+    fn new(
+        version: SequenceNumber,
+        accounts: &[ObjectID],
+        init_balances: &BTreeMap<ObjectID, u64>,
+    ) -> Self {
+        let balances = accounts
+            .iter()
+            .map(|id| {
+                let balance = init_balances.get(id).copied().unwrap_or_default();
+                (*id, BTreeMap::from([(version, balance)]))
+            })
+            .collect();
+        Self {
+            balances: RwLock::new(balances),
+        }
+    }
+
+    /// Apply `changes` on top of the balances at `version`, producing the balances at
+    /// `next_version`. History older than `version` is dropped.
+    fn settle(
+        &self,
+        version: SequenceNumber,
+        next_version: SequenceNumber,
+        changes: &BTreeMap<ObjectID, i128>,
+    ) {
+        let mut balances = self.balances.write();
+        for (id, history) in balances.iter_mut() {
+            let current = history
+                .range(..=version)
+                .next_back()
+                .map_or(0, |(_, balance)| *balance);
+            let change = changes.get(id).copied().unwrap_or_default();
+            let next = (current as i128 + change).clamp(0, u64::MAX as i128) as u64;
+            *history = history.split_off(&version);
+            history.insert(next_version, next);
+        }
+    }
+}
+
+impl AccountBalanceRead for BenchBalanceRead {
+    fn get_account_balance(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> u64 {
+        self.balances
+            .read()
+            .get(account_id)
+            .and_then(|history| history.range(..=accumulator_version).next_back())
+            .map_or(0, |(_, balance)| *balance)
+    }
+}
+
+/// Run `config`'s workload against a real [`BalanceWithdrawScheduler`], reporting progress
+/// through metrics registered in `registry`.
+///
+/// For every accumulator version, a batch of withdraws is scheduled and awaited, the
+/// withdraws that were scheduled with sufficient balance are settled (along with deposits),
+/// and the workload moves on to the next version after `settlement_interval`.
+pub async fn run_workload(config: WorkloadConfig, registry: &Registry) {
+    let metrics = BenchMetrics::new(registry);
+    let mut generator = config.workload.generator();
+
+    let mut version = SequenceNumber::from_u64(1);
+    let balance_read = Arc::new(BenchBalanceRead::new(
+        version,
+        generator.accounts(),
+        generator.init_balances(),
+    ));
+    let scheduler = BalanceWithdrawScheduler::new(balance_read.clone(), version);
+
+    let start = Instant::now();
+    while start.elapsed() < config.duration {
+        let Some(withdraws) = generator.next_batch() else {
+            break;
+        };
+
+        let reservations: BTreeMap<_, _> = withdraws
+            .iter()
+            .map(|w| (w.tx_digest, w.reservations.clone()))
+            .collect();
+
+        let batch_start = Instant::now();
+        let mut receivers = scheduler.schedule_withdraws(version, withdraws);
+        let mut changes: BTreeMap<ObjectID, i128> = BTreeMap::new();
+        let mut withdrawn = BTreeMap::new();
+        while let Some(result) = receivers.next().await {
+            let Ok(result) = result else {
+                continue;
+            };
+            let status = match result.status {
+                ScheduleStatus::SufficientBalance => {
+                    let tx_reservations = &reservations[&result.tx_digest];
+                    for (account, amount) in tx_reservations {
+                        *changes.entry(*account).or_default() -= *amount as i128;
+                    }
+                    withdrawn.insert(result.tx_digest, tx_reservations.clone());
+                    "sufficient_balance"
+                }
+                ScheduleStatus::InsufficientBalance => "insufficient_balance",
+                ScheduleStatus::AlreadyExecuted => "already_executed",
+                ScheduleStatus::PolicyRejected => "policy_rejected",
+                ScheduleStatus::Rejected => "rejected",
+            };
+            metrics.scheduled_txs.with_label_values(&[status]).inc();
+        }
+        metrics
+            .schedule_latency
+            .observe(batch_start.elapsed().as_secs_f64());
+        metrics.reserved_balance.set(
+            generator
+                .hot_accounts()
+                .iter()
+                .map(|id| scheduler.get_reserved_balance(id) as i64)
+                .sum(),
+        );
+
+        tokio::time::sleep(config.settlement_interval).await;
+
+        for account in generator.accounts() {
+            *changes.entry(*account).or_default() += config.deposit_per_settlement as i128;
+        }
+        let next_version = version.next();
+        balance_read.settle(version, next_version, &changes);
+        scheduler.settle_balances(BalanceSettlement {
+            balance_changes: changes,
+            withdraws: withdrawn,
+        });
+        version = next_version;
+
+        metrics.settlements.inc();
+        metrics.settled_version.set(version.value() as i64);
+    }
+
+    info!(
+        "Balance scheduler bench finished after {} settlements",
+        metrics.settlements.get()
+    );
+}
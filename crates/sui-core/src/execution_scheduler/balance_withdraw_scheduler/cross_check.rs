@@ -0,0 +1,290 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Arc,
+};
+
+use crate::execution_scheduler::balance_withdraw_scheduler::{
+    balance_read::AccountBalanceRead, policy::WithdrawPolicy, ScheduleResult, ScheduleStatus,
+    TxBalanceWithdraw,
+};
+use mysten_common::{fatal, in_test_configuration};
+use parking_lot::Mutex;
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+
+/// Environment variable that enables cross-checking the withdraw scheduler against
+/// [`ReferenceWithdrawScheduler`], when set to `1` or `true`. Only honoured in test
+/// configurations (debug builds, simtests and antithesis).
+pub(crate) const CROSS_CHECK_ENV_VAR: &str = "SUI_CROSS_CHECK_WITHDRAW_SCHEDULER";
+
+/// Number of recent scheduler events kept for the state dump on a divergence.
+const HISTORY_LEN: usize = 1000;
+
+/// Whether the withdraw scheduler's decisions should be cross-checked against the reference
+/// model.
+pub(crate) fn cross_check_enabled() -> bool {
+    in_test_configuration()
+        && std::env::var(CROSS_CHECK_ENV_VAR).is_ok_and(|v| matches!(v.as_str(), "1" | "true"))
+}
+
+/// A reference model of the balance withdraw scheduler, used as a safety net for the live
+/// scheduler's decisions while it matures.
+///
+/// The model is deliberately simple: every withdraw waits for its accumulator version to be
+/// settled, and the withdraws of each batch are then checked one at a time, in order, against
+/// the settled balances at that version. It never schedules ahead of settlement, and ignores
+/// unsettled deposits, which the live scheduler promises make no difference to its results.
+///
+/// It is fed the same withdraws, settlements and amendments as the live scheduler, and each of
+/// its decisions is compared with the live decision for the same transaction. Any divergence is
+/// fatal, and dumps the model's state, including its recent history, to help reproduce it.
+/// Decisions that either side reports as [`ScheduleStatus::AlreadyExecuted`] are not compared,
+/// because which side sees a settlement first is a race that does not affect execution.
+///
+/// Unlike the live scheduler, the model reads balances on the calling task, which is only
+/// acceptable because it is never enabled in production.
+pub(crate) struct ReferenceWithdrawScheduler {
+    balance_read: Arc<dyn AccountBalanceRead>,
+    max_reservation_per_transaction: Option<u64>,
+    policy: Arc<dyn WithdrawPolicy>,
+    state: Mutex<ReferenceState>,
+}
+
+#[derive(Debug)]
+struct ReferenceState {
+    last_settled_version: SequenceNumber,
+    /// Batches of withdraws waiting for their accumulator version to be settled, in the order
+    /// they were scheduled.
+    waiting: BTreeMap<SequenceNumber, Vec<Vec<TxBalanceWithdraw>>>,
+    /// Decisions that only one side has made so far.
+    unmatched: HashMap<TransactionDigest, (Side, ScheduleStatus)>,
+    /// The most recent events the model was fed, oldest first.
+    history: VecDeque<Event>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Side {
+    Live,
+    Reference,
+}
+
+#[derive(Debug)]
+#[allow(unused)] // Only read through the `Debug` impl, in the state dump.
+enum Event {
+    Schedule {
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+    },
+    Settle {
+        accumulator_version: SequenceNumber,
+    },
+    Amend {
+        tx_digest: TransactionDigest,
+        reservations: BTreeMap<ObjectID, u64>,
+    },
+}
+
+impl ReferenceWithdrawScheduler {
+    pub fn new(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+        max_reservation_per_transaction: Option<u64>,
+        policy: Arc<dyn WithdrawPolicy>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            balance_read,
+            max_reservation_per_transaction,
+            policy,
+            state: Mutex::new(ReferenceState {
+                last_settled_version: starting_accumulator_version,
+                waiting: BTreeMap::new(),
+                unmatched: HashMap::new(),
+                history: VecDeque::new(),
+            }),
+        })
+    }
+
+    /// Schedule a copy of withdraws that were sent to the live scheduler, in the same order.
+    pub fn schedule_withdraws(
+        &self,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+    ) {
+        let mut state = self.state.lock();
+        state.push_event(Event::Schedule {
+            accumulator_version,
+            withdraws: withdraws.clone(),
+        });
+
+        if state.last_settled_version > accumulator_version {
+            for withdraw in &withdraws {
+                state.record(
+                    Side::Reference,
+                    withdraw.tx_digest,
+                    ScheduleStatus::AlreadyExecuted,
+                );
+            }
+            return;
+        }
+
+        let mut allowed = Vec::with_capacity(withdraws.len());
+        for withdraw in withdraws {
+            if !withdraw
+                .reservations
+                .keys()
+                .all(|account| self.policy.allows_withdraw(account, accumulator_version))
+            {
+                state.record(
+                    Side::Reference,
+                    withdraw.tx_digest,
+                    ScheduleStatus::PolicyRejected,
+                );
+            } else if self
+                .max_reservation_per_transaction
+                .is_some_and(|limit| withdraw.total_reservation() > limit)
+            {
+                state.record(
+                    Side::Reference,
+                    withdraw.tx_digest,
+                    ScheduleStatus::Rejected,
+                );
+            } else {
+                allowed.push(withdraw);
+            }
+        }
+
+        if state.last_settled_version == accumulator_version {
+            self.decide(&mut state, accumulator_version, allowed);
+        } else {
+            state
+                .waiting
+                .entry(accumulator_version)
+                .or_default()
+                .push(allowed);
+        }
+    }
+
+    /// Settle the next accumulator version, after the live scheduler has settled it, and decide
+    /// the withdraws that were waiting for it.
+    pub fn settle_balances(&self) {
+        let mut state = self.state.lock();
+        let version = state.last_settled_version.saturating_next();
+        state.last_settled_version = version;
+        state.push_event(Event::Settle {
+            accumulator_version: version,
+        });
+
+        for batch in state.waiting.remove(&version).unwrap_or_default() {
+            self.decide(&mut state, version, batch);
+        }
+    }
+
+    /// Apply an amendment that the live scheduler accepted. The model has not decided the
+    /// transaction yet, unless it has already seen its version settled.
+    pub fn amend_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) {
+        let mut state = self.state.lock();
+        state.push_event(Event::Amend {
+            tx_digest: *tx_digest,
+            reservations: new_reservations.clone(),
+        });
+
+        if let Some(withdraw) = state
+            .waiting
+            .values_mut()
+            .flatten()
+            .flatten()
+            .find(|w| w.tx_digest == *tx_digest)
+        {
+            withdraw.reservations = new_reservations;
+        }
+    }
+
+    /// Compare a result from the live scheduler with the model's decision for the same
+    /// transaction, once both are known.
+    pub fn check_live_result(&self, result: &ScheduleResult) {
+        self.state
+            .lock()
+            .record(Side::Live, result.tx_digest, result.status);
+    }
+
+    /// Check the withdraws of `batch` one at a time against the balances at
+    /// `accumulator_version`, which must be the last settled version.
+    fn decide(
+        &self,
+        state: &mut ReferenceState,
+        accumulator_version: SequenceNumber,
+        batch: Vec<TxBalanceWithdraw>,
+    ) {
+        let mut balances: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for withdraw in batch {
+            for account in withdraw.reservations.keys() {
+                balances.entry(*account).or_insert_with(|| {
+                    self.balance_read
+                        .get_account_balance(account, accumulator_version)
+                });
+            }
+
+            let sufficient = withdraw
+                .reservations
+                .iter()
+                .all(|(account, amount)| balances[account] >= *amount);
+            let status = if sufficient {
+                for (account, amount) in &withdraw.reservations {
+                    *balances.get_mut(account).unwrap() -= amount;
+                }
+                ScheduleStatus::SufficientBalance
+            } else {
+                ScheduleStatus::InsufficientBalance
+            };
+
+            state.record(Side::Reference, withdraw.tx_digest, status);
+        }
+    }
+}
+
+impl ReferenceState {
+    fn push_event(&mut self, event: Event) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(event);
+    }
+
+    fn record(&mut self, side: Side, tx_digest: TransactionDigest, status: ScheduleStatus) {
+        let Some((other_side, other_status)) = self.unmatched.remove(&tx_digest) else {
+            self.unmatched.insert(tx_digest, (side, status));
+            return;
+        };
+
+        debug_assert_ne!(side, other_side);
+        let (live, reference) = match side {
+            Side::Live => (status, other_status),
+            Side::Reference => (other_status, status),
+        };
+
+        if live == reference
+            || live == ScheduleStatus::AlreadyExecuted
+            || reference == ScheduleStatus::AlreadyExecuted
+        {
+            return;
+        }
+
+        fatal!(
+            "Balance withdraw scheduler diverged from the reference model for {:?}: live \
+             {:?}, reference {:?}. Reference state: {:#?}",
+            tx_digest,
+            live,
+            reference,
+            self
+        );
+    }
+}
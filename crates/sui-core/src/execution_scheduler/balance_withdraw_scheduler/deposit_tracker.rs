@@ -0,0 +1,67 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, ops::Range};
+
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+
+/// Tracks deposits into address balance accounts by executed transactions whose accumulator
+/// versions have not been settled yet.
+///
+/// A deposit by a transaction at accumulator version `v` is reflected in the settled balance at
+/// version `v + 1`, so every withdraw scheduled at a later version is guaranteed to see it. The
+/// withdraw scheduler uses these deposits as early credits when deciding whether a withdraw is
+/// guaranteed to have sufficient balance ahead of settlement, rather than having it wait for the
+/// settlement. Deposits must only be recorded once their amounts are final, i.e. from the effects
+/// of the executed transaction, because a transaction that aborts does not deposit anything.
+#[derive(Default)]
+pub(crate) struct BalanceDepositTracker {
+    /// Deposited amounts, keyed by the accumulator version of the depositing transaction, then
+    /// by transaction, then by account.
+    deposits: BTreeMap<SequenceNumber, BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>>,
+}
+
+impl BalanceDepositTracker {
+    /// Record the amounts deposited into each account by the transaction `tx_digest`, at
+    /// `accumulator_version`. Recording the same transaction again replaces its deposits.
+    pub(crate) fn record(
+        &mut self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    ) {
+        if deposits.is_empty() {
+            return;
+        }
+
+        self.deposits
+            .entry(accumulator_version)
+            .or_default()
+            .insert(tx_digest, deposits);
+    }
+
+    /// The total amount deposited into each account by transactions at accumulator versions in
+    /// `versions`.
+    pub(crate) fn credits(&self, versions: Range<SequenceNumber>) -> BTreeMap<ObjectID, u64> {
+        let mut credits: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for (account, amount) in self
+            .deposits
+            .range(versions)
+            .flat_map(|(_, txs)| txs.values())
+            .flatten()
+        {
+            let credit = credits.entry(*account).or_default();
+            *credit = credit.saturating_add(*amount);
+        }
+        credits
+    }
+
+    /// Forget deposits at accumulator versions before `settled_version`, which are now reflected
+    /// in the settled balances.
+    pub(crate) fn settle(&mut self, settled_version: SequenceNumber) {
+        self.deposits = self.deposits.split_off(&settled_version);
+    }
+}
@@ -5,15 +5,18 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use move_core_types::language_storage::TypeTag;
+use move_core_types::{identifier::Identifier, language_storage::TypeTag};
 use sui_protocol_config::ProtocolConfig;
 use sui_test_transaction_builder::TestTransactionBuilder;
 use sui_types::accumulator_root::{update_account_balance_for_testing, AccumulatorValue};
 use sui_types::balance::Balance;
 use sui_types::base_types::ObjectID;
 use sui_types::digests::TransactionDigest;
+use sui_types::effects::TransactionEffectsAPI;
 use sui_types::execution_params::BalanceWithdrawStatus;
-use sui_types::SUI_ACCUMULATOR_ROOT_OBJECT_ID;
+use sui_types::storage::SpendableBalance;
+use sui_types::transaction::{Argument, Command};
+use sui_types::transaction_executor::{TransactionScheduleStatus, TransactionScheduleUpdate};
 use sui_types::{
     base_types::{SequenceNumber, SuiAddress},
     crypto::{get_account_key_pair, AccountKeyPair},
@@ -23,8 +26,9 @@ use sui_types::{
     programmable_transaction_builder::ProgrammableTransactionBuilder,
     transaction::BalanceWithdrawArg,
 };
+use sui_types::{SUI_ACCUMULATOR_ROOT_OBJECT_ID, SUI_FRAMEWORK_PACKAGE_ID};
 use tokio::sync::mpsc::{self, unbounded_channel};
-use tokio::time::timeout;
+use tokio::time::{timeout, Instant};
 
 use crate::execution_scheduler::balance_withdraw_scheduler::BalanceSettlement;
 use crate::{
@@ -73,6 +77,7 @@ async fn create_test_env(init_balances: BTreeMap<TypeTag, u64>) -> TestEnv {
         state.get_transaction_cache_reader().clone(),
         tx_ready_certificates,
         &state.epoch_store_for_testing(),
+        None,
         state.metrics.clone(),
     ));
     TestEnv {
@@ -108,6 +113,40 @@ impl TestEnv {
             .collect()
     }
 
+    /// A transaction that deposits `amount` from the gas coin into the sender's balance.
+    fn create_deposit_transaction(&self, amount: u64) -> VerifiedExecutableTransaction {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let amount = ptb.pure(amount).unwrap();
+        let recipient = ptb.pure(self.sender).unwrap();
+        let Argument::Result(coin) =
+            ptb.command(Command::SplitCoins(Argument::GasCoin, vec![amount]))
+        else {
+            panic!("SplitCoins must return a result");
+        };
+        let balance = ptb.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("coin").unwrap(),
+            Identifier::new("into_balance").unwrap(),
+            vec![GAS::type_tag()],
+            vec![Argument::NestedResult(coin, 0)],
+        );
+        ptb.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("balance").unwrap(),
+            Identifier::new("send_to_account").unwrap(),
+            vec![GAS::type_tag()],
+            vec![balance, recipient],
+        );
+        let tx_data = TestTransactionBuilder::new(
+            self.sender,
+            self.gas_object.compute_object_reference(),
+            self.state.reference_gas_price_for_testing().unwrap(),
+        )
+        .programmable(ptb.finish())
+        .build();
+        VerifiedExecutableTransaction::new_for_testing(tx_data, &self.sender_key)
+    }
+
     fn get_accumulator_object(&self) -> Object {
         self.state
             .get_object_cache_reader()
@@ -165,10 +204,7 @@ impl TestEnv {
     fn settle_balances(&mut self, balance_changes: BTreeMap<ObjectID, i128>) {
         let mut accumulator_object = self.get_accumulator_object();
         let next_version = accumulator_object.version().next();
-        self.scheduler.settle_balances(BalanceSettlement {
-            balance_changes: balance_changes.clone(),
-        });
-        for (object_id, balance_change) in balance_changes {
+        for (object_id, balance_change) in balance_changes.clone() {
             let mut account_object = self
                 .state
                 .get_object_cache_reader()
@@ -192,6 +228,16 @@ impl TestEnv {
         self.state
             .get_cache_writer()
             .write_object_entry_for_test(accumulator_object);
+        // The settlement is only sent once its balance changes can be read, as the checkpoint
+        // builder does.
+        self.scheduler.settle_balances(
+            next_version,
+            BalanceSettlement {
+                balance_changes,
+                withdraws: BTreeMap::new(),
+            },
+            Instant::now(),
+        );
     }
 }
 
@@ -250,3 +296,214 @@ async fn test_withdraw_schedule_e2e() {
         )]))
         .await;
 }
+
+#[tokio::test]
+async fn test_invalid_withdraws_are_rejected() {
+    telemetry_subscribers::init_for_testing();
+    let mut test_env = create_test_env(BTreeMap::from([(GAS::type_tag(), 1000)])).await;
+
+    // A reservation of zero is never signed, but if one is certified anyway, it fails without
+    // reserving anything, rather than bringing the validator down, and the transactions
+    // scheduled with it are unaffected.
+    let transactions = test_env.create_transactions(vec![0, 1000]);
+    test_env.enqueue_transactions(transactions.clone());
+    test_env
+        .expect_withdraw_results(BTreeMap::from([
+            (
+                *transactions[0].digest(),
+                BalanceWithdrawStatus::InsufficientBalance,
+            ),
+            (
+                *transactions[1].digest(),
+                BalanceWithdrawStatus::SufficientBalance,
+            ),
+        ]))
+        .await;
+    assert_eq!(
+        test_env
+            .scheduler
+            .get_reserved_balance(&test_env.account_objects[0]),
+        1000
+    );
+}
+
+#[tokio::test]
+async fn test_schedule_updates() {
+    use TransactionScheduleStatus as S;
+
+    telemetry_subscribers::init_for_testing();
+    let mut test_env = create_test_env(BTreeMap::from([(GAS::type_tag(), 1000)])).await;
+    let mut updates = test_env.scheduler.subscribe_schedule_updates();
+    let mut next_update = move || updates.try_recv().ok();
+    let update = |tx: &VerifiedExecutableTransaction, status| {
+        Some(TransactionScheduleUpdate {
+            tx_digest: *tx.digest(),
+            status,
+        })
+    };
+
+    let transactions = test_env.create_transactions(vec![400, 601]);
+    test_env.enqueue_transactions(transactions.clone());
+    test_env
+        .expect_withdraw_results(BTreeMap::from([
+            (
+                *transactions[0].digest(),
+                BalanceWithdrawStatus::SufficientBalance,
+            ),
+            (
+                *transactions[1].digest(),
+                BalanceWithdrawStatus::InsufficientBalance,
+            ),
+        ]))
+        .await;
+
+    assert_eq!(
+        next_update(),
+        update(&transactions[0], S::BalanceReservationPending)
+    );
+    assert_eq!(
+        next_update(),
+        update(&transactions[1], S::BalanceReservationPending)
+    );
+    let mut results = vec![next_update(), next_update()];
+    results.sort_by_key(|u| u.map(|u| u.tx_digest == *transactions[1].digest()));
+    assert_eq!(
+        results,
+        vec![
+            update(&transactions[0], S::SufficientBalance),
+            update(&transactions[1], S::InsufficientBalance),
+        ]
+    );
+
+    // A withdraw at a version that has not been settled yet waits for its reservation until
+    // the settlement.
+    let transactions = test_env.create_transactions(vec![100]);
+    let next_version = test_env.get_accumulator_version().next();
+    test_env.enqueue_transactions_with_version(transactions.clone(), next_version);
+    assert!(test_env.receive_certificate().await.is_none());
+    assert_eq!(
+        next_update(),
+        update(&transactions[0], S::BalanceReservationPending)
+    );
+    assert_eq!(next_update(), None);
+
+    test_env.settle_balances(BTreeMap::from([(test_env.account_objects[0], -400)]));
+    test_env
+        .expect_withdraw_results(BTreeMap::from([(
+            *transactions[0].digest(),
+            BalanceWithdrawStatus::SufficientBalance,
+        )]))
+        .await;
+    assert_eq!(
+        next_update(),
+        update(&transactions[0], S::SufficientBalance)
+    );
+}
+
+#[tokio::test]
+async fn test_executed_deposits_are_credited_ahead_of_settlement() {
+    telemetry_subscribers::init_for_testing();
+    let test_env = create_test_env(BTreeMap::from([(GAS::type_tag(), 100)])).await;
+    let epoch_store = test_env.state.epoch_store_for_testing();
+    let scheduler = test_env.state.execution_scheduler();
+    let account = test_env.account_objects[0];
+    let version = test_env.get_accumulator_version();
+
+    // Execute a transaction at the current accumulator version that deposits into the sender's
+    // balance, through the same path as any other transaction assigned that version.
+    let deposit = test_env.create_deposit_transaction(500);
+    let mut env = ExecutionEnv::default();
+    env.assigned_versions.withdraw_type = WithdrawType::Withdraw(version);
+    let (effects, _) = test_env
+        .state
+        .try_execute_immediately(&deposit, env, &epoch_store)
+        .await
+        .unwrap();
+    assert!(effects.status().is_ok());
+
+    // A withdraw at the next version can count on the deposit before it is settled, although it
+    // reserves more than the settled balance. Without the deposit, it would wait for the
+    // settlement, and reserve nothing until then.
+    let withdraw = test_env.create_transactions(vec![150]);
+    let mut env = ExecutionEnv::default();
+    env.assigned_versions.withdraw_type = WithdrawType::Withdraw(version.next());
+    scheduler.enqueue(
+        withdraw
+            .into_iter()
+            .map(|tx| (Schedulable::Transaction(tx), env.clone()))
+            .collect(),
+        &epoch_store,
+    );
+
+    timeout(Duration::from_secs(3), async {
+        while scheduler.get_reserved_balance(&account) != 150 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_spendable_balance_includes_pending_deposits() {
+    telemetry_subscribers::init_for_testing();
+    let mut test_env = create_test_env(BTreeMap::from([(GAS::type_tag(), 100)])).await;
+    let epoch_store = test_env.state.epoch_store_for_testing();
+    let account = test_env.account_objects[0];
+    let version = test_env.get_accumulator_version();
+    let spendable_balance = |test_env: &TestEnv| {
+        test_env
+            .state
+            .get_spendable_balance(test_env.sender, GAS::type_tag())
+            .unwrap()
+    };
+    assert_eq!(
+        spendable_balance(&test_env),
+        SpendableBalance::new(100, 0, 0)
+    );
+
+    // A deposit that has executed, but has not been settled, is pending.
+    let deposit = test_env.create_deposit_transaction(500);
+    let mut env = ExecutionEnv::default();
+    env.assigned_versions.withdraw_type = WithdrawType::Withdraw(version);
+    let (effects, _) = test_env
+        .state
+        .try_execute_immediately(&deposit, env, &epoch_store)
+        .await
+        .unwrap();
+    assert!(effects.status().is_ok());
+    assert_eq!(
+        spendable_balance(&test_env),
+        SpendableBalance::new(100, 500, 0)
+    );
+
+    // A withdraw at the next version reserves from the settled balance and the pending deposit.
+    let withdraw = test_env.create_transactions(vec![150]);
+    test_env.enqueue_transactions_with_version(withdraw, version.next());
+    timeout(Duration::from_secs(3), async {
+        while spendable_balance(&test_env).reserved != 150 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        spendable_balance(&test_env),
+        SpendableBalance::new(100, 500, 150)
+    );
+
+    // Once the deposit is settled, it is part of the settled balance, read at the same version
+    // as the reservation, which stays until its own version is settled.
+    test_env.settle_balances(BTreeMap::from([(account, 500)]));
+    timeout(Duration::from_secs(3), async {
+        while spendable_balance(&test_env).pending_deposits != 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        spendable_balance(&test_env),
+        SpendableBalance::new(600, 0, 150)
+    );
+}
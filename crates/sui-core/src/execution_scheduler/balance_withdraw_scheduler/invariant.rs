@@ -0,0 +1,31 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Check an invariant of the balance withdraw scheduler, evaluating to whether it holds.
+///
+/// A violation is logged as a single structured event under the `withdraw_scheduler_invariant`
+/// target, carrying the name of the invariant and the context it was given (accounts, versions,
+/// amounts), so that violations can be audited from JSON logs. It is then reported through
+/// [`mysten_common::debug_fatal`], which panics in tests and debug configurations, and otherwise
+/// counts the violation in the `system_invariant_violations` metric, so that callers must be
+/// prepared to carry on when the invariant does not hold.
+macro_rules! check_invariant {
+    ($cond:expr, $name:literal $(, $field:ident = $value:expr)* $(,)?) => {{
+        let holds = $cond;
+        if !holds {
+            tracing::error!(
+                target: "withdraw_scheduler_invariant",
+                invariant = $name,
+                $($field = ?$value,)*
+                "Balance withdraw scheduler invariant violated",
+            );
+            mysten_common::debug_fatal!(
+                "Balance withdraw scheduler invariant violated: {}",
+                $name
+            );
+        }
+        holds
+    }};
+}
+
+pub(crate) use check_invariant;
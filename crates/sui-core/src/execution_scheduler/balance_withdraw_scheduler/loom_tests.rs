@@ -0,0 +1,175 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Model checks of how the balance withdraw scheduler's worker and the threads that amend
+//! reservations, record deposits, or query the scheduler, interleave. Run with
+//! `cargo test -p sui-core --features loom --lib loom_tests`.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use loom::thread;
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+
+use crate::execution_scheduler::balance_withdraw_scheduler::{
+    balance_read::MockBalanceRead, naive_scheduler::SchedulerState, policy::AllowAllWithdraws,
+    scheduler::WithdrawReservations, AmendReservationError, ScheduleStatus, TxBalanceWithdraw,
+    WithdrawSchedulerParams,
+};
+
+/// The scheduler's synchronization primitives, with the interfaces it uses, backed by loom's so
+/// that loom can explore how they interleave.
+pub(super) mod sync {
+    use std::sync::Arc;
+
+    pub(crate) use loom::sync::MutexGuard;
+
+    pub(crate) struct Mutex<T>(loom::sync::Mutex<T>);
+
+    impl<T> Mutex<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Self(loom::sync::Mutex::new(value))
+        }
+
+        pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+            self.0.lock().unwrap()
+        }
+    }
+
+    pub(crate) struct ArcSwap<T>(loom::sync::Mutex<Arc<T>>);
+
+    impl<T> ArcSwap<T> {
+        pub(crate) fn from_pointee(value: T) -> Self {
+            Self(loom::sync::Mutex::new(Arc::new(value)))
+        }
+
+        pub(crate) fn load(&self) -> Arc<T> {
+            self.0.lock().unwrap().clone()
+        }
+
+        pub(crate) fn store(&self, value: Arc<T>) {
+            *self.0.lock().unwrap() = value;
+        }
+    }
+}
+
+const V0: SequenceNumber = SequenceNumber::from_u64(0);
+const V1: SequenceNumber = SequenceNumber::from_u64(1);
+const V2: SequenceNumber = SequenceNumber::from_u64(2);
+
+fn account() -> ObjectID {
+    ObjectID::from_single_byte(1)
+}
+
+fn withdraw(tx: u8, amount: u64) -> TxBalanceWithdraw {
+    TxBalanceWithdraw {
+        tx_digest: TransactionDigest::new([tx; 32]),
+        reservations: BTreeMap::from([(account(), amount)]),
+    }
+}
+
+fn scheduler(balance_read: Arc<MockBalanceRead>) -> Arc<SchedulerState> {
+    Arc::new(SchedulerState::new(
+        balance_read,
+        V0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    ))
+}
+
+/// An amendment that reads the balance before a settlement, but takes the lock after it, must
+/// not be checked against the old balance with the new set of reservations.
+#[test]
+fn loom_amend_races_settlement() {
+    loom::model(|| {
+        let balance_read = Arc::new(MockBalanceRead::new(V0, BTreeMap::from([(account(), 100)])));
+        let state = scheduler(balance_read.clone());
+
+        let (settled, _) = WithdrawReservations::new(V0, vec![withdraw(0, 50)]);
+        state.schedule(settled);
+        let ahead = withdraw(1, 40);
+        let (reservations, _) = WithdrawReservations::new(V1, vec![ahead.clone()]);
+        state.schedule(reservations);
+        assert_eq!(state.reserved_balance(&account()), 90);
+
+        let settler = thread::spawn({
+            let state = state.clone();
+            move || {
+                balance_read.settle_balance_changes(BTreeMap::from([(account(), -50)]));
+                state.settle();
+            }
+        });
+
+        // Whether it is checked before or after the settlement, only 10 is left for the
+        // increase.
+        let result = state.amend(&ahead.tx_digest, BTreeMap::from([(account(), 60)]));
+        settler.join().unwrap();
+
+        assert!(
+            matches!(
+                result,
+                Err(AmendReservationError::InsufficientBalance { available: 10, .. })
+            ),
+            "{result:?}"
+        );
+        assert_eq!(state.reserved_balance(&account()), 40);
+    });
+}
+
+/// Whoever receives a withdraw's result sees its reservation.
+#[test]
+fn loom_results_follow_views() {
+    loom::model(|| {
+        let balance_read = Arc::new(MockBalanceRead::new(V0, BTreeMap::from([(account(), 100)])));
+        let state = scheduler(balance_read);
+
+        let (reservations, receivers) = WithdrawReservations::new(V0, vec![withdraw(0, 50)]);
+        let worker = thread::spawn({
+            let state = state.clone();
+            move || state.schedule(reservations)
+        });
+
+        for mut receiver in receivers {
+            if let Ok(result) = receiver.try_recv() {
+                assert_eq!(result.status, ScheduleStatus::SufficientBalance);
+                assert_eq!(state.reserved_balance(&account()), 50);
+            }
+        }
+        worker.join().unwrap();
+        assert_eq!(state.reserved_balance(&account()), 50);
+    });
+}
+
+/// Deposits recorded while an earlier version is settled are still credited to withdraws
+/// scheduled ahead of their own settlement.
+#[test]
+fn loom_deposits_race_settlement() {
+    loom::model(|| {
+        let balance_read = Arc::new(MockBalanceRead::new(V0, BTreeMap::from([(account(), 100)])));
+        let state = scheduler(balance_read.clone());
+
+        let depositor = thread::spawn({
+            let state = state.clone();
+            move || {
+                state.record_deposits(
+                    V1,
+                    TransactionDigest::new([0; 32]),
+                    BTreeMap::from([(account(), 30)]),
+                )
+            }
+        });
+
+        balance_read.settle_balance_changes(BTreeMap::new());
+        state.settle();
+        depositor.join().unwrap();
+
+        let (reservations, receivers) = WithdrawReservations::new(V2, vec![withdraw(1, 130)]);
+        state.schedule(reservations);
+        for mut receiver in receivers {
+            let result = receiver.try_recv().unwrap();
+            assert_eq!(result.status, ScheduleStatus::SufficientBalance);
+        }
+    });
+}
@@ -1,21 +1,49 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    time::Duration,
+};
 
-use sui_types::{base_types::ObjectID, digests::TransactionDigest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sui_config::node::ShadowWithdrawSchedulerConfig;
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+use thiserror::Error;
 
+use invariant::check_invariant;
+
+pub(crate) mod balance_cache;
 mod balance_read;
+#[cfg(feature = "balance-scheduler-bench")]
+pub mod bench;
+mod cross_check;
+mod deposit_tracker;
+mod invariant;
+#[cfg(all(test, feature = "loom"))]
+mod loom_tests;
 mod naive_scheduler;
+pub(crate) mod policy;
 pub(crate) mod scheduler;
+pub(crate) mod shadow;
 #[cfg(test)]
 mod tests;
+#[cfg(any(test, feature = "balance-scheduler-bench"))]
+pub(crate) mod workload;
 
 #[cfg(test)]
 mod e2e_tests;
 
 /// The status of scheduling the withdraw reservations for a transaction.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+///
+/// This appears in scheduler logs and admin endpoints, so its serialized form (snake_case variant
+/// names) must remain stable.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum ScheduleStatus {
     /// We know for sure that the withdraw reservations in this transactions all have enough balance.
     /// This transaction can be executed normally as soon as its object dependencies are ready.
@@ -28,6 +56,77 @@ pub(crate) enum ScheduleStatus {
     /// The caller should stop the scheduling of this transaction.
     /// This happens when the transaction can be executed through checkpoint executor.
     AlreadyExecuted,
+    /// The withdraw policy does not allow this transaction to withdraw from at least one of its
+    /// accounts, regardless of their balance. Like [`ScheduleStatus::InsufficientBalance`], this
+    /// transaction should result in an execution failure without actually executing it.
+    PolicyRejected,
+    /// The transaction reserves more in total across its accounts than a single transaction is
+    /// allowed to, regardless of their balances. Like [`ScheduleStatus::InsufficientBalance`],
+    /// this transaction should result in an execution failure without actually executing it.
+    Rejected,
+}
+
+impl ScheduleStatus {
+    /// The name of the status, as used in metric labels.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleStatus::SufficientBalance => "sufficient_balance",
+            ScheduleStatus::InsufficientBalance => "insufficient_balance",
+            ScheduleStatus::AlreadyExecuted => "already_executed",
+            ScheduleStatus::PolicyRejected => "policy_rejected",
+            ScheduleStatus::Rejected => "rejected",
+        }
+    }
+}
+
+/// Parameters of the withdraw scheduling policy. The live scheduler uses the defaults, apart from
+/// the per-transaction reservation limit and how late withdraws can be scheduled, which come from
+/// the protocol config. Other values are only used by the shadow scheduler, to evaluate them on
+/// live traffic.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct WithdrawSchedulerParams {
+    /// Whether withdraws are scheduled ahead of settlement when their accounts are guaranteed
+    /// to have sufficient balance, rather than always waiting for their accumulator version to
+    /// be settled.
+    pub schedule_ahead_of_settlement: bool,
+    /// The largest amount a transaction can reserve from a single account. Withdraws that
+    /// reserve more are rejected as having insufficient balance.
+    pub max_reservation_per_account: Option<u64>,
+    /// The largest amount a transaction can reserve in total across all of its accounts.
+    /// Withdraws that reserve more are [`ScheduleStatus::Rejected`].
+    pub max_reservation_per_transaction: Option<u64>,
+    /// How many of the most recently settled accumulator versions withdraws can still be
+    /// scheduled at, against the exact balances at those versions, rather than being treated as
+    /// already executed. Zero disables scheduling late withdraws. Must not exceed the number of
+    /// versions the balance reader retains.
+    ///
+    /// Every batch at version `v` is checked on its own against the balance at `v`, less what
+    /// earlier withdraws in the same batch reserved, whether it arrives on time or late. A late
+    /// batch therefore gets the decisions it would have got on time, and validators reach the
+    /// same decisions whenever a batch arrives, as long as `v` is still in the window.
+    pub late_withdraw_versions: u64,
+}
+
+impl Default for WithdrawSchedulerParams {
+    fn default() -> Self {
+        Self {
+            schedule_ahead_of_settlement: true,
+            max_reservation_per_account: None,
+            max_reservation_per_transaction: None,
+            late_withdraw_versions: 0,
+        }
+    }
+}
+
+impl From<&ShadowWithdrawSchedulerConfig> for WithdrawSchedulerParams {
+    fn from(config: &ShadowWithdrawSchedulerConfig) -> Self {
+        Self {
+            schedule_ahead_of_settlement: config.schedule_ahead_of_settlement(),
+            max_reservation_per_account: config.max_reservation_per_account,
+            max_reservation_per_transaction: None,
+            late_withdraw_versions: 0,
+        }
+    }
 }
 
 /// The result of scheduling the withdraw reservations for a transaction.
@@ -35,21 +134,357 @@ pub(crate) enum ScheduleStatus {
 pub(crate) struct ScheduleResult {
     pub tx_digest: TransactionDigest,
     pub status: ScheduleStatus,
+    /// The accounts that fell short of the transaction's reservations, if its status is
+    /// [`ScheduleStatus::InsufficientBalance`]. Empty otherwise.
+    pub details: Vec<AccountShortfall>,
+}
+
+/// An account that could not cover a transaction's withdraw reservation from it.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AccountShortfall {
+    pub account: ObjectID,
+    /// The amount the transaction reserved from the account.
+    pub requested: u64,
+    /// The most the transaction could have reserved from the account: its remaining balance,
+    /// or the per-account reservation cap if that is lower.
+    pub available: u64,
+}
+
+impl AccountShortfall {
+    /// How much more the account would need to cover the reservation.
+    pub fn shortfall(&self) -> u64 {
+        self.requested.saturating_sub(self.available)
+    }
+}
+
+/// A withdraw that has been waiting for its accumulator version to be settled while at least a
+/// given number of other versions were settled, as reported by the scheduler's settlement wait
+/// watchdog.
+///
+/// This is the only way a withdraw can be held up: the scheduler never queues a withdraw behind
+/// another one that lacks balance, it decides every withdraw at a version as soon as that version
+/// is settled, and rejects the ones that do not fit. A long wait therefore means that settlement
+/// is lagging behind the versions that withdraws are scheduled at.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct WithdrawAwaitingSettlement {
+    pub tx_digest: TransactionDigest,
+    /// The accumulator version the withdraw is waiting for.
+    pub accumulator_version: SequenceNumber,
+    /// The number of versions that have been settled since the withdraw started waiting.
+    pub settlements_waited: u64,
+    /// The accounts that were not guaranteed to cover the withdraw's reservations when it was
+    /// scheduled, which is why it is waiting. Empty if the withdraw was never considered for
+    /// scheduling ahead of settlement.
+    pub blocking_accounts: Vec<AccountShortfall>,
+}
+
+/// What the scheduler knows about an account's address balance beyond its settled balance, as of
+/// the last accumulator version it settled.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub(crate) struct AccountReservations {
+    /// The last settled accumulator version, which the amounts below are relative to.
+    pub settled_version: SequenceNumber,
+    /// The amount reserved from the account by withdraws that have been scheduled, but whose
+    /// accumulator versions have not been settled yet.
+    pub reserved: u64,
+    /// The amount deposited into the account by transactions that have executed, but whose
+    /// accumulator versions have not been settled yet, i.e. whose checkpoints' settlements have
+    /// not executed. Only the deposits of transactions that know their accumulator version, i.e.
+    /// that also withdraw, are tracked, as those are the ones credited to later withdraws.
+    pub pending_deposits: u64,
 }
 
 /// Details regarding a balance settlement, generated when a settlement transaction has been executed
 /// and committed to the writeback cache.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BalanceSettlement {
-    /// The balance changes for each account object ID.
-    /// This is currently unused because the naive scheduler
-    /// always load the latest balance during scheduling.
-    #[allow(unused)]
+    /// The balance changes for each account object ID. The naive scheduler loads the latest
+    /// balances during scheduling instead, so these are only used for reporting and to
+    /// invalidate cached balances.
     pub balance_changes: BTreeMap<ObjectID, i128>,
+    /// The amount each settled transaction actually withdrew from each account, according to
+    /// its effects.
+    pub withdraws: BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>,
+}
+
+/// Progress of draining the withdraw scheduler before a planned restart. While draining, new
+/// batches of withdraws are held back, in order, instead of being scheduled, so that the
+/// reservations already made can settle. Reservations only live in memory, so the node can be
+/// restarted without losing any once the scheduler is drained. Held batches are scheduled as
+/// usual after the restart, or once draining stops.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct WithdrawDrainStatus {
+    /// Whether new batches of withdraws are being held back.
+    pub draining: bool,
+    /// The number of batches of withdraws waiting to be scheduled.
+    pub unscheduled_batches: usize,
+    /// Whether the scheduler is draining and no withdraw is reserved, being scheduled, or waiting
+    /// for its accumulator version to settle.
+    pub drained: bool,
+}
+
+/// Reconciles the amount a transaction reserved from an account when its withdraws were
+/// scheduled with the amount it actually withdrew, once its accumulator version is settled.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SettlementReceipt {
+    pub tx_digest: TransactionDigest,
+    pub account: ObjectID,
+    /// Zero if the transaction's withdraws were not scheduled on this node, e.g. because it
+    /// was executed through the checkpoint executor.
+    pub reserved: u64,
+    /// Zero if the transaction did not withdraw from the account, e.g. because it aborted.
+    pub actually_withdrawn: u64,
+}
+
+impl SettlementReceipt {
+    /// Produce a receipt for every (transaction, account) pair that either reserved or
+    /// withdrew balance.
+    pub(crate) fn reconcile(
+        reserved: BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>,
+        mut withdrawn: BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>,
+    ) -> Vec<Self> {
+        let mut receipts = vec![];
+        for (tx_digest, reservations) in reserved {
+            let mut tx_withdrawn = withdrawn.remove(&tx_digest).unwrap_or_default();
+            for (account, reserved) in reservations {
+                let actually_withdrawn = tx_withdrawn.remove(&account).unwrap_or_default();
+                check_invariant!(
+                    actually_withdrawn <= reserved,
+                    "withdrawn_within_reservation",
+                    tx_digest = tx_digest,
+                    account = account,
+                    reserved = reserved,
+                    actually_withdrawn = actually_withdrawn,
+                );
+                receipts.push(Self {
+                    tx_digest,
+                    account,
+                    reserved,
+                    actually_withdrawn,
+                });
+            }
+            receipts.extend(
+                tx_withdrawn
+                    .into_iter()
+                    .map(|(account, actually_withdrawn)| {
+                        // A transaction whose withdraws were scheduled on this node can only withdraw
+                        // from the accounts it reserved from.
+                        check_invariant!(
+                            actually_withdrawn == 0,
+                            "withdrawn_from_reserved_account",
+                            tx_digest = tx_digest,
+                            account = account,
+                            actually_withdrawn = actually_withdrawn,
+                        );
+                        Self {
+                            tx_digest,
+                            account,
+                            reserved: 0,
+                            actually_withdrawn,
+                        }
+                    }),
+            );
+        }
+
+        for (tx_digest, tx_withdrawn) in withdrawn {
+            receipts.extend(
+                tx_withdrawn
+                    .into_iter()
+                    .map(|(account, actually_withdrawn)| Self {
+                        tx_digest,
+                        account,
+                        reserved: 0,
+                        actually_withdrawn,
+                    }),
+            );
+        }
+
+        receipts
+    }
+}
+
+/// What settling an accumulator version did, logged once per version.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct SettlementSummary {
+    /// The version whose balances are now settled.
+    pub accumulator_version: SequenceNumber,
+    /// Accounts whose balance changed in the settlement.
+    pub accounts_settled: usize,
+    /// Total balance added to, and removed from, accounts by the settlement.
+    pub total_credited: u128,
+    pub total_debited: u128,
+    /// Withdraws that were waiting for the version to be settled, and were scheduled once it
+    /// was, by outcome.
+    pub sufficient: usize,
+    pub insufficient: usize,
+    /// Transactions whose reservations were released by the settlement.
+    pub released: usize,
+    /// After the settlement: transactions with reservations that have not settled yet,
+    /// transactions waiting for their version to be settled, and the batches they belong to.
+    pub reserved_txs: usize,
+    pub pending_txs: usize,
+    pub deferred_batches: usize,
+    /// Withdraws at this version (or earlier versions, if they arrived after their version was
+    /// settled) that were decided on since the previous settlement, whether they were scheduled
+    /// ahead of settlement, after it, or rejected for exceeding the reservation cap; of those,
+    /// the ones rejected for insufficient balance; and the average time from each of them being
+    /// sent to the scheduler to deciding on it, like [SettlementMetrics::reservation_wait].
+    ///
+    /// [SettlementMetrics::reservation_wait]: scheduler::SettlementMetrics::reservation_wait
+    pub scheduled_withdraws: usize,
+    pub rejected_withdraws: usize,
+    pub avg_reservation_wait: Duration,
+}
+
+impl SettlementSummary {
+    /// Fill in the balance changes from `settlement`.
+    pub(crate) fn with_balance_changes(mut self, settlement: &BalanceSettlement) -> Self {
+        self.accounts_settled = settlement.balance_changes.len();
+        for change in settlement.balance_changes.values() {
+            if *change >= 0 {
+                self.total_credited += change.unsigned_abs();
+            } else {
+                self.total_debited += change.unsigned_abs();
+            }
+        }
+        self
+    }
+
+    /// Log the summary as a single structured event under the `withdraw_scheduler_settlement`
+    /// target, so that the scheduler's behaviour can be tracked per version from JSON logs.
+    pub(crate) fn log(&self) {
+        tracing::info!(
+            target: "withdraw_scheduler_settlement",
+            accumulator_version = self.accumulator_version.value(),
+            accounts_settled = self.accounts_settled,
+            total_credited = self.total_credited,
+            total_debited = self.total_debited,
+            sufficient = self.sufficient,
+            insufficient = self.insufficient,
+            released = self.released,
+            reserved_txs = self.reserved_txs,
+            pending_txs = self.pending_txs,
+            deferred_batches = self.deferred_batches,
+            scheduled_withdraws = self.scheduled_withdraws,
+            rejected_withdraws = self.rejected_withdraws,
+            avg_reservation_wait_ms = self.avg_reservation_wait.as_millis() as u64,
+            "Settled balances",
+        );
+    }
 }
 
 /// Details regarding all balance withdraw reservations in a transaction.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub(crate) struct TxBalanceWithdraw {
     pub tx_digest: TransactionDigest,
     pub reservations: BTreeMap<ObjectID, u64>,
 }
+
+/// Reasons a set of withdraw reservations is rejected by [`TxBalanceWithdraw::new_checked`].
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub(crate) enum TxBalanceWithdrawError {
+    #[error("Transaction {tx_digest} has no balance withdraw reservations")]
+    NoReservations { tx_digest: TransactionDigest },
+
+    #[error("Balance withdraw reservation for account {account_id} must be non-zero")]
+    ZeroReservation { account_id: ObjectID },
+
+    #[error("Balance withdraw reservations for account {account_id} overflow")]
+    ReservationOverflow { account_id: ObjectID },
+
+    #[error(
+        "Transaction withdraws from {num_accounts} accounts, at most {max_accounts} are allowed"
+    )]
+    TooManyAccounts {
+        num_accounts: usize,
+        max_accounts: usize,
+    },
+}
+
+/// Reasons an amendment to the reservations of a transaction is rejected by the scheduler. A
+/// rejected amendment leaves the transaction's reservations unchanged.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub(crate) enum AmendReservationError {
+    #[error(
+        "Transaction {tx_digest} has no reservations to amend: it was not scheduled with \
+         sufficient balance, or its accumulator version has been settled"
+    )]
+    NotReserved { tx_digest: TransactionDigest },
+
+    #[error("Reservation of {amount} from account {account_id} exceeds the cap of {cap}")]
+    ExceedsCap {
+        account_id: ObjectID,
+        amount: u64,
+        cap: u64,
+    },
+
+    #[error("Reservations totalling {total} exceed the per-transaction limit of {limit}")]
+    ExceedsTransactionLimit { total: u64, limit: u64 },
+
+    #[error(
+        "Cannot increase the reservation from account {account_id} by {increase}: only \
+         {available} is guaranteed to be available"
+    )]
+    InsufficientBalance {
+        account_id: ObjectID,
+        increase: u64,
+        available: u64,
+    },
+}
+
+impl TxBalanceWithdraw {
+    /// Builds the withdraw reservations of a transaction, merging reservations against the
+    /// same account and rejecting them if any amount is zero, if the merged amount for an
+    /// account overflows, or if they touch more than `max_accounts` distinct accounts.
+    pub(crate) fn new_checked(
+        tx_digest: TransactionDigest,
+        reservations: impl IntoIterator<Item = (ObjectID, u64)>,
+        max_accounts: usize,
+    ) -> Result<Self, TxBalanceWithdrawError> {
+        let mut merged = BTreeMap::new();
+        for (account_id, amount) in reservations {
+            if amount == 0 {
+                return Err(TxBalanceWithdrawError::ZeroReservation { account_id });
+            }
+            match merged.entry(account_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(amount);
+                }
+                Entry::Occupied(mut entry) => {
+                    let total = entry
+                        .get()
+                        .checked_add(amount)
+                        .ok_or(TxBalanceWithdrawError::ReservationOverflow { account_id })?;
+                    entry.insert(total);
+                }
+            }
+        }
+
+        if merged.is_empty() {
+            return Err(TxBalanceWithdrawError::NoReservations { tx_digest });
+        }
+        if merged.len() > max_accounts {
+            return Err(TxBalanceWithdrawError::TooManyAccounts {
+                num_accounts: merged.len(),
+                max_accounts,
+            });
+        }
+
+        Ok(Self {
+            tx_digest,
+            reservations: merged,
+        })
+    }
+    /// The total amount the transaction reserves across all of its accounts, saturating at
+    /// `u64::MAX`.
+    pub(crate) fn total_reservation(&self) -> u64 {
+        total_reservation(&self.reservations)
+    }
+}
+
+/// The total of `reservations`, saturating at `u64::MAX`.
+fn total_reservation(reservations: &BTreeMap<ObjectID, u64>) -> u64 {
+    reservations
+        .values()
+        .fold(0u64, |total, amount| total.saturating_add(*amount))
+}
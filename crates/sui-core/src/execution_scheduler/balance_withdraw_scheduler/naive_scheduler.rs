@@ -1,123 +1,1451 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use sui_types::base_types::SequenceNumber;
-use tokio::sync::watch;
+#[cfg(not(all(test, feature = "loom")))]
+use arc_swap::ArcSwap;
+use mysten_common::{debug_fatal, fatal};
+use mysten_metrics::monitored_mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+#[cfg(not(all(test, feature = "loom")))]
+use parking_lot::{Mutex, MutexGuard};
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+use tokio::sync::{oneshot, watch};
 use tracing::debug;
 
+#[cfg(all(test, feature = "loom"))]
+use crate::execution_scheduler::balance_withdraw_scheduler::loom_tests::sync::{
+    ArcSwap, Mutex, MutexGuard,
+};
 use crate::execution_scheduler::balance_withdraw_scheduler::{
     balance_read::AccountBalanceRead,
+    deposit_tracker::BalanceDepositTracker,
+    invariant::check_invariant,
+    policy::WithdrawPolicy,
     scheduler::{BalanceWithdrawSchedulerTrait, WithdrawReservations},
-    BalanceSettlement, ScheduleResult, ScheduleStatus,
+    total_reservation, AccountReservations, AccountShortfall, AmendReservationError,
+    BalanceSettlement, ScheduleResult, ScheduleStatus, SettlementReceipt, SettlementSummary,
+    TxBalanceWithdraw, WithdrawAwaitingSettlement, WithdrawSchedulerParams,
 };
 
-/// A naive implementation of the balance withdraw scheduler that does not attempt to optimize the scheduling.
-/// For each withdraw reservation, it will always wait until the dependent accumulator object is available,
-/// and then check if the balance is sufficient.
-/// This implementation is simple and easy to understand, but it is not efficient.
-/// It is only used to unblock further development of the balance withdraw scheduler.
+type TxReservations = BTreeMap<TransactionDigest, BTreeMap<ObjectID, u64>>;
+
+/// The withdraws of a batch, in order. Withdraws that have already been scheduled no longer
+/// carry a sender.
+type WithdrawBatch = Vec<(TxBalanceWithdraw, Option<oneshot::Sender<ScheduleResult>>)>;
+
+/// A simple implementation of the balance withdraw scheduler, that checks each withdraw
+/// reservation against the balance read from storage at its accumulator version.
+///
+/// Withdraws do not always have to wait for their accumulator version to be settled:
+/// a withdraw is scheduled as soon as its accounts are guaranteed to have sufficient balance
+/// regardless of how the unsettled versions before it settle. This lets withdraws from
+/// accounts that are not contended make progress ahead of settlements. Every other withdraw
+/// waits until its accumulator version is settled, and is then checked against the exact
+/// balance. Either way, the result is the same as if every withdraw had waited.
+///
+/// That relies on batches arriving in the order of their accumulator versions: a withdraw is only
+/// scheduled ahead if it is guaranteed to have sufficient balance after every withdraw at an
+/// earlier version, and the scheduler can only account for the withdraws it has received. A
+/// batch that arrives after a batch at a later version is never scheduled ahead, and always waits
+/// for its version to be settled instead. If withdraws at later versions were already scheduled
+/// ahead from the same accounts, they may have been decided differently than they would have
+/// been had the batches arrived in order, which is reported as an invariant violation.
+///
+/// Scheduling and settling read balances from storage for every account involved, so they are
+/// done one at a time, in the order they were requested, by a worker task that runs them off the
+/// async runtime's threads. Callers only wait for the worker, and never block a runtime thread
+/// themselves, however large the batch.
+///
+/// Balances are never read from storage while the lock on the reservations is held, so the
+/// methods that are called from other threads (e.g. to amend a reservation, or record deposits)
+/// only ever wait for in-memory bookkeeping. Queries about the reservations, which RPC requests
+/// make, do not take the lock at all: they read a snapshot that is published whenever the
+/// reservations change.
 pub(crate) struct NaiveBalanceWithdrawScheduler {
+    state: Arc<SchedulerState>,
+    commands: UnboundedSender<Command>,
+}
+
+pub(super) struct SchedulerState {
     balance_read: Arc<dyn AccountBalanceRead>,
+    params: WithdrawSchedulerParams,
+    policy: Arc<dyn WithdrawPolicy>,
     last_settled_version_sender: watch::Sender<SequenceNumber>,
     // We must keep a receiver alive to make sure sends go through and can update the last settled version.
     last_settled_version_receiver: watch::Receiver<SequenceNumber>,
+    /// Only updated together with the last settled version, so that the two are always
+    /// consistent with each other while the lock is held.
+    reservations: Mutex<Reservations>,
+    /// A snapshot of the reservations, for queries that should not wait for the lock on them.
+    views: ArcSwap<ReservationViews>,
+}
+
+/// What queries need to know about the reservations, as of the last time they changed.
+struct ReservationViews {
+    /// The last settled version, as of the snapshot.
+    settled_version: SequenceNumber,
+    /// The total amount reserved from each account at versions that are not settled yet.
+    reserved: BTreeMap<ObjectID, u64>,
+    /// The total amount deposited into each account at versions that are not settled yet.
+    deposits: BTreeMap<ObjectID, u64>,
+    /// Whether no withdraw is reserved, or waiting for its accumulator version to be settled.
+    idle: bool,
+    /// Every withdraw waiting for its accumulator version to be settled, in the order of their
+    /// versions.
+    waiting: Vec<WithdrawAwaitingSettlement>,
+}
+
+/// Work for the worker task, each with a channel to signal its completion on.
+enum Command {
+    /// Schedule a batch of withdraws. Completes once each withdraw has either been sent its
+    /// result, or been deferred until its accumulator version is settled.
+    Schedule(WithdrawReservations, oneshot::Sender<()>),
+    /// Settle the next accumulator version. Completes with the reservations it released, by
+    /// version, and a summary of the settlement.
+    Settle(oneshot::Sender<(BTreeMap<SequenceNumber, TxReservations>, SettlementSummary)>),
+}
+
+/// A withdraw waiting for its accumulator version to be settled.
+struct WaitingWithdraw {
+    accumulator_version: SequenceNumber,
+    /// The last settled version when the withdraw was scheduled.
+    since: SequenceNumber,
+    /// The accounts that were not guaranteed to cover the withdraw's reservations.
+    blocking_accounts: Vec<AccountShortfall>,
+    /// When the withdraw was sent to the scheduler.
+    sent_at: Instant,
+}
+
+/// The decisions made about withdraws at an accumulator version, that have not been reported in
+/// a settlement summary yet.
+#[derive(Default)]
+struct Decisions {
+    /// Withdraws that were decided to have sufficient or insufficient balance.
+    scheduled: usize,
+    /// Of those, the withdraws that had insufficient balance.
+    insufficient: usize,
+    /// Total time from each withdraw being sent to the scheduler to deciding on it.
+    total_wait: Duration,
+}
+
+/// How many withdraws in a batch were scheduled with sufficient, or insufficient balance.
+#[derive(Default)]
+struct ScheduleOutcomes {
+    sufficient: usize,
+    insufficient: usize,
+}
+
+#[derive(Default)]
+struct Reservations {
+    /// Amounts reserved by withdraws that were scheduled as having sufficient balance,
+    /// keyed by the accumulator version they were scheduled at, and then by transaction.
+    /// Entries are dropped once their accumulator version is settled.
+    reserved: BTreeMap<SequenceNumber, TxReservations>,
+    /// Amounts requested by withdraws that are waiting for their accumulator version to be
+    /// settled before they can be scheduled, keyed the same way as `reserved`.
+    pending: BTreeMap<SequenceNumber, TxReservations>,
+    /// The batches that those withdraws belong to, in the order they were scheduled, keyed by
+    /// their accumulator version.
+    deferred: BTreeMap<SequenceNumber, Vec<WithdrawBatch>>,
+    /// When each pending withdraw started waiting, and why, keyed by transaction.
+    waiting: BTreeMap<TransactionDigest, WaitingWithdraw>,
+    /// Deposits by executed transactions at versions that have not been settled yet.
+    deposits: BalanceDepositTracker,
+    /// Decisions that have not been reported yet, keyed by the accumulator version of the
+    /// withdraws they were about.
+    decisions: BTreeMap<SequenceNumber, Decisions>,
+    /// Amounts reserved at the most recently settled versions, kept after they are released so
+    /// that late withdraws by transactions that were already scheduled at those versions are
+    /// treated as executed, keyed the same way as `reserved`. Only kept if late withdraws are
+    /// scheduled.
+    released: BTreeMap<SequenceNumber, TxReservations>,
+    /// The highest accumulator version of any batch received so far.
+    highest_version: SequenceNumber,
+    /// Results decided while the lock was held, that are sent once the decisions are reflected
+    /// in the published views, see [SchedulerState::publish].
+    results: Vec<(oneshot::Sender<ScheduleResult>, ScheduleResult)>,
 }
 
 impl NaiveBalanceWithdrawScheduler {
     pub fn new(
         balance_read: Arc<dyn AccountBalanceRead>,
         last_settled_accumulator_version: SequenceNumber,
+        params: WithdrawSchedulerParams,
+        policy: Arc<dyn WithdrawPolicy>,
     ) -> Arc<Self> {
+        let state = Arc::new(SchedulerState::new(
+            balance_read,
+            last_settled_accumulator_version,
+            params,
+            policy,
+        ));
+
+        let (commands, receiver) = unbounded_channel("withdraw_scheduler_commands");
+        tokio::spawn(Self::run_worker(state.clone(), receiver));
+        Arc::new(Self { state, commands })
+    }
+
+    /// Run commands one at a time until the scheduler is dropped.
+    ///
+    /// A command that does not run to completion leaves the reservations in a state that other
+    /// validators' schedulers do not share, so every decision after it could diverge from
+    /// theirs. Rather than carry on, or stop scheduling silently, that is treated as fatal.
+    async fn run_worker(state: Arc<SchedulerState>, mut commands: UnboundedReceiver<Command>) {
+        while let Some(command) = commands.recv().await {
+            let state = state.clone();
+            match tokio::task::spawn_blocking(move || state.run(command)).await {
+                Ok(()) => {}
+                Err(e) if e.is_panic() => {
+                    fatal!("Balance withdraw scheduler command panicked: {:?}", e);
+                }
+                Err(e) => {
+                    debug_fatal!("Balance withdraw scheduler command was cancelled: {:?}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Send `command` to the worker, and wait for it to complete. Returns `None` if the worker
+    /// has stopped.
+    async fn send<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> Command) -> Option<T> {
+        let (sender, receiver) = oneshot::channel();
+        self.commands.send(command(sender)).ok()?;
+        receiver.await.ok()
+    }
+}
+
+impl SchedulerState {
+    pub(super) fn new(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        last_settled_accumulator_version: SequenceNumber,
+        params: WithdrawSchedulerParams,
+        policy: Arc<dyn WithdrawPolicy>,
+    ) -> Self {
         let (last_settled_version_sender, last_settled_version_receiver) =
             watch::channel(last_settled_accumulator_version);
-        Arc::new(Self {
+        let reservations = Reservations::default();
+        let views = ArcSwap::from_pointee(reservations.views(last_settled_accumulator_version));
+        Self {
             balance_read,
+            params,
+            policy,
             last_settled_version_sender,
             last_settled_version_receiver,
-        })
+            reservations: Mutex::new(reservations),
+            views,
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl BalanceWithdrawSchedulerTrait for NaiveBalanceWithdrawScheduler {
-    async fn schedule_withdraws(&self, withdraws: WithdrawReservations) {
-        let mut receiver = self.last_settled_version_sender.subscribe();
-        while *receiver.borrow_and_update() < withdraws.accumulator_version {
-            debug!(
-                "Waiting for accumulator version {:?} to be settled",
-                withdraws.accumulator_version
-            );
-            if receiver.changed().await.is_err() {
-                return;
+    fn run(&self, command: Command) {
+        match command {
+            Command::Schedule(withdraws, done) => {
+                self.schedule(withdraws);
+                let _ = done.send(());
+            }
+            Command::Settle(done) => {
+                let _ = done.send(self.settle());
             }
         }
-        if *receiver.borrow() > withdraws.accumulator_version {
+    }
+
+    pub(super) fn schedule(&self, withdraws: WithdrawReservations) {
+        let WithdrawReservations {
+            accumulator_version,
+            withdraws,
+            senders,
+            sent_at,
+        } = withdraws;
+        let sent_at = sent_at.into_std();
+
+        // Versions are only settled by the worker, which is running this command, so the last
+        // settled version does not change while the batch is scheduled.
+        let last_settled_version = *self.last_settled_version_receiver.borrow();
+        // Like the settled version, what was released, and the highest version received, are
+        // only changed by the worker.
+        let (late, in_order) = {
+            let mut reservations = self.reservations.lock();
+            let in_order = accumulator_version >= reservations.highest_version;
+            reservations.highest_version = reservations.highest_version.max(accumulator_version);
+            (
+                reservations.released.contains_key(&accumulator_version),
+                in_order,
+            )
+        };
+
+        if last_settled_version > accumulator_version {
+            if late {
+                self.schedule_late(accumulator_version, withdraws, senders, sent_at);
+                return;
+            }
+
             debug!(
                 "Accumulator version {:?} is already settled",
-                withdraws.accumulator_version
+                accumulator_version
             );
-            for (withdraw, sender) in withdraws.withdraws.into_iter().zip(withdraws.senders) {
+            for (withdraw, sender) in withdraws.into_iter().zip(senders) {
                 let _ = sender.send(ScheduleResult {
                     tx_digest: withdraw.tx_digest,
                     status: ScheduleStatus::AlreadyExecuted,
+                    details: vec![],
                 });
             }
             return;
         }
 
-        // Map from each account ID that we have seen so far to the current
-        // remaining balance for reservation.
-        let mut cur_balances = BTreeMap::new();
-        for (withdraw, sender) in withdraws.withdraws.into_iter().zip(withdraws.senders) {
+        let (withdraws, senders, over_limits) =
+            self.reject(accumulator_version, withdraws, senders);
+
+        // Scheduling ahead assumes every withdraw at an earlier version has been received, which
+        // a batch that arrives after one at a later version shows is not the case.
+        let ahead = self.params.schedule_ahead_of_settlement && in_order;
+        let balances = if last_settled_version == accumulator_version || ahead {
+            self.read_balances(
+                withdraws.iter().flat_map(|w| w.reservations.keys()),
+                last_settled_version,
+            )
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut reservations = self.reservations.lock();
+        for _ in 0..over_limits {
+            reservations.record_decision(accumulator_version, false, sent_at);
+        }
+
+        if withdraws.is_empty() {
+            return;
+        }
+
+        if !in_order {
+            debug!(
+                "Withdraws at accumulator version {:?} arrived after withdraws at a later version",
+                accumulator_version
+            );
+            let accounts: BTreeSet<_> = withdraws
+                .iter()
+                .flat_map(|w| w.reservations.keys())
+                .collect();
+            check_invariant!(
+                !reservations.reserved_after(accumulator_version, &accounts),
+                "batches_arrive_in_version_order",
+                accumulator_version = accumulator_version,
+                highest_version = reservations.highest_version,
+            );
+        }
+
+        if last_settled_version == accumulator_version {
+            let batch = withdraws.into_iter().zip(senders.into_iter().map(Some));
+            reservations.schedule_settled(balances, accumulator_version, batch.collect(), sent_at);
+            self.publish(reservations);
+            return;
+        }
+
+        let batch = if ahead {
+            reservations.schedule_ahead(
+                &balances,
+                last_settled_version,
+                accumulator_version,
+                withdraws,
+                senders,
+                sent_at,
+            )
+        } else {
+            reservations.defer(
+                last_settled_version,
+                accumulator_version,
+                withdraws,
+                senders,
+                sent_at,
+            )
+        };
+        if batch.iter().any(|(_, sender)| sender.is_some()) {
+            // The rest of the batch is scheduled once its version is settled, so that later
+            // batches can still be scheduled ahead of it.
+            debug!(
+                "Waiting for accumulator version {:?} to be settled",
+                accumulator_version
+            );
+            reservations
+                .deferred
+                .entry(accumulator_version)
+                .or_default()
+                .push(batch);
+        }
+        self.publish(reservations);
+    }
+
+    /// Schedule `withdraws` at `accumulator_version`, which has already been settled, but whose
+    /// reservations are still known, see [Reservations::schedule_late].
+    fn schedule_late(
+        &self,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+        sent_at: Instant,
+    ) {
+        debug!(
+            "Accumulator version {:?} is already settled, scheduling late",
+            accumulator_version
+        );
+        let (withdraws, senders, over_limits) =
+            self.reject(accumulator_version, withdraws, senders);
+
+        let accounts: BTreeSet<_> = withdraws
+            .iter()
+            .flat_map(|w| w.reservations.keys())
+            .collect();
+        let balances = accounts
+            .into_iter()
+            .map(|account_id| {
+                let balance = self
+                    .balance_read
+                    .get_account_balance_at(account_id, accumulator_version);
+                (*account_id, balance)
+            })
+            .collect();
+
+        let mut reservations = self.reservations.lock();
+        for _ in 0..over_limits {
+            reservations.record_decision(accumulator_version, false, sent_at);
+        }
+        reservations.schedule_late(&balances, accumulator_version, withdraws, senders, sent_at);
+        self.publish(reservations);
+    }
+
+    /// Settle the next accumulator version, and schedule the batches that were waiting for it.
+    /// Returns the reservations released by the settlement, and a summary of the settlement,
+    /// without its balance changes.
+    pub(super) fn settle(&self) -> (BTreeMap<SequenceNumber, TxReservations>, SettlementSummary) {
+        let next_version = self
+            .last_settled_version_receiver
+            .borrow()
+            .saturating_next();
+        debug!("Settling balances for version {:?}", next_version);
+
+        // Reservations made at versions before the new settled version have now
+        // been reflected in the settled balances.
+        let mut reservations = self.reservations.lock();
+        let unsettled = reservations.reserved.split_off(&next_version);
+        let settled = mem::replace(&mut reservations.reserved, unsettled);
+        reservations.deposits.settle(next_version);
+        let _ = self.last_settled_version_sender.send(next_version);
+
+        // Reservations are only ever made at or after the last settled version, and are
+        // released when it is settled, so this settlement can only release reservations
+        // from the version it settles.
+        let settling_version = next_version.checked_decrement();
+        for version in settled.keys() {
+            check_invariant!(
+                Some(*version) == settling_version,
+                "released_reservations_from_settled_version",
+                reserved_version = version,
+                settled_version = settling_version,
+            );
+        }
+
+        let mut summary = SettlementSummary {
+            accumulator_version: next_version,
+            released: settled.values().map(|txs| txs.len()).sum(),
+            ..Default::default()
+        };
+
+        // Keep what was reserved at the versions late withdraws can still be scheduled at.
+        let late_versions = self.params.late_withdraw_versions;
+        if late_versions > 0 {
+            if let Some(settling_version) = settling_version {
+                let released = settled.get(&settling_version).cloned().unwrap_or_default();
+                reservations.released.insert(settling_version, released);
+            }
+            let oldest =
+                SequenceNumber::from_u64(next_version.value().saturating_sub(late_versions));
+            reservations.released = reservations.released.split_off(&oldest);
+        }
+
+        // Deferred batches are scheduled before any other command is run, so they are checked
+        // against the balances at exactly their own version. Those balances are read without
+        // holding the lock, during which withdraws in the batches that were scheduled ahead can
+        // be amended to reserve from other accounts, whose balances are then read as well.
+        let batches = reservations
+            .deferred
+            .remove(&next_version)
+            .unwrap_or_default();
+        let mut balances = BTreeMap::new();
+        loop {
+            let missing: Vec<_> = reservations
+                .batch_accounts(next_version, &batches)
+                .into_iter()
+                .filter(|account_id| !balances.contains_key(account_id))
+                .collect();
+            if missing.is_empty() {
+                break;
+            }
+
+            drop(reservations);
+            balances.extend(self.read_balances(&missing, next_version));
+            reservations = self.reservations.lock();
+        }
+
+        for batch in batches {
+            let outcomes = reservations.schedule_settled(
+                balances.clone(),
+                next_version,
+                batch,
+                Instant::now(),
+            );
+            summary.sufficient += outcomes.sufficient;
+            summary.insufficient += outcomes.insufficient;
+        }
+
+        // Report the decisions about withdraws at this version, and any earlier version that
+        // were made since the last settlement (e.g. withdraws that arrived after their version
+        // was settled).
+        let unreported = reservations.decisions.split_off(&next_version.next());
+        let decisions = mem::replace(&mut reservations.decisions, unreported);
+        let mut total_wait = Duration::ZERO;
+        for decided in decisions.values() {
+            summary.scheduled_withdraws += decided.scheduled;
+            summary.rejected_withdraws += decided.insufficient;
+            total_wait += decided.total_wait;
+        }
+
+        if summary.scheduled_withdraws > 0 {
+            summary.avg_reservation_wait = total_wait / summary.scheduled_withdraws as u32;
+        }
+
+        summary.reserved_txs = reservations.reserved.values().map(|txs| txs.len()).sum();
+        summary.pending_txs = reservations.pending.values().map(|txs| txs.len()).sum();
+        summary.deferred_batches = reservations.deferred.values().map(|b| b.len()).sum();
+        self.publish(reservations);
+        (settled, summary)
+    }
+
+    /// Replace the reservations of `tx_digest` with `new_reservations`, see
+    /// [Reservations::amend].
+    pub(super) fn amend(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError> {
+        // The balances of the accounts whose reservations increase are read at the last settled
+        // version without holding the lock, and are only used if no other version was settled,
+        // and the transaction's reservations were not amended, in the meantime.
+        loop {
+            let (last_settled_version, increased) = {
+                let reservations = self.reservations.lock();
+                (
+                    *self.last_settled_version_receiver.borrow(),
+                    reservations.increased_accounts(tx_digest, &new_reservations),
+                )
+            };
+            let balances = self.read_balances(&increased, last_settled_version);
+
+            let mut reservations = self.reservations.lock();
+            if *self.last_settled_version_receiver.borrow() != last_settled_version
+                || reservations.increased_accounts(tx_digest, &new_reservations) != increased
+            {
+                continue;
+            }
+
+            let result =
+                reservations.amend(&balances, last_settled_version, tx_digest, new_reservations);
+            self.publish(reservations);
+            return result;
+        }
+    }
+
+    pub(super) fn record_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    ) {
+        let mut reservations = self.reservations.lock();
+        // Deposits at settled versions are already reflected in the settled balances.
+        if accumulator_version < *self.last_settled_version_receiver.borrow() {
+            return;
+        }
+        reservations
+            .deposits
+            .record(accumulator_version, tx_digest, deposits);
+        self.publish(reservations);
+    }
+
+    pub(super) fn is_idle(&self) -> bool {
+        self.views.load().idle
+    }
+
+    pub(super) fn withdraws_awaiting_settlement(
+        &self,
+        min_settlements: u64,
+    ) -> Vec<WithdrawAwaitingSettlement> {
+        self.views
+            .load()
+            .waiting
+            .iter()
+            .filter(|w| w.settlements_waited >= min_settlements)
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn reserved_balance(&self, account_id: &ObjectID) -> u64 {
+        self.views
+            .load()
+            .reserved
+            .get(account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The amounts reserved from, and deposited into, an account, from a single snapshot, so
+    /// that they are both relative to the same settled version.
+    pub(super) fn account_reservations(&self, account_id: &ObjectID) -> AccountReservations {
+        let views = self.views.load();
+        AccountReservations {
+            settled_version: views.settled_version,
+            reserved: views.reserved.get(account_id).copied().unwrap_or_default(),
+            pending_deposits: views.deposits.get(account_id).copied().unwrap_or_default(),
+        }
+    }
+
+    /// Read the balance of each of `accounts` at `accumulator_version` from storage. This must
+    /// not be called while holding the lock on the reservations.
+    fn read_balances<'a>(
+        &self,
+        accounts: impl IntoIterator<Item = &'a ObjectID>,
+        accumulator_version: SequenceNumber,
+    ) -> BTreeMap<ObjectID, u64> {
+        let mut balances = BTreeMap::new();
+        for account_id in accounts {
+            balances.entry(*account_id).or_insert_with(|| {
+                self.balance_read
+                    .get_account_balance(account_id, accumulator_version)
+            });
+        }
+        balances
+    }
+
+    /// Publish a snapshot of `reservations` for queries, release the lock on them, and then send
+    /// the results decided while it was held. Results are only sent once the snapshot reflects
+    /// them, so that whoever receives a result also sees its reservations.
+    fn publish(&self, mut reservations: MutexGuard<'_, Reservations>) {
+        let results = mem::take(&mut reservations.results);
+        let last_settled_version = *self.last_settled_version_receiver.borrow();
+        self.views
+            .store(Arc::new(reservations.views(last_settled_version)));
+        drop(reservations);
+
+        for (sender, result) in results {
+            let _ = sender.send(result);
+        }
+    }
+
+    /// Reject the withdraws that the withdraw policy does not allow at `accumulator_version`,
+    /// or that exceed the per-account cap or per-transaction limit, and return the rest, with
+    /// how many were rejected for exceeding the cap or limit.
+    fn reject(
+        &self,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+    ) -> (
+        Vec<TxBalanceWithdraw>,
+        Vec<oneshot::Sender<ScheduleResult>>,
+        usize,
+    ) {
+        let (withdraws, senders) = self.reject_by_policy(accumulator_version, withdraws, senders);
+        let within_limits = withdraws.len();
+        let (withdraws, senders) = self.reject_over_cap(withdraws, senders);
+        let (withdraws, senders) = self.reject_over_tx_limit(withdraws, senders);
+        let over_limits = within_limits - withdraws.len();
+        (withdraws, senders, over_limits)
+    }
+
+    /// Reject the withdraws that reserve from any account the withdraw policy does not allow at
+    /// `accumulator_version`, and return the rest.
+    fn reject_by_policy(
+        &self,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+    ) -> (Vec<TxBalanceWithdraw>, Vec<oneshot::Sender<ScheduleResult>>) {
+        withdraws
+            .into_iter()
+            .zip(senders)
+            .filter_map(|(withdraw, sender)| {
+                let rejected: Vec<_> = withdraw
+                    .reservations
+                    .keys()
+                    .filter(|account| !self.policy.allows_withdraw(account, accumulator_version))
+                    .collect();
+
+                if rejected.is_empty() {
+                    return Some((withdraw, sender));
+                }
+                debug!(
+                    "Withdraw policy rejected {:?} from accounts {:?}",
+                    withdraw.tx_digest, rejected
+                );
+                let _ = sender.send(ScheduleResult {
+                    tx_digest: withdraw.tx_digest,
+                    status: ScheduleStatus::PolicyRejected,
+                    details: vec![],
+                });
+                None
+            })
+            .unzip()
+    }
+
+    /// Reject the withdraws that reserve more than the per-account cap from any account as
+    /// having insufficient balance, and return the rest.
+    fn reject_over_cap(
+        &self,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+    ) -> (Vec<TxBalanceWithdraw>, Vec<oneshot::Sender<ScheduleResult>>) {
+        let Some(cap) = self.params.max_reservation_per_account else {
+            return (withdraws, senders);
+        };
+
+        withdraws
+            .into_iter()
+            .zip(senders)
+            .filter_map(|(withdraw, sender)| {
+                let details: Vec<_> = withdraw
+                    .reservations
+                    .iter()
+                    .filter(|(_, amount)| **amount > cap)
+                    .map(|(account, amount)| AccountShortfall {
+                        account: *account,
+                        requested: *amount,
+                        available: cap,
+                    })
+                    .collect();
+
+                if details.is_empty() {
+                    return Some((withdraw, sender));
+                }
+                debug!("Reservations of {:?} exceed the cap of {}", withdraw, cap);
+                let _ = sender.send(ScheduleResult {
+                    tx_digest: withdraw.tx_digest,
+                    status: ScheduleStatus::InsufficientBalance,
+                    details,
+                });
+                None
+            })
+            .unzip()
+    }
+
+    /// Reject the withdraws that reserve more than the per-transaction limit in total across
+    /// their accounts, and return the rest.
+    fn reject_over_tx_limit(
+        &self,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+    ) -> (Vec<TxBalanceWithdraw>, Vec<oneshot::Sender<ScheduleResult>>) {
+        let Some(limit) = self.params.max_reservation_per_transaction else {
+            return (withdraws, senders);
+        };
+
+        withdraws
+            .into_iter()
+            .zip(senders)
+            .filter_map(|(withdraw, sender)| {
+                if withdraw.total_reservation() <= limit {
+                    return Some((withdraw, sender));
+                }
+                debug!(
+                    "Reservations of {:?} exceed the per-transaction limit of {}",
+                    withdraw, limit
+                );
+                let _ = sender.send(ScheduleResult {
+                    tx_digest: withdraw.tx_digest,
+                    status: ScheduleStatus::Rejected,
+                    details: vec![],
+                });
+                None
+            })
+            .unzip()
+    }
+}
+
+impl Reservations {
+    /// Schedule the withdraws in `withdraws` that are guaranteed to have sufficient balance at
+    /// `accumulator_version`, which is ahead of `last_settled_version`. The rest are recorded
+    /// as pending. Returns the batch, with the senders of the scheduled withdraws taken out.
+    /// `balances` holds the balance of each account in `withdraws` at `last_settled_version`.
+    fn schedule_ahead(
+        &mut self,
+        balances: &BTreeMap<ObjectID, u64>,
+        last_settled_version: SequenceNumber,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+        sent_at: Instant,
+    ) -> WithdrawBatch {
+        // Settling the versions in between can only take from an account what was reserved
+        // by, or is pending for, withdraws scheduled at those versions. Anything else the
+        // settlements do to the account is a deposit.
+        let mut outstanding: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for (account, amount) in self
+            .reserved
+            .range(last_settled_version..accumulator_version)
+            .chain(self.pending.range(..accumulator_version))
+            .flat_map(|(_, txs)| txs.values())
+            .flatten()
+        {
+            let entry = outstanding.entry(*account).or_default();
+            *entry = entry.saturating_add(*amount);
+        }
+
+        // Deposits by transactions at the versions in between are guaranteed to be settled
+        // before this version, so they count towards the balance as well.
+        let credits = self
+            .deposits
+            .credits(last_settled_version..accumulator_version);
+
+        // A lower bound on the balance left in each account for the next withdraw in the batch.
+        let mut lower_bounds = BTreeMap::new();
+        let mut batch = Vec::with_capacity(withdraws.len());
+        for (withdraw, sender) in withdraws.into_iter().zip(senders) {
+            for object_id in withdraw.reservations.keys() {
+                lower_bounds.entry(*object_id).or_insert_with(|| {
+                    balances
+                        .get(object_id)
+                        .copied()
+                        .unwrap_or_default()
+                        .saturating_add(credits.get(object_id).copied().unwrap_or_default())
+                        .saturating_sub(outstanding.get(object_id).copied().unwrap_or_default())
+                });
+            }
+            let blocking_accounts: Vec<_> = withdraw
+                .reservations
+                .iter()
+                .filter(|(object_id, reservation)| lower_bounds[*object_id] < **reservation)
+                .map(|(object_id, reservation)| AccountShortfall {
+                    account: *object_id,
+                    requested: *reservation,
+                    available: lower_bounds[object_id],
+                })
+                .collect();
+            // Earlier withdraws in the batch are checked first, so whether or not this one
+            // is scheduled now, the ones after it must assume it takes its reservations.
+            for (object_id, reservation) in &withdraw.reservations {
+                // unwrap safe because we always initialize each account in the above loop.
+                let bound = lower_bounds.get_mut(object_id).unwrap();
+                *bound = bound.saturating_sub(*reservation);
+            }
+
+            if blocking_accounts.is_empty() {
+                debug!(
+                    "Reserved all withdraws for {:?} ahead of settlement",
+                    withdraw
+                );
+                self.reserved
+                    .entry(accumulator_version)
+                    .or_default()
+                    .insert(withdraw.tx_digest, withdraw.reservations.clone());
+                self.record_decision(accumulator_version, true, sent_at);
+                self.results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::SufficientBalance,
+                        details: vec![],
+                    },
+                ));
+                batch.push((withdraw, None));
+            } else {
+                self.pending
+                    .entry(accumulator_version)
+                    .or_default()
+                    .insert(withdraw.tx_digest, withdraw.reservations.clone());
+                self.waiting.insert(
+                    withdraw.tx_digest,
+                    WaitingWithdraw {
+                        accumulator_version,
+                        since: last_settled_version,
+                        blocking_accounts,
+                        sent_at,
+                    },
+                );
+                batch.push((withdraw, Some(sender)));
+            }
+        }
+        batch
+    }
+
+    /// Record the withdraws in `withdraws` as pending until `accumulator_version` is settled,
+    /// without trying to schedule any of them ahead of it.
+    fn defer(
+        &mut self,
+        last_settled_version: SequenceNumber,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+        sent_at: Instant,
+    ) -> WithdrawBatch {
+        let pending = self.pending.entry(accumulator_version).or_default();
+        withdraws
+            .into_iter()
+            .zip(senders)
+            .map(|(withdraw, sender)| {
+                pending.insert(withdraw.tx_digest, withdraw.reservations.clone());
+                self.waiting.insert(
+                    withdraw.tx_digest,
+                    WaitingWithdraw {
+                        accumulator_version,
+                        since: last_settled_version,
+                        blocking_accounts: vec![],
+                        sent_at,
+                    },
+                );
+                (withdraw, Some(sender))
+            })
+            .collect()
+    }
+
+    /// Schedule the withdraws in `batch` against the balances at `accumulator_version`, which
+    /// must be the last settled version. Returns how many of the withdraws that had not been
+    /// scheduled ahead of settlement had sufficient balance, and how many did not.
+    ///
+    /// `cur_balances` starts out with the balance at `accumulator_version` of every account in
+    /// the batch, see [Self::batch_accounts], and is updated to the remaining balance for
+    /// reservation as withdraws are scheduled. Withdraws that were not waiting for the version to
+    /// be settled were sent at `sent_at`.
+    fn schedule_settled(
+        &mut self,
+        mut cur_balances: BTreeMap<ObjectID, u64>,
+        accumulator_version: SequenceNumber,
+        batch: WithdrawBatch,
+        sent_at: Instant,
+    ) -> ScheduleOutcomes {
+        let mut outcomes = ScheduleOutcomes::default();
+        for (withdraw, sender) in batch {
+            let Some(sender) = sender else {
+                // Already scheduled ahead of settlement, which only happens when the
+                // reservations are guaranteed to fit in the remaining balance. They may have
+                // been amended since.
+                let reservations = self
+                    .reserved
+                    .get(&accumulator_version)
+                    .and_then(|txs| txs.get(&withdraw.tx_digest))
+                    .unwrap_or(&withdraw.reservations);
+                for (object_id, reservation) in reservations {
+                    // unwrap safe because the balances of amended accounts are read as well.
+                    let balance = cur_balances.get_mut(object_id).unwrap();
+                    check_invariant!(
+                        *balance >= *reservation,
+                        "reserved_ahead_within_settled_balance",
+                        tx_digest = withdraw.tx_digest,
+                        account = object_id,
+                        accumulator_version = accumulator_version,
+                        reserved = reservation,
+                        balance = balance,
+                    );
+                    *balance = balance.saturating_sub(*reservation);
+                }
+                continue;
+            };
+            let sent_at = self
+                .remove_pending(accumulator_version, &withdraw.tx_digest)
+                .map_or(sent_at, |waiting| waiting.sent_at);
+
             // We need to first walk through all reservations in this transaction
             // to see if we can successfully reserve each of them.
             // If we can, we then update the current balances atomically.
             // If not, we leave the current balances unchanged for the next transaction.
-            // We make sure to initialize each account we see in the cur_balances map.
-            let mut success = true;
+            let mut shortfalls = vec![];
             for (object_id, reservation) in &withdraw.reservations {
-                let entry = cur_balances.entry(*object_id).or_insert_with(|| {
-                    self.balance_read
-                        .get_account_balance(object_id, withdraws.accumulator_version)
-                });
-                debug!("Starting balance for {:?}: {:?}", object_id, entry);
+                let balance = cur_balances[object_id];
+                debug!("Starting balance for {:?}: {:?}", object_id, balance);
 
-                if *entry < *reservation {
+                if balance < *reservation {
                     debug!(
                         "Insufficient balance for {:?}. Requested: {:?}, Available: {:?}",
-                        object_id, reservation, entry
+                        object_id, reservation, balance
                     );
-                    success = false;
-                    break;
+                    shortfalls.push(AccountShortfall {
+                        account: *object_id,
+                        requested: *reservation,
+                        available: balance,
+                    });
                 }
             }
-            if success {
+            if shortfalls.is_empty() {
                 debug!("Successfully reserved all withdraws for {:?}", withdraw);
-                for (object_id, reservation) in withdraw.reservations {
+                for (object_id, reservation) in &withdraw.reservations {
                     // unwrap safe because we always initialize each account in the above loop.
-                    let balance = cur_balances.get_mut(&object_id).unwrap();
-                    *balance -= reservation;
+                    let balance = cur_balances.get_mut(object_id).unwrap();
+                    *balance -= *reservation;
                 }
-                let _ = sender.send(ScheduleResult {
-                    tx_digest: withdraw.tx_digest,
-                    status: ScheduleStatus::SufficientBalance,
-                });
+                self.reserved
+                    .entry(accumulator_version)
+                    .or_default()
+                    .insert(withdraw.tx_digest, withdraw.reservations);
+                outcomes.sufficient += 1;
+                self.record_decision(accumulator_version, true, sent_at);
+                self.results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::SufficientBalance,
+                        details: vec![],
+                    },
+                ));
             } else {
-                let _ = sender.send(ScheduleResult {
-                    tx_digest: withdraw.tx_digest,
-                    status: ScheduleStatus::InsufficientBalance,
+                outcomes.insufficient += 1;
+                self.record_decision(accumulator_version, false, sent_at);
+                self.results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::InsufficientBalance,
+                        details: shortfalls,
+                    },
+                ));
+            }
+        }
+
+        outcomes
+    }
+
+    /// Schedule `withdraws` at `accumulator_version`, which has already been settled, against
+    /// the exact balances at that version, if those balances are still known. Withdraws whose
+    /// transaction was already scheduled at the version, or whose balances are no longer known,
+    /// are treated as already executed.
+    ///
+    /// Like a batch that arrives on time (see [Self::schedule_settled]), the batch is checked on
+    /// its own: each withdraw is checked against the balance at the version less what earlier
+    /// withdraws in the same batch reserved, regardless of what other batches reserved at the
+    /// version. So a batch gets the same decisions whether it arrives on time or late.
+    ///
+    /// `balances` holds the exact balance of each account in `withdraws` at
+    /// `accumulator_version`, or `None` if it is no longer known.
+    fn schedule_late(
+        &mut self,
+        balances: &BTreeMap<ObjectID, Option<u64>>,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+        senders: Vec<oneshot::Sender<ScheduleResult>>,
+        sent_at: Instant,
+    ) {
+        let Some(released) = self.released.get(&accumulator_version) else {
+            return;
+        };
+
+        // The balance left in each account for the next withdraw, if it is still known.
+        let mut cur_balances: BTreeMap<ObjectID, Option<u64>> = BTreeMap::new();
+        let mut decisions = vec![];
+        let mut scheduled = vec![];
+        let mut results = vec![];
+        for (withdraw, sender) in withdraws.into_iter().zip(senders) {
+            if released.contains_key(&withdraw.tx_digest) {
+                results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::AlreadyExecuted,
+                        details: vec![],
+                    },
+                ));
+                continue;
+            }
+
+            for object_id in withdraw.reservations.keys() {
+                cur_balances
+                    .entry(*object_id)
+                    .or_insert_with(|| balances.get(object_id).copied().flatten());
+            }
+
+            let mut shortfalls = vec![];
+            let mut known = true;
+            for (object_id, reservation) in &withdraw.reservations {
+                match cur_balances[object_id] {
+                    None => known = false,
+                    Some(balance) if balance < *reservation => shortfalls.push(AccountShortfall {
+                        account: *object_id,
+                        requested: *reservation,
+                        available: balance,
+                    }),
+                    Some(_) => {}
+                }
+            }
+
+            if !known {
+                debug!(
+                    "Balances at {:?} are no longer known for {:?}",
+                    accumulator_version, withdraw.tx_digest
+                );
+                results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::AlreadyExecuted,
+                        details: vec![],
+                    },
+                ));
+            } else if shortfalls.is_empty() {
+                debug!("Reserved all late withdraws for {:?}", withdraw);
+                for (object_id, reservation) in &withdraw.reservations {
+                    // unwrap safe because every balance was known above.
+                    let balance = cur_balances.get_mut(object_id).unwrap().as_mut().unwrap();
+                    *balance -= *reservation;
+                }
+                decisions.push(true);
+                results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::SufficientBalance,
+                        details: vec![],
+                    },
+                ));
+                scheduled.push((withdraw.tx_digest, withdraw.reservations));
+            } else {
+                decisions.push(false);
+                results.push((
+                    sender,
+                    ScheduleResult {
+                        tx_digest: withdraw.tx_digest,
+                        status: ScheduleStatus::InsufficientBalance,
+                        details: shortfalls,
+                    },
+                ));
+            }
+        }
+
+        self.results.extend(results);
+
+        self.released
+            .entry(accumulator_version)
+            .or_default()
+            .extend(scheduled);
+        for sufficient in decisions {
+            self.record_decision(accumulator_version, sufficient, sent_at);
+        }
+    }
+
+    /// Replace the reservations of `tx_digest`, which must have been scheduled with sufficient
+    /// balance at a version that is not settled yet, with `new_reservations`.
+    ///
+    /// Increases are only accepted if they fit in a lower bound on the account's balance that
+    /// leaves room for every other reservation from the account, at any unsettled version, and
+    /// for every withdraw from the account pending at the same or an earlier version. Withdraws
+    /// scheduled at later versions assumed this transaction takes no more than it originally
+    /// reserved, so their guarantees must be preserved as well.
+    ///
+    /// `balances` holds the balance of each account in `new_reservations` at
+    /// `last_settled_version`.
+    fn amend(
+        &mut self,
+        balances: &BTreeMap<ObjectID, u64>,
+        last_settled_version: SequenceNumber,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError> {
+        let Some((accumulator_version, old_reservations)) =
+            self.reserved.iter().find_map(|(version, txs)| {
+                txs.get(tx_digest)
+                    .map(|reservations| (*version, reservations))
+            })
+        else {
+            return Err(AmendReservationError::NotReserved {
+                tx_digest: *tx_digest,
+            });
+        };
+
+        let credits = self
+            .deposits
+            .credits(last_settled_version..accumulator_version);
+
+        for (account_id, amount) in &new_reservations {
+            let old_amount = old_reservations
+                .get(account_id)
+                .copied()
+                .unwrap_or_default();
+            let increase = amount.saturating_sub(old_amount);
+            if increase == 0 {
+                continue;
+            }
+
+            // This includes the transaction's own reservation, which the lower bound must not
+            // count as available twice.
+            let outstanding = self
+                .reserved
+                .values()
+                .chain(
+                    self.pending
+                        .range(..=accumulator_version)
+                        .map(|(_, txs)| txs),
+                )
+                .flat_map(|txs| txs.values())
+                .filter_map(|reservations| reservations.get(account_id))
+                .fold(0u64, |total, amount| total.saturating_add(*amount));
+
+            let available = balances
+                .get(account_id)
+                .copied()
+                .unwrap_or_default()
+                .saturating_add(credits.get(account_id).copied().unwrap_or_default())
+                .saturating_sub(outstanding);
+
+            if available < increase {
+                debug!(
+                    "Cannot increase reservation of {:?} from {:?} by {}: {} available",
+                    tx_digest, account_id, increase, available
+                );
+                return Err(AmendReservationError::InsufficientBalance {
+                    account_id: *account_id,
+                    increase,
+                    available,
                 });
             }
         }
+
+        debug!(
+            "Amended reservations of {:?} to {:?}",
+            tx_digest, new_reservations
+        );
+        self.reserved
+            .entry(accumulator_version)
+            .or_default()
+            .insert(*tx_digest, new_reservations);
+        Ok(())
+    }
+
+    /// The accounts that `new_reservations` would reserve more from than `tx_digest` currently
+    /// reserves, if it is reserved at all.
+    fn increased_accounts(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: &BTreeMap<ObjectID, u64>,
+    ) -> Vec<ObjectID> {
+        let Some(old_reservations) = self.reserved.values().find_map(|txs| txs.get(tx_digest))
+        else {
+            return vec![];
+        };
+
+        new_reservations
+            .iter()
+            .filter(|(account_id, amount)| {
+                **amount
+                    > old_reservations
+                        .get(*account_id)
+                        .copied()
+                        .unwrap_or_default()
+            })
+            .map(|(account_id, _)| *account_id)
+            .collect()
+    }
+
+    /// Whether any of `accounts` has been reserved from at a version after `accumulator_version`.
+    fn reserved_after(
+        &self,
+        accumulator_version: SequenceNumber,
+        accounts: &BTreeSet<&ObjectID>,
+    ) -> bool {
+        self.reserved
+            .range(accumulator_version.next()..)
+            .flat_map(|(_, txs)| txs.values())
+            .flat_map(|reservations| reservations.keys())
+            .any(|account_id| accounts.contains(account_id))
+    }
+
+    /// A snapshot of the reservations for queries, as of `last_settled_version`.
+    fn views(&self, last_settled_version: SequenceNumber) -> ReservationViews {
+        let mut reserved: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for (account_id, amount) in self
+            .reserved
+            .range(last_settled_version..)
+            .flat_map(|(_, txs)| txs.values())
+            .flatten()
+        {
+            let total = reserved.entry(*account_id).or_default();
+            *total = total.saturating_add(*amount);
+        }
+
+        ReservationViews {
+            settled_version: last_settled_version,
+            reserved,
+            deposits: self
+                .deposits
+                .credits(last_settled_version..SequenceNumber::MAX),
+            idle: self.reserved.is_empty() && self.pending.is_empty() && self.deferred.is_empty(),
+            waiting: self.awaiting_settlement(last_settled_version, 0),
+        }
+    }
+
+    /// The accounts whose balances scheduling `batches` at `accumulator_version` needs: those
+    /// the withdraws in the batches reserve from, and those the withdraws that were scheduled
+    /// ahead of settlement have since been amended to reserve from.
+    fn batch_accounts(
+        &self,
+        accumulator_version: SequenceNumber,
+        batches: &[WithdrawBatch],
+    ) -> BTreeSet<ObjectID> {
+        let amended = self.reserved.get(&accumulator_version);
+        batches
+            .iter()
+            .flatten()
+            .flat_map(|(withdraw, _)| {
+                let amended = amended.and_then(|txs| txs.get(&withdraw.tx_digest));
+                withdraw
+                    .reservations
+                    .keys()
+                    .chain(amended.into_iter().flat_map(|r| r.keys()))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// The pending withdraws that have been waiting while at least `min_settlements` versions
+    /// were settled, in the order of their accumulator versions.
+    fn awaiting_settlement(
+        &self,
+        last_settled_version: SequenceNumber,
+        min_settlements: u64,
+    ) -> Vec<WithdrawAwaitingSettlement> {
+        let mut waiting: Vec<_> = self
+            .waiting
+            .iter()
+            .filter_map(|(tx_digest, waiting)| {
+                let settlements_waited = last_settled_version
+                    .value()
+                    .saturating_sub(waiting.since.value());
+                (settlements_waited >= min_settlements).then(|| WithdrawAwaitingSettlement {
+                    tx_digest: *tx_digest,
+                    accumulator_version: waiting.accumulator_version,
+                    settlements_waited,
+                    blocking_accounts: waiting.blocking_accounts.clone(),
+                })
+            })
+            .collect();
+
+        waiting.sort_by_key(|w| (w.accumulator_version, w.tx_digest));
+        waiting
+    }
+
+    /// Stop tracking `tx_digest` as pending at `accumulator_version`, returning how it was
+    /// waiting, if it was.
+    fn remove_pending(
+        &mut self,
+        accumulator_version: SequenceNumber,
+        tx_digest: &TransactionDigest,
+    ) -> Option<WaitingWithdraw> {
+        if let Some(pending) = self.pending.get_mut(&accumulator_version) {
+            pending.remove(tx_digest);
+            if pending.is_empty() {
+                self.pending.remove(&accumulator_version);
+            }
+        }
+        self.waiting.remove(tx_digest)
+    }
+
+    /// Record that a withdraw at `accumulator_version`, sent at `sent_at`, was decided
+    /// to have `sufficient` balance, or not, to report in the summary of its settlement.
+    fn record_decision(
+        &mut self,
+        accumulator_version: SequenceNumber,
+        sufficient: bool,
+        sent_at: Instant,
+    ) {
+        let decisions = self.decisions.entry(accumulator_version).or_default();
+        decisions.scheduled += 1;
+        if !sufficient {
+            decisions.insufficient += 1;
+        }
+        decisions.total_wait += sent_at.elapsed();
+    }
+}
+
+#[async_trait::async_trait]
+impl BalanceWithdrawSchedulerTrait for NaiveBalanceWithdrawScheduler {
+    async fn schedule_withdraws(&self, withdraws: WithdrawReservations) {
+        self.send(|done| Command::Schedule(withdraws, done)).await;
     }
 
-    // We don't use the settlement information in the naive scheduler.
+    // We don't use the balance changes in the naive scheduler.
     // Instead, the withdraw scheduling always read the balance state fro storage.
-    async fn settle_balances(&self, _settlement: BalanceSettlement) {
-        let next_version = self.last_settled_version_receiver.borrow().next();
-        debug!("Settling balances for version {:?}", next_version);
-        let _ = self.last_settled_version_sender.send(next_version);
+    // The settled withdraws are only used to produce settlement receipts.
+    async fn settle_balances(
+        &self,
+        settlement: BalanceSettlement,
+    ) -> (Vec<SettlementReceipt>, SettlementSummary) {
+        let Some((settled, summary)) = self.send(Command::Settle).await else {
+            return (vec![], SettlementSummary::default());
+        };
+
+        let summary = summary.with_balance_changes(&settlement);
+        let receipts = SettlementReceipt::reconcile(
+            settled.into_values().flatten().collect(),
+            settlement.withdraws,
+        );
+        (receipts, summary)
+    }
+
+    fn record_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    ) {
+        self.state
+            .record_deposits(accumulator_version, tx_digest, deposits);
+    }
+
+    fn amend_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        mut new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError> {
+        new_reservations.retain(|_, amount| *amount > 0);
+        if let Some(cap) = self.state.params.max_reservation_per_account {
+            if let Some((account_id, amount)) =
+                new_reservations.iter().find(|(_, amount)| **amount > cap)
+            {
+                return Err(AmendReservationError::ExceedsCap {
+                    account_id: *account_id,
+                    amount: *amount,
+                    cap,
+                });
+            }
+        }
+        if let Some(limit) = self.state.params.max_reservation_per_transaction {
+            let total = total_reservation(&new_reservations);
+            if total > limit {
+                return Err(AmendReservationError::ExceedsTransactionLimit { total, limit });
+            }
+        }
+
+        self.state.amend(tx_digest, new_reservations)
+    }
+
+    fn is_idle(&self) -> bool {
+        self.state.is_idle()
+    }
+
+    fn withdraws_awaiting_settlement(
+        &self,
+        min_settlements: u64,
+    ) -> Vec<WithdrawAwaitingSettlement> {
+        self.state.withdraws_awaiting_settlement(min_settlements)
+    }
+
+    fn get_reserved_balance(&self, account_id: &ObjectID) -> u64 {
+        self.state.reserved_balance(account_id)
+    }
+
+    fn get_account_reservations(&self, account_id: &ObjectID) -> AccountReservations {
+        self.state.account_reservations(account_id)
     }
 }
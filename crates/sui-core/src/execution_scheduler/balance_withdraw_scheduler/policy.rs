@@ -0,0 +1,30 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use sui_types::base_types::{ObjectID, SequenceNumber};
+
+/// Decides which accounts withdraws may reserve balance from, e.g. to enforce regulatory freezes
+/// that are applied through system transactions. The scheduler consults the policy for every
+/// account a transaction withdraws from, before checking its balance, and rejects the
+/// transaction with [`super::ScheduleStatus::PolicyRejected`] if any account is not allowed.
+///
+/// Every validator must reach the same decision for the same withdraw, so a policy may only
+/// depend on state as of the accumulator version the withdraw is scheduled at.
+pub(crate) trait WithdrawPolicy: Send + Sync {
+    /// Whether withdraws at `accumulator_version` may reserve balance from `account_id`.
+    fn allows_withdraw(&self, account_id: &ObjectID, accumulator_version: SequenceNumber) -> bool;
+}
+
+/// The default policy, which allows withdraws from every account.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct AllowAllWithdraws;
+
+impl WithdrawPolicy for AllowAllWithdraws {
+    fn allows_withdraw(
+        &self,
+        _account_id: &ObjectID,
+        _accumulator_version: SequenceNumber,
+    ) -> bool {
+        true
+    }
+}
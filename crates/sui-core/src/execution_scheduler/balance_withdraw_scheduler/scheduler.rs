@@ -1,38 +1,185 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use crate::execution_scheduler::balance_withdraw_scheduler::{
-    balance_read::AccountBalanceRead, naive_scheduler::NaiveBalanceWithdrawScheduler,
-    BalanceSettlement, ScheduleResult, TxBalanceWithdraw,
+    balance_read::AccountBalanceRead,
+    cross_check::{cross_check_enabled, ReferenceWithdrawScheduler},
+    naive_scheduler::NaiveBalanceWithdrawScheduler,
+    policy::{AllowAllWithdraws, WithdrawPolicy},
+    shadow::ShadowBalanceWithdrawScheduler,
+    AccountReservations, AmendReservationError, BalanceSettlement, ScheduleResult,
+    SettlementReceipt, SettlementSummary, TxBalanceWithdraw, WithdrawAwaitingSettlement,
+    WithdrawDrainStatus, WithdrawSchedulerParams,
+};
+use futures::{
+    stream::{FuturesUnordered, StreamExt},
+    FutureExt,
 };
-use futures::stream::FuturesUnordered;
 use mysten_metrics::monitored_mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use sui_types::base_types::SequenceNumber;
-use tokio::sync::oneshot;
-use tracing::debug;
+use prometheus::{Histogram, HistogramVec, IntCounter, IntGauge};
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+use tokio::{
+    sync::{broadcast, oneshot, watch},
+    time::Instant,
+};
+use tracing::{debug, warn};
 
 #[async_trait::async_trait]
 pub(crate) trait BalanceWithdrawSchedulerTrait: Send + Sync {
     async fn schedule_withdraws(&self, withdraws: WithdrawReservations);
-    async fn settle_balances(&self, settlement: BalanceSettlement);
+    /// Returns a receipt for each withdraw settled by `settlement`.
+    async fn settle_balances(
+        &self,
+        settlement: BalanceSettlement,
+    ) -> (Vec<SettlementReceipt>, SettlementSummary);
+    /// Records the amounts deposited into each account by an executed transaction at
+    /// `accumulator_version`, to be credited to withdraws scheduled at later versions before
+    /// the deposits are settled.
+    fn record_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    );
+    /// Returns the total amount currently reserved from the given account by withdraws
+    /// that have been scheduled but whose accumulator version has not been settled yet.
+    fn get_reserved_balance(&self, account_id: &ObjectID) -> u64;
+    /// Returns the amounts reserved from, and deposited into, the given account at accumulator
+    /// versions that have not been settled yet, along with the last settled version.
+    fn get_account_reservations(&self, account_id: &ObjectID) -> AccountReservations;
+    /// Returns whether no withdraw is reserved, or waiting for its accumulator version to be
+    /// settled.
+    fn is_idle(&self) -> bool;
+    /// Replaces the reservations of a transaction that was scheduled with sufficient balance,
+    /// and whose accumulator version has not been settled yet, with `new_reservations`.
+    fn amend_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError>;
+    /// Returns the withdraws that have been waiting for their accumulator version to be settled
+    /// while at least `min_settlements` versions were settled.
+    fn withdraws_awaiting_settlement(
+        &self,
+        min_settlements: u64,
+    ) -> Vec<WithdrawAwaitingSettlement>;
 }
 
 pub(crate) struct WithdrawReservations {
     pub accumulator_version: SequenceNumber,
     pub withdraws: Vec<TxBalanceWithdraw>,
     pub senders: Vec<oneshot::Sender<ScheduleResult>>,
+    /// When the withdraws were sent to the scheduler, which is when the wait for their results
+    /// is measured from.
+    pub sent_at: Instant,
+}
+
+/// The results of a batch of withdraws, on their way from the scheduler to its caller through
+/// the relay task, see [relay_results].
+struct RelayedBatch {
+    accumulator_version: SequenceNumber,
+    sent_at: Instant,
+    results: FuturesUnordered<oneshot::Receiver<ScheduleResult>>,
+    senders: Vec<oneshot::Sender<ScheduleResult>>,
+}
+
+/// What sees each result of the live scheduler before it is passed on to the caller.
+struct ResultObservers {
+    reservation_wait: Option<HistogramVec>,
+    cross_check: Option<Arc<ReferenceWithdrawScheduler>>,
+    shadow: Option<Arc<ShadowBalanceWithdrawScheduler>>,
+}
+
+/// Metrics for the path from the checkpoint builder producing a settlement's balance changes, to
+/// the scheduler applying the settlement, and for the withdraws waiting on settlements to be
+/// scheduled.
+#[derive(Clone)]
+pub(crate) struct SettlementMetrics {
+    /// Time from the balance changes being produced to the settlement being applied, including
+    /// waiting for the settlement transactions to execute.
+    pub application_latency: Histogram,
+    /// Time from the settlement being sent to the scheduler to the scheduler starting to apply
+    /// it.
+    pub queue_latency: Histogram,
+    /// Settlements that were sent while an earlier settlement had not been applied yet, and so
+    /// queued behind it. Settlements are always sent, and applied, in the order of their
+    /// versions, so this counts how often the scheduler falls behind the checkpoint builder, not
+    /// settlements arriving out of order.
+    pub queued_behind_unapplied: IntCounter,
+    /// Withdraws that have been waiting for at least [LONG_SETTLEMENT_WAIT_SETTLEMENTS]
+    /// settlements.
+    pub long_settlement_waits: IntGauge,
+    /// Time from a withdraw being sent to the scheduler to its result, by [ScheduleStatus].
+    /// Withdraws that are scheduled with sufficient balance hold their reservations from then on.
+    ///
+    /// [ScheduleStatus]: super::ScheduleStatus
+    pub reservation_wait: HistogramVec,
+}
+
+/// A settlement on its way to the scheduler, with the times it went through each step.
+#[derive(Debug)]
+struct PendingSettlement {
+    settlement: BalanceSettlement,
+    produced_at: Instant,
+    sent_at: Instant,
 }
 
 #[derive(Clone)]
 pub(crate) struct BalanceWithdrawScheduler {
     inner: Arc<dyn BalanceWithdrawSchedulerTrait>,
+    /// Where balances are read from, which is told about each settlement before the scheduler
+    /// applies it, see [AccountBalanceRead::settle_balances].
+    balance_read: Arc<dyn AccountBalanceRead>,
+    /// The accumulator version the scheduler was created at, whose settlements were already
+    /// reflected in the store, see [Self::settle_balances_at].
+    starting_accumulator_version: SequenceNumber,
+    /// Optionally, a copy of the scheduler with alternative parameters, whose results are only
+    /// compared with the live results.
+    shadow: Option<Arc<ShadowBalanceWithdrawScheduler>>,
+    /// Optionally, a reference model that every decision of the scheduler is checked against,
+    /// see [ReferenceWithdrawScheduler].
+    cross_check: Option<Arc<ReferenceWithdrawScheduler>>,
     /// Use channels to process withdraws and settlements asynchronously without blocking the caller.
     withdraw_sender: UnboundedSender<WithdrawReservations>,
-    settlement_sender: UnboundedSender<BalanceSettlement>,
+    settlement_sender: UnboundedSender<PendingSettlement>,
+    receipt_sender: broadcast::Sender<SettlementReceipt>,
+    /// Where the results of each batch of withdraws are sent to be observed before they are
+    /// passed on to the caller, if anything observes them.
+    result_relay: Option<UnboundedSender<RelayedBatch>>,
+    /// The last accumulator version whose settlement has been applied.
+    settled_version: watch::Sender<SequenceNumber>,
+    /// Settlements that have been sent, but not applied yet.
+    unapplied_settlements: AtomicUsize,
+    metrics: Option<SettlementMetrics>,
+    /// Whether new batches of withdraws are held back instead of scheduled.
+    draining: watch::Sender<bool>,
+    /// Batches of withdraws that have been sent, but not scheduled yet.
+    unscheduled_batches: AtomicUsize,
+    /// Whether a batch of withdraws is being scheduled. Set before checking whether the
+    /// scheduler is draining, so that a batch that got past that check is never missed by
+    /// [Self::drain_status].
+    scheduling: AtomicBool,
 }
 
+/// Number of settlement receipts buffered for each subscriber. Subscribers that fall further
+/// behind than this miss receipts.
+const SETTLEMENT_RECEIPT_CHANNEL_CAPACITY: usize = 10_000;
+
+/// Number of settlements a withdraw can wait for its accumulator version to be settled through
+/// before the settlement wait watchdog flags it.
+pub(crate) const LONG_SETTLEMENT_WAIT_SETTLEMENTS: u64 = 10;
+
 impl WithdrawReservations {
     pub fn new(
         accumulator_version: SequenceNumber,
@@ -49,6 +196,7 @@ impl WithdrawReservations {
                 accumulator_version,
                 withdraws,
                 senders,
+                sent_at: Instant::now(),
             },
             receivers,
         )
@@ -60,15 +208,114 @@ impl BalanceWithdrawScheduler {
         balance_read: Arc<dyn AccountBalanceRead>,
         starting_accumulator_version: SequenceNumber,
     ) -> Arc<Self> {
-        let inner = NaiveBalanceWithdrawScheduler::new(balance_read, starting_accumulator_version);
+        Self::new_with_shadow(
+            balance_read,
+            starting_accumulator_version,
+            WithdrawSchedulerParams::default(),
+            Arc::new(AllowAllWithdraws),
+            None,
+            None,
+        )
+    }
+
+    /// Create a scheduler that schedules withdraws according to `params`, that only lets
+    /// withdraws reserve from the accounts that `policy` allows, that also feeds every withdraw,
+    /// settlement and deposit to `shadow`, if it is set, to compare its results with the live
+    /// ones, and that reports how long settlements take to apply to `metrics`, if they are set.
+    ///
+    /// In test configurations, the scheduler's decisions are also cross-checked against a
+    /// reference model if `SUI_CROSS_CHECK_WITHDRAW_SCHEDULER` is set, see
+    /// [ReferenceWithdrawScheduler].
+    pub fn new_with_shadow(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+        params: WithdrawSchedulerParams,
+        policy: Arc<dyn WithdrawPolicy>,
+        shadow: Option<Arc<ShadowBalanceWithdrawScheduler>>,
+        metrics: Option<SettlementMetrics>,
+    ) -> Arc<Self> {
+        Self::new_impl(
+            balance_read,
+            starting_accumulator_version,
+            params,
+            policy,
+            shadow,
+            metrics,
+            cross_check_enabled(),
+        )
+    }
+
+    /// Create a scheduler whose decisions are always cross-checked against the reference model.
+    #[cfg(test)]
+    pub fn new_with_cross_check(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+    ) -> Arc<Self> {
+        Self::new_impl(
+            balance_read,
+            starting_accumulator_version,
+            WithdrawSchedulerParams::default(),
+            Arc::new(AllowAllWithdraws),
+            None,
+            None,
+            true,
+        )
+    }
+
+    fn new_impl(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+        params: WithdrawSchedulerParams,
+        policy: Arc<dyn WithdrawPolicy>,
+        shadow: Option<Arc<ShadowBalanceWithdrawScheduler>>,
+        metrics: Option<SettlementMetrics>,
+        cross_check: bool,
+    ) -> Arc<Self> {
+        let cross_check = cross_check.then(|| {
+            ReferenceWithdrawScheduler::new(
+                balance_read.clone(),
+                starting_accumulator_version,
+                params.max_reservation_per_transaction,
+                policy.clone(),
+            )
+        });
+        let inner = NaiveBalanceWithdrawScheduler::new(
+            balance_read.clone(),
+            starting_accumulator_version,
+            params,
+            policy,
+        );
         let (withdraw_sender, withdraw_receiver) =
             unbounded_channel("withdraw_scheduler_withdraws");
         let (settlement_sender, settlement_receiver) =
             unbounded_channel("withdraw_scheduler_settlements");
+        let (receipt_sender, _) = broadcast::channel(SETTLEMENT_RECEIPT_CHANNEL_CAPACITY);
+        let observers = ResultObservers {
+            reservation_wait: metrics.as_ref().map(|m| m.reservation_wait.clone()),
+            cross_check: cross_check.clone(),
+            shadow: shadow.clone(),
+        };
+        let result_relay = observers.any().then(|| {
+            let (sender, receiver) = unbounded_channel("withdraw_scheduler_results");
+            tokio::spawn(relay_results(receiver, observers));
+            sender
+        });
         let scheduler = Arc::new(Self {
             inner,
+            balance_read,
+            starting_accumulator_version,
+            shadow,
+            cross_check,
             withdraw_sender,
             settlement_sender,
+            receipt_sender,
+            result_relay,
+            settled_version: watch::Sender::new(starting_accumulator_version),
+            unapplied_settlements: AtomicUsize::new(0),
+            metrics,
+            draining: watch::Sender::new(false),
+            unscheduled_batches: AtomicUsize::new(0),
+            scheduling: AtomicBool::new(false),
         });
         tokio::spawn(scheduler.clone().process_withdraw_task(withdraw_receiver));
         tokio::spawn(
@@ -82,6 +329,14 @@ impl BalanceWithdrawScheduler {
     /// This function will be called at most once per consensus commit batch that all reads the same root accumulator version.
     /// If a consensus commit batch does not contain any withdraw reservations, it can skip calling this function.
     /// It must be called sequentially in order to correctly schedule withdraws.
+    ///
+    /// Batches must be sent in the order of their accumulator versions, because withdraws are
+    /// only scheduled ahead of settlement if they are guaranteed to be covered after every
+    /// withdraw at an earlier version that has been sent. The execution scheduler sends the
+    /// withdraws of each enqueued set of transactions in version order, and transactions are
+    /// enqueued in consensus commit (or checkpoint) order, whose accumulator versions never
+    /// decrease. A batch that is sent after a batch at a later version waits for its version to
+    /// be settled instead, see [NaiveBalanceWithdrawScheduler].
     pub fn schedule_withdraws(
         &self,
         accumulator_version: SequenceNumber,
@@ -92,35 +347,367 @@ impl BalanceWithdrawScheduler {
             accumulator_version, withdraws
         );
         let (reservations, receivers) = WithdrawReservations::new(accumulator_version, withdraws);
+        let sent_at = reservations.sent_at;
+        self.unscheduled_batches.fetch_add(1, Ordering::SeqCst);
         if let Err(err) = self.withdraw_sender.send(reservations) {
+            self.unscheduled_batches.fetch_sub(1, Ordering::SeqCst);
             tracing::error!("Failed to send withdraw reservations: {:?}", err);
         }
-        receivers
+
+        let Some(result_relay) = &self.result_relay else {
+            return receivers;
+        };
+
+        let (senders, relayed) = (0..receivers.len()).map(|_| oneshot::channel()).unzip();
+        let batch = RelayedBatch {
+            accumulator_version,
+            sent_at,
+            results: receivers,
+            senders,
+        };
+        if let Err(err) = result_relay.send(batch) {
+            tracing::error!("Failed to relay withdraw results: {:?}", err);
+        }
+        relayed
     }
 
     /// This function is called whenever a settlement transaction is executed.
     /// It is only called from checkpoint builder, once for each accumulator version, in order.
     pub fn settle_balances(&self, settlement: BalanceSettlement) {
-        if let Err(err) = self.settlement_sender.send(settlement) {
+        self.settle_balances_produced_at(settlement, Instant::now());
+    }
+
+    /// Like [Self::settle_balances], for a settlement whose balance changes were produced at
+    /// `produced_at`, which is when its application latency is measured from.
+    pub fn settle_balances_produced_at(&self, settlement: BalanceSettlement, produced_at: Instant) {
+        let pending = PendingSettlement {
+            settlement,
+            produced_at,
+            sent_at: Instant::now(),
+        };
+
+        let unapplied = self.unapplied_settlements.fetch_add(1, Ordering::SeqCst);
+        if let Err(err) = self.settlement_sender.send(pending) {
+            self.unapplied_settlements.fetch_sub(1, Ordering::SeqCst);
             tracing::error!("Failed to send balance settlement: {:?}", err);
+            return;
+        }
+
+        if unapplied > 0 {
+            if let Some(metrics) = &self.metrics {
+                metrics.queued_behind_unapplied.inc();
+            }
+        }
+    }
+
+    /// Like [Self::settle_balances_produced_at], for a settlement that advanced the accumulator
+    /// root to `settled_version`. Settlements that do not advance it past the version the
+    /// scheduler was created at are ignored: their balance changes were already in the store when
+    /// the scheduler started, and applying them again would move the scheduler's settled version
+    /// past the root's, making it treat withdraws at the next versions as already executed.
+    ///
+    /// This happens when the node restarts, because the checkpoint builder replays the
+    /// settlements of the checkpoints it had built but not written before the restart.
+    pub fn settle_balances_at(
+        &self,
+        settled_version: SequenceNumber,
+        settlement: BalanceSettlement,
+        produced_at: Instant,
+    ) {
+        if settled_version <= self.starting_accumulator_version {
+            debug!(
+                ?settled_version,
+                starting_version = ?self.starting_accumulator_version,
+                "Ignoring settlement that was applied before the scheduler started"
+            );
+            return;
+        }
+
+        self.settle_balances_produced_at(settlement, produced_at);
+    }
+
+    /// This function is called once a transaction at `accumulator_version` has executed, with
+    /// the amounts it deposited into each account according to its effects. It must not be
+    /// called before the transaction has executed, because the deposits of a transaction that
+    /// aborts are never settled.
+    pub fn record_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    ) {
+        if let Some(shadow) = &self.shadow {
+            shadow.record_deposits(accumulator_version, tx_digest, deposits.clone());
         }
+        self.inner
+            .record_deposits(accumulator_version, tx_digest, deposits);
+    }
+
+    /// Returns the amount reserved from the given account that is not yet reflected in
+    /// the settled balance.
+    pub fn get_reserved_balance(&self, account_id: &ObjectID) -> u64 {
+        self.inner.get_reserved_balance(account_id)
+    }
+
+    /// Returns the amounts reserved from, and deposited into, the given account that are not
+    /// yet reflected in the settled balance, and the settled version they are relative to.
+    pub(crate) fn get_account_reservations(&self, account_id: &ObjectID) -> AccountReservations {
+        self.inner.get_account_reservations(account_id)
+    }
+
+    /// Amend the reservations of a transaction before it executes, e.g. because its maximum
+    /// withdraw was re-estimated. Reductions are released immediately, while increases are only
+    /// accepted if they are guaranteed to fit in the account's balance without affecting any
+    /// other scheduled withdraw. The amendment is atomic: if any increase cannot be satisfied,
+    /// none of the reservations change.
+    ///
+    /// The transaction must already have been scheduled with sufficient balance, and its
+    /// accumulator version not yet settled. Like scheduling, amendments must be made in the same
+    /// order on every validator for them to reach the same decisions.
+    pub fn amend_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError> {
+        if let Some(shadow) = &self.shadow {
+            shadow.amend_reservation(tx_digest, new_reservations.clone());
+        }
+        let Some(cross_check) = &self.cross_check else {
+            return self.inner.amend_reservation(tx_digest, new_reservations);
+        };
+        self.inner
+            .amend_reservation(tx_digest, new_reservations.clone())?;
+        cross_check.amend_reservation(tx_digest, new_reservations);
+        Ok(())
+    }
+
+    /// Start or stop draining the scheduler, see [WithdrawDrainStatus]. Stopping schedules the
+    /// batches that were held back, in order.
+    pub fn set_draining(&self, draining: bool) -> WithdrawDrainStatus {
+        self.draining.send_replace(draining);
+        self.drain_status()
+    }
+
+    pub fn drain_status(&self) -> WithdrawDrainStatus {
+        let draining = *self.draining.borrow();
+        WithdrawDrainStatus {
+            draining,
+            unscheduled_batches: self.unscheduled_batches.load(Ordering::SeqCst),
+            drained: draining && !self.scheduling.load(Ordering::SeqCst) && self.inner.is_idle(),
+        }
+    }
+
+    /// Returns the withdraws that have been waiting for their accumulator version to be settled
+    /// while at least `min_settlements` versions were settled, in the order of their versions.
+    pub fn withdraws_awaiting_settlement(
+        &self,
+        min_settlements: u64,
+    ) -> Vec<WithdrawAwaitingSettlement> {
+        self.inner.withdraws_awaiting_settlement(min_settlements)
+    }
+
+    /// Subscribe to receipts for withdraws as their accumulator versions are settled. Only
+    /// receipts for settlements processed after subscribing are received.
+    pub fn subscribe_settlement_receipts(&self) -> broadcast::Receiver<SettlementReceipt> {
+        self.receipt_sender.subscribe()
+    }
+
+    /// Subscribe to the last accumulator version whose settlement has been applied. Settlements
+    /// that are ignored by [Self::settle_balances_at] do not change it.
+    pub fn subscribe_settled_version(&self) -> watch::Receiver<SequenceNumber> {
+        self.settled_version.subscribe()
     }
 
     async fn process_withdraw_task(
         self: Arc<Self>,
         mut withdraw_receiver: UnboundedReceiver<WithdrawReservations>,
     ) {
+        let mut draining = self.draining.subscribe();
         while let Some(event) = withdraw_receiver.recv().await {
+            loop {
+                self.scheduling.store(true, Ordering::SeqCst);
+                if !*draining.borrow_and_update() {
+                    break;
+                }
+
+                // Hold the batch, and every batch after it, until draining stops.
+                self.scheduling.store(false, Ordering::SeqCst);
+                debug!(
+                    "Holding withdraws at accumulator version {:?} while draining",
+                    event.accumulator_version
+                );
+                if draining.changed().await.is_err() {
+                    return;
+                }
+            }
+
+            let accumulator_version = event.accumulator_version;
+            let withdraws = (self.shadow.is_some() || self.cross_check.is_some())
+                .then(|| event.withdraws.clone());
             self.inner.schedule_withdraws(event).await;
+            if let Some(withdraws) = withdraws {
+                if let Some(cross_check) = &self.cross_check {
+                    cross_check.schedule_withdraws(accumulator_version, withdraws.clone());
+                }
+                if let Some(shadow) = &self.shadow {
+                    shadow.schedule_withdraws(accumulator_version, withdraws);
+                }
+            }
+
+            self.unscheduled_batches.fetch_sub(1, Ordering::SeqCst);
+            self.scheduling.store(false, Ordering::SeqCst);
         }
     }
 
     async fn process_settlement_task(
         self: Arc<Self>,
-        mut settlement_receiver: UnboundedReceiver<BalanceSettlement>,
+        mut settlement_receiver: UnboundedReceiver<PendingSettlement>,
     ) {
-        while let Some(settlement) = settlement_receiver.recv().await {
-            self.inner.settle_balances(settlement).await;
+        while let Some(pending) = settlement_receiver.recv().await {
+            let PendingSettlement {
+                settlement,
+                produced_at,
+                sent_at,
+            } = pending;
+
+            if let Some(metrics) = &self.metrics {
+                metrics
+                    .queue_latency
+                    .observe(sent_at.elapsed().as_secs_f64());
+            }
+
+            let shadow_settlement = self.shadow.as_ref().map(|_| settlement.clone());
+            self.balance_read
+                .settle_balances(&settlement.balance_changes);
+            let (receipts, summary) = self.inner.settle_balances(settlement).await;
+            if let Some(cross_check) = &self.cross_check {
+                cross_check.settle_balances();
+            }
+            self.unapplied_settlements.fetch_sub(1, Ordering::SeqCst);
+            if let Some(metrics) = &self.metrics {
+                metrics
+                    .application_latency
+                    .observe(produced_at.elapsed().as_secs_f64());
+            }
+
+            summary.log();
+            self.settled_version
+                .send_replace(summary.accumulator_version);
+            self.check_settlement_waits();
+            for receipt in receipts {
+                // Sending only fails if there are no subscribers.
+                let _ = self.receipt_sender.send(receipt);
+            }
+            if let (Some(shadow), Some(settlement)) = (&self.shadow, shadow_settlement) {
+                shadow.settle_balances(settlement);
+            }
+        }
+    }
+
+    /// Flag the withdraws that a settlement has just made wait too long, and report how many have
+    /// waited too long in total. Withdraws are only flagged once, when they reach the threshold,
+    /// because each settlement makes every waiting withdraw wait one more.
+    fn check_settlement_waits(&self) {
+        let waiting = self
+            .inner
+            .withdraws_awaiting_settlement(LONG_SETTLEMENT_WAIT_SETTLEMENTS);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.long_settlement_waits.set(waiting.len() as i64);
+        }
+
+        for withdraw in waiting
+            .iter()
+            .filter(|w| w.settlements_waited == LONG_SETTLEMENT_WAIT_SETTLEMENTS)
+        {
+            // Accounts can hold different coin types, so their shortfalls are reported
+            // separately, rather than summed.
+            let shortfalls: Vec<_> = withdraw
+                .blocking_accounts
+                .iter()
+                .map(|a| (a.account, a.shortfall()))
+                .collect();
+
+            warn!(
+                tx_digest = ?withdraw.tx_digest,
+                accumulator_version = withdraw.accumulator_version.value(),
+                settlements_waited = withdraw.settlements_waited,
+                shortfalls = ?shortfalls,
+                "Withdraw has been waiting long for its accumulator version to be settled",
+            );
+        }
+    }
+}
+
+/// Pass the results of every batch of withdraws sent to the scheduler on to its caller, as they
+/// arrive, once `observers` have seen them. A single task relays the results of every batch, so
+/// that observing results does not cost a task per batch.
+async fn relay_results(mut batches: UnboundedReceiver<RelayedBatch>, observers: ResultObservers) {
+    let mut pending = FuturesUnordered::new();
+    loop {
+        tokio::select! {
+            batch = batches.recv() => {
+                let Some(batch) = batch else {
+                    break;
+                };
+
+                let RelayedBatch {
+                    accumulator_version,
+                    sent_at,
+                    results,
+                    senders,
+                } = batch;
+                pending.extend(results.into_iter().zip(senders).map(|(result, sender)| {
+                    result.map(move |result| (accumulator_version, sent_at, result, sender))
+                }));
+            }
+
+            Some((accumulator_version, sent_at, result, sender)) = pending.next(),
+                if !pending.is_empty() =>
+            {
+                observers.relay(accumulator_version, sent_at, result, sender);
+            }
+        }
+    }
+
+    // The scheduler has been dropped, but the results of batches it already sent may still come
+    // in.
+    while let Some((accumulator_version, sent_at, result, sender)) = pending.next().await {
+        observers.relay(accumulator_version, sent_at, result, sender);
+    }
+}
+
+impl ResultObservers {
+    fn any(&self) -> bool {
+        self.reservation_wait.is_some() || self.cross_check.is_some() || self.shadow.is_some()
+    }
+
+    /// Observe the `result` of a withdraw at `accumulator_version` that was sent to the scheduler
+    /// at `sent_at`, and pass it on through `sender`.
+    fn relay(
+        &self,
+        accumulator_version: SequenceNumber,
+        sent_at: Instant,
+        result: Result<ScheduleResult, oneshot::error::RecvError>,
+        sender: oneshot::Sender<ScheduleResult>,
+    ) {
+        // The sender of a result is only dropped if the scheduler is shutting down, in which case
+        // the caller's receiver is dropped along with `sender`.
+        let Ok(result) = result else {
+            return;
+        };
+
+        if let Some(reservation_wait) = &self.reservation_wait {
+            reservation_wait
+                .with_label_values(&[result.status.as_str()])
+                .observe(sent_at.elapsed().as_secs_f64());
+        }
+        if let Some(cross_check) = &self.cross_check {
+            cross_check.check_live_result(&result);
+        }
+        if let Some(shadow) = &self.shadow {
+            shadow.observe_live_result(accumulator_version, &result);
         }
+        let _ = sender.send(result);
     }
 }
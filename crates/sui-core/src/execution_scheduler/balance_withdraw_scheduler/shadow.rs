@@ -0,0 +1,328 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use futures::{
+    stream::{FuturesUnordered, StreamExt},
+    FutureExt,
+};
+use mysten_metrics::monitored_mpsc::{channel, Receiver, Sender};
+use parking_lot::Mutex;
+use prometheus::IntCounterVec;
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+};
+use tokio::sync::mpsc::error::TrySendError;
+use tracing::{debug, warn};
+
+use crate::execution_scheduler::balance_withdraw_scheduler::{
+    balance_read::AccountBalanceRead,
+    naive_scheduler::NaiveBalanceWithdrawScheduler,
+    policy::WithdrawPolicy,
+    scheduler::{BalanceWithdrawSchedulerTrait, WithdrawReservations},
+    BalanceSettlement, ScheduleResult, ScheduleStatus, TxBalanceWithdraw, WithdrawSchedulerParams,
+};
+
+/// Number of commands that can be queued for the shadow scheduler. A shadow that falls further
+/// behind the live scheduler than this is stopped, rather than holding the live scheduler back.
+pub(super) const SHADOW_COMMAND_CHANNEL_CAPACITY: usize = 10_000;
+
+/// Number of versions a result is kept for after the shadow settles its version, waiting for
+/// the other scheduler's result for the same transaction. Both schedulers have decided every
+/// withdraw at a version by the time it is settled, and the live scheduler settles it first, so
+/// results are only left unmatched for longer if the other result was lost.
+const UNMATCHED_RESULT_VERSIONS: u64 = 2;
+
+/// Metrics comparing the decisions of a shadow scheduler with those of the live scheduler.
+#[derive(Clone)]
+pub(crate) struct ShadowSchedulerMetrics {
+    /// Results of the shadow scheduler, labelled by status.
+    pub results: IntCounterVec,
+    /// Transactions whose result from the shadow scheduler differs from their result from the
+    /// live scheduler, labelled by both statuses.
+    pub divergences: IntCounterVec,
+}
+
+/// A copy of the balance withdraw scheduler that is fed the same withdraws, settlements and
+/// deposits as the live scheduler, in the same order, but schedules them with alternative
+/// parameters.
+///
+/// Its results never reach execution: they are only compared with the live results and reported
+/// as metrics, so that operators can evaluate a change to the parameters on live traffic. Both
+/// schedulers read balances from the same store, so the shadow checks its withdraws against the
+/// balances that resulted from the live decisions, rather than a counterfactual history.
+///
+/// The shadow runs detached from the live scheduler, on its own task, which is sent the live
+/// scheduler's commands through a bounded channel. Balance reads are bounded by the version they
+/// are made at, so a shadow that lags behind still reads the balances the live scheduler read. If
+/// it falls too far behind, it is stopped for good, since it cannot skip a command and still be
+/// compared with the live scheduler.
+pub(crate) struct ShadowBalanceWithdrawScheduler {
+    commands: Sender<Command>,
+    comparison: Arc<Comparison>,
+}
+
+/// What the live scheduler was asked to do, for the shadow to do the same.
+enum Command {
+    Schedule(SequenceNumber, Vec<TxBalanceWithdraw>),
+    Settle(BalanceSettlement),
+    RecordDeposits(SequenceNumber, TransactionDigest, BTreeMap<ObjectID, u64>),
+    Amend(TransactionDigest, BTreeMap<ObjectID, u64>),
+}
+
+/// The results of both schedulers, matched up by transaction.
+struct Comparison {
+    /// Results of transactions that only one of the two schedulers has produced so far, keyed by
+    /// the accumulator version of their withdraws.
+    unmatched: Mutex<BTreeMap<SequenceNumber, HashMap<TransactionDigest, (Side, ScheduleStatus)>>>,
+    /// Whether the shadow was stopped because it fell too far behind.
+    stopped: AtomicBool,
+    metrics: ShadowSchedulerMetrics,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Side {
+    Live,
+    Shadow,
+}
+
+impl ShadowBalanceWithdrawScheduler {
+    pub fn new(
+        balance_read: Arc<dyn AccountBalanceRead>,
+        starting_accumulator_version: SequenceNumber,
+        params: WithdrawSchedulerParams,
+        policy: Arc<dyn WithdrawPolicy>,
+        metrics: ShadowSchedulerMetrics,
+    ) -> Arc<Self> {
+        let inner = NaiveBalanceWithdrawScheduler::new(
+            balance_read,
+            starting_accumulator_version,
+            params,
+            policy,
+        );
+        let (commands, receiver) = channel(
+            "withdraw_scheduler_shadow_commands",
+            SHADOW_COMMAND_CHANNEL_CAPACITY,
+        );
+        let comparison = Arc::new(Comparison {
+            unmatched: Mutex::new(BTreeMap::new()),
+            stopped: AtomicBool::new(false),
+            metrics,
+        });
+
+        // The task only holds on to what it needs, so that it stops once the shadow is dropped.
+        tokio::spawn(run(inner, comparison.clone(), receiver));
+        Arc::new(Self {
+            commands,
+            comparison,
+        })
+    }
+
+    /// Schedule a copy of withdraws that were sent to the live scheduler, in the same order.
+    pub fn schedule_withdraws(
+        &self,
+        accumulator_version: SequenceNumber,
+        withdraws: Vec<TxBalanceWithdraw>,
+    ) {
+        self.send(Command::Schedule(accumulator_version, withdraws));
+    }
+
+    pub fn settle_balances(&self, settlement: BalanceSettlement) {
+        self.send(Command::Settle(settlement));
+    }
+
+    pub fn record_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        tx_digest: TransactionDigest,
+        deposits: BTreeMap<ObjectID, u64>,
+    ) {
+        self.send(Command::RecordDeposits(
+            accumulator_version,
+            tx_digest,
+            deposits,
+        ));
+    }
+
+    /// Apply an amendment that was made to the live scheduler. The shadow may not have
+    /// scheduled the transaction with sufficient balance, or may not be able to satisfy the
+    /// amendment, in which case its reservations are left as they are.
+    pub fn amend_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) {
+        self.send(Command::Amend(*tx_digest, new_reservations));
+    }
+
+    /// Compare a result of the live scheduler for a withdraw at `accumulator_version` with the
+    /// shadow's result for the same transaction, once both are known.
+    pub fn observe_live_result(
+        &self,
+        accumulator_version: SequenceNumber,
+        result: &ScheduleResult,
+    ) {
+        self.comparison
+            .record(accumulator_version, Side::Live, result.clone());
+    }
+
+    fn send(&self, command: Command) {
+        if self.comparison.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+
+        match self.commands.try_send(command) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!(
+                    "Shadow withdraw scheduler fell more than {} commands behind, stopping it",
+                    SHADOW_COMMAND_CHANNEL_CAPACITY
+                );
+                self.comparison.stop();
+            }
+            // The task only stops once the shadow has been stopped.
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.comparison.stopped.load(Ordering::Relaxed)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn unmatched_results(&self) -> usize {
+        self.comparison
+            .unmatched
+            .lock()
+            .values()
+            .map(HashMap::len)
+            .sum()
+    }
+}
+
+/// Run the commands sent to the shadow on `inner`, one at a time, recording its results.
+async fn run(
+    inner: Arc<NaiveBalanceWithdrawScheduler>,
+    comparison: Arc<Comparison>,
+    mut commands: Receiver<Command>,
+) {
+    let mut pending = FuturesUnordered::new();
+    while let Some(command) = commands.recv().await {
+        if comparison.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+
+        match command {
+            Command::Schedule(accumulator_version, withdraws) => {
+                let (reservations, receivers) =
+                    WithdrawReservations::new(accumulator_version, withdraws);
+                inner.schedule_withdraws(reservations).await;
+                pending.extend(
+                    receivers
+                        .into_iter()
+                        .map(|receiver| receiver.map(move |result| (accumulator_version, result))),
+                );
+            }
+
+            Command::Settle(settlement) => {
+                let (_, summary) = inner.settle_balances(settlement).await;
+                comparison.expire(summary.accumulator_version);
+            }
+
+            Command::RecordDeposits(accumulator_version, tx_digest, deposits) => {
+                inner.record_deposits(accumulator_version, tx_digest, deposits);
+            }
+
+            Command::Amend(tx_digest, new_reservations) => {
+                let _ = inner.amend_reservation(&tx_digest, new_reservations);
+            }
+        }
+
+        // Results are sent by the command that decides them, so every result there is to record
+        // is ready by now.
+        while let Some(Some((accumulator_version, result))) = pending.next().now_or_never() {
+            // The sender is only dropped if the scheduler is shutting down.
+            if let Ok(result) = result {
+                comparison.record(accumulator_version, Side::Shadow, result);
+            }
+        }
+    }
+}
+
+impl Comparison {
+    fn record(&self, accumulator_version: SequenceNumber, side: Side, result: ScheduleResult) {
+        if self.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if side == Side::Shadow {
+            self.metrics
+                .results
+                .with_label_values(&[result.status.as_str()])
+                .inc();
+        }
+
+        let mut unmatched = self.unmatched.lock();
+        let at_version = unmatched.entry(accumulator_version).or_default();
+        let Some((other_side, other_status)) = at_version.remove(&result.tx_digest) else {
+            at_version.insert(result.tx_digest, (side, result.status));
+            return;
+        };
+
+        if at_version.is_empty() {
+            unmatched.remove(&accumulator_version);
+        }
+
+        debug_assert_ne!(side, other_side);
+        let (live, shadow) = match side {
+            Side::Live => (result.status, other_status),
+            Side::Shadow => (other_status, result.status),
+        };
+
+        if live != shadow {
+            self.metrics
+                .divergences
+                .with_label_values(&[live.as_str(), shadow.as_str()])
+                .inc();
+        }
+    }
+
+    /// Forget the unmatched results at versions that the shadow settled long enough before
+    /// `settled_version` that the other result is never coming.
+    fn expire(&self, settled_version: SequenceNumber) {
+        let oldest = SequenceNumber::from_u64(
+            settled_version
+                .value()
+                .saturating_sub(UNMATCHED_RESULT_VERSIONS),
+        );
+
+        let mut unmatched = self.unmatched.lock();
+        let kept = unmatched.split_off(&oldest);
+        let expired: usize = mem::replace(&mut *unmatched, kept)
+            .values()
+            .map(HashMap::len)
+            .sum();
+
+        if expired > 0 {
+            debug!(
+                ?settled_version,
+                expired, "Forgot shadow scheduler results that were never matched"
+            );
+        }
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.unmatched.lock().clear();
+    }
+}
@@ -1,20 +1,37 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::execution_scheduler::balance_withdraw_scheduler::workload::Workload;
 use crate::execution_scheduler::balance_withdraw_scheduler::ScheduleResult;
 use crate::execution_scheduler::balance_withdraw_scheduler::{
-    balance_read::MockBalanceRead, scheduler::BalanceWithdrawScheduler, BalanceSettlement,
-    ScheduleStatus, TxBalanceWithdraw,
+    balance_read::{AccountBalanceRead, MockBalanceRead},
+    cross_check::ReferenceWithdrawScheduler,
+    naive_scheduler::{NaiveBalanceWithdrawScheduler, SchedulerState},
+    policy::{AllowAllWithdraws, WithdrawPolicy},
+    scheduler::{
+        BalanceWithdrawScheduler, BalanceWithdrawSchedulerTrait, SettlementMetrics,
+        WithdrawReservations,
+    },
+    shadow::{
+        ShadowBalanceWithdrawScheduler, ShadowSchedulerMetrics, SHADOW_COMMAND_CHANNEL_CAPACITY,
+    },
+    AccountReservations, AccountShortfall, AmendReservationError, BalanceSettlement,
+    ScheduleStatus, SettlementReceipt, SettlementSummary, TxBalanceWithdraw,
+    TxBalanceWithdrawError, WithdrawAwaitingSettlement, WithdrawDrainStatus,
+    WithdrawSchedulerParams,
 };
 use futures::stream::{FuturesUnordered, StreamExt};
-use rand::{seq::SliceRandom, Rng};
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use sui_types::{
     base_types::{ObjectID, SequenceNumber},
     digests::TransactionDigest,
 };
 use tokio::sync::oneshot;
-use tokio::time::timeout;
+use tokio::time::{timeout, Instant};
 
 #[derive(Clone)]
 struct TestScheduler {
@@ -32,10 +49,26 @@ impl TestScheduler {
         }
     }
 
+    /// Like [Self::new], but every decision of the scheduler is cross-checked against the
+    /// reference model.
+    fn new_cross_checked(
+        init_version: SequenceNumber,
+        init_balances: BTreeMap<ObjectID, u64>,
+    ) -> Self {
+        let mock_read = Arc::new(MockBalanceRead::new(init_version, init_balances));
+        let scheduler =
+            BalanceWithdrawScheduler::new_with_cross_check(mock_read.clone(), init_version);
+        Self {
+            mock_read,
+            scheduler,
+        }
+    }
+
     fn settle_balance_changes(&self, changes: BTreeMap<ObjectID, i128>) {
         self.mock_read.settle_balance_changes(changes.clone());
         self.scheduler.settle_balances(BalanceSettlement {
             balance_changes: changes,
+            withdraws: BTreeMap::new(),
         });
     }
 }
@@ -98,6 +131,55 @@ async fn test_basic_insufficient_balance() {
     .await;
 }
 
+#[tokio::test]
+async fn test_insufficient_balance_details() {
+    let init_version = SequenceNumber::from_u64(0);
+    let rich = ObjectID::random();
+    let poor = ObjectID::random();
+    let broke = ObjectID::random();
+    let test = TestScheduler::new(
+        init_version,
+        BTreeMap::from([(rich, 100), (poor, 10), (broke, 0)]),
+    );
+
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(rich, 50), (poor, 20), (broke, 5)]),
+    };
+
+    let mut receivers = test
+        .scheduler
+        .schedule_withdraws(init_version, vec![withdraw.clone()]);
+    let result = timeout(Duration::from_secs(3), receivers.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    // Every account that falls short is reported, not just the first.
+    let mut expected = vec![
+        AccountShortfall {
+            account: poor,
+            requested: 20,
+            available: 10,
+        },
+        AccountShortfall {
+            account: broke,
+            requested: 5,
+            available: 0,
+        },
+    ];
+    expected.sort_by_key(|shortfall| shortfall.account);
+    assert_eq!(
+        result,
+        ScheduleResult {
+            tx_digest: withdraw.tx_digest,
+            status: ScheduleStatus::InsufficientBalance,
+            details: expected,
+        }
+    );
+}
+
 #[tokio::test]
 async fn test_already_executed() {
     let init_version = SequenceNumber::from_u64(0);
@@ -289,6 +371,78 @@ async fn test_already_executed_with_sequential_settlements() {
     .await;
 }
 
+#[tokio::test]
+async fn test_replayed_settlements_after_restart() {
+    // The scheduler starts from the accumulator version in the store, which already includes the
+    // settlements that the checkpoint builder replays after a restart.
+    let v5 = SequenceNumber::from_u64(5);
+    let account = ObjectID::random();
+    let test = TestScheduler::new(v5, BTreeMap::from([(account, 100)]));
+    let mut settled_version = test.scheduler.subscribe_settled_version();
+
+    for version in [4, 5] {
+        test.scheduler.settle_balances_at(
+            SequenceNumber::from_u64(version),
+            BalanceSettlement {
+                balance_changes: BTreeMap::from([(account, -20i128)]),
+                withdraws: BTreeMap::new(),
+            },
+            Instant::now(),
+        );
+    }
+    // Replayed settlements are ignored without being sent to the scheduler.
+    assert_eq!(*settled_version.borrow_and_update(), v5);
+
+    // Had the replayed settlements been applied, withdraws at the starting version would be
+    // treated as already executed.
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 100)]),
+    };
+
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v5, vec![withdraw.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The first settlement past the starting version is applied as usual.
+    test.mock_read
+        .settle_balance_changes(BTreeMap::from([(account, -100i128)]));
+    test.scheduler.settle_balances_at(
+        v5.next(),
+        BalanceSettlement {
+            balance_changes: BTreeMap::from([(account, -100i128)]),
+            withdraws: BTreeMap::new(),
+        },
+        Instant::now(),
+    );
+    timeout(
+        Duration::from_secs(3),
+        settled_version.wait_for(|version| *version == v5.next()),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 10)]),
+    };
+
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v5, vec![withdraw.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw.tx_digest, ScheduleStatus::AlreadyExecuted)]),
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn test_basic_settlement() {
     let init_version = SequenceNumber::from_u64(0);
@@ -505,104 +659,1922 @@ async fn test_settlement_far_ahead_of_schedule() {
 }
 
 #[tokio::test]
-async fn stress_test() {
-    let num_accounts = 5;
-    let num_transactions = 10000;
+async fn test_reserved_balance_released_on_settlement() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account1, 100), (account2, 100)]));
 
-    let mut version = SequenceNumber::from_u64(0);
-    let accounts = (0..num_accounts)
-        .map(|_| ObjectID::random())
-        .collect::<Vec<_>>();
-    let mut rng = rand::thread_rng();
-    let init_balances = accounts
-        .iter()
-        .filter_map(|account_id| {
-            if rng.gen_bool(0.7) {
-                Some((*account_id, rng.gen_range(0..20)))
-            } else {
-                None
-            }
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 30), (account2, 10)]),
+    };
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 50)]),
+    };
+    // Insufficient withdraws must not count towards the reserved amount.
+    let withdraw3 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 50)]),
+    };
+
+    let receivers = test.scheduler.schedule_withdraws(
+        v0,
+        vec![withdraw1.clone(), withdraw2.clone(), withdraw3.clone()],
+    );
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (withdraw1.tx_digest, ScheduleStatus::SufficientBalance),
+            (withdraw2.tx_digest, ScheduleStatus::SufficientBalance),
+            (withdraw3.tx_digest, ScheduleStatus::InsufficientBalance),
+        ]),
+    )
+    .await;
+
+    assert_eq!(test.scheduler.get_reserved_balance(&account1), 80);
+    assert_eq!(test.scheduler.get_reserved_balance(&account2), 10);
+
+    test.settle_balance_changes(BTreeMap::from([(account1, -80), (account2, -10)]));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(test.scheduler.get_reserved_balance(&account1), 0);
+    assert_eq!(test.scheduler.get_reserved_balance(&account2), 0);
+}
+
+#[tokio::test]
+async fn test_account_reservations_include_pending_deposits() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let other = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account, 100)]));
+
+    // Deposits by transactions that have executed, at v0 and v1, neither of which is settled.
+    test.scheduler.record_deposits(
+        v0,
+        TransactionDigest::random(),
+        BTreeMap::from([(account, 40)]),
+    );
+    test.scheduler.record_deposits(
+        v1,
+        TransactionDigest::random(),
+        BTreeMap::from([(account, 10)]),
+    );
+
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 30)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v0, vec![withdraw.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    assert_eq!(
+        test.scheduler.get_account_reservations(&account),
+        AccountReservations {
+            settled_version: v0,
+            reserved: 30,
+            pending_deposits: 50,
+        }
+    );
+
+    // Settling v0 settles its deposit and withdraw, which are no longer pending, but not the
+    // deposit at v1.
+    test.settle_balance_changes(BTreeMap::from([(account, 10)]));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(
+        test.scheduler.get_account_reservations(&account),
+        AccountReservations {
+            settled_version: v1,
+            reserved: 0,
+            pending_deposits: 10,
+        }
+    );
+    assert_eq!(
+        test.scheduler.get_account_reservations(&other),
+        AccountReservations {
+            settled_version: v1,
+            ..Default::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_settlement_summary() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams {
+            schedule_ahead_of_settlement: false,
+            ..Default::default()
+        },
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = |amount| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, amount)]),
+    };
+
+    let withdraw1 = withdraw(60);
+    let (reservations, receivers) = WithdrawReservations::new(v0, vec![withdraw1.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // Both withdraws wait for v1 to be settled, when only the first of them fits.
+    let (withdraw2, withdraw3) = (withdraw(30), withdraw(50));
+    let (reservations, receivers) =
+        WithdrawReservations::new(v1, vec![withdraw2.clone(), withdraw3.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+
+    let changes = BTreeMap::from([(account, -60)]);
+    mock_read.settle_balance_changes(changes.clone());
+    let (_, summary) = scheduler
+        .settle_balances(BalanceSettlement {
+            balance_changes: changes,
+            withdraws: BTreeMap::new(),
         })
-        .collect::<BTreeMap<_, _>>();
+        .await;
 
-    let mut withdraws = Vec::new();
-    let mut settlements = Vec::new();
-    let mut cur_reservations = Vec::new();
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (withdraw2.tx_digest, ScheduleStatus::SufficientBalance),
+            (withdraw3.tx_digest, ScheduleStatus::InsufficientBalance),
+        ]),
+    )
+    .await;
 
-    for idx in 0..num_transactions {
-        let num_accounts = rng.gen_range(1..3);
-        let account_ids = accounts
-            .choose_multiple(&mut rng, num_accounts)
-            .cloned()
-            .collect::<Vec<_>>();
-        let reservations = account_ids
+    assert_eq!(
+        summary,
+        SettlementSummary {
+            accumulator_version: v1,
+            accounts_settled: 1,
+            total_credited: 0,
+            total_debited: 60,
+            sufficient: 1,
+            insufficient: 1,
+            released: 1,
+            reserved_txs: 1,
+            pending_txs: 0,
+            deferred_batches: 0,
+            // All three withdraws are reported, including the one at v0 that was decided after
+            // v0 was settled.
+            scheduled_withdraws: 3,
+            rejected_withdraws: 1,
+            // How long the withdraws waited depends on timing.
+            avg_reservation_wait: summary.avg_reservation_wait,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_reservation_wait_measured_from_send() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    );
+
+    // The withdraw is queued for a while before the scheduler receives it, which the summary
+    // counts as waiting, like the reservation wait metric does.
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 10)]),
+    };
+    let (reservations, receivers) = WithdrawReservations::new(v0, vec![withdraw.clone()]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    mock_read.settle_balance_changes(BTreeMap::from([(account, -10)]));
+    let (_, summary) = scheduler
+        .settle_balances(BalanceSettlement {
+            balance_changes: BTreeMap::from([(account, -10)]),
+            withdraws: BTreeMap::new(),
+        })
+        .await;
+
+    assert_eq!(summary.scheduled_withdraws, 1);
+    assert!(summary.avg_reservation_wait >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_late_withdraws() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams {
+            late_withdraw_versions: 2,
+            ..Default::default()
+        },
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = |amount| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, amount)]),
+    };
+
+    let settle = |changes: BTreeMap<ObjectID, i128>| {
+        mock_read.settle_balance_changes(changes.clone());
+        scheduler.settle_balances(BalanceSettlement {
+            balance_changes: changes,
+            withdraws: BTreeMap::new(),
+        })
+    };
+
+    let schedule = |withdraws: Vec<TxBalanceWithdraw>, expected: Vec<ScheduleStatus>| {
+        let expected = withdraws
             .iter()
-            .map(|account_id| (*account_id, rng.gen_range(1..10)))
-            .collect::<BTreeMap<_, _>>();
-        cur_reservations.push(TxBalanceWithdraw {
-            tx_digest: TransactionDigest::random(),
-            reservations,
-        });
-        if rng.gen_bool(0.2) || idx == num_transactions - 1 {
-            // Every now and then we generate a settlement to advance the version.
-            // We don't really settle any balance changes here, as this test
-            // is primarily focusing on the scheduler's ability to handle
-            // random combinations ofwithdraws reservations.
-            withdraws.push((version, std::mem::take(&mut cur_reservations)));
-            version = version.next();
-            settlements.push((version, BTreeMap::new()));
+            .map(|withdraw| withdraw.tx_digest)
+            .zip(expected)
+            .collect();
+        let (reservations, receivers) = WithdrawReservations::new(v0, withdraws);
+        let scheduler = scheduler.clone();
+        async move {
+            scheduler.schedule_withdraws(reservations).await;
+            wait_for_results(receivers, expected).await;
         }
-    }
+    };
 
-    // Run through the scheduler many times and check that the results are always the same.
-    let mut expected_results = None;
-    let mut handles = Vec::new();
+    let withdraw1 = withdraw(60);
+    schedule(
+        vec![withdraw1.clone()],
+        vec![ScheduleStatus::SufficientBalance],
+    )
+    .await;
+    settle(BTreeMap::from([(account, -60)])).await;
+
+    // A late batch at v0 is checked on its own against the balance at v0, as it would have been
+    // on time, and a transaction that was already scheduled at v0 is still treated as executed.
+    schedule(
+        vec![withdraw(30), withdraw(50), withdraw(30), withdraw1.clone()],
+        vec![
+            ScheduleStatus::SufficientBalance,
+            ScheduleStatus::SufficientBalance,
+            ScheduleStatus::InsufficientBalance,
+            ScheduleStatus::AlreadyExecuted,
+        ],
+    )
+    .await;
 
-    // Spawn 10 concurrent tasks
-    for _ in 0..10 {
-        let init_balances = init_balances.clone();
-        let settlements = settlements.clone();
-        let withdraws = withdraws.clone();
+    // Once v0 is no longer among the most recently settled versions, it is too late.
+    settle(BTreeMap::new()).await;
+    settle(BTreeMap::new()).await;
+    schedule(vec![withdraw(10)], vec![ScheduleStatus::AlreadyExecuted]).await;
+}
 
-        let handle = tokio::spawn(async move {
-            let test = TestScheduler::new(version, init_balances);
+/// Withdraws that arrive after their version was settled get the same decisions as they would
+/// have got had they arrived on time: every batch at a version is checked on its own against the
+/// balances at that version, whenever it arrives.
+#[test]
+fn test_late_decisions_match_on_time_decisions() {
+    let v0 = SequenceNumber::from_u64(0);
+    let accounts: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+    let init_balances: BTreeMap<_, _> = accounts.iter().map(|account| (*account, 100)).collect();
 
-            // Start a separate thread to run all settlements on the scheduler.
-            let test_clone = test.clone();
-            let settlements = settlements.clone();
-            let settle_task = tokio::spawn(async move {
-                for (_version, balance_changes) in settlements {
-                    test_clone.settle_balance_changes(balance_changes);
-                    tokio::time::sleep(Duration::from_millis(5)).await;
-                }
-            });
+    // Three batches that each withdraw 40 from the same account fit in its balance of 100 on
+    // their own, but not together. Within a batch, withdraws do add up.
+    let withdraw = |amount| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(accounts[0], amount)]),
+    };
+    let mut batches = vec![
+        vec![withdraw(40)],
+        vec![withdraw(40)],
+        vec![withdraw(40)],
+        vec![withdraw(60), withdraw(60)],
+    ];
+
+    let mut rng = StdRng::seed_from_u64(0);
+    batches.extend((0..4).map(|_| {
+        (0..5)
+            .map(|_| TxBalanceWithdraw {
+                tx_digest: TransactionDigest::random(),
+                reservations: (0..rng.gen_range(1..=2))
+                    .map(|_| (accounts[rng.gen_range(0..3)], rng.gen_range(1..=40)))
+                    .collect(),
+            })
+            .collect()
+    }));
+
+    let new_scheduler = || {
+        let mock_read = Arc::new(MockBalanceRead::new(v0, init_balances.clone()));
+        let state = SchedulerState::new(
+            mock_read.clone(),
+            v0,
+            WithdrawSchedulerParams {
+                late_withdraw_versions: 2,
+                ..Default::default()
+            },
+            Arc::new(AllowAllWithdraws),
+        );
+        (mock_read, state)
+    };
 
-            let mut all_receivers = FuturesUnordered::new();
-            for (version, withdraws) in withdraws {
-                let receivers = test.scheduler.schedule_withdraws(version, withdraws);
-                tokio::time::sleep(Duration::from_millis(5)).await;
-                all_receivers.extend(receivers);
-            }
-            // Wait for the settle task to finish.
-            settle_task.await.unwrap();
+    let schedule = |state: &SchedulerState, batch: &[TxBalanceWithdraw]| {
+        let (reservations, receivers) = WithdrawReservations::new(v0, batch.to_vec());
+        state.schedule(reservations);
+        receivers
+            .into_iter()
+            .map(|mut receiver| {
+                let result = receiver.try_recv().unwrap();
+                (result.tx_digest, result.status)
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
 
-            let mut results = BTreeMap::new();
-            while let Some(result) = all_receivers.next().await {
-                let result = result.unwrap();
-                results.insert(result.tx_digest, result.status);
-            }
-            results
-        });
-        handles.push(handle);
+    // Every batch arrives before v0 is settled.
+    let (_, on_time) = new_scheduler();
+    let expected: BTreeMap<_, _> = batches
+        .iter()
+        .flat_map(|batch| schedule(&on_time, batch))
+        .collect();
+
+    // Only the first batch arrives before v0 is settled, the rest after one or two more
+    // settlements, which change the balances.
+    let (mock_read, late) = new_scheduler();
+    let mut actual = schedule(&late, &batches[0]);
+    mock_read.settle_balance_changes(BTreeMap::from([(accounts[0], 25)]));
+    late.settle();
+    actual.extend(schedule(&late, &batches[1]));
+    mock_read.settle_balance_changes(BTreeMap::from([(accounts[1], -50)]));
+    late.settle();
+    actual.extend(batches[2..].iter().flat_map(|batch| schedule(&late, batch)));
+
+    assert_eq!(actual, expected);
+    for batch in &batches[..3] {
+        assert_eq!(
+            expected[&batch[0].tx_digest],
+            ScheduleStatus::SufficientBalance
+        );
     }
+    assert_eq!(
+        expected[&batches[3][0].tx_digest],
+        ScheduleStatus::SufficientBalance
+    );
+    assert_eq!(
+        expected[&batches[3][1].tx_digest],
+        ScheduleStatus::InsufficientBalance
+    );
+}
 
-    // Wait for all tasks to complete and compare results
-    for handle in handles {
-        let results = handle.await.unwrap();
-        if expected_results.is_none() {
-            expected_results = Some(results);
-        } else {
-            assert_eq!(expected_results, Some(results));
+#[tokio::test]
+async fn test_withdraws_awaiting_settlement() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v3 = SequenceNumber::from_u64(3);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = |amount| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, amount)]),
+    };
+
+    let withdraw1 = withdraw(60);
+    let (reservations, receivers) = WithdrawReservations::new(v0, vec![withdraw1.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The first withdraw's reservation leaves at most 40 for the second, so it waits for v3 to
+    // be settled.
+    let withdraw2 = withdraw(50);
+    let (reservations, receivers) = WithdrawReservations::new(v3, vec![withdraw2.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+
+    let waiting = |settlements_waited| WithdrawAwaitingSettlement {
+        tx_digest: withdraw2.tx_digest,
+        accumulator_version: v3,
+        settlements_waited,
+        blocking_accounts: vec![AccountShortfall {
+            account,
+            requested: 50,
+            available: 40,
+        }],
+    };
+
+    assert_eq!(scheduler.withdraws_awaiting_settlement(0), vec![waiting(0)]);
+    assert!(scheduler.withdraws_awaiting_settlement(1).is_empty());
+
+    for (i, changes) in [
+        BTreeMap::from([(account, -60)]),
+        BTreeMap::new(),
+        BTreeMap::new(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let expected = if i >= 2 { vec![waiting(2)] } else { vec![] };
+        assert_eq!(scheduler.withdraws_awaiting_settlement(2), expected);
+
+        mock_read.settle_balance_changes(changes.clone());
+        scheduler
+            .settle_balances(BalanceSettlement {
+                balance_changes: changes,
+                withdraws: BTreeMap::new(),
+            })
+            .await;
+    }
+
+    // Once v3 is settled, the withdraw is scheduled, and no longer waiting.
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::InsufficientBalance)]),
+    )
+    .await;
+    assert!(scheduler.withdraws_awaiting_settlement(0).is_empty());
+}
+
+/// Freezes an account from a given accumulator version onwards.
+struct FreezeAccount {
+    account: ObjectID,
+    from: SequenceNumber,
+}
+
+impl WithdrawPolicy for FreezeAccount {
+    fn allows_withdraw(&self, account_id: &ObjectID, accumulator_version: SequenceNumber) -> bool {
+        *account_id != self.account || accumulator_version < self.from
+    }
+}
+
+#[tokio::test]
+async fn test_withdraw_policy() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(
+        v0,
+        BTreeMap::from([(account1, 100), (account2, 100)]),
+    ));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(FreezeAccount {
+            account: account1,
+            from: v1,
+        }),
+    );
+
+    let withdraw = |reservations: &[(ObjectID, u64)]| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: reservations.iter().copied().collect(),
+    };
+
+    // The account is not frozen yet.
+    let withdraw1 = withdraw(&[(account1, 10)]);
+    let (reservations, receivers) = WithdrawReservations::new(v0, vec![withdraw1.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // Once it is, withdraws that touch it are rejected, even though it has enough balance, and
+    // even if they also withdraw from accounts that are not frozen. Rejected withdraws do not
+    // reserve anything.
+    let withdraw2 = withdraw(&[(account1, 10)]);
+    let withdraw3 = withdraw(&[(account1, 10), (account2, 10)]);
+    let withdraw4 = withdraw(&[(account2, 20)]);
+    let (reservations, receivers) = WithdrawReservations::new(
+        v1,
+        vec![withdraw2.clone(), withdraw3.clone(), withdraw4.clone()],
+    );
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (withdraw2.tx_digest, ScheduleStatus::PolicyRejected),
+            (withdraw3.tx_digest, ScheduleStatus::PolicyRejected),
+            (withdraw4.tx_digest, ScheduleStatus::SufficientBalance),
+        ]),
+    )
+    .await;
+
+    assert_eq!(scheduler.get_reserved_balance(&account1), 10);
+    assert_eq!(scheduler.get_reserved_balance(&account2), 20);
+}
+
+#[tokio::test]
+async fn test_max_reservation_per_transaction() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(
+        v0,
+        BTreeMap::from([(account1, 100), (account2, 100)]),
+    ));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams {
+            max_reservation_per_transaction: Some(50),
+            ..Default::default()
+        },
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = |reservations: &[(ObjectID, u64)]| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: reservations.iter().copied().collect(),
+    };
+
+    // The limit applies to the total across accounts, even though each account has enough
+    // balance and no single reservation exceeds it. Rejected withdraws do not reserve anything.
+    let withdraw1 = withdraw(&[(account1, 30), (account2, 20)]);
+    let withdraw2 = withdraw(&[(account1, 30), (account2, 30)]);
+    let withdraw3 = withdraw(&[(account2, 51)]);
+    let (reservations, receivers) = WithdrawReservations::new(
+        v0,
+        vec![withdraw1.clone(), withdraw2.clone(), withdraw3.clone()],
+    );
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (withdraw1.tx_digest, ScheduleStatus::SufficientBalance),
+            (withdraw2.tx_digest, ScheduleStatus::Rejected),
+            (withdraw3.tx_digest, ScheduleStatus::Rejected),
+        ]),
+    )
+    .await;
+
+    assert_eq!(scheduler.get_reserved_balance(&account1), 30);
+    assert_eq!(scheduler.get_reserved_balance(&account2), 20);
+
+    // Amendments cannot take a transaction over the limit either.
+    assert_eq!(
+        scheduler.amend_reservation(
+            &withdraw1.tx_digest,
+            BTreeMap::from([(account1, 30), (account2, 21)]),
+        ),
+        Err(AmendReservationError::ExceedsTransactionLimit {
+            total: 51,
+            limit: 50,
+        })
+    );
+    assert_eq!(scheduler.get_reserved_balance(&account2), 20);
+}
+
+#[tokio::test]
+async fn test_drain() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account, 100)]));
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 50)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v0, vec![withdraw1.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The first withdraw's reservation has not settled yet.
+    let status = test.scheduler.set_draining(true);
+    assert_eq!(
+        status,
+        WithdrawDrainStatus {
+            draining: true,
+            unscheduled_batches: 0,
+            drained: false,
+        }
+    );
+
+    // New withdraws are held back while draining.
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 30)]),
+    };
+    let mut receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw2.clone()]);
+    assert!(timeout(Duration::from_millis(50), receivers.next())
+        .await
+        .is_err());
+    assert_eq!(test.scheduler.drain_status().unscheduled_batches, 1);
+
+    test.settle_balance_changes(BTreeMap::from([(account, -50)]));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        test.scheduler.drain_status(),
+        WithdrawDrainStatus {
+            draining: true,
+            unscheduled_batches: 1,
+            drained: true,
+        }
+    );
+
+    // Held withdraws are scheduled once draining stops, against the balance they would have
+    // seen anyway.
+    test.scheduler.set_draining(false);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(test.scheduler.drain_status().unscheduled_batches, 0);
+}
+
+/// Schedule `withdraws` one at a time against `balances`, as if each of them waited for its
+/// accumulator version to be settled.
+fn schedule_sequentially(
+    balances: &BTreeMap<ObjectID, u64>,
+    withdraws: &[TxBalanceWithdraw],
+) -> BTreeMap<TransactionDigest, ScheduleStatus> {
+    let mut cur_balances = balances.clone();
+    let mut results = BTreeMap::new();
+    for withdraw in withdraws {
+        let sufficient = withdraw.reservations.iter().all(|(account, reservation)| {
+            cur_balances.get(account).copied().unwrap_or_default() >= *reservation
+        });
+        let status = if sufficient {
+            for (account, reservation) in &withdraw.reservations {
+                *cur_balances.get_mut(account).unwrap() -= *reservation;
+            }
+            ScheduleStatus::SufficientBalance
+        } else {
+            ScheduleStatus::InsufficientBalance
+        };
+        results.insert(withdraw.tx_digest, status);
+    }
+    results
+}
+
+#[tokio::test]
+async fn test_schedule_ahead_of_settlement() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account1, 100), (account2, 100)]));
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 60)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v0, vec![withdraw1.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // Nothing at v0 touches account2, so a withdraw from it at v1 does not need to wait for
+    // v0 to be settled.
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account2, 30)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw2.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(test.scheduler.get_reserved_balance(&account2), 30);
+
+    // The withdraw at v0 may take 60 from account1, leaving only 40, so this one has to wait.
+    let withdraw3 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 60)]),
+    };
+    let mut receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw3.clone()]);
+    assert!(timeout(Duration::from_millis(100), receivers.next())
+        .await
+        .is_err());
+
+    // Once v0 is settled with a deposit into account1, there is enough left for it.
+    test.settle_balance_changes(BTreeMap::from([(account1, -30i128)]));
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw3.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(test.scheduler.get_reserved_balance(&account1), 60);
+}
+
+#[tokio::test]
+async fn test_schedule_ahead_with_deposits() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let v2 = v1.next();
+    let account = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account, 10)]));
+
+    // A deposit at v0 is settled before v1, so withdraws at v1 can count on it, but a deposit at
+    // v1 is not.
+    test.scheduler.record_deposits(
+        v0,
+        TransactionDigest::random(),
+        BTreeMap::from([(account, 90)]),
+    );
+    test.scheduler.record_deposits(
+        v1,
+        TransactionDigest::random(),
+        BTreeMap::from([(account, 50)]),
+    );
+
+    // The first withdraw fits in the settled balance plus the deposit at v0, but then the second
+    // does not, and has to wait to be checked against the settled balance.
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 70)]),
+    };
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 40)]),
+    };
+    let mut receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw1.clone(), withdraw2.clone()]);
+    let result = timeout(Duration::from_secs(3), receivers.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        result,
+        ScheduleResult {
+            tx_digest: withdraw1.tx_digest,
+            status: ScheduleStatus::SufficientBalance,
+            details: vec![],
+        }
+    );
+    assert!(timeout(Duration::from_millis(100), receivers.next())
+        .await
+        .is_err());
+
+    // A withdraw at v2 can count on both deposits, less everything that may be taken at v1.
+    let withdraw3 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 40)]),
+    };
+    let receivers3 = test
+        .scheduler
+        .schedule_withdraws(v2, vec![withdraw3.clone()]);
+    wait_for_results(
+        receivers3,
+        BTreeMap::from([(withdraw3.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // Once v0 is settled, the second withdraw is checked against the exact balance.
+    test.settle_balance_changes(BTreeMap::from([(account, 90i128)]));
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::InsufficientBalance)]),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_out_of_order_batch_waits_for_settlement() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let v2 = v1.next();
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(
+        v0,
+        BTreeMap::from([(account1, 100), (account2, 100)]),
+    ));
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 30)]),
+    };
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account2, 50)]),
+    };
+
+    // The batch at v2 arrives first, and is scheduled ahead of settlement.
+    let (reservations, receivers) = WithdrawReservations::new(v2, vec![withdraw2.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The batch at v1 would have been scheduled ahead as well had it arrived first, but arriving
+    // after a later batch, it waits for v1 to be settled.
+    let (reservations, receivers) = WithdrawReservations::new(v1, vec![withdraw1.clone()]);
+    scheduler.schedule_withdraws(reservations).await;
+    let waiting = scheduler.withdraws_awaiting_settlement(0);
+    assert_eq!(waiting.len(), 1);
+    assert_eq!(waiting[0].tx_digest, withdraw1.tx_digest);
+    assert_eq!(waiting[0].accumulator_version, v1);
+
+    mock_read.settle_balance_changes(BTreeMap::new());
+    scheduler
+        .settle_balances(BalanceSettlement {
+            balance_changes: BTreeMap::new(),
+            withdraws: BTreeMap::new(),
+        })
+        .await;
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+}
+
+#[test]
+#[should_panic(expected = "batches_arrive_in_version_order")]
+fn test_out_of_order_batch_after_conflicting_reservations() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let state = SchedulerState::new(
+        mock_read,
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = |amount| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, amount)]),
+    };
+
+    // The withdraw at v2 was scheduled ahead assuming nothing at v1 withdraws from the account,
+    // which the batch at v1 that arrives after it shows to be wrong.
+    let (reservations, _receivers) =
+        WithdrawReservations::new(v0.next().next(), vec![withdraw(80)]);
+    state.schedule(reservations);
+    let (reservations, _receivers) = WithdrawReservations::new(v0.next(), vec![withdraw(50)]);
+    state.schedule(reservations);
+}
+
+#[tokio::test]
+async fn test_schedule_ahead_matches_sequential() {
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut workload = Workload::new()
+            .accounts(4)
+            .initial_balances(1.0, 0..=49)
+            .settle_prob(0.25)
+            .max_withdraw(19)
+            .seed(seed)
+            .generator();
+        let accounts = workload.accounts().to_vec();
+        let init_version = SequenceNumber::from_u64(0);
+        let mut balances = workload.init_balances().clone();
+        let test = TestScheduler::new_cross_checked(init_version, balances.clone());
+
+        // Generate a batch for each version up front, along with the results of scheduling it
+        // sequentially, and the balance changes that its settlement makes.
+        let mut version = init_version;
+        let mut batches = Vec::new();
+        for _ in 0..10 {
+            let withdraws = workload.next_batch().unwrap();
+            let expected_results = schedule_sequentially(&balances, &withdraws);
+            let mut changes = BTreeMap::new();
+            for withdraw in &withdraws {
+                if expected_results[&withdraw.tx_digest] == ScheduleStatus::SufficientBalance {
+                    for (account_id, reservation) in &withdraw.reservations {
+                        *changes.entry(*account_id).or_insert(0i128) -= *reservation as i128;
+                    }
+                }
+            }
+            for account_id in &accounts {
+                if rng.gen_bool(0.5) {
+                    *changes.entry(*account_id).or_insert(0i128) += rng.gen_range(0..20);
+                }
+            }
+            for (account_id, change) in &changes {
+                let balance = balances.get_mut(account_id).unwrap();
+                *balance = (*balance as i128 + change) as u64;
+            }
+
+            batches.push((version, withdraws, expected_results, changes));
+            version = version.next();
+        }
+
+        // Schedule every batch before settling anything, so that all but the first one are
+        // scheduled ahead of their settlement.
+        let all_receivers = batches
+            .iter()
+            .map(|(version, withdraws, _, _)| {
+                test.scheduler
+                    .schedule_withdraws(*version, withdraws.clone())
+            })
+            .collect::<Vec<_>>();
+        for ((_, _, expected_results, changes), receivers) in batches.into_iter().zip(all_receivers)
+        {
+            wait_for_results(receivers, expected_results).await;
+            test.settle_balance_changes(changes);
+        }
+    }
+}
+
+#[tokio::test]
+async fn stress_test() {
+    let workload = Workload::new()
+        .accounts(5)
+        .txs(10_000)
+        .settle_prob(0.2)
+        .generate();
+    let init_balances = workload.init_balances;
+
+    let mut version = SequenceNumber::from_u64(0);
+    let mut withdraws = Vec::new();
+    let mut settlements = Vec::new();
+
+    // Every batch is followed by a settlement to advance the version. We don't really settle any
+    // balance changes here, as this test is primarily focusing on the scheduler's ability to
+    // handle random combinations of withdraw reservations.
+    for batch in workload.batches {
+        withdraws.push((version, batch));
+        version = version.next();
+        settlements.push((version, BTreeMap::new()));
+    }
+
+    // Run through the scheduler many times and check that the results are always the same.
+    let mut expected_results = None;
+    let mut handles = Vec::new();
+
+    // Spawn 10 concurrent tasks
+    for _ in 0..10 {
+        let init_balances = init_balances.clone();
+        let settlements = settlements.clone();
+        let withdraws = withdraws.clone();
+
+        let handle = tokio::spawn(async move {
+            let test = TestScheduler::new_cross_checked(version, init_balances);
+
+            // Start a separate thread to run all settlements on the scheduler.
+            let test_clone = test.clone();
+            let settlements = settlements.clone();
+            let settle_task = tokio::spawn(async move {
+                for (_version, balance_changes) in settlements {
+                    test_clone.settle_balance_changes(balance_changes);
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            });
+
+            let mut all_receivers = FuturesUnordered::new();
+            for (version, withdraws) in withdraws {
+                let receivers = test.scheduler.schedule_withdraws(version, withdraws);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                all_receivers.extend(receivers);
+            }
+            // Wait for the settle task to finish.
+            settle_task.await.unwrap();
+
+            let mut results = BTreeMap::new();
+            while let Some(result) = all_receivers.next().await {
+                let result = result.unwrap();
+                results.insert(result.tx_digest, result.status);
+            }
+            results
+        });
+        handles.push(handle);
+    }
+
+    // Wait for all tasks to complete and compare results
+    for handle in handles {
+        let results = handle.await.unwrap();
+        if expected_results.is_none() {
+            expected_results = Some(results);
+        } else {
+            assert_eq!(expected_results, Some(results));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_settlement_receipts() {
+    let init_version = SequenceNumber::from_u64(0);
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let test = TestScheduler::new(
+        init_version,
+        BTreeMap::from([(account1, 100), (account2, 100)]),
+    );
+    let mut receipts = test.scheduler.subscribe_settlement_receipts();
+
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 50)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(init_version, vec![withdraw.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The scheduled transaction withdraws less than it reserved, and a transaction that was
+    // never scheduled on this node withdraws from another account.
+    let unscheduled = TransactionDigest::random();
+    let changes = BTreeMap::from([(account1, -30), (account2, -10)]);
+    test.mock_read.settle_balance_changes(changes.clone());
+    test.scheduler.settle_balances(BalanceSettlement {
+        balance_changes: changes,
+        withdraws: BTreeMap::from([
+            (withdraw.tx_digest, BTreeMap::from([(account1, 30)])),
+            (unscheduled, BTreeMap::from([(account2, 10)])),
+        ]),
+    });
+
+    let received = timeout(Duration::from_secs(3), async {
+        let mut received = vec![];
+        while received.len() < 2 {
+            received.push(receipts.recv().await.unwrap());
+        }
+        received
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        received,
+        vec![
+            SettlementReceipt {
+                tx_digest: withdraw.tx_digest,
+                account: account1,
+                reserved: 50,
+                actually_withdrawn: 30,
+            },
+            SettlementReceipt {
+                tx_digest: unscheduled,
+                account: account2,
+                reserved: 0,
+                actually_withdrawn: 10,
+            },
+        ]
+    );
+    assert_eq!(test.scheduler.get_reserved_balance(&account1), 0);
+}
+
+#[tokio::test]
+async fn test_amend_reservation() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let test = TestScheduler::new(v0, BTreeMap::from([(account, 100)]));
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 60)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v0, vec![withdraw1.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 30)]),
+    };
+    let receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw2.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 90);
+
+    // An increase that fits in what is left after every other reservation is accepted.
+    test.scheduler
+        .amend_reservation(&withdraw1.tx_digest, BTreeMap::from([(account, 70)]))
+        .unwrap();
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 100);
+
+    // One that doesn't is rejected, leaving the reservation as it was.
+    assert_eq!(
+        test.scheduler
+            .amend_reservation(&withdraw1.tx_digest, BTreeMap::from([(account, 80)])),
+        Err(AmendReservationError::InsufficientBalance {
+            account_id: account,
+            increase: 10,
+            available: 0,
+        })
+    );
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 100);
+
+    // Decreases are always accepted, and release the balance for other amendments, even at
+    // earlier versions.
+    test.scheduler
+        .amend_reservation(&withdraw2.tx_digest, BTreeMap::from([(account, 10)]))
+        .unwrap();
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 80);
+    test.scheduler
+        .amend_reservation(&withdraw1.tx_digest, BTreeMap::from([(account, 90)]))
+        .unwrap();
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 100);
+
+    let unknown = TransactionDigest::random();
+    assert_eq!(
+        test.scheduler
+            .amend_reservation(&unknown, BTreeMap::from([(account, 10)])),
+        Err(AmendReservationError::NotReserved { tx_digest: unknown })
+    );
+
+    // Once its version is settled, a reservation can no longer be amended.
+    test.settle_balance_changes(BTreeMap::from([(account, -90i128)]));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(test.scheduler.get_reserved_balance(&account), 10);
+    assert_eq!(
+        test.scheduler
+            .amend_reservation(&withdraw1.tx_digest, BTreeMap::new()),
+        Err(AmendReservationError::NotReserved {
+            tx_digest: withdraw1.tx_digest,
+        })
+    );
+}
+
+fn shadow_metrics() -> ShadowSchedulerMetrics {
+    ShadowSchedulerMetrics {
+        results: IntCounterVec::new(Opts::new("results", "results"), &["status"]).unwrap(),
+        divergences: IntCounterVec::new(
+            Opts::new("divergences", "divergences"),
+            &["live", "shadow"],
+        )
+        .unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn test_shadow_scheduler() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(
+        v0,
+        BTreeMap::from([(account1, 100), (account2, 100)]),
+    ));
+
+    let metrics = shadow_metrics();
+
+    // The shadow caps reservations, and never schedules ahead of settlement.
+    let shadow = ShadowBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams {
+            schedule_ahead_of_settlement: false,
+            max_reservation_per_account: Some(50),
+            ..Default::default()
+        },
+        Arc::new(AllowAllWithdraws),
+        metrics.clone(),
+    );
+    let scheduler = BalanceWithdrawScheduler::new_with_shadow(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        Some(shadow),
+        None,
+    );
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 40)]),
+    };
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account2, 70)]),
+    };
+
+    // The live results are unaffected by the shadow.
+    let receivers = scheduler.schedule_withdraws(v0, vec![withdraw1.clone(), withdraw2.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (withdraw1.tx_digest, ScheduleStatus::SufficientBalance),
+            (withdraw2.tx_digest, ScheduleStatus::SufficientBalance),
+        ]),
+    )
+    .await;
+
+    // Only the live scheduler schedules this withdraw ahead of settlement.
+    let withdraw3 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 10)]),
+    };
+    let receivers = scheduler.schedule_withdraws(v1, vec![withdraw3.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw3.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    mock_read.settle_balance_changes(BTreeMap::from([(account1, -40), (account2, -70)]));
+    scheduler.settle_balances(BalanceSettlement {
+        balance_changes: BTreeMap::new(),
+        withdraws: BTreeMap::new(),
+    });
+
+    let results =
+        |status: ScheduleStatus| metrics.results.with_label_values(&[status.as_str()]).get();
+
+    timeout(Duration::from_secs(3), async {
+        while results(ScheduleStatus::SufficientBalance) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // Only the withdraw over the cap diverged: the one that had to wait for settlement in the
+    // shadow still had sufficient balance once it was settled.
+    assert_eq!(results(ScheduleStatus::SufficientBalance), 2);
+    assert_eq!(results(ScheduleStatus::InsufficientBalance), 1);
+    assert_eq!(
+        metrics
+            .divergences
+            .with_label_values(&["sufficient_balance", "insufficient_balance"])
+            .get(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_shadow_expires_unmatched_results() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+    let shadow = ShadowBalanceWithdrawScheduler::new(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        shadow_metrics(),
+    );
+
+    let withdraw = || TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 1)]),
+    };
+    let wait_for_unmatched = |expected: usize| {
+        let shadow = shadow.clone();
+        async move {
+            timeout(Duration::from_secs(3), async {
+                while shadow.unmatched_results() != expected {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap_or_else(|_| {
+                panic!(
+                    "expected {expected} unmatched results, found {}",
+                    shadow.unmatched_results()
+                )
+            });
+        }
+    };
+
+    // The live scheduler never reports its results for these withdraws, so the shadow's results
+    // are left unmatched.
+    let mut version = v0;
+    shadow.schedule_withdraws(version, vec![withdraw()]);
+    wait_for_unmatched(1).await;
+
+    for unmatched in [2, 3] {
+        mock_read.settle_balance_changes(BTreeMap::new());
+        shadow.settle_balances(BalanceSettlement {
+            balance_changes: BTreeMap::new(),
+            withdraws: BTreeMap::new(),
+        });
+        version = version.next();
+        shadow.schedule_withdraws(version, vec![withdraw()]);
+        wait_for_unmatched(unmatched).await;
+    }
+
+    // Once the shadow has settled far enough past the first version, its result is forgotten.
+    mock_read.settle_balance_changes(BTreeMap::new());
+    shadow.settle_balances(BalanceSettlement {
+        balance_changes: BTreeMap::new(),
+        withdraws: BTreeMap::new(),
+    });
+    version = version.next();
+    shadow.schedule_withdraws(version, vec![withdraw(), withdraw()]);
+    wait_for_unmatched(4).await;
+}
+
+#[tokio::test]
+async fn test_shadow_stops_when_it_falls_behind() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+
+    // The shadow reads balances through a gate, so that it can be held up while the live
+    // scheduler carries on.
+    let (reads_sender, mut reads) = tokio::sync::mpsc::unbounded_channel();
+    let (gate, gate_receiver) = std::sync::mpsc::channel();
+    let shadow = ShadowBalanceWithdrawScheduler::new(
+        Arc::new(GatedBalanceRead {
+            inner: MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])),
+            reads: reads_sender,
+            gate: std::sync::Mutex::new(gate_receiver),
+        }),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        shadow_metrics(),
+    );
+    let scheduler = BalanceWithdrawScheduler::new_with_shadow(
+        mock_read,
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        Some(shadow.clone()),
+        None,
+    );
+
+    let withdraw = |amount: u64| TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, amount)]),
+    };
+
+    let withdraw1 = withdraw(10);
+    let receivers = scheduler.schedule_withdraws(v0, vec![withdraw1.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(reads.recv().await, Some(account));
+
+    // While the shadow is blocked, the live scheduler keeps sending it commands, until there are
+    // more than it can queue.
+    for _ in 0..=SHADOW_COMMAND_CHANNEL_CAPACITY {
+        scheduler.record_deposits(v0.next(), TransactionDigest::random(), BTreeMap::new());
+    }
+    assert!(shadow.is_stopped());
+
+    // The live scheduler was never held up by the shadow.
+    let withdraw2 = withdraw(20);
+    let receivers = scheduler.schedule_withdraws(v0, vec![withdraw2.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(scheduler.get_reserved_balance(&account), 30);
+
+    drop(gate);
+    assert_eq!(shadow.unmatched_results(), 0);
+}
+
+#[tokio::test]
+async fn test_settlement_metrics() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+
+    let metrics = SettlementMetrics {
+        application_latency: Histogram::with_opts(HistogramOpts::new(
+            "application_latency",
+            "application_latency",
+        ))
+        .unwrap(),
+        queue_latency: Histogram::with_opts(HistogramOpts::new("queue_latency", "queue_latency"))
+            .unwrap(),
+        queued_behind_unapplied: IntCounter::new("queued", "queued").unwrap(),
+        long_settlement_waits: IntGauge::new("long_settlement_waits", "long_settlement_waits")
+            .unwrap(),
+        reservation_wait: HistogramVec::new(
+            HistogramOpts::new("reservation_wait", "reservation_wait"),
+            &["status"],
+        )
+        .unwrap(),
+    };
+    let scheduler = BalanceWithdrawScheduler::new_with_shadow(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        None,
+        Some(metrics.clone()),
+    );
+
+    // The scheduler does not get to apply the first settlement before the second one arrives,
+    // so the second one queues behind it.
+    for _ in 0..2 {
+        mock_read.settle_balance_changes(BTreeMap::from([(account, -10)]));
+        scheduler.settle_balances(BalanceSettlement {
+            balance_changes: BTreeMap::from([(account, -10)]),
+            withdraws: BTreeMap::new(),
+        });
+    }
+
+    timeout(Duration::from_secs(3), async {
+        while metrics.application_latency.get_sample_count() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(metrics.queue_latency.get_sample_count(), 2);
+    assert_eq!(metrics.queued_behind_unapplied.get(), 1);
+
+    // Once both settlements are applied, the next one does not queue behind them.
+    mock_read.settle_balance_changes(BTreeMap::new());
+    scheduler.settle_balances(BalanceSettlement {
+        balance_changes: BTreeMap::new(),
+        withdraws: BTreeMap::new(),
+    });
+
+    timeout(Duration::from_secs(3), async {
+        while metrics.application_latency.get_sample_count() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(metrics.queued_behind_unapplied.get(), 1);
+}
+
+#[tokio::test]
+async fn test_reservation_wait_metrics() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let mock_read = Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)])));
+
+    let reservation_wait = HistogramVec::new(
+        HistogramOpts::new("reservation_wait", "reservation_wait"),
+        &["status"],
+    )
+    .unwrap();
+    let scheduler = BalanceWithdrawScheduler::new_with_shadow(
+        mock_read.clone(),
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+        None,
+        Some(SettlementMetrics {
+            application_latency: Histogram::with_opts(HistogramOpts::new(
+                "application_latency",
+                "application_latency",
+            ))
+            .unwrap(),
+            queue_latency: Histogram::with_opts(HistogramOpts::new(
+                "queue_latency",
+                "queue_latency",
+            ))
+            .unwrap(),
+            queued_behind_unapplied: IntCounter::new("queued", "queued").unwrap(),
+            long_settlement_waits: IntGauge::new("long_settlement_waits", "long_settlement_waits")
+                .unwrap(),
+            reservation_wait: reservation_wait.clone(),
+        }),
+    );
+
+    // Withdraws at the settled version are decided straight away.
+    let sufficient = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 60)]),
+    };
+    let insufficient = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 60)]),
+    };
+    let receivers =
+        scheduler.schedule_withdraws(v0, vec![sufficient.clone(), insufficient.clone()]);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([
+            (sufficient.tx_digest, ScheduleStatus::SufficientBalance),
+            (insufficient.tx_digest, ScheduleStatus::InsufficientBalance),
+        ]),
+    )
+    .await;
+
+    let sufficient_wait = reservation_wait.with_label_values(&["sufficient_balance"]);
+    let insufficient_wait = reservation_wait.with_label_values(&["insufficient_balance"]);
+    assert_eq!(sufficient_wait.get_sample_count(), 1);
+    assert_eq!(insufficient_wait.get_sample_count(), 1);
+
+    // A withdraw at the next version that may not fit in what the first withdraw leaves waits for
+    // the settlement, and the wait is recorded once it gets its reservation.
+    let waiting = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 60)]),
+    };
+    let receivers = scheduler.schedule_withdraws(v1, vec![waiting.clone()]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(sufficient_wait.get_sample_count(), 1);
+
+    // The settlement also deposits 20 into the account, leaving enough for it.
+    mock_read.settle_balance_changes(BTreeMap::from([(account, -40)]));
+    scheduler.settle_balances(BalanceSettlement {
+        balance_changes: BTreeMap::from([(account, -40)]),
+        withdraws: BTreeMap::from([(sufficient.tx_digest, BTreeMap::from([(account, 60)]))]),
+    });
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(waiting.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    assert_eq!(sufficient_wait.get_sample_count(), 2);
+    assert!(sufficient_wait.get_sample_sum() >= 0.1);
+    assert_eq!(insufficient_wait.get_sample_count(), 1);
+}
+
+/// Reads balances from `inner`, but blocks the reading thread until the test lets each read
+/// through, and reports the account of each read as it starts.
+struct GatedBalanceRead {
+    inner: MockBalanceRead,
+    reads: tokio::sync::mpsc::UnboundedSender<ObjectID>,
+    gate: std::sync::Mutex<std::sync::mpsc::Receiver<()>>,
+}
+
+impl AccountBalanceRead for GatedBalanceRead {
+    fn get_account_balance(
+        &self,
+        account_id: &ObjectID,
+        accumulator_version: SequenceNumber,
+    ) -> u64 {
+        let _ = self.reads.send(*account_id);
+        let _ = self.gate.lock().unwrap().recv();
+        self.inner
+            .get_account_balance(account_id, accumulator_version)
+    }
+}
+
+#[tokio::test]
+async fn test_balance_reads_do_not_block_runtime() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+    let (reads_sender, mut reads) = tokio::sync::mpsc::unbounded_channel();
+    let (gate, gate_receiver) = std::sync::mpsc::channel();
+    let balance_read = Arc::new(GatedBalanceRead {
+        inner: MockBalanceRead::new(v0, BTreeMap::from([(account1, 100), (account2, 100)])),
+        reads: reads_sender,
+        gate: std::sync::Mutex::new(gate_receiver),
+    });
+    let scheduler = NaiveBalanceWithdrawScheduler::new(
+        balance_read,
+        v0,
+        WithdrawSchedulerParams::default(),
+        Arc::new(AllowAllWithdraws),
+    );
+    let schedule = |withdraw: &TxBalanceWithdraw| {
+        let (reservations, receivers) = WithdrawReservations::new(v0, vec![withdraw.clone()]);
+        let scheduler = scheduler.clone();
+        tokio::spawn(async move { scheduler.schedule_withdraws(reservations).await });
+        receivers
+    };
+
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account1, 30)]),
+    };
+    let receivers = schedule(&withdraw1);
+    assert_eq!(reads.recv().await, Some(account1));
+    gate.send(()).unwrap();
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw1.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+
+    // The scheduler is now blocked reading the balance of the second account.
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account2, 40)]),
+    };
+    let receivers = schedule(&withdraw2);
+    assert_eq!(reads.recv().await, Some(account2));
+
+    // The test runtime has a single thread, which other tasks can still run on, and queries and
+    // updates that do not read balances complete without waiting for the read.
+    tokio::spawn(async {}).await.unwrap();
+    assert_eq!(scheduler.get_reserved_balance(&account1), 30);
+    assert!(!scheduler.is_idle());
+    assert!(scheduler.withdraws_awaiting_settlement(0).is_empty());
+    scheduler.record_deposits(
+        v0,
+        TransactionDigest::random(),
+        BTreeMap::from([(account1, 5)]),
+    );
+
+    // Reducing a reservation does not need to read any balances.
+    scheduler
+        .amend_reservation(&withdraw1.tx_digest, BTreeMap::from([(account1, 20)]))
+        .unwrap();
+    assert_eq!(scheduler.get_reserved_balance(&account1), 20);
+
+    drop(gate);
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+    assert_eq!(scheduler.get_reserved_balance(&account2), 40);
+}
+
+#[test]
+fn test_tx_balance_withdraw_new_checked() {
+    let tx_digest = TransactionDigest::random();
+    let account1 = ObjectID::random();
+    let account2 = ObjectID::random();
+
+    // Reservations against the same account are merged.
+    let withdraw = TxBalanceWithdraw::new_checked(
+        tx_digest,
+        [(account1, 10), (account2, 5), (account1, 20)],
+        2,
+    )
+    .unwrap();
+    assert_eq!(withdraw.tx_digest, tx_digest);
+    assert_eq!(
+        withdraw.reservations,
+        BTreeMap::from([(account1, 30), (account2, 5)])
+    );
+
+    assert_eq!(
+        TxBalanceWithdraw::new_checked(tx_digest, [(account1, 10), (account2, 0)], 2).unwrap_err(),
+        TxBalanceWithdrawError::ZeroReservation {
+            account_id: account2
+        }
+    );
+    assert_eq!(
+        TxBalanceWithdraw::new_checked(tx_digest, [(account1, u64::MAX), (account1, 1)], 2)
+            .unwrap_err(),
+        TxBalanceWithdrawError::ReservationOverflow {
+            account_id: account1
+        }
+    );
+    assert_eq!(
+        TxBalanceWithdraw::new_checked(tx_digest, [(account1, 10), (account2, 10)], 1).unwrap_err(),
+        TxBalanceWithdrawError::TooManyAccounts {
+            num_accounts: 2,
+            max_accounts: 1
+        }
+    );
+    assert_eq!(
+        TxBalanceWithdraw::new_checked(tx_digest, [], 1).unwrap_err(),
+        TxBalanceWithdrawError::NoReservations { tx_digest }
+    );
+}
+
+#[test]
+fn test_serde_representation() {
+    for (status, repr) in [
+        (ScheduleStatus::SufficientBalance, "sufficient_balance"),
+        (ScheduleStatus::InsufficientBalance, "insufficient_balance"),
+        (ScheduleStatus::AlreadyExecuted, "already_executed"),
+        (ScheduleStatus::PolicyRejected, "policy_rejected"),
+        (ScheduleStatus::Rejected, "rejected"),
+    ] {
+        let json = serde_json::to_value(status).unwrap();
+        assert_eq!(json, serde_json::json!(repr));
+        assert_eq!(
+            serde_json::from_value::<ScheduleStatus>(json).unwrap(),
+            status
+        );
+    }
+
+    // The schema lists the same representations.
+    let schema = serde_json::to_value(schemars::schema_for!(ScheduleStatus)).unwrap();
+    assert_eq!(
+        schema["enum"],
+        serde_json::json!([
+            "sufficient_balance",
+            "insufficient_balance",
+            "already_executed",
+            "policy_rejected",
+            "rejected"
+        ]),
+    );
+
+    let tx_digest = TransactionDigest::random();
+    let account = ObjectID::random();
+
+    let withdraw = TxBalanceWithdraw::new_checked(tx_digest, [(account, 10)], 1).unwrap();
+    let json = serde_json::to_string(&withdraw).unwrap();
+    assert_eq!(
+        serde_json::from_str::<TxBalanceWithdraw>(&json).unwrap(),
+        withdraw
+    );
+
+    let settlement = BalanceSettlement {
+        balance_changes: BTreeMap::from([(account, -10)]),
+        withdraws: BTreeMap::from([(tx_digest, BTreeMap::from([(account, 10)]))]),
+    };
+    let json = serde_json::to_string(&settlement).unwrap();
+    assert_eq!(
+        serde_json::from_str::<BalanceSettlement>(&json).unwrap(),
+        settlement
+    );
+}
+
+// Invariant violations only panic in debug builds.
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "withdrawn_within_reservation")]
+fn test_withdrawn_beyond_reservation_violates_invariant() {
+    let tx_digest = TransactionDigest::random();
+    let account = ObjectID::random();
+    SettlementReceipt::reconcile(
+        BTreeMap::from([(tx_digest, BTreeMap::from([(account, 10)]))]),
+        BTreeMap::from([(tx_digest, BTreeMap::from([(account, 20)]))]),
+    );
+}
+
+#[tokio::test]
+async fn test_cross_check_with_amendments() {
+    let v0 = SequenceNumber::from_u64(0);
+    let v1 = v0.next();
+    let account = ObjectID::random();
+    let test = TestScheduler::new_cross_checked(v0, BTreeMap::from([(account, 100)]));
+
+    // The first withdraw is scheduled ahead of v0 being settled, but the second has to wait.
+    let withdraw1 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 60)]),
+    };
+    let withdraw2 = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 70)]),
+    };
+    let mut receivers = test
+        .scheduler
+        .schedule_withdraws(v1, vec![withdraw1.clone(), withdraw2.clone()]);
+    let result = timeout(Duration::from_secs(3), receivers.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.tx_digest, withdraw1.tx_digest);
+    assert_eq!(result.status, ScheduleStatus::SufficientBalance);
+
+    // Amending the first withdraw down leaves enough for the second once v0 is settled. The
+    // reference model only agrees if it was told about the amendment.
+    test.scheduler
+        .amend_reservation(&withdraw1.tx_digest, BTreeMap::from([(account, 20)]))
+        .unwrap();
+    test.settle_balance_changes(BTreeMap::new());
+    wait_for_results(
+        receivers,
+        BTreeMap::from([(withdraw2.tx_digest, ScheduleStatus::SufficientBalance)]),
+    )
+    .await;
+}
+
+#[test]
+#[should_panic(expected = "diverged from the reference model")]
+fn test_cross_check_divergence_is_fatal() {
+    let v0 = SequenceNumber::from_u64(0);
+    let account = ObjectID::random();
+    let reference = ReferenceWithdrawScheduler::new(
+        Arc::new(MockBalanceRead::new(v0, BTreeMap::from([(account, 100)]))),
+        v0,
+        None,
+        Arc::new(AllowAllWithdraws),
+    );
+
+    let withdraw = TxBalanceWithdraw {
+        tx_digest: TransactionDigest::random(),
+        reservations: BTreeMap::from([(account, 50)]),
+    };
+    reference.schedule_withdraws(v0, vec![withdraw.clone()]);
+    reference.check_live_result(&ScheduleResult {
+        tx_digest: withdraw.tx_digest,
+        status: ScheduleStatus::InsufficientBalance,
+        details: vec![],
+    });
+}
+
+#[test]
+fn test_workload_is_deterministic() {
+    let workload = Workload::new().txs(100).seed(42);
+    let first = workload.generate();
+    let second = workload.generate();
+    assert_eq!(first.accounts, second.accounts);
+    assert_eq!(first.init_balances, second.init_balances);
+    assert_eq!(first.batches, second.batches);
+
+    let other = workload.clone().seed(43).generate();
+    assert_ne!(first.batches, other.batches);
+}
+
+#[test]
+fn test_workload_shape() {
+    let workload = Workload::new()
+        .accounts(10)
+        .txs(95)
+        .settle_every(10)
+        .initial_balances(1.0, 100..=100)
+        .max_accounts_per_tx(3)
+        .max_withdraw(5)
+        .generate();
+
+    assert_eq!(workload.accounts.len(), 10);
+    assert!(workload
+        .accounts
+        .iter()
+        .all(|a| workload.init_balances[a] == 100));
+
+    // Every batch but the last is full, and the last one ends at the workload's last transaction.
+    let sizes: Vec<_> = workload.batches.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [vec![10; 9], vec![5]].concat());
+
+    for withdraw in workload.batches.iter().flatten() {
+        assert!((1..=3).contains(&withdraw.reservations.len()));
+        for (account, amount) in &withdraw.reservations {
+            assert!(workload.accounts.contains(account));
+            assert!((1..=15).contains(amount));
+        }
+    }
+}
+
+#[test]
+fn test_workload_hot_accounts() {
+    let mut generator = Workload::new()
+        .accounts(10)
+        .hot_accounts(2, 1.0)
+        .generator();
+    let hot_accounts = generator.hot_accounts().to_vec();
+    assert_eq!(hot_accounts, generator.accounts()[..2]);
+
+    // The workload is unbounded, so batches keep coming, all of them against the hot accounts.
+    for _ in 0..100 {
+        let batch = generator.next_batch().unwrap();
+        assert!(!batch.is_empty());
+        for withdraw in batch {
+            assert!(withdraw
+                .reservations
+                .keys()
+                .all(|account| hot_accounts.contains(account)));
         }
     }
 }
@@ -0,0 +1,281 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small DSL for describing synthetic withdraw workloads, shared by the scheduler's unit tests
+//! and the `balance-scheduler-bench` soak binary, so that they all draw from the same generator,
+//! and the same seed always produces the same workload:
+//!
+//! ```ignore
+//! let workload = Workload::new().accounts(5).txs(10_000).settle_prob(0.2).generate();
+//! ```
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sui_types::{base_types::ObjectID, digests::TransactionDigest};
+
+use super::TxBalanceWithdraw;
+
+/// Description of a synthetic workload: the accounts that withdraws are drawn from, their
+/// initial balances, the shape of each transaction's reservations, and how many transactions are
+/// scheduled against each accumulator version before it is settled.
+#[derive(Clone, Debug)]
+pub struct Workload {
+    accounts: usize,
+    txs: Option<usize>,
+    batch_size: BatchSize,
+    funded_ratio: f64,
+    initial_balance: RangeInclusive<u64>,
+    hot_accounts: usize,
+    hot_ratio: f64,
+    max_accounts_per_tx: usize,
+    max_withdraw: u64,
+    seed: u64,
+}
+
+/// How many transactions are scheduled against each accumulator version.
+#[derive(Clone, Copy, Debug)]
+enum BatchSize {
+    /// Each transaction is the last one scheduled against its version with this probability.
+    Random(f64),
+
+    /// Exactly this many transactions are scheduled against each version.
+    Fixed(usize),
+}
+
+/// Produces the accounts, initial balances, and batches of withdraws of a [Workload],
+/// deterministically from its seed.
+pub(crate) struct WorkloadGenerator {
+    workload: Workload,
+    rng: StdRng,
+    accounts: Vec<ObjectID>,
+    init_balances: BTreeMap<ObjectID, u64>,
+    /// Transactions left to generate, if the workload is finite.
+    remaining: Option<usize>,
+}
+
+/// A finite workload, fully generated up-front.
+#[cfg(test)]
+pub(crate) struct GeneratedWorkload {
+    pub accounts: Vec<ObjectID>,
+    pub init_balances: BTreeMap<ObjectID, u64>,
+    /// Withdraws to schedule against consecutive accumulator versions, each batch followed by a
+    /// settlement.
+    pub batches: Vec<Vec<TxBalanceWithdraw>>,
+}
+
+impl Workload {
+    /// An unbounded workload over 5 accounts, 70% of which start with a balance of up to 19. Each
+    /// transaction reserves between 1 and 9 from one or two accounts, and each transaction is
+    /// followed by a settlement with probability 0.2.
+    pub fn new() -> Self {
+        Self {
+            accounts: 5,
+            txs: None,
+            batch_size: BatchSize::Random(0.2),
+            funded_ratio: 0.7,
+            initial_balance: 0..=19,
+            hot_accounts: 0,
+            hot_ratio: 0.0,
+            max_accounts_per_tx: 2,
+            max_withdraw: 9,
+            seed: 0,
+        }
+    }
+
+    /// Number of accounts that withdraws are drawn from.
+    pub fn accounts(mut self, accounts: usize) -> Self {
+        assert!(accounts > 0, "workload needs at least one account");
+        self.accounts = accounts;
+        self
+    }
+
+    /// Stop the workload after `txs` transactions.
+    pub fn txs(mut self, txs: usize) -> Self {
+        self.txs = Some(txs);
+        self
+    }
+
+    /// Settle after each transaction with probability `prob`, so that versions see a random
+    /// number of transactions.
+    pub fn settle_prob(mut self, prob: f64) -> Self {
+        assert!(
+            prob > 0.0 && prob <= 1.0,
+            "settlement probability must be in (0, 1]"
+        );
+        self.batch_size = BatchSize::Random(prob);
+        self
+    }
+
+    /// Settle after every `txs` transactions.
+    pub fn settle_every(mut self, txs: usize) -> Self {
+        assert!(txs > 0, "settlements need at least one transaction");
+        self.batch_size = BatchSize::Fixed(txs);
+        self
+    }
+
+    /// Each account starts with a balance drawn from `balance` with probability `funded_ratio`,
+    /// and with no balance otherwise.
+    pub fn initial_balances(mut self, funded_ratio: f64, balance: RangeInclusive<u64>) -> Self {
+        self.funded_ratio = funded_ratio;
+        self.initial_balance = balance;
+        self
+    }
+
+    /// Direct `ratio` of all reservations to the first `accounts` accounts.
+    pub fn hot_accounts(mut self, accounts: usize, ratio: f64) -> Self {
+        self.hot_accounts = accounts;
+        self.hot_ratio = ratio;
+        self
+    }
+
+    /// Maximum number of accounts a single transaction withdraws from.
+    pub fn max_accounts_per_tx(mut self, accounts: usize) -> Self {
+        assert!(accounts > 0, "transactions need at least one account");
+        self.max_accounts_per_tx = accounts;
+        self
+    }
+
+    /// Maximum amount reserved by a single withdraw.
+    pub fn max_withdraw(mut self, amount: u64) -> Self {
+        assert!(amount > 0, "withdraws must reserve a non-zero amount");
+        self.max_withdraw = amount;
+        self
+    }
+
+    /// Seed for the workload's random number generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// A generator for this workload's batches, which can be drawn one at a time, e.g. for
+    /// workloads that run until a deadline, rather than for a number of transactions.
+    pub(crate) fn generator(&self) -> WorkloadGenerator {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        let accounts: Vec<_> = (0..self.accounts)
+            .map(|_| ObjectID::random_from_rng(&mut rng))
+            .collect();
+
+        let init_balances = accounts
+            .iter()
+            .filter_map(|account| {
+                rng.gen_bool(self.funded_ratio)
+                    .then(|| (*account, rng.gen_range(self.initial_balance.clone())))
+            })
+            .collect();
+
+        WorkloadGenerator {
+            workload: self.clone(),
+            rng,
+            accounts,
+            init_balances,
+            remaining: self.txs,
+        }
+    }
+
+    /// Generate all of this workload's transactions, split into batches. The workload must have
+    /// a number of transactions.
+    #[cfg(test)]
+    pub(crate) fn generate(&self) -> GeneratedWorkload {
+        assert!(self.txs.is_some(), "cannot generate an unbounded workload");
+        let mut generator = self.generator();
+
+        let mut batches = vec![];
+        while let Some(batch) = generator.next_batch() {
+            batches.push(batch);
+        }
+
+        GeneratedWorkload {
+            accounts: generator.accounts,
+            init_balances: generator.init_balances,
+            batches,
+        }
+    }
+}
+
+impl Default for Workload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkloadGenerator {
+    /// All the accounts in the workload.
+    pub(crate) fn accounts(&self) -> &[ObjectID] {
+        &self.accounts
+    }
+
+    /// The accounts that receive the workload's hot reservations (at least one).
+    pub(crate) fn hot_accounts(&self) -> &[ObjectID] {
+        &self.accounts[..self.num_hot_accounts()]
+    }
+
+    /// The balances that accounts start with. Accounts that are missing start with no balance.
+    pub(crate) fn init_balances(&self) -> &BTreeMap<ObjectID, u64> {
+        &self.init_balances
+    }
+
+    /// The withdraws to schedule against the next accumulator version, or `None` if the workload
+    /// has run out of transactions. The last batch is cut short to end at the workload's last
+    /// transaction.
+    pub(crate) fn next_batch(&mut self) -> Option<Vec<TxBalanceWithdraw>> {
+        let limit = self.remaining.unwrap_or(usize::MAX);
+        if limit == 0 {
+            return None;
+        }
+
+        let mut batch = vec![];
+        while batch.len() < limit {
+            batch.push(self.next_withdraw());
+            match self.workload.batch_size {
+                BatchSize::Random(prob) if self.rng.gen_bool(prob) => break,
+                BatchSize::Random(_) => {}
+                BatchSize::Fixed(txs) if batch.len() >= txs => break,
+                BatchSize::Fixed(_) => {}
+            }
+        }
+
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= batch.len();
+        }
+
+        Some(batch)
+    }
+
+    /// A single transaction's withdraws.
+    fn next_withdraw(&mut self) -> TxBalanceWithdraw {
+        let Workload {
+            max_accounts_per_tx,
+            max_withdraw,
+            hot_ratio,
+            ..
+        } = self.workload;
+
+        let num_accounts = self.rng.gen_range(1..=max_accounts_per_tx);
+        let hot_accounts = self.num_hot_accounts();
+        let reservations: Vec<_> = (0..num_accounts)
+            .map(|_| {
+                let account = if self.rng.gen_bool(hot_ratio) {
+                    self.accounts[self.rng.gen_range(0..hot_accounts)]
+                } else {
+                    self.accounts[self.rng.gen_range(0..self.accounts.len())]
+                };
+
+                (account, self.rng.gen_range(1..=max_withdraw))
+            })
+            .collect();
+
+        TxBalanceWithdraw::new_checked(
+            TransactionDigest::new(self.rng.gen()),
+            reservations,
+            max_accounts_per_tx,
+        )
+        .expect("generated reservations are valid")
+    }
+
+    fn num_hot_accounts(&self) -> usize {
+        self.workload.hot_accounts.clamp(1, self.accounts.len())
+    }
+}
@@ -10,47 +10,82 @@ use crate::{
     execution_cache::{ObjectCacheRead, TransactionCacheRead},
     execution_scheduler::{
         balance_withdraw_scheduler::{
-            scheduler::BalanceWithdrawScheduler, BalanceSettlement, ScheduleStatus,
-            TxBalanceWithdraw,
+            balance_cache::{BalanceReadCacheMetrics, CachedBalanceRead},
+            policy::{AllowAllWithdraws, WithdrawPolicy},
+            scheduler::{
+                BalanceWithdrawScheduler, SettlementMetrics, LONG_SETTLEMENT_WAIT_SETTLEMENTS,
+            },
+            shadow::{ShadowBalanceWithdrawScheduler, ShadowSchedulerMetrics},
+            AccountReservations, AmendReservationError, BalanceSettlement, ScheduleStatus,
+            SettlementReceipt, TxBalanceWithdraw, WithdrawAwaitingSettlement, WithdrawDrainStatus,
+            WithdrawSchedulerParams,
         },
         ExecutingGuard, PendingCertificateStats,
     },
 };
 use futures::stream::{FuturesUnordered, StreamExt};
-use mysten_common::debug_fatal;
+use mysten_common::{debug_fatal, random_util::randomize_cache_capacity_in_tests};
 use mysten_metrics::spawn_monitored_task;
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    num::NonZeroUsize,
     sync::Arc,
 };
-use sui_config::node::AuthorityOverloadConfig;
+use sui_config::node::{AuthorityOverloadConfig, ShadowWithdrawSchedulerConfig};
 use sui_types::{
-    base_types::{FullObjectID, SequenceNumber},
+    accumulator_event::AddressBalanceEvent,
+    base_types::{FullObjectID, ObjectID, SequenceNumber},
+    digests::TransactionDigest,
+    effects::{TransactionEffects, TransactionEffectsAPI},
     error::SuiResult,
     executable_transaction::VerifiedExecutableTransaction,
-    storage::{ChildObjectResolver, InputKey},
+    storage::{ChildObjectResolver, InputKey, SpendableBalance},
     transaction::{SenderSignedData, TransactionDataAPI, TransactionKey},
+    transaction_executor::{TransactionScheduleStatus, TransactionScheduleUpdate},
     SUI_ACCUMULATOR_ROOT_OBJECT_ID,
 };
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{broadcast, mpsc::UnboundedSender};
 use tokio::time::Instant;
 use tracing::{debug, error};
 
-use super::{overload_tracker::OverloadTracker, PendingCertificate};
+use super::{
+    overload_tracker::OverloadTracker,
+    package_limiter::{PackageLimiter, PackagePermits},
+    queue_tracker::{QueueGuard, QueueTracker, SchedulerQueue, SchedulerQueueSnapshot},
+    PendingCertificate,
+};
 
 #[derive(Clone)]
 pub struct ExecutionScheduler {
     object_cache_read: Arc<dyn ObjectCacheRead>,
     transaction_cache_read: Arc<dyn TransactionCacheRead>,
     overload_tracker: Arc<OverloadTracker>,
+    queue_tracker: Arc<QueueTracker>,
+    package_limiter: Arc<PackageLimiter>,
     tx_ready_certificates: UnboundedSender<PendingCertificate>,
     balance_withdraw_scheduler: Option<Arc<BalanceWithdrawScheduler>>,
+    /// Cache of account balances, shared by the balance withdraw scheduler and RPC reads.
+    balance_cache: Option<Arc<CachedBalanceRead>>,
+    /// Updates on how transactions are scheduled, for RPC clients following their transactions.
+    schedule_updates: broadcast::Sender<TransactionScheduleUpdate>,
     metrics: Arc<AuthorityMetrics>,
 }
 
+/// Number of accounts whose balances are cached for the balance withdraw scheduler.
+const BALANCE_READ_CACHE_CAPACITY: usize = 100_000;
+
+/// Number of recent settlements whose balance changes are retained, so that balances can still be
+/// read exactly at those versions. Bounds how late withdraws can be scheduled.
+const BALANCE_HISTORY_VERSIONS: usize = 16;
+
+/// Number of schedule updates buffered for each subscriber. Subscribers that fall further behind
+/// miss updates.
+const SCHEDULE_UPDATES_CHANNEL_CAPACITY: usize = 10_000;
+
 struct PendingGuard<'a> {
     scheduler: &'a ExecutionScheduler,
     cert: &'a VerifiedExecutableTransaction,
+    _queue_guard: QueueGuard,
 }
 
 impl<'a> PendingGuard<'a> {
@@ -62,7 +97,14 @@ impl<'a> PendingGuard<'a> {
         scheduler
             .overload_tracker
             .add_pending_certificate(cert.data());
-        Self { scheduler, cert }
+        let _queue_guard = scheduler
+            .queue_tracker
+            .enter(SchedulerQueue::ObjectLockWaiters, *cert.digest());
+        Self {
+            scheduler,
+            cert,
+            _queue_guard,
+        }
     }
 }
 
@@ -85,28 +127,117 @@ impl ExecutionScheduler {
         transaction_cache_read: Arc<dyn TransactionCacheRead>,
         tx_ready_certificates: UnboundedSender<PendingCertificate>,
         epoch_store: &Arc<AuthorityPerEpochStore>,
+        shadow_withdraw_scheduler: Option<&ShadowWithdrawSchedulerConfig>,
         metrics: Arc<AuthorityMetrics>,
     ) -> Self {
         tracing::info!("Creating new ExecutionScheduler");
         let balance_accumulator_enabled = epoch_store.accumulators_enabled();
-        let balance_withdraw_scheduler = if balance_accumulator_enabled {
+        let (balance_withdraw_scheduler, balance_cache) = if balance_accumulator_enabled {
             let starting_accumulator_version = object_cache_read
                 .get_object(&SUI_ACCUMULATOR_ROOT_OBJECT_ID)
                 .expect("Accumulator root object must be present if balance accumulator is enabled")
                 .version();
-            Some(BalanceWithdrawScheduler::new(
+            let balance_cache = Arc::new(CachedBalanceRead::new(
                 Arc::new(child_object_resolver),
                 starting_accumulator_version,
-            ))
+                NonZeroUsize::new(randomize_cache_capacity_in_tests(
+                    BALANCE_READ_CACHE_CAPACITY,
+                ))
+                .unwrap(),
+                BALANCE_HISTORY_VERSIONS,
+                Some(BalanceReadCacheMetrics {
+                    hits: metrics.balance_read_cache_hits.clone(),
+                    misses: metrics.balance_read_cache_misses.clone(),
+                    invalidations: metrics.balance_read_cache_invalidations.clone(),
+                }),
+            ));
+            // Transactions that were pending execution before a restart are scheduled against
+            // the settled balances of their accounts as soon as they are sequenced, or their
+            // deferral ends, so read those balances ahead of time, in the background, so that
+            // neither startup nor the first withdraws wait for the store. The cache only keeps
+            // balances read at the version that is still settled, so this can race settlements.
+            let warm_cache = balance_cache.clone();
+            let warm_epoch_store = epoch_store.clone();
+            tokio::task::spawn_blocking(move || {
+                let accounts = warm_epoch_store.pending_balance_withdraw_accounts();
+                if accounts.is_empty() {
+                    return;
+                }
+                tracing::info!(
+                    "Warming balance cache for {} accounts with pending withdraws",
+                    accounts.len()
+                );
+                for account_id in &accounts {
+                    warm_cache.get_settled_balance(account_id);
+                }
+            });
+            let balance_read = balance_cache.clone();
+            // No accounts are frozen yet, so every withdraw is allowed.
+            let policy: Arc<dyn WithdrawPolicy> = Arc::new(AllowAllWithdraws);
+            // Every validator must reach the same decisions, so the limit comes from the
+            // protocol config, and the shadow scheduler applies it too, so that it does not
+            // diverge from the live one only because of it.
+            let max_reservation_per_transaction = epoch_store
+                .protocol_config()
+                .max_withdraw_reservation_per_tx_as_option();
+            // The same goes for how late withdraws can be scheduled. No more versions than the
+            // balance cache retains can be scheduled at, and withdraws at versions it does not
+            // retain are treated as already executed, as if late withdraws were disabled.
+            let late_withdraw_versions = epoch_store
+                .protocol_config()
+                .late_withdraw_versions_as_option()
+                .unwrap_or(0)
+                .min(BALANCE_HISTORY_VERSIONS as u64);
+            let shadow = shadow_withdraw_scheduler.map(|config| {
+                ShadowBalanceWithdrawScheduler::new(
+                    balance_read.clone(),
+                    starting_accumulator_version,
+                    WithdrawSchedulerParams {
+                        max_reservation_per_transaction,
+                        late_withdraw_versions,
+                        ..WithdrawSchedulerParams::from(config)
+                    },
+                    policy.clone(),
+                    ShadowSchedulerMetrics {
+                        results: metrics.balance_withdraw_shadow_schedule_results.clone(),
+                        divergences: metrics.balance_withdraw_shadow_divergences.clone(),
+                    },
+                )
+            });
+            let scheduler = BalanceWithdrawScheduler::new_with_shadow(
+                balance_read,
+                starting_accumulator_version,
+                WithdrawSchedulerParams {
+                    max_reservation_per_transaction,
+                    late_withdraw_versions,
+                    ..Default::default()
+                },
+                policy,
+                shadow,
+                Some(SettlementMetrics {
+                    application_latency: metrics.balance_settlement_application_latency.clone(),
+                    queue_latency: metrics.balance_settlement_queue_latency.clone(),
+                    queued_behind_unapplied: metrics
+                        .balance_settlements_queued_behind_unapplied
+                        .clone(),
+                    long_settlement_waits: metrics.balance_withdraws_long_settlement_wait.clone(),
+                    reservation_wait: metrics.balance_withdraw_reservation_wait.clone(),
+                }),
+            );
+            (Some(scheduler), Some(balance_cache))
         } else {
-            None
+            (None, None)
         };
         Self {
             object_cache_read,
             transaction_cache_read,
             overload_tracker: Arc::new(OverloadTracker::new()),
+            queue_tracker: Arc::new(QueueTracker::default()),
+            package_limiter: Arc::new(PackageLimiter::default()),
             tx_ready_certificates,
             balance_withdraw_scheduler,
+            balance_cache,
+            schedule_updates: broadcast::channel(SCHEDULE_UPDATES_CHANNEL_CAPACITY).0,
             metrics,
         }
     }
@@ -212,18 +343,65 @@ impl ExecutionScheduler {
         execution_env: ExecutionEnv,
         enqueue_time: Instant,
     ) {
+        let packages = self
+            .package_limiter
+            .limited_packages(cert.transaction_data());
+        if packages.is_empty() {
+            self.send_ready_certificate(
+                cert.clone(),
+                execution_env,
+                enqueue_time,
+                PackagePermits::default(),
+            );
+            return;
+        }
+
+        // The transaction is ready, but has to wait for a slot in the packages it calls into.
+        // Waiting happens off to the side, so that it doesn't hold up transactions that call into
+        // other packages, including ones that depend on the same shared objects.
+        let scheduler = self.clone();
+        let cert = cert.clone();
+        let waiting = self
+            .metrics
+            .transaction_manager_num_pending_certificates
+            .clone();
+        let queue_guard = self
+            .queue_tracker
+            .enter(SchedulerQueue::PackageLimitWaiters, *cert.digest());
+
+        waiting.inc();
+        spawn_monitored_task!(async move {
+            let permits = scheduler.package_limiter.acquire(&packages).await;
+            waiting.dec();
+            drop(queue_guard);
+            scheduler.send_ready_certificate(cert, execution_env, enqueue_time, permits);
+        });
+    }
+
+    fn send_ready_certificate(
+        &self,
+        cert: VerifiedExecutableTransaction,
+        execution_env: ExecutionEnv,
+        enqueue_time: Instant,
+        package_permits: PackagePermits,
+    ) {
+        let executing_guard = ExecutingGuard::new(
+            self.metrics
+                .transaction_manager_num_executing_certificates
+                .clone(),
+            self.queue_tracker
+                .enter(SchedulerQueue::ReadyToExecute, *cert.digest()),
+        );
+
         let pending_cert = PendingCertificate {
-            certificate: cert.clone(),
+            certificate: cert,
             execution_env,
             stats: PendingCertificateStats {
                 enqueue_time,
                 ready_time: Some(Instant::now()),
             },
-            executing_guard: Some(ExecutingGuard::new(
-                self.metrics
-                    .transaction_manager_num_executing_certificates
-                    .clone(),
-            )),
+            executing_guard: Some(executing_guard),
+            package_permits,
         };
         let _ = self.tx_ready_certificates.send(pending_cert);
     }
@@ -240,28 +418,70 @@ impl ExecutionScheduler {
             .balance_withdraw_scheduler
             .as_ref()
             .expect("Balance withdraw scheduler must be enabled if there are withdraws");
+        let max_accounts = epoch_store
+            .protocol_config()
+            .max_balance_withdraws_per_tx_as_option();
         let mut withdraws = BTreeMap::new();
+        let mut scheduled = Vec::with_capacity(certs.len());
+        let mut rejected = Vec::new();
         let mut prev_version = None;
-        for (cert, version, _) in &certs {
-            let tx_withdraws = cert
-                .transaction_data()
-                .process_balance_withdraws()
-                .expect("Balance withdraws should have already been checked");
-            assert!(!tx_withdraws.is_empty());
+        for (cert, version, env) in certs {
             if let Some(prev_version) = prev_version {
                 // Transactions must be in order.
-                assert!(prev_version <= *version);
+                assert!(prev_version <= version);
             }
-            prev_version = Some(*version);
-            let tx_digest = *cert.digest();
-            withdraws
-                .entry(*version)
-                .or_insert(Vec::new())
-                .push(TxBalanceWithdraw {
-                    tx_digest,
-                    reservations: tx_withdraws,
+            prev_version = Some(version);
+
+            // Withdraws are checked before transactions are signed, but a transaction that was
+            // certified under different limits, or a bug in those checks, must not bring the
+            // validator down. The outcome only depends on the transaction and the protocol
+            // config, so every validator rejects the same transactions.
+            let tx_withdraw = cert
+                .transaction_data()
+                .process_balance_withdraws()
+                .map_err(|e| e.to_string())
+                .and_then(|tx_withdraws| {
+                    let max_accounts = max_accounts.ok_or_else(|| {
+                        "max_balance_withdraws_per_tx is not configured".to_string()
+                    })?;
+                    TxBalanceWithdraw::new_checked(
+                        *cert.digest(),
+                        tx_withdraws,
+                        max_accounts as usize,
+                    )
+                    .map_err(|e| e.to_string())
                 });
+
+            match tx_withdraw {
+                Ok(tx_withdraw) => {
+                    withdraws
+                        .entry(version)
+                        .or_insert(Vec::new())
+                        .push(tx_withdraw);
+                    scheduled.push((cert, env));
+                }
+                Err(error) => {
+                    error!(
+                        tx_digest = ?cert.digest(),
+                        "Rejecting transaction with invalid balance withdraws: {error}"
+                    );
+                    // The transaction fails without executing, the same way as when its
+                    // accounts have insufficient balance.
+                    self.publish_schedule_update(
+                        *cert.digest(),
+                        TransactionScheduleStatus::InsufficientBalance,
+                    );
+                    rejected.push((cert, env.with_insufficient_balance()));
+                }
+            }
+        }
+        if !rejected.is_empty() {
+            self.enqueue_transactions(rejected, epoch_store);
         }
+        if scheduled.is_empty() {
+            return;
+        }
+
         let mut receivers = FuturesUnordered::new();
         for (version, tx_withdraws) in withdraws {
             receivers.extend(scheduler.schedule_withdraws(version, tx_withdraws));
@@ -270,32 +490,83 @@ impl ExecutionScheduler {
         let epoch_store = epoch_store.clone();
         spawn_monitored_task!(epoch_store.clone().within_alive_epoch(async move {
             let mut cert_map = HashMap::new();
-            for (cert, _, env) in certs {
-                cert_map.insert(*cert.digest(), (cert, env));
+            for (cert, env) in scheduled {
+                let queue_guard = scheduler
+                    .queue_tracker
+                    .enter(SchedulerQueue::BalanceWaiters, *cert.digest());
+                scheduler.publish_schedule_update(
+                    *cert.digest(),
+                    TransactionScheduleStatus::BalanceReservationPending,
+                );
+                cert_map.insert(*cert.digest(), (cert, env, queue_guard));
             }
             while let Some(result) = receivers.next().await {
+                if let Ok(result) = &result {
+                    match result.status {
+                        ScheduleStatus::SufficientBalance => scheduler.publish_schedule_update(
+                            result.tx_digest,
+                            TransactionScheduleStatus::SufficientBalance,
+                        ),
+                        ScheduleStatus::InsufficientBalance
+                        | ScheduleStatus::PolicyRejected
+                        | ScheduleStatus::Rejected => scheduler.publish_schedule_update(
+                            result.tx_digest,
+                            TransactionScheduleStatus::InsufficientBalance,
+                        ),
+                        ScheduleStatus::AlreadyExecuted => (),
+                    }
+                }
                 match result {
                     Ok(result) => match result.status {
                         ScheduleStatus::InsufficientBalance => {
                             let tx_digest = result.tx_digest;
                             debug!(
                                 ?tx_digest,
+                                shortfalls = ?result.details,
                                 "Balance withdraw scheduling result: Insufficient balance"
                             );
-                            let (cert, env) = cert_map.remove(&tx_digest).expect("cert must exist");
+                            let (cert, env, _) =
+                                cert_map.remove(&tx_digest).expect("cert must exist");
+                            let env = env.with_insufficient_balance();
+                            scheduler.enqueue_transactions(vec![(cert, env)], &epoch_store);
+                        }
+                        ScheduleStatus::PolicyRejected => {
+                            let tx_digest = result.tx_digest;
+                            debug!(
+                                ?tx_digest,
+                                "Balance withdraw scheduling result: Rejected by withdraw policy"
+                            );
+                            // The transaction fails without executing, the same way as when
+                            // its accounts have insufficient balance.
+                            let (cert, env, _) =
+                                cert_map.remove(&tx_digest).expect("cert must exist");
+                            let env = env.with_insufficient_balance();
+                            scheduler.enqueue_transactions(vec![(cert, env)], &epoch_store);
+                        }
+                        ScheduleStatus::Rejected => {
+                            let tx_digest = result.tx_digest;
+                            debug!(
+                                ?tx_digest,
+                                "Balance withdraw scheduling result: Exceeds the per-transaction \
+                                 reservation limit"
+                            );
+                            let (cert, env, _) =
+                                cert_map.remove(&tx_digest).expect("cert must exist");
                             let env = env.with_insufficient_balance();
                             scheduler.enqueue_transactions(vec![(cert, env)], &epoch_store);
                         }
                         ScheduleStatus::SufficientBalance => {
                             let tx_digest = result.tx_digest;
                             debug!(?tx_digest, "Balance withdraw scheduling result: Success");
-                            let (cert, env) = cert_map.remove(&tx_digest).expect("cert must exist");
+                            let (cert, env, _) =
+                                cert_map.remove(&tx_digest).expect("cert must exist");
                             let env = env.with_sufficient_balance();
                             scheduler.enqueue_transactions(vec![(cert, env)], &epoch_store);
                         }
                         ScheduleStatus::AlreadyExecuted => {
                             let tx_digest = result.tx_digest;
                             debug!(?tx_digest, "Withdraw already executed");
+                            cert_map.remove(&tx_digest);
                         }
                     },
                     Err(e) => {
@@ -498,11 +769,159 @@ impl ExecutionScheduler {
             .inc_by(already_executed_certs_num);
     }
 
-    pub fn settle_balances(&self, settlement: BalanceSettlement) {
+    /// Settle balances with `settlement`, whose balance changes were produced at `produced_at`,
+    /// and which advanced the accumulator root to `settled_version`. Settlements that were
+    /// already applied to the store before the scheduler was created are ignored.
+    pub fn settle_balances(
+        &self,
+        settled_version: SequenceNumber,
+        settlement: BalanceSettlement,
+        produced_at: Instant,
+    ) {
         self.balance_withdraw_scheduler
             .as_ref()
             .expect("Balance withdraw scheduler must be enabled if there are settlements")
-            .settle_balances(settlement);
+            .settle_balances_at(settled_version, settlement, produced_at);
+    }
+
+    /// Credit the amounts deposited into address balance accounts by a transaction at
+    /// `accumulator_version`, according to its committed `effects`, to withdraws scheduled at
+    /// later versions, ahead of settlement. Does nothing if accumulators are disabled.
+    pub fn record_balance_deposits(
+        &self,
+        accumulator_version: SequenceNumber,
+        effects: &TransactionEffects,
+    ) {
+        let Some(scheduler) = &self.balance_withdraw_scheduler else {
+            return;
+        };
+
+        let mut deposits: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for event in effects.accumulator_events() {
+            if let Some(AddressBalanceEvent::Deposit { amount, .. }) = event.address_balance_event()
+            {
+                let deposit = deposits.entry(event.accumulator_obj).or_default();
+                *deposit = deposit.saturating_add(amount);
+            }
+        }
+
+        scheduler.record_deposits(accumulator_version, *effects.transaction_digest(), deposits);
+    }
+
+    /// Subscribe to updates on how transactions are scheduled. Only transactions that withdraw
+    /// from address balances are reported, as they wait for their withdraws to be reserved.
+    pub fn subscribe_schedule_updates(&self) -> broadcast::Receiver<TransactionScheduleUpdate> {
+        self.schedule_updates.subscribe()
+    }
+
+    fn publish_schedule_update(
+        &self,
+        tx_digest: TransactionDigest,
+        status: TransactionScheduleStatus,
+    ) {
+        // Nobody may be subscribed, which is not an error.
+        let _ = self
+            .schedule_updates
+            .send(TransactionScheduleUpdate { tx_digest, status });
+    }
+
+    /// Subscribe to receipts reconciling the balance reserved by each scheduled withdraw with
+    /// the amount actually withdrawn, as accumulator versions are settled. Returns `None` if
+    /// accumulators are disabled.
+    pub fn subscribe_settlement_receipts(&self) -> Option<broadcast::Receiver<SettlementReceipt>> {
+        self.balance_withdraw_scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.subscribe_settlement_receipts())
+    }
+
+    /// Returns the balance of the given address balance account as of the last accumulator
+    /// version settled by the balance withdraw scheduler, together with the deposits and
+    /// reservations made at versions that have not been settled yet. All of them are relative to
+    /// the same settled version. Returns `None` if accumulators are disabled.
+    pub fn get_spendable_balance(&self, account_id: &ObjectID) -> Option<SpendableBalance> {
+        let scheduler = self.balance_withdraw_scheduler.as_ref()?;
+        let cache = self.balance_cache.as_ref()?;
+
+        // The cache is told about each settlement before the scheduler, so it can always read the
+        // balance at the scheduler's settled version.
+        let AccountReservations {
+            settled_version,
+            reserved,
+            pending_deposits,
+        } = scheduler.get_account_reservations(account_id);
+        let settled = cache.get_balance_at(account_id, settled_version);
+
+        Some(SpendableBalance::new(settled, pending_deposits, reserved))
+    }
+
+    /// Returns the amount reserved from the given address balance account by scheduled
+    /// withdraws that have not been settled yet. Always 0 if accumulators are disabled.
+    pub fn get_reserved_balance(&self, account_id: &ObjectID) -> u64 {
+        self.balance_withdraw_scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.get_reserved_balance(account_id))
+            .unwrap_or(0)
+    }
+
+    /// Replace the balance reserved by a transaction that was scheduled with sufficient balance,
+    /// but whose accumulator version has not been settled yet. With accumulators disabled,
+    /// nothing is ever reserved, so this always fails with `NotReserved`.
+    pub(crate) fn amend_balance_reservation(
+        &self,
+        tx_digest: &TransactionDigest,
+        new_reservations: BTreeMap<ObjectID, u64>,
+    ) -> Result<(), AmendReservationError> {
+        match &self.balance_withdraw_scheduler {
+            Some(scheduler) => scheduler.amend_reservation(tx_digest, new_reservations),
+            None => Err(AmendReservationError::NotReserved {
+                tx_digest: *tx_digest,
+            }),
+        }
+    }
+
+    /// Start or stop draining the balance withdraw scheduler ahead of a planned restart. Returns
+    /// `None` if accumulators are disabled.
+    pub fn set_withdraw_draining(&self, draining: bool) -> Option<WithdrawDrainStatus> {
+        self.balance_withdraw_scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.set_draining(draining))
+    }
+
+    /// Returns whether the balance withdraw scheduler is draining, and whether it has drained.
+    /// Returns `None` if accumulators are disabled.
+    pub fn withdraw_drain_status(&self) -> Option<WithdrawDrainStatus> {
+        self.balance_withdraw_scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.drain_status())
+    }
+
+    /// Returns the withdraws that have been waiting for their accumulator version to be settled
+    /// while at least `min_settlements` versions were settled, or the settlement wait watchdog's
+    /// threshold if it is not set. Returns `None` if accumulators are disabled.
+    pub fn withdraws_awaiting_settlement(
+        &self,
+        min_settlements: Option<u64>,
+    ) -> Option<Vec<WithdrawAwaitingSettlement>> {
+        let min_settlements = min_settlements.unwrap_or(LONG_SETTLEMENT_WAIT_SETTLEMENTS);
+        self.balance_withdraw_scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.withdraws_awaiting_settlement(min_settlements))
+    }
+
+    /// Returns the depth of, and the oldest transaction waiting in, each of the scheduler's
+    /// queues.
+    pub fn queue_snapshots(&self) -> Vec<SchedulerQueueSnapshot> {
+        self.queue_tracker.snapshot()
+    }
+
+    /// Replace the limits on how many transactions calling into each package can be executing at
+    /// once. Packages that are not in `limits` are no longer limited.
+    pub fn set_package_limits(&self, limits: &BTreeMap<ObjectID, NonZeroUsize>) {
+        self.package_limiter.set_limits(limits);
+    }
+
+    pub fn package_limits(&self) -> BTreeMap<ObjectID, NonZeroUsize> {
+        self.package_limiter.limits()
     }
 
     pub fn check_execution_overload(
@@ -534,9 +953,10 @@ impl ExecutionScheduler {
 
 #[cfg(test)]
 mod test {
-    use std::{time::Duration, vec};
+    use std::{collections::BTreeMap, time::Duration, vec};
 
     use crate::authority::shared_object_version_manager::AssignedVersions;
+    use nonzero_ext::nonzero;
     use sui_test_transaction_builder::TestTransactionBuilder;
     use sui_types::executable_transaction::VerifiedExecutableTransaction;
     use sui_types::object::Owner;
@@ -546,7 +966,7 @@ mod test {
         crypto::deterministic_random_account_key,
         object::Object,
         transaction::{CallArg, ObjectArg},
-        SUI_FRAMEWORK_PACKAGE_ID,
+        MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID,
     };
     use tokio::time::Instant;
     use tokio::{
@@ -556,7 +976,7 @@ mod test {
 
     use crate::authority::ExecutionEnv;
     use crate::authority::{authority_tests::init_state_with_objects, AuthorityState};
-    use crate::execution_scheduler::SchedulingSource;
+    use crate::execution_scheduler::{SchedulerQueue, SchedulingSource};
 
     use super::{ExecutionScheduler, PendingCertificate};
 
@@ -573,6 +993,7 @@ mod test {
             state.get_transaction_cache_reader().clone(),
             tx_ready_certificates,
             &state.epoch_store_for_testing(),
+            None,
             state.metrics.clone(),
         );
 
@@ -580,13 +1001,21 @@ mod test {
     }
 
     fn make_transaction(gas_object: Object, input: Vec<CallArg>) -> VerifiedExecutableTransaction {
-        // Use fake module, function, package and gas prices since they are irrelevant for testing
+        make_package_transaction(SUI_FRAMEWORK_PACKAGE_ID, gas_object, input)
+    }
+
+    fn make_package_transaction(
+        package: ObjectID,
+        gas_object: Object,
+        input: Vec<CallArg>,
+    ) -> VerifiedExecutableTransaction {
+        // Use fake module, function and gas prices since they are irrelevant for testing
         // execution scheduler.
         let rgp = 100;
         let (sender, keypair) = deterministic_random_account_key();
         let transaction =
             TestTransactionBuilder::new(sender, gas_object.compute_object_reference(), rgp)
-                .move_call(SUI_FRAMEWORK_PACKAGE_ID, "counter", "assert_value", input)
+                .move_call(package, "counter", "assert_value", input)
                 .build_and_sign(&keypair);
         VerifiedExecutableTransaction::new_system(
             VerifiedTransaction::new_unchecked(transaction),
@@ -1427,4 +1856,126 @@ mod test {
 
         execution_scheduler.check_empty_for_testing();
     }
+
+    // Tests that transactions calling into a package at its execution limit wait for a slot,
+    // without holding up transactions that call into other packages, including ones waiting on
+    // shared objects, and that limits can be changed while transactions are waiting.
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn execution_scheduler_package_limits() {
+        telemetry_subscribers::init_for_testing();
+        let (owner, _keypair) = deterministic_random_account_key();
+        let gas_objects: Vec<Object> = (0..10)
+            .map(|_| {
+                let gas_object_id = ObjectID::random();
+                Object::with_id_owner_for_testing(gas_object_id, owner)
+            })
+            .collect();
+        let shared_object = Object::shared_for_testing();
+        let initial_shared_version = shared_object.owner().start_version().unwrap();
+
+        let state =
+            init_state_with_objects([gas_objects.clone(), vec![shared_object.clone()]].concat())
+                .await;
+
+        let (execution_scheduler, mut rx_ready_certificates) = make_execution_scheduler(&state);
+        execution_scheduler.set_package_limits(&BTreeMap::from([(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            nonzero!(1usize),
+        )]));
+
+        let package_limit_waiters = |scheduler: &ExecutionScheduler| {
+            scheduler
+                .queue_snapshots()
+                .into_iter()
+                .find(|snapshot| snapshot.queue == SchedulerQueue::PackageLimitWaiters)
+                .unwrap()
+                .depth
+        };
+
+        // Two transactions calling into the limited package are ready at the same time, but only
+        // one of them can be executing.
+        let transaction_0 = make_transaction(gas_objects[0].clone(), vec![]);
+        let transaction_1 = make_transaction(gas_objects[1].clone(), vec![]);
+        execution_scheduler.enqueue_transactions(
+            vec![
+                (transaction_0.clone(), ExecutionEnv::new()),
+                (transaction_1.clone(), ExecutionEnv::new()),
+            ],
+            &state.epoch_store_for_testing(),
+        );
+
+        let executing = rx_ready_certificates.recv().await.unwrap();
+        sleep(Duration::from_secs(1)).await;
+        assert!(rx_ready_certificates.try_recv().is_err());
+        assert_eq!(package_limit_waiters(&execution_scheduler), 1);
+        assert_eq!(execution_scheduler.num_pending_certificates(), 2);
+
+        // A transaction calling into another package, that waits on a shared object, is sent for
+        // execution as soon as the shared object is available.
+        let shared_version = 1000.into();
+        let transaction_2 = make_package_transaction(
+            MOVE_STDLIB_PACKAGE_ID,
+            gas_objects[2].clone(),
+            vec![CallArg::Object(ObjectArg::SharedObject {
+                id: shared_object.id(),
+                initial_shared_version,
+                mutable: true,
+            })],
+        );
+        execution_scheduler.enqueue_transactions(
+            vec![(
+                transaction_2.clone(),
+                ExecutionEnv::new().with_assigned_versions(AssignedVersions::non_withdraw(vec![(
+                    (shared_object.id(), initial_shared_version),
+                    shared_version,
+                )])),
+            )],
+            &state.epoch_store_for_testing(),
+        );
+
+        sleep(Duration::from_secs(1)).await;
+        assert!(rx_ready_certificates.try_recv().is_err());
+
+        let mut new_shared_object = shared_object.clone();
+        new_shared_object
+            .data
+            .try_as_move_mut()
+            .unwrap()
+            .increment_version_to(shared_version);
+        state
+            .get_cache_writer()
+            .write_object_entry_for_test(new_shared_object);
+
+        let other_package = rx_ready_certificates.recv().await.unwrap();
+        assert_eq!(other_package.certificate.digest(), transaction_2.digest());
+        assert_eq!(package_limit_waiters(&execution_scheduler), 1);
+
+        // Once the executing transaction finishes, the waiting one gets its slot.
+        let executing_digest = *executing.certificate.digest();
+        drop(executing);
+        let waited = rx_ready_certificates.recv().await.unwrap();
+        {
+            let mut want_digests = vec![*transaction_0.digest(), *transaction_1.digest()];
+            want_digests.sort();
+            let mut got_digests = vec![executing_digest, *waited.certificate.digest()];
+            got_digests.sort();
+            assert_eq!(want_digests, got_digests);
+        }
+        assert_eq!(package_limit_waiters(&execution_scheduler), 0);
+
+        // Removing the limit lets transactions through while the package's slot is taken.
+        execution_scheduler.set_package_limits(&BTreeMap::new());
+        let transaction_3 = make_transaction(gas_objects[3].clone(), vec![]);
+        execution_scheduler.enqueue_transactions(
+            vec![(transaction_3.clone(), ExecutionEnv::new())],
+            &state.epoch_store_for_testing(),
+        );
+        let unlimited = rx_ready_certificates.recv().await.unwrap();
+        assert_eq!(unlimited.certificate.digest(), transaction_3.digest());
+
+        drop(other_package);
+        drop(waited);
+        drop(unlimited);
+        execution_scheduler.check_empty_for_testing();
+    }
 }
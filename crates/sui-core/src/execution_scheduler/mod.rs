@@ -2,14 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::authority::ExecutionEnv;
+#[cfg(feature = "balance-scheduler-bench")]
+pub use balance_withdraw_scheduler::bench as balance_withdraw_scheduler_bench;
+pub use balance_withdraw_scheduler::{
+    SettlementReceipt, WithdrawAwaitingSettlement, WithdrawDrainStatus,
+};
 pub use execution_scheduler_impl::ExecutionScheduler;
+use package_limiter::PackagePermits;
 use prometheus::IntGauge;
+use queue_tracker::QueueGuard;
+pub use queue_tracker::{SchedulerQueue, SchedulerQueueSnapshot};
 use sui_types::executable_transaction::VerifiedExecutableTransaction;
 use tokio::time::Instant;
 
 pub(crate) mod balance_withdraw_scheduler;
 pub(crate) mod execution_scheduler_impl;
 mod overload_tracker;
+mod package_limiter;
+mod queue_tracker;
 
 // TODO: Cleanup this struct.
 #[derive(Clone, Debug)]
@@ -36,18 +46,23 @@ pub struct PendingCertificate {
     // Stores stats about this transaction.
     pub stats: PendingCertificateStats,
     pub executing_guard: Option<ExecutingGuard>,
+    // Execution slots in the packages it calls into, held until it finishes executing.
+    pub package_permits: PackagePermits,
 }
 
 #[derive(Debug)]
 pub struct ExecutingGuard {
     num_executing_certificates: IntGauge,
+    // Keeps the certificate in the ready-to-execute queue until it is picked up for execution.
+    _queue_guard: QueueGuard,
 }
 
 impl ExecutingGuard {
-    pub fn new(num_executing_certificates: IntGauge) -> Self {
+    pub(crate) fn new(num_executing_certificates: IntGauge, queue_guard: QueueGuard) -> Self {
         num_executing_certificates.inc();
         Self {
             num_executing_certificates,
+            _queue_guard: queue_guard,
         }
     }
 }
@@ -0,0 +1,184 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use parking_lot::RwLock;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroUsize,
+    sync::Arc,
+};
+use sui_types::{base_types::ObjectID, transaction::TransactionDataAPI};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits how many transactions calling into each of a set of Move packages can be executing at
+/// once, so that a congested package cannot take over every execution slot.
+///
+/// Limits only delay when a transaction that is ready is handed to the execution driver, so they
+/// never affect execution results. Limits can be changed at any time: transactions that are
+/// already executing keep their slots, and transactions that are waiting re-evaluate against the
+/// new limit.
+#[derive(Debug, Default)]
+pub(crate) struct PackageLimiter {
+    limits: RwLock<BTreeMap<ObjectID, PackageLimit>>,
+}
+
+#[derive(Debug)]
+struct PackageLimit {
+    limit: NonZeroUsize,
+    slots: Arc<Semaphore>,
+}
+
+/// Execution slots held by a transaction, released when it finishes executing.
+#[derive(Debug, Default)]
+pub struct PackagePermits {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl PackageLimiter {
+    /// Replace every package's limit with `limits`. Packages that are not in `limits` are no
+    /// longer limited.
+    pub(crate) fn set_limits(&self, limits: &BTreeMap<ObjectID, NonZeroUsize>) {
+        let mut current = self.limits.write();
+        current.retain(|package, limit| {
+            let keep = limits.get(package) == Some(&limit.limit);
+            if !keep {
+                // Wakes up transactions waiting on the old limit, to wait on the new one instead.
+                limit.slots.close();
+            }
+            keep
+        });
+
+        for (package, limit) in limits {
+            current.entry(*package).or_insert_with(|| PackageLimit {
+                limit: *limit,
+                slots: Arc::new(Semaphore::new(limit.get())),
+            });
+        }
+    }
+
+    pub(crate) fn limits(&self) -> BTreeMap<ObjectID, NonZeroUsize> {
+        self.limits
+            .read()
+            .iter()
+            .map(|(package, limit)| (*package, limit.limit))
+            .collect()
+    }
+
+    /// The limited packages that the transaction calls into, in a consistent order.
+    pub(crate) fn limited_packages(&self, tx_data: &impl TransactionDataAPI) -> Vec<ObjectID> {
+        let limits = self.limits.read();
+        if limits.is_empty() {
+            return vec![];
+        }
+
+        let packages: BTreeSet<_> = tx_data
+            .move_calls()
+            .into_iter()
+            .map(|(package, _, _)| *package)
+            .filter(|package| limits.contains_key(package))
+            .collect();
+
+        packages.into_iter().collect()
+    }
+
+    /// Wait for an execution slot in each of `packages`.
+    ///
+    /// Slots are acquired one package at a time, in the order returned by [`limited_packages`],
+    /// so that two transactions calling into the same packages cannot each hold a slot that the
+    /// other is waiting for. Transactions that hold slots are ready to execute, so they always
+    /// release them eventually, regardless of what other transactions are waiting for.
+    ///
+    /// [`limited_packages`]: Self::limited_packages
+    pub(crate) async fn acquire(&self, packages: &[ObjectID]) -> PackagePermits {
+        let mut permits = Vec::with_capacity(packages.len());
+        for package in packages {
+            loop {
+                let Some(slots) = self.slots(package) else {
+                    // The package is no longer limited.
+                    break;
+                };
+
+                // Acquisition only fails if the limit was changed while waiting.
+                if let Ok(permit) = slots.acquire_owned().await {
+                    permits.push(permit);
+                    break;
+                }
+            }
+        }
+
+        PackagePermits { _permits: permits }
+    }
+
+    fn slots(&self, package: &ObjectID) -> Option<Arc<Semaphore>> {
+        self.limits
+            .read()
+            .get(package)
+            .map(|limit| limit.slots.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nonzero_ext::nonzero;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limit_in_flight() {
+        let package = ObjectID::random();
+        let limiter = Arc::new(PackageLimiter::default());
+        limiter.set_limits(&BTreeMap::from([(package, nonzero!(2usize))]));
+
+        let first = limiter.acquire(&[package]).await;
+        let _second = limiter.acquire(&[package]).await;
+
+        // The third transaction waits until one of the first two finishes executing.
+        let third = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(&[package]).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!third.is_finished());
+
+        drop(first);
+        timeout(Duration::from_secs(1), third)
+            .await
+            .expect("Slot was not released")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_change_limits() {
+        let package = ObjectID::random();
+        let limiter = Arc::new(PackageLimiter::default());
+        limiter.set_limits(&BTreeMap::from([(package, nonzero!(1usize))]));
+
+        let _first = limiter.acquire(&[package]).await;
+        let second = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(&[package]).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!second.is_finished());
+
+        // Raising the limit lets the waiting transaction through, while the first one is still
+        // executing.
+        limiter.set_limits(&BTreeMap::from([(package, nonzero!(2usize))]));
+        let _second = timeout(Duration::from_secs(1), second)
+            .await
+            .expect("Waiter was not woken up by the new limit")
+            .unwrap();
+
+        // Removing the limit lets every transaction through.
+        limiter.set_limits(&BTreeMap::new());
+        assert!(limiter.limits().is_empty());
+        timeout(Duration::from_secs(1), limiter.acquire(&[package]))
+            .await
+            .expect("Package is still limited");
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use sui_types::digests::TransactionDigest;
+use tokio::time::Instant;
+
+/// Number of shards each queue's entries are spread across, so that transactions entering and
+/// leaving a queue concurrently rarely contend for the same lock.
+const QUEUE_SHARDS: usize = 16;
+
+/// The queues a transaction can wait in while it is in the execution scheduler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulerQueue {
+    /// Waiting for input objects (including shared object versions) to become available.
+    ObjectLockWaiters,
+    /// Waiting for the balance withdraw scheduler to decide whether its withdraws have
+    /// sufficient balance.
+    BalanceWaiters,
+    /// Waiting for an execution slot in a package that limits how many of the transactions
+    /// calling into it can be executing at once.
+    PackageLimitWaiters,
+    /// Ready, waiting for the execution driver to pick it up.
+    ReadyToExecute,
+}
+
+impl SchedulerQueue {
+    const ALL: [SchedulerQueue; 4] = [
+        SchedulerQueue::ObjectLockWaiters,
+        SchedulerQueue::BalanceWaiters,
+        SchedulerQueue::PackageLimitWaiters,
+        SchedulerQueue::ReadyToExecute,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Point-in-time view of a single scheduler queue.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerQueueSnapshot {
+    pub queue: SchedulerQueue,
+    pub depth: usize,
+    /// The transaction that has been waiting in this queue for the longest time.
+    pub oldest_digest: Option<TransactionDigest>,
+    pub oldest_age_ms: Option<u64>,
+}
+
+/// Tracks which transactions are waiting in each of the execution scheduler's queues, and
+/// since when. Only used for introspection, scheduling decisions never depend on it.
+///
+/// Each entry into a queue gets a ticket of its own, so the same transaction can be in a queue
+/// more than once (e.g. if it is enqueued again while still waiting), and leaving the queue only
+/// removes the entry it was given. Tickets are handed out in order, so within each shard, the
+/// entry that has waited longest is the first one.
+#[derive(Debug, Default)]
+pub(crate) struct QueueTracker {
+    next_ticket: AtomicU64,
+    queues: [Queue; 4],
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    shards: [Mutex<BTreeMap<u64, (TransactionDigest, Instant)>>; QUEUE_SHARDS],
+}
+
+impl QueueTracker {
+    /// Records `digest` as waiting in `queue` until the returned guard is dropped.
+    pub(crate) fn enter(
+        self: &Arc<Self>,
+        queue: SchedulerQueue,
+        digest: TransactionDigest,
+    ) -> QueueGuard {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        self.queues[queue.index()]
+            .shard(ticket)
+            .lock()
+            .insert(ticket, (digest, Instant::now()));
+        QueueGuard {
+            tracker: self.clone(),
+            queue,
+            ticket,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<SchedulerQueueSnapshot> {
+        SchedulerQueue::ALL
+            .into_iter()
+            .map(|queue| {
+                let mut depth = 0;
+                let mut oldest: Option<(TransactionDigest, Instant)> = None;
+                for shard in &self.queues[queue.index()].shards {
+                    let waiting = shard.lock();
+                    depth += waiting.len();
+                    if let Some((_, (digest, since))) = waiting.first_key_value() {
+                        if oldest.is_none_or(|(_, oldest_since)| *since < oldest_since) {
+                            oldest = Some((*digest, *since));
+                        }
+                    }
+                }
+
+                SchedulerQueueSnapshot {
+                    queue,
+                    depth,
+                    oldest_digest: oldest.map(|(digest, _)| digest),
+                    oldest_age_ms: oldest.map(|(_, since)| since.elapsed().as_millis() as u64),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Queue {
+    fn shard(&self, ticket: u64) -> &Mutex<BTreeMap<u64, (TransactionDigest, Instant)>> {
+        &self.shards[ticket as usize % QUEUE_SHARDS]
+    }
+}
+
+/// Removes a transaction's entry from a [`QueueTracker`] queue when dropped.
+#[derive(Debug)]
+pub(crate) struct QueueGuard {
+    tracker: Arc<QueueTracker>,
+    queue: SchedulerQueue,
+    ticket: u64,
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        self.tracker.queues[self.queue.index()]
+            .shard(self.ticket)
+            .lock()
+            .remove(&self.ticket);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_snapshot() {
+        let tracker = Arc::new(QueueTracker::default());
+        let first = TransactionDigest::random();
+        let second = TransactionDigest::random();
+
+        let first_guard = tracker.enter(SchedulerQueue::BalanceWaiters, first);
+        tokio::time::advance(std::time::Duration::from_millis(10)).await;
+        let _second_guard = tracker.enter(SchedulerQueue::BalanceWaiters, second);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.len(), 4);
+        let balance = &snapshot[SchedulerQueue::BalanceWaiters.index()];
+        assert_eq!(balance.depth, 2);
+        assert_eq!(balance.oldest_digest, Some(first));
+        assert_eq!(balance.oldest_age_ms, Some(10));
+        assert_eq!(snapshot[SchedulerQueue::ReadyToExecute.index()].depth, 0);
+
+        drop(first_guard);
+        let snapshot = tracker.snapshot();
+        let balance = &snapshot[SchedulerQueue::BalanceWaiters.index()];
+        assert_eq!(balance.depth, 1);
+        assert_eq!(balance.oldest_digest, Some(second));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_same_digest_entered_twice() {
+        let tracker = Arc::new(QueueTracker::default());
+        let digest = TransactionDigest::random();
+
+        let first_guard = tracker.enter(SchedulerQueue::ReadyToExecute, digest);
+        tokio::time::advance(std::time::Duration::from_millis(10)).await;
+        let second_guard = tracker.enter(SchedulerQueue::ReadyToExecute, digest);
+
+        let ready = |tracker: &QueueTracker| {
+            tracker.snapshot()[SchedulerQueue::ReadyToExecute.index()].clone()
+        };
+        let snapshot = ready(&tracker);
+        assert_eq!(snapshot.depth, 2);
+        assert_eq!(snapshot.oldest_age_ms, Some(10));
+
+        // Leaving the queue only removes the entry that the guard was given, so the
+        // transaction is still tracked, since it entered the second time.
+        drop(first_guard);
+        let snapshot = ready(&tracker);
+        assert_eq!(snapshot.depth, 1);
+        assert_eq!(snapshot.oldest_digest, Some(digest));
+        assert_eq!(snapshot.oldest_age_ms, Some(0));
+
+        drop(second_guard);
+        let snapshot = ready(&tracker);
+        assert_eq!(snapshot.depth, 0);
+        assert_eq!(snapshot.oldest_digest, None);
+    }
+}
@@ -36,6 +36,7 @@ use sui_types::storage::ObjectStore;
 use sui_types::storage::OwnedObjectInfo;
 use sui_types::storage::RpcIndexes;
 use sui_types::storage::RpcStateReader;
+use sui_types::storage::SpendableBalance;
 use sui_types::storage::TransactionInfo;
 use sui_types::storage::WriteStore;
 use sui_types::storage::{ObjectKey, ReadStore};
@@ -492,6 +493,17 @@ impl RpcStateReader for RestReadStore {
             .map(Some)
             .map_err(StorageError::custom)
     }
+
+    fn get_spendable_balance(
+        &self,
+        owner: &SuiAddress,
+        coin_type: &StructTag,
+    ) -> Result<Option<SpendableBalance>> {
+        self.state
+            .get_spendable_balance(*owner, coin_type.clone().into())
+            .map(Some)
+            .map_err(StorageError::custom)
+    }
 }
 
 impl RpcIndexes for RpcIndexStore {
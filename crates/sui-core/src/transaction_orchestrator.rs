@@ -39,7 +39,9 @@ use sui_types::quorum_driver_types::{
 };
 use sui_types::sui_system_state::SuiSystemState;
 use sui_types::transaction::{Transaction, TransactionData, VerifiedTransaction};
-use sui_types::transaction_executor::{SimulateTransactionResult, TransactionChecks};
+use sui_types::transaction_executor::{
+    SimulateTransactionResult, TransactionChecks, TransactionScheduleUpdate,
+};
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::{Receiver, Sender};
 use tokio::task::JoinHandle;
@@ -1138,4 +1140,14 @@ where
         self.validator_state
             .simulate_transaction(transaction, checks)
     }
+
+    fn subscribe_schedule_updates(
+        &self,
+    ) -> Option<tokio::sync::broadcast::Receiver<TransactionScheduleUpdate>> {
+        Some(
+            self.validator_state
+                .execution_scheduler()
+                .subscribe_schedule_updates(),
+        )
+    }
 }
@@ -0,0 +1,159 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A `const fn` implementation of BLAKE2b-256 (RFC 7693), producing the same digests as
+//! `sui_types::crypto::DefaultHash`, so that object IDs can be derived at compile time, and on
+//! targets where the node's cryptography dependencies are not available. It is only meant for
+//! hashing small inputs: prefer `DefaultHash` at runtime, wherever it is available.
+
+const BLOCK_LENGTH: usize = 128;
+pub const DIGEST_LENGTH: usize = 32;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+pub struct Blake2b256 {
+    h: [u64; 8],
+    /// Bytes that have not been compressed yet. The last block is only compressed on
+    /// finalization, so this buffer can be full.
+    buf: [u8; BLOCK_LENGTH],
+    buf_len: usize,
+    /// Number of bytes compressed so far.
+    t: u128,
+}
+
+impl Blake2b256 {
+    pub const fn new() -> Self {
+        let mut h = IV;
+        // Parameter block: digest length, no key, fanout and depth of 1.
+        h[0] ^= 0x01010000 ^ DIGEST_LENGTH as u64;
+        Self {
+            h,
+            buf: [0; BLOCK_LENGTH],
+            buf_len: 0,
+            t: 0,
+        }
+    }
+
+    pub const fn update(&mut self, data: &[u8]) {
+        let mut i = 0;
+        while i < data.len() {
+            if self.buf_len == BLOCK_LENGTH {
+                self.t += BLOCK_LENGTH as u128;
+                compress(&mut self.h, &self.buf, self.t, false);
+                self.buf_len = 0;
+            }
+
+            self.buf[self.buf_len] = data[i];
+            self.buf_len += 1;
+            i += 1;
+        }
+    }
+
+    pub const fn finalize(mut self) -> [u8; DIGEST_LENGTH] {
+        self.t += self.buf_len as u128;
+        let mut i = self.buf_len;
+        while i < BLOCK_LENGTH {
+            self.buf[i] = 0;
+            i += 1;
+        }
+
+        compress(&mut self.h, &self.buf, self.t, true);
+
+        let mut out = [0u8; DIGEST_LENGTH];
+        let mut i = 0;
+        while i < DIGEST_LENGTH {
+            out[i] = self.h[i / 8].to_le_bytes()[i % 8];
+            i += 1;
+        }
+
+        out
+    }
+}
+
+impl Default for Blake2b256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn compress(h: &mut [u64; 8], block: &[u8; BLOCK_LENGTH], t: u128, last: bool) {
+    let mut m = [0u64; 16];
+    let mut i = 0;
+    while i < 16 {
+        let mut word = [0u8; 8];
+        let mut j = 0;
+        while j < 8 {
+            word[j] = block[i * 8 + j];
+            j += 1;
+        }
+        m[i] = u64::from_le_bytes(word);
+        i += 1;
+    }
+
+    let mut v = [0u64; 16];
+    let mut i = 0;
+    while i < 8 {
+        v[i] = h[i];
+        v[i + 8] = IV[i];
+        i += 1;
+    }
+
+    v[12] ^= t as u64;
+    v[13] ^= (t >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    let mut r = 0;
+    while r < 12 {
+        let s = &SIGMA[r % 10];
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        r += 1;
+    }
+
+    let mut i = 0;
+    while i < 8 {
+        h[i] ^= v[i] ^ v[i + 8];
+        i += 1;
+    }
+}
+
+const fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derivation of dynamic field and derived object IDs, without depending on the rest of
+//! `sui-types`, so that it can be built for `no_std` targets, and exported to JavaScript with the
+//! `wasm` feature, letting SDKs compute the same IDs as the node, locally.
+//!
+//! Types are passed in as their BCS-serialized `TypeTag`, rather than parsed, so that callers can
+//! use whichever representation of Move types they already have. The IDs computed here are
+//! checked against `sui-types` with the vectors in `tests/vectors.json`, which SDKs in other
+//! languages can check their own implementations against too.
+
+#![no_std]
+
+pub mod blake2b;
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
+use blake2b::Blake2b256;
+
+/// The length of an address, or object ID, in bytes.
+pub const ADDRESS_LENGTH: usize = 32;
+
+/// `HashingIntentScope::ChildObjectId`, which domain-separates child object IDs from the IDs of
+/// objects created by transactions.
+const CHILD_OBJECT_ID_SCOPE: u8 = 0xf0;
+
+/// The variant index of `TypeTag::Struct` in BCS.
+const STRUCT_TYPE_TAG: u8 = 7;
+
+/// `0x2`, the address of the Sui framework, which defines `derived_object::DerivedObjectKey`.
+const SUI_FRAMEWORK_ADDRESS: [u8; ADDRESS_LENGTH] = {
+    let mut address = [0u8; ADDRESS_LENGTH];
+    address[ADDRESS_LENGTH - 1] = 2;
+    address
+};
+
+const DERIVED_OBJECT_MODULE_NAME: &str = "derived_object";
+const DERIVED_OBJECT_KEY_STRUCT_NAME: &str = "DerivedObjectKey";
+const EPOCH_SCOPED_KEY_STRUCT_NAME: &str = "EpochScopedKey";
+
+/// Compute the ID of the dynamic field of `parent` whose key has the type serialized as
+/// `key_type_bcs`, and whose BCS-serialized value is `key_bcs`.
+pub const fn derive_dynamic_field_id(
+    parent: &[u8; ADDRESS_LENGTH],
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> [u8; ADDRESS_LENGTH] {
+    let mut hasher = hash_key(parent, key_bcs);
+    hasher.update(key_type_bcs);
+    hasher.finalize()
+}
+
+/// Compute the ID of the object derived from `parent` with a key whose type is serialized as
+/// `key_type_bcs`, and whose BCS-serialized value is `key_bcs`.
+///
+/// This is the ID of the dynamic field of `parent` with the same key, wrapped in
+/// `0x2::derived_object::DerivedObjectKey`, so the type of the wrapped key is serialized around
+/// `key_type_bcs`, rather than parsing it.
+pub const fn derive_object_id(
+    parent: &[u8; ADDRESS_LENGTH],
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> [u8; ADDRESS_LENGTH] {
+    let mut hasher = hash_key(parent, key_bcs);
+
+    // BCS of `TypeTag::Struct(0x2::derived_object::DerivedObjectKey<K>)`.
+    hash_derived_object_type_params(&mut hasher, DERIVED_OBJECT_KEY_STRUCT_NAME);
+    hasher.update(key_type_bcs);
+
+    hasher.finalize()
+}
+
+/// Compute the ID of the object derived from `parent` in `epoch`, with a key whose type is
+/// serialized as `key_type_bcs`, and whose BCS-serialized value is `key_bcs`.
+///
+/// This is the derived object ID for the key wrapped in
+/// `0x2::derived_object::EpochScopedKey { epoch, key }`, so the same parent and key derive a
+/// different ID in every epoch.
+pub const fn derive_object_id_epoch_scoped(
+    parent: &[u8; ADDRESS_LENGTH],
+    epoch: u64,
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> [u8; ADDRESS_LENGTH] {
+    // The BCS of the wrapped key is its fields, in order.
+    let mut hasher = hash_key_prefix(parent, 8 + key_bcs.len());
+    hasher.update(&epoch.to_le_bytes());
+    hasher.update(key_bcs);
+
+    // BCS of `TypeTag::Struct(0x2::derived_object::DerivedObjectKey<EpochScopedKey<K>>)`.
+    hash_derived_object_type_params(&mut hasher, DERIVED_OBJECT_KEY_STRUCT_NAME);
+    hash_derived_object_type_params(&mut hasher, EPOCH_SCOPED_KEY_STRUCT_NAME);
+    hasher.update(key_type_bcs);
+
+    hasher.finalize()
+}
+
+/// Start hashing a child object ID: hash(parent || len(key) || key || key_type_tag), up to the
+/// key's type.
+const fn hash_key(parent: &[u8; ADDRESS_LENGTH], key_bcs: &[u8]) -> Blake2b256 {
+    let mut hasher = hash_key_prefix(parent, key_bcs.len());
+    hasher.update(key_bcs);
+    hasher
+}
+
+/// Start hashing a child object ID, up to the key, which is `key_len` bytes long.
+const fn hash_key_prefix(parent: &[u8; ADDRESS_LENGTH], key_len: usize) -> Blake2b256 {
+    let mut hasher = Blake2b256::new();
+    hasher.update(&[CHILD_OBJECT_ID_SCOPE]);
+    hasher.update(parent);
+    // The node hashes the length as a `usize`, which is 8 bytes on every platform it runs on,
+    // but only 4 on wasm32, so it is widened explicitly.
+    hasher.update(&(key_len as u64).to_le_bytes());
+    hasher
+}
+
+/// Hash the BCS of `TypeTag::Struct(0x2::derived_object::<name>)`, up to its single type
+/// parameter, which the caller hashes next.
+const fn hash_derived_object_type_params(hasher: &mut Blake2b256, name: &str) {
+    hasher.update(&[STRUCT_TYPE_TAG]);
+    hasher.update(&SUI_FRAMEWORK_ADDRESS);
+    hash_bcs_bytes(hasher, DERIVED_OBJECT_MODULE_NAME.as_bytes());
+    hash_bcs_bytes(hasher, name.as_bytes());
+    hash_uleb128(hasher, 1);
+}
+
+const fn hash_bcs_bytes(hasher: &mut Blake2b256, bytes: &[u8]) {
+    hash_uleb128(hasher, bytes.len());
+    hasher.update(bytes);
+}
+
+const fn hash_uleb128(hasher: &mut Blake2b256, mut value: usize) {
+    while value >= 0x80 {
+        hasher.update(&[(value as u8 & 0x7f) | 0x80]);
+        value >>= 7;
+    }
+    hasher.update(&[value as u8]);
+}
@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports for JavaScript. Addresses, types and keys are passed as byte arrays, and IDs are
+//! returned as byte arrays, leaving encoding to the caller.
+
+extern crate alloc;
+
+use alloc::{format, vec::Vec};
+
+use wasm_bindgen::prelude::*;
+
+use crate::ADDRESS_LENGTH;
+
+/// Compute the ID of the dynamic field of `parent` whose key has the type serialized as
+/// `keyTypeBcs`, and whose BCS-serialized value is `keyBcs`.
+#[wasm_bindgen(js_name = deriveDynamicFieldId)]
+pub fn derive_dynamic_field_id(
+    parent: &[u8],
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let parent = parse_address(parent)?;
+    Ok(crate::derive_dynamic_field_id(&parent, key_type_bcs, key_bcs).to_vec())
+}
+
+/// Compute the ID of the object derived from `parent` with a key whose type is serialized as
+/// `keyTypeBcs`, and whose BCS-serialized value is `keyBcs`.
+#[wasm_bindgen(js_name = deriveObjectId)]
+pub fn derive_object_id(
+    parent: &[u8],
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let parent = parse_address(parent)?;
+    Ok(crate::derive_object_id(&parent, key_type_bcs, key_bcs).to_vec())
+}
+
+/// Compute the ID of the object derived from `parent` in `epoch`, with a key whose type is
+/// serialized as `keyTypeBcs`, and whose BCS-serialized value is `keyBcs`.
+#[wasm_bindgen(js_name = deriveObjectIdEpochScoped)]
+pub fn derive_object_id_epoch_scoped(
+    parent: &[u8],
+    epoch: u64,
+    key_type_bcs: &[u8],
+    key_bcs: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let parent = parse_address(parent)?;
+    Ok(crate::derive_object_id_epoch_scoped(&parent, epoch, key_type_bcs, key_bcs).to_vec())
+}
+
+fn parse_address(bytes: &[u8]) -> Result<[u8; ADDRESS_LENGTH], JsValue> {
+    bytes.try_into().map_err(|_| {
+        JsValue::from_str(&format!(
+            "Expected a {ADDRESS_LENGTH} byte address, got {} bytes",
+            bytes.len()
+        ))
+    })
+}
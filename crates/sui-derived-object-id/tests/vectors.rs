@@ -0,0 +1,116 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks the vectors in `vectors.json` against both this crate and `sui-types`, so that an SDK
+//! that reproduces the vectors computes the same IDs as the node. The same vectors are checked
+//! against `sui::derived_object::derive_address` in the framework's `derived_object_tests`.
+
+use std::str::FromStr;
+
+use move_core_types::language_storage::TypeTag;
+use serde::Deserialize;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    derived_object, dynamic_field,
+};
+
+const VECTORS: &str = include_str!("vectors.json");
+
+#[derive(Deserialize)]
+struct Vector {
+    parent: SuiAddress,
+    /// The key's type, for readers of the vectors. Only its BCS serialization is used.
+    key_type: String,
+    key_type_bcs: String,
+    key_bcs: String,
+    dynamic_field_id: ObjectID,
+    derived_object_id: ObjectID,
+    epoch: u64,
+    epoch_scoped_derived_object_id: ObjectID,
+}
+
+fn vectors() -> Vec<Vector> {
+    serde_json::from_str(VECTORS).unwrap()
+}
+
+#[test]
+fn test_vectors_match_crate() {
+    for v in vectors() {
+        let parent = v.parent.to_inner();
+        let key_type_bcs = hex::decode(&v.key_type_bcs).unwrap();
+        let key_bcs = hex::decode(&v.key_bcs).unwrap();
+
+        let dynamic_field_id =
+            sui_derived_object_id::derive_dynamic_field_id(&parent, &key_type_bcs, &key_bcs);
+        assert_eq!(
+            ObjectID::new(dynamic_field_id),
+            v.dynamic_field_id,
+            "Dynamic field ID mismatch for key of type {}",
+            v.key_type,
+        );
+
+        let derived_object_id =
+            sui_derived_object_id::derive_object_id(&parent, &key_type_bcs, &key_bcs);
+        assert_eq!(
+            ObjectID::new(derived_object_id),
+            v.derived_object_id,
+            "Derived object ID mismatch for key of type {}",
+            v.key_type,
+        );
+
+        let epoch_scoped_id = sui_derived_object_id::derive_object_id_epoch_scoped(
+            &parent,
+            v.epoch,
+            &key_type_bcs,
+            &key_bcs,
+        );
+        assert_eq!(
+            ObjectID::new(epoch_scoped_id),
+            v.epoch_scoped_derived_object_id,
+            "Epoch-scoped derived object ID mismatch for key of type {}",
+            v.key_type,
+        );
+    }
+}
+
+#[test]
+fn test_vectors_match_node() {
+    for v in vectors() {
+        let key_type: TypeTag = bcs::from_bytes(&hex::decode(&v.key_type_bcs).unwrap()).unwrap();
+        let key_bcs = hex::decode(&v.key_bcs).unwrap();
+
+        assert_eq!(key_type, TypeTag::from_str(&v.key_type).unwrap());
+
+        let dynamic_field_id =
+            dynamic_field::derive_dynamic_field_id(v.parent, &key_type, &key_bcs).unwrap();
+        assert_eq!(dynamic_field_id, v.dynamic_field_id);
+
+        let derived_object_id =
+            derived_object::derive_object_id(v.parent, &key_type, &key_bcs).unwrap();
+        assert_eq!(derived_object_id, v.derived_object_id);
+
+        let epoch_scoped_id = derived_object::derive_object_id_scoped(
+            v.parent,
+            &key_type,
+            &key_bcs,
+            derived_object::DerivationScope::Epoch(v.epoch),
+        )
+        .unwrap();
+        assert_eq!(epoch_scoped_id, v.epoch_scoped_derived_object_id);
+    }
+}
+
+#[test]
+fn test_derivation_is_const() {
+    const PARENT: [u8; 32] = [0xac; 32];
+    const ID: [u8; 32] =
+        sui_derived_object_id::derive_object_id(&PARENT, &[2], &[42, 0, 0, 0, 0, 0, 0, 0]);
+
+    let expected = derived_object::derive_object_id(
+        SuiAddress::from_bytes(PARENT).unwrap(),
+        &TypeTag::U64,
+        &bcs::to_bytes(&42u64).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(ObjectID::new(ID), expected);
+}
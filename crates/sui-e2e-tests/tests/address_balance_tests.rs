@@ -1,24 +1,34 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{collections::BTreeMap, time::Duration};
+
+use futures::future::join_all;
 use move_core_types::identifier::Identifier;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use sui_json_rpc_types::SuiTransactionBlockEffectsAPI;
 use sui_keys::keystore::AccountKeystore;
 use sui_macros::*;
 use sui_protocol_config::ProtocolConfig;
 use sui_sdk::wallet_context::WalletContext;
+#[cfg(msim)]
+use sui_simulator::{configs::*, SimConfig};
 use sui_types::{
     accumulator_metadata::AccumulatorOwner,
     accumulator_root::{AccumulatorValue, U128},
     balance::Balance,
     base_types::{ObjectRef, SuiAddress},
     effects::TransactionEffectsAPI,
+    execution_status::ExecutionFailureStatus,
     gas_coin::GAS,
+    message_envelope::Message,
     programmable_transaction_builder::ProgrammableTransactionBuilder,
-    storage::ChildObjectResolver,
+    storage::{ChildObjectResolver, ObjectStore},
     transaction::{Argument, Command, TransactionData, TransactionKind},
-    SUI_FRAMEWORK_PACKAGE_ID,
+    SUI_ACCUMULATOR_ROOT_OBJECT_ID, SUI_FRAMEWORK_PACKAGE_ID,
 };
+#[cfg(msim)]
+use test_cluster::chaos::{ChaosFault, ChaosPlan};
 use test_cluster::TestClusterBuilder;
 
 async fn get_sender_and_gas(context: &mut WalletContext) -> (SuiAddress, ObjectRef) {
@@ -42,6 +52,30 @@ async fn get_sender_and_gas(context: &mut WalletContext) -> (SuiAddress, ObjectR
     (sender, gas)
 }
 
+#[feature_matrix_test(features = [enable_accumulators])]
+async fn test_accumulator_root_created_at_genesis() {
+    let test_cluster = TestClusterBuilder::new().build().await;
+
+    test_cluster.fullnode_handle.sui_node.with(|node| {
+        let state = node.state();
+        let epoch_store = state.epoch_store_for_testing();
+        let protocol_config = epoch_store.protocol_config();
+        let enabled = protocol_config.enable_accumulators();
+        let root = state
+            .get_object_store()
+            .get_object(&SUI_ACCUMULATOR_ROOT_OBJECT_ID);
+        assert_eq!(root.is_some(), enabled);
+
+        // Enabling accumulators also sets the configs that they depend on.
+        assert_eq!(
+            protocol_config
+                .max_balance_withdraws_per_tx_as_option()
+                .is_some(),
+            enabled
+        );
+    });
+}
+
 #[ignore(reason = "currently panics")]
 #[sim_test]
 async fn test_deposits() -> Result<(), anyhow::Error> {
@@ -242,6 +276,39 @@ async fn test_withdraw_non_existent_balance() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_dry_run_withdraw_insufficient_balance() -> Result<(), anyhow::Error> {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let context = &mut test_cluster.wallet;
+
+    let (sender, gas) = get_sender_and_gas(context).await;
+
+    // The sender has no address balance, so the withdraw must be rejected by the dry run in the
+    // same way the withdraw scheduler would reject it, instead of reaching execution.
+    let tx = withdraw_from_balance_tx(1000, sender, gas, rgp);
+    let digest = tx.digest();
+    let (_, _, effects, _) = test_cluster
+        .fullnode_handle
+        .sui_node
+        .state()
+        .dry_exec_transaction(tx, digest)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        effects.status().clone().unwrap_err().0,
+        ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+    );
+
+    Ok(())
+}
+
 #[ignore(reason = "currently panics")]
 #[sim_test]
 async fn test_withdraw_underflow() -> Result<(), anyhow::Error> {
@@ -280,6 +347,543 @@ async fn test_withdraw_underflow() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Validators see transactions arrive with a wide spread of delays, so that each one decides the
+/// schedule of a withdraw at a different point relative to the settlement of earlier balance
+/// changes.
+#[cfg(msim)]
+fn withdraw_latency_config() -> SimConfig {
+    bimodal_latency_ms(10..50, 300..800, 0.1)
+}
+
+#[cfg(msim)]
+#[sim_test(config = "withdraw_latency_config()")]
+async fn test_withdraw_schedule_is_deterministic_across_validators() -> Result<(), anyhow::Error> {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let mut test_cluster = TestClusterBuilder::new()
+        .with_num_validators(4)
+        .build()
+        .await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let context = &mut test_cluster.wallet;
+
+    let sender = context
+        .config
+        .keystore
+        .addresses()
+        .first()
+        .cloned()
+        .unwrap();
+
+    let mut gas: Vec<_> = context
+        .gas_objects(sender)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, object)| object.object_ref())
+        .collect();
+    assert!(gas.len() >= 5, "Expected at least 5 gas coins");
+
+    let tx = make_send_to_account_tx(1000, sender, sender, gas.pop().unwrap(), rgp);
+    test_cluster.sign_and_execute_transaction(&tx).await;
+
+    // Each withdraw pays for gas with its own coin, so the only thing ordering them is their
+    // reservation against the same balance, which can only cover three of the four.
+    let mut txs = vec![];
+    for gas in gas.into_iter().take(4) {
+        let tx = withdraw_from_balance_tx(300, sender, gas, rgp);
+        txs.push(test_cluster.sign_transaction(&tx).await);
+    }
+
+    let digests: Vec<_> = txs.iter().map(|tx| *tx.digest()).collect();
+    let results = join_all(
+        txs.into_iter()
+            .map(|tx| test_cluster.execute_transaction_return_raw_effects(tx)),
+    )
+    .await;
+
+    let succeeded = results
+        .iter()
+        .filter(|r| r.as_ref().unwrap().0.status().is_ok())
+        .count();
+    assert_eq!(succeeded, 3, "Expected exactly three withdraws to succeed");
+
+    for (effects, _) in results.iter().map(|r| r.as_ref().unwrap()) {
+        if effects.status().is_err() {
+            assert_eq!(
+                effects.status().clone().unwrap_err().0,
+                ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+            );
+        }
+    }
+
+    // Every validator must have arrived at the same schedule for the withdraws, and so the same
+    // effects for each of them.
+    let mut expected: Option<Vec<_>> = None;
+    for handle in test_cluster.all_validator_handles() {
+        let state = handle.with(|node| node.state());
+        let effects: Vec<_> = state
+            .get_transaction_cache_reader()
+            .notify_read_executed_effects("", &digests)
+            .await
+            .into_iter()
+            .map(|effects| effects.digest())
+            .collect();
+
+        match &expected {
+            None => expected = Some(effects),
+            Some(expected) => assert_eq!(
+                expected, &effects,
+                "Validators disagree on the outcome of balance withdraws"
+            ),
+        }
+    }
+
+    test_cluster.trigger_reconfiguration().await;
+
+    Ok(())
+}
+
+#[sim_test]
+async fn test_withdraw_schedule_conforms_to_execution() -> Result<(), anyhow::Error> {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let context = &mut test_cluster.wallet;
+
+    let sender = context
+        .config
+        .keystore
+        .addresses()
+        .first()
+        .cloned()
+        .unwrap();
+
+    let mut gas: Vec<_> = context
+        .gas_objects(sender)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, object)| object.object_ref())
+        .collect();
+
+    // The schedule of each withdraw is reported by the receipts issued when its version settles:
+    // withdraws scheduled with sufficient balance reserve it, and the rest reserve nothing.
+    let mut receipts = test_cluster.all_validator_handles()[0].with(|node| {
+        node.state()
+            .execution_scheduler()
+            .subscribe_settlement_receipts()
+            .expect("Accumulators are enabled")
+    });
+
+    // Each round sends one transaction per gas coin, concurrently, so that deposits and
+    // withdraws race against each other for the same balance.
+    let mut rng = StdRng::from_seed([0; 32]);
+    let mut withdraws = BTreeMap::new();
+    for _ in 0..10 {
+        let mut txs = vec![];
+        for gas in &gas {
+            let amount = rng.gen_range(1..=500);
+            let tx = if rng.gen_bool(0.5) {
+                make_send_to_account_tx(amount, sender, sender, *gas, rgp)
+            } else {
+                let reservation = amount + rng.gen_range(0..=100);
+                let tx = withdraw_from_balance_tx_with_reservation(
+                    amount,
+                    reservation,
+                    sender,
+                    *gas,
+                    rgp,
+                );
+                withdraws.insert(tx.digest(), amount);
+                tx
+            };
+            txs.push(test_cluster.sign_transaction(&tx).await);
+        }
+
+        let results = join_all(
+            txs.into_iter()
+                .map(|tx| test_cluster.execute_transaction_return_raw_effects(tx)),
+        )
+        .await;
+
+        for (gas, result) in gas.iter_mut().zip(results) {
+            let (effects, _) = result?;
+            *gas = effects.gas_object().0;
+
+            let Some(amount) = withdraws.get_mut(effects.transaction_digest()) else {
+                assert!(effects.status().is_ok(), "Deposit failed: {effects:?}");
+                continue;
+            };
+
+            // Record the outcome of each withdraw: the amount withdrawn if it succeeded, or 0 if
+            // it was rejected.
+            if effects.status().is_err() {
+                assert_eq!(
+                    effects.status().clone().unwrap_err().0,
+                    ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+                    "Withdraw failed for a reason other than insufficient balance",
+                );
+                *amount = 0;
+            }
+        }
+    }
+
+    // Finish with a deposit and a withdraw that is guaranteed to succeed: once the withdraw's
+    // receipt arrives, every earlier version has been settled, and all receipts for the workload
+    // have been issued.
+    let tx = make_send_to_account_tx(1, sender, sender, gas[0], rgp);
+    let (effects, _) = test_cluster
+        .execute_transaction_return_raw_effects(test_cluster.sign_transaction(&tx).await)
+        .await?;
+    let tx = withdraw_from_balance_tx(1, sender, effects.gas_object().0, rgp);
+    let last = tx.digest();
+    test_cluster.sign_and_execute_transaction(&tx).await;
+
+    let mut reserved = BTreeMap::new();
+    tokio::time::timeout(Duration::from_secs(60), async {
+        loop {
+            let receipt = receipts.recv().await.unwrap();
+            reserved.insert(
+                receipt.tx_digest,
+                (receipt.reserved, receipt.actually_withdrawn),
+            );
+            if receipt.tx_digest == last {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for settlement receipts");
+
+    for (digest, withdrawn) in withdraws {
+        match reserved.get(&digest) {
+            // Scheduled with sufficient balance: must have succeeded on-chain and withdrawn
+            // exactly what it asked for.
+            Some(&(reservation, actually_withdrawn)) if reservation > 0 => {
+                assert_ne!(
+                    withdrawn, 0,
+                    "{digest}: scheduled with sufficient balance, but rejected on-chain",
+                );
+                assert_eq!(
+                    actually_withdrawn, withdrawn,
+                    "{digest}: settled amount differs from the amount withdrawn on-chain",
+                );
+            }
+
+            // Scheduled with insufficient balance: must have been rejected on-chain.
+            _ => assert_eq!(
+                withdrawn, 0,
+                "{digest}: scheduled with insufficient balance, but succeeded on-chain",
+            ),
+        }
+    }
+
+    test_cluster.trigger_reconfiguration().await;
+
+    Ok(())
+}
+
+/// Withdraws submitted while epochs change are each executed exactly once, in the epoch they were
+/// certified in, and the scheduler carries its settled versions across the reconfiguration
+/// without leaving any balance reserved.
+#[sim_test]
+async fn test_withdraws_across_reconfiguration() -> Result<(), anyhow::Error> {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let test_cluster = TestClusterBuilder::new()
+        .with_epoch_duration_ms(5_000)
+        .build()
+        .await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let context = &test_cluster.wallet;
+
+    let sender = context
+        .config
+        .keystore
+        .addresses()
+        .first()
+        .cloned()
+        .unwrap();
+
+    let mut gas: Vec<_> = context
+        .gas_objects(sender)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, object)| object.object_ref())
+        .collect();
+
+    let epoch = || {
+        test_cluster
+            .fullnode_handle
+            .sui_node
+            .with(|node| node.state().epoch_store_for_testing().epoch())
+    };
+
+    // Keep submitting rounds of concurrent deposits and withdraws until two epoch boundaries
+    // have passed, so that some rounds are in flight while the epoch changes.
+    let mut rng = StdRng::from_seed([0; 32]);
+    let mut balance = 0i128;
+    let mut executed = BTreeMap::new();
+    while epoch() < 2 {
+        let mut txs = vec![];
+        let mut withdraws = std::collections::BTreeSet::new();
+        for gas in &gas {
+            let amount = rng.gen_range(1..=500);
+            let tx = if rng.gen_bool(0.5) {
+                make_send_to_account_tx(amount, sender, sender, *gas, rgp)
+            } else {
+                let tx = withdraw_from_balance_tx(amount, sender, *gas, rgp);
+                withdraws.insert(tx.digest());
+                tx
+            };
+            txs.push((test_cluster.sign_transaction(&tx).await, amount as i128));
+        }
+
+        let results = join_all(txs.into_iter().map(|(tx, amount)| {
+            let test_cluster = &test_cluster;
+            async move {
+                (
+                    test_cluster
+                        .execute_transaction_return_raw_effects(tx)
+                        .await,
+                    amount,
+                )
+            }
+        }))
+        .await;
+
+        for (gas, (result, amount)) in gas.iter_mut().zip(results) {
+            let (effects, _) = result?;
+            *gas = effects.gas_object().0;
+
+            let digest = *effects.transaction_digest();
+            if !withdraws.contains(&digest) {
+                assert!(effects.status().is_ok(), "Deposit failed: {effects:?}");
+                balance += amount;
+                continue;
+            }
+
+            if effects.status().is_ok() {
+                balance -= amount;
+            } else {
+                assert_eq!(
+                    effects.status().clone().unwrap_err().0,
+                    ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+                    "Withdraw failed for a reason other than insufficient balance",
+                );
+            }
+
+            assert!(
+                executed.insert(digest, effects).is_none(),
+                "{digest}: withdraw executed more than once",
+            );
+        }
+    }
+
+    let epochs: std::collections::BTreeSet<_> = executed
+        .values()
+        .map(|effects| effects.executed_epoch())
+        .collect();
+    assert!(
+        epochs.len() > 1,
+        "Expected withdraws to be executed in more than one epoch, got {epochs:?}",
+    );
+
+    // A last deposit keeps the account from being empty, and settling the epoch it is in settles
+    // everything before it.
+    let tx = make_send_to_account_tx(1, sender, sender, gas[0], rgp);
+    test_cluster.sign_and_execute_transaction(&tx).await;
+    balance += 1;
+    test_cluster.trigger_reconfiguration().await;
+
+    let digests: Vec<_> = executed.keys().copied().collect();
+    let account_id =
+        AccumulatorValue::get_field_id(sender, &Balance::type_tag(GAS::type_tag())).unwrap();
+    for handle in test_cluster.all_validator_handles() {
+        let state = handle.with(|node| node.state());
+
+        // Every validator executed each withdraw once, with the same effects as were returned to
+        // the client, no matter which side of the epoch boundary it was scheduled on.
+        let effects = state
+            .get_transaction_cache_reader()
+            .notify_read_executed_effects("", &digests)
+            .await;
+        for (digest, effects) in digests.iter().zip(effects) {
+            assert_eq!(
+                effects.digest(),
+                executed[digest].digest(),
+                "{digest}: validator disagrees on the outcome of the withdraw",
+            );
+        }
+
+        // Nothing is left reserved, or waiting for a version that was settled in an earlier
+        // epoch.
+        let scheduler = state.execution_scheduler();
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while scheduler.get_reserved_balance(&account_id) != 0 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("Balance is still reserved after reconfiguration");
+        assert_eq!(
+            scheduler.withdraws_awaiting_settlement(Some(0)),
+            Some(vec![]),
+            "Withdraws are still waiting for settlement after reconfiguration",
+        );
+    }
+
+    test_cluster.fullnode_handle.sui_node.with(|node| {
+        let state = node.state();
+        let child_object_resolver = state.get_child_object_resolver().as_ref();
+        verify_accumulator_exists(child_object_resolver, sender, balance.try_into().unwrap());
+    });
+
+    Ok(())
+}
+
+/// Deposits and withdraws keep settling correctly while validators are killed, partitioned from
+/// the rest of the committee, and see the clock jump forward.
+#[cfg(msim)]
+#[sim_test]
+async fn test_address_balance_recovers_from_chaos() -> Result<(), anyhow::Error> {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let test_cluster = std::sync::Arc::new(
+        TestClusterBuilder::new()
+            .with_epoch_duration_ms(10_000)
+            .build()
+            .await,
+    );
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let context = &test_cluster.wallet;
+
+    let sender = context
+        .config
+        .keystore
+        .addresses()
+        .first()
+        .cloned()
+        .unwrap();
+
+    let mut gas: Vec<_> = context
+        .gas_objects(sender)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, object)| object.object_ref())
+        .collect();
+
+    // Each fault leaves a quorum of validators, so the workload keeps making progress throughout.
+    let chaos = ChaosPlan::new()
+        .at_checkpoint(
+            5,
+            ChaosFault::KillValidators {
+                count: 1,
+                restart_after: Duration::from_secs(5),
+            },
+        )
+        .at_epoch(
+            1,
+            ChaosFault::Partition {
+                minority: 1,
+                duration: Duration::from_secs(5),
+            },
+        )
+        .at_epoch(2, ChaosFault::ClockJump(Duration::from_secs(30)))
+        .run(test_cluster.clone());
+
+    let epoch = || {
+        test_cluster
+            .fullnode_handle
+            .sui_node
+            .with(|node| node.state().epoch_store_for_testing().epoch())
+    };
+
+    // Transactions in a round execute in any order, so the balance is only tracked as a total.
+    let mut rng = StdRng::from_seed([0; 32]);
+    let mut balance = 0i128;
+    while epoch() < 3 {
+        let mut txs = vec![];
+        let mut withdraws = std::collections::BTreeSet::new();
+        for gas in &gas {
+            let amount = rng.gen_range(1..=500);
+            let tx = if rng.gen_bool(0.5) {
+                make_send_to_account_tx(amount, sender, sender, *gas, rgp)
+            } else {
+                let tx = withdraw_from_balance_tx(amount, sender, *gas, rgp);
+                withdraws.insert(tx.digest());
+                tx
+            };
+            txs.push((test_cluster.sign_transaction(&tx).await, amount as i128));
+        }
+
+        let results = join_all(txs.into_iter().map(|(tx, amount)| {
+            let test_cluster = &test_cluster;
+            async move {
+                (
+                    test_cluster
+                        .execute_transaction_return_raw_effects(tx)
+                        .await,
+                    amount,
+                )
+            }
+        }))
+        .await;
+
+        for (gas, (result, amount)) in gas.iter_mut().zip(results) {
+            let (effects, _) = result?;
+            *gas = effects.gas_object().0;
+
+            if !withdraws.contains(effects.transaction_digest()) {
+                assert!(effects.status().is_ok(), "Deposit failed: {effects:?}");
+                balance += amount;
+            } else if effects.status().is_ok() {
+                balance -= amount;
+            } else {
+                assert_eq!(
+                    effects.status().clone().unwrap_err().0,
+                    ExecutionFailureStatus::InsufficientBalanceForWithdraw,
+                    "Withdraw failed for a reason other than insufficient balance",
+                );
+            }
+        }
+    }
+
+    chaos.wait_for_recovery(Duration::from_secs(60)).await;
+
+    // A last deposit, once the cluster has recovered, keeps the account from being empty.
+    let tx = make_send_to_account_tx(1, sender, sender, gas[0], rgp);
+    test_cluster.sign_and_execute_transaction(&tx).await;
+    balance += 1;
+
+    // Settle everything the workload did, and check that no balance change was lost or applied
+    // twice along the way.
+    test_cluster.trigger_reconfiguration().await;
+    test_cluster.fullnode_handle.sui_node.with(|node| {
+        let state = node.state();
+        let child_object_resolver = state.get_child_object_resolver().as_ref();
+        verify_accumulator_exists(child_object_resolver, sender, balance.try_into().unwrap());
+    });
+
+    Ok(())
+}
+
 fn withdraw_from_balance_tx(
     amount: u64,
     sender: SuiAddress,
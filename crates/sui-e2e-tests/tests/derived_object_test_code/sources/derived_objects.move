@@ -0,0 +1,82 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module derived_object_test_code::derived_objects {
+    use sui::derived_object;
+    use sui::party;
+    use sui::transfer::Receiving;
+
+    /// Owners that a derived object can be given when it is claimed.
+    const ADDRESS_OWNER: u8 = 0;
+    const SHARED: u8 = 1;
+    const PARTY: u8 = 2;
+    const WRAPPED: u8 = 3;
+
+    /// The object that objects are derived from.
+    public struct Registry has key {
+        id: UID,
+    }
+
+    public struct Derived has key, store {
+        id: UID,
+    }
+
+    /// An object that is sent to a derived object's address.
+    public struct Item has key, store {
+        id: UID,
+    }
+
+    public struct Wrapper has key {
+        id: UID,
+        derived: Derived,
+    }
+
+    public entry fun create_registry(ctx: &mut TxContext) {
+        transfer::share_object(Registry { id: object::new(ctx) })
+    }
+
+    /// Claim the object derived from `registry` with `key`, and give it the owner `owner`.
+    public entry fun claim(registry: &mut Registry, key: u64, owner: u8, ctx: &mut TxContext) {
+        let derived = Derived { id: derived_object::claim(&mut registry.id, key) };
+        let sender = ctx.sender();
+        if (owner == ADDRESS_OWNER) {
+            transfer::public_transfer(derived, sender)
+        } else if (owner == SHARED) {
+            transfer::public_share_object(derived)
+        } else if (owner == PARTY) {
+            transfer::public_party_transfer(derived, party::single_owner(sender))
+        } else if (owner == WRAPPED) {
+            transfer::transfer(Wrapper { id: object::new(ctx), derived }, sender)
+        } else {
+            abort 0
+        }
+    }
+
+    /// Send a new item to `recipient`, which can be the address of an object that is yet to be
+    /// claimed.
+    public entry fun send_item(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(Item { id: object::new(ctx) }, recipient)
+    }
+
+    /// Receive an item sent to `derived`, and send it back to it, to show that it was received.
+    public entry fun receive_item(derived: &mut Derived, item: Receiving<Item>) {
+        let item = transfer::public_receive(&mut derived.id, item);
+        transfer::public_transfer(item, derived.id.to_address())
+    }
+
+    /// Receive an item sent to the derived object in `wrapper`, and send it back to it.
+    public entry fun receive_item_wrapped(wrapper: &mut Wrapper, item: Receiving<Item>) {
+        let item = transfer::public_receive(&mut wrapper.derived.id, item);
+        transfer::public_transfer(item, wrapper.derived.id.to_address())
+    }
+
+    public entry fun wrap(derived: Derived, ctx: &mut TxContext) {
+        transfer::transfer(Wrapper { id: object::new(ctx), derived }, ctx.sender())
+    }
+
+    public entry fun unwrap(wrapper: Wrapper, ctx: &TxContext) {
+        let Wrapper { id, derived } = wrapper;
+        id.delete();
+        transfer::public_transfer(derived, ctx.sender())
+    }
+}
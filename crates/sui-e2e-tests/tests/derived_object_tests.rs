@@ -0,0 +1,380 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! How derived objects interact with the other ways objects can be owned: each test claims a
+//! derived object from a shared registry, gives it one kind of owner, and checks how its owner
+//! changes, and the shape of the effects, as it receives an object that was sent to its address
+//! before it was claimed.
+
+use std::path::PathBuf;
+
+use move_core_types::language_storage::TypeTag;
+use sui_macros::*;
+use sui_test_transaction_builder::publish_package;
+use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
+use sui_types::derived_object::{derive_object_id, DerivationScope};
+use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
+use sui_types::object::Owner;
+use sui_types::transaction::{CallArg, ObjectArg};
+use test_cluster::{TestCluster, TestClusterBuilder};
+
+/// The owners a derived object can be given when it is claimed, matching the constants in the
+/// `derived_objects` test module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Claim {
+    AddressOwner = 0,
+    Shared = 1,
+    Party = 2,
+    Wrapped = 3,
+}
+
+#[sim_test]
+async fn derived_object_address_owner_receives() {
+    receive_into_derived_object(Claim::AddressOwner).await;
+}
+
+#[sim_test]
+async fn derived_object_shared_receives() {
+    receive_into_derived_object(Claim::Shared).await;
+}
+
+#[sim_test]
+async fn derived_object_party_receives() {
+    if sui_simulator::has_mainnet_protocol_config_override() {
+        return;
+    }
+
+    receive_into_derived_object(Claim::Party).await;
+}
+
+#[sim_test]
+async fn derived_object_wrapped_receives() {
+    receive_into_derived_object(Claim::Wrapped).await;
+}
+
+#[sim_test]
+async fn derived_object_claimed_once() {
+    let env = TestEnvironment::new().await;
+    let fx = env.claim(0, Claim::AddressOwner).await.unwrap();
+    assert!(fx.status().is_ok());
+
+    // The key is taken, whichever owner the second claim asks for.
+    for claim in [Claim::AddressOwner, Claim::Shared, Claim::Wrapped] {
+        let fx = env.claim(0, claim).await.unwrap();
+        assert!(fx.status().is_err(), "{claim:?}: {:?}", fx.status());
+    }
+
+    // Other keys can still be claimed.
+    let fx = env.claim(1, Claim::Shared).await.unwrap();
+    assert!(fx.status().is_ok());
+}
+
+#[sim_test]
+async fn derived_object_wrap_and_unwrap() {
+    let env = TestEnvironment::new().await;
+    let sender = env.test_cluster.get_address_0();
+    let id = env.derived_id(0);
+
+    let fx = env.claim(0, Claim::AddressOwner).await.unwrap();
+    let derived = env.claimed(&fx, 0, Owner::AddressOwner(sender));
+
+    // Wrapping the derived object removes it from the store, under the same ID.
+    let fx = env
+        .move_call(
+            "wrap",
+            vec![CallArg::Object(ObjectArg::ImmOrOwnedObject(derived))],
+        )
+        .await
+        .unwrap();
+    assert!(fx.status().is_ok());
+    assert_eq!(ids(fx.wrapped()), vec![id]);
+    let (wrapper, _) = created_excluding_derived(&fx, id)
+        .into_iter()
+        .next()
+        .unwrap();
+
+    // Unwrapping it restores it, still under its derived ID.
+    let fx = env
+        .move_call(
+            "unwrap",
+            vec![CallArg::Object(ObjectArg::ImmOrOwnedObject(wrapper))],
+        )
+        .await
+        .unwrap();
+    assert!(fx.status().is_ok());
+    let unwrapped = fx.unwrapped();
+    assert_eq!(unwrapped.len(), 1);
+    assert_eq!(unwrapped[0].0 .0, id);
+    assert_eq!(unwrapped[0].1, Owner::AddressOwner(sender));
+    assert_eq!(ids(fx.deleted()), vec![wrapper.0]);
+}
+
+/// Send an item to the address of the object derived with key `0` before it is claimed, claim it
+/// as `claim`, and then receive the item into it.
+async fn receive_into_derived_object(claim: Claim) {
+    let env = TestEnvironment::new().await;
+    let sender = env.test_cluster.get_address_0();
+    let id = env.derived_id(0);
+
+    // Objects can be sent to a derived object's address before it exists.
+    let item = env.send_item(id.into()).await;
+
+    let fx = env.claim(0, claim).await.unwrap();
+    assert!(fx.status().is_ok(), "{:?}", fx.status());
+
+    // The registry records the claim in a dynamic field, which is never the derived object.
+    let registry_fields: Vec<_> = fx
+        .created()
+        .into_iter()
+        .filter(|(_, owner)| *owner == Owner::ObjectOwner(env.registry.0.into()))
+        .collect();
+    assert_eq!(registry_fields.len(), 1);
+    assert_ne!(registry_fields[0].0 .0, id);
+
+    let receive = match claim {
+        Claim::AddressOwner => {
+            let derived = env.claimed(&fx, 0, Owner::AddressOwner(sender));
+            env.receive("receive_item", ObjectArg::ImmOrOwnedObject(derived), item)
+                .await
+        }
+
+        Claim::Shared => {
+            let derived = env.claimed(
+                &fx,
+                0,
+                Owner::Shared {
+                    initial_shared_version: fx.lamport_version(),
+                },
+            );
+
+            let derived = ObjectArg::SharedObject {
+                id,
+                initial_shared_version: derived.1,
+                mutable: true,
+            };
+            env.receive("receive_item", derived, item).await
+        }
+
+        Claim::Party => {
+            let derived = env.claimed(
+                &fx,
+                0,
+                Owner::ConsensusAddressOwner {
+                    start_version: fx.lamport_version(),
+                    owner: sender,
+                },
+            );
+
+            let derived = ObjectArg::SharedObject {
+                id,
+                initial_shared_version: derived.1,
+                mutable: true,
+            };
+            env.receive("receive_item", derived, item).await
+        }
+
+        Claim::Wrapped => {
+            // The derived object was created and wrapped in the same transaction, so it never
+            // appears in the store, but its ID is still the derived ID.
+            assert!(env.created_derived(&fx, 0).is_empty());
+            let created = created_excluding_derived(&fx, id);
+            let (wrapper, _) = created
+                .into_iter()
+                .find(|(_, owner)| *owner == Owner::AddressOwner(sender))
+                .unwrap();
+
+            let fx = env
+                .receive_fx(
+                    "receive_item_wrapped",
+                    ObjectArg::ImmOrOwnedObject(wrapper),
+                    item,
+                )
+                .await;
+            let mutated = fx.mutated();
+            assert!(mutated
+                .iter()
+                .all(|((object_id, _, _), _)| *object_id != id));
+            find(&mutated, wrapper.0);
+            find(&mutated, item.0)
+        }
+    };
+
+    // The item was received into the derived object, and sent back to it.
+    assert_eq!(receive.1, Owner::AddressOwner(id.into()));
+    assert!(receive.0 .1 > item.1);
+}
+
+struct TestEnvironment {
+    test_cluster: TestCluster,
+    package: ObjectID,
+    registry: (ObjectID, SequenceNumber),
+}
+
+impl TestEnvironment {
+    async fn new() -> Self {
+        let test_cluster = TestClusterBuilder::new().build().await;
+
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/derived_object_test_code");
+        let package = publish_package(&test_cluster.wallet, path).await.0;
+
+        let mut env = Self {
+            test_cluster,
+            package,
+            registry: (ObjectID::ZERO, SequenceNumber::new()),
+        };
+
+        let fx = env.move_call("create_registry", vec![]).await.unwrap();
+        assert!(fx.status().is_ok());
+        let ((id, _, _), owner) = fx.created().into_iter().next().unwrap();
+        env.registry = (id, owner.start_version().unwrap());
+        env
+    }
+
+    fn derived_id(&self, key: u64) -> ObjectID {
+        derive_object_id(
+            self.registry.0,
+            &TypeTag::U64,
+            &bcs::to_bytes(&key).unwrap(),
+        )
+        .unwrap()
+    }
+
+    async fn move_call(
+        &self,
+        function: &'static str,
+        arguments: Vec<CallArg>,
+    ) -> anyhow::Result<TransactionEffects> {
+        let transaction = self
+            .test_cluster
+            .test_transaction_builder()
+            .await
+            .move_call(self.package, "derived_objects", function, arguments)
+            .build();
+        let transaction = self
+            .test_cluster
+            .wallet
+            .sign_transaction(&transaction)
+            .await;
+
+        let (fx, _) = self
+            .test_cluster
+            .execute_transaction_return_raw_effects(transaction)
+            .await?;
+        Ok(fx)
+    }
+
+    async fn claim(&self, key: u64, claim: Claim) -> anyhow::Result<TransactionEffects> {
+        let registry = ObjectArg::SharedObject {
+            id: self.registry.0,
+            initial_shared_version: self.registry.1,
+            mutable: true,
+        };
+
+        let arguments = vec![
+            CallArg::Object(registry),
+            CallArg::Pure(bcs::to_bytes(&key).unwrap()),
+            CallArg::Pure(bcs::to_bytes(&(claim as u8)).unwrap()),
+        ];
+
+        self.move_call("claim", arguments).await
+    }
+
+    /// The derived objects with key `key` created by the transaction with effects `fx`.
+    fn created_derived(&self, fx: &TransactionEffects, key: u64) -> Vec<(ObjectRef, Owner)> {
+        fx.created_derived_objects(&[(TypeTag::U64, bcs::to_bytes(&key).unwrap())])
+            .into_iter()
+            .map(|derived| {
+                assert_eq!(derived.parent, self.registry.0);
+                assert_eq!(derived.scope, DerivationScope::Global);
+                (derived.object_ref, derived.owner)
+            })
+            .collect()
+    }
+
+    /// The object derived with key `key`, which the transaction with effects `fx` claimed and
+    /// gave to `owner`.
+    fn claimed(&self, fx: &TransactionEffects, key: u64, owner: Owner) -> ObjectRef {
+        let created = self.created_derived(fx, key);
+        assert_eq!(created.len(), 1, "{created:?}");
+
+        let (derived, derived_owner) = created.into_iter().next().unwrap();
+        assert_eq!(derived.0, self.derived_id(key));
+        assert_eq!(derived_owner, owner);
+        derived
+    }
+
+    async fn send_item(&self, recipient: SuiAddress) -> ObjectRef {
+        let fx = self
+            .move_call(
+                "send_item",
+                vec![CallArg::Pure(bcs::to_bytes(&recipient).unwrap())],
+            )
+            .await
+            .unwrap();
+        assert!(fx.status().is_ok());
+
+        let (item, owner) = fx.created().into_iter().next().unwrap();
+        assert_eq!(owner, Owner::AddressOwner(recipient));
+        item
+    }
+
+    async fn receive_fx(
+        &self,
+        function: &'static str,
+        receiver: ObjectArg,
+        item: ObjectRef,
+    ) -> TransactionEffects {
+        let arguments = vec![
+            CallArg::Object(receiver),
+            CallArg::Object(ObjectArg::Receiving(item)),
+        ];
+
+        let fx = self.move_call(function, arguments).await.unwrap();
+        assert!(fx.status().is_ok(), "{:?}", fx.status());
+        fx
+    }
+
+    /// Receive `item` into the derived object `receiver`, which keeps its ID and owner, and
+    /// return the item's new reference and owner.
+    async fn receive(
+        &self,
+        function: &'static str,
+        receiver: ObjectArg,
+        item: ObjectRef,
+    ) -> (ObjectRef, Owner) {
+        let (id, consensus) = match receiver {
+            ObjectArg::ImmOrOwnedObject((id, _, _)) => (id, false),
+            ObjectArg::SharedObject { id, .. } => (id, true),
+            ObjectArg::Receiving(_) => unreachable!(),
+        };
+
+        let fx = self.receive_fx(function, receiver, item).await;
+        let mutated = fx.mutated();
+        let (_, receiver_owner) = find(&mutated, id);
+        assert_eq!(receiver_owner.is_consensus(), consensus);
+        find(&mutated, item.0)
+    }
+}
+
+fn find(objects: &[(ObjectRef, Owner)], id: ObjectID) -> (ObjectRef, Owner) {
+    objects
+        .iter()
+        .find(|((object_id, _, _), _)| *object_id == id)
+        .cloned()
+        .unwrap_or_else(|| panic!("{id} not in {objects:?}"))
+}
+
+fn ids(objects: Vec<ObjectRef>) -> Vec<ObjectID> {
+    objects.into_iter().map(|(id, _, _)| id).collect()
+}
+
+/// The objects created by `fx`, other than the derived object `id`, and dynamic fields.
+fn created_excluding_derived(fx: &TransactionEffects, id: ObjectID) -> Vec<(ObjectRef, Owner)> {
+    fx.created()
+        .into_iter()
+        .filter(|((object_id, _, _), owner)| {
+            *object_id != id && !matches!(owner, Owner::ObjectOwner(_))
+        })
+        .collect()
+}
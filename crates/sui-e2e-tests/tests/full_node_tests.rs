@@ -39,8 +39,8 @@ use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::quorum_driver_types::{ExecuteTransactionRequestType, ExecuteTransactionRequestV3};
 use sui_types::storage::ObjectStore;
 use sui_types::transaction::{
-    CallArg, GasData, TransactionData, TransactionKind, TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
-    TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+    CallArg, GasData, TransactionData, TransactionDataAPI, TransactionKind,
+    TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS, TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
 };
 use sui_types::utils::{
     to_sender_signed_transaction, to_sender_signed_transaction_with_multi_signers,
@@ -186,6 +186,47 @@ async fn test_sponsored_transaction() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_sponsored_transaction_builder() -> Result<(), anyhow::Error> {
+    telemetry_subscribers::init_for_testing();
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let sender = test_cluster.get_address_0();
+    let sponsor = test_cluster.get_address_1();
+    let recipient = test_cluster.get_address_2();
+
+    // The sender transfers one of its coins, while the sponsor pays for gas.
+    let coins = test_cluster
+        .wallet
+        .get_gas_objects_owned_by_address(sender, None)
+        .await?;
+    let transferred = test_cluster.wallet.get_full_object_ref(coins[0].0).await?;
+    let sponsor_gas = test_cluster
+        .wallet
+        .get_one_gas_object_owned_by_address(sponsor)
+        .await?
+        .unwrap();
+
+    let tx_data = test_cluster
+        .test_transaction_builder_with_gas_object(sender, coins[1])
+        .await
+        .with_sponsor(sponsor, sponsor_gas)
+        .transfer(transferred, recipient)
+        .build();
+    assert_eq!(tx_data.gas_owner(), sponsor);
+    assert_eq!(tx_data.gas(), &[sponsor_gas]);
+
+    // The wallet signs for both the sender and the sponsor.
+    let tx = test_cluster.sign_transaction(&tx_data).await;
+    assert_eq!(tx.data().tx_signatures().len(), 2);
+    test_cluster.execute_transaction(tx).await;
+
+    let wallet = &test_cluster.wallet;
+    assert_eq!(wallet.get_object_owner(&coins[0].0).await?, recipient);
+    assert_ne!(wallet.get_object_ref(sponsor_gas.0).await?, sponsor_gas);
+    assert_eq!(wallet.get_object_ref(coins[1].0).await?, coins[1]);
+    Ok(())
+}
+
 #[sim_test]
 async fn test_full_node_move_function_index() -> Result<(), anyhow::Error> {
     telemetry_subscribers::init_for_testing();
@@ -1449,3 +1490,26 @@ async fn publish_init_events_without_local_execution() {
         .unwrap();
     assert_eq!(response.events.unwrap().data.len(), 1);
 }
+
+#[sim_test]
+async fn test_restore_cluster_from_snapshot() -> Result<(), anyhow::Error> {
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let (transferred_object, _, receiver, _, _) = transfer_coin(&test_cluster.wallet).await?;
+
+    let snapshot = mysten_common::tempdir()?;
+    test_cluster.snapshot_to(snapshot.path()).await?;
+
+    // The restored cluster sees the effects of transactions executed before the snapshot...
+    let test_cluster = TestClusterBuilder::from_snapshot(snapshot.path())
+        .build()
+        .await;
+    let object = test_cluster
+        .get_object_from_fullnode_store(&transferred_object)
+        .await
+        .expect("Transferred object should be restored");
+    assert_eq!(object.owner.get_owner_address().unwrap(), receiver);
+
+    // ...and can continue executing transactions.
+    transfer_coin(&test_cluster.wallet).await?;
+    Ok(())
+}
@@ -52,3 +52,61 @@ async fn test_create_randomness_state_object() {
         });
     }
 }
+
+#[sim_test]
+async fn test_pause_and_step_randomness() {
+    use std::time::Duration;
+    use sui_types::base_types::SequenceNumber;
+
+    let test_cluster = TestClusterBuilder::new().build().await;
+
+    let randomness_versions = || -> Vec<SequenceNumber> {
+        test_cluster
+            .all_validator_handles()
+            .iter()
+            .map(|h| {
+                h.with(|node| {
+                    node.state()
+                        .get_object_cache_reader()
+                        .get_latest_object_ref_or_tombstone(SUI_RANDOMNESS_STATE_OBJECT_ID)
+                        .expect("randomness state object should exist")
+                        .1
+                })
+            })
+            .collect()
+    };
+
+    test_cluster.pause_randomness();
+
+    // Let any round that was released before the pause finish executing.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let paused = randomness_versions();
+    assert!(paused.iter().all(|v| *v == paused[0]));
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert_eq!(randomness_versions(), paused);
+
+    // Stepping releases exactly one round on every validator.
+    test_cluster.step_randomness(1);
+    let stepped = paused[0].next();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while randomness_versions().iter().any(|v| *v != stepped) {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("randomness should advance by one round");
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(randomness_versions().iter().all(|v| *v == stepped));
+
+    // Once resumed, randomness keeps advancing.
+    test_cluster.resume_randomness();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while randomness_versions().iter().any(|v| *v <= stepped.next()) {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("randomness should resume");
+}
@@ -662,6 +662,42 @@ async fn test_reconfig_with_committee_change_basic() {
     });
 }
 
+#[sim_test]
+async fn test_add_and_remove_validator() {
+    let initial_num_validators = 4;
+    let mut test_cluster = TestClusterBuilder::new()
+        .with_num_validators(initial_num_validators)
+        .build()
+        .await;
+
+    let new_validator = ValidatorGenesisConfigBuilder::new().build(&mut OsRng);
+    let handle = test_cluster.add_validator(new_validator).await;
+    let name = handle.with(|node| node.state().name);
+
+    let committee = test_cluster.committee();
+    assert_eq!(committee.epoch, 1);
+    assert_eq!(committee.num_members(), initial_num_validators + 1);
+    handle.with(|node| {
+        assert!(node
+            .state()
+            .is_validator(&node.state().epoch_store_for_testing()));
+    });
+
+    // The network keeps making progress with the new validator in the committee.
+    let tx = make_transfer_sui_transaction(&test_cluster.wallet, None, None).await;
+    test_cluster.execute_transaction(tx).await;
+
+    test_cluster.remove_validator(&name).await;
+
+    let committee = test_cluster.committee();
+    assert_eq!(committee.epoch, 2);
+    assert_eq!(committee.num_members(), initial_num_validators);
+    assert!(!committee.authority_exists(&name));
+
+    let tx = make_transfer_sui_transaction(&test_cluster.wallet, None, None).await;
+    test_cluster.execute_transaction(tx).await;
+}
+
 #[sim_test]
 async fn test_protocol_upgrade_to_sip_39_enabled_version() {
     let initial_num_validators = 10;
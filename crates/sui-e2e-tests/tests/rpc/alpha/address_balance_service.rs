@@ -0,0 +1,113 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use move_core_types::identifier::Identifier;
+use sui_macros::sim_test;
+use sui_protocol_config::ProtocolConfig;
+use sui_rpc_api::alpha::{AddressBalanceServiceClient, GetSpendableBalanceRequest};
+use sui_test_transaction_builder::TestTransactionBuilder;
+use sui_types::{
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, Command},
+    SUI_FRAMEWORK_PACKAGE_ID,
+};
+use test_cluster::TestClusterBuilder;
+
+#[sim_test]
+async fn get_spendable_balance() {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let context = &test_cluster.wallet;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let accounts_and_objs = context.get_all_accounts_and_gas_objects().await.unwrap();
+    let sender = accounts_and_objs[0].0;
+    let gas = accounts_and_objs[0].1[0];
+
+    let mut client = AddressBalanceServiceClient::connect(test_cluster.rpc_url().to_owned())
+        .await
+        .unwrap();
+    let request = GetSpendableBalanceRequest {
+        owner: Some(sender.to_string()),
+        coin_type: Some("0x2::sui::SUI".to_owned()),
+    };
+
+    // Nothing has been deposited yet.
+    let balance = client
+        .get_spendable_balance(request.clone())
+        .await
+        .unwrap()
+        .into_inner()
+        .balance
+        .unwrap();
+    assert_eq!(balance.coin_type.as_deref(), Some("0x2::sui::SUI"));
+    assert_eq!(balance.total, Some(0));
+    assert_eq!(balance.spendable, Some(0));
+
+    // Deposit into the sender's address balance.
+    let deposit = {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let amount = builder.pure(1000u64).unwrap();
+        let recipient = builder.pure(sender).unwrap();
+        let Argument::Result(coin) =
+            builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]))
+        else {
+            panic!("SplitCoins must return a result");
+        };
+        let balance = builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("coin").unwrap(),
+            Identifier::new("into_balance").unwrap(),
+            vec!["0x2::sui::SUI".parse().unwrap()],
+            vec![Argument::NestedResult(coin, 0)],
+        );
+        builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("balance").unwrap(),
+            Identifier::new("send_to_account").unwrap(),
+            vec!["0x2::sui::SUI".parse().unwrap()],
+            vec![balance, recipient],
+        );
+        TestTransactionBuilder::new(sender, gas, rgp)
+            .programmable(builder.finish())
+            .build()
+    };
+    test_cluster.sign_and_execute_transaction(&deposit).await;
+
+    // The deposit is counted once its checkpoint's settlement has executed.
+    let balance = tokio::time::timeout(Duration::from_secs(60), async {
+        loop {
+            let balance = client
+                .get_spendable_balance(request.clone())
+                .await
+                .unwrap()
+                .into_inner()
+                .balance
+                .unwrap();
+            if balance.settled == Some(1000) {
+                break balance;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(balance.pending_deposits, Some(0));
+    assert_eq!(balance.total, Some(1000));
+    assert_eq!(balance.reserved, Some(0));
+    assert_eq!(balance.spendable, Some(1000));
+
+    let error = client
+        .get_spendable_balance(GetSpendableBalanceRequest {
+            owner: Some("not an address".to_owned()),
+            ..request
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+}
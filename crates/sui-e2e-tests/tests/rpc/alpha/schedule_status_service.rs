@@ -0,0 +1,163 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use move_core_types::identifier::Identifier;
+use prost_types::FieldMask;
+use sui_macros::sim_test;
+use sui_protocol_config::ProtocolConfig;
+use sui_rpc::field::FieldMaskUtil;
+use sui_rpc::proto::sui::rpc::v2beta2::{
+    Bcs, ExecuteTransactionRequest, ExecuteTransactionResponse, Transaction, UserSignature,
+};
+use sui_rpc_api::alpha::{
+    execute_transaction_update::Update, ScheduleStatus, ScheduleStatusServiceClient,
+};
+use sui_test_transaction_builder::TestTransactionBuilder;
+use sui_types::{
+    base_types::SuiAddress, programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::BalanceWithdrawArg, type_input::TypeInput, SUI_FRAMEWORK_PACKAGE_ID,
+};
+use test_cluster::TestClusterBuilder;
+use tokio_stream::StreamExt;
+
+fn execute_request(transaction: &sui_types::transaction::Transaction) -> ExecuteTransactionRequest {
+    ExecuteTransactionRequest {
+        transaction: Some(Transaction {
+            bcs: Some(Bcs::serialize(transaction.transaction_data()).unwrap()),
+            ..Default::default()
+        }),
+        signatures: transaction
+            .tx_signatures()
+            .iter()
+            .map(|s| UserSignature {
+                bcs: Some(Bcs {
+                    name: None,
+                    value: Some(s.as_ref().to_owned().into()),
+                }),
+                ..Default::default()
+            })
+            .collect(),
+        read_mask: Some(FieldMask::from_paths(["finality", "transaction.effects"])),
+    }
+}
+
+#[sim_test]
+async fn execute_transactions_with_schedule_statuses() {
+    let _guard = ProtocolConfig::apply_overrides_for_testing(|_, mut cfg| {
+        cfg.enable_accumulators_for_testing();
+        cfg
+    });
+
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let context = &test_cluster.wallet;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let accounts_and_objs = context.get_all_accounts_and_gas_objects().await.unwrap();
+    let sender = accounts_and_objs[0].0;
+    let gas = &accounts_and_objs[0].1;
+
+    // A transfer, which does not withdraw from an address balance.
+    let transfer = TestTransactionBuilder::new(sender, gas[0], rgp)
+        .transfer_sui(Some(9), SuiAddress::random_for_testing_only())
+        .build();
+
+    // A withdraw from the sender's address balance, which is empty.
+    let withdraw = {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .balance_withdraw(BalanceWithdrawArg::new_with_amount(
+                1000,
+                TypeInput::from(sui_types::gas_coin::GAS::type_tag()),
+            ))
+            .unwrap();
+        let amount = builder.pure(1000u64).unwrap();
+        let balance = builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("balance").unwrap(),
+            Identifier::new("withdraw_from_account").unwrap(),
+            vec!["0x2::sui::SUI".parse().unwrap()],
+            vec![amount],
+        );
+        let coin = builder.programmable_move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::new("coin").unwrap(),
+            Identifier::new("from_balance").unwrap(),
+            vec!["0x2::sui::SUI".parse().unwrap()],
+            vec![balance],
+        );
+        builder.transfer_arg(sender, coin);
+        TestTransactionBuilder::new(sender, gas[1], rgp)
+            .programmable(builder.finish())
+            .build()
+    };
+
+    let requests = vec![
+        execute_request(&context.sign_transaction(&transfer).await),
+        execute_request(&context.sign_transaction(&withdraw).await),
+        // A request without a transaction fails, without affecting the others.
+        ExecuteTransactionRequest::default(),
+    ];
+
+    let mut client = ScheduleStatusServiceClient::connect(test_cluster.rpc_url().to_owned())
+        .await
+        .unwrap();
+    let mut updates = client
+        .execute_transactions(tokio_stream::iter(requests))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut statuses: BTreeMap<u64, Vec<ScheduleStatus>> = BTreeMap::new();
+    let mut responses: BTreeMap<u64, ExecuteTransactionResponse> = BTreeMap::new();
+    let mut errors = BTreeMap::new();
+    while let Some(update) = updates.next().await {
+        let update = update.unwrap();
+        let index = update.request_index.unwrap();
+        assert!(
+            !responses.contains_key(&index) && !errors.contains_key(&index),
+            "update after the final update for request {index}"
+        );
+
+        match update.update.unwrap() {
+            Update::ScheduleStatus(status) => {
+                assert!(update.digest.is_some());
+                statuses
+                    .entry(index)
+                    .or_default()
+                    .push(ScheduleStatus::try_from(status).unwrap());
+            }
+            Update::Response(response) => {
+                assert!(update.digest.is_some());
+                responses.insert(index, response);
+            }
+            Update::Error(error) => {
+                errors.insert(index, (update.digest, error));
+            }
+        }
+    }
+
+    // The transfer succeeds, without waiting for any balance.
+    let effects = |index| {
+        responses[&index]
+            .transaction
+            .as_ref()
+            .unwrap()
+            .effects
+            .clone()
+            .unwrap()
+    };
+    assert!(effects(0).status.unwrap().success());
+    assert!(!statuses.contains_key(&0));
+
+    // The withdraw fails. The RPC is served by a fullnode, which executes transactions from
+    // their certified effects rather than scheduling their withdraws, so it never reports any
+    // statuses. The order of the statuses reported by a node that does schedule withdraws is
+    // covered by the service's unit tests, and the statuses the scheduler publishes by its own.
+    assert!(!effects(1).status.unwrap().success());
+    assert!(statuses.is_empty(), "unexpected statuses: {statuses:?}");
+
+    let (digest, _) = &errors[&2];
+    assert!(digest.is_none());
+    assert_eq!(responses.len() + errors.len(), 3);
+}
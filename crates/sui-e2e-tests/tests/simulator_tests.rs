@@ -141,3 +141,18 @@ async fn test_net_determinism() {
         .notify_read_executed_effects("", &[digest])
         .await;
 }
+
+// Test that the components starting up a network + fullnode are handed the same addresses, in the
+// same order, on every run.
+#[cfg(msim)]
+#[sim_test(check_determinism)]
+async fn test_address_allocation_determinism() {
+    use sui_config::local_ip_utils;
+
+    local_ip_utils::start_recording_allocations();
+
+    let test_cluster = TestClusterBuilder::new().build().await;
+    test_cluster.spawn_new_fullnode().await;
+
+    local_ip_utils::check_allocation_determinism("test_address_allocation_determinism");
+}
@@ -4,7 +4,7 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Serialize, Deserialize, Error, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Error, Debug, Clone, PartialEq, Eq)]
 pub enum FaucetError {
     #[error("Wallet Error: `{0}`")]
     Wallet(String),
@@ -12,6 +12,9 @@ pub enum FaucetError {
     #[error("Coin Transfer Failed `{0}`")]
     Transfer(String),
 
+    #[error("Too many requests for `{0}`, please try again later")]
+    TooManyRequests(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -6,6 +6,8 @@ use std::net::Ipv4Addr;
 
 pub const DEFAULT_AMOUNT: u64 = 200_000_000_000;
 pub const DEFAULT_NUM_COINS: usize = 5;
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 10;
+pub const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
 
 #[derive(Parser, Clone)]
 #[clap(
@@ -29,6 +31,19 @@ pub struct FaucetConfig {
 
     #[clap(long, default_value_t = 60)]
     pub wallet_client_timeout_secs: u64,
+
+    /// Maximum number of requests that are served per recipient within each
+    /// `rate-limit-window-secs`. Requests are not rate limited if this is not set.
+    #[clap(long)]
+    pub max_requests_per_recipient: Option<u32>,
+
+    #[clap(long, default_value_t = DEFAULT_RATE_LIMIT_WINDOW_SECS)]
+    pub rate_limit_window_secs: u64,
+
+    /// Maximum number of requests that are served by a single transaction. Requests that arrive
+    /// while a transaction is executing are batched into the next one.
+    #[clap(long, default_value_t = DEFAULT_MAX_BATCH_SIZE)]
+    pub max_batch_size: usize,
 }
 
 impl Default for FaucetConfig {
@@ -39,6 +54,9 @@ impl Default for FaucetConfig {
             amount: DEFAULT_AMOUNT,
             num_coins: DEFAULT_NUM_COINS,
             wallet_client_timeout_secs: 60,
+            max_requests_per_recipient: None,
+            rate_limit_window_secs: DEFAULT_RATE_LIMIT_WINDOW_SECS,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         }
     }
 }
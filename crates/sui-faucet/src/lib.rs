@@ -12,5 +12,5 @@ pub use app_state::AppState;
 pub use errors::FaucetError;
 pub use faucet_config::FaucetConfig;
 pub use local_faucet::LocalFaucet;
-pub use server::{create_wallet_context, start_faucet};
+pub use server::{create_wallet_context, serve_faucet, start_faucet};
 pub use types::{CoinInfo, FaucetRequest, FaucetResponse, FixedAmountRequest, RequestStatus};
@@ -1,12 +1,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt;
 use std::sync::Arc;
 
 use anyhow::bail;
-use tokio::sync::Mutex;
-use tokio::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+use tokio::time::{Duration, Instant};
 use tracing::info;
 
 use crate::FaucetConfig;
@@ -37,6 +38,24 @@ pub struct LocalFaucet {
     coin_id: Arc<Mutex<ObjectID>>,
     coin_amount: u64,
     num_coins: usize,
+    max_batch_size: usize,
+
+    /// Requests waiting to be included in the next transaction.
+    pending: std::sync::Mutex<VecDeque<PendingRequest>>,
+
+    /// When each recipient's requests were served, within the current rate limit window.
+    rate_limit: Option<RateLimit>,
+    served: std::sync::Mutex<HashMap<SuiAddress, VecDeque<Instant>>>,
+}
+
+struct PendingRequest {
+    recipient: SuiAddress,
+    tx: oneshot::Sender<Result<Vec<CoinInfo>, FaucetError>>,
+}
+
+struct RateLimit {
+    max_requests: u32,
+    window: Duration,
 }
 
 /// We do not just derive(Debug) because WalletContext and the WriteAheadLog do not implement Debug / are also hard
@@ -64,14 +83,98 @@ impl LocalFaucet {
             coin_id: Arc::new(Mutex::new(*coins[0].id())),
             coin_amount: config.amount,
             num_coins: config.num_coins,
+            max_batch_size: config.max_batch_size.max(1),
+            pending: std::sync::Mutex::new(VecDeque::new()),
+            rate_limit: config
+                .max_requests_per_recipient
+                .map(|max_requests| RateLimit {
+                    max_requests,
+                    window: Duration::from_secs(config.rate_limit_window_secs),
+                }),
+            served: std::sync::Mutex::new(HashMap::new()),
         }))
     }
 
-    /// Make transaction and execute it.
+    /// Send `num_coins` coins to `recipient`. Requests that arrive while another transaction is
+    /// executing are batched together, and served by a single transaction once it completes.
     pub async fn local_request_execute_tx(
         &self,
         recipient: SuiAddress,
     ) -> Result<Vec<CoinInfo>, FaucetError> {
+        self.check_rate_limit(recipient)?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .push_back(PendingRequest { recipient, tx });
+
+        // Whichever request gets hold of the gas coin next serves all the requests queued so far
+        // (up to the batch size). This request may have already been served by an earlier batch,
+        // or may be served by a later one, but every request that is queued drains the queue at
+        // least once, so none are left behind.
+        {
+            let coin_id = self.coin_id.lock().await;
+            let batch: Vec<_> = {
+                let mut pending = self.pending.lock().unwrap();
+                let size = pending.len().min(self.max_batch_size);
+                pending.drain(..size).collect()
+            };
+
+            if !batch.is_empty() {
+                let recipients: Vec<_> = batch.iter().map(|r| r.recipient).collect();
+                let results: Vec<Result<_, _>> =
+                    match self.execute_batch(*coin_id, &recipients).await {
+                        Ok(coins) => coins.into_iter().map(Ok).collect(),
+                        Err(e) => vec![Err(e); batch.len()],
+                    };
+
+                for (request, result) in batch.into_iter().zip(results) {
+                    let _ = request.tx.send(result);
+                }
+            }
+        }
+
+        rx.await
+            .map_err(|_| FaucetError::internal("Request was dropped before it was served"))?
+    }
+
+    /// Fail if `recipient` has already been served the maximum number of requests within the
+    /// current rate limit window, otherwise count this request against its limit.
+    fn check_rate_limit(&self, recipient: SuiAddress) -> Result<(), FaucetError> {
+        let Some(RateLimit {
+            max_requests,
+            window,
+        }) = self.rate_limit
+        else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut served = self.served.lock().unwrap();
+        let times = served.entry(recipient).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= window)
+        {
+            times.pop_front();
+        }
+
+        if times.len() >= max_requests as usize {
+            return Err(FaucetError::TooManyRequests(recipient.to_string()));
+        }
+
+        times.push_back(now);
+        Ok(())
+    }
+
+    /// Send `num_coins` coins to each of `recipients` in a single transaction, paid for by the
+    /// gas coin `coin_id`, and return the coins sent to each recipient, in the same order.
+    async fn execute_batch(
+        &self,
+        coin_id: ObjectID,
+        recipients: &[SuiAddress],
+    ) -> Result<Vec<Vec<CoinInfo>>, FaucetError> {
         let gas_price = self
             .wallet
             .get_reference_gas_price()
@@ -79,29 +182,31 @@ impl LocalFaucet {
             .map_err(|e| FaucetError::internal(format!("Failed to get gas price: {}", e)))?;
 
         let mut ptb = ProgrammableTransactionBuilder::new();
-        let recipients = vec![recipient; self.num_coins];
-        let amounts = vec![self.coin_amount; self.num_coins];
-        ptb.pay_sui(recipients, amounts)
+        let payees: Vec<_> = recipients
+            .iter()
+            .flat_map(|r| std::iter::repeat_n(*r, self.num_coins))
+            .collect();
+        let amounts = vec![self.coin_amount; payees.len()];
+        ptb.pay_sui(payees, amounts)
             .map_err(FaucetError::internal)?;
 
         let ptb = ptb.finish();
 
-        let coin_id = self.coin_id.lock().await;
         let coin_id_ref = self
             .wallet
-            .get_object_ref(*coin_id)
+            .get_object_ref(coin_id)
             .await
             .map_err(|e| FaucetError::internal(format!("Failed to get object ref: {}", e)))?;
         let tx_data = TransactionData::new_programmable(
             self.active_address,
             vec![coin_id_ref],
             ptb,
-            GAS_BUDGET,
+            GAS_BUDGET * recipients.len() as u64,
             gas_price,
         );
 
         let tx = self
-            .execute_txn_with_retries(tx_data, *coin_id, NUM_RETRIES)
+            .execute_txn_with_retries(tx_data, coin_id, NUM_RETRIES)
             .await
             .map_err(FaucetError::internal)?;
 
@@ -111,17 +216,29 @@ impl LocalFaucet {
             ));
         };
 
-        let coins: Vec<CoinInfo> = effects
-            .created()
-            .iter()
-            .map(|o| CoinInfo {
+        // Share out the created coins between the requests, by owner, so that a recipient that
+        // made several requests in the same batch gets `num_coins` coins for each of them.
+        let mut created: BTreeMap<SuiAddress, Vec<CoinInfo>> = BTreeMap::new();
+        for o in effects.created() {
+            let Ok(owner) = o.owner.get_owner_address() else {
+                continue;
+            };
+
+            created.entry(owner).or_default().push(CoinInfo {
                 amount: self.coin_amount,
                 id: o.object_id(),
                 transfer_tx_digest: *effects.transaction_digest(),
-            })
-            .collect();
+            });
+        }
 
-        Ok(coins)
+        Ok(recipients
+            .iter()
+            .map(|r| {
+                let coins = created.entry(*r).or_default();
+                let n = coins.len().min(self.num_coins);
+                coins.drain(..n).collect()
+            })
+            .collect())
     }
 
     async fn execute_txn(
@@ -224,6 +341,8 @@ async fn find_gas_coins_and_address(
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeSet;
+
     use super::*;
     use test_cluster::TestClusterBuilder;
 
@@ -261,6 +380,73 @@ mod tests {
         assert_eq!(coins.data.len(), 2 * local_faucet.num_coins);
     }
 
+    #[tokio::test]
+    async fn test_local_faucet_batched_requests() {
+        let cluster = TestClusterBuilder::new().build().await;
+        let client = cluster.sui_client().clone();
+
+        let config = FaucetConfig::default();
+        let local_faucet = LocalFaucet::new(cluster.wallet, config).await.unwrap();
+
+        // One of the recipients makes two requests.
+        let mut recipients: Vec<_> = (0..4)
+            .map(|_| SuiAddress::random_for_testing_only())
+            .collect();
+        recipients.push(recipients[0]);
+
+        let results = futures::future::join_all(
+            recipients
+                .iter()
+                .map(|r| local_faucet.local_request_execute_tx(*r)),
+        )
+        .await;
+
+        for result in &results {
+            assert_eq!(result.as_ref().unwrap().len(), local_faucet.num_coins);
+        }
+
+        // Concurrent requests share transactions.
+        let digests: BTreeSet<_> = results
+            .iter()
+            .flat_map(|coins| coins.as_ref().unwrap())
+            .map(|c| c.transfer_tx_digest)
+            .collect();
+        assert!(digests.len() < recipients.len());
+
+        let coins = client
+            .coin_read_api()
+            .get_coins(recipients[0], None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(coins.data.len(), 2 * local_faucet.num_coins);
+    }
+
+    #[tokio::test]
+    async fn test_local_faucet_rate_limit() {
+        let cluster = TestClusterBuilder::new().build().await;
+
+        let config = FaucetConfig {
+            max_requests_per_recipient: Some(1),
+            ..Default::default()
+        };
+        let local_faucet = LocalFaucet::new(cluster.wallet, config).await.unwrap();
+
+        let recipient = SuiAddress::random_for_testing_only();
+        local_faucet
+            .local_request_execute_tx(recipient)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            local_faucet.local_request_execute_tx(recipient).await,
+            Err(FaucetError::TooManyRequests(_)),
+        ));
+
+        // Other recipients are not affected.
+        let other = SuiAddress::random_for_testing_only();
+        local_faucet.local_request_execute_tx(other).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_find_gas_coins_and_address() {
         let mut cluster = TestClusterBuilder::new().build().await;
@@ -42,8 +42,13 @@ async fn request_local_gas(
         .await;
 
     if let Err(e) = request {
+        let status = match e {
+            FaucetError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            status,
             Json(FaucetResponse {
                 status: RequestStatus::Failure(e),
                 coins_sent: None,
@@ -102,12 +107,22 @@ async fn handle_error(error: BoxError) -> impl IntoResponse {
 /// Start a faucet that is run locally. This should only be used for starting a local network, and
 /// not for devnet/testnet deployments!
 pub async fn start_faucet(app_state: Arc<AppState>) -> Result<(), anyhow::Error> {
+    let FaucetConfig { port, host_ip, .. } = app_state.config;
+    let addr = SocketAddr::new(IpAddr::V4(host_ip), port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    serve_faucet(app_state, listener).await
+}
+
+/// Serve a local faucet on a `listener` that has already been bound, ignoring the host and port in
+/// its config. This is useful for binding to an arbitrary free port (e.g. in tests).
+pub async fn serve_faucet(
+    app_state: Arc<AppState>,
+    listener: tokio::net::TcpListener,
+) -> Result<(), anyhow::Error> {
     let cors = CorsLayer::new()
         .allow_methods(vec![Method::GET, Method::POST])
         .allow_headers(Any)
         .allow_origin(Any);
-    let FaucetConfig { port, host_ip, .. } = app_state.config;
-
     info!("Starting faucet in local mode");
     let app = Router::new()
         .route("/", get(health))
@@ -123,9 +138,7 @@ pub async fn start_faucet(app_state: Arc<AppState>) -> Result<(), anyhow::Error>
                 .into_inner(),
         );
 
-    let addr = SocketAddr::new(IpAddr::V4(host_ip), port);
-    info!("listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    info!("listening on {}", listener.local_addr()?);
     axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
@@ -0,0 +1,75 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Derived objects are objects whose IDs are computed from the ID of a parent object and a key,
+/// rather than from the transaction that creates them, so they can be located without an index.
+/// Each parent and key derive a single object, which can only be claimed once.
+///
+/// A derived object's ID is the ID of the parent's dynamic field whose name is the key wrapped in
+/// `DerivedObjectKey`, so it never collides with one of the parent's own dynamic fields. Once it
+/// has been claimed, a derived object is an ordinary object: it can be owned, shared, wrapped,
+/// and receive objects sent to its address, independently of its parent.
+module sui::derived_object;
+
+use sui::dynamic_field as df;
+
+/// The object derived from this parent and key has already been claimed.
+const EObjectAlreadyExists: u64 = 0;
+
+/// Wraps the key that a derived object's ID is computed from.
+public struct DerivedObjectKey<K: copy + drop + store>(K) has copy, drop, store;
+
+/// Wraps the key of an object derived within an epoch, so that the same parent and key derive a
+/// different object in every epoch.
+public struct EpochScopedKey<K: copy + drop + store> has copy, drop, store {
+    epoch: u64,
+    key: K,
+}
+
+/// The name of the dynamic field of the parent that records that the derived object with this ID
+/// has been claimed.
+public struct Claimed(ID) has copy, drop, store;
+
+/// Claim the object derived from `parent` with `key`, returning its `UID`.
+/// Aborts with `EObjectAlreadyExists` if it has already been claimed, even if it has since been
+/// deleted.
+public fun claim<K: copy + drop + store>(parent: &mut UID, key: K): UID {
+    let addr = derive_address(parent.to_inner(), key);
+    let id = object::id_from_address(addr);
+    assert!(!df::exists_(parent, Claimed(id)), EObjectAlreadyExists);
+
+    df::add(parent, Claimed(id), true);
+    object::new_uid_from_hash(addr)
+}
+
+/// Claim the object derived from `parent` with `key` in the current epoch, returning its `UID`.
+/// Aborts with `EObjectAlreadyExists` if it has already been claimed in this epoch.
+public fun claim_for_epoch<K: copy + drop + store>(
+    parent: &mut UID,
+    key: K,
+    ctx: &TxContext,
+): UID {
+    claim(parent, EpochScopedKey { epoch: ctx.epoch(), key })
+}
+
+/// Whether the object derived from `parent` with `key` has been claimed.
+public fun exists_<K: copy + drop + store>(parent: &UID, key: K): bool {
+    let addr = derive_address(parent.to_inner(), key);
+    df::exists_(parent, Claimed(object::id_from_address(addr)))
+}
+
+/// The address of the object derived from `parent` with `key`, whether it has been claimed or not.
+public fun derive_address<K: copy + drop + store>(parent: ID, key: K): address {
+    df::hash_type_and_key(parent.to_address(), DerivedObjectKey(key))
+}
+
+#[test_only]
+/// The address of the object derived from `parent` with `key` in `epoch`, as claimed by
+/// `claim_for_epoch` in that epoch.
+public fun derive_address_for_epoch<K: copy + drop + store>(
+    parent: ID,
+    epoch: u64,
+    key: K,
+): address {
+    derive_address(parent, EpochScopedKey { epoch, key })
+}
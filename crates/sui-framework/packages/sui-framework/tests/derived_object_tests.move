@@ -0,0 +1,139 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[test_only]
+module sui::derived_object_tests;
+
+use sui::derived_object;
+use sui::dynamic_field;
+use sui::test_scenario;
+
+public struct Key(u64) has copy, drop, store;
+
+#[test]
+fun claim_derives_address() {
+    let mut scenario = test_scenario::begin(@0x0);
+    let mut parent = scenario.new_object();
+
+    assert!(!derived_object::exists_(&parent, Key(0)));
+    let derived = derived_object::claim(&mut parent, Key(0));
+    assert!(derived_object::exists_(&parent, Key(0)));
+    assert!(!derived_object::exists_(&parent, Key(1)));
+
+    let expected = derived_object::derive_address(parent.to_inner(), Key(0));
+    assert!(derived.to_address() == expected);
+
+    // The derived object's ID is not one of the parent's dynamic fields.
+    assert!(!dynamic_field::exists_(&parent, Key(0)));
+    assert!(expected != derived_object::derive_address(parent.to_inner(), Key(1)));
+
+    derived.delete();
+    parent.delete();
+    scenario.end();
+}
+
+#[test]
+#[expected_failure(abort_code = sui::derived_object::EObjectAlreadyExists)]
+fun claim_twice() {
+    let mut scenario = test_scenario::begin(@0x0);
+    let mut parent = scenario.new_object();
+
+    let derived = derived_object::claim(&mut parent, Key(0));
+    derived.delete();
+
+    // Deleting the derived object does not let it be claimed again.
+    let _derived = derived_object::claim(&mut parent, Key(0));
+    abort 42
+}
+
+#[test]
+fun claim_for_epoch() {
+    let mut scenario = test_scenario::begin(@0x0);
+    let mut parent = scenario.new_object();
+
+    let derived_0 = derived_object::claim_for_epoch(&mut parent, Key(0), scenario.ctx());
+    scenario.next_epoch(@0x0);
+    let derived_1 = derived_object::claim_for_epoch(&mut parent, Key(0), scenario.ctx());
+    assert!(derived_0.to_address() != derived_1.to_address());
+
+    // Epoch-scoped objects do not claim the key itself.
+    assert!(!derived_object::exists_(&parent, Key(0)));
+    let derived = derived_object::claim(&mut parent, Key(0));
+    assert!(derived.to_address() != derived_0.to_address());
+
+    derived.delete();
+    derived_0.delete();
+    derived_1.delete();
+    parent.delete();
+    scenario.end();
+}
+
+/// Known-answer vectors from `crates/sui-derived-object-id/tests/vectors.json`, which are checked
+/// against the node's derivation in Rust, and which SDKs check their own derivation against. The
+/// vector keyed by a `Balance` is only checked in Rust, because a `Balance` cannot be a key.
+#[test]
+fun derive_address_vectors() {
+    let parent = object::id_from_address(@0x2);
+    assert!(
+        derived_object::derive_address(parent, 42u64)
+            == @0xa56e251f5d7109f7c598e047d884510929d1c90c42afa160c112361f104281fa,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, 0, 42u64)
+            == @0x512e83fba9428d253c7d8b583ade587b747e7917a35f1bf1db466726a863e49d,
+    );
+
+    let parent = object::id_from_address(
+        @0x5d0a2b9c4e3f1a7b8c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b,
+    );
+    assert!(
+        derived_object::derive_address(parent, b"derived")
+            == @0x05da7f368ffec88b282d0249c0b654a0df17d5a1a9540575b721c36c32acc9bf,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, 42, b"derived")
+            == @0xa29d54b1580aa3d0e84553352748a2e140ec7a49cdbc2688c0bbf1b5211995b0,
+    );
+
+    let parent = object::id_from_address(@0x6);
+    assert!(
+        derived_object::derive_address(parent, @0x1234)
+            == @0xddfc6530c7f802c2b3339c611ef5aae5529957a06dce7e9b1807d5549ce50b46,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, 1000, @0x1234)
+            == @0xbb48b5757d935f6a17a0feef01cbbffc4f385c6281af7d7aeca540e017a37f95,
+    );
+
+    let parent = object::id_from_address(@0xc0ffee);
+    assert!(
+        derived_object::derive_address(parent, x"00")
+            == @0x7955f2a61df1b8e0eda7259a8e1f073dd0e33742897c9d3a6fafc40828d562ef,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, 123456789, x"00")
+            == @0x5b53cb6b4e2a5f03131d5f137e6191809c3dc7bac8078408f4fbbc2c73a046fe,
+    );
+
+    // A key longer than 127 bytes, whose length takes two bytes in BCS.
+    let key = vector::tabulate!(200, |i| i as u8);
+    assert!(
+        derived_object::derive_address(parent, key)
+            == @0x2f7c666e6eebc9950efa3096c61afbe98bfedb9cf7560c25aa3ef21e830f3bd8,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, 1 << 32, key)
+            == @0xa5fef776f58d4ae5b6bd3d606729eee4945ac2743d4951e20fc0f169c317d6d1,
+    );
+
+    let parent = object::id_from_address(@0x7);
+    let key = b"a longer key, with some punctuation!".to_string();
+    assert!(
+        derived_object::derive_address(parent, key)
+            == @0xb95f2bf618304d45b958f5875188d7354385f59c39ef7d5e54aebd8f1af54404,
+    );
+    assert!(
+        derived_object::derive_address_for_epoch(parent, std::u64::max_value!(), key)
+            == @0x39cb046449e83fc28ad21276bf9aa1df94d8bcbc5024ee127daae9b651173a42,
+    );
+}
@@ -26,6 +26,7 @@ mod connection_info;
 mod fuse;
 mod io;
 mod listener;
+pub mod tls;
 
 pub use config::Config;
 pub use listener::Listener;
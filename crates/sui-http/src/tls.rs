@@ -0,0 +1,222 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::crypto::CryptoProvider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use tokio_rustls::rustls::sign::CertifiedKey;
+
+use crate::BoxError;
+
+/// Build a TLS server config that serves the PEM formatted certificate chain in `cert_file`, with
+/// the PEM formatted private key in `key_file`.
+///
+/// If `client_ca_file` is set, clients must present a certificate signed by one of the PEM
+/// formatted CA certificates in it. If `reload_interval` is set, the certificate and key files are
+/// checked for changes at that interval, and reloaded if they changed, see [CertReloader].
+pub fn server_config_from_files(
+    cert_file: impl AsRef<Path>,
+    key_file: impl AsRef<Path>,
+    client_ca_file: Option<&Path>,
+    reload_interval: Option<Duration>,
+) -> Result<rustls::ServerConfig, BoxError> {
+    let builder = rustls::ServerConfig::builder();
+    let provider = builder.crypto_provider().clone();
+
+    let builder = if let Some(client_ca_file) = client_ca_file {
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(client_ca_file)? {
+            roots.add(cert?)?;
+        }
+
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()?;
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let reloader = Arc::new(CertReloader::new(provider, cert_file, key_file)?);
+    if let Some(interval) = reload_interval {
+        reloader.reload_every(interval);
+    }
+
+    Ok(builder.with_cert_resolver(reloader))
+}
+
+/// Serves a certificate chain and private key loaded from PEM files, that can be reloaded when
+/// the files change, e.g. because the certificate was rotated, without restarting the server.
+#[derive(Debug)]
+pub struct CertReloader {
+    provider: Arc<CryptoProvider>,
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    loaded: RwLock<Loaded>,
+}
+
+/// The contents of the certificate and key files, as of the last time they were loaded.
+#[derive(Debug)]
+struct Loaded {
+    cert_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+    certified_key: Arc<CertifiedKey>,
+}
+
+impl CertReloader {
+    /// Load the certificate chain and private key from `cert_file` and `key_file`, using
+    /// `provider` to load the key.
+    pub fn new(
+        provider: Arc<CryptoProvider>,
+        cert_file: impl AsRef<Path>,
+        key_file: impl AsRef<Path>,
+    ) -> Result<Self, BoxError> {
+        let cert_file = cert_file.as_ref().to_owned();
+        let key_file = key_file.as_ref().to_owned();
+        let cert_pem = std::fs::read(&cert_file)?;
+        let key_pem = std::fs::read(&key_file)?;
+        let certified_key = certified_key(&provider, &cert_pem, &key_pem)?;
+
+        Ok(Self {
+            provider,
+            cert_file,
+            key_file,
+            loaded: RwLock::new(Loaded {
+                cert_pem,
+                key_pem,
+                certified_key,
+            }),
+        })
+    }
+
+    /// Reload the certificate chain and private key if either file's contents changed since they
+    /// were last loaded. Returns whether they were reloaded. If the new files cannot be loaded,
+    /// the previous certificate keeps being served.
+    pub fn reload(&self) -> Result<bool, BoxError> {
+        let cert_pem = std::fs::read(&self.cert_file)?;
+        let key_pem = std::fs::read(&self.key_file)?;
+
+        {
+            let loaded = self.loaded.read().unwrap();
+            if loaded.cert_pem == cert_pem && loaded.key_pem == key_pem {
+                return Ok(false);
+            }
+        }
+
+        let certified_key = certified_key(&self.provider, &cert_pem, &key_pem)?;
+        *self.loaded.write().unwrap() = Loaded {
+            cert_pem,
+            key_pem,
+            certified_key,
+        };
+
+        Ok(true)
+    }
+
+    /// Check the files for changes every `interval`, in a background task that stops once the
+    /// reloader is dropped.
+    pub fn reload_every(self: &Arc<Self>, interval: Duration) {
+        let reloader = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            // The first tick completes immediately, and the files were just loaded.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(reloader) = reloader.upgrade() else {
+                    return;
+                };
+
+                match reloader.reload() {
+                    Ok(true) => tracing::info!(
+                        cert_file = %reloader.cert_file.display(),
+                        "Reloaded TLS certificate",
+                    ),
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!(
+                        cert_file = %reloader.cert_file.display(),
+                        error = %e,
+                        "Failed to reload TLS certificate, still serving the previous one",
+                    ),
+                }
+            }
+        });
+    }
+}
+
+impl ResolvesServerCert for CertReloader {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.loaded.read().unwrap().certified_key.clone())
+    }
+}
+
+fn certified_key(
+    provider: &CryptoProvider,
+    cert_pem: &[u8],
+    key_pem: &[u8],
+) -> Result<Arc<CertifiedKey>, BoxError> {
+    let certs = CertificateDer::pem_slice_iter(cert_pem).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err("No certificates found in TLS certificate file".into());
+    }
+
+    let key = provider
+        .key_provider
+        .load_private_key(PrivateKeyDer::from_pem_slice(key_pem)?)?;
+
+    Ok(Arc::new(CertifiedKey::new(certs, key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_cert(dir: &Path) -> CertificateDer<'static> {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        std::fs::write(dir.join("cert.pem"), cert.pem()).unwrap();
+        std::fs::write(dir.join("key.pem"), key_pair.serialize_pem()).unwrap();
+        cert.der().clone()
+    }
+
+    fn served(reloader: &CertReloader) -> CertificateDer<'static> {
+        reloader.loaded.read().unwrap().certified_key.cert[0].clone()
+    }
+
+    #[test]
+    fn reload_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = rustls::ServerConfig::builder().crypto_provider().clone();
+
+        let first = write_cert(dir.path());
+        let reloader = CertReloader::new(
+            provider,
+            dir.path().join("cert.pem"),
+            dir.path().join("key.pem"),
+        )
+        .unwrap();
+        assert_eq!(served(&reloader), first);
+
+        // Nothing changed, so nothing is reloaded.
+        assert!(!reloader.reload().unwrap());
+        assert_eq!(served(&reloader), first);
+
+        // The certificate is rotated.
+        let second = write_cert(dir.path());
+        assert!(reloader.reload().unwrap());
+        assert_eq!(served(&reloader), second);
+
+        // A broken certificate is not picked up.
+        std::fs::write(dir.path().join("cert.pem"), "not a certificate").unwrap();
+        reloader.reload().unwrap_err();
+        assert_eq!(served(&reloader), second);
+    }
+}
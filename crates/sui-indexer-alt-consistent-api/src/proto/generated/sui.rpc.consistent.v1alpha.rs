@@ -193,17 +193,7 @@ pub struct Owner {
 }
 /// Nested message and enum types in `Owner`.
 pub mod owner {
-    #[derive(
-        Clone,
-        Copy,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash,
-        PartialOrd,
-        Ord,
-        ::prost::Enumeration
-    )]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum OwnerKind {
         Unknown = 0,
@@ -211,6 +201,7 @@ pub mod owner {
         Object = 2,
         Shared = 3,
         Immutable = 4,
+        ConsensusAddress = 5,
     }
     impl OwnerKind {
         /// String value of the enum field names used in the ProtoBuf definition.
@@ -224,6 +215,7 @@ pub mod owner {
                 Self::Object => "OBJECT",
                 Self::Shared => "SHARED",
                 Self::Immutable => "IMMUTABLE",
+                Self::ConsensusAddress => "CONSENSUS_ADDRESS",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
@@ -234,6 +226,7 @@ pub mod owner {
                 "OBJECT" => Some(Self::Object),
                 "SHARED" => Some(Self::Shared),
                 "IMMUTABLE" => Some(Self::Immutable),
+                "CONSENSUS_ADDRESS" => Some(Self::ConsensusAddress),
                 _ => None,
             }
         }
@@ -305,10 +298,10 @@ pub mod consistent_service_client {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
-    use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
     #[derive(Debug, Clone)]
     pub struct ConsistentServiceClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -352,9 +345,8 @@ pub mod consistent_service_client {
                     <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
                 >,
             >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::Body>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+            <T as tonic::codegen::Service<http::Request<tonic::body::Body>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
         {
             ConsistentServiceClient::new(InterceptedService::new(inner, interceptor))
         }
@@ -392,201 +384,133 @@ pub mod consistent_service_client {
         pub async fn available_range(
             &mut self,
             request: impl tonic::IntoRequest<super::AvailableRangeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::AvailableRangeResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::AvailableRangeResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/AvailableRange",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "AvailableRange",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "AvailableRange",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn batch_get_balances(
             &mut self,
             request: impl tonic::IntoRequest<super::BatchGetBalancesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::BatchGetBalancesResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::BatchGetBalancesResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/BatchGetBalances",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "BatchGetBalances",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "BatchGetBalances",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn get_balance(
             &mut self,
             request: impl tonic::IntoRequest<super::GetBalanceRequest>,
         ) -> std::result::Result<tonic::Response<super::Balance>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/GetBalance",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "GetBalance",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "GetBalance",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn list_balances(
             &mut self,
             request: impl tonic::IntoRequest<super::ListBalancesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListBalancesResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ListBalancesResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListBalances",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "ListBalances",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "ListBalances",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn list_objects_by_type(
             &mut self,
             request: impl tonic::IntoRequest<super::ListObjectsByTypeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListObjectsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ListObjectsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListObjectsByType",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "ListObjectsByType",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "ListObjectsByType",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn list_owned_objects(
             &mut self,
             request: impl tonic::IntoRequest<super::ListOwnedObjectsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListObjectsResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ListObjectsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListOwnedObjects",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "ListOwnedObjects",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "ListOwnedObjects",
+            ));
             self.inner.unary(req, path, codec).await
         }
         pub async fn service_config(
             &mut self,
             request: impl tonic::IntoRequest<super::ServiceConfigRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ServiceConfigResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
+        ) -> std::result::Result<tonic::Response<super::ServiceConfigResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ServiceConfig",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "sui.rpc.consistent.v1alpha.ConsistentService",
-                        "ServiceConfig",
-                    ),
-                );
+            req.extensions_mut().insert(GrpcMethod::new(
+                "sui.rpc.consistent.v1alpha.ConsistentService",
+                "ServiceConfig",
+            ));
             self.inner.unary(req, path, codec).await
         }
     }
@@ -598,7 +522,7 @@ pub mod consistent_service_server {
         dead_code,
         missing_docs,
         clippy::wildcard_imports,
-        clippy::let_unit_value,
+        clippy::let_unit_value
     )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with ConsistentServiceServer.
@@ -607,17 +531,11 @@ pub mod consistent_service_server {
         async fn available_range(
             &self,
             request: tonic::Request<super::AvailableRangeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::AvailableRangeResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::AvailableRangeResponse>, tonic::Status>;
         async fn batch_get_balances(
             &self,
             request: tonic::Request<super::BatchGetBalancesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::BatchGetBalancesResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::BatchGetBalancesResponse>, tonic::Status>;
         async fn get_balance(
             &self,
             request: tonic::Request<super::GetBalanceRequest>,
@@ -625,31 +543,19 @@ pub mod consistent_service_server {
         async fn list_balances(
             &self,
             request: tonic::Request<super::ListBalancesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListBalancesResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ListBalancesResponse>, tonic::Status>;
         async fn list_objects_by_type(
             &self,
             request: tonic::Request<super::ListObjectsByTypeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListObjectsResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ListObjectsResponse>, tonic::Status>;
         async fn list_owned_objects(
             &self,
             request: tonic::Request<super::ListOwnedObjectsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListObjectsResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ListObjectsResponse>, tonic::Status>;
         async fn service_config(
             &self,
             request: tonic::Request<super::ServiceConfigRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ServiceConfigResponse>,
-            tonic::Status,
-        >;
+        ) -> std::result::Result<tonic::Response<super::ServiceConfigResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ConsistentServiceServer<T> {
@@ -672,10 +578,7 @@ pub mod consistent_service_server {
                 max_encoding_message_size: None,
             }
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
         where
             F: tonic::service::Interceptor,
         {
@@ -730,23 +633,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/AvailableRange" => {
                     #[allow(non_camel_case_types)]
                     struct AvailableRangeSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::AvailableRangeRequest>
-                    for AvailableRangeSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::AvailableRangeRequest>
+                        for AvailableRangeSvc<T>
+                    {
                         type Response = super::AvailableRangeResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::AvailableRangeRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::available_range(&inner, request)
-                                    .await
+                                <T as ConsistentService>::available_range(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -776,26 +675,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/BatchGetBalances" => {
                     #[allow(non_camel_case_types)]
                     struct BatchGetBalancesSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::BatchGetBalancesRequest>
-                    for BatchGetBalancesSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::BatchGetBalancesRequest>
+                        for BatchGetBalancesSvc<T>
+                    {
                         type Response = super::BatchGetBalancesResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::BatchGetBalancesRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::batch_get_balances(
-                                        &inner,
-                                        request,
-                                    )
-                                    .await
+                                <T as ConsistentService>::batch_get_balances(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -825,15 +717,11 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/GetBalance" => {
                     #[allow(non_camel_case_types)]
                     struct GetBalanceSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::GetBalanceRequest>
-                    for GetBalanceSvc<T> {
+                    impl<T: ConsistentService> tonic::server::UnaryService<super::GetBalanceRequest>
+                        for GetBalanceSvc<T>
+                    {
                         type Response = super::Balance;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::GetBalanceRequest>,
@@ -870,23 +758,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListBalances" => {
                     #[allow(non_camel_case_types)]
                     struct ListBalancesSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::ListBalancesRequest>
-                    for ListBalancesSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::ListBalancesRequest>
+                        for ListBalancesSvc<T>
+                    {
                         type Response = super::ListBalancesResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ListBalancesRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::list_balances(&inner, request)
-                                    .await
+                                <T as ConsistentService>::list_balances(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -916,25 +800,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListObjectsByType" => {
                     #[allow(non_camel_case_types)]
                     struct ListObjectsByTypeSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::ListObjectsByTypeRequest>
-                    for ListObjectsByTypeSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::ListObjectsByTypeRequest>
+                        for ListObjectsByTypeSvc<T>
+                    {
                         type Response = super::ListObjectsResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ListObjectsByTypeRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::list_objects_by_type(
-                                        &inner,
-                                        request,
-                                    )
+                                <T as ConsistentService>::list_objects_by_type(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -965,26 +843,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ListOwnedObjects" => {
                     #[allow(non_camel_case_types)]
                     struct ListOwnedObjectsSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::ListOwnedObjectsRequest>
-                    for ListOwnedObjectsSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::ListOwnedObjectsRequest>
+                        for ListOwnedObjectsSvc<T>
+                    {
                         type Response = super::ListObjectsResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ListOwnedObjectsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::list_owned_objects(
-                                        &inner,
-                                        request,
-                                    )
-                                    .await
+                                <T as ConsistentService>::list_owned_objects(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1014,23 +885,19 @@ pub mod consistent_service_server {
                 "/sui.rpc.consistent.v1alpha.ConsistentService/ServiceConfig" => {
                     #[allow(non_camel_case_types)]
                     struct ServiceConfigSvc<T: ConsistentService>(pub Arc<T>);
-                    impl<
-                        T: ConsistentService,
-                    > tonic::server::UnaryService<super::ServiceConfigRequest>
-                    for ServiceConfigSvc<T> {
+                    impl<T: ConsistentService>
+                        tonic::server::UnaryService<super::ServiceConfigRequest>
+                        for ServiceConfigSvc<T>
+                    {
                         type Response = super::ServiceConfigResponse;
-                        type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
-                            tonic::Status,
-                        >;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
                             request: tonic::Request<super::ServiceConfigRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ConsistentService>::service_config(&inner, request)
-                                    .await
+                                <T as ConsistentService>::service_config(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1057,25 +924,19 @@ pub mod consistent_service_server {
                     };
                     Box::pin(fut)
                 }
-                _ => {
-                    Box::pin(async move {
-                        let mut response = http::Response::new(
-                            tonic::body::Body::default(),
-                        );
-                        let headers = response.headers_mut();
-                        headers
-                            .insert(
-                                tonic::Status::GRPC_STATUS,
-                                (tonic::Code::Unimplemented as i32).into(),
-                            );
-                        headers
-                            .insert(
-                                http::header::CONTENT_TYPE,
-                                tonic::metadata::GRPC_CONTENT_TYPE,
-                            );
-                        Ok(response)
-                    })
-                }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(tonic::body::Body::default());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
             }
         }
     }
@@ -33,33 +33,28 @@ impl Processor for ObjectByOwner {
 
         // Objects that are in the inputs but not the outputs have been deleted.
         for (id, &(input, _)) in &input_objects {
-            let Some(key_in) = Key::from_object(input) else {
-                continue;
-            };
-
             if !output_objects.contains_key(id) {
-                values.push(Value::Del(key_in));
+                values.extend(Key::from_object(input).into_iter().map(Value::Del));
             }
         }
 
         for (id, (output, digest)) in output_objects {
-            let Some(key_out) = Key::from_object(output) else {
-                continue;
-            };
-
-            // If the ID is in the input objects with a different key, it needs to be deleted at
-            // that location.
-            if let Some(key_in) = input_objects
-                .get(&id)
-                .and_then(|(input, _)| Key::from_object(input))
-            {
-                if key_in != key_out {
-                    values.push(Value::Del(key_in));
+            let keys_out = Key::from_object(output);
+
+            // If the ID is in the input objects with keys that it no longer has, it needs to be
+            // deleted at those locations.
+            if let Some((input, _)) = input_objects.get(&id) {
+                for key_in in Key::from_object(input) {
+                    if !keys_out.contains(&key_in) {
+                        values.push(Value::Del(key_in));
+                    }
                 }
             }
 
-            // The object is always put at its output location.
-            values.push(Value::Put(key_out, (output.version(), digest)));
+            // The object is always put at its output locations.
+            for key_out in keys_out {
+                values.push(Value::Put(key_out, (output.version(), digest)));
+            }
         }
 
         Ok(values)
@@ -62,7 +62,7 @@ pub(super) fn list_owned_objects(
             return Err(Error::MissingOwner.into());
         }
 
-        (kind @ (GK::Address | GK::Object), "") => {
+        (kind @ (GK::Address | GK::Object | GK::ConsensusAddress), "") => {
             return Err(Error::MissingAddress(kind).into());
         }
 
@@ -70,6 +70,7 @@ pub(super) fn list_owned_objects(
         (GK::Immutable, "") => SK::Immutable,
         (GK::Address, address) => SK::AddressOwner(addr(address)?),
         (GK::Object, address) => SK::ObjectOwner(addr(address)?),
+        (GK::ConsensusAddress, address) => SK::ConsensusAddressOwner(addr(address)?),
 
         (kind @ (GK::Shared | GK::Immutable), _) => {
             return Err(Error::UnexpectedAddress(kind).into());
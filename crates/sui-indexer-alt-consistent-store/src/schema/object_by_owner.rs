@@ -41,16 +41,34 @@ pub(crate) enum OwnerKind {
     ObjectOwner(SuiAddress),
     Shared,
     Immutable,
+    /// Only ConsensusAddressOwner maps to this OwnerKind, so that an address's consensus-owned
+    /// objects can be fetched without the rest of its objects.
+    ConsensusAddressOwner(SuiAddress),
 }
 
 impl Key {
-    pub(crate) fn from_object(obj: &Object) -> Option<Key> {
-        Some(Key {
-            kind: OwnerKind::from_owner(obj.owner()),
-            type_: obj.type_()?.clone().into(),
-            balance: obj.as_coin_maybe().map(|coin| !coin.balance.value()),
+    /// The keys that `obj` is indexed under: one for its owner, and an additional key for objects
+    /// owned by an address through consensus. Returns no keys for packages.
+    pub(crate) fn from_object(obj: &Object) -> Vec<Key> {
+        let Some(type_) = obj.type_() else {
+            return vec![];
+        };
+
+        let type_: StructTag = type_.clone().into();
+        let balance = obj.as_coin_maybe().map(|coin| !coin.balance.value());
+        let key = |kind| Key {
+            kind,
+            type_: type_.clone(),
+            balance,
             object_id: obj.id(),
-        })
+        };
+
+        let mut keys = vec![key(OwnerKind::from_owner(obj.owner()))];
+        if let Owner::ConsensusAddressOwner { owner, .. } = obj.owner() {
+            keys.push(key(OwnerKind::ConsensusAddressOwner(*owner)));
+        }
+
+        keys
     }
 }
 
@@ -82,6 +100,10 @@ impl Encode for OwnerKind {
             }
             OwnerKind::Shared => w.write(&[2]),
             OwnerKind::Immutable => w.write(&[3]),
+            OwnerKind::ConsensusAddressOwner(address) => {
+                w.write(&[4])?;
+                BorrowCompat(address).encode(e)
+            }
         }
     }
 }
@@ -103,9 +125,13 @@ impl<C> Decode<C> for OwnerKind {
             }
             2 => Ok(OwnerKind::Shared),
             3 => Ok(OwnerKind::Immutable),
+            4 => {
+                let address = Compat::<SuiAddress>::decode(d)?.0;
+                Ok(OwnerKind::ConsensusAddressOwner(address))
+            }
             v => Err(DecodeError::UnexpectedVariant {
                 type_name: "OwnerKind",
-                allowed: &AllowedEnumVariants::Range { min: 0, max: 3 },
+                allowed: &AllowedEnumVariants::Range { min: 0, max: 4 },
                 found: v as u32,
             }),
         }
@@ -136,5 +162,8 @@ mod tests {
 
         let immutable = OwnerKind::Immutable;
         assert_eq!(immutable, key::decode(&key::encode(&immutable)).unwrap());
+
+        let consensus = OwnerKind::ConsensusAddressOwner(SuiAddress::random_for_testing_only());
+        assert_eq!(consensus, key::decode(&key::encode(&consensus)).unwrap());
     }
 }
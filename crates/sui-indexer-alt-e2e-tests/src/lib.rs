@@ -13,7 +13,7 @@ use diesel_async::RunQueryDsl;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use simulacrum::Simulacrum;
+use simulacrum::{AdvanceEpochConfig, Simulacrum};
 use sui_indexer_alt::{config::IndexerConfig, setup_indexer};
 use sui_indexer_alt_consistent_api::proto::rpc::consistent::v1alpha::{
     consistent_service_client::ConsistentServiceClient, AvailableRangeRequest,
@@ -230,20 +230,37 @@ impl FullCluster {
     /// contents.
     pub async fn create_checkpoint(&mut self) -> VerifiedCheckpoint {
         let checkpoint = self.executor.create_checkpoint();
+        self.wait_for_offchain(checkpoint.sequence_number).await;
+        checkpoint
+    }
+
+    /// Advance the chain to the next epoch, and wait for the off-chain services to ingest the
+    /// checkpoint that ends the current epoch. Returns that checkpoint.
+    pub async fn advance_epoch(&mut self) -> VerifiedCheckpoint {
+        self.executor.advance_epoch(AdvanceEpochConfig::default());
+        let checkpoint = self
+            .executor
+            .store()
+            .get_highest_checkpint()
+            .expect("Advancing the epoch creates a checkpoint");
+        self.wait_for_offchain(checkpoint.sequence_number).await;
+        checkpoint
+    }
+
+    /// Wait for the indexer, consistent store and GraphQL service to ingest `checkpoint`.
+    async fn wait_for_offchain(&self, checkpoint: u64) {
         let indexer = self
             .offchain
-            .wait_for_indexer(checkpoint.sequence_number, Duration::from_secs(10));
+            .wait_for_indexer(checkpoint, Duration::from_secs(10));
         let consistent_store = self
             .offchain
-            .wait_for_consistent_store(checkpoint.sequence_number, Duration::from_secs(10));
+            .wait_for_consistent_store(checkpoint, Duration::from_secs(10));
         let graphql = self
             .offchain
-            .wait_for_graphql(checkpoint.sequence_number, Duration::from_secs(10));
+            .wait_for_graphql(checkpoint, Duration::from_secs(10));
 
         try_join!(indexer, consistent_store, graphql)
             .expect("Timed out waiting for indexer and consistent store");
-
-        checkpoint
     }
 
     /// The URL to talk to the database on.
@@ -0,0 +1,43 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --addresses P=0x0 --simulator
+
+//# programmable --sender A --inputs 100 @0x42
+//> 0: SplitCoins(Gas, [Input(0)]);
+//> 1: TransferObjects([Result(0)], Input(1))
+
+//# create-checkpoint
+
+//# programmable --sender A --inputs 200 @0x42
+//> 0: SplitCoins(Gas, [Input(0)]);
+//> 1: TransferObjects([Result(0)], Input(1))
+
+//# create-checkpoint
+
+//# create-checkpoint
+
+//# publish --upgradeable --sender A
+module P::M { }
+
+//# create-checkpoint
+
+//# run-graphql
+{ # A sent every transaction, and was affected by genesis, where its gas coin was created
+  sender: address(address: "@{A}") { ...Activity }
+
+  # 0x42 received two coins, and has not sent any transactions
+  recipient: address(address: "0x42") { ...Activity }
+
+  # 0x43 has not been involved in any transactions
+  inactive: address(address: "0x43") { ...Activity }
+}
+
+fragment Activity on Address {
+  activity {
+    firstTxCheckpoint
+    lastTxCheckpoint
+    txCount
+    packagesPublished
+  }
+}
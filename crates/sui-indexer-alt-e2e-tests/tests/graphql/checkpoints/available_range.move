@@ -0,0 +1,50 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --simulator
+
+//# programmable --sender A --inputs 1u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# create-checkpoint
+
+//# run-graphql
+{ # Nothing has been pruned, so every connection has data from genesis up to
+  # the latest checkpoint
+  ...Ranges
+
+  # When viewed at an earlier checkpoint, the ranges end at that checkpoint
+  earlier: checkpoint(sequenceNumber: 1) { query { ...Ranges } }
+}
+
+fragment Ranges on Query {
+  checkpoints(first: 1) {
+    availableRange { ...Range }
+  }
+
+  transactions(first: 1) {
+    availableRange { ...Range }
+  }
+
+  checkpoint(sequenceNumber: 1) {
+    transactions(first: 1) {
+      availableRange { ...Range }
+    }
+  }
+
+  transaction(digest: "@{digest_1}") {
+    effects {
+      events(first: 1) {
+        availableRange { ...Range }
+      }
+    }
+  }
+}
+
+fragment Range on AvailableRange {
+  first { sequenceNumber }
+  last { sequenceNumber }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A B --simulator
+
+//# run-graphql
+{ # only the genesis checkpoint and transaction so far
+  e0: epoch(epochId: 0) { ...Totals }
+}
+
+fragment Totals on Epoch {
+  totalCheckpoints
+  totalTransactions
+  totalGasFees
+  totalStakeRewards
+}
+
+//# programmable --sender A --inputs 42 @B
+//> 0: SplitCoins(Gas, [Input(0)]);
+//> 1: TransferObjects([Result(0)], Input(1))
+
+//# create-checkpoint
+
+//# run-graphql
+{ # totals include the checkpointed transaction, before the epoch has ended
+  e0: epoch(epochId: 0) { ...Totals }
+}
+
+fragment Totals on Epoch {
+  totalCheckpoints
+  totalTransactions
+  totalGasFees
+  totalStakeRewards
+}
+
+//# programmable --sender A --inputs 42 @B
+//> 0: SplitCoins(Gas, [Input(0)]);
+//> 1: TransferObjects([Result(0)], Input(1))
+
+//# create-checkpoint
+
+//# run-graphql
+{ # totals roll forward with each checkpoint, and viewing an earlier checkpoint
+  # shows the totals as of that checkpoint
+  e0: epoch(epochId: 0) { ...Totals }
+  checkpoint(sequenceNumber: 1) {
+    query {
+      e0: epoch(epochId: 0) { ...Totals }
+    }
+  }
+}
+
+fragment Totals on Epoch {
+  totalCheckpoints
+  totalTransactions
+  totalGasFees
+  totalStakeRewards
+}
+
+//# advance-epoch
+
+//# run-graphql
+{ # once the epoch has ended, totals come from its end, and stake rewards
+  # include stake subsidies
+  e0: epoch(epochId: 0) { ...Totals }
+}
+
+fragment Totals on Epoch {
+  totalCheckpoints
+  totalTransactions
+  totalGasFees
+  totalStakeRewards
+}
@@ -15,7 +15,7 @@
 //> 1: TransferObjects([Result(0)], Input(1))
 
 //# run-graphql
-{ # no stake rewards after SplitCoins before epoch has end
+{ # no stake rewards yet, because SplitCoins has not been checkpointed
   e0: epoch(epochId: 0) {
     totalStakeRewards
   }
@@ -0,0 +1,43 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --simulator
+
+//# programmable --sender A --inputs 42 @A
+//> 0: SplitCoins(Gas, [Input(0), Input(0)]);
+//> 1: TransferObjects([NestedResult(0,0), NestedResult(0,1)], Input(1))
+
+//# run sui::kiosk::default --sender A
+
+//# run sui::kiosk::place --type-args sui::coin::Coin<sui::sui::SUI> --args object(2,0) object(2,1) object(1,0) --sender A
+
+//# create-checkpoint
+
+//# run-graphql
+{ # A kiosk with a single item, which is not listed, and no transfer policy for its type
+  kiosk(address: "@{obj_2_0}") {
+    itemCount
+    profits
+    allowExtensions
+    items { isLocked listing { price isExclusive } }
+    listings { isLocked }
+    policies { rules balance }
+  }
+
+  # The kiosk's owner cap is not a kiosk
+  notKiosk: kiosk(address: "@{obj_2_1}") { itemCount }
+}
+
+//# run sui::kiosk::place_and_list --type-args sui::coin::Coin<sui::sui::SUI> --args object(2,0) object(2,1) object(1,1) 100 --sender A --summarize
+
+//# create-checkpoint
+
+//# run-graphql
+{ # Only the second item is listed
+  kiosk(address: "@{obj_2_0}") {
+    itemCount
+    items { isLocked }
+    listings { isLocked listing { price isExclusive } }
+    policies { rules balance }
+  }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --simulator
+
+//# programmable --sender A --inputs 1u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# programmable --sender A --inputs 2u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# programmable --sender A --inputs 3u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# run-graphql
+{ # All versions of the object, including versions before and after this one
+  object(address: "@{obj_0_0}", version: 2) {
+    version
+    objectVersions {
+      pageInfo {
+        hasPreviousPage
+        hasNextPage
+      }
+      nodes { version }
+    }
+  }
+}
+
+//# run-graphql
+{ # Limited and filtered, from a MoveObject
+  object(address: "@{obj_0_0}") {
+    asMoveObject {
+      version
+      first: objectVersions(first: 2) {
+        pageInfo {
+          hasPreviousPage
+          hasNextPage
+        }
+        nodes { version }
+      }
+      last: objectVersions(last: 2, filter: { beforeVersion: 4 }) {
+        pageInfo {
+          hasPreviousPage
+          hasNextPage
+        }
+        nodes { version }
+      }
+    }
+  }
+}
+
+//# run-graphql
+{ # Walking the object's lineage through the transactions that created each version
+  object(address: "@{obj_0_0}") {
+    objectVersions(filter: { afterVersion: 1 }) {
+      nodes {
+        version
+        previousTransaction {
+          programmableTransaction {
+            inputs {
+              nodes {
+                ... on Pure { bytes }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
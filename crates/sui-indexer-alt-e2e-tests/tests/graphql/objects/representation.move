@@ -0,0 +1,36 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --simulator
+
+//# programmable --sender A --inputs 1u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# run-graphql
+{ # The clock's contents, in each representation
+  object(address: "0x6") {
+    asMoveObject {
+      default: contents { ...Value }
+      both: contents(representation: BOTH) { ...Value }
+      bcs: contents(representation: BCS) { ...Value }
+      json: contents(representation: JSON) { ...Value }
+    }
+  }
+}
+
+fragment Value on MoveValue {
+  type { repr }
+  bcs
+  json
+}
+
+//# run-graphql
+{ # Representations of transaction data that were not requested are null
+  transaction(digest: "@{digest_1}") {
+    bcs: transactionData(representation: BCS) { json }
+    json: transactionData(representation: JSON) { bcs }
+  }
+}
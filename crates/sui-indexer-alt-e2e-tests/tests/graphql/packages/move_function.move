@@ -0,0 +1,89 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --simulator
+
+//# create-checkpoint
+
+//# run-graphql
+{ # Fetching modules and functions by name, from a system package
+  package(address: "0x2") {
+    module(name: "coin") {
+      name
+      package { address }
+      function(name: "value") {
+        module { name }
+        name
+      }
+      doesNotExist: function(name: "does_not_exist") { name }
+    }
+    doesNotExist: module(name: "does_not_exist") { name }
+  }
+}
+
+//# run-graphql
+{ # Visibility, entry modifiers, and type parameter constraints
+  sui: package(address: "0x2") {
+    coin: module(name: "coin") {
+      value: function(name: "value") { ...Modifiers }
+      join: function(name: "join") { ...Modifiers }
+    }
+    transfer: module(name: "transfer") {
+      publicTransfer: function(name: "public_transfer") { ...Modifiers }
+    }
+    object: module(name: "object") {
+      newUidFromHash: function(name: "new_uid_from_hash") { ...Modifiers }
+      deleteImpl: function(name: "delete_impl") { ...Modifiers }
+    }
+  }
+}
+
+fragment Modifiers on MoveFunction {
+  visibility
+  isEntry
+  typeParameters { constraints }
+}
+
+//# run-graphql
+{ # Parameter and return types, as flat representations
+  sui: package(address: "0x2") {
+    coin: module(name: "coin") {
+      value: function(name: "value") { ...Types }
+      split: function(name: "split") { ...Types }
+    }
+    transfer: module(name: "transfer") {
+      publicTransfer: function(name: "public_transfer") { ...Types }
+    }
+  }
+
+  std: package(address: "0x1") {
+    string: module(name: "string") {
+      utf8: function(name: "utf8") { ...Types }
+    }
+  }
+}
+
+fragment Types on MoveFunction {
+  parameters { repr }
+  return { repr }
+}
+
+//# run-graphql
+{ # Parameter and return types, as structured signatures
+  sui: package(address: "0x2") {
+    coin: module(name: "coin") {
+      split: function(name: "split") { ...Signatures }
+    }
+  }
+
+  std: package(address: "0x1") {
+    string: module(name: "string") {
+      utf8: function(name: "utf8") { ...Signatures }
+    }
+  }
+}
+
+fragment Signatures on MoveFunction {
+  parameters { signature }
+  return { signature }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A --simulator
+
+//# programmable --sender A --inputs 1u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# programmable --sender A --inputs 2u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# programmable --sender A --inputs 3u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# programmable --sender A --inputs 4u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# programmable --sender A --inputs 5u64
+//> SplitCoins(Gas, [Input(0)]);
+//> MergeCoins(Gas, [Result(0)])
+
+//# create-checkpoint
+
+//# run-graphql
+{ # All transactions that wrote a version of the gas coin, after the version it was created at
+  transactions(filter: { affectedObject: { address: "@{obj_0_0}", afterVersion: 1 } }) {
+    nodes { ...Amount }
+  }
+}
+
+fragment Amount on Transaction {
+  programmableTransaction {
+    inputs {
+      nodes {
+        ... on Pure { bytes }
+      }
+    }
+  }
+}
+
+//# run-graphql
+{ # A range of versions that starts part way through a checkpoint
+  transactions(filter: { affectedObject: { address: "@{obj_0_0}", afterVersion: 2, beforeVersion: 6 } }) {
+    nodes { ...Amount }
+  }
+}
+
+fragment Amount on Transaction {
+  programmableTransaction {
+    inputs {
+      nodes {
+        ... on Pure { bytes }
+      }
+    }
+  }
+}
+
+//# run-graphql
+{ # A single version, written by the second transaction in its checkpoint
+  transactions(filter: { affectedObject: { address: "@{obj_0_0}", afterVersion: 4, beforeVersion: 6 } }) {
+    nodes { ...Amount }
+  }
+}
+
+fragment Amount on Transaction {
+  programmableTransaction {
+    inputs {
+      nodes {
+        ... on Pure { bytes }
+      }
+    }
+  }
+}
+
+//# run-graphql
+{ # No versions in range
+  transactions(filter: { affectedObject: { address: "@{obj_0_0}", afterVersion: 6 } }) {
+    nodes { ...Amount }
+  }
+}
+
+fragment Amount on Transaction {
+  programmableTransaction {
+    inputs {
+      nodes {
+        ... on Pure { bytes }
+      }
+    }
+  }
+}
+
+//# run-graphql
+{ # Combined with a checkpoint filter
+  transactions(filter: {
+    afterCheckpoint: 1,
+    affectedObject: { address: "@{obj_0_0}", afterVersion: 1 }
+  }) {
+    nodes { ...Amount }
+  }
+}
+
+fragment Amount on Transaction {
+  programmableTransaction {
+    inputs {
+      nodes {
+        ... on Pure { bytes }
+      }
+    }
+  }
+}
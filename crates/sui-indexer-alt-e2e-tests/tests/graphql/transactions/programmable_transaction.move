@@ -0,0 +1,54 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//# init --protocol-version 70 --accounts A B --simulator
+
+//# programmable --sender A --inputs 100u64 200u64 @B
+//> 0: SplitCoins(Gas, [Input(0), Input(1)]);
+//> 1: TransferObjects([NestedResult(0, 0), NestedResult(0, 1)], Input(2));
+
+//# advance-clock --duration-ns 1000000
+
+//# create-checkpoint
+
+//# run-graphql
+{ # A programmable transaction's inputs and commands, decoded
+  transaction(digest: "@{digest_1}") {
+    kind { __typename }
+    programmableTransaction {
+      inputs(first: 2) {
+        nodes {
+          __typename
+          ... on Pure { bytes }
+        }
+      }
+      commands {
+        nodes {
+          __typename
+          ... on SplitCoinsCommand {
+            coin { ...Arg }
+            amounts { ...Arg }
+          }
+          ... on TransferObjectsCommand {
+            inputs { ...Arg }
+            address { ...Arg }
+          }
+        }
+      }
+    }
+  }
+}
+
+fragment Arg on TransactionArgument {
+  __typename
+  ... on Input { ix }
+  ... on TxResult { cmd ix }
+}
+
+//# run-graphql
+{ # System transactions are not programmable
+  transaction(digest: "@{digest_2}") {
+    kind { __typename }
+    programmableTransaction { __typename }
+  }
+}
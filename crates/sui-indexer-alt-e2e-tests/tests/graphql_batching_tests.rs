@@ -0,0 +1,124 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::bail;
+use prometheus::Registry;
+use reqwest::Client;
+use serde_json::json;
+use simulacrum::Simulacrum;
+use sui_indexer_alt::config::IndexerConfig;
+use sui_indexer_alt_consistent_store::config::ServiceConfig as ConsistentConfig;
+use sui_indexer_alt_e2e_tests::FullCluster;
+use sui_indexer_alt_framework::IndexerArgs;
+use sui_indexer_alt_graphql::config::RpcConfig as GraphQlConfig;
+use sui_indexer_alt_jsonrpc::config::RpcConfig as JsonRpcConfig;
+use sui_types::base_types::SuiAddress;
+use tokio_util::sync::CancellationToken;
+
+/// Metric recording the size of each batch of keys that GraphQL's data loaders fetch from the
+/// database.
+const BATCH_SIZE_METRIC: &str = "graphql_db_batch_size";
+
+const TRANSACTIONS_QUERY: &str = r#"
+    query($first: Int) {
+        transactions(first: $first) {
+            nodes {
+                digest
+                effects {
+                    status
+                    events {
+                        nodes {
+                            sequenceNumber
+                            timestamp
+                        }
+                    }
+                }
+            }
+        }
+    }
+"#;
+
+/// A page of transactions, their effects and events, is fetched with the same number of database
+/// batches, regardless of how many transactions are on the page.
+#[tokio::test]
+async fn test_transaction_effects_events_batched() {
+    let registry = Registry::new();
+    let mut cluster = FullCluster::new_with_configs(
+        Simulacrum::new(),
+        IndexerArgs::default(),
+        IndexerArgs::default(),
+        IndexerConfig::for_test(),
+        ConsistentConfig::for_test(),
+        JsonRpcConfig::default(),
+        GraphQlConfig::default(),
+        &registry,
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    for _ in 0..60 {
+        cluster
+            .request_gas(SuiAddress::random_for_testing_only(), 1_000_000)
+            .unwrap();
+    }
+    cluster.create_checkpoint().await;
+
+    let mut batches = vec![];
+    for first in [1, 5, 50] {
+        let before = batch_count(&registry);
+        let nodes = transactions(&cluster, first).await.unwrap();
+        assert_eq!(nodes, first);
+        batches.push(batch_count(&registry) - before);
+    }
+
+    assert!(batches[0] > 0, "no batches recorded");
+    assert_eq!(batches[0], batches[1], "batches per page size: {batches:?}");
+    assert_eq!(batches[1], batches[2], "batches per page size: {batches:?}");
+}
+
+/// Fetch the first `first` transactions, with their effects and events, and return how many were
+/// returned.
+async fn transactions(cluster: &FullCluster, first: usize) -> anyhow::Result<usize> {
+    let response: serde_json::Value = Client::new()
+        .post(cluster.graphql_url().as_str())
+        .json(&json!({
+            "query": TRANSACTIONS_QUERY,
+            "variables": { "first": first },
+        }))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(errors) = response.get("errors") {
+        bail!("GraphQL errors: {errors}");
+    }
+
+    let Some(nodes) = response
+        .pointer("/data/transactions/nodes")
+        .and_then(|n| n.as_array())
+    else {
+        bail!("No transactions in response: {response}");
+    };
+
+    for node in nodes {
+        if node.pointer("/effects/events/nodes").is_none() {
+            bail!("No events in transaction: {node}");
+        }
+    }
+
+    Ok(nodes.len())
+}
+
+/// The total number of batches that data loaders have fetched from the database so far, across
+/// all loaders.
+fn batch_count(registry: &Registry) -> u64 {
+    registry
+        .gather()
+        .iter()
+        .filter(|family| family.get_name() == BATCH_SIZE_METRIC)
+        .flat_map(|family| family.get_metric())
+        .map(|metric| metric.get_histogram().get_sample_count())
+        .sum()
+}
@@ -704,6 +704,118 @@ async fn test_objects_by_address_owner() {
     assert!(page_info.has_next_page);
 }
 
+/// Type filters on an address's objects are applied by the index that the objects are paginated
+/// from, rather than to each page after it has been fetched, so pages are full even when most of
+/// the address's objects do not match the filter.
+#[tokio::test]
+async fn test_address_objects_by_type() {
+    let mut cluster = FullCluster::new().await.unwrap();
+    let (a, _) = get_account_key_pair();
+
+    // Checkpoint 1: Most of A's objects are not coins.
+    let c300 = create_coin(&mut cluster, a, 300);
+    let c200 = create_coin(&mut cluster, a, 200);
+    let c100 = create_coin(&mut cluster, a, 100);
+
+    let mut u8_tables = vec![];
+    for _ in 0..4 {
+        u8_tables.push(create_table(&mut cluster, a, TypeTag::U8, 1));
+        create_table(&mut cluster, a, TypeTag::U64, 1);
+        create_bag(&mut cluster, a, TypeTag::U8, 1);
+    }
+
+    cluster.create_checkpoint().await;
+
+    let query = format!(
+        r#"
+        query($filter: ObjectFilter, $first: Int, $last: Int, $after: String, $before: String) {{
+            address(address: "{a}") {{
+                objects(filter: $filter, first: $first, last: $last, after: $after, before: $before) {{
+                    pageInfo {{
+                        hasNextPage
+                        hasPreviousPage
+                        startCursor
+                        endCursor
+                    }}
+                    edges {{
+                        cursor
+                        node {{
+                            address
+                            version
+                            digest
+                        }}
+                    }}
+                }}
+            }}
+        }}
+        "#
+    );
+
+    // Test 1: The first page of coins is full, even though most of A's objects are not coins.
+    let (page, page_info) = objects_page(
+        &cluster,
+        &query,
+        json!({
+            "filter": { "type": "0x2::coin::Coin<0x2::sui::SUI>" },
+            "first": 2,
+            "after": null,
+            "last": null,
+            "before": null,
+        }),
+        |v| v.pointer("/data/address/objects"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        vec![c300, c200],
+        page.iter().map(|t| t.1).collect::<Vec<_>>()
+    );
+    assert!(page_info.has_next_page);
+    assert!(!page_info.has_previous_page);
+
+    // Test 2: Paginating through a type instantiation, in either direction, only visits the
+    // objects of that instantiation.
+    let expected = vec![c300, c200, c100];
+    for (forward, mut expected) in [(true, expected.clone()), (false, expected)] {
+        let results = objects(
+            &cluster,
+            &query,
+            json!({ "type": "0x2::coin::Coin<0x2::sui::SUI>" }),
+            2,
+            forward,
+            |v| v.pointer("/data/address/objects"),
+        )
+        .await
+        .unwrap();
+
+        if !forward {
+            expected.reverse();
+        }
+
+        assert_eq!(expected, results.iter().map(|t| t.1).collect::<Vec<_>>());
+    }
+
+    // Test 3: A type instantiation excludes other instantiations of the same type.
+    let mut results: Vec<_> = objects(
+        &cluster,
+        &query,
+        json!({ "type": "0x2::table::Table<u8, u8>" }),
+        3,
+        true,
+        |v| v.pointer("/data/address/objects"),
+    )
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|t| t.1)
+    .collect();
+
+    results.sort();
+    u8_tables.sort();
+    assert_eq!(u8_tables, results);
+}
+
 #[tokio::test]
 async fn test_objects_by_object_owner() {
     let mut cluster = FullCluster::new().await.unwrap();
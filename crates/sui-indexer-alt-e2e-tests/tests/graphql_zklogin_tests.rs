@@ -0,0 +1,221 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto_zkp::bn254::zk_login::{parse_jwks, OIDCProvider};
+use prometheus::Registry;
+use reqwest::Client;
+use serde_json::{json, Value};
+use shared_crypto::intent::{Intent, IntentMessage};
+use simulacrum::Simulacrum;
+use sui_indexer_alt::config::IndexerConfig;
+use sui_indexer_alt_consistent_store::config::ServiceConfig as ConsistentConfig;
+use sui_indexer_alt_e2e_tests::FullCluster;
+use sui_indexer_alt_framework::IndexerArgs;
+use sui_indexer_alt_graphql::config::RpcConfig as GraphQlConfig;
+use sui_indexer_alt_jsonrpc::config::RpcConfig as JsonRpcConfig;
+use sui_test_transaction_builder::TestTransactionBuilder;
+use sui_types::{
+    authenticator_state::ActiveJwk,
+    base_types::{random_object_ref, SuiAddress},
+    crypto::Signature,
+    signature::GenericSignature,
+    storage::ObjectStore,
+    transaction::{TransactionData, VerifiedTransaction},
+    utils::load_test_vectors,
+    zk_login_authenticator::ZkLoginAuthenticator,
+    zk_login_util::DEFAULT_JWK_BYTES,
+    SUI_AUTHENTICATOR_STATE_OBJECT_ID,
+};
+use tokio_util::sync::CancellationToken;
+
+/// zkLogin inputs, and the ephemeral keys that sign alongside them, generated for the test prover.
+/// They are issued by Twitch, and verify against the JWKs in `DEFAULT_JWK_BYTES`.
+const TEST_VECTORS: &str = "../sui-types/src/unit_tests/zklogin_test_vectors.json";
+
+/// The epoch that signatures in these tests are valid until.
+const MAX_EPOCH: u64 = 2;
+
+const VERIFY_QUERY: &str = r#"
+    query($bytes: Base64!, $signature: Base64!, $intentScope: ZkLoginIntentScope!, $author: SuiAddress!) {
+        verifyZkLoginSignature(
+            bytes: $bytes,
+            signature: $signature,
+            intentScope: $intentScope,
+            author: $author,
+        ) {
+            success
+            errors
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn test_verify_transaction_data() {
+    let cluster = cluster_with_jwks().await;
+    let (author, tx_bytes, signature) = signed_transaction_data(&cluster);
+
+    let result = verify(&cluster, &tx_bytes, &signature, "TRANSACTION_DATA", author).await;
+    assert_eq!(result, json!({ "success": true, "errors": [] }), "{result}");
+}
+
+/// Bytes signed as transaction data do not verify as a personal message.
+#[tokio::test]
+async fn test_verify_wrong_intent_scope() {
+    let cluster = cluster_with_jwks().await;
+    let (author, tx_bytes, signature) = signed_transaction_data(&cluster);
+
+    let result = verify(&cluster, &tx_bytes, &signature, "PERSONAL_MESSAGE", author).await;
+    assert_eq!(result["success"], json!(false), "{result}");
+    assert_eq!(result["errors"].as_array().unwrap().len(), 1, "{result}");
+}
+
+/// Signatures stop verifying once the epoch they are valid until has passed.
+#[tokio::test]
+async fn test_verify_expired_signature() {
+    let mut cluster = cluster_with_jwks().await;
+    let (author, tx_bytes, signature) = signed_transaction_data(&cluster);
+
+    for _ in 0..=MAX_EPOCH {
+        cluster.advance_epoch().await;
+    }
+
+    let result = verify(&cluster, &tx_bytes, &signature, "TRANSACTION_DATA", author).await;
+    assert_eq!(result["success"], json!(false), "{result}");
+
+    let error = result["errors"][0].as_str().unwrap();
+    assert!(error.contains("ZKLogin expired at epoch 2"), "{error}");
+}
+
+/// Signatures that cannot be parsed are rejected as bad input, rather than failing verification.
+#[tokio::test]
+async fn test_verify_malformed_signature() {
+    let cluster = cluster_with_jwks().await;
+    let (author, tx_bytes, signature) = signed_transaction_data(&cluster);
+
+    let response = query(
+        &cluster,
+        &tx_bytes,
+        &signature[..signature.len() / 2],
+        "TRANSACTION_DATA",
+        author,
+    )
+    .await;
+
+    let message = response["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("Invalid signature"), "{response}");
+}
+
+/// Set up a cluster whose authenticator state holds the JWKs that the test vectors verify against,
+/// as of its latest checkpoint.
+async fn cluster_with_jwks() -> FullCluster {
+    let executor = Simulacrum::new();
+    let authenticator_state =
+        ObjectStore::get_object(executor.store(), &SUI_AUTHENTICATOR_STATE_OBJECT_ID)
+            .expect("Authenticator state is created at genesis");
+    let initial_shared_version = authenticator_state
+        .owner
+        .start_version()
+        .expect("Authenticator state is shared");
+
+    let mut cluster = FullCluster::new_with_configs(
+        executor,
+        IndexerArgs::default(),
+        IndexerArgs::default(),
+        IndexerConfig::for_test(),
+        ConsistentConfig::for_test(),
+        JsonRpcConfig::default(),
+        GraphQlConfig::default(),
+        &Registry::new(),
+        CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    let new_active_jwks = parse_jwks(DEFAULT_JWK_BYTES, &OIDCProvider::Twitch, true)
+        .unwrap()
+        .into_iter()
+        .map(|(jwk_id, jwk)| ActiveJwk {
+            jwk_id,
+            jwk,
+            epoch: 0,
+        })
+        .collect();
+
+    let update = VerifiedTransaction::new_authenticator_state_update(
+        0,
+        0,
+        new_active_jwks,
+        initial_shared_version,
+    );
+
+    let (fx, error) = cluster.execute_transaction(update.into()).unwrap();
+    assert!(error.is_none(), "Failed to update JWKs: {fx:#?}");
+    cluster.create_checkpoint().await;
+    cluster
+}
+
+/// Sign a transfer as the zkLogin address from the test vectors. Returns the address, the BCS
+/// serialized transaction data, and its serialized zkLogin signature, valid until `MAX_EPOCH`.
+fn signed_transaction_data(cluster: &FullCluster) -> (SuiAddress, Vec<u8>, Vec<u8>) {
+    let (kp, pk, inputs) = &load_test_vectors(TEST_VECTORS)[1];
+    let author = SuiAddress::from(pk);
+
+    let tx_data: TransactionData =
+        TestTransactionBuilder::new(author, random_object_ref(), cluster.reference_gas_price())
+            .transfer_sui(None, SuiAddress::ZERO)
+            .build();
+
+    let message = IntentMessage::new(Intent::sui_transaction(), tx_data.clone());
+    let signature = GenericSignature::ZkLoginAuthenticator(ZkLoginAuthenticator::new(
+        inputs.clone(),
+        MAX_EPOCH,
+        Signature::new_secure(&message, kp),
+    ));
+
+    (
+        author,
+        bcs::to_bytes(&tx_data).unwrap(),
+        signature.as_ref().to_vec(),
+    )
+}
+
+/// Verify `signature` over `bytes` by `author`, returning the verification result.
+async fn verify(
+    cluster: &FullCluster,
+    bytes: &[u8],
+    signature: &[u8],
+    intent_scope: &str,
+    author: SuiAddress,
+) -> Value {
+    let response = query(cluster, bytes, signature, intent_scope, author).await;
+    assert!(response.get("errors").is_none(), "{response}");
+    response["data"]["verifyZkLoginSignature"].clone()
+}
+
+/// Run the verification query, returning the whole response.
+async fn query(
+    cluster: &FullCluster,
+    bytes: &[u8],
+    signature: &[u8],
+    intent_scope: &str,
+    author: SuiAddress,
+) -> Value {
+    Client::new()
+        .post(cluster.graphql_url().as_str())
+        .json(&json!({
+            "query": VERIFY_QUERY,
+            "variables": {
+                "bytes": Base64::encode(bytes),
+                "signature": Base64::encode(signature),
+                "intentScope": intent_scope,
+                "author": author.to_string(),
+            },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
@@ -121,7 +121,7 @@ impl IngestionService {
     }
 
     /// The client this service uses to fetch checkpoints.
-    pub(crate) fn client(&self) -> &IngestionClient {
+    pub fn client(&self) -> &IngestionClient {
         &self.client
     }
 
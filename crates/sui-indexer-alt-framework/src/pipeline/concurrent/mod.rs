@@ -100,9 +100,13 @@ pub struct PrunerConfig {
     /// this new watermark, in milliseconds.
     pub delay_ms: u64,
 
-    /// How much data to keep, this is measured in checkpoints.
+    /// How much data to keep, this is measured in checkpoints. Ignored if `retain_epochs` is set.
     pub retention: u64,
 
+    /// How much data to keep, measured in epochs: the pipeline's latest epoch, and the
+    /// `retain_epochs - 1` epochs before it. Takes precedence over `retention` if set.
+    pub retain_epochs: Option<u64>,
+
     /// The maximum range to try and prune in one request, measured in checkpoints.
     pub max_chunk_size: u64,
 
@@ -159,6 +163,7 @@ impl Default for PrunerConfig {
             interval_ms: 300_000,
             delay_ms: 120_000,
             retention: 4_000_000,
+            retain_epochs: None,
             max_chunk_size: 2_000,
             prune_concurrency: 1,
         }
@@ -278,7 +283,9 @@ pub(crate) fn pipeline<H: Handler + Send + Sync + 'static>(
     })
 }
 
-const fn max_chunk_rows<H: Handler>() -> usize {
+/// The most rows of `H` that can be written in a single statement, without exceeding Postgres'
+/// limit on the number of bind parameters.
+pub const fn max_chunk_rows<H: Handler>() -> usize {
     if H::Value::FIELD_COUNT == 0 {
         i16::MAX as usize
     } else {
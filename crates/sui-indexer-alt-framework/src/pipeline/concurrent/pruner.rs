@@ -524,6 +524,7 @@ mod tests {
             interval_ms: 10,
             delay_ms: 2000,
             retention: 1,
+            retain_epochs: None,
             max_chunk_size: 100,
             prune_concurrency: 1,
         };
@@ -620,6 +621,7 @@ mod tests {
             interval_ms: 10,
             delay_ms: 20_000,
             retention: 1,
+            retain_epochs: None,
             max_chunk_size: 100,
             prune_concurrency: 1,
         };
@@ -698,6 +700,7 @@ mod tests {
             interval_ms: 3_000, // Long interval to test retried attempts of failed range.
             delay_ms: 100,      // Short delay to speed up each interval
             retention: 1,
+            retain_epochs: None,
             max_chunk_size: 1, // Process one checkpoint at a time
             prune_concurrency: 1,
         };
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use tokio::{task::JoinHandle, time::interval};
 use tokio_util::sync::CancellationToken;
@@ -9,7 +9,7 @@ use tracing::{debug, info, warn};
 
 use crate::{
     metrics::IndexerMetrics,
-    store::{Connection, Store},
+    store::{CommitterWatermark, Connection, Store},
 };
 
 use super::{Handler, PrunerConfig};
@@ -23,6 +23,10 @@ use super::{Handler, PrunerConfig};
 /// last updated that watermark. The timestamp is always fetched from the database (not from the
 /// indexer or the reader), to avoid issues with drift between clocks.
 ///
+/// With a `retain_epochs` policy, `reader_lo` trails the start of the oldest epoch to retain, which
+/// the task learns by watching the pipeline's committer watermark cross epoch boundaries (see
+/// [EpochBoundaries]).
+///
 /// If there is no pruner configuration, this task will immediately exit. Otherwise, the task exits
 /// when the provided cancellation token is triggered.
 pub(super) fn reader_watermark<H: Handler + 'static>(
@@ -38,6 +42,7 @@ pub(super) fn reader_watermark<H: Handler + 'static>(
         };
 
         let mut poll = interval(config.interval());
+        let mut epochs = EpochBoundaries::default();
 
         loop {
             tokio::select! {
@@ -66,9 +71,37 @@ pub(super) fn reader_watermark<H: Handler + 'static>(
                         }
                     };
 
-                    // Calculate the new reader watermark based on the current high watermark.
-                    let new_reader_lo = (current.checkpoint_hi_inclusive as u64 + 1)
-                        .saturating_sub(config.retention);
+                    // Calculate the new reader watermark based on the current high watermark, or
+                    // the epochs the pipeline has been through.
+                    let new_reader_lo = if let Some(retain_epochs) = config.retain_epochs {
+                        match conn.committer_watermark(H::NAME).await {
+                            Ok(Some(watermark)) => epochs.observe(watermark),
+
+                            Ok(None) => {
+                                warn!(pipeline = H::NAME, "No watermark for pipeline, skipping");
+                                continue;
+                            }
+
+                            Err(e) => {
+                                warn!(pipeline = H::NAME, "Failed to get committer watermark: {e}");
+                                continue;
+                            }
+                        }
+
+                        let Some(reader_lo) = epochs.reader_lo(retain_epochs) else {
+                            debug!(
+                                pipeline = H::NAME,
+                                retain_epochs,
+                                "Start of oldest retained epoch not observed yet",
+                            );
+                            continue;
+                        };
+
+                        reader_lo
+                    } else {
+                        (current.checkpoint_hi_inclusive as u64 + 1)
+                            .saturating_sub(config.retention)
+                    };
 
                     if new_reader_lo <= current.reader_lo as u64 {
                         debug!(
@@ -106,6 +139,59 @@ pub(super) fn reader_watermark<H: Handler + 'static>(
     })
 }
 
+/// The checkpoints at which a pipeline's committer watermark entered each epoch, as observed by
+/// the reader watermark task.
+///
+/// The task only sees the watermark when it polls, so each boundary is recorded as the checkpoint
+/// after the last one it saw in the previous epoch. This is a lower bound on the epoch's actual
+/// first checkpoint, which errs on the side of retaining more data. Boundaries are not persisted,
+/// so after a restart, a pipeline only resumes pruning once it has seen the start of its oldest
+/// retained epoch again.
+#[derive(Default)]
+struct EpochBoundaries {
+    /// The last watermark observed.
+    last: Option<CommitterWatermark>,
+
+    /// Lower bound on the first checkpoint of each epoch whose start was observed.
+    starts: BTreeMap<u64, u64>,
+}
+
+impl EpochBoundaries {
+    fn observe(&mut self, watermark: CommitterWatermark) {
+        if let Some(last) = self.last {
+            if watermark.epoch_hi_inclusive > last.epoch_hi_inclusive {
+                self.starts.insert(
+                    watermark.epoch_hi_inclusive,
+                    last.checkpoint_hi_inclusive + 1,
+                );
+            }
+        }
+
+        self.last = Some(watermark);
+    }
+
+    /// The lowest checkpoint to keep, to retain the latest `retain_epochs` epochs, or `None` if
+    /// the start of the oldest of those epochs (or an earlier one) has not been observed yet.
+    fn reader_lo(&self, retain_epochs: u64) -> Option<u64> {
+        let last = self.last?;
+        let Some(oldest) = (last.epoch_hi_inclusive + 1).checked_sub(retain_epochs) else {
+            // There aren't enough epochs yet to prune any of them.
+            return Some(0);
+        };
+
+        if oldest == 0 {
+            return Some(0);
+        }
+
+        // If the oldest epoch's start was missed (because the watermark moved through more than
+        // one epoch between polls), fall back to the start of an earlier epoch.
+        self.starts
+            .range(..=oldest)
+            .next_back()
+            .map(|(_, checkpoint)| *checkpoint)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
@@ -177,6 +263,7 @@ mod tests {
             interval_ms,
             delay_ms: 100,
             retention: TEST_RETENTION,
+            retain_epochs: None,
             max_chunk_size: 100,
             prune_concurrency: 1,
         };
@@ -375,4 +462,54 @@ mod tests {
         setup.cancel.cancel();
         let _ = setup.handle.await;
     }
+
+    #[test]
+    fn test_epoch_boundaries() {
+        let watermark = |epoch, checkpoint| CommitterWatermark {
+            epoch_hi_inclusive: epoch,
+            checkpoint_hi_inclusive: checkpoint,
+            ..Default::default()
+        };
+
+        let mut epochs = EpochBoundaries::default();
+        assert_eq!(epochs.reader_lo(2), None);
+
+        // Not enough epochs to prune anything yet.
+        epochs.observe(watermark(0, 5));
+        assert_eq!(epochs.reader_lo(2), Some(0));
+
+        // The start of epoch 1 is recorded as the checkpoint after the last one seen in epoch 0.
+        epochs.observe(watermark(1, 12));
+        epochs.observe(watermark(1, 20));
+        assert_eq!(epochs.reader_lo(2), Some(0));
+        assert_eq!(epochs.reader_lo(1), Some(6));
+
+        // Skipping over the start of epoch 3 falls back to the start of epoch 2.
+        epochs.observe(watermark(2, 25));
+        epochs.observe(watermark(4, 40));
+        assert_eq!(epochs.reader_lo(2), Some(21));
+        assert_eq!(epochs.reader_lo(3), Some(21));
+        assert_eq!(epochs.reader_lo(4), Some(6));
+    }
+
+    #[test]
+    fn test_epoch_boundaries_after_restart() {
+        // The pipeline starts observing mid-way through epoch 3, so it can't prune by epoch until
+        // it has seen the start of its oldest retained epoch.
+        let mut epochs = EpochBoundaries::default();
+        epochs.observe(CommitterWatermark {
+            epoch_hi_inclusive: 3,
+            checkpoint_hi_inclusive: 100,
+            ..Default::default()
+        });
+        assert_eq!(epochs.reader_lo(1), None);
+
+        epochs.observe(CommitterWatermark {
+            epoch_hi_inclusive: 4,
+            checkpoint_hi_inclusive: 150,
+            ..Default::default()
+        });
+        assert_eq!(epochs.reader_lo(1), Some(101));
+        assert_eq!(epochs.reader_lo(2), None);
+    }
 }
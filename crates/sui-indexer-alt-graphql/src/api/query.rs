@@ -7,24 +7,37 @@ use sui_types::digests::ChainIdentifier;
 
 use crate::{
     error::RpcError,
+    extensions::proofs::ProofTargets,
     pagination::{Page, PaginationConfig},
     scope::Scope,
 };
 
 use super::{
-    scalars::{digest::Digest, sui_address::SuiAddress, type_filter::TypeInput, uint53::UInt53},
+    scalars::{
+        base64::Base64, big_int::BigInt, date_time::DateTime, digest::Digest,
+        sui_address::SuiAddress, type_filter::TypeInput, uint53::UInt53,
+    },
     types::{
         address::Address,
-        checkpoint::{filter::CheckpointFilter, CCheckpoint, Checkpoint},
+        available_range::AvailableRangeFields,
+        checkpoint::{self, filter::CheckpointFilter, CCheckpoint, Checkpoint},
+        coin_metadata::{self, CoinMetadata},
         epoch::Epoch,
+        kiosk::Kiosk,
         move_package::{self, MovePackage, PackageCheckpointFilter, PackageKey},
         move_type::{self, MoveType},
         object::{self, Object, ObjectKey, VersionFilter},
         object_filter::{ObjectFilter, Validator as OFValidator},
         protocol_configs::ProtocolConfigs,
         service_config::ServiceConfig,
-        transaction::{filter::TransactionFilter, CTransaction, Transaction},
+        service_status::ServiceStatus,
+        transaction::{
+            self,
+            filter::{TransactionFilter, Validator as TFValidator},
+            CTransaction, Transaction,
+        },
         transaction_effects::TransactionEffects,
+        zklogin::{self, ZkLoginIntentScope, ZkLoginVerifyResult},
     },
 };
 
@@ -65,6 +78,18 @@ impl Query {
         Ok(Checkpoint::with_sequence_number(scope, sequence_number))
     }
 
+    /// Fetch the latest checkpoint whose timestamp is at or before the given timestamp.
+    ///
+    /// Returns `null` if every checkpoint that the store has data for is after the timestamp.
+    async fn checkpoint_at_timestamp(
+        &self,
+        ctx: &Context<'_>,
+        timestamp: DateTime,
+    ) -> Result<Option<Checkpoint>, RpcError> {
+        let scope = self.scope(ctx)?;
+        Checkpoint::at_timestamp(ctx, scope, timestamp).await
+    }
+
     /// Paginate checkpoints in the network, optionally bounded to checkpoints in the given epoch.
     async fn checkpoints(
         &self,
@@ -74,7 +99,8 @@ impl Query {
         last: Option<u64>,
         before: Option<CCheckpoint>,
         filter: Option<CheckpointFilter>,
-    ) -> Result<Connection<String, Checkpoint>, RpcError> {
+    ) -> Result<Connection<String, Checkpoint, AvailableRangeFields>, RpcError<checkpoint::Error>>
+    {
         let scope = self.scope(ctx)?;
         let pagination: &PaginationConfig = ctx.data()?;
         let limits = pagination.limits("Query", "checkpoints");
@@ -85,6 +111,17 @@ impl Query {
         Checkpoint::paginate(ctx, scope, page, filter).await
     }
 
+    /// Fetch the metadata for a coin type (e.g. `0x2::sui::SUI`), from its `0x2::coin::CoinMetadata` object.
+    ///
+    /// Returns `null` if no metadata could be found for the coin type.
+    async fn coin_metadata(
+        &self,
+        ctx: &Context<'_>,
+        coin_type: TypeInput,
+    ) -> Result<Option<CoinMetadata>, RpcError<coin_metadata::Error>> {
+        CoinMetadata::by_coin_type(ctx, self.scope(ctx)?, coin_type.into()).await
+    }
+
     /// Fetch an epoch by its ID, or fetch the latest epoch if no ID is provided.
     ///
     /// Returns `null` if the epoch does not exist yet, or was pruned.
@@ -97,6 +134,17 @@ impl Query {
         Epoch::fetch(ctx, scope, epoch_id).await
     }
 
+    /// Fetch a kiosk (a `0x2::kiosk::Kiosk` object) by its address, along with its items, their listings, and the transfer policies for their types, as of the latest checkpoint.
+    ///
+    /// Returns `null` if there is no object at this address, or it is not a kiosk.
+    async fn kiosk(
+        &self,
+        ctx: &Context<'_>,
+        address: SuiAddress,
+    ) -> Result<Option<Kiosk>, RpcError<object::Error>> {
+        Kiosk::fetch(ctx, self.scope(ctx)?, address).await
+    }
+
     /// Fetch checkpoints by their sequence numbers.
     ///
     /// Returns a list of checkpoints that is guaranteed to be the same length as `keys`. If a checkpoint in `keys` could not be found in the store, its corresponding entry in the result will be `null`. This could be because the checkpoint does not exist yet, or because it was pruned.
@@ -136,10 +184,14 @@ impl Query {
         ctx: &Context<'_>,
         keys: Vec<ObjectKey>,
     ) -> Result<Vec<Option<Object>>, RpcError<object::Error>> {
-        let scope = self.scope(ctx)?;
-        let objects = keys
-            .into_iter()
-            .map(|k| Object::by_key(ctx, scope.clone(), k));
+        let scope = &self.scope(ctx)?;
+        let objects = keys.into_iter().map(|k| async move {
+            let object = Object::by_key(ctx, scope.clone(), k).await?;
+            if let Some(object) = &object {
+                ProofTargets::add_object(ctx, object).await?;
+            }
+            Ok::<_, RpcError<object::Error>>(object)
+        });
 
         try_join_all(objects).await
     }
@@ -168,10 +220,14 @@ impl Query {
         ctx: &Context<'_>,
         keys: Vec<Digest>,
     ) -> Result<Vec<Option<Transaction>>, RpcError> {
-        let scope = self.scope(ctx)?;
-        let transactions = keys
-            .into_iter()
-            .map(|d| Transaction::fetch(ctx, scope.clone(), d));
+        let scope = &self.scope(ctx)?;
+        let transactions = keys.into_iter().map(|d| async move {
+            let transaction = Transaction::fetch(ctx, scope.clone(), d).await?;
+            if let Some(transaction) = &transaction {
+                ProofTargets::add_transaction(ctx, transaction);
+            }
+            Ok::<_, RpcError>(transaction)
+        });
 
         try_join_all(transactions).await
     }
@@ -234,7 +290,7 @@ impl Query {
         root_version: Option<UInt53>,
         at_checkpoint: Option<UInt53>,
     ) -> Result<Option<Object>, RpcError<object::Error>> {
-        Object::by_key(
+        let object = Object::by_key(
             ctx,
             self.scope(ctx)?,
             ObjectKey {
@@ -244,7 +300,13 @@ impl Query {
                 at_checkpoint,
             },
         )
-        .await
+        .await?;
+
+        if let Some(object) = &object {
+            ProofTargets::add_object(ctx, object).await?;
+        }
+
+        Ok(object)
     }
 
     /// Paginate objects in the live object set, optionally filtered by owner and/or type. `filter` can be one of:
@@ -400,6 +462,22 @@ impl Query {
         ServiceConfig
     }
 
+    /// The health of the indexer backing this RPC service, including any backfills it is running to recover stalled pipelines.
+    async fn service_status(&self) -> ServiceStatus {
+        ServiceStatus
+    }
+
+    /// The total supply of a coin type (e.g. `0x2::sui::SUI`), from its `0x2::coin::TreasuryCap` object.
+    ///
+    /// Returns `null` if the coin's treasury cap could not be found in the live object set (e.g. because it has been wrapped).
+    async fn total_supply(
+        &self,
+        ctx: &Context<'_>,
+        coin_type: TypeInput,
+    ) -> Result<Option<BigInt>, RpcError<coin_metadata::Error>> {
+        coin_metadata::total_supply(ctx, self.scope(ctx)?, coin_type.into()).await
+    }
+
     /// Fetch a transaction by its digest.
     ///
     /// Returns `null` if the transaction does not exist in the store, either because it never existed or because it was pruned.
@@ -408,7 +486,12 @@ impl Query {
         ctx: &Context<'_>,
         digest: Digest,
     ) -> Result<Option<Transaction>, RpcError> {
-        Transaction::fetch(ctx, self.scope(ctx)?, digest).await
+        let transaction = Transaction::fetch(ctx, self.scope(ctx)?, digest).await?;
+        if let Some(transaction) = &transaction {
+            ProofTargets::add_transaction(ctx, transaction);
+        }
+
+        Ok(transaction)
     }
 
     /// Fetch transaction effects by its transaction's digest.
@@ -422,7 +505,30 @@ impl Query {
         TransactionEffects::fetch(ctx, self.scope(ctx)?, digest).await
     }
 
+    /// Search for transactions whose digests start with `prefix`, in order of their digests.
+    ///
+    /// `prefix` is the start of a Base58-encoded transaction digest, and must be at least `ServiceConfig.minDigestPrefixLength` characters long. Transactions that have been pruned from the store are not found.
+    async fn transaction_search(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u64>,
+        after: Option<transaction::CDigest>,
+        last: Option<u64>,
+        before: Option<transaction::CDigest>,
+        prefix: String,
+    ) -> Result<Connection<String, Transaction, AvailableRangeFields>, RpcError<transaction::Error>>
+    {
+        let scope = self.scope(ctx)?;
+        let pagination: &PaginationConfig = ctx.data()?;
+        let limits = pagination.limits("Query", "transactionSearch");
+        let page = Page::from_params(limits, first, after, last, before)?;
+
+        Transaction::search_by_digest_prefix(ctx, scope, page, &prefix).await
+    }
+
     /// The transactions that exist in the network, optionally filtered by transaction filters.
+    ///
+    /// Besides the cursors returned in its results, `after` and `before` accept cursors that point at the start of a checkpoint, to page through transactions starting from, or ending before, that checkpoint. These cursors are the Base64 encoding of `{"checkpoint": <sequence number>}`.
     async fn transactions(
         &self,
         ctx: &Context<'_>,
@@ -430,8 +536,8 @@ impl Query {
         after: Option<CTransaction>,
         last: Option<u64>,
         before: Option<CTransaction>,
-        filter: Option<TransactionFilter>,
-    ) -> Result<Connection<String, Transaction>, RpcError> {
+        #[graphql(validator(custom = "TFValidator::default()"))] filter: Option<TransactionFilter>,
+    ) -> Result<Connection<String, Transaction, AvailableRangeFields>, RpcError> {
         let scope = self.scope(ctx)?;
         let pagination: &PaginationConfig = ctx.data()?;
         let limits = pagination.limits("Query", "transactions");
@@ -455,6 +561,32 @@ impl Query {
     ) -> Result<Option<MoveType>, RpcError<move_type::Error>> {
         MoveType::canonicalize(type_.into(), self.scope(ctx)?).await
     }
+
+    /// Verify a zkLogin signature based on the provided transaction or personal message, against the JWKs that were active on-chain as of the latest checkpoint.
+    ///
+    /// - `bytes` are either the bytes of a serialized `TransactionData` or a personal message, depending on `intentScope`.
+    /// - `signature` is a serialized zkLogin signature, which is Base64-encoded and starts with its signature scheme flag.
+    /// - `author` is the address of the signer.
+    ///
+    /// Signatures that fail verification produce an unsuccessful result, while inputs that cannot be verified (e.g. because they are not zkLogin signatures) produce an error.
+    async fn verify_zk_login_signature(
+        &self,
+        ctx: &Context<'_>,
+        bytes: Base64,
+        signature: Base64,
+        intent_scope: ZkLoginIntentScope,
+        author: SuiAddress,
+    ) -> Result<ZkLoginVerifyResult, RpcError<zklogin::Error>> {
+        ZkLoginVerifyResult::verify(
+            ctx,
+            &self.scope(ctx)?,
+            bytes.0,
+            signature.0,
+            intent_scope,
+            author.into(),
+        )
+        .await
+    }
 }
 
 impl Query {
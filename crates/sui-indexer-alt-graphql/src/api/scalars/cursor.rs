@@ -4,11 +4,13 @@
 use std::ops::Deref;
 
 use async_graphql::{
-    connection::CursorType, InputValueError, InputValueResult, Scalar, ScalarType, Value,
+    connection::CursorType, InputType, InputValueError, InputValueResult, Scalar, ScalarType, Value,
 };
 use fastcrypto::encoding::{Base64, Encoding};
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::error::{ErrorCode, ERROR_CODE};
+
 /// Cursor that hides its value by encoding it as JSON and then Base64.
 ///
 /// In the GraphQL schema this will show up as a `String`.
@@ -33,6 +35,12 @@ pub(crate) enum Error {
     BadJson,
 }
 
+/// Errors decoding a cursor are reported while parsing the request's inputs, so they carry their
+/// [ErrorCode] on the input error.
+fn bad_cursor<T: InputType>(err: Error) -> InputValueError<T> {
+    InputValueError::custom(err).with_extension(ERROR_CODE, ErrorCode::BadCursor.as_str())
+}
+
 impl<C> JsonCursor<C> {
     pub(crate) fn new(cursor: C) -> Self {
         Self(cursor)
@@ -53,7 +61,7 @@ where
 {
     fn parse(value: Value) -> InputValueResult<Self> {
         if let Value::String(s) = value {
-            Self::decode_cursor(&s).map_err(InputValueError::custom)
+            Self::decode_cursor(&s).map_err(bad_cursor)
         } else {
             Err(InputValueError::expected_type(value))
         }
@@ -77,7 +85,7 @@ where
 {
     fn parse(value: Value) -> InputValueResult<Self> {
         if let Value::String(s) = value {
-            Self::decode_cursor(&s).map_err(InputValueError::custom)
+            Self::decode_cursor(&s).map_err(bad_cursor)
         } else {
             Err(InputValueError::expected_type(value))
         }
@@ -9,7 +9,7 @@ use chrono::TimeZone;
 
 use crate::error::RpcError;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub(crate) struct DateTime(chrono::DateTime<chrono::Utc>);
 
 impl DateTime {
@@ -22,6 +22,11 @@ impl DateTime {
                 .context("Cannot convert timestamp into DateTime")?,
         ))
     }
+
+    /// The timestamp since the unix epoch in milliseconds.
+    pub(crate) fn timestamp_ms(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
 }
 
 /// ISO-8601 Date and Time: RFC3339 in UTC with format: YYYY-MM-DDTHH:MM:SS.mmmZ. Note that the milliseconds part is optional, and it may be omitted if its value is 0.
@@ -20,6 +20,24 @@ pub(crate) enum OwnerKind {
 
     /// Object is frozen.
     Immutable,
+
+    /// Object is owned by an address, and accessed through consensus (e.g. a party object).
+    ConsensusAddress,
+}
+
+impl OwnerKind {
+    /// Owner kinds intersect if they are the same, or if one is `ADDRESS` and the other is
+    /// `CONSENSUS_ADDRESS`, because objects owned by an address through consensus are also owned
+    /// by that address.
+    pub(crate) fn intersect(self, other: Self) -> Option<Self> {
+        use OwnerKind as K;
+        match (self, other) {
+            (K::Address, K::ConsensusAddress) | (K::ConsensusAddress, K::Address) => {
+                Some(K::ConsensusAddress)
+            }
+            (a, b) => (a == b).then_some(a),
+        }
+    }
 }
 
 impl From<OwnerKind> for proto::owner::OwnerKind {
@@ -29,6 +47,7 @@ impl From<OwnerKind> for proto::owner::OwnerKind {
             OwnerKind::Object => proto::owner::OwnerKind::Object,
             OwnerKind::Shared => proto::owner::OwnerKind::Shared,
             OwnerKind::Immutable => proto::owner::OwnerKind::Immutable,
+            OwnerKind::ConsensusAddress => proto::owner::OwnerKind::ConsensusAddress,
         }
     }
 }
@@ -40,6 +59,7 @@ impl fmt::Display for OwnerKind {
             OwnerKind::Object => write!(f, "'OBJECT'"),
             OwnerKind::Shared => write!(f, "'SHARED'"),
             OwnerKind::Immutable => write!(f, "'IMMUTABLE'"),
+            OwnerKind::ConsensusAddress => write!(f, "'CONSENSUS_ADDRESS'"),
         }
     }
 }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use async_graphql::*;
-use move_core_types::language_storage::StructTag;
+use move_core_types::{identifier::Identifier, language_storage::StructTag};
 use std::{fmt, str::FromStr};
 use sui_types::{
     parse_sui_address, parse_sui_module_id, parse_sui_struct_tag, parse_sui_type_tag, TypeTag,
@@ -30,6 +30,22 @@ pub(crate) enum TypeFilter {
     Type(StructTag),
 }
 
+/// GraphQL scalar containing a filter on fully-qualified names (e.g. of functions). The filter can
+/// be one of:
+///
+/// - A package address: `0x2`,
+/// - A module: `0x2::coin`,
+/// - A fully-qualified name: `0x2::coin::take`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum FqNameFilter {
+    /// Filter by package address
+    Package(SuiAddress),
+    /// Filter by module (package and module name)
+    Module(SuiAddress, String),
+    /// Filter by fully-qualified name (package, module, and name)
+    FqName(SuiAddress, String, String),
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("Invalid type, expected: package::module::type[<type_params, ...>] or primitive type")]
 pub(crate) struct TypeInputError;
@@ -38,6 +54,10 @@ pub(crate) struct TypeInputError;
 #[error("Invalid filter format, expected: package[::module[::type[<type_params, ...>]]]")]
 pub(crate) struct TypeFilterError;
 
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid filter format, expected: package[::module[::name]]")]
+pub(crate) struct FqNameFilterError;
+
 impl TypeFilter {
     /// Try to create a filter whose results are the intersection of `self`'s results and `other`'s
     /// results. May return `None` if the filters are incompatible (would result in no matches)
@@ -82,6 +102,7 @@ impl TypeFilter {
 
 impl_string_input!(TypeInput);
 impl_string_input!(TypeFilter);
+impl_string_input!(FqNameFilter);
 
 impl From<TypeInput> for TypeTag {
     fn from(input: TypeInput) -> Self {
@@ -123,6 +144,39 @@ impl FromStr for TypeFilter {
     }
 }
 
+impl FromStr for FqNameFilter {
+    type Err = FqNameFilterError;
+
+    fn from_str(s: &str) -> Result<Self, FqNameFilterError> {
+        let mut parts = s.split("::");
+        let package = parts.next().ok_or(FqNameFilterError)?;
+        let package = parse_sui_address(package)
+            .map_err(|_| FqNameFilterError)?
+            .into();
+
+        let identifier = |part: Option<&str>| -> Result<Option<String>, FqNameFilterError> {
+            part.map(|i| {
+                Identifier::is_valid(i)
+                    .then(|| i.to_owned())
+                    .ok_or(FqNameFilterError)
+            })
+            .transpose()
+        };
+
+        let module = identifier(parts.next())?;
+        let name = identifier(parts.next())?;
+        if parts.next().is_some() {
+            return Err(FqNameFilterError);
+        }
+
+        Ok(match (module, name) {
+            (None, _) => FqNameFilter::Package(package),
+            (Some(module), None) => FqNameFilter::Module(package, module),
+            (Some(module), Some(name)) => FqNameFilter::FqName(package, module, name),
+        })
+    }
+}
+
 impl fmt::Display for TypeInput {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0.to_canonical_display(/* with_prefix */ true))
@@ -139,6 +193,16 @@ impl fmt::Display for TypeFilter {
     }
 }
 
+impl fmt::Display for FqNameFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FqNameFilter::Package(p) => write!(f, "{p}"),
+            FqNameFilter::Module(p, m) => write!(f, "{p}::{m}"),
+            FqNameFilter::FqName(p, m, n) => write!(f, "{p}::{m}::{n}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use insta::assert_snapshot;
@@ -241,4 +305,29 @@ mod tests {
         let other_pkg = TypeFilter::from_str("0x3").unwrap();
         assert!(pkg.intersect(other_pkg).is_none());
     }
+
+    #[test]
+    fn test_parse_fq_name_filter() {
+        let filters: Vec<_> = ["0x2", "0x2::coin", "0x2::coin::take"]
+            .into_iter()
+            .map(|i| FqNameFilter::from_str(i).unwrap().to_string())
+            .collect();
+
+        assert_snapshot!(&filters.join("\n"), @r###"
+        0x0000000000000000000000000000000000000000000000000000000000000002
+        0x0000000000000000000000000000000000000000000000000000000000000002::coin
+        0x0000000000000000000000000000000000000000000000000000000000000002::coin::take
+        "###);
+
+        for invalid in [
+            "not_an_address",
+            "0x2::",
+            "::coin",
+            "0x2::coin::",
+            "0x2::coin::take::extra",
+            "0x2::coin::Coin<0x2::sui::SUI>",
+        ] {
+            assert!(FqNameFilter::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
 }
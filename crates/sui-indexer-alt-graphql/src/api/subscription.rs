@@ -0,0 +1,106 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_graphql::{
+    connection::{CursorType, Edge, EmptyFields},
+    Context, Subscription as GqlSubscription,
+};
+use futures::Stream;
+
+use crate::{
+    error::{feature_unavailable, out_of_retention, RpcError},
+    pagination::PaginationConfig,
+    scope::Scope,
+    task::watermark::WatermarkUpdates,
+};
+
+use super::{
+    scalars::cursor::JsonCursor,
+    types::event::{filter::EventFilter, CEvent, Event},
+};
+
+#[derive(Default)]
+pub struct Subscription;
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error(
+        "Events from transaction {tx} onwards have been pruned, the earliest available transaction \
+         is {lo}"
+    )]
+    Pruned { tx: u64, lo: u64 },
+}
+
+#[GqlSubscription]
+impl Subscription {
+    /// Stream events that match the optional `filter`, as they are indexed.
+    ///
+    /// If `afterCursor` is provided, events after that cursor that have already been indexed are
+    /// streamed first, before switching to new events. Clients that lose their connection can
+    /// resubscribe with the cursor of the last event they received, to pick up where they left
+    /// off without missing any events. Without a cursor, only events indexed after the
+    /// subscription started are streamed.
+    ///
+    /// The stream fails if it falls behind the events that are still available (because they
+    /// were pruned).
+    async fn events<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        filter: Option<EventFilter>,
+        after_cursor: Option<CEvent>,
+    ) -> Result<
+        impl Stream<Item = Result<Edge<String, Event, EmptyFields>, RpcError<Error>>> + 'ctx,
+        RpcError<Error>,
+    > {
+        let updates: &WatermarkUpdates = ctx.data()?;
+        let pagination: &PaginationConfig = ctx.data()?;
+        let limit = pagination.limits("Subscription", "events").max as usize;
+        let filter = filter.unwrap_or_default();
+
+        let mut watermarks = updates.latest().await;
+        if watermarks.pipeline_lo_watermark("ev_struct_inst").is_err() {
+            return Err(feature_unavailable("event subscriptions"));
+        }
+
+        // Scanning resumes from the transaction that the cursor points into, skipping the events
+        // in it up to and including the cursor.
+        let mut after = after_cursor.map(|c| *c);
+        let mut tx_lo = match after {
+            Some(cursor) => cursor.tx_sequence_number,
+            None => watermarks.high_watermark().transaction(),
+        };
+
+        Ok(async_stream::try_stream! {
+            loop {
+                let reader_lo = watermarks.pipeline_lo_watermark("ev_struct_inst")?.transaction();
+                if tx_lo < reader_lo {
+                    Err(out_of_retention(Error::Pruned {
+                        tx: tx_lo,
+                        lo: reader_lo,
+                    }))?;
+                }
+
+                // Wait for more transactions to be indexed, once all the indexed transactions
+                // have been scanned.
+                let tx_hi = watermarks.high_watermark().transaction();
+                if tx_lo >= tx_hi {
+                    watermarks = updates
+                        .wait_for(|w| w.high_watermark().transaction() > tx_lo)
+                        .await;
+                    continue;
+                }
+
+                let scope = Scope::with_watermarks(ctx, &watermarks)?;
+                let (events, scanned_hi) =
+                    Event::scan(ctx, &scope, &filter, after, tx_lo..tx_hi, limit).await?;
+
+                for (cursor, event) in events {
+                    after = Some(cursor);
+                    yield Edge::new(JsonCursor::new(cursor).encode_cursor(), event);
+                }
+
+                tx_lo = scanned_hi;
+            }
+        })
+    }
+}
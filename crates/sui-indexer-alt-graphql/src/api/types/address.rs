@@ -15,6 +15,7 @@ use crate::{
 };
 
 use super::{
+    address_activity::AddressActivity,
     move_object::MoveObject,
     move_package::MovePackage,
     object::{self, Object},
@@ -57,6 +58,16 @@ pub(crate) struct AddressableImpl<'a>(&'a Address);
 
 #[Object]
 impl Address {
+    /// A summary of the transactions this address sent or was affected by, covering the checkpoints that the service has data for.
+    pub(crate) async fn activity(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<AddressActivity>, RpcError> {
+        Ok(Some(
+            AddressActivity::fetch(ctx, &self.scope, self.address).await?,
+        ))
+    }
+
     /// The Address' identifier, a 32-byte number represented as a 64-character hex string, with a lead "0x".
     pub(crate) async fn address(&self) -> SuiAddress {
         AddressableImpl::from(self).address()
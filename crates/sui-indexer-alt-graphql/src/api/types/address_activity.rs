@@ -0,0 +1,180 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use async_graphql::{Context, SimpleObject};
+use diesel::{
+    prelude::QueryableByName,
+    sql_types::{BigInt, Bytea, Nullable},
+};
+use futures::try_join;
+use sui_indexer_alt_reader::{kv_loader::KvLoader, pg_reader::PgReader};
+use sui_sql_macro::query;
+use sui_types::{base_types::SuiAddress, digests::TransactionDigest};
+
+use crate::{
+    api::scalars::uint53::UInt53, error::RpcError, scope::Scope, task::watermark::Watermarks,
+};
+
+use super::transaction::filter::tx_bounds;
+
+/// The pipelines that the summary is computed from. It only covers the checkpoints that all of
+/// them still have data for.
+const PIPELINES: [&str; 3] = ["tx_affected_addresses", "tx_affected_objects", "tx_digests"];
+
+/// A summary of the transactions that an address was involved in, covering the checkpoints that the service has data for.
+#[derive(SimpleObject, Clone, Debug, Default)]
+pub(crate) struct AddressActivity {
+    /// The checkpoint of the earliest transaction that the address sent, or was affected by.
+    first_tx_checkpoint: Option<UInt53>,
+
+    /// The checkpoint of the latest transaction that the address sent, or was affected by.
+    last_tx_checkpoint: Option<UInt53>,
+
+    /// The number of transactions that the address sent, or was affected by.
+    tx_count: UInt53,
+
+    /// The number of packages published by transactions that the address sent, including upgrades.
+    packages_published: UInt53,
+}
+
+#[derive(QueryableByName)]
+struct StoredActivity {
+    #[diesel(sql_type = BigInt)]
+    tx_count: i64,
+    #[diesel(sql_type = Nullable<Bytea>)]
+    first_tx_digest: Option<Vec<u8>>,
+    #[diesel(sql_type = Nullable<Bytea>)]
+    last_tx_digest: Option<Vec<u8>>,
+    #[diesel(sql_type = BigInt)]
+    packages_published: i64,
+}
+
+impl AddressActivity {
+    /// Summarize the activity of `address`, up to the checkpoint viewed at by `scope`.
+    pub(crate) async fn fetch(
+        ctx: &Context<'_>,
+        scope: &Scope,
+        address: SuiAddress,
+    ) -> Result<Self, RpcError> {
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+
+        let mut reader_lo = 0;
+        for pipeline in PIPELINES {
+            let lo = watermarks.pipeline_lo_watermark(pipeline)?.checkpoint();
+            reader_lo = reader_lo.max(lo);
+        }
+
+        let cp_hi = scope.checkpoint_viewed_at();
+        if reader_lo > cp_hi {
+            return Ok(Self::default());
+        }
+
+        let tx_bounds = tx_bounds(
+            ctx,
+            &(reader_lo..=cp_hi),
+            watermarks.high_watermark().transaction(),
+        )
+        .await?;
+
+        let query = query!(
+            r#"
+            WITH affected AS (
+                SELECT
+                    COUNT(*) AS tx_count,
+                    MIN(tx_sequence_number) AS tx_first,
+                    MAX(tx_sequence_number) AS tx_last
+                FROM
+                    tx_affected_addresses
+                WHERE
+                    affected = {Bytea}
+                AND {BigInt} <= tx_sequence_number
+                AND tx_sequence_number < {BigInt}
+            )
+
+            SELECT
+                a.tx_count,
+                (
+                    SELECT tx_digest FROM tx_digests WHERE tx_sequence_number = a.tx_first
+                ) AS first_tx_digest,
+                (
+                    SELECT tx_digest FROM tx_digests WHERE tx_sequence_number = a.tx_last
+                ) AS last_tx_digest,
+                -- Packages are immutable, so the only transaction that affects a package is the
+                -- one that publishes it.
+                (
+                    SELECT
+                        COUNT(DISTINCT p.package_id)
+                    FROM
+                        tx_affected_objects t
+                    INNER JOIN
+                        kv_packages p
+                    ON  p.package_id = t.affected
+                    WHERE
+                        t.sender = {Bytea}
+                    AND {BigInt} <= t.tx_sequence_number
+                    AND t.tx_sequence_number < {BigInt}
+                ) AS packages_published
+            FROM
+                affected a
+            "#,
+            address.to_vec(),
+            tx_bounds.start as i64,
+            tx_bounds.end as i64,
+            address.to_vec(),
+            tx_bounds.start as i64,
+            tx_bounds.end as i64,
+        );
+
+        let pg_reader: &PgReader = ctx.data()?;
+        let mut conn = pg_reader
+            .connect()
+            .await
+            .context("Failed to connect to database")?;
+
+        let results: Vec<StoredActivity> = conn
+            .results(query)
+            .await
+            .context("Failed to fetch address activity")?;
+
+        let stored = results
+            .into_iter()
+            .next()
+            .context("Address activity query returned no rows")?;
+
+        let (first_tx_checkpoint, last_tx_checkpoint) = try_join!(
+            checkpoint_of(ctx, stored.first_tx_digest),
+            checkpoint_of(ctx, stored.last_tx_digest),
+        )?;
+
+        Ok(Self {
+            first_tx_checkpoint,
+            last_tx_checkpoint,
+            tx_count: (stored.tx_count as u64).into(),
+            packages_published: (stored.packages_published as u64).into(),
+        })
+    }
+}
+
+/// The checkpoint that includes the transaction with digest `digest`, if there is one.
+async fn checkpoint_of(
+    ctx: &Context<'_>,
+    digest: Option<Vec<u8>>,
+) -> Result<Option<UInt53>, RpcError> {
+    let Some(digest) = digest else {
+        return Ok(None);
+    };
+
+    let digest =
+        TransactionDigest::try_from(digest).context("Failed to deserialize transaction digest")?;
+
+    let kv_loader: &KvLoader = ctx.data()?;
+    let transaction = kv_loader
+        .load_one_transaction(digest)
+        .await
+        .context("Failed to fetch transaction")?;
+
+    Ok(transaction.map(|t| t.cp_sequence_number().into()))
+}
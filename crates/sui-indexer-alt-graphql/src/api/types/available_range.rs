@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use async_graphql::{Context, SimpleObject};
+
+use crate::{error::RpcError, scope::Scope, task::watermark::Watermarks};
+
+use super::checkpoint::Checkpoint;
+
+/// The range of checkpoints that a paginated field has data for, based on the data that the service retains, and the checkpoint being viewed. Pages that fall outside this range are empty, even if there was data there at some point.
+#[derive(SimpleObject)]
+pub(crate) struct AvailableRange {
+    /// The first checkpoint the field has data for (inclusive).
+    pub first: Option<Checkpoint>,
+
+    /// The last checkpoint the field has data for (inclusive), which is the checkpoint being viewed.
+    pub last: Option<Checkpoint>,
+}
+
+/// Fields added to connections whose data is bounded by retention.
+#[derive(SimpleObject)]
+pub(crate) struct AvailableRangeFields {
+    /// The range of checkpoints that this connection can paginate over. Queries for data outside this range return empty pages, rather than an error.
+    pub available_range: AvailableRange,
+}
+
+impl AvailableRange {
+    /// The range from checkpoint `reader_lo` to the checkpoint being viewed in `scope`.
+    pub(crate) fn new(scope: &Scope, reader_lo: u64) -> Self {
+        Self {
+            first: Checkpoint::with_sequence_number(scope.clone(), reader_lo),
+            last: Checkpoint::with_sequence_number(scope.clone(), scope.checkpoint_viewed_at()),
+        }
+    }
+
+    /// The range of checkpoints that `pipeline` has data for, up to the checkpoint being viewed in
+    /// `scope`. Pipelines that are not tracked by the watermarks (e.g. key-value stores that are
+    /// not pruned) are assumed to have data from genesis.
+    pub(crate) fn for_pipeline<E: std::error::Error>(
+        ctx: &Context<'_>,
+        scope: &Scope,
+        pipeline: &str,
+    ) -> Result<Self, RpcError<E>> {
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        let reader_lo = watermarks
+            .pipeline_lo_watermark(pipeline)
+            .map_or(0, |w| w.checkpoint());
+
+        Ok(Self::new(scope, reader_lo))
+    }
+}
+
+impl From<AvailableRange> for AvailableRangeFields {
+    fn from(available_range: AvailableRange) -> Self {
+        Self { available_range }
+    }
+}
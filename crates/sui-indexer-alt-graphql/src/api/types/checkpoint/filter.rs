@@ -1,17 +1,28 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::ops::RangeInclusive;
+use std::{future::Future, ops::RangeInclusive};
 
 use crate::{
-    api::{scalars::uint53::UInt53, types::checkpoint::CCheckpoint},
+    api::{
+        scalars::{sui_address::SuiAddress, uint53::UInt53},
+        types::checkpoint::CCheckpoint,
+    },
     pagination::Page,
 };
 use anyhow::Context as _;
-use async_graphql::{Context, Error as RpcError, InputObject};
+use async_graphql::{dataloader::DataLoader, Context, Error as RpcError, InputObject};
 use diesel::{prelude::QueryableByName, sql_types::BigInt};
-use sui_indexer_alt_reader::pg_reader::PgReader;
+use fastcrypto::traits::ToFromBytes;
+use std::{collections::BTreeMap, sync::Arc};
+use sui_indexer_alt_reader::{epochs::EpochStartKey, kv_loader::KvLoader, pg_reader::PgReader};
 use sui_sql_macro::query;
+use sui_types::{
+    base_types::SuiAddress as NativeSuiAddress,
+    committee::Committee,
+    crypto::AuthorityPublicKeyBytes,
+    sui_system_state::{SuiSystemState, SuiSystemStateTrait},
+};
 
 use crate::intersect;
 
@@ -29,6 +40,12 @@ pub(crate) struct CheckpointFilter {
 
     /// Limit query results to checkpoints that occured strictly before the given checkpoint.
     pub before_checkpoint: Option<UInt53>,
+
+    /// Limit query results to checkpoints whose certificate includes a signature from the validator with this address. Can only be used in conjunction with `atEpoch`.
+    pub signed_by_validator: Option<SuiAddress>,
+
+    /// Limit query results to checkpoints whose certificate does not include a signature from the validator with this address. Can only be used in conjunction with `atEpoch`.
+    pub not_signed_by_validator: Option<SuiAddress>,
 }
 
 #[derive(QueryableByName, Debug)]
@@ -39,6 +56,12 @@ pub(crate) struct CpBounds {
     cp_hi_inclusive: i64,
 }
 
+#[derive(QueryableByName, Debug)]
+struct CpSequenceNumber {
+    #[diesel(sql_type = BigInt, column_name = "cp_sequence_number")]
+    cp_sequence_number: i64,
+}
+
 impl CheckpointFilter {
     pub(crate) fn intersect(self, other: Self) -> Option<Self> {
         macro_rules! intersect {
@@ -52,8 +75,15 @@ impl CheckpointFilter {
             after_checkpoint: intersect!(after_checkpoint, intersect::by_max)?,
             at_checkpoint: intersect!(at_checkpoint, intersect::by_eq)?,
             before_checkpoint: intersect!(before_checkpoint, intersect::by_min)?,
+            signed_by_validator: intersect!(signed_by_validator, intersect::by_eq)?,
+            not_signed_by_validator: intersect!(not_signed_by_validator, intersect::by_eq)?,
         })
     }
+
+    /// Whether this filter restricts checkpoints by the validators that signed them.
+    pub(crate) fn has_signer_filter(&self) -> bool {
+        self.signed_by_validator.is_some() || self.not_signed_by_validator.is_some()
+    }
 }
 
 /// The bounds on checkpoint sequence number, imposed by filters. The outermost bounds are
@@ -146,6 +176,113 @@ pub(super) async fn cp_by_epoch(
     cp_bounds: &RangeInclusive<u64>,
     epoch: u64,
 ) -> Result<Vec<u64>, RpcError> {
+    let Some(epoch_bounds) = epoch_bounds(ctx, cp_bounds, epoch).await? else {
+        return Ok(vec![]);
+    };
+
+    Ok(cp_unfiltered(&epoch_bounds, page))
+}
+
+/// The checkpoint sequence numbers in a range bounded by checkpoints in an epoch, whose
+/// certificates were (`signed_by`) or were not (`not_signed_by`) signed by the given validators.
+/// Validators are identified by their committee index for the epoch, and a validator that is not
+/// part of the committee never signs.
+pub(super) async fn cp_by_signers(
+    ctx: &Context<'_>,
+    page: &Page<CCheckpoint>,
+    cp_bounds: &RangeInclusive<u64>,
+    epoch: u64,
+    signed_by: Option<SuiAddress>,
+    not_signed_by: Option<SuiAddress>,
+) -> Result<Vec<u64>, RpcError> {
+    let Some(epoch_bounds) = epoch_bounds(ctx, cp_bounds, epoch).await? else {
+        return Ok(vec![]);
+    };
+
+    let Some(committee_indices) = committee_indices(ctx, epoch).await? else {
+        return Ok(vec![]);
+    };
+
+    let mut signer_filter = query!("");
+    if let Some(signer) = signed_by {
+        let signer: NativeSuiAddress = signer.into();
+        let Some(index) = committee_indices.get(&signer) else {
+            return Ok(vec![]);
+        };
+
+        signer_filter += query!(" AND signers @> ARRAY[{SmallInt}]", *index);
+    }
+
+    if let Some(non_signer) = not_signed_by {
+        let non_signer: NativeSuiAddress = non_signer.into();
+        if let Some(index) = committee_indices.get(&non_signer) {
+            signer_filter += query!(" AND NOT (signers @> ARRAY[{SmallInt}])", *index);
+        }
+    }
+
+    // Inclusive cursor bounds
+    let pg_lo = page.after().map_or(*epoch_bounds.start(), |cursor| {
+        cursor.max(*epoch_bounds.start())
+    });
+    let pg_hi_inclusive = page.before().map_or(*epoch_bounds.end(), |cursor| {
+        cursor.min(*epoch_bounds.end())
+    });
+
+    let query = query!(
+        r#"
+        SELECT
+            cp_sequence_number
+        FROM
+            cp_signers
+        WHERE
+            cp_sequence_number BETWEEN {BigInt} AND {BigInt}
+            {}
+        ORDER BY
+            cp_sequence_number {}
+        LIMIT
+            {BigInt}
+        "#,
+        pg_lo as i64,
+        pg_hi_inclusive as i64,
+        signer_filter,
+        if page.is_from_front() {
+            query!("ASC")
+        } else {
+            query!("DESC")
+        },
+        page.limit_with_overhead() as i64,
+    );
+
+    let pg_reader: &PgReader = ctx.data()?;
+    let mut conn = pg_reader
+        .connect()
+        .await
+        .context("Failed to connect to database")?;
+
+    let results: Vec<CpSequenceNumber> = conn
+        .results(query)
+        .await
+        .context("Failed to execute checkpoint signers query")?;
+
+    // Graphql last syntax expects results to be in ascending order.
+    let mut results: Vec<_> = results
+        .into_iter()
+        .map(|r| r.cp_sequence_number as u64)
+        .collect();
+    if !page.is_from_front() {
+        results.reverse();
+    }
+
+    Ok(results)
+}
+
+/// The bounds of the checkpoints in `epoch`, intersected with `cp_bounds`. Returns `None` if the
+/// epoch has not started yet, or if the intersection is empty.
+async fn epoch_bounds(
+    ctx: &Context<'_>,
+    cp_bounds: &RangeInclusive<u64>,
+    epoch: u64,
+) -> Result<Option<RangeInclusive<u64>>, RpcError> {
     let pg_reader: &PgReader = ctx.data()?;
 
     let query = query!(
@@ -175,12 +312,192 @@ pub(super) async fn cp_by_epoch(
         .await
         .context("Failed to execute epoch checkpoint query")?;
 
-    let (cp_lo, cp_hi_inclusive) = match results.first() {
-        Some(bounds) => (bounds.cp_lo as u64, bounds.cp_hi_inclusive as u64),
-        None => return Ok(vec![]),
+    Ok(results
+        .first()
+        .filter(|bounds| bounds.cp_lo <= bounds.cp_hi_inclusive)
+        .map(|bounds| bounds.cp_lo as u64..=bounds.cp_hi_inclusive as u64))
+}
+
+/// Maps the addresses of the validators in `epoch`'s committee to their index in the committee,
+/// which is how they are identified in checkpoint certificates. Returns `None` if the epoch has
+/// not started yet.
+async fn committee_indices(
+    ctx: &Context<'_>,
+    epoch: u64,
+) -> Result<Option<BTreeMap<NativeSuiAddress, i16>>, RpcError> {
+    let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+    let Some(start) = pg_loader
+        .load_one(EpochStartKey(epoch))
+        .await
+        .context("Failed to fetch epoch start information")?
+    else {
+        return Ok(None);
     };
 
-    Ok(cp_unfiltered(&(cp_lo..=cp_hi_inclusive), page))
+    let system_state = bcs::from_bytes::<SuiSystemState>(&start.system_state)
+        .context("Failed to deserialize system state")?;
+
+    let mut names = BTreeMap::new();
+    let mut voting_rights = BTreeMap::new();
+    for validator in system_state
+        .into_sui_system_state_summary()
+        .active_validators
+    {
+        let name = AuthorityPublicKeyBytes::from_bytes(&validator.protocol_pubkey_bytes)
+            .context("Failed to deserialize validator protocol key")?;
+        names.insert(validator.sui_address, name);
+        voting_rights.insert(name, validator.voting_power);
+    }
+
+    let committee = Committee::new(epoch, voting_rights);
+    let mut indices = BTreeMap::new();
+    for (address, name) in names {
+        let index = committee
+            .authority_index(&name)
+            .context("Validator missing from its own committee")?;
+        indices.insert(
+            address,
+            i16::try_from(index).context("Committee index out of range")?,
+        );
+    }
+
+    Ok(Some(indices))
+}
+
+/// The sequence number of the latest checkpoint between `reader_lo` and `checkpoint_viewed_at`
+/// (inclusive) whose timestamp is at or before `timestamp_ms`. Returns `None` if every checkpoint
+/// in that range is after it.
+///
+/// Checkpoint timestamps never decrease, so the checkpoint is found by binary search, looking up
+/// checkpoints by sequence number. The search starts from the checkpoints of the epoch that
+/// started most recently by `timestamp_ms`, which narrows it down to a single epoch's worth of
+/// checkpoints in most cases.
+pub(crate) async fn checkpoint_at_timestamp(
+    ctx: &Context<'_>,
+    reader_lo: u64,
+    checkpoint_viewed_at: u64,
+    timestamp_ms: u64,
+) -> Result<Option<u64>, RpcError> {
+    if reader_lo > checkpoint_viewed_at {
+        return Ok(None);
+    }
+
+    let Some(hint) = epoch_hint(ctx, reader_lo, checkpoint_viewed_at, timestamp_ms).await? else {
+        return Ok(None);
+    };
+
+    let kv_loader: &KvLoader = ctx.data()?;
+    let timestamp_of = |sequence_number: u64| async move {
+        let (summary, _, _) = kv_loader
+            .load_one_checkpoint(sequence_number)
+            .await
+            .context("Failed to fetch checkpoint")?
+            .with_context(|| format!("Checkpoint {sequence_number} not found"))?;
+
+        Ok::<_, RpcError>(summary.timestamp_ms)
+    };
+
+    // Epoch start timestamps only approximate the timestamps of the checkpoints around epoch
+    // boundaries, so the search widens past the hint if the checkpoint is not inside it.
+    let found = latest_at_or_before(hint.clone(), timestamp_ms, timestamp_of).await?;
+    Ok(match found {
+        None if *hint.start() > reader_lo => {
+            latest_at_or_before(reader_lo..=*hint.start() - 1, timestamp_ms, timestamp_of).await?
+        }
+
+        Some(cp) if cp == *hint.end() && cp < checkpoint_viewed_at => {
+            latest_at_or_before(cp..=checkpoint_viewed_at, timestamp_ms, timestamp_of).await?
+        }
+
+        found => found,
+    })
+}
+
+/// The range of checkpoints that the latest checkpoint at or before `timestamp_ms` is expected to
+/// be in, based on when epochs started: from the last checkpoint before the latest epoch that
+/// started at or before `timestamp_ms`, to the first checkpoint of the next epoch, clamped to the
+/// checkpoints between `reader_lo` and `checkpoint_viewed_at`.
+async fn epoch_hint(
+    ctx: &Context<'_>,
+    reader_lo: u64,
+    checkpoint_viewed_at: u64,
+    timestamp_ms: u64,
+) -> Result<Option<RangeInclusive<u64>>, RpcError> {
+    let pg_reader: &PgReader = ctx.data()?;
+
+    let query = query!(
+        r#"
+        SELECT
+            COALESCE((
+                SELECT
+                    MAX(cp_lo) - 1
+                FROM
+                    kv_epoch_starts
+                WHERE
+                    start_timestamp_ms <= {BigInt}
+            ), {BigInt}) AS "cp_lo",
+            COALESCE((
+                SELECT
+                    MIN(cp_lo)
+                FROM
+                    kv_epoch_starts
+                WHERE
+                    start_timestamp_ms > {BigInt}
+            ), {BigInt}) AS "cp_hi_inclusive"
+        "#,
+        timestamp_ms as i64,
+        reader_lo as i64,
+        timestamp_ms as i64,
+        checkpoint_viewed_at as i64,
+    );
+
+    let mut conn = pg_reader
+        .connect()
+        .await
+        .context("Failed to connect to database")?;
+
+    let results: Vec<CpBounds> = conn
+        .results(query)
+        .await
+        .context("Failed to execute epoch timestamp query")?;
+
+    let Some(bounds) = results.first() else {
+        return Ok(None);
+    };
+
+    let cp_lo = (bounds.cp_lo.max(0) as u64).clamp(reader_lo, checkpoint_viewed_at);
+    let cp_hi = (bounds.cp_hi_inclusive.max(0) as u64).clamp(cp_lo, checkpoint_viewed_at);
+    Ok(Some(cp_lo..=cp_hi))
+}
+
+/// Binary search for the last checkpoint in `range` whose timestamp, according to `timestamp_of`,
+/// is at or before `timestamp_ms`. Assumes that timestamps never decrease with sequence number.
+async fn latest_at_or_before<F, Fut>(
+    range: RangeInclusive<u64>,
+    timestamp_ms: u64,
+    timestamp_of: F,
+) -> Result<Option<u64>, RpcError>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<u64, RpcError>>,
+{
+    let (start, end) = range.into_inner();
+    if start > end {
+        return Ok(None);
+    }
+
+    // Find the first checkpoint that is after the timestamp, in [lo, hi).
+    let (mut lo, mut hi) = (start, end + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if timestamp_of(mid).await? <= timestamp_ms {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Ok((lo > start).then(|| lo - 1))
 }
 
 /// Determines the maximum value in an arbitrary number of Option<impl Ord>.
@@ -197,6 +514,28 @@ fn min_option<T: Ord>(xs: impl IntoIterator<Item = Option<T>>) -> Option<T> {
 mod tests {
     use super::*;
 
+    async fn search(timestamps: &[u64], range: RangeInclusive<u64>, ts: u64) -> Option<u64> {
+        latest_at_or_before(range, ts, |cp| async move { Ok(timestamps[cp as usize]) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_latest_at_or_before() {
+        let timestamps = [100, 200, 200, 300, 400, 400, 400, 500];
+
+        assert_eq!(search(&timestamps, 0..=7, 99).await, None);
+        assert_eq!(search(&timestamps, 0..=7, 100).await, Some(0));
+        assert_eq!(search(&timestamps, 0..=7, 250).await, Some(2));
+        assert_eq!(search(&timestamps, 0..=7, 400).await, Some(6));
+        assert_eq!(search(&timestamps, 0..=7, 1000).await, Some(7));
+
+        // Only checkpoints in the range are considered.
+        assert_eq!(search(&timestamps, 3..=5, 250).await, None);
+        assert_eq!(search(&timestamps, 3..=5, 1000).await, Some(5));
+        assert_eq!(search(&timestamps, 4..=4, 400).await, Some(4));
+    }
+
     #[test]
     fn test_checkpoint_bounds_no_filters() {
         assert_eq!(
@@ -1,6 +1,8 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::Arc;
+
 use anyhow::Context as _;
 use async_graphql::{
     connection::{Connection, CursorType, Edge},
@@ -19,16 +21,24 @@ use crate::{
         query::Query,
         scalars::{base64::Base64, cursor::JsonCursor, date_time::DateTime, uint53::UInt53},
     },
-    error::RpcError,
+    error::{bad_user_input, RpcError},
     pagination::{Page, PaginationConfig},
     scope::Scope,
+    task::watermark::Watermarks,
 };
 
 use super::{
-    checkpoint::filter::{checkpoint_bounds, cp_by_epoch, cp_unfiltered, CheckpointFilter},
+    available_range::{AvailableRange, AvailableRangeFields},
+    checkpoint::filter::{
+        checkpoint_at_timestamp, checkpoint_bounds, cp_by_epoch, cp_by_signers, cp_unfiltered,
+        CheckpointFilter,
+    },
     epoch::Epoch,
     gas::GasCostSummary,
-    transaction::{filter::TransactionFilter, CTransaction, Transaction},
+    transaction::{
+        filter::{TransactionFilter, Validator as TFValidator},
+        CTransaction, Transaction,
+    },
     validator_aggregated_signature::ValidatorAggregatedSignature,
 };
 
@@ -53,6 +63,12 @@ struct CheckpointContents {
 
 pub(crate) type CCheckpoint = JsonCursor<u64>;
 
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum Error {
+    #[error("Filtering checkpoints by the validators that signed them requires an epoch filter")]
+    SignersWithoutEpoch,
+}
+
 /// Checkpoints contain finalized transactions and are used for node synchronization and global transaction ordering.
 #[Object]
 impl Checkpoint {
@@ -175,8 +191,8 @@ impl CheckpointContents {
         after: Option<CTransaction>,
         last: Option<u64>,
         before: Option<CTransaction>,
-        filter: Option<TransactionFilter>,
-    ) -> Result<Option<Connection<String, Transaction>>, RpcError> {
+        #[graphql(validator(custom = "TFValidator::default()"))] filter: Option<TransactionFilter>,
+    ) -> Result<Option<Connection<String, Transaction, AvailableRangeFields>>, RpcError> {
         let Some((summary, _, _)) = &self.contents else {
             return Ok(None);
         };
@@ -188,7 +204,12 @@ impl CheckpointContents {
             at_checkpoint: Some(UInt53::from(summary.sequence_number)),
             ..Default::default()
         }) else {
-            return Ok(Some(Connection::new(false, false)));
+            let range = AvailableRange::for_pipeline(ctx, &self.scope, "tx_digests")?;
+            return Ok(Some(Connection::with_additional_fields(
+                false,
+                false,
+                range.into(),
+            )));
         };
 
         Ok(Some(
@@ -208,19 +229,53 @@ impl Checkpoint {
         })
     }
 
+    /// The latest checkpoint whose timestamp is at or before `timestamp`, as of the current scope.
+    /// Returns `None` if every checkpoint that the store has data for is after the timestamp.
+    pub(crate) async fn at_timestamp(
+        ctx: &Context<'_>,
+        scope: Scope,
+        timestamp: DateTime,
+    ) -> Result<Option<Self>, RpcError> {
+        let Ok(timestamp_ms) = u64::try_from(timestamp.timestamp_ms()) else {
+            return Ok(None);
+        };
+
+        // Checkpoints may be stored in a key-value store that is not tracked by the watermarks,
+        // and does not prune.
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        let reader_lo = watermarks
+            .pipeline_lo_watermark("kv_checkpoints")
+            .map_or(0, |w| w.checkpoint());
+
+        let sequence_number =
+            checkpoint_at_timestamp(ctx, reader_lo, scope.checkpoint_viewed_at(), timestamp_ms)
+                .await?;
+
+        Ok(sequence_number.and_then(|s| Self::with_sequence_number(scope, s)))
+    }
+
     /// Paginate through checkpoints with filters applied.
     pub(crate) async fn paginate(
         ctx: &Context<'_>,
         scope: Scope,
         page: Page<CCheckpoint>,
         filter: CheckpointFilter,
-    ) -> Result<Connection<String, Checkpoint>, RpcError> {
-        let mut conn = Connection::new(false, false);
+    ) -> Result<Connection<String, Checkpoint, AvailableRangeFields>, RpcError<Error>> {
+        if filter.has_signer_filter() && filter.at_epoch.is_none() {
+            return Err(bad_user_input(Error::SignersWithoutEpoch));
+        }
 
-        // TODO: (henrychen) Update when we figure out retention for key-value stores.
-        let cp_lo = 0;
+        // Checkpoints may be stored in a key-value store that is not tracked by the watermarks,
+        // and does not prune.
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        let cp_lo = watermarks
+            .pipeline_lo_watermark("kv_checkpoints")
+            .map_or(0, |w| w.checkpoint());
         let cp_hi_inclusive = scope.checkpoint_viewed_at();
 
+        let range = AvailableRange::new(&scope, cp_lo);
+        let mut conn = Connection::with_additional_fields(false, false, range.into());
+
         let Some(cp_bounds) = checkpoint_bounds(
             filter.after_checkpoint.map(u64::from),
             filter.at_checkpoint.map(u64::from),
@@ -228,13 +283,23 @@ impl Checkpoint {
             cp_lo,
             cp_hi_inclusive,
         ) else {
-            return Ok(Connection::new(false, false));
+            return Ok(conn);
         };
 
-        let results = if let Some(epoch) = filter.at_epoch {
-            cp_by_epoch(ctx, &page, &cp_bounds, epoch.into()).await?
-        } else {
-            cp_unfiltered(&cp_bounds, &page)
+        let results = match filter.at_epoch {
+            Some(epoch) if filter.has_signer_filter() => {
+                cp_by_signers(
+                    ctx,
+                    &page,
+                    &cp_bounds,
+                    epoch.into(),
+                    filter.signed_by_validator,
+                    filter.not_signed_by_validator,
+                )
+                .await?
+            }
+            Some(epoch) => cp_by_epoch(ctx, &page, &cp_bounds, epoch.into()).await?,
+            None => cp_unfiltered(&cp_bounds, &page),
         };
 
         let (prev, next, results) = page.paginate_results(results, |c| JsonCursor::new(*c));
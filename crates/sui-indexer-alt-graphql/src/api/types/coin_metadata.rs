@@ -0,0 +1,299 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use async_graphql::{Context, SimpleObject};
+use lru::LruCache;
+use move_core_types::language_storage::StructTag;
+use sui_indexer_alt_reader::{
+    consistent_reader::{self, ConsistentReader},
+    kv_loader::KvLoader,
+};
+use sui_types::{
+    coin::{CoinMetadata as NativeCoinMetadata, TreasuryCap},
+    gas_coin::{GAS, TOTAL_SUPPLY_MIST},
+    object::Object as NativeObject,
+    TypeTag,
+};
+
+use crate::{
+    api::scalars::{big_int::BigInt, sui_address::SuiAddress},
+    error::{bad_user_input, feature_unavailable, out_of_retention, RpcError},
+    scope::Scope,
+};
+
+/// Metadata for a coin type, read from its `0x2::coin::CoinMetadata` object.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub(crate) struct CoinMetadata {
+    /// The address of the `CoinMetadata` object.
+    pub address: SuiAddress,
+
+    /// Number of decimal places the coin uses.
+    pub decimals: Option<u8>,
+
+    /// Name for the token.
+    pub name: Option<String>,
+
+    /// Symbol for the token.
+    pub symbol: Option<String>,
+
+    /// Description of the token.
+    pub description: Option<String>,
+
+    /// URL for the token logo.
+    pub icon_url: Option<String>,
+}
+
+/// Caches the contents of the per-coin objects (`CoinMetadata` and `TreasuryCap`) that are looked
+/// up by type, shared between requests. Finding them requires a query against the live object set,
+/// which is repeated for every request viewing the same checkpoint. Entries are keyed by type and
+/// the checkpoint they were viewed at, so they never go stale, and the least recently used entries
+/// are evicted once the cache is at capacity.
+pub(crate) struct CoinMetadataCache {
+    entries: Mutex<LruCache<(StructTag, u64), Option<Arc<NativeObject>>>>,
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum Error {
+    #[error("Expected a coin type, e.g. 0x2::sui::SUI, but got {0}")]
+    NotACoinType(String),
+
+    #[error("Request is outside consistent range")]
+    OutOfRange(u64),
+}
+
+impl CoinMetadata {
+    /// Fetch the metadata for the coin with type `coin_type`, as of the checkpoint being viewed.
+    pub(crate) async fn by_coin_type(
+        ctx: &Context<'_>,
+        scope: Scope,
+        coin_type: TypeTag,
+    ) -> Result<Option<Self>, RpcError<Error>> {
+        let coin_type = coin_struct_tag(coin_type)?;
+        let cache: &CoinMetadataCache = ctx.data()?;
+
+        let Some(object) = cache
+            .load(ctx, &scope, NativeCoinMetadata::type_(coin_type))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(move_object) = object.data.try_as_move() else {
+            return Ok(None);
+        };
+
+        let metadata = NativeCoinMetadata::from_bcs_bytes(move_object.contents())
+            .context("Failed to deserialize CoinMetadata")?;
+
+        Ok(Some(Self {
+            address: object.id().into(),
+            decimals: Some(metadata.decimals),
+            name: Some(metadata.name),
+            symbol: Some(metadata.symbol),
+            description: Some(metadata.description),
+            icon_url: metadata.icon_url,
+        }))
+    }
+}
+
+/// Fetch the total supply of the coin with type `coin_type`, from its `TreasuryCap`, as of the
+/// checkpoint being viewed.
+///
+/// Returns `None` if the treasury cap is not in the live object set (e.g. because it has been
+/// wrapped in another object).
+pub(crate) async fn total_supply(
+    ctx: &Context<'_>,
+    scope: Scope,
+    coin_type: TypeTag,
+) -> Result<Option<BigInt>, RpcError<Error>> {
+    let coin_type = coin_struct_tag(coin_type)?;
+
+    // SUI does not have a treasury cap, its supply is fixed at genesis.
+    if coin_type == GAS::type_() {
+        return Ok(Some(TOTAL_SUPPLY_MIST.into()));
+    }
+
+    let cache: &CoinMetadataCache = ctx.data()?;
+    let Some(object) = cache
+        .load(ctx, &scope, TreasuryCap::type_(coin_type))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let Some(move_object) = object.data.try_as_move() else {
+        return Ok(None);
+    };
+
+    let treasury_cap = TreasuryCap::from_bcs_bytes(move_object.contents())
+        .context("Failed to deserialize TreasuryCap")?;
+
+    Ok(Some(treasury_cap.total_supply.value.into()))
+}
+
+impl CoinMetadataCache {
+    /// Create a cache that holds at most `capacity` entries (and at least one).
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Load the contents of the live object with type `type_` as of the checkpoint being viewed,
+    /// from the cache if it has been loaded at this checkpoint before, or from the store
+    /// otherwise. If there are multiple live objects with this type, the first one (ordered by
+    /// object ID) is returned.
+    async fn load(
+        &self,
+        ctx: &Context<'_>,
+        scope: &Scope,
+        type_: StructTag,
+    ) -> Result<Option<Arc<NativeObject>>, RpcError<Error>> {
+        let key = (type_, scope.checkpoint_viewed_at());
+        if let Some(contents) = self.get(&key) {
+            return Ok(contents);
+        }
+
+        let contents = latest_by_type(ctx, scope, &key.0).await?;
+        self.put(key, contents.clone());
+        Ok(contents)
+    }
+
+    /// The cached contents for `key`, if there are any. The outer `Option` is `None` on a cache
+    /// miss, and the inner one is `None` if there was no live object of this type.
+    fn get(&self, key: &(StructTag, u64)) -> Option<Option<Arc<NativeObject>>> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: (StructTag, u64), contents: Option<Arc<NativeObject>>) {
+        self.entries.lock().unwrap().put(key, contents);
+    }
+}
+
+/// Find the first live object with type `type_` as of the checkpoint being viewed, and load its
+/// contents.
+async fn latest_by_type(
+    ctx: &Context<'_>,
+    scope: &Scope,
+    type_: &StructTag,
+) -> Result<Option<Arc<NativeObject>>, RpcError<Error>> {
+    let consistent_reader: &ConsistentReader = ctx.data()?;
+    let kv_loader: &KvLoader = ctx.data()?;
+
+    let checkpoint = scope.checkpoint_viewed_at();
+    let refs = consistent_reader
+        .list_objects_by_type(
+            checkpoint,
+            type_.to_canonical_string(/* with_prefix */ true),
+            Some(1),
+            None,
+            None,
+            true,
+        )
+        .await
+        .map_err(|e| match e {
+            consistent_reader::Error::NotConfigured => {
+                feature_unavailable("fetching coin metadata")
+            }
+
+            consistent_reader::Error::OutOfRange(_) => {
+                out_of_retention(Error::OutOfRange(checkpoint))
+            }
+
+            consistent_reader::Error::Internal(error) => {
+                error.context("Failed to fetch objects by type").into()
+            }
+        })?;
+
+    let Some(edge) = refs.results.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let (id, version, _) = edge.value;
+    Ok(kv_loader
+        .load_one_object(id, version.value())
+        .await
+        .context("Failed to fetch object contents")?
+        .map(Arc::new))
+}
+
+/// Coins are identified by their struct type, e.g. `0x2::sui::SUI`.
+fn coin_struct_tag(coin_type: TypeTag) -> Result<StructTag, RpcError<Error>> {
+    match coin_type {
+        TypeTag::Struct(tag) => Ok(*tag),
+        other => Err(bad_user_input(Error::NotACoinType(
+            other.to_canonical_string(/* with_prefix */ true),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sui_types::base_types::ObjectID;
+
+    use super::*;
+
+    fn coin(name: &str) -> StructTag {
+        StructTag::from_str(&format!("0x42::{name}::{}", name.to_uppercase())).unwrap()
+    }
+
+    fn object(id: u8) -> Option<Arc<NativeObject>> {
+        Some(Arc::new(NativeObject::immutable_with_id_for_testing(
+            ObjectID::from_single_byte(id),
+        )))
+    }
+
+    fn id(contents: Option<Option<Arc<NativeObject>>>) -> Option<Option<ObjectID>> {
+        contents.map(|c| c.map(|o| o.id()))
+    }
+
+    #[test]
+    fn test_entries_are_per_checkpoint() {
+        let cache = CoinMetadataCache::new(10);
+        cache.put((coin("foo"), 1), None);
+        cache.put((coin("foo"), 2), object(1));
+
+        // The metadata was created between checkpoints 1 and 2, so requests viewing checkpoint 1
+        // should not see it, even after it has been loaded at checkpoint 2.
+        assert_eq!(id(cache.get(&(coin("foo"), 1))), Some(None));
+        assert_eq!(
+            id(cache.get(&(coin("foo"), 2))),
+            Some(Some(ObjectID::from_single_byte(1)))
+        );
+
+        // Nothing has been loaded at checkpoint 3 yet.
+        assert_eq!(id(cache.get(&(coin("foo"), 3))), None);
+    }
+
+    #[test]
+    fn test_least_recently_used_are_evicted() {
+        let cache = CoinMetadataCache::new(2);
+        cache.put((coin("foo"), 1), object(1));
+        cache.put((coin("bar"), 1), object(2));
+
+        // Using the entry for `foo` makes `bar` the least recently used entry.
+        assert!(cache.get(&(coin("foo"), 1)).is_some());
+        cache.put((coin("baz"), 1), object(3));
+
+        assert!(cache.get(&(coin("foo"), 1)).is_some());
+        assert!(cache.get(&(coin("bar"), 1)).is_none());
+        assert!(cache.get(&(coin("baz"), 1)).is_some());
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_capacity_is_at_least_one() {
+        let cache = CoinMetadataCache::new(0);
+        cache.put((coin("foo"), 1), object(1));
+        assert!(cache.get(&(coin("foo"), 1)).is_some());
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Context as _;
+use async_graphql::{dataloader::DataLoader, Context, SimpleObject};
+use move_core_types::language_storage::StructTag;
+use sui_display::v1::Format;
+use sui_indexer_alt_reader::{displays::DisplayKey, pg_reader::PgReader};
+use sui_types::{
+    collection_types::{Entry, VecMap},
+    display::DisplayVersionUpdatedEvent,
+    object::MoveObject as NativeMoveObject,
+};
+
+use crate::{
+    config::Limits,
+    error::{resource_exhausted, RpcError},
+    scope::Scope,
+};
+
+/// A field of an object's Display, rendered by substituting values from the object's contents into
+/// the format string for that field in its type's `0x2::display::Display` template.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub(crate) struct DisplayEntry {
+    /// The name of the Display field.
+    pub key: String,
+
+    /// The rendered value of the field, or `null` if it could not be rendered.
+    pub value: Option<String>,
+
+    /// Why the field could not be rendered, if it could not.
+    pub error: Option<String>,
+}
+
+/// Caches the fields of the Display templates that have been rendered, by type and Display
+/// version, shared between requests. A template's fields can only change when its version is
+/// bumped, so entries never go stale.
+#[derive(Default)]
+pub(crate) struct DisplayCache {
+    entries: RwLock<HashMap<(StructTag, u16), Arc<VecMap<String, String>>>>,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Display output is too big")]
+pub(crate) struct DisplayTooBigError;
+
+/// Render the Display for `object` according to the latest Display template for its type, as
+/// seen from `scope`. Returns `None` if there is no Display template for the object's type.
+pub(crate) async fn render<E: std::error::Error + Send + Sync + 'static>(
+    ctx: &Context<'_>,
+    scope: &Scope,
+    object: &NativeMoveObject,
+) -> Result<Option<Vec<DisplayEntry>>, RpcError<E>> {
+    let limits: &Limits = ctx.data()?;
+    let cache: &DisplayCache = ctx.data()?;
+
+    let type_: StructTag = object.type_().clone().into();
+    let Some(fields) = cache.load(ctx, &type_).await? else {
+        return Ok(None);
+    };
+
+    let layout = scope
+        .package_resolver()
+        .type_layout(type_.clone().into())
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to resolve type layout for {}",
+                type_.to_canonical_display(/* with_prefix */ true)
+            )
+        })?;
+
+    // Each field is parsed separately, so that a malformed format string only fails its own
+    // field, but they share the output budget.
+    let mut budget = limits.max_display_output_size;
+    let mut entries = vec![];
+    for Entry { key, value } in &fields.contents {
+        let field = VecMap {
+            contents: vec![Entry {
+                key: key.clone(),
+                value: value.clone(),
+            }],
+        };
+
+        let mut output = match Format::parse(limits.max_display_field_depth, &field) {
+            Ok(format) => format
+                .display(budget, object.contents(), &layout)
+                .map_err(|_| resource_exhausted(DisplayTooBigError))?,
+            Err(e) => {
+                entries.push(DisplayEntry {
+                    key: key.clone(),
+                    value: None,
+                    error: Some(format!("{e:#}")),
+                });
+                continue;
+            }
+        };
+
+        let entry = match output.remove(key) {
+            Some(Ok(value)) => {
+                budget -= key.len() + value.len();
+                DisplayEntry {
+                    key: key.clone(),
+                    value: Some(value),
+                    error: None,
+                }
+            }
+
+            Some(Err(e)) => DisplayEntry {
+                key: key.clone(),
+                value: None,
+                error: Some(format!("{e:#}")),
+            },
+
+            None => continue,
+        };
+
+        entries.push(entry);
+    }
+
+    Ok(Some(entries))
+}
+
+impl DisplayCache {
+    /// Load the fields of the latest Display template for `type_`, from the cache if this version
+    /// of the template has been loaded before, or by deserializing it otherwise. Returns `None`
+    /// if there is no Display template for `type_`.
+    async fn load<E: std::error::Error>(
+        &self,
+        ctx: &Context<'_>,
+        type_: &StructTag,
+    ) -> Result<Option<Arc<VecMap<String, String>>>, RpcError<E>> {
+        let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+
+        let Some(stored) = pg_loader
+            .load_one(DisplayKey(type_.clone()))
+            .await
+            .context("Failed to load Display template")?
+        else {
+            return Ok(None);
+        };
+
+        let key = (type_.clone(), stored.display_version as u16);
+        if let Some(fields) = self.entries.read().unwrap().get(&key) {
+            return Ok(Some(fields.clone()));
+        }
+
+        let event: DisplayVersionUpdatedEvent =
+            bcs::from_bytes(&stored.display).context("Failed to deserialize Display template")?;
+
+        let fields = Arc::new(event.fields);
+        self.entries.write().unwrap().insert(key, fields.clone());
+        Ok(Some(fields))
+    }
+}
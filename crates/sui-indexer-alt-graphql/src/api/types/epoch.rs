@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
-    checkpoint::{filter::CheckpointFilter, CCheckpoint, Checkpoint},
+    available_range::{AvailableRange, AvailableRangeFields},
+    checkpoint::{self, filter::CheckpointFilter, CCheckpoint, Checkpoint},
     move_package::{self, CSysPackage, MovePackage},
     object::{self, Object},
     protocol_configs::ProtocolConfigs,
-    transaction::{filter::TransactionFilter, CTransaction, Transaction},
+    transaction::{
+        filter::{TransactionFilter, Validator as TFValidator},
+        CTransaction, Transaction,
+    },
 };
 use crate::api::types::safe_mode::{from_system_state, SafeMode};
 use crate::api::types::stake_subsidy::{from_stake_subsidy_v1, StakeSubsidy};
@@ -27,13 +31,14 @@ use fastcrypto::encoding::{Base58, Encoding};
 use futures::try_join;
 use std::sync::Arc;
 use sui_indexer_alt_reader::cp_sequence_numbers::CpSequenceNumberKey;
+use sui_indexer_alt_reader::kv_loader::KvLoader;
 use sui_indexer_alt_reader::{
     epochs::{CheckpointBoundedEpochStartKey, EpochEndKey, EpochStartKey},
     pg_reader::PgReader,
 };
 use sui_indexer_alt_schema::cp_sequence_numbers::StoredCpSequenceNumbers;
 use sui_indexer_alt_schema::epochs::{StoredEpochEnd, StoredEpochStart};
-use sui_types::messages_checkpoint::CheckpointCommitment;
+use sui_types::messages_checkpoint::{CheckpointCommitment, CheckpointSummary};
 use sui_types::sui_system_state::SuiSystemState;
 use sui_types::sui_system_state::SuiSystemStateTrait;
 use sui_types::SUI_DENY_LIST_OBJECT_ID;
@@ -73,7 +78,10 @@ impl Epoch {
         last: Option<u64>,
         before: Option<CCheckpoint>,
         filter: Option<CheckpointFilter>,
-    ) -> Result<Option<Connection<String, Checkpoint>>, RpcError> {
+    ) -> Result<
+        Option<Connection<String, Checkpoint, AvailableRangeFields>>,
+        RpcError<checkpoint::Error>,
+    > {
         let pagination: &PaginationConfig = ctx.data()?;
         let limits = pagination.limits("Epoch", "checkpoints");
         let page = Page::from_params(limits, first, after, last, before)?;
@@ -82,7 +90,12 @@ impl Epoch {
             at_epoch: Some(self.epoch_id.into()),
             ..Default::default()
         }) else {
-            return Ok(Some(Connection::new(false, false)));
+            let range = AvailableRange::for_pipeline(ctx, &self.scope, "kv_checkpoints")?;
+            return Ok(Some(Connection::with_additional_fields(
+                false,
+                false,
+                range.into(),
+            )));
         };
 
         Ok(Some(
@@ -150,8 +163,8 @@ impl Epoch {
         after: Option<CTransaction>,
         last: Option<u64>,
         before: Option<CTransaction>,
-        filter: Option<TransactionFilter>,
-    ) -> Result<Option<Connection<String, Transaction>>, RpcError> {
+        #[graphql(validator(custom = "TFValidator::default()"))] filter: Option<TransactionFilter>,
+    ) -> Result<Option<Connection<String, Transaction, AvailableRangeFields>>, RpcError> {
         let (Some(start), end) = try_join!(self.start(ctx), self.end(ctx))? else {
             return Ok(None);
         };
@@ -171,7 +184,12 @@ impl Epoch {
             before_checkpoint: Some(UInt53::from(cp_hi)),
             ..Default::default()
         }) else {
-            return Ok(Some(Connection::new(false, false)));
+            let range = AvailableRange::for_pipeline(ctx, &self.scope, "tx_digests")?;
+            return Ok(Some(Connection::with_additional_fields(
+                false,
+                false,
+                range.into(),
+            )));
         };
 
         Ok(Some(
@@ -234,7 +252,7 @@ impl Epoch {
         Ok(Some(validator_set))
     }
 
-    /// The total number of checkpoints in this epoch.
+    /// The total number of checkpoints in this epoch. If the epoch has not finished yet, this is the number of checkpoints so far, as of the checkpoint being viewed.
     async fn total_checkpoints(&self, ctx: &Context<'_>) -> Result<Option<UInt53>, RpcError> {
         let (Some(start), end) = try_join!(self.start(ctx), self.end(ctx))? else {
             return Ok(None);
@@ -249,40 +267,49 @@ impl Epoch {
         Ok(Some(UInt53::from(hi - lo)))
     }
 
-    /// The total number of transaction blocks in this epoch (or `null` if the epoch has not finished yet).
+    /// The total number of transaction blocks in this epoch. If the epoch has not finished yet, this is the number of transactions so far, as of the checkpoint being viewed.
     async fn total_transactions(&self, ctx: &Context<'_>) -> Result<Option<UInt53>, RpcError> {
-        let (Some(cp_sequence_numbers), Some(end)) =
+        let (Some(cp_sequence_numbers), end) =
             try_join!(self.cp_sequence_numbers(ctx), self.end(ctx))?
         else {
             return Ok(None);
         };
 
         let lo = cp_sequence_numbers.tx_lo as u64;
-        let hi = end.tx_hi as u64;
+        let hi = match end {
+            Some(end) => end.tx_hi as u64,
+            None => {
+                let Some(summary) = self.latest_checkpoint(ctx).await? else {
+                    return Ok(None);
+                };
+
+                summary.network_total_transactions
+            }
+        };
 
         Ok(Some(UInt53::from(hi - lo)))
     }
 
-    /// The total amount of gas fees (in MIST) that were paid in this epoch (or `null` if the epoch has not finished yet).
+    /// The total amount of gas fees (in MIST) that were paid in this epoch. If the epoch has not finished yet, this is the amount paid so far, as of the checkpoint being viewed.
     async fn total_gas_fees(&self, ctx: &Context<'_>) -> Result<Option<BigInt>, RpcError> {
-        let Some(StoredEpochEnd { total_gas_fees, .. }) = self.end(ctx).await? else {
-            return Ok(None);
-        };
+        if let Some(StoredEpochEnd { total_gas_fees, .. }) = self.end(ctx).await? {
+            return Ok(total_gas_fees.map(BigInt::from));
+        }
 
-        Ok(total_gas_fees.map(BigInt::from))
+        Ok(self.gas_fees_so_far(ctx).await?.map(BigInt::from))
     }
 
-    /// The total MIST rewarded as stake (or `null` if the epoch has not finished yet).
+    /// The total MIST rewarded as stake. If the epoch has not finished yet, this is the amount accrued so far from gas fees, as of the checkpoint being viewed -- stake subsidies are only added when the epoch ends.
     async fn total_stake_rewards(&self, ctx: &Context<'_>) -> Result<Option<BigInt>, RpcError> {
-        let Some(StoredEpochEnd {
+        if let Some(StoredEpochEnd {
             total_stake_rewards_distributed,
             ..
         }) = self.end(ctx).await?
-        else {
-            return Ok(None);
-        };
+        {
+            return Ok(total_stake_rewards_distributed.map(BigInt::from));
+        }
 
-        Ok(total_stake_rewards_distributed.map(BigInt::from))
+        Ok(self.gas_fees_so_far(ctx).await?.map(BigInt::from))
     }
 
     /// The amount added to total gas fees to make up the total stake rewards (or `null` if the epoch has not finished yet).
@@ -542,6 +569,39 @@ impl Epoch {
             .await
     }
 
+    /// Gas fees paid so far in an epoch that has started but not finished, as of the checkpoint
+    /// being viewed, or `None` if the epoch has not started yet.
+    async fn gas_fees_so_far(&self, ctx: &Context<'_>) -> Result<Option<u64>, RpcError> {
+        if self.start(ctx).await?.is_none() {
+            return Ok(None);
+        }
+
+        // The fees paid in an epoch are its computation costs -- storage costs go to the storage
+        // fund, rather than to validators and stakers.
+        let Some(summary) = self.latest_checkpoint(ctx).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            summary.epoch_rolling_gas_cost_summary.computation_cost,
+        ))
+    }
+
+    /// Summary of the checkpoint being viewed, which carries running totals for the epoch it is
+    /// in. Only meaningful for an epoch that has started but not finished as of that checkpoint.
+    async fn latest_checkpoint(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<CheckpointSummary>, RpcError> {
+        let kv_loader: &KvLoader = ctx.data()?;
+        let contents = kv_loader
+            .load_one_checkpoint(self.scope.checkpoint_viewed_at())
+            .await
+            .context("Failed to fetch latest checkpoint contents")?;
+
+        Ok(contents.map(|(summary, _, _)| summary))
+    }
+
     async fn cp_sequence_numbers(
         &self,
         ctx: &Context<'_>,
@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context as _;
+use async_graphql::InputObject;
+use move_core_types::account_address::AccountAddress;
+use sui_pg_db::query::Query;
+use sui_sql_macro::query;
+use sui_types::{base_types::SuiAddress as NativeSuiAddress, event::Event as NativeEvent};
+
+use crate::api::scalars::{sui_address::SuiAddress, type_filter::TypeFilter};
+
+/// A filter over events, by their type and by the sender of the transaction that emitted them.
+/// Events must match every field that is set.
+#[derive(InputObject, Default, Debug, Clone, Eq, PartialEq)]
+pub(crate) struct EventFilter {
+    /// Limit to events emitted by transactions sent by this address.
+    pub sender: Option<SuiAddress>,
+
+    /// Limit to events whose type matches this filter. The filter can be one of:
+    ///
+    /// - A package address: `0x2`,
+    /// - A module: `0x2::coin`,
+    /// - A fully-qualified name: `0x2::coin::CurrencyCreated`,
+    /// - A type instantiation: `0x2::coin::CurrencyCreated<0x2::sui::SUI>`.
+    pub type_: Option<TypeFilter>,
+}
+
+impl EventFilter {
+    /// Whether `event` is one of the events this filter selects.
+    pub(crate) fn matches(&self, event: &NativeEvent) -> bool {
+        let type_ = &event.type_;
+
+        let sender = self
+            .sender
+            .is_none_or(|sender| NativeSuiAddress::from(sender) == event.sender);
+
+        let type_ = self.type_.as_ref().is_none_or(|filter| match filter {
+            TypeFilter::Package(package) => AccountAddress::from(*package) == type_.address,
+
+            TypeFilter::Module(package, module) => {
+                (AccountAddress::from(*package), module.as_str())
+                    == (type_.address, type_.module.as_str())
+            }
+
+            TypeFilter::Type(tag) if tag.type_params.is_empty() => {
+                (&tag.address, &tag.module, &tag.name)
+                    == (&type_.address, &type_.module, &type_.name)
+            }
+
+            TypeFilter::Type(tag) => tag == type_,
+        });
+
+        sender && type_
+    }
+
+    /// Conditions on `ev_struct_inst` (aliased as `e`) that select the transactions that emitted
+    /// at least one event matching this filter.
+    pub(crate) fn conditions(&self) -> anyhow::Result<Query<'static>> {
+        let mut conditions = query!("TRUE");
+
+        if let Some(sender) = self.sender {
+            conditions += query!(
+                " AND e.sender = {Bytea}",
+                NativeSuiAddress::from(sender).to_vec()
+            );
+        }
+
+        match &self.type_ {
+            None => {}
+
+            Some(TypeFilter::Package(package)) => {
+                conditions += query!(
+                    " AND e.package = {Bytea}",
+                    NativeSuiAddress::from(*package).to_vec(),
+                );
+            }
+
+            Some(TypeFilter::Module(package, module)) => {
+                conditions += query!(
+                    " AND e.package = {Bytea} AND e.module = {Text}",
+                    NativeSuiAddress::from(*package).to_vec(),
+                    module.clone(),
+                );
+            }
+
+            Some(TypeFilter::Type(tag)) => {
+                conditions += query!(
+                    " AND e.package = {Bytea} AND e.module = {Text} AND e.name = {Text}",
+                    tag.address.to_vec(),
+                    tag.module.to_string(),
+                    tag.name.to_string(),
+                );
+
+                if !tag.type_params.is_empty() {
+                    let instantiation = bcs::to_bytes(&tag.type_params)
+                        .context("Failed to serialize type parameters")?;
+                    conditions += query!(" AND e.instantiation = {Bytea}", instantiation);
+                }
+            }
+        }
+
+        Ok(conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use move_core_types::{ident_str, language_storage::StructTag};
+    use sui_types::parse_sui_struct_tag;
+
+    use super::*;
+
+    fn event(sender: &str, type_: &str) -> NativeEvent {
+        let type_: StructTag = parse_sui_struct_tag(type_).unwrap();
+        NativeEvent::new(
+            &type_.address,
+            ident_str!("m"),
+            sender.parse().unwrap(),
+            type_,
+            vec![],
+        )
+    }
+
+    fn filter(sender: Option<&str>, type_: Option<&str>) -> EventFilter {
+        EventFilter {
+            sender: sender.map(|s| s.parse().unwrap()),
+            type_: type_.map(|t| t.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_match_everything() {
+        let e = event("0x1", "0x2::coin::CurrencyCreated<0x2::sui::SUI>");
+        assert!(EventFilter::default().matches(&e));
+    }
+
+    #[test]
+    fn test_match_sender() {
+        let e = event("0x1", "0x2::coin::CurrencyCreated<0x2::sui::SUI>");
+        assert!(filter(Some("0x1"), None).matches(&e));
+        assert!(!filter(Some("0x3"), None).matches(&e));
+    }
+
+    #[test]
+    fn test_match_type() {
+        let e = event("0x1", "0x2::coin::CurrencyCreated<0x2::sui::SUI>");
+
+        assert!(filter(None, Some("0x2")).matches(&e));
+        assert!(filter(None, Some("0x2::coin")).matches(&e));
+        assert!(filter(None, Some("0x2::coin::CurrencyCreated")).matches(&e));
+        assert!(filter(None, Some("0x2::coin::CurrencyCreated<0x2::sui::SUI>")).matches(&e));
+
+        assert!(!filter(None, Some("0x3")).matches(&e));
+        assert!(!filter(None, Some("0x2::balance")).matches(&e));
+        assert!(!filter(None, Some("0x2::coin::Coin")).matches(&e));
+        assert!(!filter(None, Some("0x2::coin::CurrencyCreated<0x3::usd::USD>")).matches(&e));
+    }
+
+    #[test]
+    fn test_match_sender_and_type() {
+        let e = event("0x1", "0x2::coin::CurrencyCreated<0x2::sui::SUI>");
+        assert!(filter(Some("0x1"), Some("0x2::coin")).matches(&e));
+        assert!(!filter(Some("0x3"), Some("0x2::coin")).matches(&e));
+        assert!(!filter(Some("0x1"), Some("0x3::coin")).matches(&e));
+    }
+}
@@ -1,15 +1,21 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{ops::Range, sync::Arc};
+
 use anyhow::Context as _;
-use async_graphql::Object;
+use async_graphql::{dataloader::DataLoader, Context, Object};
+use diesel::{prelude::QueryableByName, sql_types::BigInt};
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_reader::{kv_loader::KvLoader, pg_reader::PgReader, tx_digests::TxDigestKey};
+use sui_sql_macro::query;
 use sui_types::{
     base_types::SuiAddress as NativeSuiAddress, digests::TransactionDigest,
     event::Event as NativeEvent,
 };
 
 use crate::{
-    api::scalars::{base64::Base64, date_time::DateTime, uint53::UInt53},
+    api::scalars::{base64::Base64, cursor::JsonCursor, date_time::DateTime, uint53::UInt53},
     error::RpcError,
     scope::Scope,
 };
@@ -18,6 +24,10 @@ use super::{
     address::Address, move_type::MoveType, move_value::MoveValue, transaction::Transaction,
 };
 
+use self::filter::EventFilter;
+
+pub(crate) mod filter;
+
 #[derive(Clone)]
 pub(crate) struct Event {
     pub(crate) scope: Scope,
@@ -30,6 +40,25 @@ pub(crate) struct Event {
     pub(crate) timestamp_ms: u64,
 }
 
+/// Cursor over events across transactions.
+pub(crate) type CEvent = JsonCursor<EventCursor>;
+
+/// The position of an event among all events, identified by the sequence number of the
+/// transaction that emitted it, and its position among that transaction's events.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct EventCursor {
+    #[serde(rename = "t")]
+    pub(crate) tx_sequence_number: u64,
+    #[serde(rename = "e")]
+    pub(crate) ev_sequence_number: u64,
+}
+
+#[derive(QueryableByName)]
+struct TxSequenceNumber {
+    #[diesel(sql_type = BigInt, column_name = "tx_sequence_number")]
+    tx_sequence_number: i64,
+}
+
 // TODO(DVX-1200): Support sendingModule - MoveModule
 #[Object]
 impl Event {
@@ -77,3 +106,121 @@ impl Event {
         ))
     }
 }
+
+impl Event {
+    /// Events matching `filter`, emitted by up to `limit` transactions with sequence numbers in
+    /// `tx_bounds`, that come strictly after the `after` cursor (if one is provided), in order.
+    ///
+    /// Transactions are found through the `ev_struct_inst` index, which records every event's
+    /// type and sender, and their events are then read from the transactions themselves.
+    ///
+    /// Returns the events with their cursors, and the sequence number of the first transaction in
+    /// `tx_bounds` that has not been scanned yet, which is `tx_bounds.end` if all were scanned.
+    pub(crate) async fn scan<E: std::error::Error>(
+        ctx: &Context<'_>,
+        scope: &Scope,
+        filter: &EventFilter,
+        after: Option<EventCursor>,
+        tx_bounds: Range<u64>,
+        limit: usize,
+    ) -> Result<(Vec<(EventCursor, Event)>, u64), RpcError<E>> {
+        let pg_reader: &PgReader = ctx.data()?;
+        let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+        let kv_loader: &KvLoader = ctx.data()?;
+
+        let query = query!(
+            r#"
+            SELECT DISTINCT
+                e.tx_sequence_number
+            FROM
+                ev_struct_inst e
+            WHERE
+                {}
+            AND {BigInt} <= e.tx_sequence_number
+            AND e.tx_sequence_number < {BigInt}
+            ORDER BY
+                e.tx_sequence_number
+            LIMIT {BigInt}
+            "#,
+            filter.conditions()?,
+            tx_bounds.start as i64,
+            tx_bounds.end as i64,
+            limit as i64,
+        );
+
+        let mut conn = pg_reader
+            .connect()
+            .await
+            .context("Failed to connect to database")?;
+
+        let results: Vec<TxSequenceNumber> = conn
+            .results(query)
+            .await
+            .context("Failed to fetch transactions with matching events")?;
+
+        let tx_sequence_numbers: Vec<u64> = results
+            .into_iter()
+            .map(|r| r.tx_sequence_number as u64)
+            .collect();
+
+        // If the limit was hit, there may be more matching transactions after the last one.
+        let scanned_hi = match tx_sequence_numbers.last() {
+            Some(&last) if tx_sequence_numbers.len() == limit => last + 1,
+            _ => tx_bounds.end,
+        };
+
+        let digests = pg_loader
+            .load_many(tx_sequence_numbers.iter().map(|&t| TxDigestKey(t)))
+            .await
+            .context("Failed to load transaction digests")?;
+
+        let mut tx_digests = vec![];
+        for &tx_sequence_number in &tx_sequence_numbers {
+            let stored = digests
+                .get(&TxDigestKey(tx_sequence_number))
+                .context("Missing transaction digest")?;
+
+            let digest = TransactionDigest::try_from(stored.tx_digest.clone())
+                .context("Failed to deserialize transaction digest")?;
+
+            tx_digests.push((tx_sequence_number, digest));
+        }
+
+        let contents = kv_loader
+            .load_many_transaction_events(tx_digests.iter().map(|(_, d)| *d).collect())
+            .await
+            .context("Failed to load transaction events")?;
+
+        let mut events = vec![];
+        for (tx_sequence_number, transaction_digest) in tx_digests {
+            let content = contents
+                .get(&transaction_digest)
+                .context("Missing transaction events")?;
+
+            let timestamp_ms = content.timestamp_ms();
+            for (i, native) in content.events()?.into_iter().enumerate() {
+                let cursor = EventCursor {
+                    tx_sequence_number,
+                    ev_sequence_number: i as u64,
+                };
+
+                if after.is_some_and(|after| cursor <= after) || !filter.matches(&native) {
+                    continue;
+                }
+
+                events.push((
+                    cursor,
+                    Event {
+                        scope: scope.clone(),
+                        native,
+                        transaction_digest,
+                        sequence_number: i as u64,
+                        timestamp_ms,
+                    },
+                ));
+            }
+        }
+
+        Ok((events, scanned_hi))
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use async_graphql::{dataloader::DataLoader, Context, Object, SimpleObject};
+use futures::future::try_join_all;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde::{de::DeserializeOwned, Deserialize};
+use sui_indexer_alt_reader::{
+    consistent_reader::{self, ConsistentReader},
+    kv_loader::KvLoader,
+    objects::VersionedObjectKey,
+    pg_reader::PgReader,
+    transfer_policies::TransferPolicyKey,
+};
+use sui_types::{
+    balance::Balance,
+    base_types::{ObjectID, SuiAddress as NativeSuiAddress},
+    collection_types::VecSet,
+    dynamic_field::{DOFWrapper, Field},
+    id::{ID, UID},
+    object::Object as NativeObject,
+    SUI_FRAMEWORK_ADDRESS,
+};
+use tokio::sync::OnceCell;
+
+use crate::{
+    api::scalars::{
+        big_int::BigInt, owner_kind::OwnerKind, sui_address::SuiAddress, uint53::UInt53,
+    },
+    config::Limits,
+    error::{feature_unavailable, out_of_retention, resource_exhausted, RpcError},
+    scope::Scope,
+};
+
+use super::{
+    move_type::MoveType,
+    object::{self, Object},
+};
+
+/// Types of the dynamic fields a kiosk stores its items, listings and locks in.
+const ITEM_FIELD_TYPE: &str =
+    "0x2::dynamic_field::Field<0x2::dynamic_object_field::Wrapper<0x2::kiosk::Item>,0x2::object::ID>";
+const LISTING_FIELD_TYPE: &str = "0x2::dynamic_field::Field<0x2::kiosk::Listing,u64>";
+const LOCK_FIELD_TYPE: &str = "0x2::dynamic_field::Field<0x2::kiosk::Lock,bool>";
+
+pub(crate) struct Kiosk {
+    scope: Scope,
+    object: Object,
+    native: NativeKiosk,
+    items: OnceCell<Vec<KioskItem>>,
+}
+
+/// An item placed in a kiosk.
+#[derive(Clone, SimpleObject)]
+pub(crate) struct KioskItem {
+    /// The item's ID.
+    pub address: SuiAddress,
+
+    /// The item, as of the checkpoint being viewed.
+    pub object: Option<Object>,
+
+    /// Whether the item is locked in the kiosk, so that it can only be taken out by purchasing it.
+    pub is_locked: bool,
+
+    /// The item's listing, if it is listed for sale.
+    pub listing: Option<KioskListing>,
+}
+
+/// An offer to sell an item in a kiosk.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub(crate) struct KioskListing {
+    /// The price the item is listed at, in MIST.
+    pub price: BigInt,
+
+    /// Whether the item is listed exclusively, through a purchase cap, rather than publicly.
+    pub is_exclusive: bool,
+}
+
+/// A `0x2::transfer_policy::TransferPolicy`, which sets the rules that must be followed to complete a purchase of an item of a given type from a kiosk.
+#[derive(SimpleObject)]
+pub(crate) struct TransferPolicy {
+    /// The policy's ID.
+    pub address: SuiAddress,
+
+    /// The type of item the policy applies to.
+    pub item_type: MoveType,
+
+    /// The types of the rules that must be followed to complete a purchase.
+    pub rules: Vec<String>,
+
+    /// Fees collected by the policy, in MIST.
+    pub balance: BigInt,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Kiosk has more than {0} entries of a kind")]
+pub(crate) struct KioskTooBigError(usize);
+
+/// Rust version of the Move `sui::kiosk::Kiosk` type.
+#[derive(Deserialize)]
+struct NativeKiosk {
+    id: UID,
+    profits: Balance,
+    owner: NativeSuiAddress,
+    item_count: u32,
+    allow_extensions: bool,
+}
+
+/// Rust versions of the keys of the dynamic fields a kiosk stores its items, listings and locks
+/// in, each identifying an item.
+#[derive(Deserialize)]
+struct Item {
+    id: ID,
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    id: ID,
+    is_exclusive: bool,
+}
+
+#[derive(Deserialize)]
+struct Lock {
+    id: ID,
+}
+
+/// Rust version of the Move `sui::transfer_policy::TransferPolicy` type.
+#[derive(Deserialize)]
+struct NativeTransferPolicy {
+    id: UID,
+    balance: Balance,
+    rules: VecSet<TypeName>,
+}
+
+/// Rust version of the Move `std::type_name::TypeName` type.
+#[derive(Deserialize)]
+struct TypeName {
+    name: String,
+}
+
+/// A `0x2::kiosk::Kiosk`, an object that holds items for its owner, and can list them for sale.
+///
+/// A kiosk's items, listings, and locks are stored in its dynamic fields, so they are read by walking them in the live object set, as of the checkpoint being viewed.
+#[Object]
+impl Kiosk {
+    /// The kiosk's ID.
+    async fn address(&self) -> SuiAddress {
+        self.native.id.id.bytes.into()
+    }
+
+    /// The kiosk, as an object.
+    async fn as_object(&self) -> &Object {
+        &self.object
+    }
+
+    /// The address that owns the kiosk.
+    async fn owner(&self) -> SuiAddress {
+        self.native.owner.into()
+    }
+
+    /// The number of items in the kiosk.
+    async fn item_count(&self) -> UInt53 {
+        (self.native.item_count as u64).into()
+    }
+
+    /// Proceeds from sales that have not been withdrawn yet, in MIST.
+    async fn profits(&self) -> BigInt {
+        self.native.profits.value().into()
+    }
+
+    /// Whether extensions can be added to the kiosk.
+    async fn allow_extensions(&self) -> bool {
+        self.native.allow_extensions
+    }
+
+    /// The items in the kiosk, ordered by ID.
+    async fn items(&self, ctx: &Context<'_>) -> Result<&Vec<KioskItem>, RpcError<object::Error>> {
+        self.load_items(ctx).await
+    }
+
+    /// The items in the kiosk that are listed for sale, ordered by ID.
+    async fn listings(&self, ctx: &Context<'_>) -> Result<Vec<KioskItem>, RpcError<object::Error>> {
+        Ok(self
+            .load_items(ctx)
+            .await?
+            .iter()
+            .filter(|item| item.listing.is_some())
+            .cloned()
+            .collect())
+    }
+
+    /// The transfer policies for the types of the items in the kiosk, which set the rules for purchasing them, ordered by item type, and then by ID.
+    async fn policies(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<TransferPolicy>, RpcError<object::Error>> {
+        let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+        let limits: &Limits = ctx.data()?;
+
+        // The contents of all the items, and then the policies for all their types, are each
+        // fetched in a single batch.
+        let items = self.load_items(ctx).await?;
+        let contents = try_join_all(
+            items
+                .iter()
+                .filter_map(|item| item.object.as_ref())
+                .map(|object| object.contents(ctx)),
+        )
+        .await?;
+
+        let item_types: BTreeSet<_> = contents
+            .into_iter()
+            .flatten()
+            .filter_map(|contents| contents.data.try_as_move())
+            .map(|move_object| StructTag::from(move_object.type_().clone()))
+            .collect();
+
+        let mut stored = pg_loader
+            .load_many(item_types.iter().cloned().map(TransferPolicyKey))
+            .await
+            .context("Failed to fetch transfer policies")?;
+
+        let mut ids = vec![];
+        for item_type in &item_types {
+            let Some(policies) = stored.remove(&TransferPolicyKey(item_type.clone())) else {
+                continue;
+            };
+
+            if policies.len() > limits.max_kiosk_entries {
+                return Err(resource_exhausted(KioskTooBigError(
+                    limits.max_kiosk_entries,
+                )));
+            }
+
+            for policy in policies {
+                let id = ObjectID::from_bytes(&policy.object_id)
+                    .context("Failed to deserialize transfer policy ID")?;
+                ids.push((item_type, id));
+            }
+        }
+
+        // Policies are found among the latest live objects, so read them as of the checkpoint
+        // being viewed, skipping any that did not exist yet.
+        let checkpoint: UInt53 = self.scope.checkpoint_viewed_at().into();
+        let objects = try_join_all(ids.iter().map(|(_, id)| {
+            Object::checkpoint_bounded(ctx, self.scope.clone(), (*id).into(), checkpoint)
+        }))
+        .await?;
+
+        let contents = try_join_all(objects.iter().map(|object| async move {
+            match object {
+                Some(object) => object.contents(ctx).await.map(Option::as_ref),
+                None => Ok(None),
+            }
+        }))
+        .await?;
+
+        let mut policies = vec![];
+        for ((item_type, _), contents) in ids.into_iter().zip(contents) {
+            let Some(contents) = contents else {
+                continue;
+            };
+
+            let native: NativeTransferPolicy = deserialize(contents)?;
+            policies.push(TransferPolicy {
+                address: native.id.id.bytes.into(),
+                item_type: MoveType::from_native(
+                    TypeTag::Struct(Box::new(item_type.clone())),
+                    self.scope.clone(),
+                ),
+                rules: native.rules.contents.into_iter().map(|r| r.name).collect(),
+                balance: native.balance.value().into(),
+            });
+        }
+
+        Ok(policies)
+    }
+}
+
+impl Kiosk {
+    /// Fetch the kiosk at `address`, as of the checkpoint being viewed. Returns `None` if there is
+    /// no object at this address, or it is not a kiosk.
+    pub(crate) async fn fetch(
+        ctx: &Context<'_>,
+        scope: Scope,
+        address: SuiAddress,
+    ) -> Result<Option<Self>, RpcError<object::Error>> {
+        let checkpoint: UInt53 = scope.checkpoint_viewed_at().into();
+        let Some(object) =
+            Object::checkpoint_bounded(ctx, scope.clone(), address, checkpoint).await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(contents) = object.contents(ctx).await? else {
+            return Ok(None);
+        };
+
+        let is_kiosk = contents
+            .data
+            .try_as_move()
+            .is_some_and(|o| StructTag::from(o.type_().clone()) == kiosk_type());
+
+        if !is_kiosk {
+            return Ok(None);
+        }
+
+        let native: NativeKiosk = deserialize(contents)?;
+        Ok(Some(Self {
+            scope,
+            object,
+            native,
+            items: OnceCell::new(),
+        }))
+    }
+
+    /// Read the kiosk's items, along with their listings and locks, from its dynamic fields,
+    /// lazily loading them if necessary.
+    async fn load_items(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<&Vec<KioskItem>, RpcError<object::Error>> {
+        self.items
+            .get_or_try_init(async || {
+                let kiosk = self.native.id.id.bytes.into();
+                let (items, listings, locks) = futures::try_join!(
+                    dynamic_fields(ctx, &self.scope, kiosk, ITEM_FIELD_TYPE),
+                    dynamic_fields(ctx, &self.scope, kiosk, LISTING_FIELD_TYPE),
+                    dynamic_fields(ctx, &self.scope, kiosk, LOCK_FIELD_TYPE),
+                )?;
+
+                let mut listed = BTreeMap::new();
+                for field in &listings {
+                    let field: Field<Listing, u64> = deserialize(field)?;
+                    listed.insert(
+                        field.name.id.bytes,
+                        KioskListing {
+                            price: field.value.into(),
+                            is_exclusive: field.name.is_exclusive,
+                        },
+                    );
+                }
+
+                let mut locked = BTreeSet::new();
+                for field in &locks {
+                    let field: Field<Lock, bool> = deserialize(field)?;
+                    locked.insert(field.name.id.bytes);
+                }
+
+                let mut ids = BTreeSet::new();
+                for field in &items {
+                    let field: Field<DOFWrapper<Item>, ID> = deserialize(field)?;
+                    ids.insert(field.name.name.id.bytes);
+                }
+
+                let checkpoint: UInt53 = self.scope.checkpoint_viewed_at().into();
+                let objects = try_join_all(ids.iter().map(|id| {
+                    Object::checkpoint_bounded(ctx, self.scope.clone(), (*id).into(), checkpoint)
+                }))
+                .await?;
+
+                Ok(ids
+                    .into_iter()
+                    .zip(objects)
+                    .map(|(id, object)| KioskItem {
+                        address: id.into(),
+                        object,
+                        is_locked: locked.contains(&id),
+                        listing: listed.remove(&id),
+                    })
+                    .collect())
+            })
+            .await
+    }
+}
+
+/// The type of `0x2::kiosk::Kiosk`.
+fn kiosk_type() -> StructTag {
+    StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: "kiosk".parse().unwrap(),
+        name: "Kiosk".parse().unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Load the contents of every dynamic field with type `type_` owned by the kiosk at `kiosk`, as of
+/// the checkpoint being viewed. Fails if there are more of them than the `max_kiosk_entries`
+/// limit.
+///
+/// Fields are listed a page at a time, in pages of at most `max_page_size` entries. Listing stops
+/// with an error once it has fetched as many pages as it would take to exceed the
+/// `max_kiosk_entries` limit, even if the store returned smaller pages than requested.
+async fn dynamic_fields(
+    ctx: &Context<'_>,
+    scope: &Scope,
+    kiosk: NativeSuiAddress,
+    type_: &str,
+) -> Result<Vec<NativeObject>, RpcError<object::Error>> {
+    let consistent_reader: &ConsistentReader = ctx.data()?;
+    let kv_loader: &KvLoader = ctx.data()?;
+    let limits: &Limits = ctx.data()?;
+
+    let checkpoint = scope.checkpoint_viewed_at();
+    let max_entries = limits.max_kiosk_entries.saturating_add(1);
+    let page_size = max_entries.min(limits.max_page_size.max(1) as usize);
+    let max_pages = max_entries.div_ceil(page_size);
+
+    let mut keys = vec![];
+    let mut after = None;
+    let mut pages = 0;
+    loop {
+        pages += 1;
+        let page = consistent_reader
+            .list_owned_objects(
+                checkpoint,
+                OwnerKind::Object.into(),
+                Some(kiosk.to_string()),
+                Some(type_.to_owned()),
+                Some(page_size as u32),
+                after,
+                None,
+                true,
+            )
+            .await
+            .map_err(|e| match e {
+                consistent_reader::Error::NotConfigured => feature_unavailable("fetching kiosks"),
+
+                consistent_reader::Error::OutOfRange(_) => {
+                    out_of_retention(object::Error::OutOfRange(checkpoint))
+                }
+
+                consistent_reader::Error::Internal(error) => {
+                    error.context("Failed to fetch live objects").into()
+                }
+            })?;
+
+        after = page.results.last().map(|edge| edge.token.clone());
+        for edge in page.results {
+            let (id, version, _) = edge.value;
+            keys.push(VersionedObjectKey(id, version.value()));
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+
+        if keys.len() > limits.max_kiosk_entries || pages >= max_pages {
+            return Err(resource_exhausted(KioskTooBigError(
+                limits.max_kiosk_entries,
+            )));
+        }
+    }
+
+    if keys.len() > limits.max_kiosk_entries {
+        return Err(resource_exhausted(KioskTooBigError(
+            limits.max_kiosk_entries,
+        )));
+    }
+
+    let mut objects = kv_loader
+        .load_many_objects(keys.clone())
+        .await
+        .context("Failed to fetch object contents")?;
+
+    Ok(keys.iter().filter_map(|key| objects.remove(key)).collect())
+}
+
+/// Deserialize the contents of a Move object, as a `T`.
+fn deserialize<T: DeserializeOwned>(object: &NativeObject) -> Result<T, RpcError<object::Error>> {
+    let move_object = object
+        .data
+        .try_as_move()
+        .with_context(|| format!("{} is not a Move object", object.id()))?;
+
+    Ok(bcs::from_bytes(move_object.contents())
+        .with_context(|| format!("Failed to deserialize {}", object.id()))?)
+}
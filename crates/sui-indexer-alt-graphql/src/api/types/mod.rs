@@ -2,15 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub(crate) mod address;
+pub(crate) mod address_activity;
+pub(crate) mod available_range;
 pub(crate) mod balance_change;
 pub(crate) mod checkpoint;
+pub(crate) mod coin_metadata;
+pub(crate) mod display;
 pub(crate) mod epoch;
 pub(crate) mod event;
 pub(crate) mod execution_error;
 pub(crate) mod gas;
 pub(crate) mod gas_effects;
 pub(crate) mod gas_input;
+pub(crate) mod kiosk;
 mod linkage;
+pub(crate) mod move_function;
+pub(crate) mod move_module;
 pub(crate) mod move_object;
 pub(crate) mod move_package;
 pub(crate) mod move_type;
@@ -18,9 +25,12 @@ pub(crate) mod move_value;
 pub(crate) mod object;
 pub(crate) mod object_change;
 pub(crate) mod object_filter;
+pub(crate) mod open_move_type;
 pub(crate) mod protocol_configs;
+pub(crate) mod representation;
 pub(crate) mod safe_mode;
 pub(crate) mod service_config;
+pub(crate) mod service_status;
 mod stake_subsidy;
 pub(crate) mod storage_fund;
 pub(crate) mod system_parameters;
@@ -33,3 +43,4 @@ pub(crate) mod unchanged_consensus_object;
 pub(crate) mod user_signature;
 pub(crate) mod validator_aggregated_signature;
 pub(crate) mod validator_set;
+pub(crate) mod zklogin;
@@ -0,0 +1,122 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_graphql::{Enum, Object, SimpleObject};
+use move_binary_format::file_format::Visibility;
+use sui_package_resolver::FunctionDef;
+
+use super::{
+    move_module::MoveModule,
+    move_type::{abilities, MoveAbility},
+    open_move_type::OpenMoveType,
+};
+
+pub(crate) struct MoveFunction {
+    /// The module that this function was defined in.
+    module: MoveModule,
+
+    /// The function's unqualified name.
+    name: String,
+
+    /// The function's signature, deserialized from its module's bytecode.
+    def: FunctionDef,
+}
+
+/// The visibility modifier describes which modules can access this function.
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum MoveVisibility {
+    /// A friend function can be accessed by modules in the same package.
+    Friend,
+    /// A private function can only be accessed from within the module in which it was defined.
+    Private,
+    /// A public function can be accessed by any module.
+    Public,
+}
+
+/// Declaration of a type parameter on a Move function.
+#[derive(SimpleObject)]
+pub(crate) struct MoveFunctionTypeParameter {
+    /// Ability constraints on this type parameter.
+    constraints: Vec<MoveAbility>,
+}
+
+/// Signature of a function, defined in a Move module.
+#[Object]
+impl MoveFunction {
+    /// The module that this function was defined in.
+    async fn module(&self) -> &MoveModule {
+        &self.module
+    }
+
+    /// The function's unqualified name.
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The function's visibility: `public`, `public(friend)`, or `private`.
+    async fn visibility(&self) -> Option<MoveVisibility> {
+        Some(self.def.visibility.into())
+    }
+
+    /// Whether the function has the `entry` modifier or not.
+    async fn is_entry(&self) -> Option<bool> {
+        Some(self.def.is_entry)
+    }
+
+    /// Constraints on the function's formal type parameters.
+    ///
+    /// Move bytecode does not name type parameters, so when they are referenced (e.g. in parameter and return types), they are identified by their index in this list.
+    async fn type_parameters(&self) -> Option<Vec<MoveFunctionTypeParameter>> {
+        let type_params = self
+            .def
+            .type_params
+            .iter()
+            .map(|set| MoveFunctionTypeParameter {
+                constraints: abilities(*set),
+            })
+            .collect();
+
+        Some(type_params)
+    }
+
+    /// The function's parameter types. These types can reference type parameters introduced by this function (see `typeParameters`).
+    async fn parameters(&self) -> Option<Vec<OpenMoveType>> {
+        let parameters = self
+            .def
+            .parameters
+            .iter()
+            .map(|signature| OpenMoveType::from(signature.clone()))
+            .collect();
+
+        Some(parameters)
+    }
+
+    /// The function's return types. There can be multiple because functions in Move can return multiple values. These types can reference type parameters introduced by this function (see `typeParameters`).
+    #[graphql(name = "return")]
+    async fn return_(&self) -> Option<Vec<OpenMoveType>> {
+        let return_ = self
+            .def
+            .return_
+            .iter()
+            .map(|signature| OpenMoveType::from(signature.clone()))
+            .collect();
+
+        Some(return_)
+    }
+}
+
+impl MoveFunction {
+    pub(crate) fn new(module: MoveModule, name: String, def: FunctionDef) -> Self {
+        Self { module, name, def }
+    }
+}
+
+impl From<Visibility> for MoveVisibility {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::Public => MoveVisibility::Public,
+            Visibility::Private => MoveVisibility::Private,
+            Visibility::Friend => MoveVisibility::Friend,
+        }
+    }
+}
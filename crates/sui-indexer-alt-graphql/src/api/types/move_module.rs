@@ -0,0 +1,71 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use async_graphql::Object;
+use sui_package_resolver::Package as ParsedPackage;
+
+use crate::error::RpcError;
+
+use super::{move_function::MoveFunction, move_package::MovePackage};
+
+#[derive(Clone)]
+pub(crate) struct MoveModule {
+    /// The package that this module was defined in.
+    package: MovePackage,
+
+    /// The module's unqualified name.
+    name: String,
+
+    /// The package this module belongs to, deserialized.
+    parsed: Arc<ParsedPackage>,
+}
+
+/// Modules are a unit of code organization in Move. Modules belong to packages, and contain type and function definitions.
+#[Object]
+impl MoveModule {
+    /// The module's unqualified name.
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The package that this module was defined in.
+    async fn package(&self) -> &MovePackage {
+        &self.package
+    }
+
+    /// The function named `name` in this module, if it exists.
+    async fn function(&self, name: String) -> Result<Option<MoveFunction>, RpcError> {
+        let module = self
+            .parsed
+            .module(&self.name)
+            .context("Failed to find module in parsed package")?;
+
+        let Some(def) = module
+            .function_def(&name)
+            .with_context(|| format!("Failed to deserialize function {}", name))?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(MoveFunction::new(self.clone(), name, def)))
+    }
+}
+
+impl MoveModule {
+    /// Construct a module called `name` from the `parsed` representation of `package`. Returns
+    /// `None` if the package does not contain a module by that name.
+    pub(crate) fn with_name(
+        package: MovePackage,
+        parsed: Arc<ParsedPackage>,
+        name: String,
+    ) -> Option<Self> {
+        parsed.modules().contains_key(&name).then_some(Self {
+            package,
+            name,
+            parsed,
+        })
+    }
+}
@@ -15,10 +15,12 @@ use crate::{
 
 use super::{
     address::AddressableImpl,
+    display::{self, DisplayEntry},
     move_type::MoveType,
     move_value::MoveValue,
     object::{self, CLive, CVersion, Object, ObjectImpl, VersionFilter},
     object_filter::{ObjectFilter, Validator as OFValidator},
+    representation::Representation,
     transaction::Transaction,
 };
 
@@ -39,7 +41,13 @@ pub(crate) struct MoveObject {
     field(
         name = "contents",
         ty = "Result<Option<MoveValue>, RpcError<object::Error>>",
-        desc = "The structured representation of the object's contents."
+        desc = "The structured representation of the object's contents.\n\n`representation` selects whether the contents are offered as BCS, JSON, or both (the default).",
+        arg(name = "representation", ty = "Option<Representation>")
+    ),
+    field(
+        name = "display",
+        ty = "Result<Option<Vec<DisplayEntry>>, RpcError<object::Error>>",
+        desc = "The object's Display, rendered from the latest `0x2::display::Display` template for its type, with values substituted from the object's contents at this version.\n\n`null` if there is no Display template for the object's type."
     ),
     field(
         name = "move_object_bcs",
@@ -73,11 +81,26 @@ impl MoveObject {
     }
 
     /// The structured representation of the object's contents.
+    ///
+    /// `representation` selects whether the contents are offered as BCS, JSON, or both (the default).
     pub(crate) async fn contents(
         &self,
         ctx: &Context<'_>,
+        representation: Option<Representation>,
     ) -> Result<Option<MoveValue>, RpcError<object::Error>> {
-        MoveObjectImpl(self).contents(ctx).await
+        MoveObjectImpl(self)
+            .contents(ctx, representation.unwrap_or_default())
+            .await
+    }
+
+    /// The object's Display, rendered from the latest `0x2::display::Display` template for its type, with values substituted from the object's contents at this version.
+    ///
+    /// `null` if there is no Display template for the object's type.
+    pub(crate) async fn display(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<Vec<DisplayEntry>>, RpcError<object::Error>> {
+        MoveObjectImpl(self).display(ctx).await
     }
 
     /// The Base64-encoded BCS serialize of this object, as a `MoveObject`.
@@ -111,6 +134,21 @@ impl MoveObject {
         ObjectImpl::from(&self.super_).object_bcs(ctx).await
     }
 
+    /// Paginate all versions of this object, including this one.
+    pub(crate) async fn object_versions(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u64>,
+        after: Option<CVersion>,
+        last: Option<u64>,
+        before: Option<CVersion>,
+        filter: Option<VersionFilter>,
+    ) -> Result<Connection<String, Object>, RpcError<object::Error>> {
+        ObjectImpl::from(&self.super_)
+            .object_versions(ctx, first, after, last, before, filter)
+            .await
+    }
+
     /// Paginate all versions of this object after this one.
     pub(crate) async fn object_versions_after(
         &self,
@@ -223,6 +261,7 @@ impl MoveObjectImpl<'_> {
     pub(crate) async fn contents(
         &self,
         ctx: &Context<'_>,
+        representation: Representation,
     ) -> Result<Option<MoveValue>, RpcError<object::Error>> {
         let Some(native) = self.0.native(ctx).await? else {
             return Ok(None);
@@ -233,7 +272,20 @@ impl MoveObjectImpl<'_> {
             self.0.super_.super_.scope.clone(),
         );
 
-        Ok(Some(MoveValue::new(type_, native.contents().to_owned())))
+        Ok(Some(
+            MoveValue::new(type_, native.contents().to_owned()).with_representation(representation),
+        ))
+    }
+
+    pub(crate) async fn display(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<Vec<DisplayEntry>>, RpcError<object::Error>> {
+        let Some(native) = self.0.native(ctx).await? else {
+            return Ok(None);
+        };
+
+        display::render(ctx, &self.0.super_.super_.scope, native).await
     }
 
     pub(crate) async fn move_object_bcs(
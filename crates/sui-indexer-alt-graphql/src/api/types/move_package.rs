@@ -18,6 +18,7 @@ use sui_indexer_alt_reader::{
     pg_reader::PgReader,
 };
 use sui_indexer_alt_schema::{packages::StoredPackage, schema::kv_packages};
+use sui_package_resolver::Package as ParsedPackage;
 use sui_pg_db::sql;
 use sui_sql_macro::query;
 use sui_types::{
@@ -41,6 +42,7 @@ use crate::{
 use super::{
     address::AddressableImpl,
     linkage::Linkage,
+    move_module::MoveModule,
     move_object::MoveObject,
     object::{self, CLive, CVersion, Object, ObjectImpl, VersionFilter},
     object_filter::{ObjectFilter, Validator as OFValidator},
@@ -48,6 +50,7 @@ use super::{
     type_origin::TypeOrigin,
 };
 
+#[derive(Clone)]
 pub(crate) struct MovePackage {
     /// Representation of this Move Package as a generic Object.
     super_: Object,
@@ -139,6 +142,14 @@ impl MovePackage {
         ObjectImpl::from(&self.super_).digest()
     }
 
+    /// The module named `name` in this package.
+    async fn module(&self, name: String) -> Result<Option<MoveModule>, RpcError> {
+        let parsed = ParsedPackage::read_from_package(&self.native)
+            .context("Failed to deserialize package")?;
+
+        Ok(MoveModule::with_name(self.clone(), Arc::new(parsed), name))
+    }
+
     /// BCS representation of the package's modules.  Modules appear as a sequence of pairs (module
     /// name, followed by module bytes), in alphabetic order by module name.
     async fn module_bcs(&self) -> Result<Option<Base64>, RpcError> {
@@ -169,6 +180,21 @@ impl MovePackage {
         ObjectImpl::from(&self.super_).object_bcs(ctx).await
     }
 
+    /// Paginate all versions of this package treated as an object, including this one.
+    pub(crate) async fn object_versions(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u64>,
+        after: Option<CVersion>,
+        last: Option<u64>,
+        before: Option<CVersion>,
+        filter: Option<VersionFilter>,
+    ) -> Result<Connection<String, Object>, RpcError<object::Error>> {
+        ObjectImpl::from(&self.super_)
+            .object_versions(ctx, first, after, last, before, filter)
+            .await
+    }
+
     /// Paginate all versions of this package treated as an object, after this one.
     pub(crate) async fn object_versions_after(
         &self,
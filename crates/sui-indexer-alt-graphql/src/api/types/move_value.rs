@@ -12,11 +12,12 @@ use crate::{
     error::{resource_exhausted, RpcError},
 };
 
-use super::move_type::MoveType;
+use super::{move_type::MoveType, representation::Representation};
 
 pub(crate) struct MoveValue {
     type_: MoveType,
     native: Vec<u8>,
+    representation: Representation,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -26,8 +27,12 @@ pub(crate) struct MoveValueTooBigError;
 #[Object]
 impl MoveValue {
     /// The BCS representation of this value, Base64-encoded.
+    ///
+    /// `null` if the value was fetched with a `representation` that excludes BCS.
     async fn bcs(&self) -> Option<Base64> {
-        Some(Base64::from(self.native.clone()))
+        self.representation
+            .includes_bcs()
+            .then(|| Base64::from(self.native.clone()))
     }
 
     /// Representation of a Move value in JSON, where:
@@ -41,7 +46,13 @@ impl MoveValue {
     /// - Structs are represented by JSON objects.
     /// - Enums are represented by JSON objects, with a field named `@variant` containing the variant name.
     /// - Empty optional values are represented by `null`.
+    ///
+    /// `null` if the value was fetched with a `representation` that excludes JSON, in which case the value is not rendered.
     async fn json(&self, ctx: &Context<'_>) -> Result<Option<Json>, RpcError> {
+        if !self.representation.includes_json() {
+            return Ok(None);
+        }
+
         let limits: &Limits = ctx.data()?;
 
         let Some(layout) = self.type_.layout_impl().await? else {
@@ -63,7 +74,17 @@ impl MoveValue {
 
 impl MoveValue {
     pub(crate) fn new(type_: MoveType, native: Vec<u8>) -> Self {
-        Self { type_, native }
+        Self {
+            type_,
+            native,
+            representation: Representation::Both,
+        }
+    }
+
+    /// Only offer this value in the given representations.
+    pub(crate) fn with_representation(mut self, representation: Representation) -> Self {
+        self.representation = representation;
+        self
     }
 }
 
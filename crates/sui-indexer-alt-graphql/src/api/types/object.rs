@@ -38,7 +38,7 @@ use crate::{
         sui_address::SuiAddress,
         uint53::UInt53,
     },
-    error::{bad_user_input, feature_unavailable, RpcError},
+    error::{bad_cursor, bad_user_input, feature_unavailable, out_of_retention, RpcError},
     intersect,
     pagination::{Page, PaginationConfig},
     scope::Scope,
@@ -46,7 +46,8 @@ use crate::{
 
 use super::{
     address::{Address, AddressableImpl},
-    move_object::MoveObject,
+    display::DisplayEntry,
+    move_object::{MoveObject, MoveObjectImpl},
     move_package::MovePackage,
     object_filter::{ObjectFilter, Validator as OFValidator},
     transaction::Transaction,
@@ -80,6 +81,16 @@ use super::{
         ty = "Result<Option<Base64>, RpcError>",
         desc = "The Base64-encoded BCS serialization of this object, as an `Object`."
     ),
+    field(
+        name = "object_versions",
+        arg(name = "first", ty = "Option<u64>"),
+        arg(name = "after", ty = "Option<CVersion>"),
+        arg(name = "last", ty = "Option<u64>"),
+        arg(name = "before", ty = "Option<CVersion>"),
+        arg(name = "filter", ty = "Option<VersionFilter>"),
+        ty = "Result<Option<Connection<String, Object>>, RpcError<Error>>",
+        desc = "Paginate all versions of this object, including this one."
+    ),
     field(
         name = "object_versions_after",
         arg(name = "first", ty = "Option<u64>"),
@@ -205,6 +216,20 @@ impl Object {
         MoveObject::from_object(self, ctx).await
     }
 
+    /// The object's Display, rendered from the latest `0x2::display::Display` template for its type, with values substituted from the object's contents at this version.
+    ///
+    /// `null` if the object is not a Move object, or there is no Display template for its type.
+    async fn display(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<Vec<DisplayEntry>>, RpcError<Error>> {
+        let Some(object) = MoveObject::from_object(self, ctx).await? else {
+            return Ok(None);
+        };
+
+        MoveObjectImpl(&object).display(ctx).await
+    }
+
     /// Attempts to convert the object into a MovePackage.
     async fn as_move_package(
         &self,
@@ -233,6 +258,23 @@ impl Object {
         ObjectImpl::from(self).object_bcs(ctx).await
     }
 
+    /// Paginate all versions of this object, including this one.
+    ///
+    /// Each version links to the transaction that created it, via `previousTransaction`, so the object's lineage can be walked in a single query.
+    async fn object_versions(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u64>,
+        after: Option<CVersion>,
+        last: Option<u64>,
+        before: Option<CVersion>,
+        filter: Option<VersionFilter>,
+    ) -> Result<Connection<String, Object>, RpcError<Error>> {
+        ObjectImpl::from(self)
+            .object_versions(ctx, first, after, last, before, filter)
+            .await
+    }
+
     /// Paginate all versions of this object after this one.
     async fn object_versions_after(
         &self,
@@ -571,7 +613,7 @@ impl Object {
         // checkpoint.
         let checkpoint = match (page.after(), page.before()) {
             (Some(a), Some(b)) if a.0 != b.0 => {
-                return Err(bad_user_input(Error::CursorInconsistency(a.0, b.0)));
+                return Err(bad_cursor(Error::CursorInconsistency(a.0, b.0)));
             }
 
             (None, None) => scope.checkpoint_viewed_at(),
@@ -581,12 +623,14 @@ impl Object {
         // Set the checkpoint being viewed to the one calculated from the cursors, so that
         // nested queries about the resulting objects also treat this checkpoint as latest.
         let Some(scope) = scope.with_checkpoint_viewed_at(checkpoint) else {
-            return Err(bad_user_input(Error::Future(checkpoint)));
+            return Err(bad_cursor(Error::Future(checkpoint)));
         };
 
         let refs = match filter {
             ObjectFilter {
-                owner_kind: kind @ (None | Some(OwnerKind::Address | OwnerKind::Object)),
+                owner_kind:
+                    kind @ (None
+                    | Some(OwnerKind::Address | OwnerKind::Object | OwnerKind::ConsensusAddress)),
                 owner: Some(address),
                 type_,
             } => {
@@ -651,7 +695,7 @@ impl Object {
             }
 
             consistent_reader::Error::OutOfRange(_) => {
-                bad_user_input(Error::OutOfRange(checkpoint))
+                out_of_retention(Error::OutOfRange(checkpoint))
             }
 
             consistent_reader::Error::Internal(error) => {
@@ -730,6 +774,29 @@ impl ObjectImpl<'_> {
         Ok(Some(Base64(bytes)))
     }
 
+    pub(crate) async fn object_versions(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u64>,
+        after: Option<CVersion>,
+        last: Option<u64>,
+        before: Option<CVersion>,
+        filter: Option<VersionFilter>,
+    ) -> Result<Connection<String, Object>, RpcError<Error>> {
+        let pagination: &PaginationConfig = ctx.data()?;
+        let limits = pagination.limits("IObject", "objectVersions");
+        let page = Page::from_params(limits, first, after, last, before)?;
+
+        Object::paginate_by_version(
+            ctx,
+            self.0.super_.scope.clone(),
+            page,
+            self.0.super_.address,
+            filter.unwrap_or_default(),
+        )
+        .await
+    }
+
     pub(crate) async fn object_versions_after(
         &self,
         ctx: &Context<'_>,
@@ -13,18 +13,20 @@ use crate::{
 ///
 /// - A filter on type (all live objects whose type matches that filter).
 /// - Fetching all objects owned by an address or object, optionally filtered by type.
+/// - Fetching all objects owned by an address through consensus, optionally filtered by type.
 /// - Fetching all shared or immutable objects, filtered by type.
 #[derive(InputObject, Default, Debug, Clone, Eq, PartialEq)]
 pub(crate) struct ObjectFilter {
-    /// Filter on whether the object is address-owned, object-owned, shared, or immutable.
+    /// Filter on whether the object is address-owned, object-owned, shared, immutable, or owned by an address through consensus.
     ///
-    /// - If this field is set to "ADDRESS" or "OBJECT", then an owner filter must also be provided.
+    /// - If this field is set to "ADDRESS", "OBJECT", or "CONSENSUS_ADDRESS", then an owner filter must also be provided.
+    /// - "ADDRESS" includes objects that are owned by the address through consensus, while "CONSENSUS_ADDRESS" only includes those objects.
     /// - If this field is set to "SHARED" or "IMMUTABLE", then a type filter must also be provided.
     pub owner_kind: Option<OwnerKind>,
 
     /// Specifies the address of the owning address or object.
     ///
-    /// This field is required if `ownerKind` is "ADDRESS" or "OBJECT". If provided without `ownerKind`, `ownerKind` defaults to "ADDRESS".
+    /// This field is required if `ownerKind` is "ADDRESS", "OBJECT", or "CONSENSUS_ADDRESS". If provided without `ownerKind`, `ownerKind` defaults to "ADDRESS".
     pub owner: Option<SuiAddress>,
 
     /// Filter on the object's type. The filter can be one of:
@@ -60,7 +62,7 @@ impl ObjectFilter {
         }
 
         Some(Self {
-            owner_kind: intersect!(owner_kind, intersect::by_eq)?,
+            owner_kind: intersect!(owner_kind, OwnerKind::intersect)?,
             owner: intersect!(owner, intersect::by_eq)?,
             type_: intersect!(type_, TypeFilter::intersect)?,
         })
@@ -71,7 +73,12 @@ impl CustomValidator<ObjectFilter> for Validator {
     fn check(&self, filter: &ObjectFilter) -> Result<(), InputValueError<ObjectFilter>> {
         match filter {
             ObjectFilter {
-                owner_kind: Some(kind @ (OwnerKind::Address | OwnerKind::Object)),
+                owner_kind:
+                    Some(
+                        kind @ (OwnerKind::Address
+                        | OwnerKind::Object
+                        | OwnerKind::ConsensusAddress),
+                    ),
                 owner: None,
                 type_: _,
             } => Err(InputValueError::custom(format!(
@@ -108,7 +115,8 @@ impl CustomValidator<ObjectFilter> for Validator {
 
             // Valid address/object owner filter
             ObjectFilter {
-                owner_kind: None | Some(OwnerKind::Address | OwnerKind::Object),
+                owner_kind:
+                    None | Some(OwnerKind::Address | OwnerKind::Object | OwnerKind::ConsensusAddress),
                 owner: Some(_),
                 type_: _,
             } => Ok(()),
@@ -169,6 +177,41 @@ mod tests {
         assert!(o.intersect(a).is_none());
     }
 
+    #[test]
+    fn test_consensus_address_intersection() {
+        let a = ObjectFilter {
+            owner_kind: Some(OwnerKind::Address),
+            owner: Some("0x1".parse().unwrap()),
+            type_: None,
+        };
+
+        let c = ObjectFilter {
+            owner_kind: Some(OwnerKind::ConsensusAddress),
+            owner: None,
+            type_: None,
+        };
+
+        let o = ObjectFilter {
+            owner_kind: Some(OwnerKind::Object),
+            owner: None,
+            type_: None,
+        };
+
+        let a_c = ObjectFilter {
+            owner_kind: Some(OwnerKind::ConsensusAddress),
+            owner: Some("0x1".parse().unwrap()),
+            type_: None,
+        };
+
+        // Objects owned by an address through consensus are also owned by the address, so the
+        // intersection narrows to the consensus-owned objects, regardless of intersection order.
+        assert_eq!(a.clone().intersect(c.clone()).unwrap(), a_c);
+        assert_eq!(c.clone().intersect(a.clone()).unwrap(), a_c);
+
+        assert!(c.clone().intersect(o.clone()).is_none());
+        assert!(o.intersect(c).is_none());
+    }
+
     #[test]
     fn test_owner_intersection() {
         let a1 = ObjectFilter {
@@ -0,0 +1,199 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_graphql::{scalar, Object};
+use serde::{Deserialize, Serialize};
+use sui_package_resolver::{OpenSignature, OpenSignatureBody, Reference};
+
+pub(crate) struct OpenMoveType {
+    native: OpenSignature,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct OpenMoveTypeSignature {
+    #[serde(rename = "ref")]
+    ref_: Option<OpenMoveTypeReference>,
+    body: OpenMoveTypeSignatureBody,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) enum OpenMoveTypeReference {
+    #[serde(rename = "&")]
+    Immutable,
+
+    #[serde(rename = "&mut")]
+    Mutable,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum OpenMoveTypeSignatureBody {
+    TypeParameter(u16),
+    Address,
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Vector(Box<OpenMoveTypeSignatureBody>),
+    Datatype {
+        package: String,
+        module: String,
+        #[serde(rename = "type")]
+        type_: String,
+        #[serde(rename = "typeParameters")]
+        type_parameters: Vec<OpenMoveTypeSignatureBody>,
+    },
+}
+
+/// Represents types that could contain references or free type parameters. Such types can appear
+/// as function parameters, or return types.
+#[Object]
+impl OpenMoveType {
+    /// Flat representation of the type signature, as a displayable string.
+    async fn repr(&self) -> String {
+        let body = repr(&self.native.body);
+        match self.native.ref_ {
+            None => body,
+            Some(Reference::Immutable) => format!("&{body}"),
+            Some(Reference::Mutable) => format!("&mut {body}"),
+        }
+    }
+
+    /// Structured representation of the type signature.
+    async fn signature(&self) -> OpenMoveTypeSignature {
+        OpenMoveTypeSignature::from(self.native.clone())
+    }
+}
+
+scalar!(
+    OpenMoveTypeSignature,
+    "OpenMoveTypeSignature",
+    "The shape of an abstract Move Type (a type that can contain free type parameters, and can \
+     optionally be taken by reference), corresponding to the following recursive type:
+
+type OpenMoveTypeSignature = {
+  ref: (\"&\" | \"&mut\")?,
+  body: OpenMoveTypeSignatureBody,
+}
+
+type OpenMoveTypeSignatureBody =
+    \"address\"
+  | \"bool\"
+  | \"u8\" | \"u16\" | ... | \"u256\"
+  | { vector: OpenMoveTypeSignatureBody }
+  | {
+      datatype: {
+        package: string,
+        module: string,
+        type: string,
+        typeParameters: [OpenMoveTypeSignatureBody],
+      }
+    }
+  | { typeParameter: number }"
+);
+
+impl From<OpenSignature> for OpenMoveType {
+    fn from(native: OpenSignature) -> Self {
+        Self { native }
+    }
+}
+
+impl From<OpenSignature> for OpenMoveTypeSignature {
+    fn from(signature: OpenSignature) -> Self {
+        Self {
+            ref_: signature.ref_.map(|r| match r {
+                Reference::Immutable => OpenMoveTypeReference::Immutable,
+                Reference::Mutable => OpenMoveTypeReference::Mutable,
+            }),
+            body: signature.body.into(),
+        }
+    }
+}
+
+impl From<OpenSignatureBody> for OpenMoveTypeSignatureBody {
+    fn from(signature: OpenSignatureBody) -> Self {
+        use OpenSignatureBody as S;
+
+        match signature {
+            S::Address => Self::Address,
+            S::Bool => Self::Bool,
+            S::U8 => Self::U8,
+            S::U16 => Self::U16,
+            S::U32 => Self::U32,
+            S::U64 => Self::U64,
+            S::U128 => Self::U128,
+            S::U256 => Self::U256,
+            S::Vector(signature) => Self::Vector(Box::new((*signature).into())),
+
+            S::Datatype(key, type_params) => Self::Datatype {
+                package: key.package.to_canonical_string(/* with_prefix */ true),
+                module: key.module.to_string(),
+                type_: key.name.to_string(),
+                type_parameters: type_params.into_iter().map(Self::from).collect(),
+            },
+
+            S::TypeParameter(idx) => Self::TypeParameter(idx),
+        }
+    }
+}
+
+/// Flat representation of an open type signature: Datatypes are fully-qualified, with their
+/// package addresses in canonical form, and type parameters are represented by their index,
+/// prefixed with `$`.
+fn repr(signature: &OpenSignatureBody) -> String {
+    use OpenSignatureBody as S;
+
+    match signature {
+        S::Address => "address".to_string(),
+        S::Bool => "bool".to_string(),
+        S::U8 => "u8".to_string(),
+        S::U16 => "u16".to_string(),
+        S::U32 => "u32".to_string(),
+        S::U64 => "u64".to_string(),
+        S::U128 => "u128".to_string(),
+        S::U256 => "u256".to_string(),
+        S::Vector(signature) => format!("vector<{}>", repr(signature)),
+
+        S::Datatype(key, type_params) => {
+            let package = key.package.to_canonical_string(/* with_prefix */ true);
+            let mut repr_ = format!("{package}::{}::{}", key.module, key.name);
+            if !type_params.is_empty() {
+                let params: Vec<_> = type_params.iter().map(repr).collect();
+                repr_.push_str(&format!("<{}>", params.join(", ")));
+            }
+            repr_
+        }
+
+        S::TypeParameter(idx) => format!("${idx}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use move_core_types::account_address::AccountAddress;
+    use sui_package_resolver::DatatypeKey;
+
+    use super::*;
+
+    #[test]
+    fn test_repr() {
+        let coin = OpenSignatureBody::Datatype(
+            DatatypeKey {
+                package: AccountAddress::from_str("0x2").unwrap(),
+                module: "coin".into(),
+                name: "Coin".into(),
+            },
+            vec![OpenSignatureBody::TypeParameter(0)],
+        );
+
+        assert_eq!(
+            repr(&OpenSignatureBody::Vector(Box::new(coin))),
+            "vector<0x0000000000000000000000000000000000000000000000000000000000000002::coin::Coin<$0>>",
+        );
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_graphql::Enum;
+
+/// The representations to render a value in, for fields that offer it in more than one. Clients
+/// that only need one representation can skip rendering (and transferring) the other.
+#[derive(Enum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) enum Representation {
+    /// Only the value's BCS representation, without rendering it as JSON.
+    Bcs,
+
+    /// Only the value's JSON representation, without its BCS representation.
+    Json,
+
+    /// Both the value's BCS and JSON representations.
+    #[default]
+    Both,
+}
+
+impl Representation {
+    pub(crate) fn includes_bcs(&self) -> bool {
+        matches!(self, Self::Bcs | Self::Both)
+    }
+
+    pub(crate) fn includes_json(&self) -> bool {
+        matches!(self, Self::Json | Self::Both)
+    }
+}
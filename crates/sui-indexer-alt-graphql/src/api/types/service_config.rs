@@ -177,4 +177,28 @@ impl ServiceConfig {
         let limits: &Limits = ctx.data()?;
         Ok(Some(limits.max_move_value_bound))
     }
+
+    /// Maximum depth a Display format string is allowed to nest field accesses, when rendering `MoveObject.display`.
+    async fn max_display_field_depth(&self, ctx: &Context<'_>) -> Result<Option<usize>, RpcError> {
+        let limits: &Limits = ctx.data()?;
+        Ok(Some(limits.max_display_field_depth))
+    }
+
+    /// Maximum number of bytes occupied by Display field names and values in the output of `MoveObject.display` for a single object.
+    async fn max_display_output_size(&self, ctx: &Context<'_>) -> Result<Option<usize>, RpcError> {
+        let limits: &Limits = ctx.data()?;
+        Ok(Some(limits.max_display_output_size))
+    }
+
+    /// Maximum number of each kind of entry (items, listings, locks) read from a single kiosk by `Query.kiosk`, and of transfer policies read for each type of item in it.
+    async fn max_kiosk_entries(&self, ctx: &Context<'_>) -> Result<Option<usize>, RpcError> {
+        let limits: &Limits = ctx.data()?;
+        Ok(Some(limits.max_kiosk_entries))
+    }
+
+    /// Minimum number of characters in the digest prefix passed to `Query.transactionSearch`.
+    async fn min_digest_prefix_length(&self, ctx: &Context<'_>) -> Result<Option<usize>, RpcError> {
+        let limits: &Limits = ctx.data()?;
+        Ok(Some(limits.min_digest_prefix_length))
+    }
 }
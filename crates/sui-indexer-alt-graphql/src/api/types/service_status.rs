@@ -0,0 +1,68 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context as _;
+use async_graphql::{Context, Object, SimpleObject};
+use diesel::{ExpressionMethods, QueryDsl as _};
+use sui_indexer_alt_reader::pg_reader::PgReader;
+use sui_indexer_alt_schema::{backfills::StoredBackfill, schema::backfills};
+
+use crate::{
+    api::scalars::{date_time::DateTime, uint53::UInt53},
+    error::RpcError,
+};
+
+pub(crate) struct ServiceStatus;
+
+/// A backfill of a range of checkpoints for one of the indexer's pipelines, launched because the pipeline stalled.
+#[derive(SimpleObject)]
+pub(crate) struct Backfill {
+    /// The name of the pipeline being backfilled.
+    pub pipeline: String,
+
+    /// The first checkpoint in the range being backfilled (inclusive).
+    pub first_checkpoint: UInt53,
+
+    /// The last checkpoint in the range being backfilled (inclusive).
+    pub last_checkpoint: UInt53,
+
+    /// When the backfill was started.
+    pub started_at: DateTime,
+}
+
+#[Object]
+impl ServiceStatus {
+    /// Backfills that are currently running for the indexer's pipelines, oldest first.
+    async fn backfills(&self, ctx: &Context<'_>) -> Result<Option<Vec<Backfill>>, RpcError> {
+        use backfills::dsl as b;
+
+        let pg_reader: &PgReader = ctx.data()?;
+        let mut conn = pg_reader
+            .connect()
+            .await
+            .context("Failed to connect to database")?;
+
+        let stored: Vec<StoredBackfill> = conn
+            .results(
+                b::backfills
+                    .filter(b::finished_at_ms.is_null())
+                    .order_by((b::started_at_ms, b::pipeline)),
+            )
+            .await
+            .context("Failed to fetch ongoing backfills")?;
+
+        let backfills = stored
+            .into_iter()
+            .map(|b| {
+                Ok(Backfill {
+                    pipeline: b.pipeline,
+                    first_checkpoint: UInt53::from(b.first_checkpoint as u64),
+                    last_checkpoint: UInt53::from(b.last_checkpoint as u64),
+                    started_at: DateTime::from_ms(b.started_at_ms)?,
+                })
+            })
+            .collect::<Result<_, RpcError>>()?;
+
+        Ok(Some(backfills))
+    }
+}
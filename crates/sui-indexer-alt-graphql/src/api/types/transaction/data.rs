@@ -0,0 +1,50 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use async_graphql::{Object, Value};
+use sui_indexer_alt_reader::kv_loader::TransactionContents as NativeTransactionContents;
+
+use crate::{
+    api::{
+        scalars::{base64::Base64, json::Json},
+        types::representation::Representation,
+    },
+    error::RpcError,
+};
+
+/// The data that the sender signed for a transaction (its kind, sender, gas data and expiration), in the representations that were requested for it.
+pub(crate) struct TransactionData {
+    pub(crate) contents: Arc<NativeTransactionContents>,
+    pub(crate) representation: Representation,
+}
+
+#[Object]
+impl TransactionData {
+    /// The Base64-encoded BCS serialization of the transaction data.
+    ///
+    /// `null` if the data was fetched with a `representation` that excludes BCS.
+    async fn bcs(&self) -> Result<Option<Base64>, RpcError> {
+        if !self.representation.includes_bcs() {
+            return Ok(None);
+        }
+
+        Ok(Some(Base64(self.contents.raw_transaction()?)))
+    }
+
+    /// The transaction data rendered as JSON.
+    ///
+    /// `null` if the data was fetched with a `representation` that excludes JSON, in which case it is not deserialized.
+    async fn json(&self) -> Result<Option<Json>, RpcError> {
+        if !self.representation.includes_json() {
+            return Ok(None);
+        }
+
+        let data = self.contents.data()?;
+        let value = serde_json::to_value(data).context("Failed to serialize transaction data")?;
+        let value = Value::from_json(value).context("Failed to render transaction data")?;
+        Ok(Some(Json::from(value)))
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::RangeInclusive;
+
+use move_core_types::u256::U256;
+
+use super::Error;
+
+/// Base58 digits, in order of their value.
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The number of bytes in a transaction digest.
+const DIGEST_LENGTH: usize = 32;
+
+/// The number of characters in the longest Base58 encoding of a transaction digest.
+const MAX_ENCODED_LENGTH: usize = 44;
+
+/// The ranges of transaction digests whose Base58 encodings start with `prefix`, as inclusive
+/// ranges of their big-endian bytes, which is how they compare in the database. Ranges are
+/// disjoint and in ascending order.
+///
+/// A prefix does not correspond to a single range of digests, because Base58 encodings vary in
+/// length: Each leading zero byte is encoded as a leading `1`, and the remaining bytes are encoded
+/// as a number, whose encoding is longer the larger it is. Digests that start with `prefix` are
+/// found by considering each length that the rest of their encoding could have.
+pub(super) fn digest_ranges(
+    prefix: &str,
+    min_length: usize,
+) -> Result<Vec<RangeInclusive<[u8; DIGEST_LENGTH]>>, Error> {
+    if prefix.len() < min_length {
+        return Err(Error::PrefixTooShort {
+            min: min_length,
+            actual: prefix.len(),
+        });
+    }
+
+    if prefix.len() > MAX_ENCODED_LENGTH {
+        return Err(Error::PrefixTooLong(prefix.to_owned()));
+    }
+
+    let digits = prefix
+        .chars()
+        .map(|c| {
+            ALPHABET
+                .iter()
+                .position(|d| *d as char == c)
+                .ok_or(Error::PrefixNotBase58(c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let zeroes = digits.iter().take_while(|d| **d == 0).count();
+    if zeroes > DIGEST_LENGTH {
+        return Ok(vec![]);
+    }
+
+    // The digests with `zeroes` leading zero bytes, followed by a non-zero byte, are those whose
+    // encoding starts with exactly `zeroes` 1s.
+    let max = if zeroes == 0 {
+        U256::max_value()
+    } else {
+        (U256::one() << (8 * (DIGEST_LENGTH - zeroes) as u32)) - U256::one()
+    };
+
+    // If the prefix is all 1s, the rest of the digest is unconstrained.
+    let rest = &digits[zeroes..];
+    if rest.is_empty() {
+        return Ok(vec![to_bytes(U256::zero())..=to_bytes(max)]);
+    }
+
+    if zeroes == DIGEST_LENGTH {
+        return Ok(vec![]);
+    }
+
+    let min = U256::one() << (8 * (DIGEST_LENGTH - zeroes - 1) as u32);
+
+    // The value of the rest of the prefix. If it does not fit in 256 bits, it is larger than any
+    // digest.
+    let base = U256::from(58u8);
+    let mut lo = U256::zero();
+    for digit in rest {
+        let Some(next) = lo
+            .checked_mul(base)
+            .and_then(|v| v.checked_add(U256::from(*digit as u8)))
+        else {
+            return Ok(vec![]);
+        };
+
+        lo = next;
+    }
+
+    // Each additional digit after the prefix multiplies the range of values it represents by 58.
+    let mut ranges = vec![];
+    let mut hi = lo.checked_add(U256::one());
+    for _ in prefix.len()..=MAX_ENCODED_LENGTH {
+        if lo > max {
+            break;
+        }
+
+        let hi_inclusive = hi.map_or(U256::max_value(), |hi| hi - U256::one());
+        if hi_inclusive >= min {
+            ranges.push(to_bytes(lo.max(min))..=to_bytes(hi_inclusive.min(max)));
+        }
+
+        let Some(next) = lo.checked_mul(base) else {
+            break;
+        };
+
+        lo = next;
+        hi = hi.and_then(|hi| hi.checked_mul(base));
+    }
+
+    Ok(ranges)
+}
+
+fn to_bytes(value: U256) -> [u8; DIGEST_LENGTH] {
+    let mut bytes = value.to_le_bytes();
+    bytes.reverse();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use fastcrypto::encoding::{Base58, Encoding};
+
+    use super::*;
+
+    fn in_ranges(
+        ranges: &[RangeInclusive<[u8; DIGEST_LENGTH]>],
+        digest: &[u8; DIGEST_LENGTH],
+    ) -> bool {
+        ranges.iter().any(|range| range.contains(digest))
+    }
+
+    #[test]
+    fn test_prefixes_of_digests() {
+        let mut digests = vec![[0u8; DIGEST_LENGTH], [0xff; DIGEST_LENGTH]];
+        for i in 0..100u8 {
+            let mut digest = [0u8; DIGEST_LENGTH];
+            for (j, b) in digest.iter_mut().enumerate() {
+                *b = (i as usize * 31 + j * 17) as u8 ^ i.rotate_left(j as u32);
+            }
+
+            // Include digests with leading zero bytes, which are encoded differently.
+            for b in digest.iter_mut().take(i as usize % 3) {
+                *b = 0;
+            }
+
+            digests.push(digest);
+        }
+
+        for digest in &digests {
+            let encoded = Base58::encode(digest);
+            for len in 1..=encoded.len() {
+                let prefix = &encoded[..len];
+                let ranges = digest_ranges(prefix, 0).unwrap();
+                assert!(
+                    in_ranges(&ranges, digest),
+                    "{prefix} should match {encoded}"
+                );
+
+                for other in &digests {
+                    let other_encoded = Base58::encode(other);
+                    assert_eq!(
+                        in_ranges(&ranges, other),
+                        other_encoded.starts_with(prefix),
+                        "{prefix} vs {other_encoded}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges_are_disjoint_and_ascending() {
+        let ranges = digest_ranges("4vJ9JU", 0).unwrap();
+        assert!(!ranges.is_empty());
+        for pair in ranges.windows(2) {
+            assert!(pair[0].end() < pair[1].start());
+        }
+    }
+
+    #[test]
+    fn test_invalid_prefixes() {
+        assert!(matches!(
+            digest_ranges("abc", 6),
+            Err(Error::PrefixTooShort { min: 6, actual: 3 }),
+        ));
+
+        assert!(matches!(
+            digest_ranges("abc0ef", 6),
+            Err(Error::PrefixNotBase58('0')),
+        ));
+
+        assert!(matches!(
+            digest_ranges(&"z".repeat(45), 6),
+            Err(Error::PrefixTooLong(_)),
+        ));
+    }
+
+    #[test]
+    fn test_unmatchable_prefixes() {
+        // Larger than any digest.
+        assert!(digest_ranges(&"z".repeat(44), 0).unwrap().is_empty());
+
+        // More leading zero bytes than a digest has.
+        assert!(digest_ranges(&"1".repeat(33), 0).unwrap().is_empty());
+        assert!(digest_ranges(&format!("{}2", "1".repeat(32)), 0)
+            .unwrap()
+            .is_empty());
+    }
+}
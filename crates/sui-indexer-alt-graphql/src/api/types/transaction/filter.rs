@@ -4,15 +4,29 @@
 use anyhow::Context as _;
 use std::ops::{Range, RangeInclusive};
 
-use async_graphql::{Context, InputObject};
+use async_graphql::{Context, CustomValidator, Enum, InputObject, InputValueError};
 use diesel::prelude::QueryableByName;
-use diesel::sql_types::BigInt;
+use diesel::sql_types::{BigInt, Bytea, Nullable};
 use sui_indexer_alt_reader::pg_reader::PgReader;
+use sui_indexer_alt_schema::transactions::StoredKind;
+use sui_pg_db::query::Query;
 use sui_sql_macro::query;
+use sui_types::base_types::SuiAddress as NativeSuiAddress;
 
-use crate::api::scalars::uint53::UInt53;
+use crate::api::scalars::{
+    date_time::DateTime,
+    sui_address::SuiAddress,
+    type_filter::{FqNameFilter, TypeInput},
+    uint53::UInt53,
+};
+use crate::api::types::checkpoint::filter::checkpoint_at_timestamp;
+use crate::api::types::transaction::{CTransaction, TransactionCursor};
 use crate::error::RpcError;
 use crate::intersect;
+use crate::pagination::Page;
+
+/// The maximum number of filters that can be combined in a filter's `anyOf`.
+pub(crate) const MAX_ANY_OF_FILTERS: usize = 10;
 
 #[derive(InputObject, Debug, Default, Clone)]
 pub(crate) struct TransactionFilter {
@@ -24,6 +38,63 @@ pub(crate) struct TransactionFilter {
 
     /// Limit to transaction that occured strictly before the given checkpoint.
     pub before_checkpoint: Option<UInt53>,
+
+    /// Limit to transactions in checkpoints whose timestamp is strictly after the given timestamp. This is equivalent to `afterCheckpoint` with the checkpoint returned by `checkpointAtTimestamp` for the same timestamp.
+    pub after_timestamp: Option<DateTime>,
+
+    /// Limit to transactions in checkpoints whose timestamp is strictly before the given timestamp.
+    pub before_timestamp: Option<DateTime>,
+
+    /// Limit to transactions that modified the given object, optionally restricted to the transactions that wrote a range of its versions.
+    pub affected_object: Option<AffectedObjectFilter>,
+
+    /// Limit to transactions that took a coin of the given type as input, including as gas. The type is the coin's type parameter, e.g. `0x2::sui::SUI` for transactions that used `0x2::coin::Coin<0x2::sui::SUI>`.
+    pub uses_coin_type: Option<TypeInput>,
+
+    /// Limit to transactions that were not sent by the given address. Transactions that the address only sponsored are still included.
+    pub not_sent_address: Option<SuiAddress>,
+
+    /// Limit to transactions that are not of the given kind.
+    pub exclude_kind: Option<TransactionKindInput>,
+
+    /// Limit to transactions that did not call the given function. The filter can be one of:
+    ///
+    /// - A package address: `0x2`, excluding transactions that called any function in the package,
+    /// - A module: `0x2::coin`, excluding transactions that called any function in the module,
+    /// - A fully-qualified name: `0x2::coin::take`, excluding transactions that called the function.
+    pub not_function: Option<FqNameFilter>,
+
+    /// Limit to transactions that match at least one of the given filters, as well as the other fields of this filter, e.g. `{ anyOf: [{ affectedObject: { address: "0x1" } }, { usesCoinType: "0x2::sui::SUI" }] }` for transactions that modified object `0x1`, or used a SUI coin.
+    ///
+    /// Filters in `anyOf` cannot themselves contain `anyOf`, and at most 10 filters can be combined.
+    pub any_of: Option<Vec<TransactionFilter>>,
+}
+
+/// Checks that a filter's `anyOf` is not empty, not nested, and within [MAX_ANY_OF_FILTERS].
+#[derive(Default)]
+pub(crate) struct Validator;
+
+/// The kind of a transaction, to filter transactions by.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum TransactionKindInput {
+    /// A system transaction, e.g. a checkpoint's consensus commit prologue, or a change of epoch.
+    SystemTx,
+
+    /// A programmable transaction, sent by a user.
+    ProgrammableTx,
+}
+
+/// Filter for transactions that modified an object, identified by its ID, and optionally by the range of versions of the object they wrote.
+#[derive(InputObject, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AffectedObjectFilter {
+    /// The ID of the object.
+    pub address: SuiAddress,
+
+    /// Limit to transactions that wrote a version of the object strictly after the given version.
+    pub after_version: Option<UInt53>,
+
+    /// Limit to transactions that wrote a version of the object strictly before the given version.
+    pub before_version: Option<UInt53>,
 }
 
 #[derive(QueryableByName)]
@@ -34,6 +105,14 @@ struct TxBounds {
     tx_hi: i64,
 }
 
+#[derive(QueryableByName)]
+struct AffectedTxBounds {
+    #[diesel(sql_type = Nullable<BigInt>, column_name = "tx_lo")]
+    tx_lo: Option<i64>,
+    #[diesel(sql_type = Nullable<BigInt>, column_name = "tx_hi")]
+    tx_hi: Option<i64>,
+}
+
 impl TransactionFilter {
     /// Try to create a filter whose results are the intersection of transaction blocks in `self`'s
     /// results and transaction blocks in `other`'s results. This may not be possible if the
@@ -50,8 +129,271 @@ impl TransactionFilter {
             after_checkpoint: intersect!(after_checkpoint, intersect::by_max)?,
             at_checkpoint: intersect!(at_checkpoint, intersect::by_eq)?,
             before_checkpoint: intersect!(before_checkpoint, intersect::by_min)?,
+            after_timestamp: intersect!(after_timestamp, intersect::by_max)?,
+            before_timestamp: intersect!(before_timestamp, intersect::by_min)?,
+            affected_object: intersect!(affected_object, intersect::by_eq)?,
+            uses_coin_type: intersect!(uses_coin_type, intersect::by_eq)?,
+            not_sent_address: intersect!(not_sent_address, intersect::by_eq)?,
+            exclude_kind: intersect!(exclude_kind, intersect::by_eq)?,
+            not_function: intersect!(not_function, intersect::by_eq)?,
+            any_of: intersect!(any_of, intersect_any_of)?,
         })
     }
+
+    /// The filters whose results are combined to produce this filter's results: one for each filter
+    /// in `anyOf`, intersected with the rest of this filter, skipping the ones that are
+    /// inconsistent. A filter without `anyOf` is its own only branch.
+    pub(crate) fn branches(mut self) -> Vec<Self> {
+        let Some(any_of) = self.any_of.take() else {
+            return vec![self];
+        };
+
+        any_of
+            .into_iter()
+            .filter_map(|branch| self.clone().intersect(branch))
+            .collect()
+    }
+
+    /// Replace the filter's timestamp bounds with the equivalent bounds on checkpoints, searching
+    /// the checkpoints between `reader_lo` and `checkpoint_viewed_at`. Returns `None` if no
+    /// checkpoint in that range can satisfy the timestamp bounds.
+    pub(crate) async fn resolve_timestamps(
+        mut self,
+        ctx: &Context<'_>,
+        reader_lo: u64,
+        checkpoint_viewed_at: u64,
+    ) -> Result<Option<Self>, RpcError> {
+        let at_or_before = |timestamp_ms: i64| async move {
+            let Ok(timestamp_ms) = u64::try_from(timestamp_ms) else {
+                return Ok(None);
+            };
+
+            checkpoint_at_timestamp(ctx, reader_lo, checkpoint_viewed_at, timestamp_ms).await
+        };
+
+        // Checkpoints after a timestamp are the ones after the latest checkpoint at or before it,
+        // and if there is no such checkpoint, every checkpoint is after it.
+        let after_checkpoint = match self.after_timestamp.take() {
+            Some(after) => at_or_before(after.timestamp_ms()).await?,
+            None => None,
+        };
+
+        // Checkpoints before a timestamp are the ones up to and including the latest checkpoint
+        // that is at least a millisecond before it, and if there is no such checkpoint, none are.
+        let before_checkpoint = match self.before_timestamp.take() {
+            Some(before) => match at_or_before(before.timestamp_ms().saturating_sub(1)).await? {
+                Some(cp) => Some(cp + 1),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+
+        Ok(self.intersect(Self {
+            after_checkpoint: after_checkpoint.map(UInt53::from),
+            before_checkpoint: before_checkpoint.map(UInt53::from),
+            ..Default::default()
+        }))
+    }
+
+    /// Whether the filter limits transactions by anything other than their checkpoint, so that its
+    /// transactions need to be looked up in the database, rather than enumerated by sequence number.
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.affected_object.is_some() || self.uses_coin_type.is_some() || self.has_exclusions()
+    }
+
+    /// The BCS-serialized coin type that the filter limits transactions to using, as it is stored in
+    /// `tx_coin_types`.
+    pub(crate) fn coin_type_bytes(&self) -> Result<Option<Vec<u8>>, RpcError> {
+        let Some(TypeInput(coin_type)) = &self.uses_coin_type else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            bcs::to_bytes(coin_type).context("Failed to serialize coin type")?,
+        ))
+    }
+
+    /// Whether the filter excludes transactions based on their sender, kind, or calls.
+    pub(crate) fn has_exclusions(&self) -> bool {
+        self.not_sent_address.is_some()
+            || self.exclude_kind.is_some()
+            || self.not_function.is_some()
+    }
+
+    /// The pipelines that [Self::exclusions] reads from.
+    pub(crate) fn exclusion_pipelines(&self) -> Vec<&'static str> {
+        let mut pipelines = vec![];
+        if self.not_sent_address.is_some() {
+            pipelines.push("tx_affected_addresses");
+        }
+
+        if self.exclude_kind.is_some() {
+            pipelines.push("tx_kinds");
+        }
+
+        if self.not_function.is_some() {
+            pipelines.push("tx_calls");
+        }
+
+        pipelines
+    }
+
+    /// Conditions to add to the `WHERE` clause of a query over transactions aliased as `t`, to
+    /// exclude the transactions that this filter's negated fields exclude. Each condition is an
+    /// anti-join that looks up the transaction's sequence number in an index that is keyed on the
+    /// excluded value first, so it does not need to scan the excluded transactions.
+    pub(crate) fn exclusions(&self) -> Query<'static> {
+        let mut exclusions = query!("");
+
+        // Senders are always among the addresses that their transactions affect.
+        if let Some(sender) = self.not_sent_address {
+            let sender = NativeSuiAddress::from(sender).to_vec();
+            exclusions += query!(
+                r#"
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        tx_affected_addresses a
+                    WHERE
+                        a.affected = {Bytea}
+                    AND a.tx_sequence_number = t.tx_sequence_number
+                    AND a.sender = {Bytea}
+                )
+                "#,
+                sender.clone(),
+                sender,
+            );
+        }
+
+        if let Some(kind) = self.exclude_kind {
+            exclusions += query!(
+                r#"
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        tx_kinds k
+                    WHERE
+                        k.tx_kind = {SmallInt}
+                    AND k.tx_sequence_number = t.tx_sequence_number
+                )
+                "#,
+                StoredKind::from(kind) as i16,
+            );
+        }
+
+        if let Some(function) = &self.not_function {
+            let calls = match function {
+                FqNameFilter::Package(package) => query!(
+                    "c.package = {Bytea}",
+                    NativeSuiAddress::from(*package).to_vec(),
+                ),
+
+                FqNameFilter::Module(package, module) => query!(
+                    "c.package = {Bytea} AND c.module = {Text}",
+                    NativeSuiAddress::from(*package).to_vec(),
+                    module.clone(),
+                ),
+
+                FqNameFilter::FqName(package, module, name) => query!(
+                    "c.package = {Bytea} AND c.module = {Text} AND c.function = {Text}",
+                    NativeSuiAddress::from(*package).to_vec(),
+                    module.clone(),
+                    name.clone(),
+                ),
+            };
+
+            exclusions += query!(
+                r#"
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        tx_calls c
+                    WHERE
+                        {}
+                    AND c.tx_sequence_number = t.tx_sequence_number
+                )
+                "#,
+                calls,
+            );
+        }
+
+        exclusions
+    }
+
+    /// A filter equivalent to the cursors on `page` that point at checkpoints: transactions from
+    /// the checkpoint after `after`, and before the checkpoint before `before`.
+    pub(crate) fn from_cursors(page: &Page<CTransaction>) -> Self {
+        let after_checkpoint = page.after().and_then(|cursor| match **cursor {
+            TransactionCursor::Checkpoint { checkpoint } => checkpoint.checked_sub(1),
+            TransactionCursor::Transaction(_) => None,
+        });
+
+        let before_checkpoint = page.before().and_then(|cursor| match **cursor {
+            TransactionCursor::Checkpoint { checkpoint } => Some(checkpoint),
+            TransactionCursor::Transaction(_) => None,
+        });
+
+        Self {
+            after_checkpoint: after_checkpoint.map(UInt53::from),
+            before_checkpoint: before_checkpoint.map(UInt53::from),
+            ..Default::default()
+        }
+    }
+}
+
+impl CustomValidator<TransactionFilter> for Validator {
+    fn check(&self, filter: &TransactionFilter) -> Result<(), InputValueError<TransactionFilter>> {
+        let Some(any_of) = &filter.any_of else {
+            return Ok(());
+        };
+
+        if any_of.is_empty() {
+            return Err(InputValueError::custom(
+                "`anyOf` requires at least one filter",
+            ));
+        }
+
+        if any_of.len() > MAX_ANY_OF_FILTERS {
+            return Err(InputValueError::custom(format!(
+                "`anyOf` accepts at most {MAX_ANY_OF_FILTERS} filters, received {}",
+                any_of.len(),
+            )));
+        }
+
+        if any_of.iter().any(|f| f.any_of.is_some()) {
+            return Err(InputValueError::custom(
+                "Filters in `anyOf` cannot contain `anyOf` themselves",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<TransactionKindInput> for StoredKind {
+    fn from(kind: TransactionKindInput) -> Self {
+        match kind {
+            TransactionKindInput::SystemTx => StoredKind::SystemTransaction,
+            TransactionKindInput::ProgrammableTx => StoredKind::ProgrammableTransaction,
+        }
+    }
+}
+
+/// Intersect two disjunctions of filters, by intersecting every pair of their filters. This is
+/// inconsistent if none of the pairs are consistent.
+fn intersect_any_of(
+    this: Vec<TransactionFilter>,
+    that: Vec<TransactionFilter>,
+) -> Option<Vec<TransactionFilter>> {
+    let any_of: Vec<_> = this
+        .iter()
+        .flat_map(|a| that.iter().filter_map(|b| a.clone().intersect(b.clone())))
+        .collect();
+
+    (!any_of.is_empty()).then_some(any_of)
 }
 
 /// The tx_sequence_numbers within checkpoint bounds
@@ -122,3 +464,389 @@ pub(crate) async fn tx_bounds(
 
     Ok(tx_lo..tx_hi)
 }
+
+/// Narrow `tx_bounds` to the transactions that wrote versions of the object in `filter` within its
+/// version bounds. Returns `None` if no version of the object falls within those bounds.
+///
+/// Every transaction that modifies an object writes exactly one version of it, so the transaction
+/// that wrote a given version is found by locating the version's checkpoint in `obj_versions`,
+/// counting the earlier versions of the object written in that checkpoint (k), and picking the
+/// (k+1)-th transaction in the checkpoint that affected the object from `tx_affected_objects`.
+pub(crate) async fn affected_object_tx_bounds(
+    ctx: &Context<'_>,
+    filter: &AffectedObjectFilter,
+    tx_bounds: &Range<u64>,
+) -> Result<Option<Range<u64>>, RpcError> {
+    if filter.after_version.is_none() && filter.before_version.is_none() {
+        return Ok(Some(tx_bounds.clone()));
+    }
+
+    let pg_reader: &PgReader = ctx.data()?;
+    let object_id = NativeSuiAddress::from(filter.address).to_vec();
+
+    let after_version = filter.after_version.map_or(-1, |v| u64::from(v) as i64);
+    let before_version = filter
+        .before_version
+        .map_or(i64::MAX, |v| u64::from(v) as i64);
+
+    let query = query!(
+        r#"
+        WITH
+        -- The first and last versions of the object within the version bounds.
+        v_lo AS (
+            SELECT
+                object_version,
+                cp_sequence_number
+            FROM
+                obj_versions
+            WHERE
+                object_id = {Bytea}
+            AND object_version > {BigInt}
+            ORDER BY
+                object_version ASC
+            LIMIT 1
+        ),
+
+        v_hi AS (
+            SELECT
+                object_version,
+                cp_sequence_number
+            FROM
+                obj_versions
+            WHERE
+                object_id = {Bytea}
+            AND object_version < {BigInt}
+            ORDER BY
+                object_version DESC
+            LIMIT 1
+        ),
+
+        -- The transactions that wrote those versions, found by their position among the
+        -- transactions in the same checkpoint that affected the object.
+        tx_lo AS (
+            SELECT
+                t.tx_sequence_number AS tx_lo
+            FROM
+                v_lo
+            JOIN
+                cp_sequence_numbers c
+            ON  c.cp_sequence_number = v_lo.cp_sequence_number
+            JOIN
+                tx_affected_objects t
+            ON  t.affected = {Bytea}
+            AND t.tx_sequence_number >= c.tx_lo
+            ORDER BY
+                t.tx_sequence_number ASC
+            OFFSET (
+                SELECT
+                    COUNT(*)
+                FROM
+                    obj_versions o,
+                    v_lo
+                WHERE
+                    o.object_id = {Bytea}
+                AND o.cp_sequence_number = v_lo.cp_sequence_number
+                AND o.object_version < v_lo.object_version
+            )
+            LIMIT 1
+        ),
+
+        tx_hi AS (
+            SELECT
+                t.tx_sequence_number AS tx_hi
+            FROM
+                v_hi
+            JOIN
+                cp_sequence_numbers c
+            ON  c.cp_sequence_number = v_hi.cp_sequence_number
+            JOIN
+                tx_affected_objects t
+            ON  t.affected = {Bytea}
+            AND t.tx_sequence_number >= c.tx_lo
+            ORDER BY
+                t.tx_sequence_number ASC
+            OFFSET (
+                SELECT
+                    COUNT(*)
+                FROM
+                    obj_versions o,
+                    v_hi
+                WHERE
+                    o.object_id = {Bytea}
+                AND o.cp_sequence_number = v_hi.cp_sequence_number
+                AND o.object_version < v_hi.object_version
+            )
+            LIMIT 1
+        )
+
+        SELECT
+            (SELECT tx_lo FROM tx_lo) AS "tx_lo",
+            (SELECT tx_hi FROM tx_hi) AS "tx_hi";"#,
+        object_id.clone(),
+        after_version,
+        object_id.clone(),
+        before_version,
+        object_id.clone(),
+        object_id.clone(),
+        object_id.clone(),
+        object_id,
+    );
+
+    let mut conn = pg_reader
+        .connect()
+        .await
+        .context("Failed to connect to database")?;
+
+    let results: Vec<AffectedTxBounds> = conn
+        .results(query)
+        .await
+        .context("Failed to execute query")?;
+
+    let Some(AffectedTxBounds {
+        tx_lo: Some(tx_lo),
+        tx_hi: Some(tx_hi_inclusive),
+    }) = results.first()
+    else {
+        return Ok(None);
+    };
+
+    let tx_lo = (*tx_lo as u64).max(tx_bounds.start);
+    let tx_hi = (*tx_hi_inclusive as u64 + 1).min(tx_bounds.end);
+    Ok((tx_lo < tx_hi).then_some(tx_lo..tx_hi))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use async_graphql::connection::CursorType;
+    use fastcrypto::encoding::{Base64, Encoding};
+    use sui_types::{gas_coin::GAS, TypeTag};
+
+    use crate::{api::scalars::cursor::JsonCursor, pagination::PageLimits};
+
+    use super::*;
+
+    const LIMITS: PageLimits = PageLimits {
+        default: 10,
+        max: 10,
+    };
+
+    fn cursor(s: &str) -> CTransaction {
+        CTransaction::decode_cursor(&Base64::encode(s)).unwrap()
+    }
+
+    #[test]
+    fn test_transaction_cursor_encoding() {
+        // Transaction cursors are encoded as their bare sequence number.
+        let tx = JsonCursor::new(TransactionCursor::Transaction(42));
+        assert_eq!(tx.encode_cursor(), JsonCursor::new(42u64).encode_cursor());
+        assert_eq!(*cursor("42"), TransactionCursor::Transaction(42));
+
+        assert_eq!(
+            *cursor(r#"{"checkpoint":7}"#),
+            TransactionCursor::Checkpoint { checkpoint: 7 }
+        );
+    }
+
+    #[test]
+    fn test_filter_from_checkpoint_cursors() {
+        let page = Page::from_params(
+            &LIMITS,
+            None,
+            Some(cursor(r#"{"checkpoint":7}"#)),
+            None,
+            Some(cursor(r#"{"checkpoint":9}"#)),
+        )
+        .unwrap();
+
+        let filter = TransactionFilter::from_cursors(&page);
+        assert_eq!(filter.after_checkpoint, Some(UInt53::from(6u64)));
+        assert_eq!(filter.before_checkpoint, Some(UInt53::from(9u64)));
+    }
+
+    #[test]
+    fn test_filter_from_transaction_cursors() {
+        let page = Page::from_params(&LIMITS, None, Some(cursor("7")), None, None).unwrap();
+
+        let filter = TransactionFilter::from_cursors(&page);
+        assert_eq!(filter.after_checkpoint, None);
+        assert_eq!(filter.before_checkpoint, None);
+    }
+
+    #[test]
+    fn test_intersect_timestamps() {
+        let ts = |ms| Some(DateTime::from_ms(ms).unwrap());
+        let earlier = TransactionFilter {
+            after_timestamp: ts(1_000),
+            before_timestamp: ts(5_000),
+            ..Default::default()
+        };
+
+        let later = TransactionFilter {
+            after_timestamp: ts(2_000),
+            before_timestamp: ts(9_000),
+            ..Default::default()
+        };
+
+        // Timestamp bounds narrow each other, like checkpoint bounds.
+        let filter = earlier.intersect(later).unwrap();
+        assert_eq!(filter.after_timestamp, ts(2_000));
+        assert_eq!(filter.before_timestamp, ts(5_000));
+        assert!(!filter.is_indexed());
+    }
+
+    #[test]
+    fn test_intersect_exclusions() {
+        let sender = SuiAddress::from(NativeSuiAddress::random_for_testing_only());
+        let excluding = TransactionFilter {
+            not_sent_address: Some(sender),
+            exclude_kind: Some(TransactionKindInput::SystemTx),
+            ..Default::default()
+        };
+
+        // Exclusions are kept when combined with bounds.
+        let bounded = TransactionFilter {
+            at_checkpoint: Some(UInt53::from(7u64)),
+            ..Default::default()
+        };
+
+        let filter = excluding.clone().intersect(bounded).unwrap();
+        assert_eq!(filter.at_checkpoint, Some(UInt53::from(7u64)));
+        assert_eq!(filter.not_sent_address, Some(sender));
+        assert_eq!(filter.exclude_kind, Some(TransactionKindInput::SystemTx));
+        assert!(filter.has_exclusions());
+        assert_eq!(
+            filter.exclusion_pipelines(),
+            vec!["tx_affected_addresses", "tx_kinds"]
+        );
+
+        // Only one value of each negated field can be excluded at a time.
+        let other_kind = TransactionFilter {
+            exclude_kind: Some(TransactionKindInput::ProgrammableTx),
+            ..Default::default()
+        };
+        assert!(excluding.intersect(other_kind).is_none());
+    }
+
+    #[test]
+    fn test_intersect_coin_types() {
+        let sui = TransactionFilter {
+            uses_coin_type: Some(TypeInput(GAS::type_tag())),
+            ..Default::default()
+        };
+
+        let bounded = TransactionFilter {
+            before_checkpoint: Some(UInt53::from(7u64)),
+            ..Default::default()
+        };
+
+        let filter = sui.clone().intersect(bounded).unwrap();
+        assert_eq!(filter.uses_coin_type, Some(TypeInput(GAS::type_tag())));
+        assert_eq!(
+            filter.coin_type_bytes().unwrap(),
+            Some(bcs::to_bytes(&GAS::type_tag()).unwrap())
+        );
+
+        // A transaction can use coins of many types, but the filter only accepts one.
+        let other = TransactionFilter {
+            uses_coin_type: Some(TypeInput(TypeTag::from_str("0x42::usdc::USDC").unwrap())),
+            ..Default::default()
+        };
+        assert!(sui.intersect(other).is_none());
+    }
+
+    #[test]
+    fn test_any_of_branches() {
+        let object = AffectedObjectFilter {
+            address: SuiAddress::from(NativeSuiAddress::random_for_testing_only()),
+            after_version: None,
+            before_version: None,
+        };
+
+        let filter = TransactionFilter {
+            at_checkpoint: Some(UInt53::from(7u64)),
+            any_of: Some(vec![
+                TransactionFilter {
+                    affected_object: Some(object.clone()),
+                    ..Default::default()
+                },
+                TransactionFilter {
+                    uses_coin_type: Some(TypeInput(GAS::type_tag())),
+                    ..Default::default()
+                },
+                // Inconsistent with the rest of the filter, so it does not become a branch.
+                TransactionFilter {
+                    at_checkpoint: Some(UInt53::from(8u64)),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        // Bounds from cursors apply to every branch.
+        let bounded = TransactionFilter {
+            after_checkpoint: Some(UInt53::from(3u64)),
+            ..Default::default()
+        };
+
+        let branches = filter.intersect(bounded).unwrap().branches();
+        assert_eq!(branches.len(), 2);
+        for branch in &branches {
+            assert_eq!(branch.after_checkpoint, Some(UInt53::from(3u64)));
+            assert_eq!(branch.at_checkpoint, Some(UInt53::from(7u64)));
+            assert!(branch.any_of.is_none());
+            assert!(branch.is_indexed());
+        }
+
+        assert_eq!(branches[0].affected_object, Some(object));
+        assert_eq!(branches[1].uses_coin_type, Some(TypeInput(GAS::type_tag())));
+
+        // A filter without `anyOf` is its only branch.
+        let branches = TransactionFilter::default().branches();
+        assert_eq!(branches.len(), 1);
+        assert!(!branches[0].is_indexed());
+    }
+
+    #[test]
+    fn test_validate_any_of() {
+        let any_of = |filters: Vec<TransactionFilter>| TransactionFilter {
+            any_of: Some(filters),
+            ..Default::default()
+        };
+
+        assert!(Validator.check(&TransactionFilter::default()).is_ok());
+        assert!(Validator
+            .check(&any_of(vec![
+                TransactionFilter::default();
+                MAX_ANY_OF_FILTERS
+            ]))
+            .is_ok());
+
+        assert!(Validator.check(&any_of(vec![])).is_err());
+        assert!(Validator
+            .check(&any_of(vec![
+                TransactionFilter::default();
+                MAX_ANY_OF_FILTERS + 1
+            ]))
+            .is_err());
+        assert!(Validator
+            .check(&any_of(vec![any_of(vec![TransactionFilter::default()])]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_filter_from_genesis_checkpoint_cursor() {
+        let page = Page::from_params(
+            &LIMITS,
+            None,
+            Some(cursor(r#"{"checkpoint":0}"#)),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let filter = TransactionFilter::from_cursors(&page);
+        assert_eq!(filter.after_checkpoint, None);
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{ops::Range, sync::Arc};
+use std::{collections::BTreeSet, ops::Range, sync::Arc};
 
 use anyhow::Context as _;
 use async_graphql::{
@@ -9,22 +9,36 @@ use async_graphql::{
     dataloader::DataLoader,
     Context, Object,
 };
+use diesel::{
+    prelude::QueryableByName,
+    sql_types::{BigInt, Bytea},
+};
 use fastcrypto::encoding::{Base58, Encoding};
+use serde::{Deserialize, Serialize};
 use sui_indexer_alt_reader::{
     kv_loader::{KvLoader, TransactionContents as NativeTransactionContents},
     pg_reader::PgReader,
     tx_digests::TxDigestKey,
 };
+use sui_pg_db::query::Query;
+use sui_sql_macro::query;
 
 use sui_types::{
     base_types::SuiAddress as NativeSuiAddress,
     digests::TransactionDigest,
-    transaction::{TransactionDataAPI, TransactionExpiration},
+    transaction::{
+        TransactionDataAPI, TransactionExpiration, TransactionKind as NativeTransactionKind,
+    },
 };
 
 use crate::{
-    api::scalars::{base64::Base64, cursor::JsonCursor, digest::Digest},
-    error::RpcError,
+    api::scalars::{
+        base64::Base64,
+        cursor::{BcsCursor, JsonCursor},
+        digest::Digest,
+    },
+    config::Limits,
+    error::{bad_user_input, unsupported_filter, RpcError},
     pagination::Page,
     scope::Scope,
     task::watermark::Watermarks,
@@ -32,16 +46,23 @@ use crate::{
 
 use super::{
     address::Address,
+    available_range::{AvailableRange, AvailableRangeFields},
     checkpoint::filter::checkpoint_bounds,
     epoch::Epoch,
     gas_input::GasInput,
-    transaction::filter::{tx_bounds, TransactionFilter},
+    representation::Representation,
+    transaction::{
+        data::TransactionData,
+        filter::{affected_object_tx_bounds, tx_bounds, TransactionFilter},
+    },
     transaction_effects::{EffectsContents, TransactionEffects},
     user_signature::UserSignature,
 };
 
-use super::transaction_kind::TransactionKind;
+use super::transaction_kind::{programmable::ProgrammableTransaction, TransactionKind};
 
+pub(crate) mod data;
+mod digest_prefix;
 pub(crate) mod filter;
 
 #[derive(Clone)]
@@ -56,7 +77,43 @@ pub(crate) struct TransactionContents {
     pub(crate) contents: Option<Arc<NativeTransactionContents>>,
 }
 
-pub(crate) type CTransaction = JsonCursor<u64>;
+pub(crate) type CTransaction = JsonCursor<TransactionCursor>;
+
+/// Cursor over transactions found by searching for a digest prefix, identifying a transaction by
+/// its digest.
+pub(crate) type CDigest = BcsCursor<Vec<u8>>;
+
+/// Cursor over transactions, identifying a transaction by its sequence number.
+type CTxSequenceNumber = JsonCursor<u64>;
+
+/// The position of a cursor over transactions. The service only returns cursors that point at
+/// transactions, but clients can also construct a cursor that points at the start of a checkpoint,
+/// to page through transactions from (or up to) that checkpoint without first looking up its
+/// transactions.
+///
+/// Cursors pointing at transactions are serialized as the transaction's sequence number, so they
+/// are compatible with cursors issued before checkpoint cursors were introduced.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub(crate) enum TransactionCursor {
+    /// The transaction with this sequence number.
+    Transaction(u64),
+
+    /// The boundary just before the first transaction in this checkpoint.
+    Checkpoint { checkpoint: u64 },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("Digest prefix must be at least {min} characters long, got {actual}")]
+    PrefixTooShort { min: usize, actual: usize },
+
+    #[error("Digest prefix {0:?} is longer than any transaction digest")]
+    PrefixTooLong(String),
+
+    #[error("Digest prefix contains {0:?}, which is not a Base58 character")]
+    PrefixNotBase58(char),
+}
 
 /// Description of a transaction, the unit of activity on Sui.
 #[Object]
@@ -85,6 +142,29 @@ impl Transaction {
         ))
     }
 
+    /// The commands and inputs of this transaction, decoded into structured form, if it is a programmable transaction.
+    ///
+    /// This is equivalent to the `ProgrammableTransaction` member of `kind`, and is `null` for system transactions.
+    async fn programmable_transaction(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<ProgrammableTransaction>, RpcError> {
+        let contents = self.contents.fetch(ctx, self.digest).await?;
+        let Some(content) = &contents.contents else {
+            return Ok(None);
+        };
+
+        let NativeTransactionKind::ProgrammableTransaction(native) = content.data()?.into_kind()
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(ProgrammableTransaction {
+            native,
+            scope: contents.scope.clone(),
+        }))
+    }
+
     #[graphql(flatten)]
     async fn contents(&self, ctx: &Context<'_>) -> Result<TransactionContents, RpcError> {
         self.contents.fetch(ctx, self.digest).await
@@ -141,6 +221,18 @@ impl TransactionContents {
         Ok(Some(Base64(content.raw_transaction()?)))
     }
 
+    /// The data that the sender signed for this transaction, offered as BCS, JSON, or both (the default), depending on `representation`. Representations that are not requested are not rendered.
+    async fn transaction_data(
+        &self,
+        representation: Option<Representation>,
+    ) -> Option<TransactionData> {
+        let content = self.contents.as_ref()?;
+        Some(TransactionData {
+            contents: content.clone(),
+            representation: representation.unwrap_or_default(),
+        })
+    }
+
     /// User signatures for this transaction.
     async fn signatures(&self) -> Result<Vec<UserSignature>, RpcError> {
         let Some(content) = &self.contents else {
@@ -194,31 +286,106 @@ impl Transaction {
         scope: Scope,
         page: Page<CTransaction>,
         filter: TransactionFilter,
-    ) -> Result<Connection<String, Transaction>, RpcError> {
-        let mut conn = Connection::new(false, false);
+    ) -> Result<Connection<String, Transaction, AvailableRangeFields>, RpcError> {
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        let mut reader_lo = watermarks.pipeline_lo_watermark("tx_digests")?.checkpoint();
+
+        // Cursors that point at checkpoints are equivalent to bounds on the filter, leaving only
+        // the cursors that point at transactions on the page.
+        let Some(filter) = filter.intersect(TransactionFilter::from_cursors(&page)) else {
+            let range = AvailableRange::new(&scope, reader_lo);
+            return Ok(Connection::with_additional_fields(
+                false,
+                false,
+                range.into(),
+            ));
+        };
 
-        if page.limit() == 0 {
-            return Ok(Connection::new(false, false));
+        let page = page.map_cursors(|cursor| match *cursor {
+            TransactionCursor::Transaction(tx_sequence_number) => {
+                Some(JsonCursor::new(tx_sequence_number))
+            }
+            TransactionCursor::Checkpoint { .. } => None,
+        });
+
+        // The filter's results are the union of the results of its branches, each of which is
+        // bounded separately.
+        let branches = filter.branches();
+
+        let mut pipelines = BTreeSet::new();
+        for branch in &branches {
+            pipelines.extend(branch.exclusion_pipelines());
+            if branch.affected_object.is_some() {
+                pipelines.extend(["obj_versions", "tx_affected_objects"]);
+            }
+
+            if branch.uses_coin_type.is_some() {
+                pipelines.insert("tx_coin_types");
+            }
         }
 
-        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        // A filter needs every pipeline that it reads from to be indexed, otherwise the service
+        // cannot support it.
+        for pipeline in pipelines {
+            let lo = watermarks
+                .pipeline_lo_watermark(pipeline)
+                .map_err(|_| {
+                    unsupported_filter(format!(
+                        "Filtering transactions by data from the '{pipeline}' pipeline"
+                    ))
+                })?
+                .checkpoint();
+            reader_lo = reader_lo.max(lo);
+        }
 
-        let reader_lo = watermarks.pipeline_lo_watermark("tx_digests")?.checkpoint();
+        let range = AvailableRange::new(&scope, reader_lo);
+        let mut conn = Connection::with_additional_fields(false, false, range.into());
+        if page.limit() == 0 {
+            return Ok(conn);
+        }
 
         let global_tx_hi = watermarks.high_watermark().transaction();
 
-        let Some(cp_bounds) = checkpoint_bounds(
-            filter.after_checkpoint.map(u64::from),
-            filter.at_checkpoint.map(u64::from),
-            filter.before_checkpoint.map(u64::from),
-            reader_lo,
-            scope.checkpoint_viewed_at(),
-        ) else {
-            return Ok(Connection::new(false, false));
-        };
+        let mut bounded = vec![];
+        for branch in branches {
+            let Some(branch) = branch
+                .resolve_timestamps(ctx, reader_lo, scope.checkpoint_viewed_at())
+                .await?
+            else {
+                continue;
+            };
+
+            let Some(cp_bounds) = checkpoint_bounds(
+                branch.after_checkpoint.map(u64::from),
+                branch.at_checkpoint.map(u64::from),
+                branch.before_checkpoint.map(u64::from),
+                reader_lo,
+                scope.checkpoint_viewed_at(),
+            ) else {
+                continue;
+            };
+
+            let tx_bounds = tx_bounds(ctx, &cp_bounds, global_tx_hi).await?;
+            let tx_bounds = if let Some(affected_object) = &branch.affected_object {
+                let Some(tx_bounds) =
+                    affected_object_tx_bounds(ctx, affected_object, &tx_bounds).await?
+                else {
+                    continue;
+                };
+
+                tx_bounds
+            } else {
+                tx_bounds
+            };
+
+            bounded.push((branch, tx_bounds));
+        }
 
-        let tx_bounds = tx_bounds(ctx, &cp_bounds, global_tx_hi).await?;
-        let tx_digest_keys = tx_unfiltered(&tx_bounds, &page);
+        let tx_digest_keys = match bounded.as_slice() {
+            [] => return Ok(conn),
+            [(filter, tx_bounds)] if !filter.is_indexed() => tx_unfiltered(tx_bounds, &page),
+            bounded => tx_filtered(ctx, bounded, &page).await?,
+        };
 
         // Paginate the resulting tx_sequence_numbers and create cursor objects for pagination.
         let (prev, next, results) = page.paginate_results(tx_digest_keys, |&t| JsonCursor::new(t));
@@ -251,11 +418,128 @@ impl Transaction {
 
         Ok(conn)
     }
+
+    /// Paginate through the transactions whose Base58 digests start with `prefix`, in order of
+    /// their digests.
+    ///
+    /// The prefix is converted into ranges of digest bytes, which are looked up through the index
+    /// on `tx_digests`, so the cost of the search depends on how many transactions match, which
+    /// is why the prefix must be at least `Limits::min_digest_prefix_length` characters long.
+    pub(crate) async fn search_by_digest_prefix(
+        ctx: &Context<'_>,
+        scope: Scope,
+        page: Page<CDigest>,
+        prefix: &str,
+    ) -> Result<Connection<String, Transaction, AvailableRangeFields>, RpcError<Error>> {
+        let limits: &Limits = ctx.data()?;
+        let ranges = digest_prefix::digest_ranges(prefix, limits.min_digest_prefix_length)
+            .map_err(bad_user_input)?;
+
+        let range = AvailableRange::for_pipeline(ctx, &scope, "tx_digests")?;
+        let mut conn = Connection::with_additional_fields(false, false, range.into());
+        if ranges.is_empty() || page.limit() == 0 {
+            return Ok(conn);
+        }
+
+        let mut matches = query!("");
+        for (i, range) in ranges.iter().enumerate() {
+            if i > 0 {
+                matches += query!(" OR ");
+            }
+
+            matches += query!(
+                "t.tx_digest BETWEEN {Bytea} AND {Bytea}",
+                range.start().as_slice(),
+                range.end().as_slice(),
+            );
+        }
+
+        let mut pagination = query!("");
+        if let Some(after) = page.after() {
+            pagination += query!(" AND {Bytea} <= t.tx_digest", after.as_slice());
+        }
+
+        if let Some(before) = page.before() {
+            pagination += query!(" AND t.tx_digest <= {Bytea}", before.as_slice());
+        }
+
+        // Transactions after the checkpoint being viewed are excluded by bounding their sequence
+        // numbers by the first transaction of the next checkpoint, or the global high watermark
+        // if that checkpoint has not been indexed yet.
+        let watermarks: &Arc<Watermarks> = ctx.data()?;
+        let query = query!(
+            r#"
+            SELECT
+                t.tx_digest
+            FROM
+                tx_digests t
+            WHERE
+                ({})
+            AND t.tx_sequence_number < COALESCE(
+                (
+                    SELECT
+                        tx_lo
+                    FROM
+                        cp_sequence_numbers
+                    WHERE
+                        cp_sequence_number = {BigInt} + 1
+                    LIMIT 1
+                ),
+                {BigInt}
+            )
+            {}
+            ORDER BY {}
+            LIMIT {BigInt}
+            "#,
+            matches,
+            scope.checkpoint_viewed_at() as i64,
+            watermarks.high_watermark().transaction() as i64,
+            pagination,
+            if page.is_from_front() {
+                query!("t.tx_digest")
+            } else {
+                query!("t.tx_digest DESC")
+            },
+            page.limit_with_overhead() as i64,
+        );
+
+        let pg_reader: &PgReader = ctx.data()?;
+        let mut c = pg_reader
+            .connect()
+            .await
+            .context("Failed to connect to database")?;
+
+        let mut results: Vec<StoredTxDigest> = c
+            .results(query)
+            .await
+            .context("Failed to search transactions by digest prefix")?;
+
+        if !page.is_from_front() {
+            results.reverse();
+        }
+
+        let (prev, next, results) =
+            page.paginate_results(results, |t| BcsCursor::new(t.tx_digest.clone()));
+
+        for (cursor, stored) in results {
+            let digest = TransactionDigest::try_from(stored.tx_digest)
+                .context("Failed to deserialize transaction digest")?;
+            conn.edges.push(Edge::new(
+                cursor.encode_cursor(),
+                Self::with_id(scope.clone(), digest),
+            ));
+        }
+
+        conn.has_previous_page = prev;
+        conn.has_next_page = next;
+
+        Ok(conn)
+    }
 }
 
 /// The tx_sequence_numbers with cursors applied inclusively.
 /// Results are limited to `page.limit() + 2` to allow has_previous_page and has_next_page calculations.
-fn tx_unfiltered(tx_bounds: &Range<u64>, page: &Page<CTransaction>) -> Vec<u64> {
+fn tx_unfiltered(tx_bounds: &Range<u64>, page: &Page<CTxSequenceNumber>) -> Vec<u64> {
     // Inclusive cursor bounds
     let pg_lo = page
         .after()
@@ -279,6 +563,166 @@ fn tx_unfiltered(tx_bounds: &Range<u64>, page: &Page<CTransaction>) -> Vec<u64>
     }
 }
 
+/// The tx_sequence_numbers of transactions that match any of the `branches`, with cursors applied
+/// inclusively. Results are limited to `page.limit() + 2` to allow has_previous_page and
+/// has_next_page calculations.
+///
+/// Each branch is fetched by its own bounded query, which is limited in the same way, so that
+/// every page of the union is among the results of those queries. When there are multiple
+/// branches, their results are merged with a `UNION` (which also removes transactions that match
+/// more than one branch), before the page is limited again.
+async fn tx_filtered(
+    ctx: &Context<'_>,
+    branches: &[(TransactionFilter, Range<u64>)],
+    page: &Page<CTxSequenceNumber>,
+) -> Result<Vec<u64>, RpcError> {
+    let pg_reader: &PgReader = ctx.data()?;
+
+    let query = if let [(filter, tx_bounds)] = branches {
+        tx_filtered_query(filter, tx_bounds, page)?
+    } else {
+        let mut union = query!("");
+        for (i, (filter, tx_bounds)) in branches.iter().enumerate() {
+            if i > 0 {
+                union += query!("UNION");
+            }
+
+            union += query!("({})", tx_filtered_query(filter, tx_bounds, page)?);
+        }
+
+        query!(
+            r#"
+            SELECT
+                u.tx_sequence_number
+            FROM
+                ({}) u
+            ORDER BY {}
+            LIMIT {BigInt}
+            "#,
+            union,
+            if page.is_from_front() {
+                query!("u.tx_sequence_number")
+            } else {
+                query!("u.tx_sequence_number DESC")
+            },
+            page.limit_with_overhead() as i64,
+        )
+    };
+
+    let mut conn = pg_reader
+        .connect()
+        .await
+        .context("Failed to connect to database")?;
+
+    let results: Vec<TxSequenceNumber> = conn
+        .results(query)
+        .await
+        .context("Failed to fetch filtered transactions")?;
+
+    // Graphql last syntax expects results to be in ascending order. If we are paginating backwards,
+    // we reverse the results after applying limits.
+    let mut results: Vec<_> = results
+        .into_iter()
+        .map(|r| r.tx_sequence_number as u64)
+        .collect();
+    if !page.is_from_front() {
+        results.reverse();
+    }
+
+    Ok(results)
+}
+
+/// A query for the tx_sequence_numbers of transactions within `tx_bounds` that affected `filter`'s
+/// affected object (if it has one), that used its coin type (if it has one), and that its negated
+/// fields do not exclude, with cursors applied inclusively, in the order of the page, and limited to
+/// `page.limit() + 2`.
+fn tx_filtered_query(
+    filter: &TransactionFilter,
+    tx_bounds: &Range<u64>,
+    page: &Page<CTxSequenceNumber>,
+) -> Result<Query<'static>, RpcError> {
+    // Inclusive cursor bounds
+    let pg_lo = page
+        .after()
+        .map_or(tx_bounds.start, |cursor| cursor.max(tx_bounds.start));
+    let pg_hi = page
+        .before()
+        .map(|cursor: &JsonCursor<u64>| cursor.saturating_add(1))
+        .map_or(tx_bounds.end, |cursor| cursor.min(tx_bounds.end));
+
+    // Transactions that affected an object are found in `tx_affected_objects`, transactions that
+    // used a coin type are found in `tx_coin_types`, and every transaction has an entry in
+    // `tx_digests`. If the filter constrains both the affected object and the coin type, the
+    // candidates come from the former, and are checked against the latter.
+    let coin_type = filter.coin_type_bytes()?;
+    let candidates = match (&filter.affected_object, coin_type) {
+        (Some(affected_object), coin_type) => {
+            let mut candidates = query!(
+                "tx_affected_objects t WHERE t.affected = {Bytea}",
+                NativeSuiAddress::from(affected_object.address).to_vec(),
+            );
+
+            if let Some(coin_type) = coin_type {
+                candidates += query!(
+                    r#"
+                    AND EXISTS (
+                        SELECT
+                            1
+                        FROM
+                            tx_coin_types c
+                        WHERE
+                            c.coin_type = {Bytea}
+                        AND c.tx_sequence_number = t.tx_sequence_number
+                    )
+                    "#,
+                    coin_type,
+                );
+            }
+
+            candidates
+        }
+
+        (None, Some(coin_type)) => query!("tx_coin_types t WHERE t.coin_type = {Bytea}", coin_type),
+        (None, None) => query!("tx_digests t WHERE TRUE"),
+    };
+
+    Ok(query!(
+        r#"
+        SELECT
+            t.tx_sequence_number
+        FROM
+            {}
+        AND {BigInt} <= t.tx_sequence_number
+        AND t.tx_sequence_number < {BigInt}
+        {}
+        ORDER BY {}
+        LIMIT {BigInt}
+        "#,
+        candidates,
+        pg_lo as i64,
+        pg_hi as i64,
+        filter.exclusions(),
+        if page.is_from_front() {
+            query!("t.tx_sequence_number")
+        } else {
+            query!("t.tx_sequence_number DESC")
+        },
+        page.limit_with_overhead() as i64,
+    ))
+}
+
+#[derive(QueryableByName)]
+struct StoredTxDigest {
+    #[diesel(sql_type = Bytea, column_name = "tx_digest")]
+    tx_digest: Vec<u8>,
+}
+
+#[derive(QueryableByName)]
+struct TxSequenceNumber {
+    #[diesel(sql_type = BigInt, column_name = "tx_sequence_number")]
+    tx_sequence_number: i64,
+}
+
 impl TransactionContents {
     fn empty(scope: Scope) -> Self {
         Self {
@@ -31,6 +31,7 @@ use crate::{
 };
 
 use super::{
+    available_range::{AvailableRange, AvailableRangeFields},
     balance_change::BalanceChange,
     checkpoint::Checkpoint,
     epoch::Epoch,
@@ -177,9 +178,16 @@ impl EffectsContents {
         after: Option<CEvent>,
         last: Option<u64>,
         before: Option<CEvent>,
-    ) -> Result<Option<Connection<String, Event>>, RpcError> {
+    ) -> Result<Option<Connection<String, Event, AvailableRangeFields>>, RpcError> {
+        // Events are read from the transaction's contents, so they are available for as long as
+        // the transaction is.
+        let range = AvailableRange::for_pipeline(ctx, &self.scope, "kv_transactions")?;
         let Some(content) = &self.contents else {
-            return Ok(Some(Connection::new(false, false)));
+            return Ok(Some(Connection::with_additional_fields(
+                false,
+                false,
+                range.into(),
+            )));
         };
 
         let pagination: &PaginationConfig = ctx.data()?;
@@ -188,7 +196,11 @@ impl EffectsContents {
 
         let events = content.events()?;
         let cursors = page.paginate_indices(events.len());
-        let mut conn = Connection::new(cursors.has_previous_page, cursors.has_next_page);
+        let mut conn = Connection::with_additional_fields(
+            cursors.has_previous_page,
+            cursors.has_next_page,
+            range.into(),
+        );
 
         let transaction_digest = content.digest()?;
         let timestamp_ms = content.timestamp_ms();
@@ -366,7 +378,7 @@ impl EffectsContents {
         after: Option<CDependency>,
         last: Option<u64>,
         before: Option<CDependency>,
-    ) -> Result<Option<Connection<String, Transaction>>, RpcError> {
+    ) -> Result<Option<Connection<String, Transaction, AvailableRangeFields>>, RpcError> {
         let pagination: &PaginationConfig = ctx.data()?;
         let limits = pagination.limits("TransactionEffects", "dependencies");
         let page = Page::from_params(limits, first, after, last, before)?;
@@ -379,7 +391,12 @@ impl EffectsContents {
         let dependencies = effects.dependencies();
         let cursors = page.paginate_indices(dependencies.len());
 
-        let mut conn = Connection::new(cursors.has_previous_page, cursors.has_next_page);
+        let range = AvailableRange::for_pipeline(ctx, &self.scope, "kv_transactions")?;
+        let mut conn = Connection::with_additional_fields(
+            cursors.has_previous_page,
+            cursors.has_next_page,
+            range.into(),
+        );
         for edge in cursors.edges {
             let dependency_digest = dependencies[*edge.cursor];
             let transaction = Transaction::with_id(self.scope.clone(), dependency_digest);
@@ -0,0 +1,234 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _};
+use async_graphql::{dataloader::DataLoader, Context, Enum, SimpleObject};
+use fastcrypto_zkp::bn254::{
+    zk_login::{JwkId, JWK},
+    zk_login_api::ZkLoginEnv,
+};
+use im::hashmap::HashMap as ImHashMap;
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+use sui_indexer_alt_reader::{
+    epochs::CheckpointBoundedEpochStartKey, kv_loader::KvLoader,
+    object_versions::CheckpointBoundedObjectVersionKey, pg_reader::PgReader,
+};
+use sui_protocol_config::Chain;
+use sui_types::{
+    authenticator_state::{ActiveJwk, AuthenticatorState, AuthenticatorStateInner},
+    base_types::{ObjectID, SuiAddress as NativeSuiAddress},
+    crypto::ToFromBytes,
+    digests::ChainIdentifier,
+    dynamic_field::{derive_dynamic_field_id, Field},
+    object::Object as NativeObject,
+    signature::{GenericSignature, VerifyParams},
+    signature_verification::VerifiedDigestCache,
+    transaction::TransactionData,
+    TypeTag, SUI_AUTHENTICATOR_STATE_OBJECT_ID,
+};
+use tracing::warn;
+
+use crate::{
+    error::{bad_user_input, RpcError},
+    scope::Scope,
+};
+
+/// The intent scope that the bytes were signed with, which determines how they are parsed for
+/// signature verification.
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ZkLoginIntentScope {
+    /// The bytes are parsed as BCS-serialized transaction data.
+    TransactionData,
+
+    /// The bytes are parsed as a personal message.
+    PersonalMessage,
+}
+
+/// The result of verifying a zkLogin signature.
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ZkLoginVerifyResult {
+    /// Whether the signature was verified successfully.
+    pub success: Option<bool>,
+
+    /// The reasons that verification failed, if it did not succeed.
+    pub errors: Option<Vec<String>>,
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum Error {
+    #[error("Invalid signature: {0}")]
+    BadSignature(String),
+
+    #[error("Invalid transaction data: {0}")]
+    BadTransactionData(String),
+
+    #[error("Only zkLogin signatures can be verified")]
+    NotZkLogin,
+}
+
+impl ZkLoginVerifyResult {
+    /// Verify `signature` as a zkLogin signature over `bytes` by `author`, as of the latest
+    /// checkpoint in `scope`. The signature is checked against the JWKs that were active on-chain
+    /// at that checkpoint, and the epoch that it belongs to.
+    ///
+    /// Signatures that fail verification produce an unsuccessful result, rather than an error.
+    /// Errors are reserved for inputs that cannot be verified at all.
+    pub(crate) async fn verify(
+        ctx: &Context<'_>,
+        scope: &Scope,
+        bytes: Vec<u8>,
+        signature: Vec<u8>,
+        intent_scope: ZkLoginIntentScope,
+        author: NativeSuiAddress,
+    ) -> Result<Self, RpcError<Error>> {
+        let signature = GenericSignature::from_bytes(&signature)
+            .map_err(|e| bad_user_input(Error::BadSignature(e.to_string())))?;
+
+        if !matches!(signature, GenericSignature::ZkLoginAuthenticator(_)) {
+            return Err(bad_user_input(Error::NotZkLogin));
+        }
+
+        let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+        let Some(epoch) = pg_loader
+            .load_one(CheckpointBoundedEpochStartKey(scope.checkpoint_viewed_at()))
+            .await
+            .context("Failed to fetch current epoch")?
+        else {
+            return Err(anyhow!("Cannot find current epoch").into());
+        };
+
+        let chain_id: ChainIdentifier = *ctx.data()?;
+        let zklogin_env = match chain_id.chain() {
+            // Testnet and mainnet are treated the same since it is permanent.
+            Chain::Mainnet | Chain::Testnet => ZkLoginEnv::Prod,
+            _ => ZkLoginEnv::Test,
+        };
+
+        // The same parameters as JSON-RPC's `sui_verifyZkLoginSignature`, so that both services
+        // reach the same verdict.
+        let verify_params = VerifyParams::new(
+            active_jwks(ctx, scope).await?,
+            vec![],
+            zklogin_env,
+            true,
+            true,
+            true,
+            Some(30),
+            true,
+        );
+
+        let epoch = epoch.epoch as u64;
+        let cache = Arc::new(VerifiedDigestCache::new_empty());
+        let result = match intent_scope {
+            ZkLoginIntentScope::TransactionData => {
+                let tx_data: TransactionData = bcs::from_bytes(&bytes)
+                    .map_err(|e| bad_user_input(Error::BadTransactionData(e.to_string())))?;
+
+                let message = IntentMessage::new(Intent::sui_transaction(), tx_data);
+                signature.verify_authenticator(&message, author, epoch, &verify_params, cache)
+            }
+
+            ZkLoginIntentScope::PersonalMessage => {
+                let data = PersonalMessage { message: bytes };
+                let message = IntentMessage::new(Intent::personal_message(), data);
+                signature.verify_authenticator(&message, author, epoch, &verify_params, cache)
+            }
+        };
+
+        Ok(match result {
+            Ok(()) => Self {
+                success: Some(true),
+                errors: Some(vec![]),
+            },
+            Err(e) => Self {
+                success: Some(false),
+                errors: Some(vec![e.to_string()]),
+            },
+        })
+    }
+}
+
+/// The JWKs that were active as of the latest checkpoint in `scope`, read from the authenticator
+/// state object (`0x7`), keyed by their ID.
+async fn active_jwks(
+    ctx: &Context<'_>,
+    scope: &Scope,
+) -> Result<ImHashMap<JwkId, JWK>, RpcError<Error>> {
+    let outer = live_object(ctx, scope, SUI_AUTHENTICATOR_STATE_OBJECT_ID)
+        .await?
+        .context("Cannot find authenticator state")?;
+
+    let outer: AuthenticatorState = bcs::from_bytes(
+        outer
+            .data
+            .try_as_move()
+            .context("Authenticator state is not a Move object")?
+            .contents(),
+    )
+    .context("Failed to deserialize authenticator state")?;
+
+    let inner_id = derive_dynamic_field_id(
+        outer.id.id.bytes,
+        &TypeTag::U64,
+        &bcs::to_bytes(&outer.version).context("Failed to serialize version")?,
+    )
+    .context("Failed to derive authenticator state inner ID")?;
+
+    let inner = live_object(ctx, scope, inner_id)
+        .await?
+        .context("Cannot find authenticator state inner")?;
+
+    let inner: Field<u64, AuthenticatorStateInner> = bcs::from_bytes(
+        inner
+            .data
+            .try_as_move()
+            .context("Authenticator state inner is not a Move object")?
+            .contents(),
+    )
+    .context("Failed to deserialize authenticator state inner")?;
+
+    let mut jwks = ImHashMap::new();
+    for ActiveJwk { jwk_id, jwk, .. } in inner.value.active_jwks {
+        if jwks.contains_key(&jwk_id) {
+            warn!("JWK with kid {jwk_id:?} already exists");
+        } else {
+            jwks.insert(jwk_id, jwk);
+        }
+    }
+
+    Ok(jwks)
+}
+
+/// Load the latest version of the object with ID `id` as of the latest checkpoint in `scope`, or
+/// `None` if it does not exist at that checkpoint.
+async fn live_object(
+    ctx: &Context<'_>,
+    scope: &Scope,
+    id: ObjectID,
+) -> Result<Option<NativeObject>, RpcError<Error>> {
+    let pg_loader: &Arc<DataLoader<PgReader>> = ctx.data()?;
+    let kv_loader: &KvLoader = ctx.data()?;
+
+    let Some(stored) = pg_loader
+        .load_one(CheckpointBoundedObjectVersionKey(
+            id,
+            scope.checkpoint_viewed_at(),
+        ))
+        .await
+        .context("Failed to fetch object version")?
+    else {
+        return Ok(None);
+    };
+
+    // Lack of an object digest indicates that the object was deleted or wrapped at this version.
+    if stored.object_digest.is_none() {
+        return Ok(None);
+    }
+
+    Ok(kv_loader
+        .load_one_object(id, stored.object_version as u64)
+        .await
+        .context("Failed to fetch object contents")?)
+}
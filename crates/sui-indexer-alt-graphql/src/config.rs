@@ -13,7 +13,11 @@ use sui_protocol_config::{Chain, ProtocolConfig, ProtocolVersion};
 use tracing::warn;
 
 use crate::{
-    extensions::{query_limits::QueryLimitsConfig, timeout::TimeoutConfig},
+    extensions::{
+        authorization::{AuthorizationRules, Grants},
+        query_limits::QueryLimitsConfig,
+        timeout::TimeoutConfig,
+    },
     pagination::{PageLimits, PaginationConfig},
 };
 
@@ -27,6 +31,15 @@ pub struct RpcConfig {
 
     /// Configuration for the watermark task.
     pub watermark: WatermarkConfig,
+
+    /// Configuration for caches shared between requests.
+    pub cache: CacheConfig,
+
+    /// Restrictions on which callers can access which fields.
+    pub authorization: AuthorizationConfig,
+
+    /// Limits on how frequently callers can make requests.
+    pub rate_limit: RateLimitConfig,
 }
 
 #[DefaultConfig]
@@ -35,6 +48,9 @@ pub struct RpcLayer {
     pub limits: LimitsLayer,
     pub health: HealthLayer,
     pub watermark: WatermarkLayer,
+    pub cache: CacheLayer,
+    pub authorization: AuthorizationLayer,
+    pub rate_limit: RateLimitLayer,
 
     #[serde(flatten)]
     pub extra: toml::Table,
@@ -44,12 +60,17 @@ pub struct RpcLayer {
 pub struct HealthConfig {
     /// How long to wait for a health check to complete before timing out.
     pub max_checkpoint_lag: Duration,
+
+    /// How long to wait for each dependency (the database, the full node) to respond to a
+    /// readiness check, before considering it unavailable.
+    pub probe_timeout: Duration,
 }
 
 #[DefaultConfig]
 #[derive(Default, Clone, Debug)]
 pub struct HealthLayer {
     pub max_checkpoint_lag_ms: Option<u64>,
+    pub probe_timeout_ms: Option<u64>,
 
     #[serde(flatten)]
     pub extra: toml::Table,
@@ -130,6 +151,21 @@ pub struct Limits {
 
     /// Maximum budget in bytes to spend when outputting a structured Move value.
     pub max_move_value_bound: usize,
+
+    /// Maximum depth a Display format string is allowed to nest field accesses.
+    pub max_display_field_depth: usize,
+
+    /// Maximum number of bytes occupied by Display field names and values in the output for a
+    /// single object.
+    pub max_display_output_size: usize,
+
+    /// Maximum number of each kind of entry (items, listings, locks) read from a single kiosk, and
+    /// of transfer policies read for each type of item in it.
+    pub max_kiosk_entries: usize,
+
+    /// Minimum number of characters in the Base58 digest prefix that transactions can be searched
+    /// by. Shorter prefixes match too many transactions to be useful.
+    pub min_digest_prefix_length: usize,
 }
 
 #[DefaultConfig]
@@ -152,6 +188,10 @@ pub struct LimitsLayer {
     pub max_type_nodes: Option<usize>,
     pub max_move_value_depth: Option<usize>,
     pub max_move_value_bound: Option<usize>,
+    pub max_display_field_depth: Option<usize>,
+    pub max_display_output_size: Option<usize>,
+    pub max_kiosk_entries: Option<usize>,
+    pub min_digest_prefix_length: Option<usize>,
 
     #[serde(flatten)]
     pub extra: toml::Table,
@@ -171,12 +211,84 @@ pub struct WatermarkLayer {
     pub extra: toml::Table,
 }
 
+pub struct CacheConfig {
+    /// The maximum number of coin metadata and treasury cap objects to cache, per coin type and
+    /// checkpoint viewed.
+    pub coin_metadata_capacity: usize,
+}
+
+#[DefaultConfig]
+#[derive(Default, Clone, Debug)]
+pub struct CacheLayer {
+    pub coin_metadata_capacity: Option<usize>,
+
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+#[derive(Default)]
+pub struct AuthorizationConfig {
+    /// Fields that can only be accessed by callers that have been granted access to them, as
+    /// `Type.field` coordinates (e.g. `MovePackage.moduleBcs`). All other fields can be accessed by
+    /// anyone.
+    pub restricted: BTreeSet<String>,
+
+    /// The restricted fields that callers are granted access to, by the API key they supply in the
+    /// `x-sui-rpc-api-key` header. A grant of `*` gives access to all restricted fields.
+    pub api_keys: BTreeMap<String, BTreeSet<String>>,
+
+    /// The restricted fields that callers are granted access to, by the origin of their request.
+    /// A grant of `*` gives access to all restricted fields.
+    pub origins: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[DefaultConfig]
+#[derive(Default, Clone, Debug)]
+pub struct AuthorizationLayer {
+    pub restricted: Option<BTreeSet<String>>,
+    pub api_keys: Option<BTreeMap<String, BTreeSet<String>>>,
+    pub origins: Option<BTreeMap<String, BTreeSet<String>>>,
+
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+pub struct RateLimitConfig {
+    /// The number of requests per second each caller can make on average, or 0 to disable rate
+    /// limiting. Callers are identified by their IP address, unless they supply one of the API
+    /// keys in `api_keys`.
+    pub requests_per_second: u32,
+
+    /// The number of requests a caller can make at once, after it has not made any requests for a
+    /// while.
+    pub burst: u32,
+
+    /// Requests per second for callers supplying each API key in the `x-sui-rpc-api-key` header,
+    /// overriding `requests_per_second`. These callers are limited per API key rather than per IP
+    /// address.
+    pub api_keys: BTreeMap<String, u32>,
+}
+
+#[DefaultConfig]
+#[derive(Default, Clone, Debug)]
+pub struct RateLimitLayer {
+    pub requests_per_second: Option<u32>,
+    pub burst: Option<u32>,
+    pub api_keys: Option<BTreeMap<String, u32>>,
+
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
 impl RpcLayer {
     pub fn example() -> Self {
         Self {
             limits: Limits::default().into(),
             health: HealthConfig::default().into(),
             watermark: WatermarkConfig::default().into(),
+            cache: CacheConfig::default().into(),
+            authorization: AuthorizationConfig::default().into(),
+            rate_limit: RateLimitConfig::default().into(),
             extra: Default::default(),
         }
     }
@@ -187,6 +299,9 @@ impl RpcLayer {
             limits: self.limits.finish(Limits::default()),
             health: self.health.finish(HealthConfig::default()),
             watermark: self.watermark.finish(WatermarkConfig::default()),
+            cache: self.cache.finish(CacheConfig::default()),
+            authorization: self.authorization.finish(AuthorizationConfig::default()),
+            rate_limit: self.rate_limit.finish(RateLimitConfig::default()),
         }
     }
 }
@@ -199,6 +314,10 @@ impl HealthLayer {
                 .max_checkpoint_lag_ms
                 .map(Duration::from_millis)
                 .unwrap_or(base.max_checkpoint_lag),
+            probe_timeout: self
+                .probe_timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(base.probe_timeout),
         }
     }
 }
@@ -229,8 +348,8 @@ impl Limits {
                 ("Mutation", "executeTransaction", "txBytes"),
                 ("Mutation", "executeTransaction", "signatures"),
                 ("Query", "simulateTransaction", "txBytes"),
-                ("Query", "verifyZkloginSignature", "bytes"),
-                ("Query", "verifyZkloginSignature", "signature"),
+                ("Query", "verifyZkLoginSignature", "bytes"),
+                ("Query", "verifyZkLoginSignature", "signature"),
             ]),
         }
     }
@@ -306,6 +425,16 @@ impl LimitsLayer {
             max_move_value_bound: self
                 .max_move_value_bound
                 .unwrap_or(base.max_move_value_bound),
+            max_display_field_depth: self
+                .max_display_field_depth
+                .unwrap_or(base.max_display_field_depth),
+            max_display_output_size: self
+                .max_display_output_size
+                .unwrap_or(base.max_display_output_size),
+            max_kiosk_entries: self.max_kiosk_entries.unwrap_or(base.max_kiosk_entries),
+            min_digest_prefix_length: self
+                .min_digest_prefix_length
+                .unwrap_or(base.min_digest_prefix_length),
         }
     }
 }
@@ -322,10 +451,82 @@ impl WatermarkLayer {
     }
 }
 
+impl CacheLayer {
+    pub(crate) fn finish(mut self, base: CacheConfig) -> CacheConfig {
+        check_extra("cache", mem::take(&mut self.extra));
+        CacheConfig {
+            coin_metadata_capacity: self
+                .coin_metadata_capacity
+                .unwrap_or(base.coin_metadata_capacity),
+        }
+    }
+}
+
+impl AuthorizationLayer {
+    pub(crate) fn finish(mut self, base: AuthorizationConfig) -> AuthorizationConfig {
+        check_extra("authorization", mem::take(&mut self.extra));
+        AuthorizationConfig {
+            restricted: self.restricted.unwrap_or(base.restricted),
+            api_keys: self.api_keys.unwrap_or(base.api_keys),
+            origins: self.origins.unwrap_or(base.origins),
+        }
+    }
+}
+
+impl RateLimitLayer {
+    pub(crate) fn finish(mut self, base: RateLimitConfig) -> RateLimitConfig {
+        check_extra("rate-limit", mem::take(&mut self.extra));
+        RateLimitConfig {
+            requests_per_second: self.requests_per_second.unwrap_or(base.requests_per_second),
+            burst: self.burst.unwrap_or(base.burst),
+            api_keys: self.api_keys.unwrap_or(base.api_keys),
+        }
+    }
+}
+
+impl AuthorizationConfig {
+    pub(crate) fn rules(&self) -> AuthorizationRules {
+        let mut restricted: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for coordinate in &self.restricted {
+            let Some((type_name, field)) = coordinate.split_once('.') else {
+                warn!("Ignoring restricted field {coordinate:?}, expected 'Type.field'");
+                continue;
+            };
+
+            restricted
+                .entry(type_name.to_owned())
+                .or_default()
+                .insert(field.to_owned());
+        }
+
+        let grants = |grants: &BTreeMap<String, BTreeSet<String>>| {
+            grants
+                .iter()
+                .map(|(caller, fields)| {
+                    let grants = if fields.contains("*") {
+                        Grants::All
+                    } else {
+                        Grants::Fields(fields.clone())
+                    };
+
+                    (caller.clone(), grants)
+                })
+                .collect()
+        };
+
+        AuthorizationRules {
+            restricted,
+            api_keys: grants(&self.api_keys),
+            origins: grants(&self.origins),
+        }
+    }
+}
+
 impl From<HealthConfig> for HealthLayer {
     fn from(value: HealthConfig) -> Self {
         Self {
             max_checkpoint_lag_ms: Some(value.max_checkpoint_lag.as_millis() as u64),
+            probe_timeout_ms: Some(value.probe_timeout.as_millis() as u64),
             extra: Default::default(),
         }
     }
@@ -351,6 +552,10 @@ impl From<Limits> for LimitsLayer {
             max_type_nodes: Some(value.max_type_nodes),
             max_move_value_depth: Some(value.max_move_value_depth),
             max_move_value_bound: Some(value.max_move_value_bound),
+            max_display_field_depth: Some(value.max_display_field_depth),
+            max_display_output_size: Some(value.max_display_output_size),
+            max_kiosk_entries: Some(value.max_kiosk_entries),
+            min_digest_prefix_length: Some(value.min_digest_prefix_length),
             extra: Default::default(),
         }
     }
@@ -365,10 +570,42 @@ impl From<WatermarkConfig> for WatermarkLayer {
     }
 }
 
+impl From<CacheConfig> for CacheLayer {
+    fn from(value: CacheConfig) -> Self {
+        Self {
+            coin_metadata_capacity: Some(value.coin_metadata_capacity),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<AuthorizationConfig> for AuthorizationLayer {
+    fn from(value: AuthorizationConfig) -> Self {
+        Self {
+            restricted: Some(value.restricted),
+            api_keys: Some(value.api_keys),
+            origins: Some(value.origins),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<RateLimitConfig> for RateLimitLayer {
+    fn from(value: RateLimitConfig) -> Self {
+        Self {
+            requests_per_second: Some(value.requests_per_second),
+            burst: Some(value.burst),
+            api_keys: Some(value.api_keys),
+            extra: Default::default(),
+        }
+    }
+}
+
 impl Default for HealthConfig {
     fn default() -> Self {
         Self {
             max_checkpoint_lag: Duration::from_secs(300),
+            probe_timeout: Duration::from_secs(5),
         }
     }
 }
@@ -418,6 +655,20 @@ impl Default for Limits {
             max_type_nodes,
             max_move_value_depth,
             max_move_value_bound: 1024 * 1024,
+            max_display_field_depth: 10,
+            max_display_output_size: 1024 * 1024,
+            max_kiosk_entries: 1000,
+            min_digest_prefix_length: 6,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 0,
+            burst: 50,
+            api_keys: BTreeMap::new(),
         }
     }
 }
@@ -430,6 +681,14 @@ impl Default for WatermarkConfig {
     }
 }
 
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            coin_metadata_capacity: 10_000,
+        }
+    }
+}
+
 /// Check whether there are any unrecognized extra fields and if so, warn about them.
 fn check_extra(pos: &str, extra: toml::Table) {
     if !extra.is_empty() {
@@ -13,6 +13,7 @@ use async_graphql::{ErrorExtensionValues, ErrorExtensions, Response, Value};
 pub(crate) mod code {
     pub const BAD_USER_INPUT: &str = "BAD_USER_INPUT";
     pub const FEATURE_UNAVAILABLE: &str = "FEATURE_UNAVAILABLE";
+    pub const FORBIDDEN: &str = "FORBIDDEN";
     pub const GRAPHQL_PARSE_FAILED: &str = "GRAPHQL_PARSE_FAILED";
     pub const GRAPHQL_VALIDATION_FAILED: &str = "GRAPHQL_VALIDATION_FAILED";
     pub const INTERNAL_SERVER_ERROR: &str = "INTERNAL_SERVER_ERROR";
@@ -20,15 +21,48 @@ pub(crate) mod code {
     pub const RESOURCE_EXHAUSTED: &str = "RESOURCE_EXHAUSTED";
 }
 
+/// Machine-readable reasons for an error, for the `extensions.errorCode` field of a GraphQL
+/// error. These identify what went wrong more precisely than `extensions.code`, so that clients
+/// can branch on them, rather than on error messages. Not every error has one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorCode {
+    /// A pagination cursor could not be decoded, or is inconsistent with the request it was
+    /// passed to (e.g. it disagrees with the other cursor, or points into the future).
+    BadCursor,
+
+    /// The request is for data that is outside the range that the service retains, e.g. because
+    /// it has been pruned.
+    OutOfRetention,
+
+    /// The request took too long to process.
+    Timeout,
+
+    /// An error produced by the internal workings of the service.
+    Internal,
+
+    /// The request uses a filter that the service does not support, e.g. because it does not
+    /// index the data that the filter needs.
+    UnsupportedFilter,
+}
+
+/// The extension that holds an error's [ErrorCode].
+pub(crate) const ERROR_CODE: &str = "errorCode";
+
 #[derive(thiserror::Error, Debug, Clone)]
 pub(crate) enum RpcError<E: std::error::Error = Infallible> {
     /// An error that is the user's fault.
     BadUserInput(Arc<E>),
 
+    /// A user error related to a pagination cursor.
+    BadCursor(Arc<E>),
+
     /// This feature is not available, because GraphQL does not have access to the underlying
     /// store.
     FeatureUnavailable { what: &'static str },
 
+    /// The caller is not authorized to access this part of the schema.
+    Forbidden { what: String },
+
     /// An error that is produced by the framework, it gets wrapped so that we can add an error
     /// extension to it.
     GraphQlError(async_graphql::Error),
@@ -36,6 +70,9 @@ pub(crate) enum RpcError<E: std::error::Error = Infallible> {
     /// An error produced by the internal workings of the service (our fault).
     InternalError(Arc<anyhow::Error>),
 
+    /// The user requested data outside the range that the service retains.
+    OutOfRetention(Arc<E>),
+
     /// A user error related to pagination and cursors.
     Pagination(#[from] pagination::Error),
 
@@ -44,6 +81,9 @@ pub(crate) enum RpcError<E: std::error::Error = Infallible> {
 
     /// Expended some limit, such as node count, depth, etc, during query execution.
     ResourceExhausted(Arc<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// The user requested a filter that the service does not support.
+    UnsupportedFilter { what: String },
 }
 
 impl<E: std::error::Error> From<RpcError<E>> for async_graphql::Error {
@@ -53,13 +93,37 @@ impl<E: std::error::Error> From<RpcError<E>> for async_graphql::Error {
                 ext.set("code", code::BAD_USER_INPUT);
             }),
 
+            RpcError::BadCursor(err) => err.to_string().extend_with(|_, ext| {
+                ext.set("code", code::BAD_USER_INPUT);
+                ext.set(ERROR_CODE, ErrorCode::BadCursor.as_str());
+            }),
+
             RpcError::FeatureUnavailable { what } => {
                 format!("{what} not available").extend_with(|_, ext| {
                     ext.set("code", code::FEATURE_UNAVAILABLE);
                 })
             }
 
+            RpcError::Forbidden { what } => {
+                format!("Not authorized to access {what}").extend_with(|_, ext| {
+                    ext.set("code", code::FORBIDDEN);
+                })
+            }
+
             RpcError::GraphQlError(mut err) => {
+                // Errors from the framework that do not already have a code are not expected, so
+                // they are treated as internal errors.
+                let has_code = err
+                    .extensions
+                    .as_ref()
+                    .is_some_and(|ext| ext.get("code").is_some());
+
+                if !has_code {
+                    err = err.extend_with(|_, ext| {
+                        ext.set(ERROR_CODE, ErrorCode::Internal.as_str());
+                    });
+                }
+
                 fill_error_code(&mut err.extensions, code::INTERNAL_SERVER_ERROR);
                 err
             }
@@ -71,16 +135,23 @@ impl<E: std::error::Error> From<RpcError<E>> for async_graphql::Error {
                 let Some(top) = chain.next() else {
                     return "Unknown error".extend_with(|_, ext| {
                         ext.set("code", code::INTERNAL_SERVER_ERROR);
+                        ext.set(ERROR_CODE, ErrorCode::Internal.as_str());
                     });
                 };
 
                 let chain: Vec<_> = chain.map(|e| e.to_string()).collect();
                 top.to_string().extend_with(|_, ext| {
                     ext.set("code", code::INTERNAL_SERVER_ERROR);
+                    ext.set(ERROR_CODE, ErrorCode::Internal.as_str());
                     ext.set("chain", chain);
                 })
             }
 
+            RpcError::OutOfRetention(err) => err.to_string().extend_with(|_, ext| {
+                ext.set("code", code::BAD_USER_INPUT);
+                ext.set(ERROR_CODE, ErrorCode::OutOfRetention.as_str());
+            }),
+
             RpcError::Pagination(err) => err.to_string().extend_with(|_, ext| {
                 ext.set("code", code::BAD_USER_INPUT);
             }),
@@ -89,6 +160,7 @@ impl<E: std::error::Error> From<RpcError<E>> for async_graphql::Error {
                 format!("{kind} timed out after {:.2}s", limit.as_secs_f64()).extend_with(
                     |_, ext| {
                         ext.set("code", code::REQUEST_TIMEOUT);
+                        ext.set(ERROR_CODE, ErrorCode::Timeout.as_str());
                     },
                 )
             }
@@ -96,6 +168,25 @@ impl<E: std::error::Error> From<RpcError<E>> for async_graphql::Error {
             RpcError::ResourceExhausted(err) => err.to_string().extend_with(|_, ext| {
                 ext.set("code", code::RESOURCE_EXHAUSTED);
             }),
+
+            RpcError::UnsupportedFilter { what } => {
+                format!("{what} is not supported").extend_with(|_, ext| {
+                    ext.set("code", code::BAD_USER_INPUT);
+                    ext.set(ERROR_CODE, ErrorCode::UnsupportedFilter.as_str());
+                })
+            }
+        }
+    }
+}
+
+impl ErrorCode {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadCursor => "BAD_CURSOR",
+            ErrorCode::OutOfRetention => "OUT_OF_RETENTION",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::UnsupportedFilter => "UNSUPPORTED_FILTER",
         }
     }
 }
@@ -145,11 +236,31 @@ pub(crate) fn bad_user_input<E: std::error::Error>(err: E) -> RpcError<E> {
     RpcError::BadUserInput(Arc::new(err))
 }
 
+/// Signal an error with a pagination cursor.
+pub(crate) fn bad_cursor<E: std::error::Error>(err: E) -> RpcError<E> {
+    RpcError::BadCursor(Arc::new(err))
+}
+
+/// Signal that the request is for data outside the range that the service retains.
+pub(crate) fn out_of_retention<E: std::error::Error>(err: E) -> RpcError<E> {
+    RpcError::OutOfRetention(Arc::new(err))
+}
+
+/// Signal that filtering by `what` is not supported.
+pub(crate) fn unsupported_filter<E: std::error::Error>(what: String) -> RpcError<E> {
+    RpcError::UnsupportedFilter { what }
+}
+
 /// Signal that feature `what` is not available.
 pub(crate) fn feature_unavailable<E: std::error::Error>(what: &'static str) -> RpcError<E> {
     RpcError::FeatureUnavailable { what }
 }
 
+/// Signal that the caller is not authorized to access `what`.
+pub(crate) fn forbidden(what: String) -> RpcError {
+    RpcError::Forbidden { what }
+}
+
 /// Signal a timeout. `kind` specifies what operation timed out and is included in the error
 /// message.
 pub(crate) fn request_timeout(kind: &'static str, limit: Duration) -> RpcError {
@@ -216,6 +327,31 @@ mod tests {
         assert_eq!(ext.get("code"), Some(&code::BAD_USER_INPUT.into()));
     }
 
+    #[test]
+    fn test_error_codes() {
+        let err: async_graphql::Error = bad_cursor(Error).into();
+        let ext = err.extensions.as_ref().expect("No extensions");
+        assert_eq!(ext.get("code"), Some(&code::BAD_USER_INPUT.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"BAD_CURSOR".into()));
+
+        let err: async_graphql::Error = out_of_retention(Error).into();
+        let ext = err.extensions.as_ref().expect("No extensions");
+        assert_eq!(ext.get("code"), Some(&code::BAD_USER_INPUT.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"OUT_OF_RETENTION".into()));
+
+        let err: async_graphql::Error =
+            unsupported_filter::<Infallible>("Filtering by kind".to_owned()).into();
+        assert_eq!(err.message, "Filtering by kind is not supported");
+        let ext = err.extensions.as_ref().expect("No extensions");
+        assert_eq!(ext.get("code"), Some(&code::BAD_USER_INPUT.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"UNSUPPORTED_FILTER".into()));
+
+        // Errors without a more precise reason only have a code.
+        let err: async_graphql::Error = bad_user_input(Error).into();
+        let ext = err.extensions.as_ref().expect("No extensions");
+        assert_eq!(ext.get(ERROR_CODE), None);
+    }
+
     /// If the GraphQL error does not have a code, it should be set to `INTERNAL_SERVER_ERROR`.
     #[test]
     fn test_graphql_error() {
@@ -226,6 +362,7 @@ mod tests {
 
         let ext = err.extensions.as_ref().expect("No extensions");
         assert_eq!(ext.get("code"), Some(&code::INTERNAL_SERVER_ERROR.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"INTERNAL".into()));
     }
 
     /// If the GraphQL error does already have a code, it should be left as is.
@@ -241,6 +378,7 @@ mod tests {
 
         let ext = err.extensions.as_ref().expect("No extensions");
         assert_eq!(ext.get("code"), Some(&code::BAD_USER_INPUT.into()));
+        assert_eq!(ext.get(ERROR_CODE), None);
     }
 
     #[test]
@@ -256,6 +394,7 @@ mod tests {
 
         let ext = err.extensions.as_ref().expect("No extensions");
         assert_eq!(ext.get("code"), Some(&code::INTERNAL_SERVER_ERROR.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"INTERNAL".into()));
         assert_eq!(
             ext.get("chain"),
             Some(&vec!["Immediate predecessor", "Root cause"].into())
@@ -270,5 +409,6 @@ mod tests {
 
         let ext = err.extensions.as_ref().expect("No extensions");
         assert_eq!(ext.get("code"), Some(&code::REQUEST_TIMEOUT.into()));
+        assert_eq!(ext.get(ERROR_CODE), Some(&"TIMEOUT".into()));
     }
 }
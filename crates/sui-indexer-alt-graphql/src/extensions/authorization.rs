@@ -0,0 +1,236 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ServerError, ServerResult, Value,
+};
+use axum::http::{header::ORIGIN, HeaderMap, HeaderName};
+
+use crate::error::forbidden;
+
+static API_KEY: HeaderName = HeaderName::from_static("x-sui-rpc-api-key");
+
+/// Context data identifying who is making a request, for the purposes of authorization.
+#[derive(Clone, Debug, Default)]
+pub struct Caller {
+    /// The API key the request was made with, from the `x-sui-rpc-api-key` header.
+    pub api_key: Option<String>,
+
+    /// The origin the request was made from, from the `Origin` header.
+    pub origin: Option<String>,
+}
+
+/// Decides which fields a caller is allowed to resolve. Authorizers are consulted for every field
+/// in a request, so they should be cheap to call.
+pub trait Authorizer: Send + Sync + 'static {
+    /// Whether `caller` is allowed to resolve `field` on `type_name`.
+    fn authorize(&self, caller: &Caller, type_name: &str, field: &str) -> bool;
+}
+
+/// Authorization rules, read from the service's configuration. A restricted field can only be
+/// resolved by callers that have been granted access to it, by their API key or their origin.
+/// Fields that are not restricted can be resolved by anyone.
+pub(crate) struct AuthorizationRules {
+    /// Restricted fields, as a map from type name to field names.
+    pub(crate) restricted: BTreeMap<String, BTreeSet<String>>,
+
+    /// Restricted fields that callers with each API key have been granted access to.
+    pub(crate) api_keys: BTreeMap<String, Grants>,
+
+    /// Restricted fields that callers from each origin have been granted access to.
+    pub(crate) origins: BTreeMap<String, Grants>,
+}
+
+/// The restricted fields a caller has been granted access to.
+pub(crate) enum Grants {
+    All,
+    Fields(BTreeSet<String>),
+}
+
+/// Extension factory for an extension that checks that a caller (see [Caller]) is authorized to
+/// access each field that it requests, using a pluggable [Authorizer].
+pub struct Authorization(Arc<dyn Authorizer>);
+
+struct AuthorizationExt(Arc<dyn Authorizer>);
+
+impl Caller {
+    /// Identify the caller from the headers of its request.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &HeaderName| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned())
+        };
+
+        Self {
+            api_key: header(&API_KEY),
+            origin: header(&ORIGIN),
+        }
+    }
+}
+
+impl Authorizer for AuthorizationRules {
+    fn authorize(&self, caller: &Caller, type_name: &str, field: &str) -> bool {
+        let Some(fields) = self.restricted.get(type_name) else {
+            return true;
+        };
+
+        if !fields.contains(field) {
+            return true;
+        }
+
+        let coordinate = format!("{type_name}.{field}");
+        let granted = |grants: Option<&Grants>| match grants {
+            None => false,
+            Some(Grants::All) => true,
+            Some(Grants::Fields(fields)) => fields.contains(&coordinate),
+        };
+
+        granted(caller.api_key.as_ref().and_then(|k| self.api_keys.get(k)))
+            || granted(caller.origin.as_ref().and_then(|o| self.origins.get(o)))
+    }
+}
+
+impl Authorization {
+    pub fn new(authorizer: impl Authorizer) -> Self {
+        Self(Arc::new(authorizer))
+    }
+}
+
+impl ExtensionFactory for Authorization {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuthorizationExt(self.0.clone()))
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for AuthorizationExt {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.is_for_introspection {
+            return next.run(ctx, info).await;
+        }
+
+        let anonymous = Caller::default();
+        let caller = ctx.data_opt::<Caller>().unwrap_or(&anonymous);
+        if !self.0.authorize(caller, info.parent_type, info.name) {
+            let what = format!("{}.{}", info.parent_type, info.name);
+            return Err(ServerError::from(forbidden(what)));
+        }
+
+        next.run(ctx, info).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema};
+
+    use crate::error::{code, error_codes};
+
+    use super::*;
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn public(&self) -> bool {
+            true
+        }
+
+        async fn secret(&self) -> bool {
+            true
+        }
+
+        async fn dump(&self) -> bool {
+            true
+        }
+    }
+
+    fn rules() -> AuthorizationRules {
+        AuthorizationRules {
+            restricted: BTreeMap::from_iter([(
+                "Query".to_owned(),
+                BTreeSet::from_iter(["secret".to_owned(), "dump".to_owned()]),
+            )]),
+            api_keys: BTreeMap::from_iter([
+                ("admin".to_owned(), Grants::All),
+                (
+                    "partner".to_owned(),
+                    Grants::Fields(BTreeSet::from_iter(["Query.secret".to_owned()])),
+                ),
+            ]),
+            origins: BTreeMap::from_iter([(
+                "https://example.com".to_owned(),
+                Grants::Fields(BTreeSet::from_iter(["Query.dump".to_owned()])),
+            )]),
+        }
+    }
+
+    async fn execute(query: &str, caller: Caller) -> Vec<String> {
+        let response = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(Authorization::new(rules()))
+            .finish()
+            .execute(Request::new(query).data(caller))
+            .await;
+
+        error_codes(&response)
+            .into_iter()
+            .map(|c| c.to_owned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_unrestricted_field() {
+        assert!(execute("{ public }", Caller::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restricted_field_anonymous() {
+        assert_eq!(
+            execute("{ public secret }", Caller::default()).await,
+            vec![code::FORBIDDEN],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restricted_field_api_key() {
+        let admin = Caller {
+            api_key: Some("admin".to_owned()),
+            origin: None,
+        };
+
+        let partner = Caller {
+            api_key: Some("partner".to_owned()),
+            origin: None,
+        };
+
+        assert!(execute("{ secret dump }", admin).await.is_empty());
+        assert_eq!(
+            execute("{ secret dump }", partner).await,
+            vec![code::FORBIDDEN],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restricted_field_origin() {
+        let caller = Caller {
+            api_key: None,
+            origin: Some("https://example.com".to_owned()),
+        };
+
+        assert!(execute("{ dump }", caller.clone()).await.is_empty());
+        assert_eq!(execute("{ secret }", caller).await, vec![code::FORBIDDEN]);
+    }
+}
@@ -1,6 +1,8 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod authorization;
 pub(crate) mod logging;
+pub(crate) mod proofs;
 pub(crate) mod query_limits;
 pub(crate) mod timeout;
@@ -0,0 +1,271 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    Context, Response, ServerError, Value,
+};
+use axum::http::{HeaderName, HeaderValue};
+use fastcrypto::encoding::{Base64, Encoding};
+use futures::future::try_join_all;
+use headers::{Error, Header};
+use serde::Serialize;
+use sui_indexer_alt_reader::kv_loader::KvLoader;
+use sui_types::{
+    base_types::ObjectRef, digests::TransactionDigest,
+    messages_checkpoint::CheckpointSequenceNumber,
+};
+
+use crate::{
+    api::types::{
+        object::{self, Object},
+        transaction::Transaction,
+    },
+    error::RpcError,
+};
+
+static SHOW_PROOFS: HeaderName = HeaderName::from_static("x-sui-rpc-show-proofs");
+
+/// Header indicating that the client would like their GraphQL response to be extended with proofs
+/// for the objects and transactions it fetches by key, anchored in the checkpoints that include
+/// them. The value of this header doesn't matter, it just has to be present.
+pub(crate) struct ShowProofs(pub HeaderValue);
+
+/// The transactions whose proofs a request needs, and the objects each of them proves. Only
+/// present in the context of requests that asked for proofs.
+#[derive(Default)]
+pub(crate) struct ProofTargets(Mutex<BTreeMap<TransactionDigest, BTreeSet<ObjectRef>>>);
+
+/// Extension factory for attaching proofs to responses, under the `proofs` response extension.
+///
+/// Each proof links a transaction to the validator-signed summary of the checkpoint that includes
+/// it, so that a client can check the service's answers without trusting it:
+///
+/// - The checkpoint summary must be signed by a quorum of the committee for its epoch.
+/// - The digest of the checkpoint contents must match the summary's content digest.
+/// - The checkpoint contents must include the transaction, with the digest of its effects.
+/// - Each object must be one of the objects changed by those effects, at the same version and
+///   digest.
+pub(crate) struct Proofs;
+
+struct ProofsExt;
+
+/// Proof material for a single transaction, and the objects it wrote.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionProof {
+    /// The transaction's digest, encoded in Base58.
+    transaction: String,
+    checkpoint: CheckpointSequenceNumber,
+    /// BCS-encoded `CheckpointSummary`, Base64-encoded.
+    checkpoint_summary_bcs: String,
+    /// BCS-encoded `AuthorityStrongQuorumSignInfo` over the summary, Base64-encoded.
+    checkpoint_signature_bcs: String,
+    /// BCS-encoded `CheckpointContents`, Base64-encoded.
+    checkpoint_contents_bcs: String,
+    /// BCS-encoded `TransactionEffects`, Base64-encoded.
+    effects_bcs: String,
+    objects: Vec<ObjectProof>,
+}
+
+#[derive(Serialize)]
+struct ObjectProof {
+    address: String,
+    version: u64,
+    digest: String,
+}
+
+impl ProofTargets {
+    /// Request a proof for `transaction`, if the request asked for proofs.
+    pub(crate) fn add_transaction(ctx: &Context<'_>, transaction: &Transaction) {
+        if let Some(targets) = ctx.data_opt::<Self>() {
+            targets
+                .0
+                .lock()
+                .unwrap()
+                .entry(transaction.digest)
+                .or_default();
+        }
+    }
+
+    /// Request a proof for `object`, via the transaction that wrote it, if the request asked for
+    /// proofs.
+    pub(crate) async fn add_object(
+        ctx: &Context<'_>,
+        object: &Object,
+    ) -> Result<(), RpcError<object::Error>> {
+        let Some(targets) = ctx.data_opt::<Self>() else {
+            return Ok(());
+        };
+
+        let Some(contents) = object.contents(ctx).await? else {
+            return Ok(());
+        };
+
+        targets
+            .0
+            .lock()
+            .unwrap()
+            .entry(contents.previous_transaction)
+            .or_default()
+            .insert(contents.compute_object_reference());
+
+        Ok(())
+    }
+}
+
+impl Header for ShowProofs {
+    fn name() -> &'static HeaderName {
+        &SHOW_PROOFS
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(values: &mut I) -> Result<Self, Error> {
+        Ok(ShowProofs(
+            values.next().ok_or_else(Error::invalid)?.clone(),
+        ))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend([self.0.clone()]);
+    }
+}
+
+impl ExtensionFactory for Proofs {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ProofsExt)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for ProofsExt {
+    /// Once the request has been executed, build proofs for the targets it gathered, and attach
+    /// them to the response.
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+        let Some(targets) = ctx.data_opt::<ProofTargets>() else {
+            return response;
+        };
+
+        let targets = mem::take(&mut *targets.0.lock().unwrap());
+        if targets.is_empty() {
+            return response.extension("proofs", Value::List(vec![]));
+        }
+
+        let kv_loader: &KvLoader = ctx.data_unchecked();
+        let proofs = try_join_all(
+            targets
+                .into_iter()
+                .map(|(digest, objects)| prove(kv_loader, digest, objects)),
+        )
+        .await
+        .and_then(|proofs| {
+            Value::from_json(serde_json::to_value(proofs)?).context("Failed to serialize proofs")
+        });
+
+        match proofs {
+            Ok(proofs) => response.extension("proofs", proofs),
+            Err(e) => {
+                let error: RpcError = e.context("Failed to build proofs").into();
+                response.errors.push(ServerError::from(error));
+                response
+            }
+        }
+    }
+}
+
+/// Gather the proof material linking the transaction with digest `digest`, and the `objects` it
+/// wrote, to the checkpoint that includes it.
+async fn prove(
+    kv_loader: &KvLoader,
+    digest: TransactionDigest,
+    objects: BTreeSet<ObjectRef>,
+) -> anyhow::Result<TransactionProof> {
+    let transaction = kv_loader
+        .load_one_transaction(digest)
+        .await
+        .context("Failed to fetch transaction")?
+        .with_context(|| format!("Transaction {digest} not found"))?;
+
+    let checkpoint = transaction.cp_sequence_number();
+    let (summary, contents, signature) = kv_loader
+        .load_one_checkpoint(checkpoint)
+        .await
+        .context("Failed to fetch checkpoint")?
+        .with_context(|| format!("Checkpoint {checkpoint} not found"))?;
+
+    // Sanity check that the stored contents are the ones the summary commits to, rather than
+    // leave it to the client to discover.
+    anyhow::ensure!(
+        contents.digest() == &summary.content_digest,
+        "Contents of checkpoint {checkpoint} do not match its summary",
+    );
+
+    Ok(TransactionProof {
+        transaction: digest.base58_encode(),
+        checkpoint,
+        checkpoint_summary_bcs: Base64::encode(bcs::to_bytes(&summary)?),
+        checkpoint_signature_bcs: Base64::encode(bcs::to_bytes(&signature)?),
+        checkpoint_contents_bcs: Base64::encode(bcs::to_bytes(&contents)?),
+        effects_bcs: Base64::encode(transaction.raw_effects()?),
+        objects: objects
+            .into_iter()
+            .map(|(id, version, digest)| ObjectProof {
+                address: id.to_canonical_string(/* with_prefix */ true),
+                version: version.value(),
+                digest: digest.base58_encode(),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema};
+
+    use super::*;
+
+    struct Root;
+
+    #[Object]
+    impl Root {
+        async fn op(&self) -> bool {
+            true
+        }
+    }
+
+    fn schema() -> Schema<Root, EmptyMutation, EmptySubscription> {
+        Schema::build(Root, EmptyMutation, EmptySubscription)
+            .extension(Proofs)
+            .finish()
+    }
+
+    /// Proofs are opt-in, so responses to requests that did not ask for them are not extended.
+    #[tokio::test]
+    async fn test_no_proofs_requested() {
+        let response = schema().execute("{ op }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert!(!response.extensions.contains_key("proofs"));
+    }
+
+    /// Requests that asked for proofs always get the extension, even if they did not fetch
+    /// anything that needs proving.
+    #[tokio::test]
+    async fn test_no_proof_targets() {
+        let request = Request::new("{ op }").data(ProofTargets::default());
+        let response = schema().execute(request).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.extensions["proofs"], Value::List(vec![]));
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::{collections::BTreeMap, future::Future, time::Duration};
 
 use anyhow::{anyhow, bail};
 use axum::{
@@ -12,7 +12,8 @@ use axum::{
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use tokio::net::TcpStream;
+use sui_indexer_alt_reader::{full_node_client::FullNodeClient, pg_reader::PgReader};
+use tokio::{net::TcpStream, time::Instant};
 use url::Url;
 
 use crate::{config::HealthConfig, WatermarksLock};
@@ -39,6 +40,162 @@ pub(crate) struct Response {
     errors: Vec<String>,
 }
 
+/// Response body for the liveness and readiness endpoints, reporting on each of the service's
+/// dependencies. Dependencies that are not configured are omitted.
+#[derive(Serialize)]
+pub(crate) struct Report {
+    status: Status,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+/// The outcome of checking a single dependency.
+#[derive(Serialize)]
+pub(crate) struct Check {
+    status: Status,
+
+    /// How long the check took to complete.
+    latency_ms: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checkpoint_lag_ms: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_checkpoint_lag_ms: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Ok,
+    Unavailable,
+}
+
+/// A report whose response status code is always OK, for as long as the service is able to
+/// respond at all.
+pub(crate) struct Liveness(Report);
+
+/// A report whose response status code is SERVICE_UNAVAILABLE if any of the service's
+/// dependencies are unavailable, so that load balancers stop routing requests to it.
+pub(crate) struct Readiness(Report);
+
+/// Liveness endpoint, which reports on the service's dependencies, but succeeds regardless of
+/// their status, so that a replica is not restarted for problems it cannot fix itself.
+pub(crate) async fn liveness(
+    Extension(watermarks): Extension<WatermarksLock>,
+    Extension(config): Extension<HealthConfig>,
+    Extension(pg_reader): Extension<PgReader>,
+    Extension(full_node): Extension<FullNodeClient>,
+    Extension(DbProbe(db_url)): Extension<DbProbe>,
+) -> Liveness {
+    Liveness(
+        report(
+            &watermarks,
+            &config,
+            &pg_reader,
+            &full_node,
+            db_url.is_some(),
+        )
+        .await,
+    )
+}
+
+/// Readiness endpoint, which checks that the service can reach the database, that its watermarks
+/// are fresh, and that it can reach the full node (if one is configured). Fails if any of these
+/// checks fail.
+pub(crate) async fn readiness(
+    Extension(watermarks): Extension<WatermarksLock>,
+    Extension(config): Extension<HealthConfig>,
+    Extension(pg_reader): Extension<PgReader>,
+    Extension(full_node): Extension<FullNodeClient>,
+    Extension(DbProbe(db_url)): Extension<DbProbe>,
+) -> Readiness {
+    Readiness(
+        report(
+            &watermarks,
+            &config,
+            &pg_reader,
+            &full_node,
+            db_url.is_some(),
+        )
+        .await,
+    )
+}
+
+/// Check all the service's dependencies concurrently.
+async fn report(
+    watermarks: &WatermarksLock,
+    config: &HealthConfig,
+    pg_reader: &PgReader,
+    full_node: &FullNodeClient,
+    has_db: bool,
+) -> Report {
+    let database = async {
+        if has_db {
+            Some(
+                probe(config.probe_timeout, async {
+                    pg_reader.connect().await.map(|_| ())
+                })
+                .await,
+            )
+        } else {
+            None
+        }
+    };
+
+    let watermark = async {
+        let start = Instant::now();
+        let (result, lag) = match check_watermarks(watermarks).await {
+            Ok(lag) => (Ok(()), Some(lag)),
+            Err(e) => (Err(e), None),
+        };
+
+        let mut check = Check::new(start, result);
+        check.checkpoint_lag_ms = lag.map(|l| l.as_millis() as u64);
+        check.max_checkpoint_lag_ms = Some(config.max_checkpoint_lag.as_millis() as u64);
+        if lag.is_some_and(|l| l > config.max_checkpoint_lag) {
+            check.fail("Watermark lag is too high".to_owned());
+        }
+
+        check
+    };
+
+    let full_node = async {
+        let start = Instant::now();
+        match tokio::time::timeout(config.probe_timeout, full_node.probe()).await {
+            Ok(None) => None,
+            Ok(Some(result)) => Some(Check::new(start, result)),
+            Err(_) => Some(Check::new(start, Err(anyhow!("Timed out")))),
+        }
+    };
+
+    let (database, watermark, full_node) = tokio::join!(database, watermark, full_node);
+
+    let mut checks = BTreeMap::new();
+    checks.insert("watermark", watermark);
+    if let Some(database) = database {
+        checks.insert("database", database);
+    }
+
+    if let Some(full_node) = full_node {
+        checks.insert("full_node", full_node);
+    }
+
+    Report::new(checks)
+}
+
+/// Run `check`, failing it if it does not complete within `timeout`.
+async fn probe(timeout: Duration, check: impl Future<Output = anyhow::Result<()>>) -> Check {
+    let start = Instant::now();
+    let result = tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("Timed out")));
+
+    Check::new(start, result)
+}
+
 /// Health check endpoint outputs information about the services health -- how recent its
 /// information is, and any health check related errors. The response status code is
 /// INTERNAL_SERVER_ERROR if there are any errors, and OK otherwise (if the service is healthy).
@@ -106,6 +263,58 @@ async fn check_connection(url: &Option<Url>) -> anyhow::Result<()> {
     Ok(())
 }
 
+impl Report {
+    fn new(checks: BTreeMap<&'static str, Check>) -> Self {
+        let status = if checks.values().all(|c| c.status == Status::Ok) {
+            Status::Ok
+        } else {
+            Status::Unavailable
+        };
+
+        Self { status, checks }
+    }
+}
+
+impl Check {
+    fn new(start: Instant, result: anyhow::Result<()>) -> Self {
+        let mut check = Self {
+            status: Status::Ok,
+            latency_ms: start.elapsed().as_millis() as u64,
+            checkpoint_lag_ms: None,
+            max_checkpoint_lag_ms: None,
+            error: None,
+        };
+
+        if let Err(e) = result {
+            check.fail(format!("{e:#}"));
+        }
+
+        check
+    }
+
+    fn fail(&mut self, error: String) {
+        self.status = Status::Unavailable;
+        self.error = Some(error);
+    }
+}
+
+impl IntoResponse for Liveness {
+    fn into_response(self) -> AxumResponse {
+        (StatusCode::OK, Json(self.0)).into_response()
+    }
+}
+
+impl IntoResponse for Readiness {
+    fn into_response(self) -> AxumResponse {
+        let status = match self.0.status {
+            Status::Ok => StatusCode::OK,
+            Status::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        };
+
+        (status, Json(self.0)).into_response()
+    }
+}
+
 impl IntoResponse for Response {
     fn into_response(self) -> AxumResponse {
         let status = if self.errors.is_empty() {
@@ -117,3 +326,44 @@ impl IntoResponse for Response {
         (status, Json(self)).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(result: anyhow::Result<()>) -> Check {
+        Check::new(Instant::now(), result)
+    }
+
+    #[test]
+    fn test_readiness_status() {
+        let ready = Report::new(BTreeMap::from([("database", check(Ok(())))]));
+        assert_eq!(Readiness(ready).into_response().status(), StatusCode::OK);
+
+        let unready = Report::new(BTreeMap::from([
+            ("database", check(Ok(()))),
+            ("full_node", check(Err(anyhow!("Connection refused")))),
+        ]));
+        assert_eq!(
+            Readiness(unready).into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_liveness_reports_failures() {
+        let mut lagging = check(Ok(()));
+        lagging.fail("Watermark lag is too high".to_owned());
+        let report = Report::new(BTreeMap::from([("watermark", lagging)]));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["status"], "unavailable");
+        assert_eq!(json["checks"]["watermark"]["status"], "unavailable");
+        assert_eq!(
+            json["checks"]["watermark"]["error"],
+            "Watermark lag is too high"
+        );
+
+        assert_eq!(Liveness(report).into_response().status(), StatusCode::OK);
+    }
+}
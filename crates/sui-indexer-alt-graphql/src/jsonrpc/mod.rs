@@ -0,0 +1,365 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Context as _;
+use async_graphql::{Request as GqlRequest, Response as GqlResponse, Schema, Variables};
+use axum::{body::Bytes, extract::ConnectInfo, http::HeaderMap, Extension, Json};
+use axum_extra::TypedHeader;
+use futures::future;
+use headers::ContentLength;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    api::{mutation::Mutation, query::Query, subscription::Subscription},
+    error::{code, error_codes},
+    extensions::{authorization::Caller, logging::Session},
+    task::watermark::{Watermarks, WatermarksLock},
+};
+
+mod read;
+mod transactions;
+
+/// Error codes from the JSON-RPC 2.0 specification.
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+/// The number of results per page for paginated JSON-RPC methods that are called without a
+/// limit.
+#[derive(Clone, Copy)]
+pub(crate) struct DefaultPageSize(pub u32);
+
+#[derive(thiserror::Error, Debug)]
+enum Error {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(serde_json::Error),
+
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
+
+    #[error("Internal error: {0:#}")]
+    Internal(#[from] anyhow::Error),
+}
+
+/// A single JSON-RPC request. Its `id` is read separately, so that it can be included in the
+/// response even if the rest of the request is invalid.
+#[derive(Deserialize)]
+struct Request {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Result(Value),
+    Error { code: i32, message: String },
+}
+
+/// Runs the GraphQL queries that JSON-RPC methods are translated into, with the same request data
+/// that the GraphQL handler would add to a request made over the same connection.
+struct Executor {
+    schema: Schema<Query, Mutation, Subscription>,
+    addr: SocketAddr,
+    caller: Caller,
+    content_length: ContentLength,
+    watermark: Arc<Watermarks>,
+    default_page_size: u32,
+}
+
+/// Handler for JSON-RPC requests (POST requests, optionally batched), offering a subset of the
+/// `sui_*` and `suix_*` methods of the fullnode's JSON-RPC API, for clients that have not migrated
+/// to GraphQL yet.
+///
+/// Each call is translated into a GraphQL query that is run against the service's schema, so it
+/// is subject to the same limits, authorization rules, and data retention as a GraphQL request.
+/// The cursors that paginated methods return are GraphQL cursors, and are only meaningful to the
+/// same method.
+pub(crate) async fn jsonrpc(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(schema): Extension<Schema<Query, Mutation, Subscription>>,
+    Extension(watermark): Extension<WatermarksLock>,
+    Extension(DefaultPageSize(default_page_size)): Extension<DefaultPageSize>,
+    TypedHeader(content_length): TypedHeader<ContentLength>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Json<Value> {
+    let executor = Executor {
+        schema,
+        addr,
+        caller: Caller::from_headers(&headers),
+        content_length,
+        watermark: watermark.read().await.clone(),
+        default_page_size,
+    };
+
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return Json(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+    };
+
+    Json(match request {
+        Value::Array(batch) if batch.is_empty() => error_response(
+            Value::Null,
+            INVALID_REQUEST,
+            "Invalid request: empty batch".to_owned(),
+        ),
+
+        Value::Array(batch) => {
+            let responses = future::join_all(batch.into_iter().map(|r| executor.respond(r))).await;
+            serde_json::to_value(responses).unwrap_or_default()
+        }
+
+        request => serde_json::to_value(executor.respond(request).await).unwrap_or_default(),
+    })
+}
+
+impl Executor {
+    /// Respond to a single (not batched) JSON-RPC request.
+    async fn respond(&self, request: Value) -> Response {
+        let id = request.get("id").cloned().unwrap_or_default();
+        let outcome = match serde_json::from_value::<Request>(request) {
+            Ok(request) => self.call(&request.method, request.params).await,
+            Err(e) => Err(Error::InvalidRequest(e)),
+        };
+
+        Response {
+            jsonrpc: "2.0",
+            id,
+            outcome: match outcome {
+                Ok(result) => Outcome::Result(result),
+                Err(e) => Outcome::Error {
+                    code: e.code(),
+                    message: e.to_string(),
+                },
+            },
+        }
+    }
+
+    /// Dispatch a call to `method` with `params` to its translation into GraphQL.
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let result = match method {
+            "sui_getChainIdentifier" => {
+                let []: [Value; 0] = parse_params(params, &[])?;
+                serde_json::to_value(read::chain_identifier(self).await?)
+            }
+
+            "sui_getLatestCheckpointSequenceNumber" => {
+                let []: [Value; 0] = parse_params(params, &[])?;
+                serde_json::to_value(read::latest_checkpoint_sequence_number(self).await?)
+            }
+
+            "sui_getTransactionBlock" => {
+                let (digest, options) = parse_params(params, &["digest", "options"])?;
+                serde_json::to_value(transactions::transaction(self, digest, options).await?)
+            }
+
+            "suix_queryTransactionBlocks" => {
+                let (query, cursor, limit, descending_order) =
+                    parse_params(params, &["query", "cursor", "limit", "descending_order"])?;
+
+                serde_json::to_value(
+                    transactions::query_transaction_blocks(
+                        self,
+                        query,
+                        cursor,
+                        limit,
+                        descending_order,
+                    )
+                    .await?,
+                )
+            }
+
+            _ => return Err(Error::MethodNotFound(method.to_owned())),
+        };
+
+        Ok(result.context("Failed to serialize response")?)
+    }
+
+    /// Run a GraphQL `query` with `variables`, and deserialize its data as `T`.
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, Error> {
+        let request = GqlRequest::new(query)
+            .variables(Variables::from_json(variables))
+            .data(self.content_length)
+            .data(Session::new(self.addr))
+            .data(self.caller.clone())
+            .data(self.watermark.clone());
+
+        let response = self.schema.execute(request).await;
+        if !response.errors.is_empty() {
+            return Err(Error::from_graphql(&response));
+        }
+
+        let data = response
+            .data
+            .into_json()
+            .context("Failed to convert GraphQL response to JSON")?;
+
+        Ok(serde_json::from_value(data).context("Failed to deserialize GraphQL response")?)
+    }
+}
+
+impl Error {
+    /// Convert the errors in a GraphQL `response` into a JSON-RPC error. Errors caused by the
+    /// request's inputs are reported as invalid params.
+    fn from_graphql(response: &GqlResponse) -> Self {
+        let message = response
+            .errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        let user_error = error_codes(response)
+            .into_iter()
+            .any(|c| c == code::BAD_USER_INPUT || c == code::GRAPHQL_VALIDATION_FAILED);
+
+        if user_error {
+            Error::InvalidParams(message)
+        } else {
+            Error::Internal(anyhow::anyhow!(message))
+        }
+    }
+
+    fn code(&self) -> i32 {
+        match self {
+            Error::InvalidRequest(_) => INVALID_REQUEST,
+            Error::MethodNotFound(_) => METHOD_NOT_FOUND,
+            Error::InvalidParams(_) => INVALID_PARAMS,
+            Error::Internal(_) => INTERNAL_ERROR,
+        }
+    }
+}
+
+/// Deserialize a method's `params` as `T`, a tuple with one element per name in `names`. Params
+/// can be passed by position (as an array) or by name (as an object), and trailing params that
+/// are missing are treated as `null`.
+fn parse_params<T: DeserializeOwned>(params: Value, names: &[&str]) -> Result<T, Error> {
+    let mut values = match params {
+        Value::Null => vec![],
+        Value::Array(values) => values,
+        Value::Object(mut named) => names
+            .iter()
+            .map(|name| named.remove(*name).unwrap_or_default())
+            .collect(),
+        _ => {
+            return Err(Error::InvalidParams(
+                "params must be an array or an object".to_owned(),
+            ))
+        }
+    };
+
+    if values.len() > names.len() {
+        return Err(Error::InvalidParams(format!(
+            "expected at most {} params, got {}",
+            names.len(),
+            values.len(),
+        )));
+    }
+
+    values.resize(names.len(), Value::Null);
+    serde_json::from_value(Value::Array(values)).map_err(|e| Error::InvalidParams(e.to_string()))
+}
+
+fn error_response(id: Value, code: i32, message: String) -> Value {
+    let response = Response {
+        jsonrpc: "2.0",
+        id,
+        outcome: Outcome::Error { code, message },
+    };
+
+    serde_json::to_value(response).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_params_by_position() {
+        let (a, b): (u64, Option<String>) = parse_params(json!([1, "two"]), &["a", "b"]).unwrap();
+        assert_eq!((a, b.as_deref()), (1, Some("two")));
+
+        // Trailing params can be omitted.
+        let (a, b): (u64, Option<String>) = parse_params(json!([1]), &["a", "b"]).unwrap();
+        assert_eq!((a, b), (1, None));
+
+        let []: [Value; 0] = parse_params(Value::Null, &[]).unwrap();
+        let []: [Value; 0] = parse_params(json!({}), &[]).unwrap();
+    }
+
+    #[test]
+    fn test_params_by_name() {
+        let (a, b): (u64, Option<String>) =
+            parse_params(json!({ "b": "two", "a": 1 }), &["a", "b"]).unwrap();
+        assert_eq!((a, b.as_deref()), (1, Some("two")));
+
+        let (a, b): (u64, Option<String>) = parse_params(json!({ "a": 1 }), &["a", "b"]).unwrap();
+        assert_eq!((a, b), (1, None));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let err = parse_params::<(u64,)>(json!([1, 2]), &["a"]).unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS);
+
+        let err = parse_params::<(u64,)>(json!(["one"]), &["a"]).unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS);
+
+        let err = parse_params::<(u64,)>(json!([]), &["a"]).unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS);
+
+        let err = parse_params::<(u64,)>(json!(1), &["a"]).unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_response_format() {
+        let result = Response {
+            jsonrpc: "2.0",
+            id: json!(1),
+            outcome: Outcome::Result(json!("42")),
+        };
+
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": "42" }),
+        );
+
+        assert_eq!(
+            error_response(
+                json!("a"),
+                METHOD_NOT_FOUND,
+                "Method not found: foo".to_owned()
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "id": "a",
+                "error": { "code": -32601, "message": "Method not found: foo" },
+            }),
+        );
+    }
+}
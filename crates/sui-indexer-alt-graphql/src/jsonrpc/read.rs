@@ -0,0 +1,51 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Deserialize;
+use serde_json::json;
+use sui_types::sui_serde::BigInt;
+
+use super::{Error, Executor};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainIdentifier {
+    chain_identifier: String,
+}
+
+#[derive(Deserialize)]
+struct LatestCheckpoint {
+    checkpoint: Option<Checkpoint>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    sequence_number: u64,
+}
+
+/// `sui_getChainIdentifier`: The first four bytes of the genesis checkpoint's digest,
+/// hex-encoded.
+pub(super) async fn chain_identifier(executor: &Executor) -> Result<String, Error> {
+    let data: ChainIdentifier = executor
+        .execute("query { chainIdentifier }", json!({}))
+        .await?;
+
+    Ok(data.chain_identifier)
+}
+
+/// `sui_getLatestCheckpointSequenceNumber`: The sequence number of the latest checkpoint that the
+/// service has indexed.
+pub(super) async fn latest_checkpoint_sequence_number(
+    executor: &Executor,
+) -> Result<BigInt<u64>, Error> {
+    let data: LatestCheckpoint = executor
+        .execute("query { checkpoint { sequenceNumber } }", json!({}))
+        .await?;
+
+    let checkpoint = data
+        .checkpoint
+        .ok_or_else(|| anyhow::anyhow!("Latest checkpoint not found"))?;
+
+    Ok(checkpoint.sequence_number.into())
+}
@@ -0,0 +1,346 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use fastcrypto::encoding::{Base64, Encoding};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sui_json_rpc_types::{
+    Page, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    SuiTransactionBlockResponseQuery, TransactionFilter,
+};
+use sui_types::{digests::TransactionDigest, effects::TransactionEffects};
+
+use super::{Error, Executor};
+
+/// The fields of a transaction that JSON-RPC responses are built from. Fields that only some
+/// options need are skipped unless those options are set.
+const TRANSACTION: &str = r#"
+    fragment Transaction on Transaction {
+        digest
+        transactionBcs @include(if: $showRawInput)
+        effects {
+            effectsBcs @include(if: $showEffects)
+            timestamp
+            checkpoint { sequenceNumber }
+        }
+    }
+"#;
+
+const GET_TRANSACTION: &str = const_str::concat!(
+    r#"
+    query ($digest: String!, $showRawInput: Boolean!, $showEffects: Boolean!) {
+        transaction(digest: $digest) { ...Transaction }
+    }
+    "#,
+    TRANSACTION,
+);
+
+const QUERY_TRANSACTIONS: &str = const_str::concat!(
+    r#"
+    query (
+        $first: Int,
+        $after: String,
+        $last: Int,
+        $before: String,
+        $filter: TransactionFilter,
+        $showRawInput: Boolean!,
+        $showEffects: Boolean!,
+    ) {
+        transactions(first: $first, after: $after, last: $last, before: $before, filter: $filter) {
+            pageInfo { hasNextPage hasPreviousPage startCursor endCursor }
+            nodes { ...Transaction }
+        }
+    }
+    "#,
+    TRANSACTION,
+);
+
+#[derive(Deserialize)]
+struct GetTransaction {
+    transaction: Option<Transaction>,
+}
+
+#[derive(Deserialize)]
+struct QueryTransactions {
+    transactions: Connection,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Connection {
+    page_info: PageInfo,
+    nodes: Vec<Transaction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    has_previous_page: bool,
+    start_cursor: Option<String>,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    digest: TransactionDigest,
+    transaction_bcs: Option<String>,
+    effects: Option<Effects>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Effects {
+    effects_bcs: Option<String>,
+    timestamp: Option<DateTime<Utc>>,
+    checkpoint: Option<Checkpoint>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    sequence_number: u64,
+}
+
+/// `sui_getTransactionBlock`: Fetch a transaction by its digest.
+pub(super) async fn transaction(
+    executor: &Executor,
+    digest: TransactionDigest,
+    options: Option<SuiTransactionBlockResponseOptions>,
+) -> Result<SuiTransactionBlockResponse, Error> {
+    let options = options.unwrap_or_default();
+    let mut variables = variables(&options)?;
+    variables["digest"] = json!(digest.to_string());
+
+    let data: GetTransaction = executor.execute(GET_TRANSACTION, variables).await?;
+    let transaction = data
+        .transaction
+        .ok_or_else(|| Error::InvalidParams(format!("Transaction {digest} not found")))?;
+
+    response(transaction, &options)
+}
+
+/// `suix_queryTransactionBlocks`: Paginate through the transactions that match the query's filter,
+/// by paginating through the transactions that match its translation into a GraphQL filter.
+pub(super) async fn query_transaction_blocks(
+    executor: &Executor,
+    query: SuiTransactionBlockResponseQuery,
+    cursor: Option<String>,
+    limit: Option<usize>,
+    descending_order: Option<bool>,
+) -> Result<Page<SuiTransactionBlockResponse, String>, Error> {
+    let options = query.options.unwrap_or_default();
+    let limit = limit.unwrap_or(executor.default_page_size as usize);
+    let descending = descending_order.unwrap_or(false);
+
+    let mut variables = variables(&options)?;
+    variables["filter"] = filter(query.filter)?;
+    if descending {
+        variables["last"] = json!(limit);
+        variables["before"] = json!(cursor);
+    } else {
+        variables["first"] = json!(limit);
+        variables["after"] = json!(cursor);
+    }
+
+    let data: QueryTransactions = executor.execute(QUERY_TRANSACTIONS, variables).await?;
+    let Connection {
+        page_info,
+        mut nodes,
+    } = data.transactions;
+
+    // GraphQL pages are always in ascending order, so when paginating in descending order, the
+    // page is reversed, and the next page is the one before it.
+    let (next_cursor, has_next_page) = if descending {
+        nodes.reverse();
+        (page_info.start_cursor, page_info.has_previous_page)
+    } else {
+        (page_info.end_cursor, page_info.has_next_page)
+    };
+
+    Ok(Page {
+        data: nodes
+            .into_iter()
+            .map(|tx| response(tx, &options))
+            .collect::<Result<_, _>>()?,
+        next_cursor: next_cursor.or(cursor),
+        has_next_page,
+    })
+}
+
+/// Variables that control which fields of a transaction are fetched, based on the response
+/// `options`. Fails if `options` asks for parts of the response that are not supported.
+fn variables(options: &SuiTransactionBlockResponseOptions) -> Result<Value, Error> {
+    let unsupported = [
+        (options.show_input, "showInput"),
+        (options.show_events, "showEvents"),
+        (options.show_object_changes, "showObjectChanges"),
+        (options.show_balance_changes, "showBalanceChanges"),
+    ];
+
+    for (requested, option) in unsupported {
+        if requested {
+            return Err(Error::InvalidParams(format!(
+                "Option '{option}' is not supported, fetch this data through GraphQL instead"
+            )));
+        }
+    }
+
+    Ok(json!({
+        "showRawInput": options.show_raw_input,
+        "showEffects": options.show_effects || options.show_raw_effects,
+    }))
+}
+
+/// Translate a JSON-RPC transaction filter into a GraphQL `TransactionFilter`. Fails for filters
+/// that GraphQL does not offer an equivalent for.
+fn filter(filter: Option<TransactionFilter>) -> Result<Value, Error> {
+    Ok(match filter {
+        None => Value::Null,
+
+        Some(TransactionFilter::Checkpoint(checkpoint)) => json!({ "atCheckpoint": checkpoint }),
+
+        Some(TransactionFilter::AffectedObject(object)) => {
+            json!({ "affectedObject": { "address": object.to_string() } })
+        }
+
+        // Every transaction that is not a system transaction is a programmable transaction.
+        Some(TransactionFilter::TransactionKind(kind)) if kind == "ProgrammableTransaction" => {
+            json!({ "excludeKind": "SYSTEM_TX" })
+        }
+
+        Some(filter) => {
+            return Err(Error::InvalidParams(format!(
+                "Transaction filter is not supported: {filter:?}"
+            )))
+        }
+    })
+}
+
+/// Build the JSON-RPC response for a transaction, from the fields fetched for it by GraphQL.
+fn response(
+    tx: Transaction,
+    options: &SuiTransactionBlockResponseOptions,
+) -> Result<SuiTransactionBlockResponse, Error> {
+    let mut response = SuiTransactionBlockResponse::new(tx.digest);
+
+    if let Some(raw) = tx.transaction_bcs {
+        response.raw_transaction =
+            Base64::decode(&raw).context("Failed to decode transaction BCS")?;
+    }
+
+    let Some(effects) = tx.effects else {
+        return Ok(response);
+    };
+
+    response.timestamp_ms = effects.timestamp.map(|t| t.timestamp_millis() as u64);
+    response.checkpoint = effects.checkpoint.map(|c| c.sequence_number);
+
+    if let Some(raw) = effects.effects_bcs {
+        let raw = Base64::decode(&raw).context("Failed to decode effects BCS")?;
+
+        if options.show_effects {
+            let effects: TransactionEffects =
+                bcs::from_bytes(&raw).context("Failed to deserialize effects")?;
+            response.effects = Some(
+                effects
+                    .try_into()
+                    .context("Failed to convert effects into response")?,
+            );
+        }
+
+        if options.show_raw_effects {
+            response.raw_effects = raw;
+        }
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::base_types::ObjectID;
+
+    use super::*;
+
+    #[test]
+    fn test_filter_translation() {
+        assert_eq!(filter(None).unwrap(), Value::Null);
+
+        assert_eq!(
+            filter(Some(TransactionFilter::Checkpoint(42))).unwrap(),
+            json!({ "atCheckpoint": 42 }),
+        );
+
+        let object = ObjectID::from_single_byte(1);
+        assert_eq!(
+            filter(Some(TransactionFilter::AffectedObject(object))).unwrap(),
+            json!({ "affectedObject": { "address": object.to_string() } }),
+        );
+
+        assert_eq!(
+            filter(Some(TransactionFilter::TransactionKind(
+                "ProgrammableTransaction".to_owned()
+            )))
+            .unwrap(),
+            json!({ "excludeKind": "SYSTEM_TX" }),
+        );
+    }
+
+    #[test]
+    fn test_unsupported_filter() {
+        let err = filter(Some(TransactionFilter::FromAddress(Default::default()))).unwrap_err();
+        assert!(matches!(err, Error::InvalidParams(_)));
+
+        let err = filter(Some(TransactionFilter::TransactionKind(
+            "ChangeEpoch".to_owned(),
+        )))
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidParams(_)));
+    }
+
+    #[test]
+    fn test_options() {
+        let options = SuiTransactionBlockResponseOptions::new().with_raw_effects();
+        assert_eq!(
+            variables(&options).unwrap(),
+            json!({ "showRawInput": false, "showEffects": true }),
+        );
+
+        let options = SuiTransactionBlockResponseOptions::new().with_raw_input();
+        assert_eq!(
+            variables(&options).unwrap(),
+            json!({ "showRawInput": true, "showEffects": false }),
+        );
+
+        let options = SuiTransactionBlockResponseOptions::full_content();
+        let err = variables(&options).unwrap_err();
+        assert!(matches!(err, Error::InvalidParams(_)));
+    }
+
+    #[test]
+    fn test_response_without_effects() {
+        let digest = TransactionDigest::random();
+        let tx = Transaction {
+            digest,
+            transaction_bcs: Some(Base64::encode([1, 2, 3])),
+            effects: Some(Effects {
+                effects_bcs: None,
+                timestamp: DateTime::from_timestamp_millis(1_234),
+                checkpoint: Some(Checkpoint { sequence_number: 5 }),
+            }),
+        };
+
+        let options = SuiTransactionBlockResponseOptions::new().with_raw_input();
+        let response = response(tx, &options).unwrap();
+        assert_eq!(response.digest, digest);
+        assert_eq!(response.raw_transaction, vec![1, 2, 3]);
+        assert_eq!(response.timestamp_ms, Some(1_234));
+        assert_eq!(response.checkpoint, Some(5));
+        assert!(response.effects.is_none());
+        assert!(response.raw_effects.is_empty());
+    }
+}
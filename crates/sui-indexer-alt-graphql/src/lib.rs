@@ -4,28 +4,34 @@
 use std::{any::Any, net::SocketAddr, sync::Arc};
 
 use anyhow::{self, Context};
-use api::types::{address::IAddressable, move_object::IMoveObject, object::IObject};
+use api::types::{
+    address::IAddressable, coin_metadata::CoinMetadataCache, display::DisplayCache,
+    move_object::IMoveObject, object::IObject,
+};
 use async_graphql::{
-    extensions::ExtensionFactory, http::GraphiQLSource, EmptySubscription, ObjectType, Schema,
-    SchemaBuilder, SubscriptionType,
+    extensions::ExtensionFactory,
+    http::{GraphiQLSource, ALL_WEBSOCKET_PROTOCOLS},
+    Data, ObjectType, Response, Schema, SchemaBuilder, ServerError, SubscriptionType,
 };
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    extract::{ConnectInfo, MatchedPath},
-    http::Method,
-    response::Html,
+    extract::{ws::WebSocketUpgrade, ConnectInfo, MatchedPath},
+    http::{HeaderMap, Method},
+    response::{Html, IntoResponse},
     routing::{get, post, MethodRouter},
     Extension, Router,
 };
 use axum_extra::TypedHeader;
 use config::RpcConfig;
 use extensions::{
+    proofs::{ProofTargets, Proofs, ShowProofs},
     query_limits::{show_usage::ShowUsage, QueryLimitsChecker},
     timeout::Timeout,
 };
 use headers::ContentLength;
 use health::DbProbe;
 use prometheus::Registry;
+use scope::PinnedCheckpoint;
 use sui_indexer_alt_reader::pg_reader::db::DbArgs;
 use sui_indexer_alt_reader::system_package_task::{SystemPackageTask, SystemPackageTaskArgs};
 use sui_indexer_alt_reader::{
@@ -46,10 +52,15 @@ use tower_http::cors;
 use tracing::{error, info};
 use url::Url;
 
-use crate::api::{mutation::Mutation, query::Query};
+use crate::api::{mutation::Mutation, query::Query, subscription::Subscription};
 use crate::extensions::logging::{Logging, Session};
 use crate::metrics::RpcMetrics;
-use crate::middleware::version::Version;
+use crate::middleware::{
+    rate_limit::{rate_limit, RateLimiter},
+    version::Version,
+};
+
+pub use extensions::authorization::{Authorization, Authorizer, Caller};
 
 mod api;
 pub mod args;
@@ -58,9 +69,12 @@ mod error;
 mod extensions;
 mod health;
 mod intersect;
+mod jsonrpc;
 mod metrics;
 mod middleware;
 mod pagination;
+#[cfg(test)]
+mod schema_compat;
 mod scope;
 mod task;
 
@@ -101,6 +115,10 @@ pub struct RpcService<Q, M, S> {
     cancel: CancellationToken,
 }
 
+/// The maximum size in bytes of a message sent to the subscriptions endpoint.
+#[derive(Clone, Copy)]
+struct SubscriptionPayloadLimit(u32);
+
 impl<Q, M, S> RpcService<Q, M, S>
 where
     Q: ObjectType + 'static,
@@ -242,8 +260,8 @@ impl Default for RpcArgs {
 }
 
 /// The GraphQL schema this service will serve, without any extensions or context added.
-pub fn schema() -> SchemaBuilder<Query, Mutation, EmptySubscription> {
-    Schema::build(Query::default(), Mutation, EmptySubscription)
+pub fn schema() -> SchemaBuilder<Query, Mutation, Subscription> {
+    Schema::build(Query::default(), Mutation, Subscription)
         .register_output_type::<IAddressable>()
         .register_output_type::<IObject>()
         .register_output_type::<IMoveObject>()
@@ -350,17 +368,43 @@ pub async fn start_rpc(
         cancel.child_token(),
     );
 
+    // Only GraphQL requests (including JSON-RPC requests, which are translated into GraphQL) are
+    // rate limited, so that health checks are always served.
+    let rate_limiter = RateLimiter::new(config.rate_limit, metrics.clone());
+    let rate_limiter = rate_limiter.is_enabled().then(|| Arc::new(rate_limiter));
+    let rate_limited = |route: MethodRouter| match &rate_limiter {
+        Some(rate_limiter) => route.layer(axum::middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit,
+        )),
+        None => route,
+    };
+
+    // Subscriptions are served over websockets, whose messages are limited to the query payload
+    // size, because subscriptions do not accept transaction payloads.
+    let max_subscription_payload = SubscriptionPayloadLimit(config.limits.max_query_payload_size);
+
     let rpc = rpc
-        .route("/graphql", post(graphql))
+        .route("/graphql", rate_limited(post(graphql)))
+        .route("/graphql/subscriptions", get(graphql_ws))
+        .route("/jsonrpc", rate_limited(post(jsonrpc::jsonrpc)))
         .route("/graphql/health", get(health::check))
+        .route("/health", get(health::liveness))
+        .route("/ready", get(health::readiness))
         .layer(watermark_task.watermarks())
+        .layer(max_subscription_payload)
+        .layer(jsonrpc::DefaultPageSize(config.limits.default_page_size))
         .layer(config.health)
         .layer(DbProbe(database_url))
+        .layer(pg_reader.clone())
+        .layer(grpc_client.clone())
+        .extension(Authorization::new(config.authorization.rules()))
         .extension(Timeout::new(config.limits.timeouts()))
         .extension(QueryLimitsChecker::new(
             config.limits.query_limits(),
             metrics,
         ))
+        .extension(Proofs)
         .data(config.limits.pagination())
         .data(config.limits)
         .data(chain_identifier)
@@ -369,6 +413,9 @@ pub async fn start_rpc(
         .data(pg_loader)
         .data(kv_loader)
         .data(package_store)
+        .data(CoinMetadataCache::new(config.cache.coin_metadata_capacity))
+        .data(DisplayCache::default())
+        .data(watermark_task.updates())
         .data(grpc_client);
 
     let h_rpc = rpc.run().await?;
@@ -386,25 +433,75 @@ pub async fn start_rpc(
 /// Handler for RPC requests (POST requests making GraphQL queries).
 async fn graphql(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    Extension(schema): Extension<Schema<Query, Mutation, EmptySubscription>>,
+    Extension(schema): Extension<Schema<Query, Mutation, Subscription>>,
     Extension(watermark): Extension<WatermarksLock>,
     TypedHeader(content_length): TypedHeader<ContentLength>,
     show_usage: Option<TypedHeader<ShowUsage>>,
+    show_proofs: Option<TypedHeader<ShowProofs>>,
+    pinned_checkpoint: Option<TypedHeader<PinnedCheckpoint>>,
+    headers: HeaderMap,
     request: GraphQLRequest,
 ) -> GraphQLResponse {
+    let watermark = watermark.read().await.clone();
     let mut request = request
         .into_inner()
         .data(content_length)
         .data(Session::new(addr))
-        .data(watermark.read().await.clone());
+        .data(Caller::from_headers(&headers));
+
+    if let Some(TypedHeader(pinned_checkpoint)) = pinned_checkpoint {
+        if let Err(e) = pinned_checkpoint.validate(&watermark) {
+            return Response::from_errors(vec![ServerError::from(e)]).into();
+        }
+
+        request = request.data(pinned_checkpoint);
+    }
+
+    request = request.data(watermark);
 
     if let Some(TypedHeader(show_usage)) = show_usage {
         request = request.data(show_usage);
     }
 
+    if show_proofs.is_some() {
+        request = request.data(ProofTargets::default());
+    }
+
     schema.execute(request).await.into()
 }
 
+/// Handler for GraphQL subscriptions, which are served over websockets.
+///
+/// Every operation on a connection shares the information that the POST handler derives from
+/// each request, based on the request that opened the connection. Messages are limited in size by
+/// the websocket, and are all treated as being of that size by the query limits.
+async fn graphql_ws(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Extension(schema): Extension<Schema<Query, Mutation, Subscription>>,
+    Extension(watermark): Extension<WatermarksLock>,
+    Extension(SubscriptionPayloadLimit(limit)): Extension<SubscriptionPayloadLimit>,
+    headers: HeaderMap,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let watermark = watermark.read().await.clone();
+
+    let mut data = Data::default();
+    data.insert(ContentLength(limit as u64));
+    data.insert(Session::new(addr));
+    data.insert(Caller::from_headers(&headers));
+    data.insert(watermark);
+
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .max_message_size(limit as usize)
+        .on_upgrade(move |socket| {
+            GraphQLWebSocket::new(socket, schema, protocol)
+                .with_data(data)
+                .serve()
+        })
+}
+
 /// Handler for GET requests for the online IDE. GraphQL requests are forwarded to the POST handler
 /// at the same path.
 async fn graphiql(path: MatchedPath) -> Html<String> {
@@ -420,6 +517,9 @@ mod tests {
 
     use super::*;
 
+    /// Set this environment variable to accept breaking changes to the schema.
+    const ALLOW_BREAKING_CHANGES: &str = "SUI_GRAPHQL_ALLOW_BREAKING_CHANGES";
+
     /// Check that the exported schema is up-to-date.
     #[test]
     fn test_schema_sdl_export() {
@@ -432,6 +532,23 @@ mod tests {
             "schema.graphql"
         };
 
+        // Changes that could break existing clients need to be accepted explicitly, before the
+        // snapshot can be updated.
+        let snapshot = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("sui_indexer_alt_graphql__tests__{file}.snap"));
+
+        if let Ok(contents) = fs::read_to_string(snapshot) {
+            // Skip the snapshot's metadata header.
+            let old = contents.splitn(3, "---\n").nth(2).unwrap_or_default();
+            let changes = schema_compat::breaking_changes(old, &sdl).unwrap();
+            assert!(
+                changes.is_empty() || std::env::var(ALLOW_BREAKING_CHANGES).is_ok(),
+                "Breaking changes to {file} (set {ALLOW_BREAKING_CHANGES}=1 to accept them):\n{}",
+                changes.join("\n"),
+            );
+        }
+
         // Update the current schema file
         let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(file);
         fs::write(path, &sdl).unwrap();
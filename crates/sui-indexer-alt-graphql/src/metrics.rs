@@ -58,6 +58,9 @@ pub struct RpcMetrics {
     pub query_payload_size: Histogram,
     pub tx_payload_size: Histogram,
 
+    // Requests checked by the rate limiter, by caller and whether they were allowed or limited.
+    pub rate_limit_requests: IntCounterVec,
+
     // Metrics per type and field.
     pub fields_received: IntCounterVec,
     pub fields_succeeded: IntCounterVec,
@@ -216,6 +219,14 @@ impl RpcMetrics {
             )
             .unwrap(),
 
+            rate_limit_requests: register_int_counter_vec_with_registry!(
+                "graphql_rate_limit_requests",
+                "Number of requests checked by the rate limiter, by API key (or 'ip' for callers limited by IP address), and outcome",
+                &["caller", "outcome"],
+                registry,
+            )
+            .unwrap(),
+
             fields_received: register_int_counter_vec_with_registry!(
                 "graphql_fields_received",
                 "Number of times a field of a type has been requested in the GraphQL schema",
@@ -0,0 +1,298 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header::RETRY_AFTER, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{config::RateLimitConfig, extensions::authorization::Caller, metrics::RpcMetrics};
+
+static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Once this many callers are being tracked, callers whose buckets have refilled are forgotten,
+/// to bound the memory used by callers that have stopped sending requests.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token bucket rate limiter for requests to the service. Each caller has a bucket holding up to
+/// `burst` tokens, that refills at its rate of requests per second. Requests take a token from
+/// their caller's bucket, and are rejected with `429 Too Many Requests` if it is empty.
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<Key, Bucket>>,
+    metrics: Arc<RpcMetrics>,
+}
+
+/// Who a request is rate limited as.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+#[derive(Clone, Debug)]
+struct Bucket {
+    /// Tokens available as of `updated`. Fractional, because tokens refill continuously.
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The state of a caller's bucket after a request, reported back to it in response headers.
+#[derive(Clone, Debug, PartialEq)]
+struct Quota {
+    limit: u32,
+    remaining: u32,
+
+    /// How long until the bucket is full again.
+    reset: Duration,
+
+    /// How long until the next request will be accepted, if this one was rejected.
+    retry_after: Option<Duration>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig, metrics: Arc<RpcMetrics>) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    /// Whether the limiter has any limits to enforce.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config.requests_per_second > 0 || !self.config.api_keys.is_empty()
+    }
+
+    /// Take a token from the bucket of the caller identified by `api_key` and `ip`, at time `now`.
+    /// Returns `None` if the caller is not rate limited.
+    fn take(&self, api_key: Option<&str>, ip: IpAddr, now: Instant) -> Option<Quota> {
+        let (key, rate) = match api_key.and_then(|k| self.config.api_keys.get_key_value(k)) {
+            Some((key, rate)) => (Key::ApiKey(key.clone()), *rate),
+            None => (Key::Ip(ip), self.config.requests_per_second),
+        };
+
+        if rate == 0 {
+            return None;
+        }
+
+        let burst = self.config.burst.max(1);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|key, bucket| {
+                let rate = match key {
+                    Key::ApiKey(k) => self.config.api_keys.get(k).copied().unwrap_or(0),
+                    Key::Ip(_) => self.config.requests_per_second,
+                };
+                bucket.refill(rate, burst, now) < burst as f64
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst as f64,
+            updated: now,
+        });
+
+        Some(bucket.take(rate, burst, now))
+    }
+
+    /// The label that metrics for a caller are recorded under: the API key for callers that are
+    /// limited by API key, and "ip" for all other callers, to bound the metrics' cardinality.
+    fn label<'k>(&self, api_key: Option<&'k str>) -> &'k str {
+        match api_key {
+            Some(key) if self.config.api_keys.contains_key(key) => key,
+            _ => "ip",
+        }
+    }
+}
+
+impl Bucket {
+    /// Add the tokens that have accumulated since the bucket was last updated, at `rate` tokens
+    /// per second, up to `burst`, and return the tokens now available.
+    fn refill(&mut self, rate: u32, burst: u32, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(burst as f64);
+        self.updated = now;
+        self.tokens
+    }
+
+    fn take(&mut self, rate: u32, burst: u32, now: Instant) -> Quota {
+        let tokens = self.refill(rate, burst, now);
+        let rate = rate as f64;
+
+        let retry_after = if tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - tokens) / rate))
+        };
+
+        Quota {
+            limit: burst,
+            remaining: self.tokens.floor() as u32,
+            reset: Duration::from_secs_f64((burst as f64 - self.tokens) / rate),
+            retry_after,
+        }
+    }
+}
+
+impl Quota {
+    /// Describe the quota in `RateLimit-*` headers, and a `Retry-After` header if the request was
+    /// rejected. Durations are rounded up to the nearest second.
+    fn set_headers(&self, headers: &mut HeaderMap) {
+        let secs = |d: Duration| HeaderValue::from(d.as_secs_f64().ceil() as u64);
+        headers.insert(RATE_LIMIT_LIMIT.clone(), HeaderValue::from(self.limit));
+        headers.insert(
+            RATE_LIMIT_REMAINING.clone(),
+            HeaderValue::from(self.remaining),
+        );
+        headers.insert(RATE_LIMIT_RESET.clone(), secs(self.reset));
+        if let Some(retry_after) = self.retry_after {
+            headers.insert(RETRY_AFTER, secs(retry_after));
+        }
+    }
+}
+
+/// Rate limit requests by API key or IP address, rejecting requests from callers that have
+/// exhausted their quota, and reporting callers' remaining quota in response headers.
+pub(crate) async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let caller = Caller::from_headers(request.headers());
+    let api_key = caller.api_key.as_deref();
+
+    let Some(quota) = limiter.take(api_key, addr.ip(), Instant::now()) else {
+        return next.run(request).await;
+    };
+
+    let outcome = if quota.retry_after.is_some() {
+        "limited"
+    } else {
+        "allowed"
+    };
+
+    limiter
+        .metrics
+        .rate_limit_requests
+        .with_label_values(&[limiter.label(api_key), outcome])
+        .inc();
+
+    let mut response = if quota.retry_after.is_some() {
+        (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded").into_response()
+    } else {
+        next.run(request).await
+    };
+
+    quota.set_headers(response.headers_mut());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+
+    fn limiter(requests_per_second: u32, burst: u32, api_keys: &[(&str, u32)]) -> RateLimiter {
+        RateLimiter::new(
+            RateLimitConfig {
+                requests_per_second,
+                burst,
+                api_keys: api_keys.iter().map(|(k, r)| (k.to_string(), *r)).collect(),
+            },
+            RpcMetrics::new(&Registry::new()),
+        )
+    }
+
+    const IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn test_burst_then_refill() {
+        let limiter = limiter(2, 3, &[]);
+        let t0 = Instant::now();
+
+        for remaining in [2, 1, 0] {
+            let quota = limiter.take(None, IP, t0).unwrap();
+            assert_eq!(quota.remaining, remaining);
+            assert_eq!(quota.retry_after, None);
+        }
+
+        // The bucket is empty, and refills a token every half a second.
+        let quota = limiter.take(None, IP, t0).unwrap();
+        assert_eq!(quota.retry_after, Some(Duration::from_millis(500)));
+        assert_eq!(quota.reset, Duration::from_millis(1500));
+
+        let quota = limiter
+            .take(None, IP, t0 + Duration::from_millis(500))
+            .unwrap();
+        assert_eq!(quota.retry_after, None);
+        assert_eq!(quota.remaining, 0);
+
+        // The bucket never holds more than the burst size.
+        let quota = limiter
+            .take(None, IP, t0 + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(quota.remaining, 2);
+    }
+
+    #[test]
+    fn test_api_keys_limited_separately() {
+        let limiter = limiter(1, 1, &[("fast", 10)]);
+        let t0 = Instant::now();
+
+        assert!(limiter.take(None, IP, t0).unwrap().retry_after.is_none());
+        assert!(limiter.take(None, IP, t0).unwrap().retry_after.is_some());
+
+        // Unrecognized API keys share their IP address's bucket.
+        let quota = limiter.take(Some("unknown"), IP, t0).unwrap();
+        assert!(quota.retry_after.is_some());
+
+        // Recognized API keys get their own bucket, and rate.
+        let quota = limiter.take(Some("fast"), IP, t0).unwrap();
+        assert!(quota.retry_after.is_none());
+        let quota = limiter.take(Some("fast"), IP, t0).unwrap();
+        assert_eq!(quota.retry_after, Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let limiter = limiter(0, 10, &[("key", 5)]);
+        assert!(limiter.is_enabled());
+        assert!(limiter.take(None, IP, Instant::now()).is_none());
+        assert!(limiter.take(Some("key"), IP, Instant::now()).is_some());
+
+        assert!(!self::limiter(0, 10, &[]).is_enabled());
+    }
+
+    #[test]
+    fn test_headers() {
+        let quota = Quota {
+            limit: 10,
+            remaining: 0,
+            reset: Duration::from_millis(4500),
+            retry_after: Some(Duration::from_millis(100)),
+        };
+
+        let mut headers = HeaderMap::new();
+        quota.set_headers(&mut headers);
+        assert_eq!(headers[&RATE_LIMIT_LIMIT], "10");
+        assert_eq!(headers[&RATE_LIMIT_REMAINING], "0");
+        assert_eq!(headers[&RATE_LIMIT_RESET], "5");
+        assert_eq!(headers[RETRY_AFTER], "1");
+    }
+}
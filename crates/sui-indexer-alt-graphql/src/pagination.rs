@@ -156,6 +156,17 @@ impl<C> Page<C> {
     pub(crate) fn is_from_front(&self) -> bool {
         matches!(self.end, End::Front)
     }
+
+    /// Convert the page's cursors using `f`, dropping the cursors that it maps to `None`. The
+    /// page's limit and the end it is drawn from are preserved.
+    pub(crate) fn map_cursors<D>(self, mut f: impl FnMut(C) -> Option<D>) -> Page<D> {
+        Page {
+            after: self.after.and_then(&mut f),
+            before: self.before.and_then(&mut f),
+            limit: self.limit,
+            end: self.end,
+        }
+    }
 }
 
 impl Page<JsonCursor<usize>> {
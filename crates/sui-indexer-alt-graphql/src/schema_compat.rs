@@ -0,0 +1,220 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detecting changes between two versions of the schema that could break existing clients.
+
+use std::collections::BTreeMap;
+
+use async_graphql::parser::{
+    parse_schema,
+    types::{
+        FieldDefinition, InputValueDefinition, TypeDefinition, TypeKind, TypeSystemDefinition,
+    },
+    Positioned,
+};
+
+/// Compare the `old` and `new` versions of a schema (both in SDL), and return a description of
+/// every change in `new` that could break a client written against `old`:
+///
+/// - Removing a type, or changing what kind of type it is.
+/// - Removing a field, or changing its type.
+/// - Removing a field argument, or changing its type.
+/// - Adding a required field argument, or a required input field.
+/// - Removing an enum value, or a union member.
+pub(crate) fn breaking_changes(old: &str, new: &str) -> anyhow::Result<Vec<String>> {
+    let old = types(old)?;
+    let new = types(new)?;
+    let mut changes = vec![];
+
+    for (name, old) in &old {
+        let Some(new) = new.get(name) else {
+            changes.push(format!("Type '{name}' was removed"));
+            continue;
+        };
+
+        use TypeKind as K;
+        match (&old.kind, &new.kind) {
+            (K::Scalar, K::Scalar) => {}
+
+            (K::Object(o), K::Object(n)) => fields(&mut changes, name, &o.fields, &n.fields),
+            (K::Interface(o), K::Interface(n)) => fields(&mut changes, name, &o.fields, &n.fields),
+
+            (K::Union(o), K::Union(n)) => {
+                for member in &o.members {
+                    if !n.members.iter().any(|m| m.node == member.node) {
+                        changes.push(format!(
+                            "Member '{}' was removed from union '{name}'",
+                            member.node
+                        ));
+                    }
+                }
+            }
+
+            (K::Enum(o), K::Enum(n)) => {
+                for value in &o.values {
+                    let value = &value.node.value.node;
+                    if !n.values.iter().any(|v| &v.node.value.node == value) {
+                        changes.push(format!("Value '{value}' was removed from enum '{name}'"));
+                    }
+                }
+            }
+
+            (K::InputObject(o), K::InputObject(n)) => inputs(
+                &mut changes,
+                &format!("input '{name}'"),
+                &o.fields,
+                &n.fields,
+            ),
+
+            _ => changes.push(format!("Type '{name}' changed kind")),
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Parse the type definitions out of `sdl`, by name.
+fn types(sdl: &str) -> anyhow::Result<BTreeMap<String, TypeDefinition>> {
+    Ok(parse_schema(sdl)?
+        .definitions
+        .into_iter()
+        .filter_map(|def| match def {
+            TypeSystemDefinition::Type(def) => Some((def.node.name.node.to_string(), def.node)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Detect breaking changes to the fields of an object or interface called `name`.
+fn fields(
+    changes: &mut Vec<String>,
+    name: &str,
+    old: &[Positioned<FieldDefinition>],
+    new: &[Positioned<FieldDefinition>],
+) {
+    for old in old {
+        let field = &old.node.name.node;
+        let Some(new) = new.iter().find(|f| &f.node.name.node == field) else {
+            changes.push(format!("Field '{name}.{field}' was removed"));
+            continue;
+        };
+
+        let (old_ty, new_ty) = (&old.node.ty.node, &new.node.ty.node);
+        if old_ty != new_ty {
+            changes.push(format!(
+                "Field '{name}.{field}' changed type from '{old_ty}' to '{new_ty}'"
+            ));
+        }
+
+        inputs(
+            changes,
+            &format!("field '{name}.{field}'"),
+            &old.node.arguments,
+            &new.node.arguments,
+        );
+    }
+}
+
+/// Detect breaking changes to the inputs (field arguments, or input object fields) of `owner`.
+fn inputs(
+    changes: &mut Vec<String>,
+    owner: &str,
+    old: &[Positioned<InputValueDefinition>],
+    new: &[Positioned<InputValueDefinition>],
+) {
+    for old in old {
+        let input = &old.node.name.node;
+        let Some(new) = new.iter().find(|i| &i.node.name.node == input) else {
+            changes.push(format!("Input '{input}' was removed from {owner}"));
+            continue;
+        };
+
+        let (old_ty, new_ty) = (&old.node.ty.node, &new.node.ty.node);
+        if old_ty != new_ty {
+            changes.push(format!(
+                "Input '{input}' of {owner} changed type from '{old_ty}' to '{new_ty}'"
+            ));
+        }
+    }
+
+    for new in new {
+        let input = &new.node.name.node;
+        let required = !new.node.ty.node.nullable && new.node.default_value.is_none();
+        if required && !old.iter().any(|i| &i.node.name.node == input) {
+            changes.push(format!("Required input '{input}' was added to {owner}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"
+        type Query {
+            a(x: Int): String
+            b: Int!
+        }
+
+        enum Color { RED GREEN }
+
+        input Filter { after: Int }
+
+        union Thing = Query | Other
+
+        type Other { c: Int }
+    "#;
+
+    #[test]
+    fn test_additions_are_compatible() {
+        let new = r#"
+            type Query {
+                a(x: Int, y: Int): String
+                b: Int!
+                c: Boolean
+            }
+
+            enum Color { RED GREEN BLUE }
+
+            input Filter { after: Int, before: Int }
+
+            union Thing = Query | Other | Another
+
+            type Other { c: Int }
+
+            type Another { d: Int }
+        "#;
+
+        assert_eq!(breaking_changes(OLD, new).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let new = r#"
+            type Query {
+                a(x: String, z: Int!): String
+            }
+
+            enum Color { RED }
+
+            input Filter { after: Int, before: Int! }
+
+            union Thing = Query
+
+            scalar Other
+        "#;
+
+        assert_eq!(
+            breaking_changes(OLD, new).unwrap(),
+            vec![
+                "Value 'GREEN' was removed from enum 'Color'",
+                "Required input 'before' was added to input 'Filter'",
+                "Type 'Other' changed kind",
+                "Input 'x' of field 'Query.a' changed type from 'Int' to 'String'",
+                "Required input 'z' was added to field 'Query.a'",
+                "Field 'Query.b' was removed",
+                "Member 'Other' was removed from union 'Thing'",
+            ]
+        );
+    }
+}
@@ -4,10 +4,23 @@
 use std::sync::Arc;
 
 use async_graphql::Context;
+use axum::http::{HeaderName, HeaderValue};
+use headers::Header;
 use sui_indexer_alt_reader::package_resolver::PackageCache;
 use sui_package_resolver::{PackageStore, Resolver};
 
-use crate::{config::Limits, error::RpcError, task::watermark::Watermarks};
+use crate::{
+    config::Limits,
+    error::{bad_user_input, out_of_retention, RpcError},
+    task::watermark::Watermarks,
+};
+
+static CHECKPOINT: HeaderName = HeaderName::from_static("x-sui-checkpoint");
+
+/// Header pinning the checkpoint that the whole request views data at, so that a client can send
+/// several requests that all see the same snapshot. Its value is a checkpoint sequence number,
+/// which must still be available at every pipeline.
+pub(crate) struct PinnedCheckpoint(pub u64);
 
 /// A way to share information between fields in a request, similar to [Context].
 ///
@@ -27,6 +40,17 @@ pub(crate) struct Scope {
     resolver_limits: sui_package_resolver::Limits,
 }
 
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("Checkpoint {checkpoint} has been pruned, the earliest available checkpoint is {lo}")]
+    CheckpointPruned { checkpoint: u64, lo: u64 },
+
+    #[error(
+        "Checkpoint {checkpoint} is not available yet, the latest available checkpoint is {hi}"
+    )]
+    CheckpointNotYetAvailable { checkpoint: u64, hi: u64 },
+}
+
 impl Scope {
     /// Create a new scope at the top-level (initialized by information we have at the root of a
     /// request).
@@ -35,8 +59,32 @@ impl Scope {
         let package_store: &Arc<PackageCache> = ctx.data()?;
         let limits: &Limits = ctx.data()?;
 
+        // Requests that pinned a checkpoint have already had it checked against the same
+        // watermarks, by [PinnedCheckpoint::validate].
+        let checkpoint_viewed_at = match ctx.data_opt::<PinnedCheckpoint>() {
+            Some(PinnedCheckpoint(checkpoint)) => *checkpoint,
+            None => watermark.high_watermark().checkpoint(),
+        };
+
+        Ok(Self {
+            checkpoint_viewed_at,
+            package_store: package_store.clone(),
+            resolver_limits: limits.package_resolver(),
+        })
+    }
+
+    /// Create a new scope at the top-level that views data at the high watermark of `watermarks`,
+    /// rather than the watermarks the request started with. Used by requests that keep serving
+    /// new data as it is indexed, like subscriptions.
+    pub(crate) fn with_watermarks<E: std::error::Error>(
+        ctx: &Context<'_>,
+        watermarks: &Watermarks,
+    ) -> Result<Self, RpcError<E>> {
+        let package_store: &Arc<PackageCache> = ctx.data()?;
+        let limits: &Limits = ctx.data()?;
+
         Ok(Self {
-            checkpoint_viewed_at: watermark.high_watermark().checkpoint(),
+            checkpoint_viewed_at: watermarks.high_watermark().checkpoint(),
             package_store: package_store.clone(),
             resolver_limits: limits.package_resolver(),
         })
@@ -67,3 +115,66 @@ impl Scope {
         Resolver::new_with_limits(self.package_store.clone(), self.resolver_limits.clone())
     }
 }
+
+impl PinnedCheckpoint {
+    /// Check that the pinned checkpoint is one whose data is available, according to
+    /// `watermarks`.
+    pub(crate) fn validate(&self, watermarks: &Watermarks) -> Result<(), RpcError<Error>> {
+        let checkpoint = self.0;
+
+        let lo = watermarks.global_lo_checkpoint();
+        if checkpoint < lo {
+            return Err(out_of_retention(Error::CheckpointPruned { checkpoint, lo }));
+        }
+
+        let hi = watermarks.high_watermark().checkpoint();
+        if checkpoint > hi {
+            return Err(bad_user_input(Error::CheckpointNotYetAvailable {
+                checkpoint,
+                hi,
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl Header for PinnedCheckpoint {
+    fn name() -> &'static HeaderName {
+        &CHECKPOINT
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        let checkpoint = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(headers::Error::invalid)?;
+
+        Ok(PinnedCheckpoint(checkpoint))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend([HeaderValue::from(self.0)]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(value: &'static str) -> Result<PinnedCheckpoint, headers::Error> {
+        PinnedCheckpoint::decode(&mut [HeaderValue::from_static(value)].iter())
+    }
+
+    #[test]
+    fn test_decode_pinned_checkpoint() {
+        assert_eq!(decode("42").unwrap().0, 42);
+        assert_eq!(decode(" 42 ").unwrap().0, 42);
+        assert!(decode("-1").is_err());
+        assert!(decode("latest").is_err());
+    }
+}
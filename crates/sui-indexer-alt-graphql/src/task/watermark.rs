@@ -20,7 +20,12 @@ use sui_indexer_alt_reader::{
     pg_reader::PgReader,
 };
 use sui_sql_macro::query;
-use tokio::{join, sync::RwLock, task::JoinHandle, time};
+use tokio::{
+    join,
+    sync::{Notify, RwLock},
+    task::JoinHandle,
+    time,
+};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
@@ -36,6 +41,9 @@ pub(crate) struct WatermarkTask {
     /// efficiently swap in new watermark values.
     watermarks: WatermarksLock,
 
+    /// Notified every time the watermarks are updated.
+    updated: Arc<Notify>,
+
     /// Access to the Postgres DB
     pg_reader: PgReader,
 
@@ -109,6 +117,14 @@ struct WatermarkRow {
 
 pub(crate) type WatermarksLock = Arc<RwLock<Arc<Watermarks>>>;
 
+/// A handle to the watermarks that can also wait for them to be updated, for requests that outlive
+/// a single snapshot of the watermarks, like subscriptions.
+#[derive(Clone)]
+pub(crate) struct WatermarkUpdates {
+    watermarks: WatermarksLock,
+    updated: Arc<Notify>,
+}
+
 impl WatermarkTask {
     pub(crate) fn new(
         config: WatermarkConfig,
@@ -125,6 +141,7 @@ impl WatermarkTask {
 
         Self {
             watermarks: Default::default(),
+            updated: Default::default(),
             pg_reader,
             bigtable_reader,
             consistent_reader,
@@ -140,6 +157,14 @@ impl WatermarkTask {
         self.watermarks.clone()
     }
 
+    /// A handle to the watermarks that this task writes to, that can wait for them to change.
+    pub(crate) fn updates(&self) -> WatermarkUpdates {
+        WatermarkUpdates {
+            watermarks: self.watermarks.clone(),
+            updated: self.updated.clone(),
+        }
+    }
+
     /// Start a new task that regularly polls the database for watermarks.
     ///
     /// This operation consume the `self` and returns a handle to the spawned tokio task. The task
@@ -148,6 +173,7 @@ impl WatermarkTask {
         tokio::spawn(async move {
             let Self {
                 watermarks,
+                updated,
                 pg_reader,
                 bigtable_reader,
                 consistent_reader,
@@ -206,6 +232,7 @@ impl WatermarkTask {
                         );
 
                         *watermarks.write().await = Arc::new(w);
+                        updated.notify_waiters();
                     }
                 }
             }
@@ -227,6 +254,16 @@ impl Watermarks {
             .ok_or_else(|| anyhow!("'{pipeline}' not found in pipeline_lo watermarks"))
     }
 
+    /// The lowest checkpoint that every pipeline still has data for, as an inclusive checkpoint
+    /// number.
+    pub(crate) fn global_lo_checkpoint(&self) -> u64 {
+        self.pipeline_lo
+            .values()
+            .map(Watermark::checkpoint)
+            .max()
+            .unwrap_or_default()
+    }
+
     /// Timestamp corresponding to high watermark. Can be `None` if the timestamp is out of range
     /// (should not happen under normal operation).
     pub(crate) fn timestamp_hi(&self) -> Option<DateTime<Utc>> {
@@ -252,6 +289,28 @@ impl Watermarks {
     }
 }
 
+impl WatermarkUpdates {
+    /// The latest snapshot of the watermarks.
+    pub(crate) async fn latest(&self) -> Arc<Watermarks> {
+        self.watermarks.read().await.clone()
+    }
+
+    /// Wait until the watermarks satisfy `pred`, and return the first snapshot that does.
+    pub(crate) async fn wait_for(&self, pred: impl Fn(&Watermarks) -> bool) -> Arc<Watermarks> {
+        loop {
+            // Start listening for updates before checking the current watermarks, so that an
+            // update that lands in between is not missed.
+            let updated = self.updated.notified();
+            let watermarks = self.latest().await;
+            if pred(&watermarks) {
+                return watermarks;
+            }
+
+            updated.await;
+        }
+    }
+}
+
 impl Watermark {
     pub(crate) fn checkpoint(&self) -> u64 {
         self.checkpoint as u64
@@ -28,6 +28,8 @@ impl Loader<CheckpointKey> for PgReader {
     ) -> Result<HashMap<CheckpointKey, Self::Value>, Error> {
         use kv_checkpoints::dsl as c;
 
+        self.record_batch("checkpoint", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
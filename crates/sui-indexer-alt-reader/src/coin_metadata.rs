@@ -45,6 +45,8 @@ impl Loader<CoinMetadataKey> for PgReader {
             };
         }
 
+        self.record_batch("coin_metadata", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
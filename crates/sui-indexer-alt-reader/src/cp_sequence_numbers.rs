@@ -25,6 +25,8 @@ impl Loader<CpSequenceNumberKey> for PgReader {
     ) -> Result<HashMap<CpSequenceNumberKey, Self::Value>, Error> {
         use cp_sequence_numbers::dsl as c;
 
+        self.record_batch("cp_sequence_number", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -23,6 +23,8 @@ impl Loader<DisplayKey> for PgReader {
     async fn load(&self, keys: &[DisplayKey]) -> Result<HashMap<DisplayKey, Self::Value>, Error> {
         use sum_displays::dsl as d;
 
+        self.record_batch("display", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -38,6 +38,8 @@ impl Loader<EpochStartKey> for PgReader {
     ) -> Result<HashMap<EpochStartKey, Self::Value>, Error> {
         use kv_epoch_starts::dsl as s;
 
+        self.record_batch("epoch_start", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -65,6 +67,8 @@ impl Loader<CheckpointBoundedEpochStartKey> for PgReader {
         &self,
         keys: &[CheckpointBoundedEpochStartKey],
     ) -> Result<HashMap<CheckpointBoundedEpochStartKey, Self::Value>, Error> {
+        self.record_batch("checkpoint_bounded_epoch_start", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -127,6 +131,8 @@ impl Loader<EpochEndKey> for PgReader {
     async fn load(&self, keys: &[EpochEndKey]) -> Result<HashMap<EpochEndKey, Self::Value>, Error> {
         use kv_epoch_ends::dsl as e;
 
+        self.record_batch("epoch_end", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
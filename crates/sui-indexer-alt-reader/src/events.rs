@@ -36,6 +36,8 @@ impl Loader<TransactionEventsKey> for PgReader {
     ) -> Result<HashMap<TransactionEventsKey, Self::Value>, Error> {
         use kv_transactions::dsl as t;
 
+        self.record_batch("transaction_events", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -14,7 +14,6 @@ pub struct FullNodeArgs {
 /// A reader backed by the full node gRPC service.
 #[derive(Clone)]
 pub struct FullNodeClient {
-    #[allow(dead_code)]
     client: Option<sui_rpc_api::client::Client>,
     #[allow(dead_code)]
     cancel: CancellationToken,
@@ -30,4 +29,17 @@ impl FullNodeClient {
 
         Ok(Self { client, cancel })
     }
+
+    /// Check that the full node is reachable, by fetching its latest checkpoint. Returns `None` if
+    /// no full node is configured.
+    pub async fn probe(&self) -> Option<anyhow::Result<()>> {
+        let client = self.client.as_ref()?;
+        Some(
+            client
+                .get_latest_checkpoint()
+                .await
+                .map(|_| ())
+                .context("Failed to fetch latest checkpoint from full node"),
+        )
+    }
 }
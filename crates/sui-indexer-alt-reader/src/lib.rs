@@ -20,5 +20,6 @@ pub mod packages;
 pub mod pg_reader;
 pub mod system_package_task;
 pub mod transactions;
+pub mod transfer_policies;
 pub mod tx_balance_changes;
 pub mod tx_digests;
@@ -16,9 +16,16 @@ const LATENCY_SEC_BUCKETS: &[f64] = &[
     200.0, 500.0, 1000.0,
 ];
 
+/// Histogram buckets for the distribution of the number of keys a data loader fetches in a single
+/// batch.
+const BATCH_SIZE_BUCKETS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0,
+];
+
 #[derive(Clone)]
 pub(crate) struct DbReaderMetrics {
     pub latency: Histogram,
+    pub batch_size: HistogramVec,
     pub requests_received: IntCounter,
     pub requests_succeeded: IntCounter,
     pub requests_failed: IntCounter,
@@ -46,6 +53,15 @@ impl DbReaderMetrics {
             )
             .unwrap(),
 
+            batch_size: register_histogram_vec_with_registry!(
+                name("batch_size"),
+                "Number of keys fetched together by a data loader, per kind of key",
+                &["loader"],
+                BATCH_SIZE_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+
             requests_received: register_int_counter_with_registry!(
                 name("requests_received"),
                 "Number of database requests sent by the service",
@@ -38,6 +38,8 @@ impl Loader<LatestObjectVersionKey> for PgReader {
         &self,
         keys: &[LatestObjectVersionKey],
     ) -> Result<HashMap<LatestObjectVersionKey, StoredObjVersion>, Error> {
+        self.record_batch("latest_object_version", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -98,6 +100,8 @@ impl Loader<VersionBoundedObjectVersionKey> for PgReader {
         &self,
         keys: &[VersionBoundedObjectVersionKey],
     ) -> Result<HashMap<VersionBoundedObjectVersionKey, StoredObjVersion>, Error> {
+        self.record_batch("version_bounded_object_version", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -171,6 +175,8 @@ impl Loader<CheckpointBoundedObjectVersionKey> for PgReader {
         &self,
         keys: &[CheckpointBoundedObjectVersionKey],
     ) -> Result<HashMap<CheckpointBoundedObjectVersionKey, StoredObjVersion>, Error> {
+        self.record_batch("checkpoint_bounded_object_version", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -248,6 +254,8 @@ impl Loader<VersionedObjectVersionKey> for PgReader {
         &self,
         keys: &[VersionedObjectVersionKey],
     ) -> Result<HashMap<VersionedObjectVersionKey, StoredObjVersion>, Error> {
+        self.record_batch("versioned_object_version", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -25,6 +25,8 @@ impl Loader<VersionedObjectKey> for PgReader {
     ) -> Result<HashMap<VersionedObjectKey, StoredObject>, Error> {
         use kv_objects::dsl as o;
 
+        self.record_batch("versioned_object", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
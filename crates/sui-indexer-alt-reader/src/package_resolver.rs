@@ -48,6 +48,8 @@ impl Loader<PackageKey> for PgReader {
 
     async fn load(&self, keys: &[PackageKey]) -> Result<HashMap<PackageKey, Arc<Package>>> {
         let mut id_to_package = HashMap::new();
+        self.record_batch("package", keys);
+
         if keys.is_empty() {
             return Ok(id_to_package);
         }
@@ -33,6 +33,8 @@ impl Loader<PackageOriginalIdKey> for PgReader {
         &self,
         keys: &[PackageOriginalIdKey],
     ) -> Result<HashMap<PackageOriginalIdKey, StoredPackageOriginalId>, Error> {
+        self.record_batch("package_original_id", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -89,6 +91,8 @@ impl Loader<CheckpointBoundedOriginalPackageKey> for PgReader {
         &self,
         keys: &[CheckpointBoundedOriginalPackageKey],
     ) -> Result<HashMap<CheckpointBoundedOriginalPackageKey, StoredPackage>, Error> {
+        self.record_batch("checkpoint_bounded_original_package", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
@@ -165,6 +169,8 @@ impl Loader<VersionedOriginalPackageKey> for PgReader {
         &self,
         keys: &[VersionedOriginalPackageKey],
     ) -> Result<HashMap<VersionedOriginalPackageKey, StoredPackage>, Error> {
+        self.record_batch("versioned_original_package", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
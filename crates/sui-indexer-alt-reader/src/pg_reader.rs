@@ -99,6 +99,16 @@ impl PgReader {
             }
         }
     }
+
+    /// Record the size of a batch of keys that the data loader named `loader` is about to fetch.
+    /// The distribution shows how well a request's lookups are being coalesced into a constant
+    /// number of queries.
+    pub(crate) fn record_batch<K>(&self, loader: &'static str, keys: &[K]) {
+        self.metrics
+            .batch_size
+            .with_label_values(&[loader])
+            .observe(keys.len() as f64);
+    }
 }
 
 impl Connection<'_> {
@@ -26,6 +26,8 @@ impl Loader<TransactionKey> for PgReader {
     ) -> Result<HashMap<TransactionKey, Self::Value>, Error> {
         use kv_transactions::dsl as t;
 
+        self.record_batch("transaction", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
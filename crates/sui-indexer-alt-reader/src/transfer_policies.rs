@@ -0,0 +1,106 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use async_graphql::dataloader::Loader;
+use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, QueryDsl};
+use move_core_types::language_storage::StructTag;
+use sui_indexer_alt_schema::{objects::StoredObjInfo, schema::obj_info};
+use sui_types::{TypeTag, SUI_FRAMEWORK_ADDRESS};
+
+use crate::{error::Error, pg_reader::PgReader};
+
+const TRANSFER_POLICY_MODULE_NAME: &str = "transfer_policy";
+const TRANSFER_POLICY_STRUCT_NAME: &str = "TransferPolicy";
+
+/// Key for fetching the live `0x2::transfer_policy::TransferPolicy` objects that apply to a type
+/// of item, based on that type, e.g. `0x2::kiosk::Item`. Values are ordered by object ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransferPolicyKey(pub StructTag);
+
+#[async_trait::async_trait]
+impl Loader<TransferPolicyKey> for PgReader {
+    type Value = Vec<StoredObjInfo>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[TransferPolicyKey],
+    ) -> Result<HashMap<TransferPolicyKey, Vec<StoredObjInfo>>, Error> {
+        use obj_info::dsl as o;
+
+        let (candidates, newer) = diesel::alias!(obj_info as candidates, obj_info as newer);
+
+        macro_rules! candidates {
+            ($($field:ident),* $(,)?) => {
+                candidates.fields(($(o::$field),*))
+            };
+        }
+
+        macro_rules! newer {
+            ($($field:ident),* $(,)?) => {
+                newer.fields(($(o::$field),*))
+            };
+        }
+
+        self.record_batch("transfer_policies", keys);
+
+        if keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut conn = self.connect().await?;
+
+        let instantiations = keys
+            .iter()
+            .map(|TransferPolicyKey(tag)| {
+                let params: Vec<TypeTag> = vec![tag.clone().into()];
+                bcs::to_bytes(&params)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to serialize transfer policy type parameters")?;
+
+        let query = candidates
+            .left_join(
+                newer.on(candidates!(object_id)
+                    .eq(newer!(object_id))
+                    .and(candidates!(cp_sequence_number).lt(newer!(cp_sequence_number)))),
+            )
+            .select(candidates!(
+                object_id,
+                cp_sequence_number,
+                owner_kind,
+                owner_id,
+                package,
+                module,
+                name,
+                instantiation,
+            ))
+            .filter(newer!(object_id).is_null())
+            .filter(candidates!(package).eq(SUI_FRAMEWORK_ADDRESS.into_bytes()))
+            .filter(candidates!(module).eq(TRANSFER_POLICY_MODULE_NAME))
+            .filter(candidates!(name).eq(TRANSFER_POLICY_STRUCT_NAME))
+            .filter(candidates!(instantiation).eq_any(&instantiations))
+            .order_by(candidates!(object_id));
+
+        let obj_info: Vec<StoredObjInfo> = conn.results(query).await?;
+        let mut instantiations_to_stored: HashMap<_, Vec<_>> = HashMap::new();
+        for stored in obj_info {
+            instantiations_to_stored
+                .entry(stored.instantiation.clone())
+                .or_default()
+                .push(stored);
+        }
+
+        Ok(keys
+            .iter()
+            .zip(instantiations)
+            .filter_map(|(key, inst)| {
+                let stored = instantiations_to_stored.get(&Some(inst))?;
+                Some((key.clone(), stored.clone()))
+            })
+            .collect())
+    }
+}
@@ -29,6 +29,8 @@ impl Loader<TxBalanceChangeKey> for PgReader {
         use tx_balance_changes::dsl as b;
         use tx_digests::dsl as t;
 
+        self.record_batch("tx_balance_change", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
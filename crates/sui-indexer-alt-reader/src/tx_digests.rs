@@ -21,6 +21,8 @@ impl Loader<TxDigestKey> for PgReader {
     async fn load(&self, keys: &[TxDigestKey]) -> Result<HashMap<TxDigestKey, Self::Value>, Error> {
         use tx_digests::dsl as d;
 
+        self.record_batch("tx_digest", keys);
+
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
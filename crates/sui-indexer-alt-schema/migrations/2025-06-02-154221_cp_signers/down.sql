@@ -0,0 +1 @@
+DROP TABLE IF EXISTS cp_signers;
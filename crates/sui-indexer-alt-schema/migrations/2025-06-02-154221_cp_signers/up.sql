@@ -0,0 +1,13 @@
+-- This table records which validators signed each checkpoint's certificate, to support filtering
+-- checkpoints by their signers.
+CREATE TABLE IF NOT EXISTS cp_signers
+(
+    cp_sequence_number                  BIGINT       PRIMARY KEY,
+    -- The epoch this checkpoint belongs to.
+    epoch                               BIGINT       NOT NULL,
+    -- The indices of the validators that signed this checkpoint, in the committee of its epoch.
+    signers                             SMALLINT[]   NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS cp_signers_signers
+ON cp_signers USING GIN (signers);
@@ -0,0 +1,18 @@
+-- This table records backfills launched by the indexer's supervisor, to re-index the checkpoints
+-- that a pipeline stalled on, so that their progress can be surfaced to RPC clients.
+CREATE TABLE IF NOT EXISTS backfills
+(
+    pipeline                            TEXT         NOT NULL,
+    -- The range of checkpoints being backfilled, inclusive on both ends.
+    first_checkpoint                    BIGINT       NOT NULL,
+    last_checkpoint                     BIGINT       NOT NULL,
+    -- When the backfill was launched, in milliseconds since the Unix epoch.
+    started_at_ms                       BIGINT       NOT NULL,
+    -- When the backfill finished, or NULL if it is still ongoing.
+    finished_at_ms                      BIGINT,
+    PRIMARY KEY (pipeline, first_checkpoint)
+);
+
+CREATE INDEX IF NOT EXISTS backfills_ongoing
+ON backfills (started_at_ms)
+WHERE finished_at_ms IS NULL;
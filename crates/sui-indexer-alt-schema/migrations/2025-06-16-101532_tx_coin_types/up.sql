@@ -0,0 +1,13 @@
+-- This table records the types of the coins that each transaction took as input, including its gas
+-- coins, to support filtering transactions by the coin types they use.
+CREATE TABLE IF NOT EXISTS tx_coin_types
+(
+    -- The BCS-serialized TypeTag of the coin's type parameter, e.g. `0x2::sui::SUI` for a
+    -- `0x2::coin::Coin<0x2::sui::SUI>`.
+    coin_type                   BYTEA        NOT NULL,
+    tx_sequence_number          BIGINT       NOT NULL,
+    PRIMARY KEY (coin_type, tx_sequence_number)
+);
+
+CREATE INDEX IF NOT EXISTS tx_coin_types_tx_sequence_number
+ON tx_coin_types (tx_sequence_number);
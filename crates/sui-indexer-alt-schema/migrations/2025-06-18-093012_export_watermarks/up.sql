@@ -0,0 +1,9 @@
+-- This table records how far each of the indexer's exporters has published the data it exports,
+-- so that an exporter can resume where it left off after restarting. A checkpoint is only recorded
+-- once everything exported from it has been acknowledged by the sink.
+CREATE TABLE IF NOT EXISTS export_watermarks
+(
+    exporter                            TEXT         PRIMARY KEY,
+    -- The last checkpoint whose data has been exported, inclusive.
+    checkpoint_hi_inclusive             BIGINT       NOT NULL
+);
@@ -0,0 +1,13 @@
+-- This table records the progress of each shard of a backfill run by the indexer's backfill
+-- command, so that an interrupted backfill can resume where each of its shards left off.
+CREATE TABLE IF NOT EXISTS backfill_shards
+(
+    pipeline                            TEXT         NOT NULL,
+    -- The range of checkpoints this shard covers, inclusive on both ends.
+    first_checkpoint                    BIGINT       NOT NULL,
+    last_checkpoint                     BIGINT       NOT NULL,
+    -- The next checkpoint the shard will backfill. Everything before it has been written. Once
+    -- the shard has finished, this is one past its last checkpoint.
+    next_checkpoint                     BIGINT       NOT NULL,
+    PRIMARY KEY (pipeline, first_checkpoint)
+);
@@ -0,0 +1,26 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use diesel::prelude::*;
+use sui_field_count::FieldCount;
+
+use crate::schema::{backfill_shards, backfills};
+
+#[derive(Insertable, Selectable, Queryable, Debug, Clone, FieldCount)]
+#[diesel(table_name = backfills)]
+pub struct StoredBackfill {
+    pub pipeline: String,
+    pub first_checkpoint: i64,
+    pub last_checkpoint: i64,
+    pub started_at_ms: i64,
+    pub finished_at_ms: Option<i64>,
+}
+
+#[derive(Insertable, Selectable, Queryable, Debug, Clone, FieldCount)]
+#[diesel(table_name = backfill_shards)]
+pub struct StoredBackfillShard {
+    pub pipeline: String,
+    pub first_checkpoint: i64,
+    pub last_checkpoint: i64,
+    pub next_checkpoint: i64,
+}
@@ -7,7 +7,7 @@ use sui_field_count::FieldCount;
 use sui_protocol_config::{Chain, ProtocolVersion};
 use sui_types::digests::{ChainIdentifier, CheckpointDigest};
 
-use crate::schema::{kv_checkpoints, kv_genesis};
+use crate::schema::{cp_signers, kv_checkpoints, kv_genesis};
 
 #[derive(Insertable, Debug, Clone, FieldCount, Queryable)]
 #[diesel(table_name = kv_checkpoints)]
@@ -21,6 +21,15 @@ pub struct StoredCheckpoint {
     pub validator_signatures: Vec<u8>,
 }
 
+#[derive(Insertable, Selectable, Queryable, Debug, Clone, FieldCount)]
+#[diesel(table_name = cp_signers)]
+pub struct StoredCpSigners {
+    pub cp_sequence_number: i64,
+    pub epoch: i64,
+    /// Committee indices of the validators that signed the checkpoint.
+    pub signers: Vec<i16>,
+}
+
 #[derive(Insertable, Selectable, Queryable, Debug, Clone)]
 #[diesel(table_name = kv_genesis)]
 pub struct StoredGenesis {
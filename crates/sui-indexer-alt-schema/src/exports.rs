@@ -0,0 +1,14 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use diesel::prelude::*;
+use sui_field_count::FieldCount;
+
+use crate::schema::export_watermarks;
+
+#[derive(Insertable, Selectable, Queryable, Debug, Clone, FieldCount)]
+#[diesel(table_name = export_watermarks)]
+pub struct StoredExportWatermark {
+    pub exporter: String,
+    pub checkpoint_hi_inclusive: i64,
+}
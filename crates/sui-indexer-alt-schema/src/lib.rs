@@ -3,11 +3,13 @@
 
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 
+pub mod backfills;
 pub mod checkpoints;
 pub mod cp_sequence_numbers;
 pub mod displays;
 pub mod epochs;
 pub mod events;
+pub mod exports;
 pub mod objects;
 pub mod packages;
 pub mod schema;
@@ -2,6 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    backfill_shards (pipeline, first_checkpoint) {
+        pipeline -> Text,
+        first_checkpoint -> Int8,
+        last_checkpoint -> Int8,
+        next_checkpoint -> Int8,
+    }
+}
+
+diesel::table! {
+    backfills (pipeline, first_checkpoint) {
+        pipeline -> Text,
+        first_checkpoint -> Int8,
+        last_checkpoint -> Int8,
+        started_at_ms -> Int8,
+        finished_at_ms -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     coin_balance_buckets (object_id, cp_sequence_number) {
         object_id -> Bytea,
@@ -20,6 +39,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    cp_signers (cp_sequence_number) {
+        cp_sequence_number -> Int8,
+        epoch -> Int8,
+        signers -> Array<Int2>,
+    }
+}
+
 diesel::table! {
     cp_sequence_numbers (cp_sequence_number) {
         cp_sequence_number -> Int8,
@@ -48,6 +75,13 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    export_watermarks (exporter) {
+        exporter -> Text,
+        checkpoint_hi_inclusive -> Int8,
+    }
+}
+
 diesel::table! {
     kv_checkpoints (sequence_number) {
         sequence_number -> Int8,
@@ -213,6 +247,13 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    tx_coin_types (coin_type, tx_sequence_number) {
+        coin_type -> Bytea,
+        tx_sequence_number -> Int8,
+    }
+}
+
 diesel::table! {
     tx_digests (tx_sequence_number) {
         tx_sequence_number -> Int8,
@@ -241,11 +282,15 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    backfill_shards,
+    backfills,
     coin_balance_buckets,
     coin_balance_buckets_deletion_reference,
     cp_sequence_numbers,
+    cp_signers,
     ev_emit_mod,
     ev_struct_inst,
+    export_watermarks,
     kv_checkpoints,
     kv_epoch_ends,
     kv_epoch_starts,
@@ -263,6 +308,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     tx_affected_objects,
     tx_balance_changes,
     tx_calls,
+    tx_coin_types,
     tx_digests,
     tx_kinds,
     watermarks,
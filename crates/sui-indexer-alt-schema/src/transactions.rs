@@ -3,7 +3,7 @@
 
 use crate::schema::{
     kv_transactions, tx_affected_addresses, tx_affected_objects, tx_balance_changes, tx_calls,
-    tx_digests, tx_kinds,
+    tx_coin_types, tx_digests, tx_kinds,
 };
 use diesel::{
     backend::Backend,
@@ -81,6 +81,14 @@ pub struct StoredTxCalls {
     pub sender: Vec<u8>,
 }
 
+#[derive(Insertable, Debug, Clone, FieldCount, Queryable)]
+#[diesel(table_name = tx_coin_types)]
+pub struct StoredTxCoinType {
+    /// BCS-serialized type of a coin that the transaction took as input (including gas coins).
+    pub coin_type: Vec<u8>,
+    pub tx_sequence_number: i64,
+}
+
 #[derive(Insertable, Debug, Clone, FieldCount, Queryable)]
 #[diesel(table_name = tx_digests)]
 pub struct StoredTxDigest {
@@ -0,0 +1,536 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{num::NonZeroU32, ops::RangeInclusive, sync::Arc, time::Duration};
+
+use anyhow::{bail, ensure, Context};
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use futures::future;
+use prometheus::Registry;
+use sui_indexer_alt_framework::{
+    ingestion::{ClientArgs, IngestionConfig, IngestionService},
+    metrics::IndexerMetrics,
+    pipeline::{
+        concurrent::{max_chunk_rows, Handler},
+        Processor,
+    },
+    postgres::{Db, DbArgs},
+    types::full_checkpoint_content::CheckpointData,
+};
+use sui_indexer_alt_schema::{backfills::StoredBackfillShard, schema::backfill_shards, MIGRATIONS};
+use tokio::{
+    sync::Mutex,
+    time::{Interval, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::handlers::{
+    cp_sequence_numbers::CpSequenceNumbers, cp_signers::CpSigners, ev_emit_mod::EvEmitMod,
+    ev_struct_inst::EvStructInst, kv_checkpoints::KvCheckpoints, kv_epoch_ends::KvEpochEnds,
+    kv_epoch_starts::KvEpochStarts, kv_objects::KvObjects, kv_packages::KvPackages,
+    kv_transactions::KvTransactions, obj_versions::ObjVersions,
+    tx_affected_addresses::TxAffectedAddresses, tx_affected_objects::TxAffectedObjects,
+    tx_balance_changes::TxBalanceChanges, tx_calls::TxCalls, tx_coin_types::TxCoinTypes,
+    tx_digests::TxDigests, tx_kinds::TxKinds,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BackfillArgs {
+    /// The number of shards to split the range of checkpoints into. Shards are backfilled
+    /// concurrently, each one checkpoint at a time.
+    #[arg(long, default_value_t = 8)]
+    pub shards: usize,
+
+    /// The number of checkpoints a shard processes before writing their rows and recording its
+    /// progress. This is also the most work a shard repeats when the backfill is resumed.
+    #[arg(long, default_value_t = 100)]
+    pub checkpoints_per_commit: u64,
+
+    /// The most checkpoints to fetch from the checkpoint source per second, across all shards.
+    /// Unlimited if not set.
+    #[arg(long)]
+    pub max_checkpoints_per_second: Option<NonZeroU32>,
+}
+
+/// Where a backfill reads the checkpoints it processes from.
+#[async_trait::async_trait]
+pub trait CheckpointSource: Send + Sync {
+    async fn fetch(&self, checkpoint: u64) -> anyhow::Result<Arc<CheckpointData>>;
+}
+
+/// Fetches checkpoints through the framework's ingestion client, waiting for checkpoints that
+/// are not available yet.
+struct IngestionSource {
+    service: IngestionService,
+    retry_interval: Duration,
+}
+
+/// Spaces out fetches from the checkpoint source, so that all the shards of a backfill together
+/// fetch at most a given number of checkpoints per second.
+struct RateLimiter(Mutex<Interval>);
+
+#[async_trait::async_trait]
+impl CheckpointSource for IngestionSource {
+    async fn fetch(&self, checkpoint: u64) -> anyhow::Result<Arc<CheckpointData>> {
+        Ok(self
+            .service
+            .client()
+            .wait_for(checkpoint, self.retry_interval)
+            .await?)
+    }
+}
+
+impl RateLimiter {
+    fn new(per_second: NonZeroU32) -> Self {
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / per_second.get());
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self(Mutex::new(interval))
+    }
+
+    /// Wait until the next fetch is allowed.
+    async fn acquire(&self) {
+        self.0.lock().await.tick().await;
+    }
+}
+
+/// Backfill `pipeline` over `checkpoints`, reading checkpoints from the source configured by
+/// `client_args`. Only concurrent pipelines that do not depend on genesis data can be
+/// backfilled. The pipeline's watermark is not touched, so the backfill can run alongside an
+/// indexer that is running the same pipeline.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_backfill(
+    database_url: Url,
+    db_args: DbArgs,
+    client_args: ClientArgs,
+    ingestion_config: IngestionConfig,
+    pipeline: &str,
+    checkpoints: RangeInclusive<u64>,
+    args: BackfillArgs,
+    registry: &Registry,
+    cancel: CancellationToken,
+) -> anyhow::Result<()> {
+    let db = Db::for_write(database_url, db_args)
+        .await
+        .context("Failed to connect to database")?;
+
+    db.run_migrations(Some(&MIGRATIONS))
+        .await
+        .context("Failed to run pending migrations")?;
+
+    let retry_interval = ingestion_config.retry_interval();
+    let metrics = IndexerMetrics::new(None, registry);
+    let service = IngestionService::new(client_args, ingestion_config, metrics, cancel.clone())?;
+    let source = Arc::new(IngestionSource {
+        service,
+        retry_interval,
+    });
+
+    macro_rules! backfill_pipelines {
+        ($($handler:ident),* $(,)?) => {
+            $(
+                if pipeline == $handler::NAME {
+                    return backfill($handler, db, source, checkpoints, args, cancel).await;
+                }
+            )*
+        };
+    }
+
+    backfill_pipelines!(
+        CpSequenceNumbers,
+        CpSigners,
+        EvEmitMod,
+        EvStructInst,
+        KvCheckpoints,
+        KvEpochEnds,
+        KvEpochStarts,
+        KvObjects,
+        KvPackages,
+        KvTransactions,
+        ObjVersions,
+        TxAffectedAddresses,
+        TxAffectedObjects,
+        TxBalanceChanges,
+        TxCalls,
+        TxCoinTypes,
+        TxDigests,
+        TxKinds,
+    );
+
+    bail!("Pipeline {pipeline:?} cannot be backfilled");
+}
+
+/// Backfill the rows `handler` produces for `checkpoints`, read from `source`.
+///
+/// The range is split into shards that are backfilled concurrently, and each shard's progress is
+/// recorded in the `backfill_shards` table as it goes. If the same range of the same pipeline is
+/// backfilled again, e.g. after a crash, each shard resumes from where it left off, regardless of
+/// the number of shards requested this time. Shards write their rows before recording their
+/// progress, so a resumed shard may write some rows again, which concurrent pipelines tolerate.
+pub async fn backfill<H>(
+    handler: H,
+    db: Db,
+    source: Arc<dyn CheckpointSource>,
+    checkpoints: RangeInclusive<u64>,
+    args: BackfillArgs,
+    cancel: CancellationToken,
+) -> anyhow::Result<()>
+where
+    H: Handler<Store = Db> + Send + Sync + 'static,
+{
+    ensure!(
+        !checkpoints.is_empty(),
+        "No checkpoints to backfill in {checkpoints:?}"
+    );
+    ensure!(
+        args.checkpoints_per_commit > 0,
+        "Checkpoints per commit must be positive"
+    );
+
+    let shards = plan_shards::<H>(&db, &checkpoints, args.shards).await?;
+    info!(
+        pipeline = H::NAME,
+        first_checkpoint = checkpoints.start(),
+        last_checkpoint = checkpoints.end(),
+        shards = shards.len(),
+        "Starting backfill",
+    );
+
+    let handler = Arc::new(handler);
+    let limiter = args
+        .max_checkpoints_per_second
+        .map(|per_second| Arc::new(RateLimiter::new(per_second)));
+
+    let tasks = shards.into_iter().map(|shard| {
+        tokio::spawn(backfill_shard(
+            handler.clone(),
+            db.clone(),
+            source.clone(),
+            limiter.clone(),
+            shard,
+            args.checkpoints_per_commit,
+            cancel.clone(),
+        ))
+    });
+
+    // Shards that fail do not stop the others, so that as much progress as possible is recorded
+    // for the backfill to resume from.
+    for result in future::try_join_all(tasks)
+        .await
+        .context("Backfill shard panicked")?
+    {
+        result?;
+    }
+
+    info!(pipeline = H::NAME, "Backfill finished");
+    Ok(())
+}
+
+/// The shards to backfill `checkpoints` of pipeline `H` with. If the pipeline already has shards
+/// that exactly cover `checkpoints`, they are resumed. Otherwise, any shards of the pipeline within
+/// the range are replaced by `shards` new ones.
+async fn plan_shards<H: Processor>(
+    db: &Db,
+    checkpoints: &RangeInclusive<u64>,
+    shards: usize,
+) -> anyhow::Result<Vec<StoredBackfillShard>> {
+    let first = *checkpoints.start() as i64;
+    let last = *checkpoints.end() as i64;
+
+    let mut conn = db.connect().await?;
+    let existing: Vec<StoredBackfillShard> = backfill_shards::table
+        .select(StoredBackfillShard::as_select())
+        .filter(backfill_shards::pipeline.eq(H::NAME))
+        .filter(backfill_shards::first_checkpoint.between(first, last))
+        .order_by(backfill_shards::first_checkpoint)
+        .load(&mut conn)
+        .await
+        .context("Failed to read backfill shards")?;
+
+    if covers(&existing, first, last) {
+        info!(pipeline = H::NAME, "Resuming backfill");
+        return Ok(existing);
+    }
+
+    let planned: Vec<_> = split(checkpoints, shards)
+        .into_iter()
+        .map(|shard| StoredBackfillShard {
+            pipeline: H::NAME.to_owned(),
+            first_checkpoint: *shard.start() as i64,
+            last_checkpoint: *shard.end() as i64,
+            next_checkpoint: *shard.start() as i64,
+        })
+        .collect();
+
+    diesel::delete(
+        backfill_shards::table
+            .filter(backfill_shards::pipeline.eq(H::NAME))
+            .filter(backfill_shards::first_checkpoint.between(first, last)),
+    )
+    .execute(&mut conn)
+    .await
+    .context("Failed to clear backfill shards")?;
+
+    diesel::insert_into(backfill_shards::table)
+        .values(&planned)
+        .execute(&mut conn)
+        .await
+        .context("Failed to record backfill shards")?;
+
+    Ok(planned)
+}
+
+/// Backfill the checkpoints of `shard` that it has not backfilled yet, in order, recording its
+/// progress after every `checkpoints_per_commit` checkpoints.
+async fn backfill_shard<H>(
+    handler: Arc<H>,
+    db: Db,
+    source: Arc<dyn CheckpointSource>,
+    limiter: Option<Arc<RateLimiter>>,
+    shard: StoredBackfillShard,
+    checkpoints_per_commit: u64,
+    cancel: CancellationToken,
+) -> anyhow::Result<()>
+where
+    H: Handler<Store = Db>,
+{
+    let first = shard.first_checkpoint;
+    let last = shard.last_checkpoint as u64;
+    let mut next = shard.next_checkpoint as u64;
+
+    while next <= last {
+        if cancel.is_cancelled() {
+            bail!("Backfill of shard starting at {first} cancelled at checkpoint {next}");
+        }
+
+        let hi = last.min(next.saturating_add(checkpoints_per_commit - 1));
+        let mut values = vec![];
+        for checkpoint in next..=hi {
+            if let Some(limiter) = &limiter {
+                limiter.acquire().await;
+            }
+
+            let data = source
+                .fetch(checkpoint)
+                .await
+                .with_context(|| format!("Failed to fetch checkpoint {checkpoint}"))?;
+
+            values.extend(
+                handler
+                    .process(&data)
+                    .with_context(|| format!("Failed to process checkpoint {checkpoint}"))?,
+            );
+        }
+
+        let mut conn = db.connect().await?;
+        for chunk in values.chunks(max_chunk_rows::<H>()) {
+            H::commit(chunk, &mut conn)
+                .await
+                .with_context(|| format!("Failed to write checkpoints {next} to {hi}"))?;
+        }
+
+        diesel::update(backfill_shards::table)
+            .filter(backfill_shards::pipeline.eq(H::NAME))
+            .filter(backfill_shards::first_checkpoint.eq(first))
+            .set(backfill_shards::next_checkpoint.eq(hi as i64 + 1))
+            .execute(&mut conn)
+            .await
+            .context("Failed to record backfill progress")?;
+
+        debug!(
+            pipeline = H::NAME,
+            first_checkpoint = first,
+            checkpoint_hi_inclusive = hi,
+            rows = values.len(),
+            "Backfilled checkpoints",
+        );
+
+        next = hi + 1;
+    }
+
+    info!(
+        pipeline = H::NAME,
+        first_checkpoint = first,
+        last_checkpoint = last,
+        "Backfill shard finished",
+    );
+
+    Ok(())
+}
+
+/// Whether `shards` (ordered by their first checkpoint) cover `first..=last` exactly, without any
+/// gaps or overlaps.
+fn covers(shards: &[StoredBackfillShard], first: i64, last: i64) -> bool {
+    let mut next = first;
+    for shard in shards {
+        if shard.first_checkpoint != next || shard.last_checkpoint < shard.first_checkpoint {
+            return false;
+        }
+        next = shard.last_checkpoint + 1;
+    }
+
+    !shards.is_empty() && next == last + 1
+}
+
+/// Split `checkpoints` into at most `shards` contiguous ranges whose sizes differ by at most one.
+fn split(checkpoints: &RangeInclusive<u64>, shards: usize) -> Vec<RangeInclusive<u64>> {
+    let (first, last) = (*checkpoints.start(), *checkpoints.end());
+    let total = (last - first).saturating_add(1);
+    let shards = (shards.max(1) as u64).min(total);
+    let (size, extra) = (total / shards, total % shards);
+
+    let mut ranges = Vec::with_capacity(shards as usize);
+    let mut lo = first;
+    for i in 0..shards {
+        let len = size + u64::from(i < extra);
+        ranges.push(lo..=lo + (len - 1));
+        lo = lo.saturating_add(len);
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sui_indexer_alt_framework::{
+        types::test_checkpoint_data_builder::TestCheckpointDataBuilder, Indexer,
+    };
+    use sui_indexer_alt_schema::schema::tx_balance_changes;
+
+    use super::*;
+
+    struct MockSource(BTreeMap<u64, Arc<CheckpointData>>);
+
+    #[async_trait::async_trait]
+    impl CheckpointSource for MockSource {
+        async fn fetch(&self, checkpoint: u64) -> anyhow::Result<Arc<CheckpointData>> {
+            self.0
+                .get(&checkpoint)
+                .cloned()
+                .with_context(|| format!("Checkpoint {checkpoint} not found"))
+        }
+    }
+
+    /// Checkpoints 0 to 9, each with a single transaction.
+    fn checkpoints() -> BTreeMap<u64, Arc<CheckpointData>> {
+        let mut builder = TestCheckpointDataBuilder::new(0);
+        let mut checkpoints = BTreeMap::new();
+        for cp in 0..10 {
+            builder = builder.start_transaction(0).finish_transaction();
+            checkpoints.insert(cp, Arc::new(builder.build_checkpoint()));
+        }
+
+        checkpoints
+    }
+
+    async fn shards(db: &Db) -> Vec<(i64, i64, i64)> {
+        let mut conn = db.connect().await.unwrap();
+        backfill_shards::table
+            .select((
+                backfill_shards::first_checkpoint,
+                backfill_shards::last_checkpoint,
+                backfill_shards::next_checkpoint,
+            ))
+            .order_by(backfill_shards::first_checkpoint)
+            .load(&mut conn)
+            .await
+            .unwrap()
+    }
+
+    async fn balance_changes(db: &Db) -> Vec<i64> {
+        let mut conn = db.connect().await.unwrap();
+        tx_balance_changes::table
+            .select(tx_balance_changes::tx_sequence_number)
+            .order_by(tx_balance_changes::tx_sequence_number)
+            .load(&mut conn)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(&(0..=9), 3), vec![0..=3, 4..=6, 7..=9]);
+        assert_eq!(split(&(10..=13), 2), vec![10..=11, 12..=13]);
+
+        // There are never more shards than checkpoints.
+        assert_eq!(split(&(5..=6), 4), vec![5..=5, 6..=6]);
+        assert_eq!(split(&(5..=5), 0), vec![5..=5]);
+    }
+
+    #[tokio::test]
+    async fn test_backfill() {
+        let (indexer, _db) = Indexer::new_for_testing(&MIGRATIONS).await;
+        let db = indexer.store().clone();
+
+        backfill(
+            TxBalanceChanges,
+            db.clone(),
+            Arc::new(MockSource(checkpoints())),
+            0..=9,
+            BackfillArgs {
+                shards: 3,
+                checkpoints_per_commit: 2,
+                max_checkpoints_per_second: None,
+            },
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(balance_changes(&db).await, (0..10).collect::<Vec<_>>());
+        assert_eq!(shards(&db).await, vec![(0, 3, 4), (4, 6, 7), (7, 9, 10)]);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_resumes() {
+        let (indexer, _db) = Indexer::new_for_testing(&MIGRATIONS).await;
+        let db = indexer.store().clone();
+
+        // A previous backfill of the same range finished its first shard, and got part of the
+        // way through its second.
+        let mut conn = db.connect().await.unwrap();
+        diesel::insert_into(backfill_shards::table)
+            .values(&[
+                StoredBackfillShard {
+                    pipeline: TxBalanceChanges::NAME.to_owned(),
+                    first_checkpoint: 0,
+                    last_checkpoint: 4,
+                    next_checkpoint: 5,
+                },
+                StoredBackfillShard {
+                    pipeline: TxBalanceChanges::NAME.to_owned(),
+                    first_checkpoint: 5,
+                    last_checkpoint: 9,
+                    next_checkpoint: 8,
+                },
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        // Only the checkpoints the shards have yet to backfill are fetched, even though a
+        // different number of shards is requested.
+        let source = checkpoints().into_iter().filter(|(cp, _)| *cp >= 8);
+        backfill(
+            TxBalanceChanges,
+            db.clone(),
+            Arc::new(MockSource(source.collect())),
+            0..=9,
+            BackfillArgs {
+                shards: 4,
+                checkpoints_per_commit: 2,
+                max_checkpoints_per_second: None,
+            },
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(balance_changes(&db).await, vec![8, 9]);
+        assert_eq!(shards(&db).await, vec![(0, 4, 5), (5, 9, 10)]);
+    }
+}
@@ -14,6 +14,8 @@ use sui_indexer_alt_framework::{
     },
 };
 
+use crate::{exporter::ExporterConfig, supervisor::SupervisorConfig};
+
 /// Trait for merging configuration structs together.
 pub trait Merge: Sized {
     fn merge(self, other: Self) -> anyhow::Result<Self>;
@@ -39,6 +41,13 @@ pub struct IndexerConfig {
     /// Per-pipeline configurations.
     pub pipeline: PipelineLayer,
 
+    /// How pipelines are monitored for stalls, and backfilled when they stall.
+    pub supervisor: SupervisorLayer,
+
+    /// Where indexed checkpoints, transactions and balance changes are exported to, as they are
+    /// written. Exporting is disabled unless this section is present.
+    pub exporter: Option<ExporterLayer>,
+
     #[serde(flatten)]
     pub extra: toml::Table,
 }
@@ -98,7 +107,9 @@ pub struct CommitterLayer {
 pub struct PrunerLayer {
     pub interval_ms: Option<u64>,
     pub delay_ms: Option<u64>,
+    #[serde(alias = "retain_checkpoints")]
     pub retention: Option<u64>,
+    pub retain_epochs: Option<u64>,
     pub max_chunk_size: Option<u64>,
     pub prune_concurrency: Option<u64>,
 
@@ -106,6 +117,31 @@ pub struct PrunerLayer {
     pub extra: toml::Table,
 }
 
+#[DefaultConfig]
+#[derive(Clone, Default, Debug)]
+pub struct SupervisorLayer {
+    pub interval_ms: Option<u64>,
+    pub stall_threshold_ms: Option<u64>,
+    pub backfill_command: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+#[DefaultConfig]
+#[derive(Clone, Default, Debug)]
+pub struct ExporterLayer {
+    pub name: Option<String>,
+    pub nats_address: Option<String>,
+    pub subject_prefix: Option<String>,
+    pub interval_ms: Option<u64>,
+    pub max_batch_checkpoints: Option<u64>,
+    pub ack_timeout_ms: Option<u64>,
+
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
 #[DefaultConfig]
 #[derive(Clone, Default, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -117,6 +153,7 @@ pub struct PipelineLayer {
     pub coin_balance_buckets: Option<ConcurrentLayer>,
     pub obj_info: Option<ConcurrentLayer>,
     pub cp_sequence_numbers: Option<ConcurrentLayer>,
+    pub cp_signers: Option<ConcurrentLayer>,
     pub ev_emit_mod: Option<ConcurrentLayer>,
     pub ev_struct_inst: Option<ConcurrentLayer>,
     pub kv_checkpoints: Option<ConcurrentLayer>,
@@ -132,6 +169,7 @@ pub struct PipelineLayer {
     pub tx_affected_objects: Option<ConcurrentLayer>,
     pub tx_balance_changes: Option<ConcurrentLayer>,
     pub tx_calls: Option<ConcurrentLayer>,
+    pub tx_coin_types: Option<ConcurrentLayer>,
     pub tx_digests: Option<ConcurrentLayer>,
     pub tx_kinds: Option<ConcurrentLayer>,
 
@@ -149,6 +187,7 @@ impl IndexerConfig {
         example.committer = CommitterConfig::default().into();
         example.pruner = PrunerConfig::default().into();
         example.pipeline = PipelineLayer::example();
+        example.supervisor = SupervisorConfig::default().into();
 
         example
     }
@@ -252,12 +291,40 @@ impl PrunerLayer {
             interval_ms: self.interval_ms.unwrap_or(base.interval_ms),
             delay_ms: self.delay_ms.unwrap_or(base.delay_ms),
             retention: self.retention.unwrap_or(base.retention),
+            retain_epochs: self.retain_epochs.or(base.retain_epochs),
             max_chunk_size: self.max_chunk_size.unwrap_or(base.max_chunk_size),
             prune_concurrency: self.prune_concurrency.unwrap_or(base.prune_concurrency),
         })
     }
 }
 
+impl SupervisorLayer {
+    pub fn finish(self, base: SupervisorConfig) -> anyhow::Result<SupervisorConfig> {
+        check_extra("supervisor", self.extra)?;
+        Ok(SupervisorConfig {
+            interval_ms: self.interval_ms.unwrap_or(base.interval_ms),
+            stall_threshold_ms: self.stall_threshold_ms.unwrap_or(base.stall_threshold_ms),
+            backfill_command: self.backfill_command.unwrap_or(base.backfill_command),
+        })
+    }
+}
+
+impl ExporterLayer {
+    pub fn finish(self, base: ExporterConfig) -> anyhow::Result<ExporterConfig> {
+        check_extra("exporter", self.extra)?;
+        Ok(ExporterConfig {
+            name: self.name.unwrap_or(base.name),
+            nats_address: self.nats_address.unwrap_or(base.nats_address),
+            subject_prefix: self.subject_prefix.unwrap_or(base.subject_prefix),
+            interval_ms: self.interval_ms.unwrap_or(base.interval_ms),
+            max_batch_checkpoints: self
+                .max_batch_checkpoints
+                .unwrap_or(base.max_batch_checkpoints),
+            ack_timeout_ms: self.ack_timeout_ms.unwrap_or(base.ack_timeout_ms),
+        })
+    }
+}
+
 impl PipelineLayer {
     /// Generate an example configuration, suitable for demonstrating the fields available to
     /// configure.
@@ -267,6 +334,7 @@ impl PipelineLayer {
             obj_info: Some(Default::default()),
             sum_displays: Some(Default::default()),
             cp_sequence_numbers: Some(Default::default()),
+            cp_signers: Some(Default::default()),
             ev_emit_mod: Some(Default::default()),
             ev_struct_inst: Some(Default::default()),
             kv_checkpoints: Some(Default::default()),
@@ -282,6 +350,7 @@ impl PipelineLayer {
             tx_affected_objects: Some(Default::default()),
             tx_balance_changes: Some(Default::default()),
             tx_calls: Some(Default::default()),
+            tx_coin_types: Some(Default::default()),
             tx_digests: Some(Default::default()),
             tx_kinds: Some(Default::default()),
             extra: Default::default(),
@@ -303,6 +372,8 @@ impl Merge for IndexerConfig {
             committer: self.committer.merge(other.committer)?,
             pruner: self.pruner.merge(other.pruner)?,
             pipeline: self.pipeline.merge(other.pipeline)?,
+            supervisor: self.supervisor.merge(other.supervisor)?,
+            exporter: self.exporter.merge(other.exporter)?,
             extra: Default::default(),
         })
     }
@@ -359,8 +430,8 @@ impl Merge for CommitterLayer {
 }
 
 impl Merge for PrunerLayer {
-    /// Last write takes precedence for all fields except the `retention`, which takes the max of
-    /// all available values.
+    /// Last write takes precedence for all fields except the `retention` and `retain_epochs`, which
+    /// take the max of all available values.
     fn merge(self, other: PrunerLayer) -> anyhow::Result<PrunerLayer> {
         check_extra("pruner", self.extra)?;
         check_extra("pruner", other.extra)?;
@@ -372,6 +443,11 @@ impl Merge for PrunerLayer {
                 (Some(a), _) | (_, Some(a)) => Some(a),
                 (None, None) => None,
             },
+            retain_epochs: match (other.retain_epochs, self.retain_epochs) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (Some(a), _) | (_, Some(a)) => Some(a),
+                (None, None) => None,
+            },
             max_chunk_size: other.max_chunk_size.or(self.max_chunk_size),
             prune_concurrency: other.prune_concurrency.or(self.prune_concurrency),
             extra: Default::default(),
@@ -379,6 +455,35 @@ impl Merge for PrunerLayer {
     }
 }
 
+impl Merge for SupervisorLayer {
+    fn merge(self, other: SupervisorLayer) -> anyhow::Result<SupervisorLayer> {
+        check_extra("supervisor", self.extra)?;
+        check_extra("supervisor", other.extra)?;
+        Ok(SupervisorLayer {
+            interval_ms: other.interval_ms.or(self.interval_ms),
+            stall_threshold_ms: other.stall_threshold_ms.or(self.stall_threshold_ms),
+            backfill_command: other.backfill_command.or(self.backfill_command),
+            extra: Default::default(),
+        })
+    }
+}
+
+impl Merge for ExporterLayer {
+    fn merge(self, other: ExporterLayer) -> anyhow::Result<ExporterLayer> {
+        check_extra("exporter", self.extra)?;
+        check_extra("exporter", other.extra)?;
+        Ok(ExporterLayer {
+            name: other.name.or(self.name),
+            nats_address: other.nats_address.or(self.nats_address),
+            subject_prefix: other.subject_prefix.or(self.subject_prefix),
+            interval_ms: other.interval_ms.or(self.interval_ms),
+            max_batch_checkpoints: other.max_batch_checkpoints.or(self.max_batch_checkpoints),
+            ack_timeout_ms: other.ack_timeout_ms.or(self.ack_timeout_ms),
+            extra: Default::default(),
+        })
+    }
+}
+
 impl Merge for PipelineLayer {
     fn merge(self, other: PipelineLayer) -> anyhow::Result<PipelineLayer> {
         check_extra("pipeline", self.extra)?;
@@ -390,6 +495,7 @@ impl Merge for PipelineLayer {
             obj_info: self.obj_info.merge(other.obj_info)?,
             sum_displays: self.sum_displays.merge(other.sum_displays)?,
             cp_sequence_numbers: self.cp_sequence_numbers.merge(other.cp_sequence_numbers)?,
+            cp_signers: self.cp_signers.merge(other.cp_signers)?,
             ev_emit_mod: self.ev_emit_mod.merge(other.ev_emit_mod)?,
             ev_struct_inst: self.ev_struct_inst.merge(other.ev_struct_inst)?,
             kv_checkpoints: self.kv_checkpoints.merge(other.kv_checkpoints)?,
@@ -407,6 +513,7 @@ impl Merge for PipelineLayer {
             tx_affected_objects: self.tx_affected_objects.merge(other.tx_affected_objects)?,
             tx_balance_changes: self.tx_balance_changes.merge(other.tx_balance_changes)?,
             tx_calls: self.tx_calls.merge(other.tx_calls)?,
+            tx_coin_types: self.tx_coin_types.merge(other.tx_coin_types)?,
             tx_digests: self.tx_digests.merge(other.tx_digests)?,
             tx_kinds: self.tx_kinds.merge(other.tx_kinds)?,
             extra: Default::default(),
@@ -472,6 +579,7 @@ impl From<PrunerConfig> for PrunerLayer {
             interval_ms: Some(config.interval_ms),
             delay_ms: Some(config.delay_ms),
             retention: Some(config.retention),
+            retain_epochs: config.retain_epochs,
             max_chunk_size: Some(config.max_chunk_size),
             prune_concurrency: Some(config.prune_concurrency),
             extra: Default::default(),
@@ -479,6 +587,31 @@ impl From<PrunerConfig> for PrunerLayer {
     }
 }
 
+impl From<SupervisorConfig> for SupervisorLayer {
+    fn from(config: SupervisorConfig) -> Self {
+        Self {
+            interval_ms: Some(config.interval_ms),
+            stall_threshold_ms: Some(config.stall_threshold_ms),
+            backfill_command: Some(config.backfill_command),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<ExporterConfig> for ExporterLayer {
+    fn from(config: ExporterConfig) -> Self {
+        Self {
+            name: Some(config.name),
+            nats_address: Some(config.nats_address),
+            subject_prefix: Some(config.subject_prefix),
+            interval_ms: Some(config.interval_ms),
+            max_batch_checkpoints: Some(config.max_batch_checkpoints),
+            ack_timeout_ms: Some(config.ack_timeout_ms),
+            extra: Default::default(),
+        }
+    }
+}
+
 /// Check whether there are any unrecognized extra fields and if so, warn about them.
 fn check_extra(pos: &str, extra: toml::Table) -> anyhow::Result<()> {
     ensure!(
@@ -611,6 +744,7 @@ mod tests {
             interval_ms: None,
             delay_ms: Some(100),
             retention: Some(200),
+            retain_epochs: None,
             max_chunk_size: Some(300),
             prune_concurrency: Some(1),
             extra: Default::default(),
@@ -620,6 +754,7 @@ mod tests {
             interval_ms: Some(400),
             delay_ms: None,
             retention: Some(500),
+            retain_epochs: Some(2),
             max_chunk_size: Some(600),
             prune_concurrency: Some(2),
             extra: Default::default(),
@@ -634,6 +769,7 @@ mod tests {
                 interval_ms: Some(400),
                 delay_ms: Some(100),
                 retention: Some(500),
+                retain_epochs: Some(2),
                 max_chunk_size: Some(600),
                 prune_concurrency: Some(2),
                 extra: _,
@@ -646,6 +782,7 @@ mod tests {
                 interval_ms: Some(400),
                 delay_ms: Some(100),
                 retention: Some(500),
+                retain_epochs: Some(2),
                 max_chunk_size: Some(300),
                 prune_concurrency: Some(1),
                 extra: _,
@@ -734,6 +871,7 @@ mod tests {
                 interval_ms: 100,
                 delay_ms: 200,
                 retention: 300,
+                retain_epochs: None,
                 max_chunk_size: 400,
                 prune_concurrency: 1,
             }),
@@ -751,6 +889,7 @@ mod tests {
                     interval_ms: 1000,
                     delay_ms: 200,
                     retention: 300,
+                    retain_epochs: None,
                     max_chunk_size: 400,
                     prune_concurrency: 1,
                 }),
@@ -758,6 +897,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn per_pipeline_retention() {
+        let config: IndexerConfig = toml::from_str(
+            r#"
+            [pipeline.tx_digests.pruner]
+            retain_checkpoints = 1000
+
+            [pipeline.tx_calls.pruner]
+            retain_epochs = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_matches!(
+            config.pipeline.tx_digests,
+            Some(ConcurrentLayer {
+                pruner: Some(PrunerLayer {
+                    retention: Some(1000),
+                    retain_epochs: None,
+                    ..
+                }),
+                ..
+            }),
+        );
+
+        assert_matches!(
+            config.pipeline.tx_calls,
+            Some(ConcurrentLayer {
+                pruner: Some(PrunerLayer {
+                    retention: None,
+                    retain_epochs: Some(2),
+                    ..
+                }),
+                ..
+            }),
+        );
+
+        // Pipelines without a pruner retain everything.
+        assert_matches!(config.pipeline.tx_balance_changes, None);
+    }
+
     #[test]
     fn detect_unrecognized_fields() {
         let config: IndexerConfig = toml::from_str(
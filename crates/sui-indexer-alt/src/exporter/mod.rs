@@ -0,0 +1,523 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{ensure, Context};
+use diesel::{dsl::min, upsert::excluded, ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, IntCounter, IntCounterVec, IntGauge, Registry,
+};
+use sui_indexer_alt_framework::{
+    postgres::{Connection, Db},
+    types::{
+        digests::TransactionDigest,
+        effects::{TransactionEffects, TransactionEffectsAPI},
+        message_envelope::Message as _,
+        messages_checkpoint::{CheckpointContents, CheckpointSummary},
+        transaction::{TransactionData, TransactionDataAPI},
+    },
+};
+use sui_indexer_alt_schema::{
+    checkpoints::StoredCheckpoint,
+    exports::StoredExportWatermark,
+    schema::{export_watermarks, kv_checkpoints, kv_transactions, tx_balance_changes, watermarks},
+    transactions::{BalanceChange, StoredTransaction, StoredTxBalanceChange},
+};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use self::{
+    nats::{Message, Publisher},
+    records::{BalanceChangeRecord, CheckpointRecord, Record, TransactionRecord},
+};
+
+mod nats;
+mod records;
+
+/// The pipelines whose tables the exporter reads from. A checkpoint is exported once it is below
+/// the watermarks of all of them.
+const SOURCE_PIPELINES: [&str; 3] = ["kv_checkpoints", "kv_transactions", "tx_balance_changes"];
+
+#[derive(Clone, Debug)]
+pub struct ExporterConfig {
+    /// The name the exporter records its progress under. Exporters with different names export
+    /// independently of each other.
+    pub name: String,
+
+    /// The address of the NATS server to publish to, as `host:port`.
+    pub nats_address: String,
+
+    /// Prefix of the subjects that records are published to: `<prefix>.checkpoints`,
+    /// `<prefix>.transactions` and `<prefix>.balance_changes`. A JetStream stream must capture
+    /// these subjects, to acknowledge the records published to them.
+    pub subject_prefix: String,
+
+    /// How often to check for newly indexed checkpoints to export, in milliseconds.
+    pub interval_ms: u64,
+
+    /// The maximum number of checkpoints to export in a single batch. Progress is recorded after
+    /// each batch is acknowledged.
+    pub max_batch_checkpoints: u64,
+
+    /// How long to wait for a batch to be acknowledged before retrying it, in milliseconds.
+    pub ack_timeout_ms: u64,
+}
+
+/// Background task that publishes the checkpoints, transactions and balance changes that the
+/// indexer has written, to NATS JetStream, once they have passed the watermarks of the pipelines
+/// that write them, so that downstream data platforms can consume them without polling the
+/// database.
+///
+/// Delivery is at-least-once: the last exported checkpoint is recorded in the `export_watermarks`
+/// table only after every record from the checkpoints up to it has been acknowledged by the
+/// stream, and export resumes after the recorded checkpoint when the exporter restarts, or a
+/// batch fails. Records are published with IDs that JetStream uses to de-duplicate them within
+/// its duplicate window.
+pub struct Exporter {
+    db: Db,
+    config: ExporterConfig,
+    metrics: Arc<ExporterMetrics>,
+    cancel: CancellationToken,
+}
+
+struct ExporterMetrics {
+    exported_checkpoint_hi: IntGauge,
+    exported_records: IntCounterVec,
+    export_failures: IntCounter,
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        Self {
+            name: "nats".to_owned(),
+            nats_address: "localhost:4222".to_owned(),
+            subject_prefix: "sui".to_owned(),
+            interval_ms: 1_000,
+            max_batch_checkpoints: 100,
+            ack_timeout_ms: 10_000,
+        }
+    }
+}
+
+impl Exporter {
+    pub fn new(
+        db: Db,
+        config: ExporterConfig,
+        registry: &Registry,
+        cancel: CancellationToken,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            metrics: Arc::new(ExporterMetrics::new(registry)),
+            cancel,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let Self {
+                db,
+                config,
+                metrics,
+                cancel,
+            } = self;
+
+            let mut publisher = None;
+            let mut interval = tokio::time::interval(Duration::from_millis(config.interval_ms));
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        info!("Shutdown received, stopping exporter");
+                        break;
+                    }
+
+                    _ = interval.tick() => {
+                        if let Err(e) =
+                            export(&db, &config, &metrics, &mut publisher, &cancel).await
+                        {
+                            metrics.export_failures.inc();
+                            warn!(exporter = config.name, "Export failed: {e:#}");
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl ExporterMetrics {
+    fn new(registry: &Registry) -> Self {
+        Self {
+            exported_checkpoint_hi: register_int_gauge_with_registry!(
+                "indexer_exporter_checkpoint_hi_inclusive",
+                "The last checkpoint whose records have been exported and acknowledged",
+                registry,
+            )
+            .unwrap(),
+
+            exported_records: register_int_counter_vec_with_registry!(
+                "indexer_exporter_records",
+                "Number of records exported, by topic",
+                &["topic"],
+                registry,
+            )
+            .unwrap(),
+
+            export_failures: register_int_counter_with_registry!(
+                "indexer_exporter_failures",
+                "Number of times exporting a batch of checkpoints failed, and will be retried",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Export every checkpoint that has passed the source pipelines' watermarks since the last export,
+/// in batches. The connection to NATS in `publisher` is reused between calls, but is dropped if a
+/// batch fails, so that the next attempt starts from a fresh connection.
+async fn export(
+    db: &Db,
+    config: &ExporterConfig,
+    metrics: &ExporterMetrics,
+    publisher: &mut Option<Publisher>,
+    cancel: &CancellationToken,
+) -> anyhow::Result<()> {
+    let mut conn = db.connect().await?;
+
+    let Some(source_hi) = source_hi(&mut conn).await? else {
+        return Ok(());
+    };
+
+    let exported_hi: Option<i64> = export_watermarks::table
+        .select(export_watermarks::checkpoint_hi_inclusive)
+        .filter(export_watermarks::exporter.eq(&config.name))
+        .first(&mut conn)
+        .await
+        .optional()
+        .context("Failed to read export watermark")?;
+
+    // An exporter that has not exported anything yet starts from the earliest indexed checkpoint.
+    let mut next = match exported_hi {
+        Some(hi) => hi + 1,
+        None => {
+            let first: Option<i64> = kv_checkpoints::table
+                .select(min(kv_checkpoints::sequence_number))
+                .first(&mut conn)
+                .await
+                .context("Failed to read first checkpoint")?;
+
+            let Some(first) = first else {
+                return Ok(());
+            };
+
+            first
+        }
+    };
+
+    while next <= source_hi && !cancel.is_cancelled() {
+        let hi = source_hi.min(next + config.max_batch_checkpoints.max(1) as i64 - 1);
+        let messages = load_messages(&mut conn, &config.subject_prefix, next..=hi).await?;
+
+        let mut connected = match publisher.take() {
+            Some(publisher) => publisher,
+            None => {
+                let ack_timeout = Duration::from_millis(config.ack_timeout_ms);
+                Publisher::connect(&config.nats_address, ack_timeout).await?
+            }
+        };
+
+        connected.publish(&messages).await?;
+        *publisher = Some(connected);
+
+        diesel::insert_into(export_watermarks::table)
+            .values(StoredExportWatermark {
+                exporter: config.name.clone(),
+                checkpoint_hi_inclusive: hi,
+            })
+            .on_conflict(export_watermarks::exporter)
+            .do_update()
+            .set(
+                export_watermarks::checkpoint_hi_inclusive
+                    .eq(excluded(export_watermarks::checkpoint_hi_inclusive)),
+            )
+            .execute(&mut conn)
+            .await
+            .context("Failed to record export watermark")?;
+
+        metrics.exported_checkpoint_hi.set(hi);
+        for message in &messages {
+            let topic = message.subject.rsplit('.').next().unwrap_or_default();
+            metrics.exported_records.with_label_values(&[topic]).inc();
+        }
+
+        next = hi + 1;
+    }
+
+    Ok(())
+}
+
+/// The highest checkpoint that all the source pipelines have written, or `None` if any of them has
+/// not written anything yet.
+async fn source_hi(conn: &mut Connection<'_>) -> anyhow::Result<Option<i64>> {
+    let rows: Vec<i64> = watermarks::table
+        .select(watermarks::checkpoint_hi_inclusive)
+        .filter(watermarks::pipeline.eq_any(SOURCE_PIPELINES))
+        .load(conn)
+        .await
+        .context("Failed to read watermarks")?;
+
+    Ok(if rows.len() == SOURCE_PIPELINES.len() {
+        rows.into_iter().min()
+    } else {
+        None
+    })
+}
+
+/// Load the data for the checkpoints in `cp_range`, and convert it into the messages that export
+/// it, with subjects under `prefix`.
+async fn load_messages(
+    conn: &mut Connection<'_>,
+    prefix: &str,
+    cp_range: RangeInclusive<i64>,
+) -> anyhow::Result<Vec<Message>> {
+    let (cp_lo, cp_hi) = cp_range.into_inner();
+
+    let checkpoints: Vec<StoredCheckpoint> = kv_checkpoints::table
+        .filter(kv_checkpoints::sequence_number.between(cp_lo, cp_hi))
+        .order(kv_checkpoints::sequence_number)
+        .load(conn)
+        .await
+        .context("Failed to load checkpoints")?;
+
+    ensure!(
+        checkpoints.len() as i64 == cp_hi - cp_lo + 1,
+        "Expected {} checkpoints from {cp_lo} to {cp_hi}, found {}",
+        cp_hi - cp_lo + 1,
+        checkpoints.len(),
+    );
+
+    let transactions: Vec<StoredTransaction> = kv_transactions::table
+        .filter(kv_transactions::cp_sequence_number.between(cp_lo, cp_hi))
+        .load(conn)
+        .await
+        .context("Failed to load transactions")?;
+
+    let checkpoints = checkpoints
+        .into_iter()
+        .map(|c| {
+            let summary: CheckpointSummary = bcs::from_bytes(&c.checkpoint_summary)
+                .context("Failed to deserialize checkpoint summary")?;
+            let contents: CheckpointContents = bcs::from_bytes(&c.checkpoint_contents)
+                .context("Failed to deserialize checkpoint contents")?;
+            Ok((summary, contents))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Checkpoints are contiguous, so their transactions are too.
+    let (first, _) = &checkpoints[0];
+    let (last, _) = &checkpoints[checkpoints.len() - 1];
+    let tx_lo = (first.network_total_transactions - checkpoints[0].1.size() as u64) as i64;
+    let tx_hi = last.network_total_transactions as i64;
+
+    let balance_changes: Vec<StoredTxBalanceChange> = tx_balance_changes::table
+        .filter(tx_balance_changes::tx_sequence_number.between(tx_lo, tx_hi - 1))
+        .load(conn)
+        .await
+        .context("Failed to load balance changes")?;
+
+    messages(prefix, checkpoints, transactions, balance_changes)
+}
+
+/// Convert the data for a range of checkpoints into messages: for each checkpoint, a checkpoint
+/// record, followed by a record for each of its transactions, in order, each followed by the
+/// records for its balance changes.
+fn messages(
+    prefix: &str,
+    checkpoints: Vec<(CheckpointSummary, CheckpointContents)>,
+    transactions: Vec<StoredTransaction>,
+    balance_changes: Vec<StoredTxBalanceChange>,
+) -> anyhow::Result<Vec<Message>> {
+    let mut transactions: HashMap<TransactionDigest, StoredTransaction> = transactions
+        .into_iter()
+        .map(|tx| {
+            let digest = TransactionDigest::try_from(tx.tx_digest.clone())
+                .context("Failed to deserialize transaction digest")?;
+            Ok((digest, tx))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    let mut balance_changes: BTreeMap<i64, Vec<u8>> = balance_changes
+        .into_iter()
+        .map(|b| (b.tx_sequence_number, b.balance_changes))
+        .collect();
+
+    let mut messages = vec![];
+    for (summary, contents) in checkpoints {
+        let checkpoint = summary.sequence_number;
+        messages.push(
+            CheckpointRecord {
+                sequence_number: checkpoint,
+                digest: summary.digest().to_string(),
+                previous_digest: summary.previous_digest.map(|d| d.to_string()),
+                epoch: summary.epoch,
+                timestamp_ms: summary.timestamp_ms,
+                transactions: contents.size() as u64,
+                network_total_transactions: summary.network_total_transactions,
+            }
+            .message(prefix)?,
+        );
+
+        let tx_lo = summary.network_total_transactions - contents.size() as u64;
+        for (i, digests) in contents.iter().enumerate() {
+            let tx_sequence_number = tx_lo + i as u64;
+            let digest = digests.transaction;
+            let tx = transactions
+                .remove(&digest)
+                .with_context(|| format!("Transaction {digest} (cp {checkpoint}) not found"))?;
+
+            let data: TransactionData = bcs::from_bytes(&tx.raw_transaction)
+                .with_context(|| format!("Failed to deserialize transaction {digest}"))?;
+            let effects: TransactionEffects = bcs::from_bytes(&tx.raw_effects)
+                .with_context(|| format!("Failed to deserialize effects of {digest}"))?;
+            let gas = effects.gas_cost_summary();
+
+            messages.push(
+                TransactionRecord {
+                    digest: digest.to_string(),
+                    tx_sequence_number,
+                    checkpoint,
+                    epoch: effects.executed_epoch(),
+                    timestamp_ms: tx.timestamp_ms as u64,
+                    sender: data.sender(),
+                    success: effects.status().is_ok(),
+                    computation_cost: gas.computation_cost,
+                    storage_cost: gas.storage_cost,
+                    storage_rebate: gas.storage_rebate,
+                    non_refundable_storage_fee: gas.non_refundable_storage_fee,
+                }
+                .message(prefix)?,
+            );
+
+            let Some(changes) = balance_changes.remove(&(tx_sequence_number as i64)) else {
+                continue;
+            };
+
+            let changes: Vec<BalanceChange> = bcs::from_bytes(&changes)
+                .with_context(|| format!("Failed to deserialize balance changes of {digest}"))?;
+
+            for (index, change) in changes.into_iter().enumerate() {
+                let BalanceChange::V1 {
+                    owner,
+                    coin_type,
+                    amount,
+                } = change;
+
+                messages.push(
+                    BalanceChangeRecord {
+                        tx_digest: digest.to_string(),
+                        tx_sequence_number,
+                        checkpoint,
+                        index: index as u64,
+                        owner,
+                        coin_type,
+                        amount: amount.to_string(),
+                    }
+                    .message(prefix)?,
+                );
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_alt_framework::{
+        pipeline::Processor, types::test_checkpoint_data_builder::TestCheckpointDataBuilder,
+    };
+
+    use crate::handlers::{
+        kv_checkpoints::KvCheckpoints, kv_transactions::KvTransactions,
+        tx_balance_changes::TxBalanceChanges,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let mut builder = TestCheckpointDataBuilder::new(0);
+        builder = builder
+            .start_transaction(0)
+            .create_owned_object(0)
+            .finish_transaction();
+        let cp0 = Arc::new(builder.build_checkpoint());
+
+        builder = builder
+            .start_transaction(1)
+            .transfer_object(0, 1)
+            .finish_transaction()
+            .start_transaction(1)
+            .create_owned_object(1)
+            .finish_transaction();
+        let cp1 = Arc::new(builder.build_checkpoint());
+
+        let mut checkpoints = vec![];
+        let mut transactions = vec![];
+        let mut balance_changes = vec![];
+        for cp in [&cp0, &cp1] {
+            for c in KvCheckpoints.process(cp).unwrap() {
+                checkpoints.push((
+                    bcs::from_bytes(&c.checkpoint_summary).unwrap(),
+                    bcs::from_bytes(&c.checkpoint_contents).unwrap(),
+                ));
+            }
+
+            transactions.extend(KvTransactions.process(cp).unwrap());
+            balance_changes.extend(TxBalanceChanges.process(cp).unwrap());
+        }
+
+        let messages = messages("sui", checkpoints, transactions, balance_changes).unwrap();
+        let subjects: Vec<_> = messages
+            .iter()
+            .filter(|m| m.subject != "sui.balance_changes")
+            .map(|m| (m.subject.as_str(), m.schema))
+            .collect();
+
+        assert_eq!(
+            subjects,
+            vec![
+                ("sui.checkpoints", CheckpointRecord::SCHEMA),
+                ("sui.transactions", TransactionRecord::SCHEMA),
+                ("sui.checkpoints", CheckpointRecord::SCHEMA),
+                ("sui.transactions", TransactionRecord::SCHEMA),
+                ("sui.transactions", TransactionRecord::SCHEMA),
+            ],
+        );
+
+        // Checkpoints are identified by their sequence number, and transactions by their digest.
+        assert_eq!(messages[0].id, "0");
+        let tx = &cp1.transactions[1];
+        let record = messages
+            .iter()
+            .find(|m| m.id == tx.transaction.digest().to_string())
+            .unwrap();
+
+        let record: serde_json::Value = serde_json::from_slice(&record.payload).unwrap();
+        assert_eq!(record["checkpoint"], 1);
+        assert_eq!(record["tx_sequence_number"], 2);
+        assert_eq!(record["success"], true);
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal client for publishing to NATS JetStream. It implements just enough of the NATS
+//! protocol (https://docs.nats.io/reference/reference-protocols/nats-protocol) to publish messages
+//! with headers, and wait for the stream that captures them to acknowledge them.
+//!
+//! Each message is published with a reply subject in an inbox that the client subscribes to.
+//! JetStream replies to that subject once the message has been persisted (or with an error if it
+//! could not be), which is what makes delivery at-least-once: a batch of messages is only
+//! considered published once every message in it has been acknowledged.
+
+use std::{
+    collections::BTreeSet,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, ensure, Context};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+};
+
+/// Options sent to the server on connection. Headers are required to set message IDs, and
+/// `no_responders` makes the server reply immediately when no stream captures a subject, rather
+/// than leaving the publish to time out.
+const CONNECT: &str = r#"CONNECT {"verbose":false,"pedantic":false,"headers":true,"no_responders":true,"lang":"rust","name":"sui-indexer-alt"}"#;
+
+/// The subscription ID of the client's inbox.
+const INBOX_SID: &str = "1";
+
+/// A message to publish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Message {
+    pub subject: String,
+
+    /// Identifies the message, so that JetStream can de-duplicate it if it is published more than
+    /// once (within the stream's duplicate window).
+    pub id: String,
+
+    /// The schema of the payload, sent as the `Sui-Schema` header.
+    pub schema: &'static str,
+
+    pub payload: Vec<u8>,
+}
+
+pub(crate) struct Publisher {
+    reader: BufReader<OwnedReadHalf>,
+    writer: BufWriter<OwnedWriteHalf>,
+
+    /// Prefix of the reply subjects that acknowledgements are sent to.
+    inbox: String,
+
+    /// Suffix of the reply subject for the next message, to match acknowledgements to messages.
+    next_reply: u64,
+
+    ack_timeout: Duration,
+}
+
+/// JetStream's reply to a publish.
+#[derive(Deserialize)]
+struct PubAck {
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: u16,
+    description: String,
+}
+
+impl Publisher {
+    /// Connect to the NATS server at `address` (`host:port`). Publishes fail if they are not
+    /// acknowledged within `ack_timeout`.
+    pub(crate) async fn connect(address: &str, ack_timeout: Duration) -> anyhow::Result<Self> {
+        let stream = TcpStream::connect(address)
+            .await
+            .with_context(|| format!("Failed to connect to NATS server at {address}"))?;
+
+        let (reader, writer) = stream.into_split();
+        let mut publisher = Self {
+            reader: BufReader::new(reader),
+            writer: BufWriter::new(writer),
+            inbox: format!(
+                "_INBOX.sui-indexer-alt.{}.{}",
+                std::process::id(),
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos()),
+            ),
+            next_reply: 0,
+            ack_timeout,
+        };
+
+        let info = publisher.read_line().await?;
+        ensure!(
+            info.starts_with("INFO "),
+            "Expected INFO from NATS server, got: {info}"
+        );
+
+        // The server replies to the PING with a PONG once it has processed the CONNECT, or with
+        // an error if the connection was rejected.
+        let sub = format!("SUB {}.* {INBOX_SID}", publisher.inbox);
+        publisher.write_line(CONNECT).await?;
+        publisher.write_line("PING").await?;
+        publisher.write_line(&sub).await?;
+        publisher.writer.flush().await?;
+
+        loop {
+            match publisher.read_line().await?.as_str() {
+                "PONG" => break,
+                "+OK" => continue,
+                "PING" => publisher.pong().await?,
+                line if line.starts_with("-ERR") => {
+                    bail!("NATS server rejected connection: {line}")
+                }
+                line => bail!("Unexpected message from NATS server: {line}"),
+            }
+        }
+
+        Ok(publisher)
+    }
+
+    /// Publish `messages` in order, and wait until every one of them has been acknowledged.
+    pub(crate) async fn publish(&mut self, messages: &[Message]) -> anyhow::Result<()> {
+        let mut pending = BTreeSet::new();
+        for message in messages {
+            let reply = self.next_reply;
+            self.next_reply += 1;
+
+            let headers = format!(
+                "NATS/1.0\r\nNats-Msg-Id: {}\r\nSui-Schema: {}\r\n\r\n",
+                message.id, message.schema,
+            );
+
+            let hpub = format!(
+                "HPUB {} {}.{reply} {} {}",
+                message.subject,
+                self.inbox,
+                headers.len(),
+                headers.len() + message.payload.len(),
+            );
+
+            self.write_line(&hpub).await?;
+            self.writer.write_all(headers.as_bytes()).await?;
+            self.writer.write_all(&message.payload).await?;
+            self.writer.write_all(b"\r\n").await?;
+            pending.insert(reply);
+        }
+
+        self.writer.flush().await?;
+
+        let timeout = self.ack_timeout;
+        let acked = tokio::time::timeout(timeout, self.wait_for_acks(&mut pending)).await;
+        acked.with_context(|| {
+            format!(
+                "Timed out waiting for {} of {} messages to be acknowledged",
+                pending.len(),
+                messages.len(),
+            )
+        })?
+    }
+
+    /// Read from the server until all the `pending` replies have been acknowledged.
+    async fn wait_for_acks(&mut self, pending: &mut BTreeSet<u64>) -> anyhow::Result<()> {
+        while !pending.is_empty() {
+            let line = self.read_line().await?;
+            let mut parts = line.split_ascii_whitespace();
+            match parts.next() {
+                Some("PING") => self.pong().await?,
+                Some("+OK") => {}
+                Some("-ERR") => bail!("NATS server error: {line}"),
+
+                // MSG <subject> <sid> <size>
+                Some("MSG") => {
+                    let (subject, size) = match (parts.next(), parts.next(), parts.next()) {
+                        (Some(subject), Some(_sid), Some(size)) => (subject, size),
+                        _ => bail!("Malformed MSG from NATS server: {line}"),
+                    };
+
+                    let payload = self.read_payload(size).await?;
+                    let reply = self.reply(subject)?;
+                    let ack: PubAck = serde_json::from_slice(&payload)
+                        .context("Failed to parse acknowledgement")?;
+
+                    if let Some(ApiError { code, description }) = ack.error {
+                        bail!("Failed to publish message: {description} ({code})");
+                    }
+
+                    pending.remove(&reply);
+                }
+
+                // HMSG <subject> <sid> <header size> <total size>. JetStream only replies with
+                // headers when there is no payload to acknowledge, e.g. when no stream captures the
+                // subject, which is reported as a status in the header.
+                Some("HMSG") => {
+                    let (subject, size) =
+                        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                            (Some(subject), Some(_sid), Some(_), Some(size)) => (subject, size),
+                            _ => bail!("Malformed HMSG from NATS server: {line}"),
+                        };
+
+                    let message = self.read_payload(size).await?;
+                    let status = String::from_utf8_lossy(&message);
+                    let status = status.lines().next().unwrap_or_default();
+                    bail!(
+                        "Failed to publish message {}: {status}",
+                        self.reply(subject)?
+                    );
+                }
+
+                _ => bail!("Unexpected message from NATS server: {line}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The reply number of a reply `subject` in this client's inbox.
+    fn reply(&self, subject: &str) -> anyhow::Result<u64> {
+        subject
+            .strip_prefix(&self.inbox)
+            .and_then(|s| s.strip_prefix('.'))
+            .and_then(|s| s.parse().ok())
+            .with_context(|| format!("Unexpected reply subject: {subject}"))
+    }
+
+    async fn pong(&mut self) -> anyhow::Result<()> {
+        self.write_line("PONG").await?;
+        Ok(self.writer.flush().await?)
+    }
+
+    async fn read_line(&mut self) -> anyhow::Result<String> {
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line).await?;
+        ensure!(read > 0, "NATS server closed the connection");
+        Ok(line.trim_end().to_owned())
+    }
+
+    /// Read a payload of `size` bytes, followed by its terminating CRLF.
+    async fn read_payload(&mut self, size: &str) -> anyhow::Result<Vec<u8>> {
+        let size: usize = size
+            .parse()
+            .with_context(|| format!("Invalid payload size: {size}"))?;
+
+        let mut payload = vec![0u8; size + 2];
+        self.reader.read_exact(&mut payload).await?;
+        payload.truncate(size);
+        Ok(payload)
+    }
+
+    async fn write_line(&mut self, line: &str) -> anyhow::Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        Ok(self.writer.write_all(b"\r\n").await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{net::TcpListener, task::JoinHandle};
+
+    use super::*;
+
+    /// A fake NATS server that accepts a single connection, and acknowledges the messages
+    /// published to it with `ack`, returning the headers and payloads it received.
+    async fn server(
+        ack: &'static str,
+    ) -> (String, JoinHandle<anyhow::Result<Vec<(String, String)>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            writer.write_all(b"INFO {\"headers\":true}\r\n").await?;
+
+            let mut received = vec![];
+            let mut line = String::new();
+            while reader.read_line(&mut line).await? > 0 {
+                let parts: Vec<_> = line.split_ascii_whitespace().map(String::from).collect();
+                line.clear();
+
+                match parts.first().map(String::as_str) {
+                    Some("PING") => writer.write_all(b"PONG\r\n").await?,
+
+                    Some("HPUB") => {
+                        let (reply, hdr_len, len) =
+                            (&parts[2], parts[3].parse::<usize>()?, parts[4].parse()?);
+                        let mut message = vec![0u8; len + 2];
+                        reader.read_exact(&mut message).await?;
+
+                        let headers = String::from_utf8(message[..hdr_len].to_vec())?;
+                        let payload = String::from_utf8(message[hdr_len..len].to_vec())?;
+                        received.push((headers, payload));
+
+                        let msg = format!("MSG {reply} 1 {}\r\n{ack}\r\n", ack.len());
+                        writer.write_all(msg.as_bytes()).await?;
+                    }
+
+                    _ => {}
+                }
+            }
+
+            Ok::<_, anyhow::Error>(received)
+        });
+
+        (address, handle)
+    }
+
+    fn message(id: &str, payload: &str) -> Message {
+        Message {
+            subject: "sui.checkpoints".to_owned(),
+            id: id.to_owned(),
+            schema: "sui.checkpoint.v1",
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_acknowledged() {
+        let (address, server) = server(r#"{"stream":"SUI","seq":1}"#).await;
+        let mut publisher = Publisher::connect(&address, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        publisher
+            .publish(&[message("0", r#"{"a":1}"#), message("1", r#"{"b":2}"#)])
+            .await
+            .unwrap();
+
+        drop(publisher);
+        let received = server.await.unwrap().unwrap();
+        assert_eq!(received.len(), 2);
+        assert!(received[0].0.contains("Nats-Msg-Id: 0\r\n"));
+        assert!(received[0].0.contains("Sui-Schema: sui.checkpoint.v1\r\n"));
+        assert_eq!(received[0].1, r#"{"a":1}"#);
+        assert!(received[1].0.contains("Nats-Msg-Id: 1\r\n"));
+        assert_eq!(received[1].1, r#"{"b":2}"#);
+    }
+
+    #[tokio::test]
+    async fn test_publish_rejected() {
+        let (address, _server) =
+            server(r#"{"error":{"code":503,"description":"stream offline"}}"#).await;
+        let mut publisher = Publisher::connect(&address, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let err = publisher.publish(&[message("0", "{}")]).await.unwrap_err();
+        assert!(err.to_string().contains("stream offline"), "{err:#}");
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The records that the exporter publishes, one type per topic. Records are published as JSON,
+//! with their schema in the `Sui-Schema` header. A schema's version is bumped whenever its record
+//! changes in a way that existing consumers cannot read (fields are removed, renamed, or change
+//! type), while new fields can be added without bumping it.
+
+use serde::Serialize;
+use sui_indexer_alt_framework::types::{base_types::SuiAddress, object::Owner};
+
+use super::nats::Message;
+
+/// A record that is published to its own topic.
+pub(crate) trait Record: Serialize {
+    /// The topic the record is published to, appended to the exporter's subject prefix.
+    const TOPIC: &'static str;
+
+    /// The name and version of the record's schema.
+    const SCHEMA: &'static str;
+
+    /// Uniquely identifies the record within its topic, so that records published more than once
+    /// can be de-duplicated.
+    fn id(&self) -> String;
+
+    /// The message that publishes this record to its topic under `prefix`.
+    fn message(&self, prefix: &str) -> anyhow::Result<Message> {
+        Ok(Message {
+            subject: format!("{prefix}.{}", Self::TOPIC),
+            id: self.id(),
+            schema: Self::SCHEMA,
+            payload: serde_json::to_vec(self)?,
+        })
+    }
+}
+
+/// Published for each checkpoint, before the records for its transactions.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CheckpointRecord {
+    pub sequence_number: u64,
+    pub digest: String,
+    pub previous_digest: Option<String>,
+    pub epoch: u64,
+    pub timestamp_ms: u64,
+
+    /// The number of transactions in the checkpoint.
+    pub transactions: u64,
+
+    /// The total number of transactions committed since genesis, including this checkpoint's.
+    pub network_total_transactions: u64,
+}
+
+/// Published for each transaction, in the order they were executed, before the records for its
+/// balance changes.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TransactionRecord {
+    pub digest: String,
+    pub tx_sequence_number: u64,
+    pub checkpoint: u64,
+    pub epoch: u64,
+    pub timestamp_ms: u64,
+
+    /// The sender of the transaction, which is `0x0` for system transactions.
+    pub sender: SuiAddress,
+
+    /// Whether the transaction executed successfully.
+    pub success: bool,
+
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    pub storage_rebate: u64,
+    pub non_refundable_storage_fee: u64,
+}
+
+/// Published for each owner and coin type whose balance a transaction changed.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BalanceChangeRecord {
+    pub tx_digest: String,
+    pub tx_sequence_number: u64,
+    pub checkpoint: u64,
+
+    /// The position of this balance change among the transaction's balance changes.
+    pub index: u64,
+
+    pub owner: Owner,
+    pub coin_type: String,
+
+    /// The amount the balance changed by, negative if the owner's balance decreased. Serialized
+    /// as a string, because it may not fit in a JSON number.
+    pub amount: String,
+}
+
+impl Record for CheckpointRecord {
+    const TOPIC: &'static str = "checkpoints";
+    const SCHEMA: &'static str = "sui.checkpoint.v1";
+
+    fn id(&self) -> String {
+        self.sequence_number.to_string()
+    }
+}
+
+impl Record for TransactionRecord {
+    const TOPIC: &'static str = "transactions";
+    const SCHEMA: &'static str = "sui.transaction.v1";
+
+    fn id(&self) -> String {
+        self.digest.clone()
+    }
+}
+
+impl Record for BalanceChangeRecord {
+    const TOPIC: &'static str = "balance_changes";
+    const SCHEMA: &'static str = "sui.balance_change.v1";
+
+    fn id(&self) -> String {
+        format!("{}:{}", self.tx_digest, self.index)
+    }
+}
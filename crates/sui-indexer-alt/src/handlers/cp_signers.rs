@@ -0,0 +1,98 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::{
+    pipeline::{concurrent::Handler, Processor},
+    postgres::{Connection, Db},
+    types::full_checkpoint_content::CheckpointData,
+};
+use sui_indexer_alt_schema::{checkpoints::StoredCpSigners, schema::cp_signers};
+
+pub(crate) struct CpSigners;
+
+impl Processor for CpSigners {
+    const NAME: &'static str = "cp_signers";
+
+    type Value = StoredCpSigners;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let cp_sequence_number = checkpoint.checkpoint_summary.sequence_number as i64;
+        let epoch = checkpoint.checkpoint_summary.epoch as i64;
+        let signers = checkpoint
+            .checkpoint_summary
+            .auth_sig()
+            .signers_map
+            .iter()
+            .map(|index| {
+                i16::try_from(index).with_context(|| {
+                    format!("Checkpoint {cp_sequence_number} signer index {index} out of range")
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(vec![StoredCpSigners {
+            cp_sequence_number,
+            epoch,
+            signers,
+        }])
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for CpSigners {
+    type Store = Db;
+
+    async fn commit<'a>(values: &[Self::Value], conn: &mut Connection<'a>) -> Result<usize> {
+        Ok(diesel::insert_into(cp_signers::table)
+            .values(values)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?)
+    }
+
+    async fn prune<'a>(
+        &self,
+        from: u64,
+        to_exclusive: u64,
+        conn: &mut Connection<'a>,
+    ) -> Result<usize> {
+        let filter = cp_signers::table
+            .filter(cp_signers::cp_sequence_number.between(from as i64, to_exclusive as i64 - 1));
+
+        Ok(diesel::delete(filter).execute(conn).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_indexer_alt_framework::types::test_checkpoint_data_builder::TestCheckpointDataBuilder;
+
+    /// Test checkpoints are signed by every member of the test committee.
+    #[test]
+    fn test_cp_signers_process() {
+        let mut builder = TestCheckpointDataBuilder::new(0);
+        builder = builder.start_transaction(0).finish_transaction();
+        let checkpoint = Arc::new(builder.build_checkpoint());
+
+        let values = CpSigners.process(&checkpoint).unwrap();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].cp_sequence_number, 0);
+        assert_eq!(values[0].epoch, 0);
+
+        let expected: Vec<i16> = checkpoint
+            .checkpoint_summary
+            .auth_sig()
+            .signers_map
+            .iter()
+            .map(|index| index as i16)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(values[0].signers, expected);
+    }
+}
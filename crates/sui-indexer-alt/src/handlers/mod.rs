@@ -13,6 +13,7 @@ use sui_indexer_alt_framework::types::{
 
 pub(crate) mod coin_balance_buckets;
 pub(crate) mod cp_sequence_numbers;
+pub(crate) mod cp_signers;
 pub(crate) mod ev_emit_mod;
 pub(crate) mod ev_struct_inst;
 pub(crate) mod kv_checkpoints;
@@ -30,6 +31,7 @@ pub(crate) mod tx_affected_addresses;
 pub(crate) mod tx_affected_objects;
 pub(crate) mod tx_balance_changes;
 pub(crate) mod tx_calls;
+pub(crate) mod tx_coin_types;
 pub(crate) mod tx_digests;
 pub(crate) mod tx_kinds;
 
@@ -0,0 +1,165 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::{
+    pipeline::{concurrent::Handler, Processor},
+    postgres::{Connection, Db},
+    types::full_checkpoint_content::CheckpointData,
+};
+use sui_indexer_alt_schema::{schema::tx_coin_types, transactions::StoredTxCoinType};
+
+use crate::handlers::cp_sequence_numbers::tx_interval;
+
+/// Records the types of the coins that each transaction takes as input, including its gas coins.
+pub(crate) struct TxCoinTypes;
+
+impl Processor for TxCoinTypes {
+    const NAME: &'static str = "tx_coin_types";
+
+    type Value = StoredTxCoinType;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let CheckpointData {
+            transactions,
+            checkpoint_summary,
+            ..
+        } = checkpoint.as_ref();
+
+        let mut values = Vec::new();
+        let first_tx = checkpoint_summary.network_total_transactions as usize - transactions.len();
+
+        for (i, tx) in transactions.iter().enumerate() {
+            let tx_sequence_number = (first_tx + i) as i64;
+
+            // Gas coins are among the transaction's input objects, because paying for gas always
+            // modifies them.
+            let coin_types: BTreeSet<_> = tx
+                .input_objects
+                .iter()
+                .filter_map(|o| o.coin_type_maybe())
+                .collect();
+
+            for coin_type in coin_types {
+                values.push(StoredTxCoinType {
+                    coin_type: bcs::to_bytes(&coin_type).with_context(|| {
+                        format!(
+                            "Failed to serialize coin type {coin_type} in tx {tx_sequence_number}"
+                        )
+                    })?,
+                    tx_sequence_number,
+                });
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for TxCoinTypes {
+    type Store = Db;
+
+    const MIN_EAGER_ROWS: usize = 100;
+    const MAX_PENDING_ROWS: usize = 10000;
+
+    async fn commit<'a>(values: &[Self::Value], conn: &mut Connection<'a>) -> Result<usize> {
+        Ok(diesel::insert_into(tx_coin_types::table)
+            .values(values)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?)
+    }
+
+    async fn prune<'a>(
+        &self,
+        from: u64,
+        to_exclusive: u64,
+        conn: &mut Connection<'a>,
+    ) -> Result<usize> {
+        let Range {
+            start: from_tx,
+            end: to_tx,
+        } = tx_interval(conn, from..to_exclusive).await?;
+        let filter = tx_coin_types::table
+            .filter(tx_coin_types::tx_sequence_number.between(from_tx as i64, to_tx as i64 - 1));
+
+        Ok(diesel::delete(filter).execute(conn).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use sui_indexer_alt_framework::{
+        types::{gas_coin::GAS, test_checkpoint_data_builder::TestCheckpointDataBuilder, TypeTag},
+        Indexer,
+    };
+    use sui_indexer_alt_schema::MIGRATIONS;
+
+    use crate::handlers::cp_sequence_numbers::CpSequenceNumbers;
+
+    async fn get_all_tx_coin_types(conn: &mut Connection<'_>) -> Result<Vec<(TypeTag, i64)>> {
+        let stored: Vec<StoredTxCoinType> = tx_coin_types::table
+            .order_by((tx_coin_types::tx_sequence_number, tx_coin_types::coin_type))
+            .load(conn)
+            .await?;
+
+        Ok(stored
+            .into_iter()
+            .map(|s| (bcs::from_bytes(&s.coin_type).unwrap(), s.tx_sequence_number))
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_tx_coin_types() {
+        let (indexer, _db) = Indexer::new_for_testing(&MIGRATIONS).await;
+        let mut conn = indexer.store().connect().await.unwrap();
+
+        let coin_type = TypeTag::from_str("0x0::a::B").unwrap();
+        let mut builder = TestCheckpointDataBuilder::new(0)
+            .start_transaction(0)
+            .create_coin_object(0, 0, 100, coin_type.clone())
+            .finish_transaction();
+        let checkpoint = Arc::new(builder.build_checkpoint());
+        let values = TxCoinTypes.process(&checkpoint).unwrap();
+        TxCoinTypes::commit(&values, &mut conn).await.unwrap();
+        let values = CpSequenceNumbers.process(&checkpoint).unwrap();
+        CpSequenceNumbers::commit(&values, &mut conn).await.unwrap();
+
+        // The second transaction takes the coin created by the first as input.
+        builder = builder
+            .start_transaction(0)
+            .transfer_coin_balance(0, 1, 1, 10)
+            .finish_transaction();
+        let checkpoint = Arc::new(builder.build_checkpoint());
+        let values = TxCoinTypes.process(&checkpoint).unwrap();
+        TxCoinTypes::commit(&values, &mut conn).await.unwrap();
+        let values = CpSequenceNumbers.process(&checkpoint).unwrap();
+        CpSequenceNumbers::commit(&values, &mut conn).await.unwrap();
+
+        // Every transaction uses its gas coin, but only the second uses the created coin.
+        let mut expect = vec![
+            (GAS::type_tag(), 0),
+            (GAS::type_tag(), 1),
+            (coin_type.clone(), 1),
+        ];
+        expect.sort_by_key(|(t, tx)| (*tx, bcs::to_bytes(t).unwrap()));
+        assert_eq!(get_all_tx_coin_types(&mut conn).await.unwrap(), expect);
+
+        // Prune the first checkpoint.
+        let rows_pruned = TxCoinTypes.prune(0, 1, &mut conn).await.unwrap();
+        assert_eq!(rows_pruned, 1);
+        let remaining = get_all_tx_coin_types(&mut conn).await.unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|(_, tx)| *tx == 1));
+    }
+}
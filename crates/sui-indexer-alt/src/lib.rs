@@ -6,13 +6,14 @@ use bootstrap::bootstrap;
 use config::{IndexerConfig, PipelineLayer};
 use handlers::{
     coin_balance_buckets::CoinBalanceBuckets, cp_sequence_numbers::CpSequenceNumbers,
-    ev_emit_mod::EvEmitMod, ev_struct_inst::EvStructInst, kv_checkpoints::KvCheckpoints,
-    kv_epoch_ends::KvEpochEnds, kv_epoch_starts::KvEpochStarts, kv_feature_flags::KvFeatureFlags,
-    kv_objects::KvObjects, kv_packages::KvPackages, kv_protocol_configs::KvProtocolConfigs,
-    kv_transactions::KvTransactions, obj_info::ObjInfo, obj_versions::ObjVersions,
-    sum_displays::SumDisplays, tx_affected_addresses::TxAffectedAddresses,
-    tx_affected_objects::TxAffectedObjects, tx_balance_changes::TxBalanceChanges,
-    tx_calls::TxCalls, tx_digests::TxDigests, tx_kinds::TxKinds,
+    cp_signers::CpSigners, ev_emit_mod::EvEmitMod, ev_struct_inst::EvStructInst,
+    kv_checkpoints::KvCheckpoints, kv_epoch_ends::KvEpochEnds, kv_epoch_starts::KvEpochStarts,
+    kv_feature_flags::KvFeatureFlags, kv_objects::KvObjects, kv_packages::KvPackages,
+    kv_protocol_configs::KvProtocolConfigs, kv_transactions::KvTransactions, obj_info::ObjInfo,
+    obj_versions::ObjVersions, sum_displays::SumDisplays,
+    tx_affected_addresses::TxAffectedAddresses, tx_affected_objects::TxAffectedObjects,
+    tx_balance_changes::TxBalanceChanges, tx_calls::TxCalls, tx_coin_types::TxCoinTypes,
+    tx_digests::TxDigests, tx_kinds::TxKinds,
 };
 use prometheus::Registry;
 use sui_indexer_alt_framework::{
@@ -31,11 +32,14 @@ use tokio_util::sync::CancellationToken;
 use url::Url;
 
 pub mod args;
+pub mod backfill;
 #[cfg(feature = "benchmark")]
 pub mod benchmark;
 pub(crate) mod bootstrap;
 pub mod config;
+pub mod exporter;
 pub(crate) mod handlers;
+pub mod supervisor;
 
 pub async fn setup_indexer(
     database_url: Url,
@@ -56,6 +60,8 @@ pub async fn setup_indexer(
         committer,
         pruner,
         pipeline,
+        supervisor: _,
+        exporter: _,
         extra: _,
     } = indexer_config.finish()?;
 
@@ -63,6 +69,7 @@ pub async fn setup_indexer(
         sum_displays,
         coin_balance_buckets,
         cp_sequence_numbers,
+        cp_signers,
         ev_emit_mod,
         ev_struct_inst,
         kv_checkpoints,
@@ -79,6 +86,7 @@ pub async fn setup_indexer(
         tx_affected_objects,
         tx_balance_changes,
         tx_calls,
+        tx_coin_types,
         tx_digests,
         tx_kinds,
         extra: _,
@@ -179,6 +187,7 @@ pub async fn setup_indexer(
 
     // Unpruned concurrent pipelines
     add_concurrent!(CpSequenceNumbers, cp_sequence_numbers);
+    add_concurrent!(CpSigners, cp_signers);
     add_concurrent!(EvEmitMod, ev_emit_mod);
     add_concurrent!(EvStructInst, ev_struct_inst);
     add_concurrent!(KvCheckpoints, kv_checkpoints);
@@ -192,6 +201,7 @@ pub async fn setup_indexer(
     add_concurrent!(TxAffectedObjects, tx_affected_objects);
     add_concurrent!(TxBalanceChanges, tx_balance_changes);
     add_concurrent!(TxCalls, tx_calls);
+    add_concurrent!(TxCoinTypes, tx_coin_types);
     add_concurrent!(TxDigests, tx_digests);
     add_concurrent!(TxKinds, tx_kinds);
 
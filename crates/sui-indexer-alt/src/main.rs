@@ -10,9 +10,13 @@ use clap::Parser;
 use prometheus::Registry;
 use sui_indexer_alt::args::Args;
 use sui_indexer_alt::args::Command;
+use sui_indexer_alt::backfill::run_backfill;
 use sui_indexer_alt::config::IndexerConfig;
 use sui_indexer_alt::config::Merge;
+use sui_indexer_alt::exporter::{Exporter, ExporterConfig};
 use sui_indexer_alt::setup_indexer;
+use sui_indexer_alt::supervisor::{Supervisor, SupervisorConfig};
+use sui_indexer_alt_framework::ingestion::IngestionConfig;
 use sui_indexer_alt_framework::postgres::reset_database;
 use sui_indexer_alt_metrics::uptime;
 use sui_indexer_alt_metrics::MetricsService;
@@ -81,7 +85,18 @@ async fn main() -> Result<()> {
                 .register(uptime(VERSION)?)
                 .context("Failed to register uptime metric.")?;
 
-            let h_indexer = setup_indexer(
+            let supervisor_config = indexer_config
+                .supervisor
+                .clone()
+                .finish(SupervisorConfig::default())?;
+
+            let exporter_config = indexer_config
+                .exporter
+                .clone()
+                .map(|layer| layer.finish(ExporterConfig::default()))
+                .transpose()?;
+
+            let indexer = setup_indexer(
                 database_url,
                 db_args,
                 indexer_args,
@@ -91,19 +106,92 @@ async fn main() -> Result<()> {
                 metrics.registry(),
                 cancel.child_token(),
             )
-            .await?
-            .run()
-            .await
-            .context("Failed to start indexer")?;
+            .await?;
+
+            let supervisor = Supervisor::new(
+                indexer.store().clone(),
+                indexer.pipelines(),
+                supervisor_config,
+                metrics.registry(),
+                cancel.child_token(),
+            );
 
+            let exporter = exporter_config.map(|config| {
+                Exporter::new(
+                    indexer.store().clone(),
+                    config,
+                    metrics.registry(),
+                    cancel.child_token(),
+                )
+            });
+
+            let h_indexer = indexer.run().await.context("Failed to start indexer")?;
+            let h_supervisor = supervisor.run();
+            let h_exporter = exporter.map(Exporter::run);
             let h_metrics = metrics.run().await?;
 
             // Wait for the indexer to finish, then force the supporting services to shut down
             // using the cancellation token.
             let _ = h_indexer.await;
+            cancel.cancel();
+            let _ = h_supervisor.await;
+            if let Some(h_exporter) = h_exporter {
+                let _ = h_exporter.await;
+            }
+            let _ = h_metrics.await;
+            let _ = h_ctrl_c.await;
+        }
+
+        Command::Backfill {
+            database_url,
+            db_args,
+            client_args,
+            pipeline,
+            first_checkpoint,
+            last_checkpoint,
+            backfill_args,
+            metrics_args,
+        } => {
+            let cancel = CancellationToken::new();
+
+            let registry = Registry::new_custom(Some("indexer_alt".into()), None)
+                .context("Failed to create Prometheus registry.")?;
+
+            let metrics = MetricsService::new(metrics_args, registry, cancel.child_token());
+
+            let h_ctrl_c = tokio::spawn({
+                let cancel = cancel.clone();
+                async move {
+                    tokio::select! {
+                        _ = cancel.cancelled() => {}
+                        _ = signal::ctrl_c() => {
+                            info!("Received Ctrl-C, shutting down...");
+                            cancel.cancel();
+                        }
+                    }
+                }
+            });
+
+            let registry = metrics.registry().clone();
+            let h_metrics = metrics.run().await?;
+
+            let result = run_backfill(
+                database_url,
+                db_args,
+                client_args,
+                IngestionConfig::default(),
+                &pipeline,
+                first_checkpoint..=last_checkpoint,
+                backfill_args,
+                &registry,
+                cancel.child_token(),
+            )
+            .await;
+
             cancel.cancel();
             let _ = h_metrics.await;
             let _ = h_ctrl_c.await;
+            result?;
         }
 
         Command::GenerateConfig => {
@@ -0,0 +1,420 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry, IntCounterVec,
+    IntGaugeVec, Registry,
+};
+use sui_indexer_alt_framework::postgres::Db;
+use sui_indexer_alt_schema::{
+    backfills::StoredBackfill,
+    schema::{backfills, watermarks},
+};
+use tokio::{process::Command, sync::Mutex, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Placeholders in the backfill command that are substituted with the details of the backfill.
+const PIPELINE: &str = "{pipeline}";
+const FIRST_CHECKPOINT: &str = "{first_checkpoint}";
+const LAST_CHECKPOINT: &str = "{last_checkpoint}";
+
+#[derive(Clone, Debug)]
+pub struct SupervisorConfig {
+    /// How often to check the watermarks of the indexer's pipelines, in milliseconds.
+    pub interval_ms: u64,
+
+    /// How long a pipeline's watermark can go without advancing, while other pipelines make
+    /// progress, before the pipeline is considered stalled, in milliseconds.
+    pub stall_threshold_ms: u64,
+
+    /// The command to run to backfill a stalled pipeline, as a program followed by its arguments.
+    /// Occurrences of `{pipeline}`, `{first_checkpoint}` and `{last_checkpoint}` in the arguments
+    /// are replaced by the stalled pipeline's name and the (inclusive) range of checkpoints it is
+    /// missing. If the command is empty, stalled pipelines are reported, but not backfilled.
+    pub backfill_command: Vec<String>,
+}
+
+/// Background task that watches the watermarks of the indexer's pipelines, and notices when one
+/// of them stalls -- its watermark stops advancing while other pipelines make progress. Stalled
+/// pipelines are reported through logs and metrics, and, if a backfill command is configured, a
+/// backfill is launched to fill in the checkpoints the pipeline is missing. Backfills are recorded
+/// in the `backfills` table, so that their progress can be surfaced by RPC services.
+pub struct Supervisor {
+    db: Db,
+    pipelines: Vec<String>,
+    config: SupervisorConfig,
+    metrics: Arc<SupervisorMetrics>,
+    cancel: CancellationToken,
+}
+
+struct SupervisorMetrics {
+    stalled_pipelines: IntGaugeVec,
+    backfills_launched: IntCounterVec,
+}
+
+/// Tracks when each pipeline's watermark last advanced, to detect stalls.
+struct StallDetector {
+    threshold: Duration,
+    progress: BTreeMap<String, (i64, Instant)>,
+}
+
+/// A pipeline whose watermark has not advanced for longer than the stall threshold.
+#[derive(Debug, PartialEq, Eq)]
+struct Stall {
+    pipeline: String,
+
+    /// The pipeline's (stalled) watermark.
+    checkpoint_hi_inclusive: i64,
+
+    /// The highest watermark across all pipelines.
+    leader_hi_inclusive: i64,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 5_000,
+            stall_threshold_ms: 300_000,
+            backfill_command: vec![],
+        }
+    }
+}
+
+impl Supervisor {
+    pub fn new(
+        db: Db,
+        pipelines: impl IntoIterator<Item = impl Into<String>>,
+        config: SupervisorConfig,
+        registry: &Registry,
+        cancel: CancellationToken,
+    ) -> Self {
+        Self {
+            db,
+            pipelines: pipelines.into_iter().map(Into::into).collect(),
+            config,
+            metrics: Arc::new(SupervisorMetrics::new(registry)),
+            cancel,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let Self {
+                db,
+                pipelines,
+                config,
+                metrics,
+                cancel,
+            } = self;
+
+            let mut detector = StallDetector::new(Duration::from_millis(config.stall_threshold_ms));
+            let backfilling: Arc<Mutex<BTreeSet<String>>> = Default::default();
+            let mut interval = tokio::time::interval(Duration::from_millis(config.interval_ms));
+
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        info!("Shutdown received, stopping supervisor");
+                        break;
+                    }
+
+                    _ = interval.tick() => {
+                        let watermarks = match read_watermarks(&db, &pipelines).await {
+                            Ok(watermarks) => watermarks,
+                            Err(e) => {
+                                warn!("Supervisor failed to read watermarks: {e:#}");
+                                continue;
+                            }
+                        };
+
+                        let stalls = detector.observe(Instant::now(), &watermarks);
+                        for pipeline in &pipelines {
+                            let stalled = stalls.iter().any(|s| &s.pipeline == pipeline);
+                            metrics
+                                .stalled_pipelines
+                                .with_label_values(&[pipeline.as_str()])
+                                .set(stalled as i64);
+                        }
+
+                        for stall in stalls {
+                            warn!(
+                                pipeline = stall.pipeline,
+                                checkpoint_hi_inclusive = stall.checkpoint_hi_inclusive,
+                                leader_hi_inclusive = stall.leader_hi_inclusive,
+                                "Pipeline stalled",
+                            );
+
+                            if config.backfill_command.is_empty()
+                                || !backfilling.lock().await.insert(stall.pipeline.clone())
+                            {
+                                continue;
+                            }
+
+                            let backfill = StoredBackfill {
+                                pipeline: stall.pipeline,
+                                first_checkpoint: stall.checkpoint_hi_inclusive + 1,
+                                last_checkpoint: stall.leader_hi_inclusive,
+                                started_at_ms: now_ms(),
+                                finished_at_ms: None,
+                            };
+
+                            metrics
+                                .backfills_launched
+                                .with_label_values(&[backfill.pipeline.as_str()])
+                                .inc();
+
+                            tokio::spawn(run_backfill(
+                                db.clone(),
+                                config.backfill_command.clone(),
+                                backfill,
+                                backfilling.clone(),
+                            ));
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl SupervisorMetrics {
+    fn new(registry: &Registry) -> Self {
+        Self {
+            stalled_pipelines: register_int_gauge_vec_with_registry!(
+                "indexer_supervisor_stalled_pipelines",
+                "Whether each pipeline's watermark is considered stalled (1) or not (0)",
+                &["pipeline"],
+                registry,
+            )
+            .unwrap(),
+
+            backfills_launched: register_int_counter_vec_with_registry!(
+                "indexer_supervisor_backfills_launched",
+                "Number of backfills launched for stalled pipelines",
+                &["pipeline"],
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+impl StallDetector {
+    fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            progress: BTreeMap::new(),
+        }
+    }
+
+    /// Record the latest `watermarks` (as of `now`), and return the pipelines that have stalled.
+    fn observe(&mut self, now: Instant, watermarks: &BTreeMap<String, i64>) -> Vec<Stall> {
+        for (pipeline, hi) in watermarks {
+            match self.progress.get_mut(pipeline) {
+                Some((last_hi, last_advanced)) if *last_hi < *hi => {
+                    *last_hi = *hi;
+                    *last_advanced = now;
+                }
+
+                Some(_) => {}
+
+                None => {
+                    self.progress.insert(pipeline.clone(), (*hi, now));
+                }
+            }
+        }
+
+        let Some(leader_hi) = self.progress.values().map(|(hi, _)| *hi).max() else {
+            return vec![];
+        };
+
+        self.progress
+            .iter()
+            .filter(|(_, (hi, last_advanced))| {
+                *hi < leader_hi && now.duration_since(*last_advanced) >= self.threshold
+            })
+            .map(|(pipeline, (hi, _))| Stall {
+                pipeline: pipeline.clone(),
+                checkpoint_hi_inclusive: *hi,
+                leader_hi_inclusive: leader_hi,
+            })
+            .collect()
+    }
+}
+
+/// Read the high watermarks for `pipelines`.
+async fn read_watermarks(db: &Db, pipelines: &[String]) -> anyhow::Result<BTreeMap<String, i64>> {
+    let mut conn = db.connect().await?;
+    let rows: Vec<(String, i64)> = watermarks::table
+        .select((watermarks::pipeline, watermarks::checkpoint_hi_inclusive))
+        .filter(watermarks::pipeline.eq_any(pipelines))
+        .load(&mut conn)
+        .await
+        .context("Failed to read watermarks")?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Record `backfill` in the database, run the backfill command for it, and record when it
+/// finishes. The pipeline is removed from `backfilling` once the backfill has finished, so that
+/// it can be backfilled again if it remains stalled.
+async fn run_backfill(
+    db: Db,
+    command: Vec<String>,
+    backfill: StoredBackfill,
+    backfilling: Arc<Mutex<BTreeSet<String>>>,
+) {
+    let pipeline = backfill.pipeline.clone();
+    if let Err(e) = try_run_backfill(&db, &command, backfill).await {
+        warn!(pipeline, "Backfill failed: {e:#}");
+    }
+
+    backfilling.lock().await.remove(&pipeline);
+}
+
+async fn try_run_backfill(
+    db: &Db,
+    command: &[String],
+    backfill: StoredBackfill,
+) -> anyhow::Result<()> {
+    let mut conn = db.connect().await?;
+    diesel::insert_into(backfills::table)
+        .values(&backfill)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await
+        .context("Failed to record backfill")?;
+
+    let [program, args @ ..] = command else {
+        bail!("No backfill command");
+    };
+
+    let first_checkpoint = backfill.first_checkpoint.to_string();
+    let last_checkpoint = backfill.last_checkpoint.to_string();
+    let args = args.iter().map(|arg| {
+        arg.replace(PIPELINE, &backfill.pipeline)
+            .replace(FIRST_CHECKPOINT, &first_checkpoint)
+            .replace(LAST_CHECKPOINT, &last_checkpoint)
+    });
+
+    info!(
+        pipeline = backfill.pipeline,
+        first_checkpoint = backfill.first_checkpoint,
+        last_checkpoint = backfill.last_checkpoint,
+        "Launching backfill",
+    );
+
+    let status = Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .status()
+        .await
+        .context("Failed to run backfill command")?;
+
+    diesel::update(backfills::table)
+        .filter(backfills::pipeline.eq(&backfill.pipeline))
+        .filter(backfills::first_checkpoint.eq(backfill.first_checkpoint))
+        .set(backfills::finished_at_ms.eq(now_ms()))
+        .execute(&mut conn)
+        .await
+        .context("Failed to record backfill completion")?;
+
+    if !status.success() {
+        bail!("Backfill command exited with {status}");
+    }
+
+    info!(pipeline = backfill.pipeline, "Backfill finished");
+    Ok(())
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watermarks(hi: &[(&str, i64)]) -> BTreeMap<String, i64> {
+        hi.iter().map(|(p, h)| (p.to_string(), *h)).collect()
+    }
+
+    #[test]
+    fn test_no_stall_while_advancing() {
+        let mut detector = StallDetector::new(Duration::from_secs(10));
+        let t0 = Instant::now();
+
+        assert!(detector
+            .observe(t0, &watermarks(&[("a", 10), ("b", 10)]))
+            .is_empty());
+        assert!(detector
+            .observe(
+                t0 + Duration::from_secs(20),
+                &watermarks(&[("a", 20), ("b", 15)])
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_no_stall_when_leading() {
+        // A pipeline that is not behind any other pipeline is idle, rather than stalled.
+        let mut detector = StallDetector::new(Duration::from_secs(10));
+        let t0 = Instant::now();
+
+        detector.observe(t0, &watermarks(&[("a", 10), ("b", 10)]));
+        assert!(detector
+            .observe(
+                t0 + Duration::from_secs(20),
+                &watermarks(&[("a", 10), ("b", 10)])
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_stall() {
+        let mut detector = StallDetector::new(Duration::from_secs(10));
+        let t0 = Instant::now();
+
+        detector.observe(t0, &watermarks(&[("a", 10), ("b", 10)]));
+
+        // Not stalled until the threshold has passed.
+        assert!(detector
+            .observe(
+                t0 + Duration::from_secs(5),
+                &watermarks(&[("a", 15), ("b", 10)])
+            )
+            .is_empty());
+
+        assert_eq!(
+            detector.observe(
+                t0 + Duration::from_secs(10),
+                &watermarks(&[("a", 20), ("b", 10)])
+            ),
+            vec![Stall {
+                pipeline: "b".to_string(),
+                checkpoint_hi_inclusive: 10,
+                leader_hi_inclusive: 20,
+            }],
+        );
+
+        // Once the pipeline makes progress again, it is no longer stalled.
+        assert!(detector
+            .observe(
+                t0 + Duration::from_secs(11),
+                &watermarks(&[("a", 21), ("b", 11)])
+            )
+            .is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encryption at rest for key files. An encrypted key file holds the original contents of the
+//! key file, encrypted with AES-256-GCM under a key derived from a passphrase with scrypt.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sui_types::crypto::{AuthorityKeyPair, EncodeDecodeBase64, SuiKeyPair};
+use zeroize::Zeroizing;
+
+const ENCRYPTED_KEY_FILE_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const KEY_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct EncryptedKeyFile {
+    version: u8,
+    kdf: ScryptParams,
+    /// Base64 encoded.
+    salt: String,
+    /// Base64 encoded.
+    nonce: String,
+    /// Base64 encoded.
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ScryptParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+impl ScryptParams {
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &[u8],
+    ) -> anyhow::Result<Zeroizing<[u8; KEY_LENGTH]>> {
+        let params = scrypt::Params::new(self.log_n, self.r, self.p)
+            .map_err(|e| anyhow!("Invalid scrypt parameters: {e}"))?;
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        scrypt::scrypt(passphrase, salt, &params, key.as_mut())
+            .map_err(|e| anyhow!("Failed to derive key: {e}"))?;
+        Ok(key)
+    }
+}
+
+/// Encrypt the contents of a key file with `passphrase`, returning the contents of the
+/// encrypted key file.
+pub fn encrypt_key_file_contents(contents: &str, passphrase: &[u8]) -> anyhow::Result<String> {
+    let kdf = ScryptParams::default();
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut nonce = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = kdf.derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref()).map_err(|e| anyhow!(e))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), contents.trim().as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt key file"))?;
+
+    Ok(serde_json::to_string_pretty(&EncryptedKeyFile {
+        version: ENCRYPTED_KEY_FILE_VERSION,
+        kdf,
+        salt: Base64::encode(salt),
+        nonce: Base64::encode(nonce),
+        ciphertext: Base64::encode(ciphertext),
+    })?)
+}
+
+/// Decrypt the contents of an encrypted key file with `passphrase`, returning the contents of
+/// the original key file. The decrypted contents are zeroed when they are dropped.
+pub fn decrypt_key_file_contents(
+    contents: &str,
+    passphrase: &[u8],
+) -> anyhow::Result<Zeroizing<String>> {
+    let file: EncryptedKeyFile =
+        serde_json::from_str(contents).context("Not an encrypted key file")?;
+    if file.version != ENCRYPTED_KEY_FILE_VERSION {
+        bail!("Unsupported encrypted key file version {}", file.version);
+    }
+
+    let salt = Base64::decode(&file.salt).map_err(|e| anyhow!("Invalid salt: {e}"))?;
+    let nonce = Base64::decode(&file.nonce).map_err(|e| anyhow!("Invalid nonce: {e}"))?;
+    let ciphertext =
+        Base64::decode(&file.ciphertext).map_err(|e| anyhow!("Invalid ciphertext: {e}"))?;
+    if nonce.len() != NONCE_LENGTH {
+        bail!("Invalid nonce length {}", nonce.len());
+    }
+
+    let key = file.kdf.derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref()).map_err(|e| anyhow!(e))?;
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| anyhow!("Failed to decrypt key file, the passphrase may be wrong"))?,
+    );
+
+    let plaintext = std::str::from_utf8(&plaintext).context("Key file is not valid UTF-8")?;
+    Ok(Zeroizing::new(plaintext.to_owned()))
+}
+
+/// Whether the file at `path` is an encrypted key file.
+pub fn is_encrypted_key_file<P: AsRef<Path>>(path: P) -> anyhow::Result<bool> {
+    Ok(is_encrypted(&fs::read_to_string(path)?))
+}
+
+pub(crate) fn is_encrypted(contents: &str) -> bool {
+    serde_json::from_str::<EncryptedKeyFile>(contents).is_ok()
+}
+
+/// Encrypt the key file at `path` in place with `passphrase`. Returns false, leaving the file
+/// untouched, if it is already encrypted.
+///
+/// The encrypted contents are written to a temporary file next to the key file, which then
+/// replaces it, so the key file holds either its original or its encrypted contents, even if
+/// encryption is interrupted.
+pub fn encrypt_key_file<P: AsRef<Path>>(path: P, passphrase: &[u8]) -> anyhow::Result<bool> {
+    let path = path.as_ref();
+    let contents = Zeroizing::new(fs::read_to_string(path)?);
+    if is_encrypted(&contents) {
+        return Ok(false);
+    }
+
+    let encrypted = encrypt_key_file_contents(&contents, passphrase)?;
+    write_atomically(path, encrypted.as_bytes())
+        .with_context(|| format!("Failed to write encrypted key file at path {path:?}"))?;
+    Ok(true)
+}
+
+/// Replace the contents of the file at `path` with `contents`, by writing them to a temporary
+/// file in the same directory, syncing it, and renaming it over `path`. The temporary file gets
+/// the same permissions as the file it replaces.
+fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Not a file: {path:?}"))?
+        .to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{file_name}.tmp"));
+
+    let result = (|| {
+        let mut tmp = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)?;
+        tmp.set_permissions(fs::metadata(path)?.permissions())?;
+        tmp.write_all(contents)?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    // Sync the directory as well, so that the rename itself is durable.
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Read an encrypted key file holding a Base64 encoded `flag || privkey`, and return a SuiKeyPair.
+pub fn read_encrypted_keypair_from_file<P: AsRef<Path>>(
+    path: P,
+    passphrase: &[u8],
+) -> anyhow::Result<SuiKeyPair> {
+    let contents = decrypt_key_file_contents(&fs::read_to_string(path)?, passphrase)?;
+    SuiKeyPair::decode_base64(contents.trim()).map_err(|e| anyhow!(e))
+}
+
+/// Read an encrypted key file holding a Base64 encoded `privkey`, and return an AuthorityKeyPair.
+pub fn read_encrypted_authority_keypair_from_file<P: AsRef<Path>>(
+    path: P,
+    passphrase: &[u8],
+) -> anyhow::Result<AuthorityKeyPair> {
+    let contents = decrypt_key_file_contents(&fs::read_to_string(path)?, passphrase)?;
+    AuthorityKeyPair::decode_base64(contents.trim()).map_err(|e| anyhow!(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let contents = "AKtXHMe7MFc3lhzmbPGWdeeN7Da1vZX9QIk6Qa3yXmrS";
+        let encrypted = encrypt_key_file_contents(contents, b"passphrase").unwrap();
+        assert!(!encrypted.contains(contents));
+        assert_eq!(
+            decrypt_key_file_contents(&encrypted, b"passphrase")
+                .unwrap()
+                .as_str(),
+            contents
+        );
+        assert!(decrypt_key_file_contents(&encrypted, b"wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_key_file_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key.key");
+        let contents = "AKtXHMe7MFc3lhzmbPGWdeeN7Da1vZX9QIk6Qa3yXmrS";
+        fs::write(&path, contents).unwrap();
+
+        assert!(encrypt_key_file(&path, b"passphrase").unwrap());
+        assert!(is_encrypted_key_file(&path).unwrap());
+        let encrypted = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            decrypt_key_file_contents(&encrypted, b"passphrase")
+                .unwrap()
+                .as_str(),
+            contents
+        );
+
+        // Already encrypted, so the file is left as it is.
+        assert!(!encrypt_key_file(&path, b"passphrase").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), encrypted);
+
+        // Only the key file is left behind, with no temporary file next to it.
+        let entries: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("key.key")]);
+    }
+}
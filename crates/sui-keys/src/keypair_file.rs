@@ -3,6 +3,7 @@
 
 use std::path::PathBuf;
 
+use crate::key_encryption::is_encrypted;
 use anyhow::anyhow;
 use fastcrypto::encoding::{Encoding, Hex};
 use fastcrypto::{secp256k1::Secp256k1KeyPair, traits::EncodeDecodeBase64};
@@ -33,12 +34,22 @@ pub fn read_authority_keypair_from_file<P: AsRef<std::path::Path>>(
     path: P,
 ) -> anyhow::Result<AuthorityKeyPair> {
     let contents = std::fs::read_to_string(path)?;
+    if is_encrypted(&contents) {
+        return Err(anyhow!(
+            "Key file is encrypted and must be unlocked with its passphrase"
+        ));
+    }
     AuthorityKeyPair::decode_base64(contents.as_str().trim()).map_err(|e| anyhow!(e))
 }
 
 /// Read from file as Base64 encoded `flag || privkey` and return a SuiKeypair.
 pub fn read_keypair_from_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<SuiKeyPair> {
     let contents = std::fs::read_to_string(path)?;
+    if is_encrypted(&contents) {
+        return Err(anyhow!(
+            "Key file is encrypted and must be unlocked with its passphrase"
+        ));
+    }
     SuiKeyPair::decode_base64(contents.as_str().trim()).map_err(|e| anyhow!(e))
 }
 
@@ -3,6 +3,7 @@
 
 pub mod external;
 pub mod key_derive;
+pub mod key_encryption;
 pub mod key_identity;
 pub mod keypair_file;
 pub mod keystore;
@@ -6,18 +6,24 @@ use axum::{
     extract::{Query, State},
     http::StatusCode,
     routing::{get, post},
-    Router,
+    Json, Router,
 };
 use base64::Engine;
 use humantime::parse_duration;
 use serde::Deserialize;
 use std::sync::Arc;
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroUsize,
     str::FromStr,
 };
+use sui_config::node::ListenAddresses;
+use sui_core::execution_scheduler::{
+    SchedulerQueueSnapshot, WithdrawAwaitingSettlement, WithdrawDrainStatus,
+};
 use sui_types::{
-    base_types::AuthorityName,
+    base_types::{AuthorityName, ObjectID},
     crypto::{RandomnessPartialSignature, RandomnessRound, RandomnessSignature},
     digests::TransactionDigest,
     error::SuiError,
@@ -50,6 +56,11 @@ use tracing::info;
 //
 //   $ curl 'http://127.0.0.1:1337/node-config'
 //
+// View the addresses the node's listeners are bound to, after any port conflicts at startup were
+// resolved by the port conflict policy:
+//
+//   $ curl 'http://127.0.0.1:1337/listen-addresses'
+//
 // Set a time-limited tracing config. After the duration expires, tracing will be disabled
 // automatically.
 //
@@ -77,6 +88,32 @@ use tracing::info;
 // Reconfigure traffic control policy
 //
 //  $ curl 'http://127.0.0.1:1337/traffic-control?error_threshold=100&spam_threshold=100&dry_run=true'
+//
+// View the depth of each execution scheduler queue, and the transaction waiting longest in it
+//
+//  $ curl 'http://127.0.0.1:1337/scheduler/queues'
+//
+// View the limits on how many transactions calling into each package can be executing at once
+//
+//  $ curl 'http://127.0.0.1:1337/scheduler/package-limits'
+//
+// Limit package 0x123 to 8 transactions executing at once, or remove its limit
+//
+//  $ curl -X POST 'http://127.0.0.1:1337/scheduler/package-limits?package=0x123&limit=8'
+//  $ curl -X POST 'http://127.0.0.1:1337/scheduler/package-limits?package=0x123'
+//
+// Stop scheduling new balance withdraws ahead of a restart, and check whether the withdraws
+// already scheduled have settled ("drained": true), or resume scheduling withdraws
+//
+//  $ curl -X POST 'http://127.0.0.1:1337/scheduler/withdraw-drain?enabled=true'
+//  $ curl 'http://127.0.0.1:1337/scheduler/withdraw-drain'
+//  $ curl -X POST 'http://127.0.0.1:1337/scheduler/withdraw-drain?enabled=false'
+//
+// View the balance withdraws that have been waiting for their accumulator version to be settled
+// through at least the default number of settlements, or through at least 5 settlements
+//
+//  $ curl 'http://127.0.0.1:1337/scheduler/withdraw-settlement-waits'
+//  $ curl 'http://127.0.0.1:1337/scheduler/withdraw-settlement-waits?min_settlements=5'
 
 const LOGGING_ROUTE: &str = "/logging";
 const TRACING_ROUTE: &str = "/enable-tracing";
@@ -86,12 +123,17 @@ const CLEAR_BUFFER_STAKE_ROUTE: &str = "/clear-override-buffer-stake";
 const FORCE_CLOSE_EPOCH: &str = "/force-close-epoch";
 const CAPABILITIES: &str = "/capabilities";
 const NODE_CONFIG: &str = "/node-config";
+const LISTEN_ADDRESSES: &str = "/listen-addresses";
 const RANDOMNESS_PARTIAL_SIGS_ROUTE: &str = "/randomness-partial-sigs";
 const RANDOMNESS_INJECT_PARTIAL_SIGS_ROUTE: &str = "/randomness-inject-partial-sigs";
 const RANDOMNESS_INJECT_FULL_SIG_ROUTE: &str = "/randomness-inject-full-sig";
 const GET_TX_COST_ROUTE: &str = "/get-tx-cost";
 const DUMP_CONSENSUS_TX_COST_ESTIMATES_ROUTE: &str = "/dump-consensus-tx-cost-estimates";
 const TRAFFIC_CONTROL: &str = "/traffic-control";
+const SCHEDULER_QUEUES_ROUTE: &str = "/scheduler/queues";
+const SCHEDULER_PACKAGE_LIMITS_ROUTE: &str = "/scheduler/package-limits";
+const SCHEDULER_WITHDRAW_DRAIN_ROUTE: &str = "/scheduler/withdraw-drain";
+const SCHEDULER_WITHDRAW_SETTLEMENT_WAITS_ROUTE: &str = "/scheduler/withdraw-settlement-waits";
 
 struct AppState {
     node: Arc<SuiNode>,
@@ -110,6 +152,7 @@ pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: Tra
         .route(LOGGING_ROUTE, get(get_filter))
         .route(CAPABILITIES, get(capabilities))
         .route(NODE_CONFIG, get(node_config))
+        .route(LISTEN_ADDRESSES, get(listen_addresses))
         .route(LOGGING_ROUTE, post(set_filter))
         .route(
             SET_BUFFER_STAKE_ROUTE,
@@ -137,6 +180,15 @@ pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: Tra
             get(dump_consensus_tx_cost_estimates),
         )
         .route(TRAFFIC_CONTROL, post(traffic_control))
+        .route(SCHEDULER_QUEUES_ROUTE, get(scheduler_queues))
+        .route(SCHEDULER_PACKAGE_LIMITS_ROUTE, get(get_package_limits))
+        .route(SCHEDULER_PACKAGE_LIMITS_ROUTE, post(set_package_limit))
+        .route(SCHEDULER_WITHDRAW_DRAIN_ROUTE, get(get_withdraw_drain))
+        .route(SCHEDULER_WITHDRAW_DRAIN_ROUTE, post(set_withdraw_drain))
+        .route(
+            SCHEDULER_WITHDRAW_SETTLEMENT_WAITS_ROUTE,
+            get(get_withdraw_settlement_waits),
+        )
         .with_state(Arc::new(app_state));
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
@@ -278,6 +330,10 @@ async fn node_config(State(state): State<Arc<AppState>>) -> (StatusCode, String)
     (StatusCode::OK, format!("{:#?}\n", node_config))
 }
 
+async fn listen_addresses(State(state): State<Arc<AppState>>) -> Json<ListenAddresses> {
+    Json(state.node.config.listen_addresses())
+}
+
 #[derive(Deserialize)]
 struct Epoch {
     epoch: u64,
@@ -523,3 +579,101 @@ async fn traffic_control(
         Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
     }
 }
+
+async fn scheduler_queues(State(state): State<Arc<AppState>>) -> Json<Vec<SchedulerQueueSnapshot>> {
+    Json(state.node.state().execution_scheduler().queue_snapshots())
+}
+
+async fn get_package_limits(
+    State(state): State<Arc<AppState>>,
+) -> Json<BTreeMap<ObjectID, NonZeroUsize>> {
+    Json(state.node.state().execution_scheduler().package_limits())
+}
+
+#[derive(Deserialize)]
+struct SetPackageLimit {
+    package: ObjectID,
+    // Removes the package's limit if unset.
+    limit: Option<NonZeroUsize>,
+}
+
+async fn set_package_limit(
+    State(state): State<Arc<AppState>>,
+    args: Query<SetPackageLimit>,
+) -> (StatusCode, String) {
+    let Query(SetPackageLimit { package, limit }) = args;
+    let scheduler = state.node.state().execution_scheduler();
+
+    let mut limits = scheduler.package_limits();
+    let message = if let Some(limit) = limit {
+        limits.insert(package, limit);
+        format!("package {package} limited to {limit} executing transactions\n")
+    } else {
+        limits.remove(&package);
+        format!("package {package} is no longer limited\n")
+    };
+
+    scheduler.set_package_limits(&limits);
+    (StatusCode::OK, message)
+}
+
+const WITHDRAW_SCHEDULER_DISABLED: &str = "balance withdraw scheduler is not enabled\n";
+
+async fn get_withdraw_drain(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<WithdrawDrainStatus>, (StatusCode, String)> {
+    state
+        .node
+        .state()
+        .execution_scheduler()
+        .withdraw_drain_status()
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            WITHDRAW_SCHEDULER_DISABLED.to_string(),
+        ))
+}
+
+#[derive(Deserialize)]
+struct SetWithdrawDrain {
+    enabled: bool,
+}
+
+async fn set_withdraw_drain(
+    State(state): State<Arc<AppState>>,
+    args: Query<SetWithdrawDrain>,
+) -> Result<Json<WithdrawDrainStatus>, (StatusCode, String)> {
+    let Query(SetWithdrawDrain { enabled }) = args;
+    state
+        .node
+        .state()
+        .execution_scheduler()
+        .set_withdraw_draining(enabled)
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            WITHDRAW_SCHEDULER_DISABLED.to_string(),
+        ))
+}
+
+#[derive(Deserialize)]
+struct GetWithdrawSettlementWaits {
+    min_settlements: Option<u64>,
+}
+
+async fn get_withdraw_settlement_waits(
+    State(state): State<Arc<AppState>>,
+    args: Query<GetWithdrawSettlementWaits>,
+) -> Result<Json<Vec<WithdrawAwaitingSettlement>>, (StatusCode, String)> {
+    let Query(GetWithdrawSettlementWaits { min_settlements }) = args;
+    state
+        .node
+        .state()
+        .execution_scheduler()
+        .withdraws_awaiting_settlement(min_settlements)
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            WITHDRAW_SCHEDULER_DISABLED.to_string(),
+        ))
+}
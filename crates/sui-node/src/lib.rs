@@ -75,7 +75,7 @@ use mysten_service::server_timing::server_timing_middleware;
 use sui_config::node::{DBCheckpointConfig, RunWithRange};
 use sui_config::node::{ForkCrashBehavior, ForkRecoveryConfig};
 use sui_config::node_config_metrics::NodeConfigMetrics;
-use sui_config::{ConsensusConfig, NodeConfig};
+use sui_config::{ConsensusConfig, NodeConfig, TlsConfig, TlsService};
 use sui_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use sui_core::authority::epoch_start_configuration::EpochStartConfigTrait;
@@ -2589,13 +2589,26 @@ async fn build_http_servers(
 
     router = router.merge(rpc_router).layer(layers);
 
-    let https = if let Some((tls_config, https_address)) = config
-        .rpc()
-        .and_then(|config| config.tls_config().map(|tls| (tls, config.https_address())))
-    {
-        let https = sui_http::Builder::new()
-            .tls_single_cert(tls_config.cert(), tls_config.key())
-            .and_then(|builder| builder.serve(https_address, router.clone()))
+    // TLS settings specific to the RPC server take precedence over the node's.
+    let https_builder = match config.rpc().and_then(|config| config.tls_config()) {
+        Some(tls_config) => Some(
+            sui_http::Builder::new()
+                .tls_single_cert(tls_config.cert(), tls_config.key())
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ),
+        None => config
+            .tls(TlsService::Rpc)
+            .map(|tls| tls_server_builder(tls, TlsService::Rpc))
+            .transpose()?,
+    };
+
+    let https = if let Some(builder) = https_builder {
+        let https_address = config
+            .rpc()
+            .map(|config| config.https_address())
+            .unwrap_or_else(|| sui_config::RpcConfig::default().https_address());
+        let https = builder
+            .serve(https_address, router.clone())
             .map_err(|e| anyhow::anyhow!(e))?;
 
         info!(
@@ -2628,6 +2641,42 @@ async fn build_http_servers(
     ))
 }
 
+/// A builder for servers that serve `service` over TLS, according to the node's TLS settings.
+pub fn tls_server_builder(tls: &TlsConfig, service: TlsService) -> Result<sui_http::Builder> {
+    tls.validate()?;
+    let tls_config = sui_http::tls::server_config_from_files(
+        &tls.cert,
+        &tls.key,
+        tls.client_ca(service).map(|ca| ca.as_path()),
+        tls.reload_interval(),
+    )
+    .map_err(|e| anyhow!("Failed to load TLS config for {service:?}: {e}"))?;
+
+    Ok(sui_http::Builder::new().tls_config(tls_config))
+}
+
+/// Start the server that exposes the node's metrics to Prometheus, on `config.metrics_address`,
+/// over TLS if the node's TLS settings include [TlsService::Metrics]. The TLS server is stopped
+/// when the returned handle is dropped.
+pub fn start_metrics_server(
+    config: &NodeConfig,
+) -> Result<(RegistryService, Option<sui_http::ServerHandle>)> {
+    let Some(tls) = config.tls(TlsService::Metrics) else {
+        let registry_service = mysten_metrics::start_prometheus_server(config.metrics_address);
+        return Ok((registry_service, None));
+    };
+
+    let registry_service = RegistryService::new(Registry::new());
+    let server = tls_server_builder(tls, TlsService::Metrics)?
+        .serve(
+            config.metrics_address,
+            mysten_metrics::metrics_router(registry_service.clone()),
+        )
+        .map_err(|e| anyhow!(e))?;
+
+    Ok((registry_service, Some(server)))
+}
+
 #[cfg(not(test))]
 fn max_tx_per_checkpoint(protocol_config: &ProtocolConfig) -> usize {
     protocol_config.max_transactions_per_checkpoint() as usize
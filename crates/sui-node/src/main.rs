@@ -1,17 +1,22 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Context;
+use aws_sdk_kms::{primitives::Blob, Client as KmsClient};
 use clap::{ArgGroup, Parser};
+use fastcrypto::encoding::{Base64, Encoding};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use sui_rpc_api::ServerVersion;
 use tokio::sync::broadcast;
 use tokio::time::sleep;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use zeroize::Zeroizing;
 
 use mysten_common::sync::async_once_cell::AsyncOnceCell;
-use sui_config::node::RunWithRange;
+use sui_config::config_overrides::ConfigOverride;
+use sui_config::node::{KeyEncryptionConfig, RunWithRange, NODE_CONFIG_ENV_PREFIX};
 use sui_config::{Config, NodeConfig};
 use sui_core::runtime::SuiRuntimes;
 use sui_telemetry::send_telemetry_event;
@@ -33,6 +38,12 @@ struct Args {
     #[clap(long)]
     pub config_path: PathBuf,
 
+    /// Override a field of the config file, as `path=value`, with `.` between the segments of
+    /// the path, e.g. `--config-override db-path=/opt/sui/db`. Takes precedence over
+    /// `SUI_NODE__<PATH>` environment variables, which take precedence over the file.
+    #[clap(long = "config-override", value_name = "PATH=VALUE")]
+    config_overrides: Vec<ConfigOverride>,
+
     #[clap(long, help = "Specify address to listen on")]
     listen_address: Option<Multiaddr>,
 
@@ -56,13 +67,23 @@ fn main() {
     // TODO: re-enable after we figure out how to eliminate crashes in prod because of this.
     // ProtocolConfig::poison_get_for_min_version();
     let args = Args::parse();
-    let mut config = NodeConfig::load(&args.config_path).unwrap();
+    let mut config = NodeConfig::load_with_overrides(
+        &args.config_path,
+        NODE_CONFIG_ENV_PREFIX,
+        &args.config_overrides,
+    )
+    .unwrap();
     assert!(
         config.supported_protocol_versions.is_none(),
         "supported_protocol_versions cannot be read from the config file"
     );
     config.supported_protocol_versions = Some(SupportedProtocolVersions::SYSTEM_DEFAULT);
 
+    if let Err(e) = unlock_key_pairs(&config) {
+        eprintln!("Failed to unlock key files: {e:#}");
+        std::process::exit(1);
+    }
+
     // match run_with_range args
     // this means that we always modify the config used to start the node
     // for run_with_range. i.e if this is set in the config, it is ignored. only the cli args
@@ -75,9 +96,16 @@ fn main() {
         _ => config.run_with_range = None,
     };
 
+    if let Some(listen_address) = args.listen_address {
+        config.network_address = listen_address;
+    }
+
+    // Resolved before anything binds, but only logged once logging is initialized.
+    let port_rebinds = config.resolve_port_conflicts().unwrap();
+
     let runtimes = SuiRuntimes::new(&config);
     let metrics_rt = runtimes.metrics.enter();
-    let registry_service = mysten_metrics::start_prometheus_server(config.metrics_address);
+    let (registry_service, metrics_server) = sui_node::start_metrics_server(&config).unwrap();
     let prometheus_registry = registry_service.default_registry();
 
     // Initialize logging
@@ -94,8 +122,24 @@ fn main() {
         config.supported_protocol_versions
     );
 
+    for rebind in &port_rebinds {
+        warn!(
+            listener = rebind.listener,
+            configured = %rebind.configured,
+            bound = %rebind.bound,
+            policy = ?config.port_conflict_policy,
+            "Configured port is taken, listening on another port",
+        );
+    }
+    info!(addresses = ?config.listen_addresses(), "Listen addresses");
+
     info!(
-        "Started Prometheus HTTP endpoint at {}",
+        "Started Prometheus {} endpoint at {}",
+        if metrics_server.is_some() {
+            "HTTPS"
+        } else {
+            "HTTP"
+        },
         config.metrics_address
     );
 
@@ -113,10 +157,6 @@ fn main() {
         }
     }
 
-    if let Some(listen_address) = args.listen_address {
-        config.network_address = listen_address;
-    }
-
     let is_validator = config.consensus_config().is_some();
 
     let admin_interface_port = config.admin_interface_port;
@@ -223,3 +263,45 @@ async fn wait_termination(mut shutdown_rx: tokio::sync::broadcast::Receiver<()>)
         _ = shutdown_recv => {},
     }
 }
+
+/// Unlock the node's encrypted key files, if it is configured with key encryption.
+fn unlock_key_pairs(config: &NodeConfig) -> anyhow::Result<()> {
+    let Some(key_encryption) = &config.key_encryption else {
+        return Ok(());
+    };
+    let passphrase = key_passphrase(key_encryption)?;
+    config.unlock_key_pairs(&passphrase)
+}
+
+/// Fetch the passphrase that unlocks the node's encrypted key files.
+fn key_passphrase(key_encryption: &KeyEncryptionConfig) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    match key_encryption {
+        KeyEncryptionConfig::Env { passphrase_env_var } => std::env::var(passphrase_env_var)
+            .map(|passphrase| Zeroizing::new(passphrase.into_bytes()))
+            .with_context(|| format!("Key passphrase not found in ${passphrase_env_var}")),
+        KeyEncryptionConfig::Kms {
+            key_id,
+            encrypted_passphrase,
+        } => {
+            let ciphertext = Base64::decode(encrypted_passphrase)
+                .map_err(|e| anyhow::anyhow!("Invalid encrypted passphrase: {e}"))?;
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?
+                .block_on(async {
+                    let config = aws_config::load_from_env().await;
+                    let response = KmsClient::new(&config)
+                        .decrypt()
+                        .key_id(key_id)
+                        .ciphertext_blob(Blob::new(ciphertext))
+                        .send()
+                        .await
+                        .context("Failed to decrypt key passphrase with KMS")?;
+                    let plaintext = response
+                        .plaintext
+                        .context("KMS returned no key passphrase")?;
+                    Ok::<_, anyhow::Error>(Zeroizing::new(plaintext.into_inner()))
+                })
+        }
+    }
+}
@@ -1,6 +1,8 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::mem;
+
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned, ToTokens};
 use syn::{
@@ -259,6 +261,118 @@ pub fn sim_test(args: TokenStream, item: TokenStream) -> TokenStream {
     result.into()
 }
 
+/// The feature_matrix_test macro expands a test into a `#[sim_test]` for each combination of the
+/// protocol feature flags listed in its `features` argument being enabled or disabled. It is
+/// re-exported by `sui_macros`, e.g.
+///
+/// ```ignore
+/// use sui_macros::feature_matrix_test;
+///
+/// #[feature_matrix_test(features = [enable_accumulators])]
+/// async fn test_withdraw() { ... }
+/// ```
+///
+/// expands into the tests `test_withdraw::enable_accumulators_on` and
+/// `test_withdraw::enable_accumulators_off`. Each one applies its flags as protocol config
+/// overrides, so they take effect on any `TestCluster` the test builds, and then runs the
+/// original test function, which must not apply overrides of its own. Features are set with
+/// `ProtocolConfig::set_feature_for_testing`, which also sets the configs a feature depends on.
+/// Any other arguments are passed on to `#[sim_test]`.
+#[proc_macro_attribute]
+pub fn feature_matrix_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(item as syn::ItemFn);
+    let arg_parser = Punctuated::<syn::Meta, Token![,]>::parse_terminated;
+    let args = match arg_parser.parse(args) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let mut features = vec![];
+    let mut sim_test_args = vec![];
+    for arg in args {
+        let syn::Meta::NameValue(syn::MetaNameValue { path, value, .. }) = &arg else {
+            sim_test_args.push(arg);
+            continue;
+        };
+
+        if !path.is_ident("features") {
+            sim_test_args.push(arg);
+            continue;
+        }
+
+        let Expr::Array(array) = value else {
+            return syn::Error::new(value.span(), "expected a list of feature flags")
+                .to_compile_error()
+                .into();
+        };
+
+        for elem in &array.elems {
+            match elem {
+                Expr::Path(path) if path.path.get_ident().is_some() => {
+                    features.push(path.path.get_ident().unwrap().clone());
+                }
+                _ => {
+                    return syn::Error::new(elem.span(), "expected a feature flag name")
+                        .to_compile_error()
+                        .into();
+                }
+            }
+        }
+    }
+
+    if features.is_empty() {
+        return syn::Error::new(
+            input.sig.ident.span(),
+            "feature_matrix_test requires `features = [...]`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // Attributes such as `#[ignore]` apply to each of the tests, rather than the function they
+    // share.
+    let attrs = mem::take(&mut input.attrs);
+    let fn_name = &input.sig.ident;
+    let output = &input.sig.output;
+
+    let variants = (0..1u32 << features.len()).map(|mask| {
+        let enabled: Vec<_> = (0..features.len()).map(|i| mask & (1 << i) != 0).collect();
+
+        let name = features
+            .iter()
+            .zip(&enabled)
+            .map(|(feature, on)| format!("{}_{}", feature, if *on { "on" } else { "off" }))
+            .collect::<Vec<_>>()
+            .join("_");
+        let name = syn::Ident::new(&name, fn_name.span());
+
+        quote! {
+            #[::sui_macros::sim_test(#(#sim_test_args),*)]
+            #(#attrs)*
+            async fn #name() #output {
+                let _guard = ::sui_protocol_config::ProtocolConfig::apply_overrides_for_testing(
+                    |_, mut config| {
+                        #(config.set_feature_for_testing(stringify!(#features), #enabled);)*
+                        config
+                    },
+                );
+
+                super::#fn_name().await
+            }
+        }
+    });
+
+    let result = quote! {
+        #input
+
+        mod #fn_name {
+            #(#variants)*
+        }
+    };
+
+    result.into()
+}
+
 #[proc_macro]
 pub fn checked_arithmetic(input: TokenStream) -> TokenStream {
     let input_file = CheckArithmetic.fold_file(parse_macro_input!(input));
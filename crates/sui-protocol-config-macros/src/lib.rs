@@ -315,9 +315,14 @@ pub fn feature_flag_getters_macro(input: TokenStream) -> TokenStream {
                                 quote! {
                                     stringify!(#field_name) => Some(self.#field_name),
                                 },
-                                quote! {
-                                    stringify!(#field_name)
-                                },
+                                (
+                                    quote! {
+                                        stringify!(#field_name)
+                                    },
+                                    quote! {
+                                        stringify!(#field_name) => self.#field_name = val,
+                                    },
+                                ),
                             ),
                         ))
                     }
@@ -329,8 +334,11 @@ pub fn feature_flag_getters_macro(input: TokenStream) -> TokenStream {
         _ => panic!("Only structs supported."),
     };
 
-    let (by_fn_getters, (string_name_getters, field_names)): (Vec<_>, (Vec<_>, Vec<_>)) =
-        getters.unzip();
+    #[allow(clippy::type_complexity)]
+    let (by_fn_getters, (string_name_getters, (field_names, string_name_setters))): (
+        Vec<_>,
+        (Vec<_>, (Vec<_>, Vec<_>)),
+    ) = getters.unzip();
 
     let output = quote! {
         // For each getter, expand it out into a function in the impl block
@@ -352,6 +360,14 @@ pub fn feature_flag_getters_macro(input: TokenStream) -> TokenStream {
                     #(((#field_names).to_owned(), self.lookup_attr((#field_names).to_owned()).unwrap()),)*
                     ].into_iter().collect()
             }
+
+            /// Set a feature flag by its string representation, for testing
+            pub fn set_attr_for_testing(&mut self, attr: String, val: bool) {
+                match attr.as_str() {
+                    #(#string_name_setters)*
+                    _ => panic!("Attempting to set unknown feature flag: {}", attr),
+                }
+            }
         }
     };
 
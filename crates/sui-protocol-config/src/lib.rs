@@ -1604,6 +1604,38 @@ pub struct ProtocolConfig {
     /// The number of commits to consider when computing a deterministic commit rate.
     consensus_commit_rate_estimation_window_size: Option<u32>,
 
+    /// The most a single transaction can reserve in total across all the accounts it withdraws
+    /// from. Transactions that reserve more are rejected when their withdraws are scheduled.
+    /// Unlimited if not set.
+    max_withdraw_reservation_per_tx: Option<u64>,
+
+    /// How many of the most recently settled accumulator versions a withdraw can still be
+    /// scheduled at, if it arrives after its version was settled. It is checked against the
+    /// balances at that version, as it would have been had it arrived on time. Withdraws that
+    /// arrive later than that are treated as already executed. Disabled if not set.
+    late_withdraw_versions: Option<u64>,
+
+    /// Maximum number of balance withdraw reservations a transaction can make, which also bounds
+    /// the number of distinct accounts it withdraws from. Must be set in the same version that
+    /// enables accumulators. Withdraws are rejected if it is not set.
+    max_balance_withdraws_per_tx: Option<u64>,
+
+    /// Number of balance withdraw transactions waiting in the commits that have not been built
+    /// into a checkpoint yet, at which the checkpoint builder considers balances to be under
+    /// withdraw pressure, and settles them sooner. Disabled if not set.
+    settlement_pressure_withdraw_threshold: Option<u64>,
+
+    /// Minimum interval of commit timestamps between consecutive checkpoints while balances are
+    /// under withdraw pressure. Replaces `min_checkpoint_interval_ms` in that case, if it is
+    /// shorter.
+    settlement_pressure_checkpoint_interval_ms: Option<u64>,
+
+    /// Minimum interval of commit timestamps between consecutive checkpoints while no balance
+    /// withdraw transactions are waiting to be settled. Replaces `min_checkpoint_interval_ms` in
+    /// that case, if it is longer. A withdraw arriving in a later commit returns the interval to
+    /// `min_checkpoint_interval_ms`, so it is never settled later than without this setting.
+    settlement_idle_checkpoint_interval_ms: Option<u64>,
+
     /// A list of effective AliasedAddress.
     /// For each pair, `aliased` is allowed to act as `original` for any of the transaction digests
     /// listed in `tx_digests`
@@ -2745,6 +2777,18 @@ impl ProtocolConfig {
 
             consensus_commit_rate_estimation_window_size: None,
 
+            max_withdraw_reservation_per_tx: None,
+
+            late_withdraw_versions: None,
+
+            max_balance_withdraws_per_tx: None,
+
+            settlement_pressure_withdraw_threshold: None,
+
+            settlement_pressure_checkpoint_interval_ms: None,
+
+            settlement_idle_checkpoint_interval_ms: None,
+
             aliased_addresses: vec![],
             // When adding a new constant, set it to None in the earliest version, like this:
             // new_constant: None,
@@ -4050,6 +4094,11 @@ impl ProtocolConfig {
 // This is only needed for feature_flags. Please suffix each setter with `_for_testing`.
 // Non-feature_flags should already have test setters defined through macros.
 impl ProtocolConfig {
+    /// Set the feature flag named `flag`, panicking if there is no such flag.
+    pub fn set_feature_flag_for_testing(&mut self, flag: String, val: bool) {
+        self.feature_flags.set_attr_for_testing(flag, val)
+    }
+
     pub fn set_advance_to_highest_supported_protocol_version_for_testing(&mut self, val: bool) {
         self.feature_flags
             .advance_to_highest_supported_protocol_version = val
@@ -4186,6 +4235,17 @@ impl ProtocolConfig {
     pub fn enable_accumulators_for_testing(&mut self) {
         self.feature_flags.enable_accumulators = true;
         self.feature_flags.allow_private_accumulator_entrypoints = true;
+        self.max_balance_withdraws_per_tx = Some(10);
+    }
+
+    /// Enable or disable the feature named `flag`. Enabling a feature that depends on other
+    /// configs goes through its own `*_for_testing` setup, so that the result matches a real
+    /// config with the feature enabled. Panics if there is no such flag.
+    pub fn set_feature_for_testing(&mut self, flag: &str, enabled: bool) {
+        match (flag, enabled) {
+            ("enable_accumulators", true) => self.enable_accumulators_for_testing(),
+            _ => self.set_feature_flag_for_testing(flag.to_owned(), enabled),
+        }
     }
 }
 
@@ -4346,6 +4406,38 @@ mod test {
         assert_eq!(prot.max_arguments(), 456);
     }
 
+    #[test]
+    fn set_feature_for_testing() {
+        let mut config = ProtocolConfig::get_for_max_version_UNSAFE();
+        config.set_feature_for_testing("enable_accumulators", true);
+        assert!(config.enable_accumulators());
+        assert!(config.max_balance_withdraws_per_tx_as_option().is_some());
+
+        config.set_feature_for_testing("enable_accumulators", false);
+        assert!(!config.enable_accumulators());
+
+        config.set_feature_for_testing("enable_party_transfer", false);
+        assert!(!config.enable_party_transfer());
+    }
+
+    #[test]
+    fn accumulators_configure_withdraw_limit() {
+        for chain_id in &[Chain::Unknown, Chain::Mainnet, Chain::Testnet] {
+            for i in MIN_PROTOCOL_VERSION..=MAX_PROTOCOL_VERSION {
+                let config = ProtocolConfig::get_for_version(ProtocolVersion::new(i), *chain_id);
+                assert!(
+                    !config.enable_accumulators()
+                        || config.max_balance_withdraws_per_tx_as_option().is_some(),
+                    "version {i} on {chain_id:?} enables accumulators without max_balance_withdraws_per_tx",
+                );
+            }
+        }
+
+        let mut config = ProtocolConfig::get_for_max_version_UNSAFE();
+        config.enable_accumulators_for_testing();
+        assert!(config.max_balance_withdraws_per_tx_as_option().is_some());
+    }
+
     #[test]
     #[should_panic(expected = "unsupported version")]
     fn max_version_test() {
@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{env, path::PathBuf};
+
+use tonic_build::manual::{Builder, Method, Service};
+
+type Result<T> = ::std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn main() -> Result<()> {
+    let out_dir = if env::var("DUMP_GENERATED_GRPC").is_ok() {
+        PathBuf::from("")
+    } else {
+        PathBuf::from(env::var("OUT_DIR")?)
+    };
+
+    let prost_codec_path = "tonic::codec::ProstCodec";
+
+    let schedule_status_service = Service::builder()
+        .name("ScheduleStatusService")
+        .package("sui.rpc.alpha")
+        .comment("Transaction execution, with updates on how transactions are scheduled")
+        .method(
+            Method::builder()
+                .name("execute_transactions")
+                .route_name("ExecuteTransactions")
+                .input_type("sui_rpc::proto::sui::rpc::v2beta2::ExecuteTransactionRequest")
+                .output_type("crate::grpc::alpha::ExecuteTransactionUpdate")
+                .codec_path(prost_codec_path)
+                .client_streaming()
+                .server_streaming()
+                .build(),
+        )
+        .build();
+
+    let address_balance_service = Service::builder()
+        .name("AddressBalanceService")
+        .package("sui.rpc.alpha")
+        .comment("Address balances, as seen by the balance withdraw scheduler")
+        .method(
+            Method::builder()
+                .name("get_spendable_balance")
+                .route_name("GetSpendableBalance")
+                .input_type("crate::grpc::alpha::GetSpendableBalanceRequest")
+                .output_type("crate::grpc::alpha::GetSpendableBalanceResponse")
+                .codec_path(prost_codec_path)
+                .build(),
+        )
+        .build();
+
+    Builder::new()
+        .out_dir(&out_dir)
+        .compile(&[schedule_status_service, address_balance_service]);
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=DUMP_GENERATED_GRPC");
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::AddressBalanceService;
+use super::GetSpendableBalanceRequest;
+use super::GetSpendableBalanceResponse;
+use super::SpendableBalance;
+use crate::ErrorReason;
+use crate::Result;
+use crate::RpcError;
+use crate::RpcService;
+use sui_rpc::proto::google::rpc::bad_request::FieldViolation;
+use sui_sdk_types::StructTag;
+use sui_types::base_types::SuiAddress;
+use sui_types::sui_sdk_types_conversions::struct_tag_sdk_to_core;
+
+#[tonic::async_trait]
+impl AddressBalanceService for RpcService {
+    /// The address balance of an owner in a coin type, along with the deposits into it and
+    /// withdraws from it that this node knows about, but that have not been settled yet, and how
+    /// much of it a new transaction can withdraw.
+    async fn get_spendable_balance(
+        &self,
+        request: tonic::Request<GetSpendableBalanceRequest>,
+    ) -> Result<tonic::Response<GetSpendableBalanceResponse>, tonic::Status> {
+        get_spendable_balance(self, request.into_inner())
+            .map(tonic::Response::new)
+            .map_err(Into::into)
+    }
+}
+
+#[tracing::instrument(skip(service))]
+fn get_spendable_balance(
+    service: &RpcService,
+    request: GetSpendableBalanceRequest,
+) -> Result<GetSpendableBalanceResponse> {
+    let owner = request
+        .owner
+        .as_ref()
+        .ok_or_else(|| {
+            FieldViolation::new("owner")
+                .with_description("missing owner")
+                .with_reason(ErrorReason::FieldMissing)
+        })?
+        .parse::<SuiAddress>()
+        .map_err(|e| {
+            FieldViolation::new("owner")
+                .with_description(format!("invalid owner: {e}"))
+                .with_reason(ErrorReason::FieldInvalid)
+        })?;
+
+    let coin_type = request
+        .coin_type
+        .as_ref()
+        .ok_or_else(|| {
+            FieldViolation::new("coin_type")
+                .with_description("missing coin_type")
+                .with_reason(ErrorReason::FieldMissing)
+        })?
+        .parse::<StructTag>()
+        .map_err(|e| {
+            FieldViolation::new("coin_type")
+                .with_description(format!("invalid coin_type: {e}"))
+                .with_reason(ErrorReason::FieldInvalid)
+        })?;
+
+    let core_coin_type = struct_tag_sdk_to_core(coin_type.clone())?;
+
+    let balance = service
+        .reader
+        .inner()
+        .get_spendable_balance(&owner, &core_coin_type)?
+        .ok_or_else(|| {
+            RpcError::new(
+                tonic::Code::Unimplemented,
+                "spendable balances are not available on this node",
+            )
+        })?;
+
+    Ok(GetSpendableBalanceResponse {
+        balance: Some(SpendableBalance {
+            coin_type: Some(coin_type.to_string()),
+            settled: Some(balance.settled),
+            pending_deposits: Some(balance.pending_deposits),
+            total: Some(balance.total),
+            reserved: Some(balance.reserved),
+            spendable: Some(balance.spendable),
+        }),
+    })
+}
@@ -0,0 +1,120 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Services that are not part of the `sui.rpc.v2beta2` protos yet. Their messages are defined
+//! here, and their service definitions are generated by the build script.
+
+mod address_balance_service;
+mod schedule_status_service;
+
+mod generated {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/sui.rpc.alpha.ScheduleStatusService.rs"
+    ));
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/sui.rpc.alpha.AddressBalanceService.rs"
+    ));
+}
+
+pub use generated::{
+    address_balance_service_client::AddressBalanceServiceClient,
+    address_balance_service_server::{AddressBalanceService, AddressBalanceServiceServer},
+    schedule_status_service_client::ScheduleStatusServiceClient,
+    schedule_status_service_server::{ScheduleStatusService, ScheduleStatusServiceServer},
+};
+
+/// An update on one of the transactions sent to `ScheduleStatusService.ExecuteTransactions`.
+/// Every transaction gets zero or more schedule statuses, followed by either its response or an
+/// error.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ExecuteTransactionUpdate {
+    /// The position of the request for this transaction among the requests sent on the stream,
+    /// starting from 0.
+    #[prost(uint64, optional, tag = "1")]
+    pub request_index: Option<u64>,
+    /// The digest of the transaction, unless the request did not contain a valid transaction.
+    #[prost(string, optional, tag = "2")]
+    pub digest: Option<String>,
+    #[prost(oneof = "execute_transaction_update::Update", tags = "3, 4, 5")]
+    pub update: Option<execute_transaction_update::Update>,
+}
+
+pub mod execute_transaction_update {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Update {
+        /// How the transaction has been scheduled for execution by this node.
+        #[prost(enumeration = "super::ScheduleStatus", tag = "3")]
+        ScheduleStatus(i32),
+        /// The response to the request, once the transaction's effects are final.
+        #[prost(message, tag = "4")]
+        Response(sui_rpc::proto::sui::rpc::v2beta2::ExecuteTransactionResponse),
+        /// Why the request failed.
+        #[prost(message, tag = "5")]
+        Error(crate::proto::google::rpc::Status),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ScheduleStatus {
+    Unknown = 0,
+    /// The transaction is waiting for the balances it withdraws from to be reserved.
+    BalanceReservationPending = 1,
+    /// The transaction's withdraws have been reserved, and it will be executed.
+    SufficientBalance = 2,
+    /// The transaction's withdraws could not be reserved, so it will fail without executing.
+    InsufficientBalance = 3,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetSpendableBalanceRequest {
+    /// The address that owns the balance.
+    #[prost(string, optional, tag = "1")]
+    pub owner: Option<String>,
+    /// The type of the coin the balance is in, e.g. `0x2::sui::SUI`.
+    #[prost(string, optional, tag = "2")]
+    pub coin_type: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetSpendableBalanceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub balance: Option<SpendableBalance>,
+}
+
+/// An address balance, and how much of it can be withdrawn by a new transaction. The settled
+/// balance, pending deposits and reservations are all as of the same settled accumulator version.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SpendableBalance {
+    /// The type of the coin the balance is in.
+    #[prost(string, optional, tag = "1")]
+    pub coin_type: Option<String>,
+    /// The balance as of the last settled accumulator version.
+    #[prost(uint64, optional, tag = "2")]
+    pub settled: Option<u64>,
+    /// Deposits by transactions that have executed, but have not been settled yet.
+    #[prost(uint64, optional, tag = "3")]
+    pub pending_deposits: Option<u64>,
+    /// The settled balance plus pending deposits.
+    #[prost(uint64, optional, tag = "4")]
+    pub total: Option<u64>,
+    /// The amount reserved by withdraws that have been scheduled, but not settled yet.
+    #[prost(uint64, optional, tag = "5")]
+    pub reserved: Option<u64>,
+    /// The amount available to new withdraws, i.e. `total - reserved`.
+    #[prost(uint64, optional, tag = "6")]
+    pub spendable: Option<u64>,
+}
+
+impl From<sui_types::transaction_executor::TransactionScheduleStatus> for ScheduleStatus {
+    fn from(status: sui_types::transaction_executor::TransactionScheduleStatus) -> Self {
+        use sui_types::transaction_executor::TransactionScheduleStatus as S;
+        match status {
+            S::BalanceReservationPending => Self::BalanceReservationPending,
+            S::SufficientBalance => Self::SufficientBalance,
+            S::InsufficientBalance => Self::InsufficientBalance,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use super::execute_transaction_update::Update;
+use super::ExecuteTransactionUpdate;
+use super::ScheduleStatus;
+use super::ScheduleStatusService;
+use crate::grpc::v2beta2::transaction_execution_service::execute_transaction;
+use crate::RpcError;
+use crate::RpcService;
+use sui_rpc::proto::sui::rpc::v2beta2::ExecuteTransactionRequest;
+use sui_rpc::proto::sui::rpc::v2beta2::ExecuteTransactionResponse;
+use sui_types::digests::TransactionDigest;
+use sui_types::transaction_executor::TransactionExecutor;
+use sui_types::transaction_executor::TransactionScheduleStatus;
+use sui_types::transaction_executor::TransactionScheduleUpdate;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio_stream::StreamExt;
+
+/// Number of transactions from a single stream that can be executing at once. Requests are not
+/// read from the stream while this many are in flight.
+const MAX_CONCURRENT_EXECUTIONS: usize = 32;
+
+/// Number of updates that can be waiting to be sent to a client. Executions wait for room in the
+/// channel before sending more updates, so a client that does not read its updates holds up its
+/// own executions, and through them, the reading of its requests.
+const MAX_BUFFERED_UPDATES: usize = 1024;
+
+type UpdateResult = Result<ExecuteTransactionUpdate, tonic::Status>;
+
+#[tonic::async_trait]
+impl ScheduleStatusService for RpcService {
+    /// Server streaming response type for the ExecuteTransactions method.
+    type ExecuteTransactionsStream = Pin<Box<dyn tokio_stream::Stream<Item = UpdateResult> + Send>>;
+
+    /// Execute each transaction sent on the request stream, like
+    /// `TransactionExecutionService.ExecuteTransaction` does, and stream back updates on how it
+    /// is scheduled for execution by this node while it waits for its effects to be final,
+    /// followed by its response.
+    ///
+    /// Up to `MAX_CONCURRENT_EXECUTIONS` transactions from the stream are executed concurrently,
+    /// so updates for different transactions can be interleaved. Schedule statuses are only
+    /// reported by a node that schedules the transaction's withdraws itself, before its effects
+    /// are final. Fullnodes execute transactions from their certified effects, which already
+    /// record whether the withdraws were reserved, so they never report any statuses: only the
+    /// response.
+    async fn execute_transactions(
+        &self,
+        request: tonic::Request<tonic::Streaming<ExecuteTransactionRequest>>,
+    ) -> Result<tonic::Response<Self::ExecuteTransactionsStream>, tonic::Status> {
+        let executor = self
+            .executor
+            .clone()
+            .ok_or_else(|| tonic::Status::unimplemented("no transaction executor"))?;
+
+        let mut requests = request.into_inner();
+        let (sender, receiver) = mpsc::channel(MAX_BUFFERED_UPDATES);
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_EXECUTIONS));
+            let mut request_index = 0;
+            loop {
+                // Wait for an execution to finish before reading the next request, so that a
+                // single stream cannot start an unbounded number of executions.
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    return;
+                };
+                let Some(request) = requests.next().await else {
+                    return;
+                };
+                let request = match request {
+                    Ok(request) => request,
+                    Err(status) => {
+                        let _ = sender.send(Err(status)).await;
+                        return;
+                    }
+                };
+
+                tokio::spawn(execute_with_updates(
+                    service.clone(),
+                    executor.clone(),
+                    request_index,
+                    request,
+                    sender.clone(),
+                    permit,
+                ));
+                request_index += 1;
+            }
+        });
+
+        let response = Box::pin(tokio_stream::wrappers::ReceiverStream::new(receiver));
+        Ok(tonic::Response::new(response))
+    }
+}
+
+/// Execute the transaction in `request`, sending the statuses it is scheduled with to `updates`
+/// until its effects are final, and then its response. Holds `_permit` until it is done.
+async fn execute_with_updates(
+    service: RpcService,
+    executor: Arc<dyn TransactionExecutor>,
+    request_index: u64,
+    request: ExecuteTransactionRequest,
+    updates: mpsc::Sender<UpdateResult>,
+    _permit: OwnedSemaphorePermit,
+) {
+    let digest = transaction_digest(&request);
+
+    // Subscribe before the transaction is submitted, so that none of its updates are missed.
+    let schedule_updates = digest.and_then(|_| executor.subscribe_schedule_updates());
+
+    let execution = execute_transaction(&service, &executor, request);
+    send_updates(request_index, digest, schedule_updates, execution, updates).await;
+}
+
+/// Send the statuses of the transaction with digest `digest` from `schedule_updates` to `updates`
+/// until `execution` completes, and then its outcome. Statuses that were published before the
+/// execution completed are sent before its outcome.
+async fn send_updates(
+    request_index: u64,
+    digest: Option<TransactionDigest>,
+    mut schedule_updates: Option<broadcast::Receiver<TransactionScheduleUpdate>>,
+    execution: impl Future<Output = Result<ExecuteTransactionResponse, RpcError>>,
+    updates: mpsc::Sender<UpdateResult>,
+) {
+    let update = |update| {
+        Ok(ExecuteTransactionUpdate {
+            request_index: Some(request_index),
+            digest: digest.map(|d| d.to_string()),
+            update: Some(update),
+        })
+    };
+
+    tokio::pin!(execution);
+
+    let outcome = loop {
+        tokio::select! {
+            // Statuses are checked first, so that those already published are not dropped when
+            // the execution completes at the same time.
+            biased;
+            status = next_schedule_status(&mut schedule_updates, digest) => {
+                let status = ScheduleStatus::from(status);
+                if updates.send(update(Update::ScheduleStatus(status.into()))).await.is_err() {
+                    // The client has gone away.
+                    return;
+                }
+            }
+            outcome = &mut execution => break outcome,
+        }
+    };
+
+    let _ = updates
+        .send(update(match outcome {
+            Ok(response) => Update::Response(response),
+            Err(error) => Update::Error(error.into_status_proto()),
+        }))
+        .await;
+}
+
+/// The digest of the transaction in `request`, if it contains a valid transaction.
+fn transaction_digest(request: &ExecuteTransactionRequest) -> Option<TransactionDigest> {
+    let transaction = request.transaction.as_ref()?;
+    let transaction = sui_sdk_types::Transaction::try_from(transaction).ok()?;
+    Some(transaction.digest().into())
+}
+
+/// Wait for the next schedule status of the transaction with digest `digest`. Never completes if
+/// there are no updates to follow.
+async fn next_schedule_status(
+    updates: &mut Option<broadcast::Receiver<TransactionScheduleUpdate>>,
+    digest: Option<TransactionDigest>,
+) -> TransactionScheduleStatus {
+    loop {
+        let (Some(receiver), Some(digest)) = (updates.as_mut(), digest) else {
+            return std::future::pending().await;
+        };
+
+        match receiver.recv().await {
+            Ok(update) if update.tx_digest == digest => return update.status,
+            // Updates that are missed because the subscriber fell behind are not reported.
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => *updates = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `send_updates` for a transaction whose execution completes once `published` have been
+    /// published, and return the updates it sent.
+    async fn updates_for(
+        digest: TransactionDigest,
+        published: Vec<TransactionScheduleUpdate>,
+        subscribed: bool,
+    ) -> Vec<Update> {
+        let (schedule_sender, schedule_updates) = broadcast::channel(16);
+        for update in published {
+            schedule_sender.send(update).unwrap();
+        }
+
+        let (sender, mut receiver) = mpsc::channel(16);
+        let execution = std::future::ready(Ok(ExecuteTransactionResponse::default()));
+        send_updates(
+            7,
+            Some(digest),
+            subscribed.then_some(schedule_updates),
+            execution,
+            sender,
+        )
+        .await;
+
+        let mut updates = vec![];
+        while let Ok(update) = receiver.try_recv() {
+            let update = update.unwrap();
+            assert_eq!(update.request_index, Some(7));
+            assert_eq!(update.digest, Some(digest.to_string()));
+            updates.push(update.update.unwrap());
+        }
+        updates
+    }
+
+    #[tokio::test]
+    async fn statuses_are_sent_before_the_response() {
+        use TransactionScheduleStatus as S;
+
+        let digest = TransactionDigest::random();
+        let published = vec![
+            TransactionScheduleUpdate {
+                tx_digest: digest,
+                status: S::BalanceReservationPending,
+            },
+            // Statuses of other transactions are not reported.
+            TransactionScheduleUpdate {
+                tx_digest: TransactionDigest::random(),
+                status: S::SufficientBalance,
+            },
+            TransactionScheduleUpdate {
+                tx_digest: digest,
+                status: S::InsufficientBalance,
+            },
+        ];
+
+        assert_eq!(
+            updates_for(digest, published.clone(), true).await,
+            vec![
+                Update::ScheduleStatus(ScheduleStatus::BalanceReservationPending.into()),
+                Update::ScheduleStatus(ScheduleStatus::InsufficientBalance.into()),
+                Update::Response(ExecuteTransactionResponse::default()),
+            ]
+        );
+
+        // An executor that does not schedule transactions itself only sends the response.
+        assert_eq!(
+            updates_for(digest, published, false).await,
+            vec![Update::Response(ExecuteTransactionResponse::default())]
+        );
+    }
+}
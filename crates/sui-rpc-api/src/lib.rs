@@ -19,6 +19,7 @@ mod response;
 mod service;
 pub mod subscription;
 
+pub use crate::grpc::alpha;
 pub use crate::grpc::v2beta2::protocol_config_to_proto;
 pub use client::Client;
 pub use config::Config;
@@ -122,6 +123,8 @@ impl RpcService {
                 ).send_compressed(tonic::codec::CompressionEncoding::Zstd);
             let signature_verification_service2 = sui_rpc::proto::sui::rpc::v2beta2::signature_verification_service_server::SignatureVerificationServiceServer::new(self.clone());
             let move_package_service2 = sui_rpc::proto::sui::rpc::v2beta2::move_package_service_server::MovePackageServiceServer::new(self.clone());
+            let schedule_status_service = alpha::ScheduleStatusServiceServer::new(self.clone());
+            let address_balance_service = alpha::AddressBalanceServiceServer::new(self.clone());
 
             let (health_reporter, health_service) = tonic_health::server::health_reporter();
 
@@ -163,6 +166,8 @@ impl RpcService {
                 service_name(&live_data_service2),
                 service_name(&signature_verification_service2),
                 service_name(&move_package_service2),
+                service_name(&schedule_status_service),
+                service_name(&address_balance_service),
                 service_name(&reflection_v1),
                 service_name(&reflection_v1alpha),
             ] {
@@ -177,6 +182,8 @@ impl RpcService {
                 .add_service(live_data_service2)
                 .add_service(signature_verification_service2)
                 .add_service(move_package_service2)
+                .add_service(schedule_status_service)
+                .add_service(address_balance_service)
                 .add_service(reflection_v1)
                 .add_service(reflection_v1alpha);
 
@@ -0,0 +1,127 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Utilities for working with derived objects: objects whose IDs are computed deterministically
+//! from the ID of a parent object and a key, so they can be located without an index.
+//!
+//! Derived objects are claimed through a Move function in the parent's package, which takes the
+//! parent object and the key. Objects sent to a derived object's address can be received through
+//! another function in that package.
+
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use serde::Serialize;
+use sui_json_rpc_types::SuiObjectDataOptions;
+use sui_types::base_types::{ObjectID, ObjectRef};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{ObjectArg, ProgrammableTransaction};
+
+use crate::error::{Error, SuiRpcResult};
+use crate::SuiClient;
+
+pub use sui_types::derived_object::{
+    derive_object_id, derive_object_id_for_key, derived_object_key_type,
+};
+/// Keys whose Move type is known statically. Implement this for key types defined in your own
+/// packages to use them with [`DerivedObjectClient`].
+pub use sui_types::MoveTypeTagTrait as DerivedObjectKeyType;
+
+/// A Move function to call when claiming or receiving derived objects.
+#[derive(Clone, Debug)]
+pub struct DerivedObjectCall {
+    pub package: ObjectID,
+    pub module: Identifier,
+    pub function: Identifier,
+    pub type_arguments: Vec<TypeTag>,
+}
+
+/// Convenience wrapper around a [`SuiClient`] for locating derived objects, and building the
+/// transactions that claim and receive them.
+#[derive(Clone)]
+pub struct DerivedObjectClient {
+    client: SuiClient,
+}
+
+impl DerivedObjectClient {
+    pub fn new(client: SuiClient) -> Self {
+        Self { client }
+    }
+
+    /// The ID of the object derived from `parent` with `key`.
+    pub fn derived_object_id<K>(&self, parent: ObjectID, key: &K) -> SuiRpcResult<ObjectID>
+    where
+        K: DerivedObjectKeyType + Serialize,
+    {
+        Ok(derive_object_id_for_key(parent, key)?)
+    }
+
+    /// Whether the object derived from `parent` with `key` has been claimed, and is still live.
+    pub async fn exists<K>(&self, parent: ObjectID, key: &K) -> SuiRpcResult<bool>
+    where
+        K: DerivedObjectKeyType + Serialize,
+    {
+        let id = self.derived_object_id(parent, key)?;
+        let response = self
+            .client
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new())
+            .await?;
+
+        Ok(response.data.is_some())
+    }
+
+    /// Build a transaction that calls `call` with the `parent` object and `key`, to claim the
+    /// object derived from them.
+    pub fn claim_transaction<K>(
+        &self,
+        call: DerivedObjectCall,
+        parent: ObjectArg,
+        key: &K,
+    ) -> SuiRpcResult<ProgrammableTransaction>
+    where
+        K: Serialize,
+    {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let parent = builder
+            .obj(parent)
+            .map_err(|e| Error::DataError(e.to_string()))?;
+        let key = builder.pure_bytes(bcs::to_bytes(key)?, /* force_separate */ false);
+
+        builder.programmable_move_call(
+            call.package,
+            call.module,
+            call.function,
+            call.type_arguments,
+            vec![parent, key],
+        );
+
+        Ok(builder.finish())
+    }
+
+    /// Build a transaction that calls `call` with the `parent` object and `received`, an object
+    /// that was sent to the address of one of `parent`'s derived objects.
+    pub fn receive_transaction(
+        &self,
+        call: DerivedObjectCall,
+        parent: ObjectArg,
+        received: ObjectRef,
+    ) -> SuiRpcResult<ProgrammableTransaction> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let parent = builder
+            .obj(parent)
+            .map_err(|e| Error::DataError(e.to_string()))?;
+        let received = builder
+            .obj(ObjectArg::Receiving(received))
+            .map_err(|e| Error::DataError(e.to_string()))?;
+
+        builder.programmable_move_call(
+            call.package,
+            call.module,
+            call.function,
+            call.type_arguments,
+            vec![parent, received],
+        );
+
+        Ok(builder.finish())
+    }
+}
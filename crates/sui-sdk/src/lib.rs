@@ -17,6 +17,8 @@
 //!     block and submit it to the fullnode(s)
 //! * [ReadApi] - provides functions for retrieving data about different
 //!     objects and transactions
+//! * `derived_object` - utilities for working with derived objects (requires the
+//!     `derived-objects` feature)
 //! * <a href="../sui_transaction_builder/struct.TransactionBuilder.html" title="struct sui_transaction_builder::TransactionBuilder">TransactionBuilder</a> - provides functions for building transactions
 //!
 //! # Usage
@@ -104,6 +106,8 @@ use crate::apis::{CoinReadApi, EventApi, GovernanceApi, QuorumDriverApi, ReadApi
 use crate::error::{Error, SuiRpcResult};
 
 pub mod apis;
+#[cfg(feature = "derived-objects")]
+pub mod derived_object;
 pub mod error;
 pub mod json_rpc_error;
 pub mod sui_client_config;
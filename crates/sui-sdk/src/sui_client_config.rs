@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use crate::{SuiClient, SuiClientBuilder, SUI_DEVNET_URL, SUI_LOCAL_NETWORK_URL, SUI_TESTNET_URL};
+use sui_config::network_profiles::NetworkProfile;
 use sui_config::Config;
 use sui_keys::keystore::{AccountKeystore, Keystore};
 use sui_types::base_types::*;
@@ -104,6 +105,15 @@ impl SuiEnv {
         Ok(builder.build(&self.rpc).await?)
     }
 
+    pub fn from_network_profile(alias: &str, profile: &NetworkProfile) -> Self {
+        Self {
+            alias: alias.to_string(),
+            rpc: profile.rpc.clone(),
+            ws: profile.ws.clone(),
+            basic_auth: None,
+        }
+    }
+
     pub fn devnet() -> Self {
         Self {
             alias: "devnet".to_string(),
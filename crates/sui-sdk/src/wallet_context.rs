@@ -9,6 +9,7 @@ use shared_crypto::intent::Intent;
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use sui_config::network_profiles::NetworkProfile;
 use sui_config::{Config, PersistedConfig};
 use sui_json_rpc_types::{
     SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponse,
@@ -83,6 +84,16 @@ impl WalletContext {
         self
     }
 
+    /// Use the network described by `profile` for this context, as an environment named `name`.
+    /// The environment is added to the client config, replacing any existing environment with
+    /// the same name.
+    pub fn with_network_profile(mut self, name: &str, profile: &NetworkProfile) -> Self {
+        self.config.envs.retain(|env| env.alias != name);
+        self.config
+            .add_env(SuiEnv::from_network_profile(name, profile));
+        self.with_env_override(name.to_string())
+    }
+
     pub fn get_addresses(&self) -> Vec<SuiAddress> {
         self.config.keystore.addresses()
     }
@@ -440,16 +451,22 @@ impl WalletContext {
         Ok(sig)
     }
 
-    /// Sign a transaction with a key currently managed by the WalletContext
+    /// Sign a transaction with a key currently managed by the WalletContext. Sponsored
+    /// transactions are also signed by the gas owner, whose key must be managed by the
+    /// WalletContext as well.
     pub async fn sign_transaction(&self, data: &TransactionData) -> Transaction {
-        let sig = self
-            .config
-            .keystore
-            .sign_secure(&data.sender(), data, Intent::sui_transaction())
-            .await
-            .unwrap();
-        // TODO: To support sponsored transaction, we should also look at the gas owner.
-        Transaction::from_data(data.clone(), vec![sig])
+        let mut sigs = vec![];
+        for signer in data.signers() {
+            let sig = self
+                .config
+                .keystore
+                .sign_secure(&signer, data, Intent::sui_transaction())
+                .await
+                .unwrap();
+            sigs.push(sig);
+        }
+
+        Transaction::from_data(data.clone(), sigs)
     }
 
     /// Execute a transaction and wait for it to be locally executed on the fullnode.
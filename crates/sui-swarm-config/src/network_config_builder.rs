@@ -10,7 +10,7 @@ use sui_config::genesis::{TokenAllocation, TokenDistributionScheduleBuilder};
 use sui_config::node::AuthorityOverloadConfig;
 #[cfg(msim)]
 use sui_config::node::ExecutionTimeObserverConfig;
-use sui_config::ExecutionCacheConfig;
+use sui_config::{local_ip_utils, ExecutionCacheConfig};
 use sui_protocol_config::Chain;
 use sui_types::base_types::{AuthorityName, SuiAddress};
 use sui_types::committee::{Committee, ProtocolVersion};
@@ -33,6 +33,21 @@ pub enum CommitteeConfig {
     Deterministic((NonZeroUsize, Option<Vec<AccountKeyPair>>)),
 }
 
+/// Number of consecutive ports reserved for each node when ports are assigned deterministically.
+pub const PORTS_PER_NODE: usize = 10;
+
+/// First port assigned to a [`CommitteeConfig::Deterministic`] committee, unless another is given
+/// with [`ConfigBuilder::with_deterministic_ports`].
+pub const DEFAULT_DETERMINISTIC_PORT_OFFSET: u16 = 8000;
+
+/// The first port in the block of ports reserved for the `index`-th node of a network whose ports
+/// are assigned deterministically, starting at `port_offset`.
+pub fn node_port_offset(port_offset: u16, index: usize) -> u16 {
+    (port_offset as usize + index * PORTS_PER_NODE)
+        .try_into()
+        .expect("Deterministic port out of range")
+}
+
 pub type SupportedProtocolVersionsCallback = Arc<
     dyn Fn(
             usize,                 /* validator idx */
@@ -81,6 +96,7 @@ pub struct ConfigBuilder<R = OsRng> {
     max_submit_position: Option<usize>,
     submit_delay_step_override_millis: Option<u64>,
     global_state_hash_v2_enabled_config: Option<GlobalStateHashV2EnabledConfig>,
+    port_offset: Option<u16>,
     #[cfg(msim)]
     execution_time_observer_config: Option<ExecutionTimeObserverConfig>,
 }
@@ -108,6 +124,7 @@ impl ConfigBuilder {
             max_submit_position: None,
             submit_delay_step_override_millis: None,
             global_state_hash_v2_enabled_config: None,
+            port_offset: None,
             #[cfg(msim)]
             execution_time_observer_config: None,
         }
@@ -295,6 +312,15 @@ impl<R> ConfigBuilder<R> {
         self
     }
 
+    /// Bind validators to fixed ports on localhost, instead of picking available ports at random.
+    /// Each validator is given a block of [`PORTS_PER_NODE`] ports, the first starting at
+    /// `port_offset`. Committees built with [`CommitteeConfig::Validators`] keep the addresses
+    /// they were configured with.
+    pub fn with_deterministic_ports(mut self, port_offset: u16) -> Self {
+        self.port_offset = Some(port_offset);
+        self
+    }
+
     pub fn rng<N: rand::RngCore + rand::CryptoRng>(self, rng: N) -> ConfigBuilder<N> {
         ConfigBuilder {
             rng: Some(rng),
@@ -315,6 +341,7 @@ impl<R> ConfigBuilder<R> {
             max_submit_position: self.max_submit_position,
             submit_delay_step_override_millis: self.submit_delay_step_override_millis,
             global_state_hash_v2_enabled_config: self.global_state_hash_v2_enabled_config,
+            port_offset: self.port_offset,
             #[cfg(msim)]
             execution_time_observer_config: self.execution_time_observer_config,
         }
@@ -343,12 +370,18 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                 let (_, keys) = Committee::new_simple_test_committee_of_size(size.into());
 
                 keys.into_iter()
-                    .map(|authority_key| {
+                    .enumerate()
+                    .map(|(i, authority_key)| {
                         let mut builder = ValidatorGenesisConfigBuilder::new()
                             .with_protocol_key_pair(authority_key);
                         if let Some(rgp) = self.reference_gas_price {
                             builder = builder.with_gas_price(rgp);
                         }
+                        if let Some(port_offset) = self.port_offset {
+                            builder = builder
+                                .with_ip(local_ip_utils::localhost_for_testing())
+                                .with_deterministic_ports(node_port_offset(port_offset, i));
+                        }
                         builder.build(&mut rng)
                     })
                     .collect::<Vec<_>>()
@@ -361,13 +394,19 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                 let (_, protocol_keys) = Committee::new_simple_test_committee_of_size(keys.len());
                 keys.into_iter()
                     .zip(protocol_keys)
-                    .map(|(account_key, protocol_key)| {
+                    .enumerate()
+                    .map(|(i, (account_key, protocol_key))| {
                         let mut builder = ValidatorGenesisConfigBuilder::new()
                             .with_protocol_key_pair(protocol_key)
                             .with_account_key_pair(account_key);
                         if let Some(rgp) = self.reference_gas_price {
                             builder = builder.with_gas_price(rgp);
                         }
+                        if let Some(port_offset) = self.port_offset {
+                            builder = builder
+                                .with_ip(local_ip_utils::localhost_for_testing())
+                                .with_deterministic_ports(node_port_offset(port_offset, i));
+                        }
                         builder.build(&mut rng)
                     })
                     .collect::<Vec<_>>()
@@ -381,12 +420,14 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                 );
 
                 let mut configs = vec![];
+                let port_offset = self
+                    .port_offset
+                    .unwrap_or(DEFAULT_DETERMINISTIC_PORT_OFFSET);
                 for (i, key) in keys.into_iter().enumerate() {
-                    let port_offset = 8000 + i * 10;
                     let mut builder = ValidatorGenesisConfigBuilder::new()
                         .with_ip("127.0.0.1".to_owned())
                         .with_account_key_pair(key)
-                        .with_deterministic_ports(port_offset as u16);
+                        .with_deterministic_ports(node_port_offset(port_offset, i));
                     if let Some(rgp) = self.reference_gas_price {
                         builder = builder.with_gas_price(rgp);
                     }
@@ -538,6 +579,8 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroUsize;
+
     use sui_config::node::Genesis;
 
     #[test]
@@ -582,6 +625,23 @@ mod tests {
         loaded_genesis.checkpoint_contents().digest(); // cache digest before comparing.
         assert_eq!(&genesis, loaded_genesis);
     }
+
+    #[test]
+    fn deterministic_ports() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let network_config = crate::network_config_builder::ConfigBuilder::new(&dir)
+            .committee_size(NonZeroUsize::new(2).unwrap())
+            .with_deterministic_ports(12000)
+            .build();
+
+        let ports: Vec<_> = network_config
+            .validator_configs()
+            .iter()
+            .map(|config| config.network_address().to_socket_addr().unwrap().port())
+            .collect();
+
+        assert_eq!(ports, vec![12000, 12010]);
+    }
 }
 
 #[cfg(test)]
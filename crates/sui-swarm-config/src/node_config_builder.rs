@@ -1,8 +1,9 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use fastcrypto::encoding::{Encoding, Hex};
@@ -202,6 +203,8 @@ impl ValidatorConfigBuilder {
             network_address,
             metrics_address: validator.metrics_address,
             admin_interface_port: local_ip_utils::get_available_port(&localhost),
+            port_conflict_policy: Default::default(),
+            tls: None,
             json_rpc_address: local_ip_utils::new_tcp_address_for_testing(&localhost)
                 .to_socket_addr()
                 .unwrap(),
@@ -255,6 +258,9 @@ impl ValidatorConfigBuilder {
             chain_override_for_testing: self.chain_override,
             validator_client_monitor_config: None,
             fork_recovery: None,
+            key_encryption: None,
+            shadow_withdraw_scheduler: None,
+            package_execution_limits: BTreeMap::new(),
         }
     }
 
@@ -292,6 +298,7 @@ pub struct FullnodeConfigBuilder {
     data_ingestion_dir: Option<PathBuf>,
     disable_pruning: bool,
     chain_override: Option<Chain>,
+    port_offset: Option<u16>,
 }
 
 impl FullnodeConfigBuilder {
@@ -410,6 +417,13 @@ impl FullnodeConfigBuilder {
         self
     }
 
+    /// Bind the fullnode to fixed ports on localhost, starting at `port_offset`, instead of picking
+    /// available ports at random. Addresses and ports that are set explicitly take precedence.
+    pub fn with_deterministic_ports(mut self, port_offset: u16) -> Self {
+        self.port_offset = Some(port_offset);
+        self
+    }
+
     pub fn with_data_ingestion_dir(mut self, path: Option<PathBuf>) -> Self {
         self.data_ingestion_dir = path;
         self
@@ -422,7 +436,13 @@ impl FullnodeConfigBuilder {
     ) -> NodeConfig {
         // Take advantage of ValidatorGenesisConfigBuilder to build the keypairs and addresses,
         // even though this is a fullnode.
-        let validator_config = ValidatorGenesisConfigBuilder::new().build(rng);
+        let mut validator_config_builder = ValidatorGenesisConfigBuilder::new();
+        if let Some(port_offset) = self.port_offset {
+            validator_config_builder = validator_config_builder
+                .with_ip(local_ip_utils::localhost_for_testing())
+                .with_deterministic_ports(port_offset);
+        }
+        let validator_config = validator_config_builder.build(rng);
         let ip = validator_config
             .network_address
             .to_socket_addr()
@@ -472,9 +492,10 @@ impl FullnodeConfigBuilder {
 
         let localhost = local_ip_utils::localhost_for_testing();
         let json_rpc_address = self.rpc_addr.unwrap_or_else(|| {
-            let rpc_port = self
-                .rpc_port
-                .unwrap_or_else(|| local_ip_utils::get_available_port(&ip));
+            let rpc_port = self.rpc_port.unwrap_or_else(|| match self.port_offset {
+                Some(port_offset) => port_offset + 7,
+                None => local_ip_utils::get_available_port(&ip),
+            });
             format!("{}:{}", ip, rpc_port).parse().unwrap()
         });
 
@@ -506,10 +527,20 @@ impl FullnodeConfigBuilder {
                 .unwrap_or(validator_config.network_address),
             metrics_address: self
                 .metrics_address
-                .unwrap_or(local_ip_utils::new_local_tcp_socket_for_testing()),
-            admin_interface_port: self
-                .admin_interface_port
-                .unwrap_or(local_ip_utils::get_available_port(&localhost)),
+                .unwrap_or_else(|| match self.port_offset {
+                    Some(port_offset) => {
+                        SocketAddr::new(localhost.parse().unwrap(), port_offset + 8)
+                    }
+                    None => local_ip_utils::new_local_tcp_socket_for_testing(),
+                }),
+            admin_interface_port: self.admin_interface_port.unwrap_or_else(|| {
+                match self.port_offset {
+                    Some(port_offset) => port_offset + 9,
+                    None => local_ip_utils::get_available_port(&localhost),
+                }
+            }),
+            port_conflict_policy: Default::default(),
+            tls: None,
             json_rpc_address: self.json_rpc_address.unwrap_or(json_rpc_address),
             consensus_config: None,
             remove_deprecated_tables: false,
@@ -564,6 +595,94 @@ impl FullnodeConfigBuilder {
             chain_override_for_testing: self.chain_override,
             validator_client_monitor_config: None,
             fork_recovery: None,
+            key_encryption: None,
+            shadow_withdraw_scheduler: None,
+            package_execution_limits: BTreeMap::new(),
+        }
+    }
+}
+
+/// The high level role of a node, used by [`NodeConfigBuilder`] to pick sensible defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// A validator that is not yet part of the committee. Pruning is left at validator defaults.
+    Validator,
+    /// A fullnode serving RPC traffic, with default pruning and RPC indexing enabled.
+    Fullnode,
+    /// A fullnode feeding an indexer: pruning is disabled and executed checkpoints are written
+    /// to an ingestion directory inside the config directory.
+    Indexer,
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Profile::Validator => write!(f, "validator"),
+            Profile::Fullnode => write!(f, "fullnode"),
+            Profile::Indexer => write!(f, "indexer"),
+        }
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "validator" => Ok(Profile::Validator),
+            "fullnode" => Ok(Profile::Fullnode),
+            "indexer" => Ok(Profile::Indexer),
+            _ => anyhow::bail!(
+                "Unknown node profile {s:?}, expected one of: validator, fullnode, indexer"
+            ),
+        }
+    }
+}
+
+/// Builds a NodeConfig for a node joining an existing network, from a single [`Profile`].
+/// Ports are allocated through `local_ip_utils`, and every profile exposes a metrics address.
+/// The node keeps its databases in the config directory, which must outlive the node.
+#[derive(Clone, Debug)]
+pub struct NodeConfigBuilder {
+    profile: Profile,
+    config_directory: PathBuf,
+}
+
+/// Name of the directory, relative to the config directory, that the indexer profile
+/// writes executed checkpoints to.
+pub const INDEXER_DATA_INGESTION_DIR: &str = "ingestion";
+
+impl NodeConfigBuilder {
+    pub fn from_profile<P: AsRef<Path>>(profile: Profile, config_directory: P) -> Self {
+        Self {
+            profile,
+            config_directory: config_directory.as_ref().into(),
+        }
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    pub fn build<R: rand::RngCore + rand::CryptoRng>(
+        self,
+        rng: &mut R,
+        network_config: &NetworkConfig,
+    ) -> NodeConfig {
+        let config_directory = self.config_directory;
+
+        match self.profile {
+            Profile::Validator => ValidatorConfigBuilder::new()
+                .with_config_directory(config_directory)
+                .build_new_validator(rng, network_config),
+            Profile::Fullnode => FullnodeConfigBuilder::new()
+                .with_config_directory(config_directory)
+                .build(rng, network_config),
+            Profile::Indexer => FullnodeConfigBuilder::new()
+                .with_config_directory(config_directory.clone())
+                .with_disable_pruning(true)
+                .with_data_ingestion_dir(Some(config_directory.join(INDEXER_DATA_INGESTION_DIR)))
+                .build(rng, network_config),
         }
     }
 }
@@ -576,3 +695,57 @@ fn get_key_path(key_pair: &AuthorityKeyPair) -> String {
     key_path.truncate(12);
     key_path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network_config_builder::ConfigBuilder;
+
+    #[test]
+    fn node_config_from_profile() {
+        let network_dir = tempfile::TempDir::new().unwrap();
+        let network_config = ConfigBuilder::new(&network_dir).build();
+        let mut rng = rand::thread_rng();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let validator = NodeConfigBuilder::from_profile(Profile::Validator, &dir)
+            .build(&mut rng, &network_config);
+        assert!(validator.consensus_config.is_some());
+        assert!(validator.db_path().starts_with(dir.path()));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let fullnode = NodeConfigBuilder::from_profile(Profile::Fullnode, &dir)
+            .build(&mut rng, &network_config);
+        assert!(fullnode.consensus_config.is_none());
+        assert!(fullnode.db_path().starts_with(dir.path()));
+        assert_eq!(
+            fullnode.authority_store_pruning_config.num_epochs_to_retain,
+            AuthorityStorePruningConfig::default().num_epochs_to_retain,
+        );
+        assert_eq!(fullnode.checkpoint_executor_config.data_ingestion_dir, None);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let indexer = NodeConfigBuilder::from_profile(Profile::Indexer, &dir)
+            .build(&mut rng, &network_config);
+        assert!(indexer.consensus_config.is_none());
+        assert!(indexer.db_path().starts_with(dir.path()));
+        assert_eq!(
+            indexer.authority_store_pruning_config.num_epochs_to_retain,
+            u64::MAX,
+        );
+        assert_eq!(
+            indexer.checkpoint_executor_config.data_ingestion_dir,
+            Some(dir.path().join(INDEXER_DATA_INGESTION_DIR)),
+        );
+    }
+
+    #[test]
+    fn profile_round_trip() {
+        for profile in [Profile::Validator, Profile::Fullnode, Profile::Indexer] {
+            assert_eq!(profile.to_string().parse::<Profile>().unwrap(), profile);
+        }
+
+        assert!("Indexer".parse::<Profile>().is_ok());
+        assert!("archive".parse::<Profile>().is_err());
+    }
+}
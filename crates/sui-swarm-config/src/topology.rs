@@ -0,0 +1,254 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::SocketAddr;
+
+use anyhow::{bail, ensure};
+use serde::{Deserialize, Serialize};
+use sui_config::{local_ip_utils, Config};
+
+use crate::genesis_config::{ValidatorGenesisConfig, ValidatorGenesisConfigBuilder};
+use crate::network_config_builder::node_port_offset;
+
+/// A declarative description of a local network (usually read from a `swarm.yaml`), covering
+/// the validators that make up its committee, the fullnodes that follow it, and the services
+/// that run alongside it. For example:
+///
+/// ```yaml
+/// validators:
+///   - name: big
+///     stake: 60000000000000000
+///   - stake: 20000000000000000
+///   - stake: 20000000000000000
+///     gas_price: 2000
+/// fullnodes: 2
+/// fullnode_rpc_address: 127.0.0.1:9000
+/// indexer: {}
+/// faucet:
+///   address: 127.0.0.1:9123
+/// ```
+///
+/// Addresses that are not given are allocated on localhost by
+/// [`SwarmTopology::allocate_addresses`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SwarmTopology {
+    /// The validators in the genesis committee, in order.
+    pub validators: Vec<ValidatorTopology>,
+
+    /// The number of fullnodes to run.
+    #[serde(default = "default_fullnodes")]
+    pub fullnodes: usize,
+
+    /// The address that the first fullnode serves RPC on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullnode_rpc_address: Option<SocketAddr>,
+
+    /// Bind validators and fullnodes to fixed ports, starting at this port, instead of picking
+    /// available ports at random. Each node is given its own block of ports, validators first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_port: Option<u16>,
+
+    /// Run an indexer, which reads checkpoints from the first fullnode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexer: Option<ServiceTopology>,
+
+    /// Run a GraphQL service, which reads from the indexer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<ServiceTopology>,
+
+    /// Run a faucet, which sends requests to the first fullnode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<ServiceTopology>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorTopology {
+    /// The validator's name in the system state, defaults to its protocol public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The validator's stake at genesis, in MIST. Defaults to the same stake as validators in
+    /// other local networks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stake: Option<u64>,
+
+    /// The validator's gas price at genesis, in MIST.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceTopology {
+    /// The address that the service listens on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<SocketAddr>,
+}
+
+impl Config for SwarmTopology {}
+
+impl SwarmTopology {
+    /// Check that the topology describes a network that can be launched.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            !self.validators.is_empty(),
+            "Topology must have at least one validator"
+        );
+
+        for (i, validator) in self.validators.iter().enumerate() {
+            if validator.stake == Some(0) {
+                bail!("Validator {i} must have a non-zero stake");
+            }
+        }
+
+        ensure!(
+            self.fullnodes > 0 || (self.indexer.is_none() && self.faucet.is_none()),
+            "The indexer and faucet require at least one fullnode"
+        );
+
+        ensure!(
+            self.indexer.is_some() || self.graphql.is_none(),
+            "The GraphQL service requires an indexer"
+        );
+
+        Ok(())
+    }
+
+    /// Build the genesis configs of the topology's validators, allocating their addresses.
+    pub fn validator_configs<R: rand::RngCore + rand::CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Vec<ValidatorGenesisConfig> {
+        self.validators
+            .iter()
+            .enumerate()
+            .map(|(i, validator)| {
+                let mut builder =
+                    ValidatorGenesisConfigBuilder::new().with_ip(local_ip_utils::get_new_ip());
+
+                if let Some(stake) = validator.stake {
+                    builder = builder.with_stake(stake);
+                }
+
+                if let Some(gas_price) = validator.gas_price {
+                    builder = builder.with_gas_price(gas_price);
+                }
+
+                if let Some(base_port) = self.base_port {
+                    builder = builder.with_deterministic_ports(node_port_offset(base_port, i));
+                }
+
+                let mut config = builder.build(rng);
+                config.name = validator.name.clone();
+                config
+            })
+            .collect()
+    }
+
+    /// Allocate addresses on localhost for the first fullnode's RPC server and for the services,
+    /// where the topology does not give them, so that they are known before the network is
+    /// launched.
+    pub fn allocate_addresses(&mut self) {
+        if self.fullnodes > 0 && self.fullnode_rpc_address.is_none() {
+            self.fullnode_rpc_address = Some(local_ip_utils::new_local_tcp_socket_for_testing());
+        }
+
+        for service in [&mut self.indexer, &mut self.graphql, &mut self.faucet]
+            .into_iter()
+            .flatten()
+        {
+            service
+                .address
+                .get_or_insert_with(local_ip_utils::new_local_tcp_socket_for_testing);
+        }
+    }
+}
+
+fn default_fullnodes() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+validators:
+  - name: big
+    stake: 60000000000000000
+  - stake: 20000000000000000
+  - gas_price: 2000
+base_port: 12000
+indexer: {}
+graphql:
+  address: 127.0.0.1:9125
+"#;
+
+    #[test]
+    fn parse_and_build_validators() {
+        let topology: SwarmTopology = serde_yaml::from_str(EXAMPLE).unwrap();
+        topology.validate().unwrap();
+
+        assert_eq!(topology.fullnodes, 1);
+        assert_eq!(topology.faucet, None);
+        assert_eq!(
+            topology.graphql.as_ref().unwrap().address,
+            Some("127.0.0.1:9125".parse().unwrap())
+        );
+
+        let mut rng = StdRng::from_seed([0; 32]);
+        let validators = topology.validator_configs(&mut rng);
+
+        let names: Vec<_> = validators.iter().map(|v| v.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("big"), None, None]);
+
+        let stakes: Vec<_> = validators.iter().map(|v| v.stake).collect();
+        assert_eq!(stakes[0], 60_000_000_000_000_000);
+        assert_eq!(stakes[1], 20_000_000_000_000_000);
+        assert_eq!(validators[2].gas_price, 2000);
+
+        let ports: Vec<_> = validators
+            .iter()
+            .map(|v| v.network_address.to_socket_addr().unwrap().port())
+            .collect();
+        assert_eq!(ports, vec![12000, 12010, 12020]);
+    }
+
+    #[test]
+    fn allocate_addresses() {
+        let mut topology: SwarmTopology = serde_yaml::from_str(EXAMPLE).unwrap();
+        topology.allocate_addresses();
+
+        assert!(topology.fullnode_rpc_address.is_some());
+        assert!(topology.indexer.as_ref().unwrap().address.is_some());
+        assert_eq!(topology.faucet, None);
+
+        // Addresses that were given are kept.
+        assert_eq!(
+            topology.graphql.as_ref().unwrap().address,
+            Some("127.0.0.1:9125".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn invalid_topologies() {
+        let invalid = [
+            "validators: []",
+            "validators: [{ stake: 0 }]",
+            "{ validators: [{}], fullnodes: 0, faucet: {} }",
+            "{ validators: [{}], graphql: {} }",
+        ];
+
+        for yaml in invalid {
+            let topology: SwarmTopology = serde_yaml::from_str(yaml).unwrap();
+            assert!(topology.validate().is_err(), "{yaml} should be invalid");
+        }
+
+        // Typos are caught rather than silently ignored.
+        assert!(serde_yaml::from_str::<SwarmTopology>("{ validators: [{ stak: 1 }] }").is_err());
+    }
+}
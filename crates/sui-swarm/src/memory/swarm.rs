@@ -25,10 +25,11 @@ use sui_protocol_config::{Chain, ProtocolVersion};
 use sui_swarm_config::genesis_config::{AccountConfig, GenesisConfig, ValidatorGenesisConfig};
 use sui_swarm_config::network_config::NetworkConfig;
 use sui_swarm_config::network_config_builder::{
-    CommitteeConfig, ConfigBuilder, GlobalStateHashV2EnabledConfig, ProtocolVersionsConfig,
-    SupportedProtocolVersionsCallback,
+    node_port_offset, CommitteeConfig, ConfigBuilder, GlobalStateHashV2EnabledConfig,
+    ProtocolVersionsConfig, SupportedProtocolVersionsCallback,
 };
 use sui_swarm_config::node_config_builder::FullnodeConfigBuilder;
+use sui_swarm_config::topology::SwarmTopology;
 use sui_types::base_types::AuthorityName;
 use sui_types::object::Object;
 use sui_types::supported_protocol_versions::SupportedProtocolVersions;
@@ -45,6 +46,7 @@ pub struct SwarmBuilder<R = OsRng> {
     chain_override: Option<Chain>,
     additional_objects: Vec<Object>,
     fullnode_count: usize,
+    fullnode_config: Option<NodeConfig>,
     fullnode_rpc_port: Option<u16>,
     fullnode_rpc_addr: Option<SocketAddr>,
     supported_protocol_versions_config: ProtocolVersionsConfig,
@@ -63,6 +65,7 @@ pub struct SwarmBuilder<R = OsRng> {
     submit_delay_step_override_millis: Option<u64>,
     global_state_hash_v2_enabled_config: GlobalStateHashV2EnabledConfig,
     disable_fullnode_pruning: bool,
+    port_offset: Option<u16>,
     #[cfg(msim)]
     execution_time_observer_config: Option<ExecutionTimeObserverConfig>,
 }
@@ -79,6 +82,7 @@ impl SwarmBuilder {
             chain_override: None,
             additional_objects: vec![],
             fullnode_count: 0,
+            fullnode_config: None,
             fullnode_rpc_port: None,
             fullnode_rpc_addr: None,
             supported_protocol_versions_config: ProtocolVersionsConfig::Default,
@@ -96,6 +100,7 @@ impl SwarmBuilder {
             submit_delay_step_override_millis: None,
             global_state_hash_v2_enabled_config: GlobalStateHashV2EnabledConfig::Global(true),
             disable_fullnode_pruning: false,
+            port_offset: None,
             #[cfg(msim)]
             execution_time_observer_config: None,
         }
@@ -113,6 +118,7 @@ impl<R> SwarmBuilder<R> {
             chain_override: self.chain_override,
             additional_objects: self.additional_objects,
             fullnode_count: self.fullnode_count,
+            fullnode_config: self.fullnode_config,
             fullnode_rpc_port: self.fullnode_rpc_port,
             fullnode_rpc_addr: self.fullnode_rpc_addr,
             supported_protocol_versions_config: self.supported_protocol_versions_config,
@@ -131,6 +137,7 @@ impl<R> SwarmBuilder<R> {
             submit_delay_step_override_millis: self.submit_delay_step_override_millis,
             global_state_hash_v2_enabled_config: self.global_state_hash_v2_enabled_config,
             disable_fullnode_pruning: self.disable_fullnode_pruning,
+            port_offset: self.port_offset,
             #[cfg(msim)]
             execution_time_observer_config: self.execution_time_observer_config,
         }
@@ -188,6 +195,14 @@ impl<R> SwarmBuilder<R> {
         self
     }
 
+    /// Use an existing config for the first fullnode (e.g. one that was persisted along with its
+    /// store), rather than building a new one. Other fullnodes are built as usual.
+    pub fn with_fullnode_config(mut self, config: NodeConfig) -> Self {
+        assert!(self.fullnode_config.is_none());
+        self.fullnode_config = Some(config);
+        self
+    }
+
     pub fn with_accounts(mut self, accounts: Vec<AccountConfig>) -> Self {
         self.get_or_init_genesis_config().accounts = accounts;
         self
@@ -198,6 +213,16 @@ impl<R> SwarmBuilder<R> {
         self
     }
 
+    /// Bind nodes to fixed ports on localhost, instead of picking available ports at random, so
+    /// that they can be found at the same addresses across restarts. Each node is given its own
+    /// block of ports, starting at `port_offset`, validators first, then fullnodes.
+    ///
+    /// Validators from an existing network config keep the addresses they were configured with.
+    pub fn with_deterministic_ports(mut self, port_offset: u16) -> Self {
+        self.port_offset = Some(port_offset);
+        self
+    }
+
     pub fn with_fullnode_count(mut self, fullnode_count: usize) -> Self {
         self.fullnode_count = fullnode_count;
         self
@@ -341,6 +366,19 @@ impl<R> SwarmBuilder<R> {
 }
 
 impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
+    /// Configure the committee and fullnodes described by `topology`, allocating the validators'
+    /// addresses. The services in the topology are not part of the Swarm, and must be launched
+    /// separately.
+    pub fn with_topology(mut self, topology: &SwarmTopology) -> Self {
+        self.committee = CommitteeConfig::Validators(topology.validator_configs(&mut self.rng));
+        self.port_offset = topology.base_port;
+        self.fullnode_count = topology.fullnodes;
+        if let Some(fullnode_rpc_addr) = topology.fullnode_rpc_address {
+            self = self.with_fullnode_rpc_addr(fullnode_rpc_addr);
+        }
+        self
+    }
+
     /// Create the configured Swarm.
     pub fn build(self) -> Swarm {
         let dir = if let Some(dir) = self.dir {
@@ -388,6 +426,10 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
                 config_builder = config_builder.with_max_submit_position(max_submit_position);
             }
 
+            if let Some(port_offset) = self.port_offset {
+                config_builder = config_builder.with_deterministic_ports(port_offset);
+            }
+
             if let Some(submit_delay_step_override_millis) = self.submit_delay_step_override_millis
             {
                 config_builder = config_builder
@@ -451,8 +493,23 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
         }
 
         if self.fullnode_count > 0 {
+            let mut fullnode_config = self.fullnode_config;
             (0..self.fullnode_count).for_each(|idx| {
+                if let Some(config) = fullnode_config.take() {
+                    info!(
+                        "SwarmBuilder reusing full node with name {}",
+                        config.protocol_public_key()
+                    );
+                    nodes.insert(config.protocol_public_key(), Node::new(config));
+                    return;
+                }
+
                 let mut builder = fullnode_config_builder.clone();
+                if let Some(port_offset) = self.port_offset {
+                    let index = network_config.validator_configs().len() + idx;
+                    builder =
+                        builder.with_deterministic_ports(node_port_offset(port_offset, index));
+                }
                 if idx == 0 {
                     // Only the first fullnode is used as the rpc fullnode, we can only use the
                     // same address once.
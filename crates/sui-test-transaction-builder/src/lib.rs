@@ -19,7 +19,7 @@ use sui_types::object::Owner;
 use sui_types::signature::GenericSignature;
 use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
 use sui_types::transaction::{
-    CallArg, ObjectArg, ProgrammableTransaction, Transaction, TransactionData,
+    CallArg, ObjectArg, ProgrammableTransaction, Transaction, TransactionData, TransactionDataAPI,
     DEFAULT_VALIDATOR_GAS_PRICE, TEST_ONLY_GAS_UNIT_FOR_HEAVY_COMPUTATION_STORAGE,
     TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
 };
@@ -32,6 +32,7 @@ pub struct TestTransactionBuilder {
     gas_object: ObjectRef,
     gas_price: u64,
     gas_budget: Option<u64>,
+    sponsor: Option<SuiAddress>,
 }
 
 impl TestTransactionBuilder {
@@ -42,6 +43,7 @@ impl TestTransactionBuilder {
             gas_object,
             gas_price,
             gas_budget: None,
+            sponsor: None,
         }
     }
 
@@ -53,6 +55,10 @@ impl TestTransactionBuilder {
         self.gas_object
     }
 
+    pub fn sponsor(&self) -> Option<SuiAddress> {
+        self.sponsor
+    }
+
     // Use `with_type_args` below to provide type args if any
     pub fn move_call(
         mut self,
@@ -87,6 +93,15 @@ impl TestTransactionBuilder {
         self
     }
 
+    /// Pay for the transaction with `sponsor_gas`, owned by `sponsor`, instead of the sender's gas
+    /// object. The transaction then needs to be signed by both the sender and the sponsor, e.g. with
+    /// `build_and_sign_sponsored`.
+    pub fn with_sponsor(mut self, sponsor: SuiAddress, sponsor_gas: ObjectRef) -> Self {
+        self.sponsor = Some(sponsor);
+        self.gas_object = sponsor_gas;
+        self
+    }
+
     pub fn call_counter_create(self, package_id: ObjectID) -> Self {
         self.move_call(package_id, "counter", "create", vec![])
     }
@@ -336,7 +351,8 @@ impl TestTransactionBuilder {
     }
 
     pub fn build(self) -> TransactionData {
-        match self.test_data {
+        let sponsor = self.sponsor;
+        let mut data = match self.test_data {
             TestTransactionData::Move(data) => TransactionData::new_move_call(
                 self.sender,
                 data.package_id,
@@ -416,13 +432,33 @@ impl TestTransactionBuilder {
             TestTransactionData::Empty => {
                 panic!("Cannot build empty transaction");
             }
+        };
+
+        if let Some(sponsor) = sponsor {
+            data.gas_data_mut().owner = sponsor;
         }
+
+        data
     }
 
     pub fn build_and_sign(self, signer: &dyn Signer<Signature>) -> Transaction {
         Transaction::from_data_and_signer(self.build(), vec![signer])
     }
 
+    /// Build a sponsored transaction (see `with_sponsor`), signed by both the sender and the
+    /// sponsor.
+    pub fn build_and_sign_sponsored(
+        self,
+        sender_signer: &dyn Signer<Signature>,
+        sponsor_signer: &dyn Signer<Signature>,
+    ) -> Transaction {
+        assert!(
+            self.sponsor.is_some(),
+            "Cannot sign a transaction without a sponsor as sponsored"
+        );
+        Transaction::from_data_and_signer(self.build(), vec![sender_signer, sponsor_signer])
+    }
+
     pub fn build_and_sign_multisig(
         self,
         multisig_pk: MultiSigPublicKey,
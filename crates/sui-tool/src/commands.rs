@@ -5,8 +5,9 @@ use crate::{
     check_completed_snapshot,
     db_tool::{execute_db_tool_command, print_db_all_tables, DbToolCommand},
     download_db_snapshot, download_formal_snapshot, get_latest_available_epoch, get_object,
-    get_transaction_block, make_clients, restore_from_db_checkpoint, ConciseObjectOutput,
-    GroupedObjectOutput, SnapshotVerifyMode, VerboseObjectOutput,
+    get_transaction_block, make_clients, restore_from_db_checkpoint,
+    scheduler_dump::dump_scheduler_state,
+    ConciseObjectOutput, GroupedObjectOutput, SnapshotVerifyMode, VerboseObjectOutput,
 };
 use anyhow::Result;
 use consensus_core::storage::{rocksdb_store::RocksDBStore, Store};
@@ -140,6 +141,24 @@ pub enum ToolCommand {
         #[command(subcommand)]
         cmd: Option<DbToolCommand>,
     },
+
+    /// Reconstruct the balance withdraw scheduler's state by replaying the executed checkpoints
+    /// in a node's DB from genesis, and check it for anomalies, such as negative balances or
+    /// reservations that can never be settled. Exits with an error if any are found.
+    #[command(name = "scheduler-dump")]
+    SchedulerDump {
+        /// Path of the DB to read
+        #[arg(long = "db")]
+        db_path: PathBuf,
+
+        /// Stop after this checkpoint, instead of the highest executed checkpoint.
+        #[arg(long)]
+        end_checkpoint: Option<CheckpointSequenceNumber>,
+
+        /// Print the reconstructed state of every address balance.
+        #[arg(long)]
+        show_accounts: bool,
+    },
     /// Download all packages to the local filesystem from a GraphQL service. Each package gets its
     /// own sub-directory, named for its ID on chain and version containing two metadata files
     /// (linkage.json and origins.json), a file containing the overall object and a file for every
@@ -583,6 +602,11 @@ impl ToolCommand {
                     None => print_db_all_tables(path)?,
                 }
             }
+            ToolCommand::SchedulerDump {
+                db_path,
+                end_checkpoint,
+                show_accounts,
+            } => dump_scheduler_state(&db_path, end_checkpoint, show_accounts)?,
             ToolCommand::DumpPackages {
                 rpc_url,
                 output_dir,
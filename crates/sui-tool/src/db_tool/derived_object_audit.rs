@@ -0,0 +1,189 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audit of the live object set for dynamic fields whose IDs fall in the space reserved for
+//! derived objects.
+//!
+//! Derived object IDs are computed like dynamic field IDs, with the key wrapped in
+//! `0x2::derived_object::DerivedObjectKey<K>`, so the object derived from a parent with some key
+//! can never collide with the parent's dynamic field under the same key. The audit checks that
+//! this separation holds for every live dynamic field: that its ID is the one derived from its
+//! parent and key, that the same parent and key derive a different object ID, and it reports
+//! fields that are themselves keyed by a `DerivedObjectKey`, which occupy a derived object's ID.
+
+use std::path::Path;
+
+use anyhow::bail;
+use move_core_types::language_storage::TypeTag;
+use sui_core::authority::authority_store_tables::{AuthorityPerpetualTables, LiveObject};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::derived_object::{derive_object_id, is_derived_object_key};
+use sui_types::dynamic_field::{derive_dynamic_field_id, DynamicFieldType};
+use sui_types::object::{Object, Owner};
+
+/// Keys are recovered from a field's contents by searching for the prefix of its contents (after
+/// its UID) that derives its ID. Keys longer than this are not recovered, and are only counted.
+const MAX_KEY_BYTES: usize = 1024;
+
+#[derive(Default)]
+struct AuditReport {
+    /// Live dynamic fields that were audited.
+    fields: usize,
+
+    /// Dynamic fields whose keys were too long to recover.
+    unrecovered: usize,
+
+    /// Dynamic fields whose ID is not derived from their parent and key.
+    mismatched: Vec<ObjectID>,
+
+    /// Dynamic fields whose parent and key derive the same ID as a derived object.
+    inseparable: Vec<ObjectID>,
+
+    /// Dynamic fields keyed by a `DerivedObjectKey`, and the type of the key that they hold the
+    /// place of.
+    occupied: Vec<(ObjectID, SuiAddress, TypeTag)>,
+}
+
+pub fn audit_derived_object_ids(path: &Path) -> anyhow::Result<()> {
+    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None);
+
+    let mut report = AuditReport::default();
+    for object in perpetual_db.iter_live_object_set(false) {
+        if let LiveObject::Normal(object) = object {
+            report.audit(&object)?;
+        }
+    }
+
+    println!("Audited {} live dynamic fields", report.fields);
+    if report.unrecovered > 0 {
+        println!(
+            "Skipped {} dynamic fields with keys longer than {MAX_KEY_BYTES} bytes",
+            report.unrecovered,
+        );
+    }
+
+    for id in &report.mismatched {
+        println!("Dynamic field {id} does not have the ID derived from its parent and key");
+    }
+
+    for id in &report.inseparable {
+        println!(
+            "Dynamic field {id} has the same ID as the object derived from its parent and key"
+        );
+    }
+
+    for (id, parent, key_type) in &report.occupied {
+        println!(
+            "Dynamic field {id} occupies the ID of an object derived from {parent} with a key of \
+             type {key_type}",
+        );
+    }
+
+    let violations = report.mismatched.len() + report.inseparable.len() + report.occupied.len();
+    if violations > 0 {
+        bail!("Found {violations} derived object ID violations");
+    }
+
+    println!("No derived object ID violations found");
+    Ok(())
+}
+
+impl AuditReport {
+    fn audit(&mut self, object: &Object) -> anyhow::Result<()> {
+        let Some(move_object) = object.data.try_as_move() else {
+            return Ok(());
+        };
+
+        if !move_object.type_().is_dynamic_field() {
+            return Ok(());
+        }
+
+        let Owner::ObjectOwner(parent) = object.owner() else {
+            return Ok(());
+        };
+
+        let id = object.id();
+        let key_type = move_object
+            .type_()
+            .try_extract_field_name(&DynamicFieldType::DynamicField)?;
+
+        self.fields += 1;
+        if let TypeTag::Struct(tag) = &key_type {
+            if is_derived_object_key(tag) {
+                if let Some(inner) = tag.type_params.first() {
+                    self.occupied.push((id, *parent, inner.clone()));
+                }
+            }
+        }
+
+        let Some(key) = recover_key(*parent, &key_type, id, move_object.contents())? else {
+            if move_object
+                .contents()
+                .len()
+                .saturating_sub(ObjectID::LENGTH)
+                > MAX_KEY_BYTES
+            {
+                self.unrecovered += 1;
+            } else {
+                self.mismatched.push(id);
+            }
+            return Ok(());
+        };
+
+        if derive_object_id(*parent, &key_type, key)? == id {
+            self.inseparable.push(id);
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the BCS-serialized key of the dynamic field with ID `id` owned by `parent`, among the
+/// prefixes of its `contents` after its UID, as the one that derives its ID. Returns `None` if no
+/// prefix up to [MAX_KEY_BYTES] long does.
+fn recover_key<'c>(
+    parent: SuiAddress,
+    key_type: &TypeTag,
+    id: ObjectID,
+    contents: &'c [u8],
+) -> anyhow::Result<Option<&'c [u8]>> {
+    let rest = contents.get(ObjectID::LENGTH..).unwrap_or_default();
+    for len in 0..=rest.len().min(MAX_KEY_BYTES) {
+        let key = &rest[..len];
+        if derive_dynamic_field_id(parent, key_type, key)? == id {
+            return Ok(Some(key));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::dynamic_field::serialize_dynamic_field;
+    use sui_types::id::UID;
+
+    use super::*;
+
+    #[test]
+    fn test_recover_key() {
+        let parent = SuiAddress::random_for_testing_only();
+        let key = "derived".to_string();
+        let key_bytes = bcs::to_bytes(&key).unwrap();
+        let key_type = TypeTag::Vector(Box::new(TypeTag::U8));
+
+        let id = derive_dynamic_field_id(parent, &key_type, &key_bytes).unwrap();
+        let contents = serialize_dynamic_field(&UID::new(id), &key, 42u64).unwrap();
+
+        let recovered = recover_key(parent, &key_type, id, &contents).unwrap();
+        assert_eq!(recovered, Some(key_bytes.as_slice()));
+
+        // The same parent and key derive a different object ID.
+        let derived = derive_object_id(parent, &key_type, &key_bytes).unwrap();
+        assert_ne!(derived, id);
+
+        // A field under a different parent doesn't derive the same ID.
+        let other = SuiAddress::random_for_testing_only();
+        assert_eq!(recover_key(other, &key_type, id, &contents).unwrap(), None);
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use self::db_dump::{dump_table, duplicate_objects_summary, list_tables, table_summary, StoreName};
+use self::derived_object_audit::audit_derived_object_ids;
 use self::index_search::{search_index, SearchRange};
 use crate::db_tool::db_dump::{compact, print_table_metadata, prune_checkpoints, prune_objects};
 use anyhow::{anyhow, bail};
@@ -16,6 +17,7 @@ use sui_types::effects::TransactionEffectsAPI;
 use sui_types::messages_checkpoint::{CheckpointDigest, CheckpointSequenceNumber};
 use typed_store::rocks::{safe_drop_db, MetricConf};
 pub mod db_dump;
+mod derived_object_audit;
 mod index_search;
 
 #[derive(Parser)]
@@ -40,6 +42,9 @@ pub enum DbToolCommand {
     PruneObjects,
     PruneCheckpoints,
     SetCheckpointWatermark(SetCheckpointWatermarkOptions),
+    /// Check that no live dynamic field occupies, or could be confused with, the ID of a derived
+    /// object. Exits with an error if any violations are found.
+    AuditDerivedObjectIds,
 }
 
 #[derive(Parser)]
@@ -232,6 +237,7 @@ pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> an
             Ok(())
         }
         DbToolCommand::SetCheckpointWatermark(d) => set_checkpoint_watermark(&db_path, d),
+        DbToolCommand::AuditDerivedObjectIds => audit_derived_object_ids(&db_path),
     }
 }
 
@@ -69,6 +69,7 @@ use tracing::info;
 pub mod commands;
 pub mod db_tool;
 mod formal_snapshot_util;
+pub mod scheduler_dump;
 
 #[derive(
     Clone, Serialize, Deserialize, Debug, PartialEq, Copy, PartialOrd, Ord, Eq, ValueEnum, Default,
@@ -0,0 +1,380 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline reconstruction of the balance withdraw scheduler's state from a node's DB.
+//!
+//! The scheduler only keeps its reservations in memory, so there is no persisted state to dump.
+//! Instead, the state is replayed from the executed checkpoints: every transaction that withdraws
+//! from an address balance reserves the amounts in its inputs, its effects record what it
+//! actually deposited and withdrew, and the balance changes of each checkpoint are settled at its
+//! end. Replaying from genesis gives the settled balance of every address balance, which the
+//! analyzer checks for anomalies that the live scheduler should have made impossible.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
+use sui_core::checkpoints::CheckpointStore;
+use sui_types::accumulator_event::{AccumulatorEvent, AddressBalanceEvent};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::transaction::TransactionDataAPI;
+use sui_types::TypeTag;
+
+/// The withdraw scheduler's state, as implied by the checkpoints replayed so far.
+#[derive(Default)]
+struct SchedulerState {
+    /// The last checkpoint whose balance changes were settled.
+    last_settled: Option<CheckpointSequenceNumber>,
+
+    /// Transactions replayed, and how many of them reserved balance.
+    transactions: usize,
+    withdraws: usize,
+
+    accounts: BTreeMap<ObjectID, AccountState>,
+
+    /// Net balance changes of the checkpoint being replayed, applied when it is settled.
+    unsettled: BTreeMap<ObjectID, i128>,
+
+    anomalies: Vec<Anomaly>,
+}
+
+#[derive(Default)]
+struct AccountState {
+    /// The address and coin type of the balance, once an event has touched it.
+    owner: Option<(SuiAddress, TypeTag)>,
+
+    /// The balance as of the last settled checkpoint.
+    balance: i128,
+
+    /// Totals over every transaction replayed.
+    deposited: u128,
+    reserved: u128,
+    withdrawn: u128,
+}
+
+enum Anomaly {
+    /// Settling a checkpoint left an address balance below zero.
+    NegativeBalance {
+        account: ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+        balance: i128,
+    },
+
+    /// A transaction withdrew more from an address balance than it reserved.
+    Overdrawn {
+        tx_digest: TransactionDigest,
+        account: ObjectID,
+        reserved: u64,
+        withdrawn: u64,
+    },
+
+    /// A transaction reserved balance, but its effects are missing, so its reservations can never
+    /// be reconciled with a settlement.
+    OrphanedReservation {
+        tx_digest: TransactionDigest,
+        checkpoint: CheckpointSequenceNumber,
+        reservations: BTreeMap<ObjectID, u64>,
+    },
+}
+
+/// Replay the checkpoints executed by the node whose DB is at `path`, from genesis up to
+/// `end_checkpoint` (or the highest executed checkpoint), print the implied scheduler state, and
+/// fail if any anomalies were found.
+pub fn dump_scheduler_state(
+    path: &Path,
+    end_checkpoint: Option<CheckpointSequenceNumber>,
+    show_accounts: bool,
+) -> anyhow::Result<()> {
+    let perpetual_db = AuthorityPerpetualTables::open(&path.join("store"), None);
+    let checkpoint_store = CheckpointStore::new(&path.join("checkpoints"));
+
+    let Some(highest_executed) = checkpoint_store.get_highest_executed_checkpoint_seq_number()?
+    else {
+        bail!("No checkpoints have been executed");
+    };
+
+    let end = end_checkpoint.unwrap_or(highest_executed);
+    if end > highest_executed {
+        bail!("Checkpoint {end} has not been executed (highest executed: {highest_executed})");
+    }
+
+    let mut state = SchedulerState::default();
+    for seq in 0..=end {
+        let checkpoint = checkpoint_store
+            .get_checkpoint_by_sequence_number(seq)?
+            .ok_or_else(|| anyhow!("Checkpoint {seq} not found, it may have been pruned"))?;
+
+        let contents = checkpoint_store
+            .get_checkpoint_contents(&checkpoint.content_digest)?
+            .ok_or_else(|| anyhow!("Contents of checkpoint {seq} not found"))?;
+
+        for digests in contents.iter() {
+            let tx_digest = digests.transaction;
+            let transaction = perpetual_db.get_transaction(&tx_digest)?.ok_or_else(|| {
+                anyhow!("Transaction {tx_digest} not found, it may have been pruned")
+            })?;
+
+            let reservations = transaction
+                .inner()
+                .data()
+                .transaction_data()
+                .process_balance_withdraws()
+                .map_err(|e| anyhow!("Invalid withdraw reservations in {tx_digest}: {e}"))?;
+
+            let events = perpetual_db
+                .get_effects(&tx_digest)?
+                .map(|effects| effects.accumulator_events());
+
+            state.replay_transaction(seq, tx_digest, reservations, events);
+        }
+
+        state.settle(seq);
+    }
+
+    state.print(show_accounts);
+
+    if !state.anomalies.is_empty() {
+        bail!("Found {} anomalies", state.anomalies.len());
+    }
+
+    println!("No anomalies found");
+    Ok(())
+}
+
+impl SchedulerState {
+    /// Replay a transaction from `checkpoint` that reserved `reservations` from address balances,
+    /// and whose effects produced `events`, or `None` if its effects are missing.
+    fn replay_transaction(
+        &mut self,
+        checkpoint: CheckpointSequenceNumber,
+        tx_digest: TransactionDigest,
+        reservations: BTreeMap<ObjectID, u64>,
+        events: Option<Vec<AccumulatorEvent>>,
+    ) {
+        self.transactions += 1;
+        if !reservations.is_empty() {
+            self.withdraws += 1;
+        }
+
+        for (account, reserved) in &reservations {
+            self.accounts.entry(*account).or_default().reserved += *reserved as u128;
+        }
+
+        let Some(events) = events else {
+            if !reservations.is_empty() {
+                self.anomalies.push(Anomaly::OrphanedReservation {
+                    tx_digest,
+                    checkpoint,
+                    reservations,
+                });
+            }
+            return;
+        };
+
+        let mut withdrawn: BTreeMap<ObjectID, u64> = BTreeMap::new();
+        for event in &events {
+            let Some(balance_event) = event.address_balance_event() else {
+                continue;
+            };
+
+            let account = self.accounts.entry(event.accumulator_obj).or_default();
+            account.owner.get_or_insert_with(|| {
+                (balance_event.address(), balance_event.coin_type().clone())
+            });
+
+            match &balance_event {
+                AddressBalanceEvent::Deposit { amount, .. } => {
+                    account.deposited += *amount as u128;
+                }
+                AddressBalanceEvent::Withdraw { amount, .. } => {
+                    account.withdrawn += *amount as u128;
+                    let total = withdrawn.entry(event.accumulator_obj).or_default();
+                    *total = total.saturating_add(*amount);
+                }
+                AddressBalanceEvent::Settlement { .. } => {}
+            }
+
+            *self.unsettled.entry(event.accumulator_obj).or_default() += balance_event.amount();
+        }
+
+        for (account, withdrawn) in withdrawn {
+            let reserved = reservations.get(&account).copied().unwrap_or_default();
+            if withdrawn > reserved {
+                self.anomalies.push(Anomaly::Overdrawn {
+                    tx_digest,
+                    account,
+                    reserved,
+                    withdrawn,
+                });
+            }
+        }
+    }
+
+    /// Settle the balance changes of `checkpoint`, at its end.
+    fn settle(&mut self, checkpoint: CheckpointSequenceNumber) {
+        for (account, change) in std::mem::take(&mut self.unsettled) {
+            let state = self.accounts.entry(account).or_default();
+            state.balance += change;
+            if state.balance < 0 {
+                self.anomalies.push(Anomaly::NegativeBalance {
+                    account,
+                    checkpoint,
+                    balance: state.balance,
+                });
+            }
+        }
+
+        self.last_settled = Some(checkpoint);
+    }
+
+    fn print(&self, show_accounts: bool) {
+        if let Some(last_settled) = self.last_settled {
+            println!("Replayed checkpoints 0 to {last_settled}");
+        }
+
+        println!(
+            "{} transactions, {} with balance withdraws, touching {} address balances",
+            self.transactions,
+            self.withdraws,
+            self.accounts.len(),
+        );
+
+        if show_accounts {
+            for (id, account) in &self.accounts {
+                let owner = match &account.owner {
+                    Some((address, coin_type)) => format!("{address} {coin_type}"),
+                    None => "unknown owner".to_string(),
+                };
+
+                println!(
+                    "{id} ({owner}): balance {}, deposited {}, withdrawn {}, reserved {}",
+                    account.balance, account.deposited, account.withdrawn, account.reserved,
+                );
+            }
+        }
+
+        for anomaly in &self.anomalies {
+            println!("{anomaly}");
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::NegativeBalance {
+                account,
+                checkpoint,
+                balance,
+            } => write!(
+                f,
+                "Address balance {account} settled to {balance} at checkpoint {checkpoint}"
+            ),
+
+            Anomaly::Overdrawn {
+                tx_digest,
+                account,
+                reserved,
+                withdrawn,
+            } => write!(
+                f,
+                "Transaction {tx_digest} withdrew {withdrawn} from {account}, but only reserved \
+                 {reserved}"
+            ),
+
+            Anomaly::OrphanedReservation {
+                tx_digest,
+                checkpoint,
+                reservations,
+            } => write!(
+                f,
+                "Transaction {tx_digest} in checkpoint {checkpoint} reserved {reservations:?}, \
+                 but its effects are missing"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::balance::Balance;
+    use sui_types::effects::{
+        AccumulatorAddress, AccumulatorOperation, AccumulatorValue, AccumulatorWriteV1,
+    };
+    use sui_types::gas_coin::GAS;
+
+    use super::*;
+
+    fn event(account: ObjectID, operation: AccumulatorOperation, amount: u64) -> AccumulatorEvent {
+        let address = AccumulatorAddress::new(SuiAddress::ZERO, Balance::type_tag(GAS::type_tag()));
+
+        AccumulatorEvent::new(
+            account,
+            AccumulatorWriteV1 {
+                address,
+                operation,
+                value: AccumulatorValue::Integer(amount),
+            },
+        )
+    }
+
+    #[test]
+    fn test_replay_flags_anomalies() {
+        let account = ObjectID::random();
+        let deposit = TransactionDigest::random();
+        let overdraw = TransactionDigest::random();
+        let orphan = TransactionDigest::random();
+
+        let mut state = SchedulerState::default();
+        state.replay_transaction(
+            0,
+            deposit,
+            BTreeMap::new(),
+            Some(vec![event(account, AccumulatorOperation::Merge, 100)]),
+        );
+        state.settle(0);
+        assert!(state.anomalies.is_empty());
+        assert_eq!(state.accounts[&account].balance, 100);
+
+        // Withdrawing more than was reserved, and more than the settled balance.
+        state.replay_transaction(
+            1,
+            overdraw,
+            BTreeMap::from([(account, 100)]),
+            Some(vec![event(account, AccumulatorOperation::Split, 150)]),
+        );
+
+        // Reserving without effects to reconcile the reservation against.
+        state.replay_transaction(1, orphan, BTreeMap::from([(account, 10)]), None);
+        state.settle(1);
+
+        assert_eq!(state.transactions, 3);
+        assert_eq!(state.withdraws, 2);
+        assert_eq!(state.accounts[&account].balance, -50);
+        assert_eq!(state.accounts[&account].reserved, 110);
+
+        let anomalies: Vec<_> = state.anomalies.iter().map(|a| a.to_string()).collect();
+        assert_eq!(anomalies.len(), 3, "{anomalies:#?}");
+        assert!(matches!(
+            state.anomalies[0],
+            Anomaly::Overdrawn { tx_digest, reserved: 100, withdrawn: 150, .. } if tx_digest == overdraw
+        ));
+        assert!(matches!(
+            state.anomalies[1],
+            Anomaly::OrphanedReservation { tx_digest, checkpoint: 1, .. } if tx_digest == orphan
+        ));
+        assert!(matches!(
+            state.anomalies[2],
+            Anomaly::NegativeBalance {
+                balance: -50,
+                checkpoint: 1,
+                ..
+            }
+        ));
+    }
+}
@@ -1,12 +1,15 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::BTreeMap;
+
 use move_core_types::ident_str;
 use move_core_types::identifier::IdentStr;
 use mysten_common::fatal;
+use serde::{Deserialize, Serialize};
 
 use crate::balance::Balance;
-use crate::base_types::ObjectID;
+use crate::base_types::{ObjectID, SuiAddress};
 use crate::effects::{
     AccumulatorAddress, AccumulatorOperation, AccumulatorValue, AccumulatorWriteV1,
 };
@@ -21,6 +24,83 @@ pub struct AccumulatorEvent {
     pub write: AccumulatorWriteV1,
 }
 
+/// What an accumulator write means for the address balance it touches.
+///
+/// Accumulator writes in effects are generic over the accumulator's type and value. This is the
+/// interpretation of the ones that operate on address balances, so that consumers don't need to
+/// know how balances are laid out in accumulators to follow them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AddressBalanceEvent {
+    /// `amount` of `coin_type` was deposited into `address`'s balance by a transaction. The
+    /// deposit is applied to the balance when the transaction's accumulator version is settled.
+    Deposit {
+        address: SuiAddress,
+        coin_type: TypeTag,
+        amount: u64,
+    },
+
+    /// `amount` of `coin_type` was withdrawn from `address`'s balance by a transaction.
+    Withdraw {
+        address: SuiAddress,
+        coin_type: TypeTag,
+        amount: u64,
+    },
+
+    /// The deposits and withdraws of a set of transactions were applied to `address`'s balance
+    /// of `coin_type`, changing it by `net_change`.
+    Settlement {
+        address: SuiAddress,
+        coin_type: TypeTag,
+        net_change: i128,
+    },
+}
+
+impl AddressBalanceEvent {
+    pub fn address(&self) -> SuiAddress {
+        match self {
+            Self::Deposit { address, .. }
+            | Self::Withdraw { address, .. }
+            | Self::Settlement { address, .. } => *address,
+        }
+    }
+
+    pub fn coin_type(&self) -> &TypeTag {
+        match self {
+            Self::Deposit { coin_type, .. }
+            | Self::Withdraw { coin_type, .. }
+            | Self::Settlement { coin_type, .. } => coin_type,
+        }
+    }
+
+    /// The change to the balance, negative for withdraws.
+    pub fn amount(&self) -> i128 {
+        match self {
+            Self::Deposit { amount, .. } => *amount as i128,
+            Self::Withdraw { amount, .. } => -(*amount as i128),
+            Self::Settlement { net_change, .. } => *net_change,
+        }
+    }
+
+    /// The settlements that apply `events` to the balances they touch: one per address balance
+    /// whose net change is not zero, ordered by address and then coin type.
+    pub fn settle(events: impl IntoIterator<Item = AddressBalanceEvent>) -> Vec<Self> {
+        let mut net: BTreeMap<(SuiAddress, TypeTag), i128> = BTreeMap::new();
+        for event in events {
+            *net.entry((event.address(), event.coin_type().clone()))
+                .or_default() += event.amount();
+        }
+
+        net.into_iter()
+            .filter(|(_, net_change)| *net_change != 0)
+            .map(|((address, coin_type), net_change)| Self::Settlement {
+                address,
+                coin_type,
+                net_change,
+            })
+            .collect()
+    }
+}
+
 impl AccumulatorEvent {
     pub fn new(accumulator_obj: ObjectID, write: AccumulatorWriteV1) -> Self {
         Self {
@@ -29,6 +109,42 @@ impl AccumulatorEvent {
         }
     }
 
+    /// Interpret this event as a deposit into or a withdraw from an address balance. Returns
+    /// `None` if the event is for an accumulator that does not hold a balance.
+    pub fn address_balance_event(&self) -> Option<AddressBalanceEvent> {
+        let AccumulatorWriteV1 {
+            address: AccumulatorAddress { address, ty },
+            operation,
+            value,
+        } = &self.write;
+
+        let TypeTag::Struct(struct_ty) = ty else {
+            return None;
+        };
+
+        if !Balance::is_balance(struct_ty) {
+            return None;
+        }
+
+        let coin_type = struct_ty.type_params.first()?.clone();
+        let AccumulatorValue::Integer(amount) = value else {
+            return None;
+        };
+
+        Some(match operation {
+            AccumulatorOperation::Merge => AddressBalanceEvent::Deposit {
+                address: *address,
+                coin_type,
+                amount: *amount,
+            },
+            AccumulatorOperation::Split => AddressBalanceEvent::Withdraw {
+                address: *address,
+                coin_type,
+                amount: *amount,
+            },
+        })
+    }
+
     pub fn total_sui_in_event(&self) -> (u64 /* input */, u64 /* output */) {
         let Self {
             write:
@@ -75,3 +191,76 @@ impl AccumulatorEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance_event(
+        address: SuiAddress,
+        operation: AccumulatorOperation,
+        amount: u64,
+    ) -> AccumulatorEvent {
+        AccumulatorEvent::new(
+            ObjectID::random(),
+            AccumulatorWriteV1 {
+                address: AccumulatorAddress::new(address, Balance::type_tag(GAS::type_tag())),
+                operation,
+                value: AccumulatorValue::Integer(amount),
+            },
+        )
+    }
+
+    #[test]
+    fn test_address_balance_events() {
+        let alice = SuiAddress::random_for_testing_only();
+        let bob = SuiAddress::random_for_testing_only();
+
+        let events: Vec<_> = [
+            balance_event(alice, AccumulatorOperation::Merge, 100),
+            balance_event(alice, AccumulatorOperation::Split, 30),
+            balance_event(bob, AccumulatorOperation::Split, 50),
+            balance_event(bob, AccumulatorOperation::Merge, 50),
+        ]
+        .iter()
+        .map(|event| event.address_balance_event().unwrap())
+        .collect();
+
+        assert_eq!(
+            events[0],
+            AddressBalanceEvent::Deposit {
+                address: alice,
+                coin_type: GAS::type_tag(),
+                amount: 100,
+            }
+        );
+        assert_eq!(events[1].amount(), -30);
+
+        // Bob's deposits and withdraws cancel out, so his balance needs no settlement.
+        assert_eq!(
+            AddressBalanceEvent::settle(events),
+            vec![AddressBalanceEvent::Settlement {
+                address: alice,
+                coin_type: GAS::type_tag(),
+                net_change: 70,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_non_balance_accumulator() {
+        let event = AccumulatorEvent::new(
+            ObjectID::random(),
+            AccumulatorWriteV1 {
+                address: AccumulatorAddress::new(
+                    SuiAddress::random_for_testing_only(),
+                    GAS::type_tag(),
+                ),
+                operation: AccumulatorOperation::Merge,
+                value: AccumulatorValue::Integer(1),
+            },
+        );
+
+        assert_eq!(event.address_balance_event(), None);
+    }
+}
@@ -3,7 +3,7 @@
 
 use crate::base_types::SuiAddress;
 use crate::coin::Coin;
-use crate::effects::TransactionEffects;
+use crate::effects::{TransactionEffects, TransactionEffectsAPI};
 use crate::object::Object;
 use crate::object::Owner;
 use move_core_types::language_storage::TypeTag;
@@ -38,7 +38,7 @@ fn coins(objects: &[Object]) -> impl Iterator<Item = (&SuiAddress, TypeTag, u64)
 }
 
 pub fn derive_balance_changes(
-    _effects: &TransactionEffects,
+    effects: &TransactionEffects,
     input_objects: &[Object],
     output_objects: &[Object],
 ) -> Vec<BalanceChange> {
@@ -46,7 +46,7 @@ pub fn derive_balance_changes(
     let balances = coins(input_objects).fold(
         std::collections::BTreeMap::<_, i128>::new(),
         |mut acc, (address, coin_type, balance)| {
-            *acc.entry((address, coin_type)).or_default() -= balance as i128;
+            *acc.entry((*address, coin_type)).or_default() -= balance as i128;
             acc
         },
     );
@@ -54,7 +54,18 @@ pub fn derive_balance_changes(
     // 2. add all mutated/output coins
     let balances =
         coins(output_objects).fold(balances, |mut acc, (address, coin_type, balance)| {
-            *acc.entry((address, coin_type)).or_default() += balance as i128;
+            *acc.entry((*address, coin_type)).or_default() += balance as i128;
+            acc
+        });
+
+    // 3. add deposits into and subtract withdraws from address balances
+    let events = effects.accumulator_events();
+    let balances = events
+        .iter()
+        .filter_map(|event| event.address_balance_event())
+        .fold(balances, |mut acc, event| {
+            *acc.entry((event.address(), event.coin_type().clone()))
+                .or_default() += event.amount();
             acc
         });
 
@@ -66,7 +77,7 @@ pub fn derive_balance_changes(
             }
 
             Some(BalanceChange {
-                address: *address,
+                address,
                 coin_type,
                 amount,
             })
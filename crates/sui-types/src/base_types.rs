@@ -107,11 +107,7 @@ pub struct SequenceNumber(u64);
 
 impl SequenceNumber {
     pub fn one_before(&self) -> Option<SequenceNumber> {
-        if self.0 == 0 {
-            None
-        } else {
-            Some(SequenceNumber(self.0 - 1))
-        }
+        self.checked_decrement()
     }
 
     pub fn next(&self) -> SequenceNumber {
@@ -1454,6 +1450,19 @@ impl SequenceNumber {
         *self = prev;
     }
 
+    /// The sequence number before this one, or `None` if this is the first sequence number.
+    #[must_use]
+    pub fn checked_decrement(&self) -> Option<SequenceNumber> {
+        self.0.checked_sub(1).map(SequenceNumber)
+    }
+
+    /// The sequence number after this one, or `SequenceNumber::MAX` if there is none. Unlike
+    /// `next`, this never produces one of the reserved sequence numbers above `MAX`.
+    #[must_use]
+    pub fn saturating_next(&self) -> SequenceNumber {
+        SequenceNumber(self.0.saturating_add(1).min(SequenceNumber::MAX.0))
+    }
+
     /// Returns a new sequence number that is greater than all `SequenceNumber`s in `inputs`,
     /// assuming this operation will not overflow.
     #[must_use]
@@ -1498,6 +1507,40 @@ impl From<SequenceNumber> for usize {
     }
 }
 
+/// Parse a `0x`-prefixed hex string into the bytes of an address, padding it with leading 0s if it
+/// is too short. Panics if the string is not a valid hex literal.
+pub(crate) const fn hex_literal_bytes_const(literal: &str) -> [u8; ObjectID::LENGTH] {
+    let literal = literal.as_bytes();
+    if literal.len() < 2 || literal[0] != b'0' || literal[1] != b'x' {
+        panic!("Hex literal must start with 0x");
+    }
+
+    let hex_len = literal.len() - 2;
+    if hex_len > ObjectID::LENGTH * 2 {
+        panic!("Hex literal is too long");
+    }
+
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("Invalid hex character in hex literal"),
+        }
+    }
+
+    // Digits are read from the end of the literal, so that short literals are padded with 0s.
+    let mut bytes = [0u8; ObjectID::LENGTH];
+    let mut i = 0;
+    while i < hex_len {
+        let digit = nibble(literal[literal.len() - 1 - i]);
+        bytes[ObjectID::LENGTH - 1 - i / 2] |= digit << (4 * (i % 2));
+        i += 1;
+    }
+
+    bytes
+}
+
 impl ObjectID {
     /// The number of bytes in an address.
     pub const LENGTH: usize = AccountAddress::LENGTH;
@@ -1574,6 +1617,13 @@ impl ObjectID {
         }
     }
 
+    /// Const fn variant of [`ObjectID::from_hex_literal`], for defining ObjectIDs as constants.
+    /// Panics (failing compilation, when evaluated in a const context) if `literal` is not a
+    /// `0x`-prefixed hex string of at most [`ObjectID::LENGTH`] bytes.
+    pub const fn from_hex_literal_const(literal: &str) -> Self {
+        Self::new(hex_literal_bytes_const(literal))
+    }
+
     /// Create an ObjectID from `TransactionDigest` and `creation_num`.
     /// Caller is responsible for ensuring that `creation_num` is fresh
     pub fn derive_id(digest: TransactionDigest, creation_num: u64) -> Self {
@@ -0,0 +1,314 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::ident_str;
+use move_core_types::identifier::IdentStr;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde::Serialize;
+use shared_crypto::intent::HashingIntentScope;
+use sui_derived_object_id::blake2b::Blake2b256;
+
+use crate::base_types::{hex_literal_bytes_const, ObjectID, ObjectRef, SuiAddress};
+use crate::committee::EpochId;
+use crate::dynamic_field::derive_dynamic_field_id;
+use crate::object::Owner;
+use crate::{MoveTypeTagTrait, SUI_FRAMEWORK_ADDRESS};
+
+pub const DERIVED_OBJECT_MODULE_NAME: &IdentStr = ident_str!("derived_object");
+pub const DERIVED_OBJECT_KEY_STRUCT_NAME: &IdentStr = ident_str!("DerivedObjectKey");
+pub const EPOCH_SCOPED_KEY_STRUCT_NAME: &IdentStr = ident_str!("EpochScopedKey");
+
+/// Which objects a derived object's ID is unique among, when it is derived from a parent and a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivationScope {
+    /// The ID is unique for the parent and key, so only one object can ever be derived from them.
+    Global,
+    /// The ID is unique for the parent and key within an epoch, so that the same parent (e.g. a
+    /// shared registry) and key can derive a new object in each epoch.
+    Epoch(EpochId),
+}
+
+/// An object created by a transaction, whose ID is derived from another object that the
+/// transaction touched, as found by
+/// [`crate::effects::TransactionEffectsAPI::created_derived_objects`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedObjectProvenance {
+    pub object_ref: ObjectRef,
+    pub owner: Owner,
+
+    /// The object that the ID is derived from.
+    pub parent: ObjectID,
+
+    /// The type of the key that the ID is derived with, and its BCS-serialized value.
+    pub key_type: TypeTag,
+    pub key_bytes: Vec<u8>,
+
+    pub scope: DerivationScope,
+}
+
+/// The type of the key that a derived object's ID is computed from, given the type of the key
+/// that the object was claimed with: `0x2::derived_object::DerivedObjectKey<K>`.
+pub fn derived_object_key_type(key_type_tag: TypeTag) -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: DERIVED_OBJECT_MODULE_NAME.to_owned(),
+        name: DERIVED_OBJECT_KEY_STRUCT_NAME.to_owned(),
+        type_params: vec![key_type_tag],
+    }))
+}
+
+/// The type of the key that an epoch-scoped derived object's key is wrapped in, before being
+/// wrapped in `DerivedObjectKey`: `0x2::derived_object::EpochScopedKey<K>`.
+pub fn epoch_scoped_key_type(key_type_tag: TypeTag) -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: DERIVED_OBJECT_MODULE_NAME.to_owned(),
+        name: EPOCH_SCOPED_KEY_STRUCT_NAME.to_owned(),
+        type_params: vec![key_type_tag],
+    }))
+}
+
+/// Whether `tag` is the type of a key that a derived object's ID is computed from:
+/// `0x2::derived_object::DerivedObjectKey<_>`.
+pub fn is_derived_object_key(tag: &StructTag) -> bool {
+    tag.address == SUI_FRAMEWORK_ADDRESS
+        && tag.module.as_ident_str() == DERIVED_OBJECT_MODULE_NAME
+        && tag.name.as_ident_str() == DERIVED_OBJECT_KEY_STRUCT_NAME
+}
+
+/// Compute the ID of the object derived from `parent` with a key of type `key_type_tag`, whose
+/// BCS-serialized value is `key_bytes`.
+///
+/// Derived object IDs are computed the same way as dynamic field IDs, with the key wrapped in
+/// `DerivedObjectKey`, so that they can never collide with the parent's dynamic fields.
+pub fn derive_object_id<T>(
+    parent: T,
+    key_type_tag: &TypeTag,
+    key_bytes: &[u8],
+) -> Result<ObjectID, bcs::Error>
+where
+    T: Into<SuiAddress>,
+{
+    let key_type = derived_object_key_type(key_type_tag.clone());
+    derive_dynamic_field_id(parent, &key_type, key_bytes)
+}
+
+/// Compute the ID of the object derived from `parent` within `scope`, with a key of type
+/// `key_type_tag`, whose BCS-serialized value is `key_bytes`.
+///
+/// Globally scoped IDs are the same as the ones computed by [`derive_object_id`]. Epoch-scoped IDs
+/// are derived from the key wrapped in `EpochScopedKey { epoch, key }`, which mixes the epoch into
+/// the hash, while keeping the IDs distinct from any globally scoped ID for the same parent.
+pub fn derive_object_id_scoped<T>(
+    parent: T,
+    key_type_tag: &TypeTag,
+    key_bytes: &[u8],
+    scope: DerivationScope,
+) -> Result<ObjectID, bcs::Error>
+where
+    T: Into<SuiAddress>,
+{
+    match scope {
+        DerivationScope::Global => derive_object_id(parent, key_type_tag, key_bytes),
+        DerivationScope::Epoch(epoch) => {
+            // `EpochScopedKey` is serialized as its fields, in order.
+            let mut scoped_key = bcs::to_bytes(&epoch)?;
+            scoped_key.extend_from_slice(key_bytes);
+
+            let scoped_key_type = epoch_scoped_key_type(key_type_tag.clone());
+            derive_object_id(parent, &scoped_key_type, &scoped_key)
+        }
+    }
+}
+
+/// Like [`derive_object_id`], but for a key whose Move type is known statically.
+pub fn derive_object_id_for_key<T, K>(parent: T, key: &K) -> Result<ObjectID, bcs::Error>
+where
+    T: Into<SuiAddress>,
+    K: MoveTypeTagTrait + Serialize,
+{
+    derive_object_id(parent, &K::get_type_tag(), &bcs::to_bytes(key)?)
+}
+
+/// A Move type, in a form that can be constructed and serialized in a const context, to describe
+/// the type of a key when deriving object IDs at compile time (see [`derived_id!`]).
+#[derive(Debug, Clone, Copy)]
+pub enum ConstTypeTag<'a> {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(&'a ConstTypeTag<'a>),
+    Struct {
+        /// The address of the type's package, as a `0x`-prefixed hex literal.
+        address: &'a str,
+        module: &'a str,
+        name: &'a str,
+        type_params: &'a [ConstTypeTag<'a>],
+    },
+}
+
+impl ConstTypeTag<'_> {
+    /// Feed the BCS serialization of the corresponding [`TypeTag`] into `hasher`.
+    const fn hash_bcs(&self, hasher: &mut Blake2b256) {
+        // Variant indices of `TypeTag`, in declaration order.
+        match self {
+            ConstTypeTag::Bool => hasher.update(&[0]),
+            ConstTypeTag::U8 => hasher.update(&[1]),
+            ConstTypeTag::U64 => hasher.update(&[2]),
+            ConstTypeTag::U128 => hasher.update(&[3]),
+            ConstTypeTag::Address => hasher.update(&[4]),
+            ConstTypeTag::Signer => hasher.update(&[5]),
+            ConstTypeTag::Vector(inner) => {
+                hasher.update(&[6]);
+                inner.hash_bcs(hasher);
+            }
+            ConstTypeTag::Struct {
+                address,
+                module,
+                name,
+                type_params,
+            } => {
+                hasher.update(&[7]);
+                hasher.update(&hex_literal_bytes_const(address));
+                hash_bcs_bytes(hasher, module.as_bytes());
+                hash_bcs_bytes(hasher, name.as_bytes());
+                hash_uleb128(hasher, type_params.len());
+                let mut i = 0;
+                while i < type_params.len() {
+                    type_params[i].hash_bcs(hasher);
+                    i += 1;
+                }
+            }
+            ConstTypeTag::U16 => hasher.update(&[8]),
+            ConstTypeTag::U32 => hasher.update(&[9]),
+            ConstTypeTag::U256 => hasher.update(&[10]),
+        }
+    }
+}
+
+/// Const fn variant of [`derive_object_id`], for a `parent` given as a `0x`-prefixed hex literal,
+/// and a key of type `key_type` whose BCS-serialized value is `key_bytes`. Prefer the
+/// [`derived_id!`] macro, which guarantees that the ID is computed at compile time.
+pub const fn derive_object_id_const(
+    parent: &str,
+    key_type: &ConstTypeTag<'_>,
+    key_bytes: &[u8],
+) -> ObjectID {
+    let key_type = ConstTypeTag::Struct {
+        address: "0x2",
+        // DERIVED_OBJECT_MODULE_NAME and DERIVED_OBJECT_KEY_STRUCT_NAME
+        module: "derived_object",
+        name: "DerivedObjectKey",
+        type_params: &[*key_type],
+    };
+
+    // Mirrors `derive_dynamic_field_id`: hash(parent || len(key) || key || key_type_tag)
+    let mut hasher = Blake2b256::new();
+    hasher.update(&[HashingIntentScope::ChildObjectId as u8]);
+    hasher.update(&hex_literal_bytes_const(parent));
+    hasher.update(&key_bytes.len().to_le_bytes());
+    hasher.update(key_bytes);
+    key_type.hash_bcs(&mut hasher);
+    let hash = hasher.finalize();
+
+    let mut id = [0u8; ObjectID::LENGTH];
+    let mut i = 0;
+    while i < ObjectID::LENGTH {
+        id[i] = hash[i];
+        i += 1;
+    }
+
+    ObjectID::new(id)
+}
+
+const fn hash_bcs_bytes(hasher: &mut Blake2b256, bytes: &[u8]) {
+    hash_uleb128(hasher, bytes.len());
+    hasher.update(bytes);
+}
+
+const fn hash_uleb128(hasher: &mut Blake2b256, mut value: usize) {
+    while value >= 0x80 {
+        hasher.update(&[(value as u8 & 0x7f) | 0x80]);
+        value >>= 7;
+    }
+    hasher.update(&[value as u8]);
+}
+
+/// Compute the ID of an object derived from a fixed `parent` (a `0x`-prefixed hex literal) and
+/// key at compile time, given the key's type as a [`ConstTypeTag`] and its BCS-serialized value.
+///
+/// ```
+/// use sui_types::derived_object::ConstTypeTag;
+///
+/// const ID: sui_types::base_types::ObjectID =
+///     sui_types::derived_id!("0xacc", ConstTypeTag::U64, &42u64.to_le_bytes());
+/// ```
+#[macro_export]
+macro_rules! derived_id {
+    ($parent:expr, $key_type:expr, $key_bytes:expr $(,)?) => {{
+        const ID: $crate::base_types::ObjectID =
+            $crate::derived_object::derive_object_id_const($parent, &$key_type, $key_bytes);
+        ID
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use move_core_types::language_storage::TypeTag;
+
+    use super::*;
+
+    #[test]
+    fn test_derived_id_matches_runtime_derivation() {
+        const KEY_TYPE: ConstTypeTag = ConstTypeTag::Struct {
+            address: "0x2",
+            module: "balance",
+            name: "Balance",
+            type_params: &[ConstTypeTag::Vector(&ConstTypeTag::U8)],
+        };
+
+        const KEY: u64 = 0xdead_beef;
+        let id = derived_id!("0xacc", KEY_TYPE, &KEY.to_le_bytes());
+
+        let key_type = TypeTag::Struct(Box::new(StructTag {
+            address: SUI_FRAMEWORK_ADDRESS,
+            module: ident_str!("balance").to_owned(),
+            name: ident_str!("Balance").to_owned(),
+            type_params: vec![TypeTag::Vector(Box::new(TypeTag::U8))],
+        }));
+
+        let expected = derive_object_id(
+            ObjectID::from_hex_literal("0xacc").unwrap(),
+            &key_type,
+            &bcs::to_bytes(&KEY).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(id, expected);
+    }
+
+    #[test]
+    fn test_derivation_scopes() {
+        let parent = ObjectID::from_hex_literal("0xacc").unwrap();
+        let key = bcs::to_bytes(&42u64).unwrap();
+
+        let global = derive_object_id(parent, &TypeTag::U64, &key).unwrap();
+        let scoped = |scope| derive_object_id_scoped(parent, &TypeTag::U64, &key, scope).unwrap();
+
+        assert_eq!(scoped(DerivationScope::Global), global);
+
+        // Each epoch derives its own ID, which never matches the globally scoped ID.
+        let epoch_0 = scoped(DerivationScope::Epoch(0));
+        let epoch_1 = scoped(DerivationScope::Epoch(1));
+        assert_ne!(epoch_0, global);
+        assert_ne!(epoch_1, global);
+        assert_ne!(epoch_0, epoch_1);
+        assert_eq!(scoped(DerivationScope::Epoch(1)), epoch_1);
+    }
+}
@@ -9,6 +9,7 @@ use crate::crypto::{
     default_hash, AuthoritySignInfo, AuthoritySignInfoTrait, AuthorityStrongQuorumSignInfo,
     EmptySignInfo,
 };
+use crate::derived_object::{derive_object_id_scoped, DerivationScope, DerivedObjectProvenance};
 use crate::digests::{
     ObjectDigest, TransactionDigest, TransactionEffectsDigest, TransactionEventsDigest,
 };
@@ -23,6 +24,7 @@ use crate::storage::WriteKind;
 pub use effects_v1::TransactionEffectsV1;
 pub use effects_v2::UnchangedConsensusKind;
 use enum_dispatch::enum_dispatch;
+use move_core_types::language_storage::TypeTag;
 pub use object_change::{
     AccumulatorAddress, AccumulatorOperation, AccumulatorValue, AccumulatorWriteV1,
     EffectsObjectChange, ObjectIn, ObjectOut,
@@ -347,6 +349,57 @@ pub trait TransactionEffectsAPI {
 
     fn object_changes(&self) -> Vec<ObjectChange>;
 
+    /// The objects created by this transaction whose IDs are derived from one of the objects it
+    /// modified or created, with one of `keys` (each a key's type and its BCS-serialized value),
+    /// either globally or within the epoch the transaction executed in. This recovers which
+    /// created objects are derived objects, and where they were derived from, without having to
+    /// re-derive the IDs of every candidate parent and key manually.
+    fn created_derived_objects(&self, keys: &[(TypeTag, Vec<u8>)]) -> Vec<DerivedObjectProvenance> {
+        let created = self.created();
+        let parents: BTreeSet<ObjectID> = self
+            .modified_at_versions()
+            .into_iter()
+            .map(|(id, _)| id)
+            .chain(created.iter().map(|((id, _, _), _)| *id))
+            .collect();
+
+        let mut candidates = BTreeMap::new();
+        let scopes = [
+            DerivationScope::Global,
+            DerivationScope::Epoch(self.executed_epoch()),
+        ];
+
+        for parent in &parents {
+            for (key_type, key_bytes) in keys {
+                for scope in scopes {
+                    let Ok(id) = derive_object_id_scoped(*parent, key_type, key_bytes, scope)
+                    else {
+                        continue;
+                    };
+
+                    candidates
+                        .entry(id)
+                        .or_insert((*parent, key_type, key_bytes, scope));
+                }
+            }
+        }
+
+        created
+            .into_iter()
+            .filter_map(|(object_ref, owner)| {
+                let (parent, key_type, key_bytes, scope) = candidates.get(&object_ref.0)?;
+                Some(DerivedObjectProvenance {
+                    object_ref,
+                    owner,
+                    parent: *parent,
+                    key_type: (*key_type).clone(),
+                    key_bytes: (*key_bytes).clone(),
+                    scope: *scope,
+                })
+            })
+            .collect()
+    }
+
     /// The set of object refs written by this transaction, including deleted and wrapped objects.
     /// Unlike object_changes(), returns no information about the starting state of the object.
     fn written(&self) -> Vec<ObjectRef>;
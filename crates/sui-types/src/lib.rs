@@ -42,6 +42,7 @@ pub mod config;
 pub mod crypto;
 pub mod deny_list_v1;
 pub mod deny_list_v2;
+pub mod derived_object;
 pub mod digests;
 pub mod display;
 pub mod dynamic_field;
@@ -37,6 +37,7 @@ pub use read_store::OwnedObjectInfo;
 pub use read_store::ReadStore;
 pub use read_store::RpcIndexes;
 pub use read_store::RpcStateReader;
+pub use read_store::SpendableBalance;
 pub use read_store::TransactionInfo;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
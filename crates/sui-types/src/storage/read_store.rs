@@ -576,6 +576,18 @@ pub trait RpcStateReader: ObjectStore + ReadStore + Send + Sync {
         }
     }
     fn get_struct_layout(&self, type_tag: &StructTag) -> Result<Option<MoveTypeLayout>>;
+
+    /// Get the address balance of `owner` for `coin_type`, taking into account deposits and
+    /// withdraw reservations which have been made but not yet settled.
+    ///
+    /// Returns `None` if the underlying store is unable to track withdraw reservations.
+    fn get_spendable_balance(
+        &self,
+        _owner: &SuiAddress,
+        _coin_type: &StructTag,
+    ) -> Result<Option<SpendableBalance>> {
+        Ok(None)
+    }
 }
 
 pub type DynamicFieldIteratorItem = Result<DynamicFieldKey, TypedStoreError>;
@@ -664,6 +676,36 @@ pub struct BalanceInfo {
     pub balance: u64,
 }
 
+/// The portion of an address balance that can be withdrawn right now. The settled balance,
+/// pending deposits and reservations are all relative to the same settled accumulator version.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SpendableBalance {
+    /// The balance of the account as of the last settled accumulator version.
+    pub settled: u64,
+    /// The amount deposited by transactions that have executed, but whose checkpoints'
+    /// settlements have not executed yet, that withdraws can already count on.
+    pub pending_deposits: u64,
+    /// The settled balance plus pending deposits.
+    pub total: u64,
+    /// The amount reserved by withdraws that have been scheduled but not yet settled.
+    pub reserved: u64,
+    /// The amount available to new withdraws, i.e. `total - reserved`.
+    pub spendable: u64,
+}
+
+impl SpendableBalance {
+    pub fn new(settled: u64, pending_deposits: u64, reserved: u64) -> Self {
+        let total = settled.saturating_add(pending_deposits);
+        Self {
+            settled,
+            pending_deposits,
+            total,
+            reserved,
+            spendable: total.saturating_sub(reserved),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
 pub struct TransactionInfo {
     pub checkpoint: u64,
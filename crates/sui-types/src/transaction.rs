@@ -2187,6 +2187,10 @@ pub trait TransactionDataAPI {
     /// each withdraw operation.
     fn process_balance_withdraws(&self) -> UserInputResult<BTreeMap<ObjectID, u64>>;
 
+    /// Checks that the transaction makes no more balance withdraw reservations than the protocol
+    /// allows.
+    fn check_balance_withdraw_limit(&self, config: &ProtocolConfig) -> UserInputResult;
+
     // A cheap way to quickly check if the transaction has balance withdraws.
     fn has_balance_withdraws(&self) -> bool;
 
@@ -2317,20 +2321,11 @@ impl TransactionDataAPI for TransactionDataV1 {
         let mut withdraws = Vec::new();
         // TODO(address-balances): Once we support paying gas using address balances,
         // we add gas reservations here.
-        // TODO(address-balances): Use a protocol config parameter for max_withdraws.
-        let max_withdraws = 10;
         // First get all withdraw arguments.
         if let TransactionKind::ProgrammableTransaction(pt) = &self.kind {
             for input in &pt.inputs {
                 if let CallArg::BalanceWithdraw(withdraw) = input {
                     withdraws.push(withdraw.clone());
-                    if withdraws.len() > max_withdraws {
-                        return Err(UserInputError::InvalidWithdrawReservation {
-                            error: format!(
-                                "Maximum number of balance withdraw reservations is {max_withdraws}"
-                            ),
-                        });
-                    }
                 }
             }
         }
@@ -2379,6 +2374,39 @@ impl TransactionDataAPI for TransactionDataV1 {
         Ok(withdraw_map)
     }
 
+    fn check_balance_withdraw_limit(&self, config: &ProtocolConfig) -> UserInputResult {
+        let TransactionKind::ProgrammableTransaction(pt) = &self.kind else {
+            return Ok(());
+        };
+
+        let withdraws = pt
+            .inputs
+            .iter()
+            .filter(|input| matches!(input, CallArg::BalanceWithdraw(_)))
+            .count();
+        if withdraws == 0 {
+            return Ok(());
+        }
+
+        // The limit must be set wherever accumulators are enabled, but a config that enables
+        // them without it must reject withdraws rather than panic.
+        let Some(max_withdraws) = config.max_balance_withdraws_per_tx_as_option() else {
+            return Err(UserInputError::Unsupported(
+                "Maximum number of balance withdraw reservations is not configured".to_string(),
+            ));
+        };
+
+        fp_ensure!(
+            withdraws as u64 <= max_withdraws,
+            UserInputError::InvalidWithdrawReservation {
+                error: format!(
+                    "Maximum number of balance withdraw reservations is {max_withdraws}"
+                ),
+            }
+        );
+        Ok(())
+    }
+
     fn has_balance_withdraws(&self) -> bool {
         if let TransactionKind::ProgrammableTransaction(pt) = &self.kind {
             for input in &pt.inputs {
@@ -2728,6 +2756,7 @@ impl SenderSignedData {
                     )
                 }
             );
+            tx_data.check_balance_withdraw_limit(context.config)?;
             tx_data.process_balance_withdraws()?;
         }
 
@@ -4,6 +4,7 @@
 use std::collections::BTreeMap;
 
 use crate::base_types::ObjectID;
+use crate::digests::TransactionDigest;
 use crate::effects::TransactionEffects;
 use crate::effects::TransactionEvents;
 use crate::error::ExecutionError;
@@ -30,6 +31,32 @@ pub trait TransactionExecutor: Send + Sync {
         transaction: TransactionData,
         checks: TransactionChecks,
     ) -> Result<SimulateTransactionResult, SuiError>;
+
+    /// Subscribe to updates on how transactions are scheduled for execution by this node, e.g.
+    /// while they wait for balance to be reserved for their withdraws. Returns `None` if the
+    /// executor does not execute transactions itself.
+    fn subscribe_schedule_updates(
+        &self,
+    ) -> Option<tokio::sync::broadcast::Receiver<TransactionScheduleUpdate>> {
+        None
+    }
+}
+
+/// A step in scheduling a transaction for execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionScheduleUpdate {
+    pub tx_digest: TransactionDigest,
+    pub status: TransactionScheduleStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionScheduleStatus {
+    /// The transaction is waiting for the balances it withdraws from to be reserved.
+    BalanceReservationPending,
+    /// The transaction's withdraws have been reserved, and it will be executed.
+    SufficientBalance,
+    /// The transaction's withdraws could not be reserved, so it will fail without executing.
+    InsufficientBalance,
 }
 
 pub struct SimulateTransactionResult {
@@ -3,9 +3,12 @@
 
 use std::collections::BTreeMap;
 
+use sui_protocol_config::ProtocolConfig;
+
 use crate::{
     accumulator_root::AccumulatorValue,
     base_types::{random_object_ref, SuiAddress},
+    error::UserInputError,
     gas_coin::GAS,
     programmable_transaction_builder::ProgrammableTransactionBuilder,
     transaction::{BalanceWithdrawArg, TransactionData, TransactionDataAPI, WithdrawTypeParam},
@@ -99,16 +102,51 @@ fn test_withdraw_zero_amount() {
 
 #[test]
 fn test_withdraw_too_many_withdraws() {
+    let mut config = ProtocolConfig::get_for_max_version_UNSAFE();
+    config.enable_accumulators_for_testing();
+    let max_withdraws = config.max_balance_withdraws_per_tx();
+
+    let tx_with_withdraws = |count| {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        for _ in 0..count {
+            ptb.balance_withdraw(BalanceWithdrawArg::new_with_amount(
+                100,
+                TypeInput::from(GAS::type_tag()),
+            ))
+            .unwrap();
+        }
+        let sender = SuiAddress::random_for_testing_only();
+        TransactionData::new_programmable(sender, vec![random_object_ref()], ptb.finish(), 1, 1)
+    };
+
+    assert!(tx_with_withdraws(max_withdraws)
+        .check_balance_withdraw_limit(&config)
+        .is_ok());
+    assert!(tx_with_withdraws(max_withdraws + 1)
+        .check_balance_withdraw_limit(&config)
+        .is_err());
+}
+
+#[test]
+fn test_withdraw_limit_not_configured() {
+    let mut config = ProtocolConfig::get_for_max_version_UNSAFE();
+    config.set_feature_flag_for_testing("enable_accumulators".to_string(), true);
+    assert_eq!(config.max_balance_withdraws_per_tx_as_option(), None);
+
     let mut ptb = ProgrammableTransactionBuilder::new();
-    for _ in 0..11 {
-        ptb.balance_withdraw(BalanceWithdrawArg::new_with_amount(
-            100,
-            TypeInput::from(GAS::type_tag()),
-        ))
-        .unwrap();
-    }
+    ptb.balance_withdraw(BalanceWithdrawArg::new_with_amount(
+        100,
+        TypeInput::from(GAS::type_tag()),
+    ))
+    .unwrap();
     let sender = SuiAddress::random_for_testing_only();
     let tx =
         TransactionData::new_programmable(sender, vec![random_object_ref()], ptb.finish(), 1, 1);
-    assert!(tx.process_balance_withdraws().is_err());
+
+    // Withdraws are rejected, rather than panicking, when accumulators are enabled without a
+    // limit.
+    assert!(matches!(
+        tx.check_balance_withdraw_limit(&config),
+        Err(UserInputError::Unsupported(_))
+    ));
 }
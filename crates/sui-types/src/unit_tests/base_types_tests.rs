@@ -92,6 +92,33 @@ fn test_max_sequence_number() {
     assert_eq!(max.0 * 2 + 1, u64::MAX);
 }
 
+#[test]
+fn test_sequence_number_checked_arithmetic() {
+    let zero = SequenceNumber::new();
+    assert_eq!(zero.checked_decrement(), None);
+    assert_eq!(zero.saturating_next(), SequenceNumber::from(1));
+
+    let one = SequenceNumber::from(1);
+    assert_eq!(one.checked_decrement(), Some(zero));
+    assert_eq!(one.one_before(), Some(zero));
+
+    let max = SequenceNumber::MAX;
+    assert_eq!(
+        max.checked_decrement(),
+        Some(SequenceNumber::from(max.0 - 1))
+    );
+    assert_eq!(max.saturating_next(), max);
+    assert_eq!(SequenceNumber::from(max.0 - 1).saturating_next(), max);
+
+    // Reserved sequence numbers saturate down to the highest valid one.
+    assert_eq!(SequenceNumber::CANCELLED_READ.saturating_next(), max);
+    assert_eq!(SequenceNumber::from(u64::MAX).saturating_next(), max);
+    assert_eq!(
+        SequenceNumber::from(u64::MAX).checked_decrement(),
+        Some(SequenceNumber::from(u64::MAX - 1)),
+    );
+}
+
 #[test]
 fn test_gas_coin_ser_deser_roundtrip() {
     let id = ObjectID::random();
@@ -169,6 +196,22 @@ fn test_object_id_from_hex_literal() {
     );
 }
 
+#[test]
+fn test_object_id_from_hex_literal_const() {
+    const ID: ObjectID = ObjectID::from_hex_literal_const("0x1");
+    assert_eq!(ID, ObjectID::from_hex_literal("0x1").unwrap());
+
+    let hex = format!("0x{}", SAMPLE_ADDRESS);
+    assert_eq!(
+        ObjectID::from_hex_literal_const(&hex),
+        ObjectID::from_hex_literal(&hex).unwrap()
+    );
+    assert_eq!(
+        ObjectID::from_hex_literal_const("0xAbC"),
+        ObjectID::from_hex_literal("0xabc").unwrap()
+    );
+}
+
 #[test]
 fn test_object_id_ref() {
     let obj_id = ObjectID::new([1u8; ObjectID::LENGTH]);
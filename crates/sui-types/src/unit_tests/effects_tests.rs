@@ -3,6 +3,7 @@
 
 use crate::base_types::{ObjectID, SequenceNumber, SuiAddress};
 use crate::crypto::{get_key_pair_from_rng, AccountKeyPair};
+use crate::derived_object::{derive_object_id, derive_object_id_scoped, DerivationScope};
 use crate::digests::ObjectDigest;
 use crate::effects::{TestEffectsBuilder, TransactionEffectsAPI};
 use crate::object::Owner;
@@ -10,6 +11,7 @@ use crate::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use crate::transaction::{Transaction, TransactionData};
 use crate::utils::to_sender_signed_transaction;
 use fastcrypto::ed25519::Ed25519KeyPair;
+use move_core_types::language_storage::TypeTag;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
@@ -432,3 +434,52 @@ fn test_written_with_wrapped_and_deleted_distinction() {
         .iter()
         .any(|(id, _, digest)| { *id == gas_object_id && digest.is_alive() }));
 }
+
+#[test]
+fn test_created_derived_objects() {
+    let mut rng = StdRng::from_seed([0; 32]);
+    let (sender, keypair): (SuiAddress, AccountKeyPair) =
+        get_key_pair_from_rng::<Ed25519KeyPair, _>(&mut rng);
+    let gas_object_id = ObjectID::random();
+    let tx = make_test_transaction(sender, &keypair, gas_object_id);
+
+    let key = bcs::to_bytes(&42u64).unwrap();
+    let other_key = bcs::to_bytes(&43u64).unwrap();
+
+    // Derived from an input, from an object created in the same transaction, and within the
+    // epoch, alongside an object that is not derived at all.
+    let from_input = derive_object_id(gas_object_id, &TypeTag::U64, &key).unwrap();
+    let from_created = derive_object_id(from_input, &TypeTag::U64, &other_key).unwrap();
+    let epoch_scoped = derive_object_id_scoped(
+        gas_object_id,
+        &TypeTag::U64,
+        &other_key,
+        DerivationScope::Epoch(0),
+    )
+    .unwrap();
+    let unrelated = ObjectID::random();
+
+    let effects = TestEffectsBuilder::new(tx.data())
+        .with_created_objects(vec![
+            (from_input, Owner::AddressOwner(sender)),
+            (from_created, Owner::AddressOwner(sender)),
+            (epoch_scoped, Owner::AddressOwner(sender)),
+            (unrelated, Owner::AddressOwner(sender)),
+        ])
+        .build();
+
+    let keys = vec![(TypeTag::U64, key), (TypeTag::U64, other_key)];
+    let derived: Vec<_> = effects
+        .created_derived_objects(&keys)
+        .into_iter()
+        .map(|d| (d.object_ref.0, d.parent, d.scope))
+        .collect();
+
+    assert_eq!(derived.len(), 3, "{derived:?}");
+    assert!(derived.contains(&(from_input, gas_object_id, DerivationScope::Global)));
+    assert!(derived.contains(&(from_created, from_input, DerivationScope::Global)));
+    assert!(derived.contains(&(epoch_scoped, gas_object_id, DerivationScope::Epoch(0))));
+
+    // Without the keys, nothing can be recognised as derived.
+    assert!(effects.created_derived_objects(&[]).is_empty());
+}
@@ -37,7 +37,9 @@ use move_package::{source_package::parsed_manifest::Dependencies, BuildConfig as
 use prometheus::Registry;
 use serde::Serialize;
 use serde_json::{json, Value};
+use sui_config::network_profiles::NetworkProfiles;
 use sui_config::verifier_signing_config::VerifierSigningConfig;
+use sui_config::{sui_config_dir, SUI_NETWORKS_CONFIG};
 use sui_move::manage_package::resolve_lock_file_path;
 use sui_protocol_config::{Chain, ProtocolConfig, ProtocolVersion};
 use sui_source_validation::{BytecodeSourceVerifier, ValidationMode};
@@ -1664,15 +1666,29 @@ impl SuiClientCommands {
                     let active_env = context.get_active_env();
 
                     if let Ok(env) = active_env {
-                        let network = match env.rpc.as_str() {
-                            SUI_DEVNET_URL => "https://faucet.devnet.sui.io/v2/gas",
-                            SUI_TESTNET_URL => {
-                                bail!("For testnet tokens, please use the Web UI: https://faucet.sui.io/?address={address}");
-                            }
-                            SUI_LOCAL_NETWORK_URL | SUI_LOCAL_NETWORK_URL_0 => "http://127.0.0.1:9123/v2/gas",
-                            _ => bail!("Cannot recognize the active network. Please provide the gas faucet full URL.")
-                        };
-                        network.to_string()
+                        // Prefer the faucet of the network profile this environment is named
+                        // after, as long as it points at the same network.
+                        let profiles = NetworkProfiles::load_or_builtin(
+                            &sui_config_dir()?.join(SUI_NETWORKS_CONFIG),
+                        )?;
+                        let profile_faucet = profiles
+                            .get(&env.alias)
+                            .filter(|profile| profile.rpc == env.rpc)
+                            .and_then(|profile| profile.faucet.clone());
+
+                        if let Some(faucet) = profile_faucet {
+                            faucet
+                        } else {
+                            let network = match env.rpc.as_str() {
+                                SUI_DEVNET_URL => "https://faucet.devnet.sui.io/v2/gas",
+                                SUI_TESTNET_URL => {
+                                    bail!("For testnet tokens, please use the Web UI: https://faucet.sui.io/?address={address}");
+                                }
+                                SUI_LOCAL_NETWORK_URL | SUI_LOCAL_NETWORK_URL_0 => "http://127.0.0.1:9123/v2/gas",
+                                _ => bail!("Cannot recognize the active network. Please provide the gas faucet full URL.")
+                            };
+                            network.to_string()
+                        }
                     } else {
                         bail!("No URL for faucet was provided and there is no active network.")
                     }
@@ -35,7 +35,10 @@ use std::fmt::{Debug, Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use sui_config::node::KeyEncryptionConfig;
+use sui_config::{Config, NodeConfig};
 use sui_keys::key_derive::generate_new_key;
+use sui_keys::key_encryption::encrypt_key_file;
 use sui_keys::key_identity::KeyIdentity;
 use sui_keys::keypair_file::{
     read_authority_keypair_from_file, read_keypair_from_file, write_authority_keypair_to_file,
@@ -60,6 +63,7 @@ use tabled::builder::Builder;
 use tabled::settings::Rotate;
 use tabled::settings::{object::Rows, Modify, Width};
 use tracing::info;
+use zeroize::Zeroizing;
 #[cfg(test)]
 #[path = "unit_tests/keytool_tests.rs"]
 mod keytool_tests;
@@ -102,6 +106,18 @@ pub enum KeyToolCommand {
         #[clap(long, default_value = "0")]
         cur_epoch: u64,
     },
+    /// Encrypt the key files referenced by a node config at rest, and add a `key-encryption`
+    /// section to the config telling the node how to get the passphrase that unlocks them when
+    /// it starts. The passphrase is either read from the environment variable named by
+    /// `--passphrase-env-var`, or generated randomly and encrypted with the AWS KMS key
+    /// `--kms-key-id`. Keys stored in place in the config are left as they are.
+    EncryptConfigKeys {
+        config_path: PathBuf,
+        #[clap(long, conflicts_with = "kms_key_id")]
+        passphrase_env_var: Option<String>,
+        #[clap(long)]
+        kms_key_id: Option<String>,
+    },
     /// Generate a new keypair with key scheme flag {ed25519 | secp256k1 | secp256r1}
     /// with optional derivation path, default to m/44'/784'/0'/0'/0' for ed25519 or
     /// m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Word
@@ -342,6 +358,13 @@ pub struct Key {
     peer_id: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedConfigKeys {
+    config_path: PathBuf,
+    encrypted_key_files: Vec<PathBuf>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportedKey {
@@ -461,6 +484,7 @@ pub enum CommandOutput {
     Convert(ConvertOutput),
     DecodeMultiSig(DecodedMultiSigOutput),
     DecodeOrVerifyTx(DecodeOrVerifyTxOutput),
+    EncryptConfigKeys(EncryptedConfigKeys),
     Error(String),
     Generate(Key),
     Import(Key),
@@ -700,6 +724,91 @@ impl KeyToolCommand {
                 CommandOutput::List(keys)
             }
 
+            KeyToolCommand::EncryptConfigKeys {
+                config_path,
+                passphrase_env_var,
+                kms_key_id,
+            } => {
+                let config = NodeConfig::load(&config_path)?;
+                if config.key_encryption.is_some() {
+                    return Err(anyhow!(
+                        "Config at {:?} already has key encryption configured",
+                        config_path
+                    ));
+                }
+
+                let (key_encryption, passphrase) = match (passphrase_env_var, kms_key_id) {
+                    (Some(passphrase_env_var), None) => {
+                        let passphrase = std::env::var(&passphrase_env_var).map_err(|_| {
+                            anyhow!("Passphrase not found in ${passphrase_env_var}")
+                        })?;
+                        (
+                            KeyEncryptionConfig::Env { passphrase_env_var },
+                            Zeroizing::new(passphrase.into_bytes()),
+                        )
+                    }
+                    (None, Some(key_id)) => {
+                        let passphrase = Zeroizing::new(rand::thread_rng().gen::<[u8; 32]>());
+                        let sdk_config = aws_config::load_from_env().await;
+                        let response = KmsClient::new(&sdk_config)
+                            .encrypt()
+                            .key_id(&key_id)
+                            .plaintext(Blob::new(passphrase.to_vec()))
+                            .send()
+                            .await?;
+                        let ciphertext = response
+                            .ciphertext_blob
+                            .ok_or_else(|| anyhow!("KMS returned no encrypted passphrase"))?;
+                        (
+                            KeyEncryptionConfig::Kms {
+                                key_id,
+                                encrypted_passphrase: Base64::encode(ciphertext.into_inner()),
+                            },
+                            Zeroizing::new(passphrase.to_vec()),
+                        )
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "Exactly one of --passphrase-env-var or --kms-key-id is required"
+                        ));
+                    }
+                };
+
+                // Record how to unlock the keys before encrypting any of them: a node skips
+                // unlocking key files that are not encrypted, but cannot load encrypted ones
+                // without a passphrase.
+                #[derive(Serialize)]
+                #[serde(rename_all = "kebab-case")]
+                struct KeyEncryptionSection {
+                    key_encryption: KeyEncryptionConfig,
+                }
+                let section = serde_yaml::to_string(&KeyEncryptionSection { key_encryption })?;
+                let mut contents = fs::read_to_string(&config_path)?;
+                if !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+                contents.push_str(section.strip_prefix("---\n").unwrap_or(&section));
+                fs::write(&config_path, contents)?;
+
+                let key_files = [
+                    config.protocol_key_pair.path(),
+                    config.worker_key_pair.path(),
+                    config.account_key_pair.path(),
+                    config.network_key_pair.path(),
+                ];
+                let mut encrypted_key_files = vec![];
+                for path in key_files.into_iter().flatten() {
+                    if encrypt_key_file(path, &passphrase)? {
+                        encrypted_key_files.push(path.to_path_buf());
+                    }
+                }
+
+                CommandOutput::EncryptConfigKeys(EncryptedConfigKeys {
+                    config_path,
+                    encrypted_key_files,
+                })
+            }
+
             KeyToolCommand::LoadKeypair { file } => {
                 let output = match read_keypair_from_file(&file) {
                     Ok(keypair) => {
@@ -30,14 +30,17 @@ use sui_bridge::config::BridgeCommitteeConfig;
 use sui_bridge::metrics::BridgeMetrics;
 use sui_bridge::sui_client::SuiBridgeClient;
 use sui_bridge::sui_transaction_builder::build_committee_register_transaction;
+use sui_config::network_profiles::NetworkProfiles;
 use sui_config::node::Genesis;
 use sui_config::p2p::SeedPeer;
+use sui_config::scrape_config::{PrometheusConfig, ScrapeTarget};
 use sui_config::{
     genesis_blob_exists, sui_config_dir, Config, PersistedConfig, FULL_NODE_DB_PATH,
-    SUI_CLIENT_CONFIG, SUI_FULLNODE_CONFIG, SUI_NETWORK_CONFIG,
+    SUI_CLIENT_CONFIG, SUI_FULLNODE_CONFIG, SUI_NETWORKS_CONFIG, SUI_NETWORK_CONFIG,
 };
 use sui_config::{
     SUI_BENCHMARK_GENESIS_GAS_KEYSTORE_FILENAME, SUI_GENESIS_FILENAME, SUI_KEYSTORE_FILENAME,
+    SUI_PROMETHEUS_CONFIG,
 };
 use sui_faucet::{create_wallet_context, start_faucet, AppState, FaucetConfig, LocalFaucet};
 use sui_indexer::test_utils::{
@@ -72,7 +75,8 @@ use sui_swarm::memory::Swarm;
 use sui_swarm_config::genesis_config::GenesisConfig;
 use sui_swarm_config::network_config::NetworkConfig;
 use sui_swarm_config::network_config_builder::ConfigBuilder;
-use sui_swarm_config::node_config_builder::FullnodeConfigBuilder;
+use sui_swarm_config::node_config_builder::{FullnodeConfigBuilder, NodeConfigBuilder, Profile};
+use sui_swarm_config::topology::{ServiceTopology, SwarmTopology};
 use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::crypto::{SignatureScheme, SuiKeyPair, ToFromBytes};
 use tracing;
@@ -161,6 +165,45 @@ pub struct SuiEnvConfig {
     /// The Sui environment to use. This must be present in the current config file.
     #[clap(long = "client.env")]
     env: Option<String>,
+    /// The network to use, by the name of its profile in networks.yaml in the Sui config
+    /// directory, or one of the built-in profiles: localnet, devnet, testnet or mainnet. The
+    /// network is added to the client config as an environment with the same name.
+    #[clap(long = "network", conflicts_with = "env")]
+    network: Option<String>,
+}
+
+impl SuiEnvConfig {
+    /// Whether an environment other than the config's active environment was selected.
+    fn has_env_override(&self) -> bool {
+        self.env.is_some() || self.network.is_some()
+    }
+
+    /// Point `context` at the environment selected by `--client.env` or `--network`, if any.
+    fn apply_env_override(&self, context: WalletContext) -> Result<WalletContext, anyhow::Error> {
+        if let Some(env) = &self.env {
+            return Ok(context.with_env_override(env.clone()));
+        }
+
+        let Some(network) = &self.network else {
+            return Ok(context);
+        };
+
+        let profiles =
+            NetworkProfiles::load_or_builtin(&sui_config_dir()?.join(SUI_NETWORKS_CONFIG))?;
+        let Some(profile) = profiles.get(network) else {
+            bail!(
+                "Unknown network [{network}]. Known networks: {}",
+                profiles
+                    .networks
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        };
+
+        Ok(context.with_network_profile(network, profile))
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -246,6 +289,37 @@ pub enum SuiCommand {
         /// genesis with the desired number of validators.
         #[clap(long)]
         committee_size: Option<usize>,
+
+        /// Bind the nodes of the local network to fixed ports, starting at this port, instead of
+        /// picking available ports at random, so that they can be found at the same addresses
+        /// across restarts. Each node is given a block of 10 ports: validators first, followed by
+        /// the fullnode. This does not change the ports of the fullnode RPC server, faucet,
+        /// indexer or GraphQL services, which are set by their own flags.
+        ///
+        /// Validators in an existing network configuration keep the ports they were configured
+        /// with, which are persisted along with it.
+        #[clap(long, value_name = "BASE_PORT")]
+        deterministic_ports: Option<u16>,
+
+        /// Start the network described by a topology file (e.g. `swarm.yaml`), listing its
+        /// validators and their stakes, its fullnodes, and the indexer, GraphQL and faucet
+        /// services to run alongside it. Addresses that the file does not give are allocated on
+        /// localhost. Requires `--force-regenesis`, and replaces the flags that configure the
+        /// network's nodes and services.
+        #[clap(
+            long,
+            value_name = "TOPOLOGY_PATH",
+            requires = "force_regenesis",
+            conflicts_with_all = [
+                "with_faucet",
+                "with_indexer",
+                "with_graphql",
+                "no_full_node",
+                "committee_size",
+                "deterministic_ports",
+            ],
+        )]
+        topology: Option<PathBuf>,
     },
     #[clap(name = "network")]
     Network {
@@ -288,6 +362,23 @@ pub enum SuiCommand {
         committee_size: Option<usize>,
     },
     GenesisCeremony(Ceremony),
+    /// Generate the config for a single node joining the network in the Sui config directory,
+    /// using the defaults (pruning, ports, metrics) of the given profile.
+    #[clap(name = "genesis-config")]
+    GenesisNodeConfig {
+        /// Role of the node: `validator`, `fullnode` or `indexer`.
+        #[clap(long)]
+        profile: Profile,
+        #[clap(long = "network.config")]
+        network_config: Option<PathBuf>,
+        /// Directory the node keeps its databases in. Defaults to the Sui config directory.
+        #[clap(long)]
+        working_dir: Option<PathBuf>,
+        /// Path to write the node config to. Defaults to `<profile>.yaml` in the Sui config
+        /// directory.
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
     /// Sui keystore tool.
     #[clap(name = "keytool")]
     KeyTool {
@@ -419,6 +510,8 @@ impl SuiCommand {
                 no_full_node,
                 epoch_duration_ms,
                 committee_size,
+                deterministic_ports,
+                topology,
             } => {
                 start(
                     config_dir.clone(),
@@ -430,6 +523,8 @@ impl SuiCommand {
                     data_ingestion_dir,
                     no_full_node,
                     committee_size,
+                    deterministic_ports,
+                    topology,
                 )
                 .await?;
 
@@ -454,10 +549,35 @@ impl SuiCommand {
                     benchmark_ips,
                     with_faucet,
                     committee_size,
+                    None,
                 )
                 .await
             }
             SuiCommand::GenesisCeremony(cmd) => run(cmd),
+            SuiCommand::GenesisNodeConfig {
+                profile,
+                network_config,
+                working_dir,
+                output,
+            } => {
+                let sui_config_dir = sui_config_dir()?;
+                let network_config_path =
+                    network_config.unwrap_or(sui_config_dir.join(SUI_NETWORK_CONFIG));
+                let network_config: NetworkConfig = PersistedConfig::read(&network_config_path)
+                    .map_err(|err| {
+                        err.context(format!(
+                            "Cannot open Sui network config file at {:?}",
+                            network_config_path
+                        ))
+                    })?;
+                let working_dir = working_dir.unwrap_or(sui_config_dir.clone());
+                let node_config = NodeConfigBuilder::from_profile(profile, working_dir)
+                    .build(&mut OsRng, &network_config);
+                let output = output.unwrap_or(sui_config_dir.join(format!("{profile}.yaml")));
+                node_config.save(&output)?;
+                info!("{profile} node config is stored in {:?}.", output);
+                Ok(())
+            }
             SuiCommand::KeyTool {
                 keystore_path,
                 json,
@@ -481,10 +601,8 @@ impl SuiCommand {
                     .unwrap_or(sui_config_dir()?.join(SUI_CLIENT_CONFIG));
                 prompt_if_no_config(&config_path, accept_defaults).await?;
                 if let Some(cmd) = cmd {
-                    let mut context = WalletContext::new(&config_path)?;
-                    if let Some(env_override) = config.env {
-                        context = context.with_env_override(env_override);
-                    }
+                    let context = WalletContext::new(&config_path)?;
+                    let mut context = config.apply_env_override(context)?;
                     if let Ok(client) = context.get_client().await {
                         if let Err(e) = client.check_api_version() {
                             eprintln!("{}", format!("[warning] {e}").yellow().bold());
@@ -665,7 +783,7 @@ impl SuiCommand {
 
                 // If a specific environment is specified for the build command we set the chain ID
                 // to the one that is specified.
-                if client_config.env.is_some() && matches!(cmd, sui_move::Command::Build(_)) {
+                if client_config.has_env_override() && matches!(cmd, sui_move::Command::Build(_)) {
                     let (chain_id, _) =
                         get_chain_id_and_client(client_config, "sui move build").await?;
 
@@ -781,14 +899,16 @@ impl SuiCommand {
 /// Starts a local network with the given configuration.
 async fn start(
     config: Option<PathBuf>,
-    with_faucet: Option<String>,
+    mut with_faucet: Option<String>,
     indexer_feature_args: IndexerArgs,
     force_regenesis: bool,
     epoch_duration_ms: Option<u64>,
     fullnode_rpc_port: u16,
     mut data_ingestion_dir: Option<PathBuf>,
-    no_full_node: bool,
+    mut no_full_node: bool,
     committee_size: Option<usize>,
+    deterministic_ports: Option<u16>,
+    topology: Option<PathBuf>,
 ) -> Result<(), anyhow::Error> {
     if force_regenesis {
         ensure!(
@@ -799,7 +919,7 @@ async fn start(
 
     let IndexerArgs {
         mut with_indexer,
-        with_graphql,
+        mut with_graphql,
         pg_port,
         pg_host,
         pg_db_name,
@@ -809,6 +929,27 @@ async fn start(
 
     let pg_address = format!("postgres://{pg_user}:{pg_password}@{pg_host}:{pg_port}/{pg_db_name}");
 
+    let topology = match topology {
+        Some(path) => {
+            let mut topology = SwarmTopology::load(&path).map_err(|err| {
+                err.context(format!("Cannot open topology file at {}", path.display()))
+            })?;
+            topology.validate()?;
+            topology.allocate_addresses();
+
+            // The topology replaces the flags that configure the network's nodes and services.
+            let address = |s: &Option<ServiceTopology>| {
+                s.as_ref().and_then(|s| s.address).map(|a| a.to_string())
+            };
+            with_faucet = address(&topology.faucet);
+            with_indexer = address(&topology.indexer);
+            with_graphql = address(&topology.graphql);
+            no_full_node = topology.fullnodes == 0;
+            Some(topology)
+        }
+        None => None,
+    };
+
     if with_graphql.is_some() {
         with_indexer = Some(with_indexer.unwrap_or_default());
     }
@@ -829,16 +970,23 @@ async fn start(
     }
 
     let mut swarm_builder = Swarm::builder();
+    if let Some(port_offset) = deterministic_ports {
+        swarm_builder = swarm_builder.with_deterministic_ports(port_offset);
+    }
 
     // If this is set, then no data will be persisted between runs, and a new genesis will be
     // generated each run.
     let config_dir = if force_regenesis {
-        let committee_size = match committee_size {
-            Some(x) => NonZeroUsize::new(x),
-            None => NonZeroUsize::new(1),
+        if let Some(topology) = &topology {
+            swarm_builder = swarm_builder.with_topology(topology);
+        } else {
+            let committee_size = match committee_size {
+                Some(x) => NonZeroUsize::new(x),
+                None => NonZeroUsize::new(1),
+            }
+            .ok_or_else(|| anyhow!("Committee size must be at least 1."))?;
+            swarm_builder = swarm_builder.committee_size(committee_size);
         }
-        .ok_or_else(|| anyhow!("Committee size must be at least 1."))?;
-        swarm_builder = swarm_builder.committee_size(committee_size);
         let genesis_config = GenesisConfig::custom_genesis(1, 100);
         swarm_builder = swarm_builder.with_genesis_config(genesis_config);
         let epoch_duration_ms = epoch_duration_ms.unwrap_or(DEFAULT_EPOCH_DURATION_MS);
@@ -900,6 +1048,7 @@ async fn start(
                         None,
                         false,
                         committee_size,
+                        deterministic_ports,
                     )
                     .await
                     .map_err(|_| {
@@ -958,16 +1107,25 @@ async fn start(
         swarm_builder = swarm_builder.with_data_ingestion_dir(dir.clone());
     }
 
-    let mut fullnode_url = sui_config::node::default_json_rpc_address();
-    fullnode_url.set_port(fullnode_rpc_port);
-
-    if no_full_node {
-        swarm_builder = swarm_builder.with_fullnode_count(0);
+    let fullnode_url = if let Some(topology) = &topology {
+        // The topology has already configured the fullnodes.
+        topology
+            .fullnode_rpc_address
+            .unwrap_or_else(sui_config::node::default_json_rpc_address)
     } else {
-        swarm_builder = swarm_builder
-            .with_fullnode_count(1)
-            .with_fullnode_rpc_addr(fullnode_url);
-    }
+        let mut fullnode_url = sui_config::node::default_json_rpc_address();
+        fullnode_url.set_port(fullnode_rpc_port);
+
+        if no_full_node {
+            swarm_builder = swarm_builder.with_fullnode_count(0);
+        } else {
+            swarm_builder = swarm_builder
+                .with_fullnode_count(1)
+                .with_fullnode_rpc_addr(fullnode_url);
+        }
+
+        fullnode_url
+    };
 
     let mut swarm = swarm_builder.build();
     swarm.launch().await?;
@@ -1116,6 +1274,7 @@ async fn genesis(
     benchmark_ips: Option<Vec<String>>,
     with_faucet: bool,
     committee_size: Option<usize>,
+    deterministic_ports: Option<u16>,
 ) -> Result<(), anyhow::Error> {
     let sui_config_dir = &match working_dir {
         // if a directory is specified, it must exist (it
@@ -1218,7 +1377,10 @@ async fn genesis(
     let validator_info = genesis_conf.validator_config_info.take();
     let ssfn_info = genesis_conf.ssfn_config_info.take();
 
-    let builder = ConfigBuilder::new(sui_config_dir);
+    let mut builder = ConfigBuilder::new(sui_config_dir);
+    if let Some(port_offset) = deterministic_ports {
+        builder = builder.with_deterministic_ports(port_offset);
+    }
     if let Some(epoch_duration_ms) = epoch_duration_ms {
         genesis_conf.parameters.epoch_duration_ms = epoch_duration_ms;
     }
@@ -1265,6 +1427,17 @@ async fn genesis(
         .build(&mut OsRng, &network_config);
 
     fullnode_config.save(sui_config_dir.join(SUI_FULLNODE_CONFIG))?;
+
+    let scrape_targets = ScrapeTarget::from_node_configs(
+        network_config
+            .validator_configs()
+            .iter()
+            .chain([&fullnode_config]),
+    );
+    let prometheus_path = sui_config_dir.join(SUI_PROMETHEUS_CONFIG);
+    PrometheusConfig::new(&scrape_targets).save(&prometheus_path)?;
+    info!("Prometheus config file is stored in {:?}.", prometheus_path);
+
     let mut ssfn_nodes = vec![];
     if let Some(ssfn_info) = ssfn_info {
         for (i, ssfn) in ssfn_info.into_iter().enumerate() {
@@ -1490,13 +1663,11 @@ async fn get_chain_id_and_client(
 ) -> anyhow::Result<(Option<String>, Option<SuiClient>)> {
     let config = client_config
         .config
+        .clone()
         .unwrap_or(sui_config_dir()?.join(SUI_CLIENT_CONFIG));
     prompt_if_no_config(&config, false).await?;
-    let mut context = WalletContext::new(&config)?;
-
-    if let Some(env_override) = client_config.env {
-        context = context.with_env_override(env_override);
-    }
+    let context = WalletContext::new(&config)?;
+    let context = client_config.apply_env_override(context)?;
 
     let Ok(client) = context.get_client().await else {
         bail!(
@@ -41,6 +41,7 @@ use sui::{
 use sui_config::{
     PersistedConfig, SUI_CLIENT_CONFIG, SUI_FULLNODE_CONFIG, SUI_GENESIS_FILENAME,
     SUI_KEYSTORE_ALIASES_FILENAME, SUI_KEYSTORE_FILENAME, SUI_NETWORK_CONFIG,
+    SUI_PROMETHEUS_CONFIG,
 };
 use sui_json::SuiJsonValue;
 use sui_json_rpc_types::{
@@ -344,6 +345,7 @@ async fn test_genesis() -> Result<(), anyhow::Error> {
         epoch_duration_ms: None,
         no_full_node: false,
         committee_size: None,
+        deterministic_ports: None,
         indexer_feature_args: IndexerArgs::for_testing(),
     }
     .execute()
@@ -368,13 +370,14 @@ async fn test_genesis() -> Result<(), anyhow::Error> {
         .flat_map(|r| r.map(|file| file.file_name().to_str().unwrap().to_owned()))
         .collect::<Vec<_>>();
 
-    assert_eq!(7, files.len());
+    assert_eq!(8, files.len());
     assert!(files.contains(&SUI_CLIENT_CONFIG.to_string()));
     assert!(files.contains(&SUI_NETWORK_CONFIG.to_string()));
     assert!(files.contains(&SUI_FULLNODE_CONFIG.to_string()));
     assert!(files.contains(&SUI_GENESIS_FILENAME.to_string()));
     assert!(files.contains(&SUI_KEYSTORE_FILENAME.to_string()));
     assert!(files.contains(&SUI_KEYSTORE_ALIASES_FILENAME.to_string()));
+    assert!(files.contains(&SUI_PROMETHEUS_CONFIG.to_string()));
 
     // Check network config
     let network_conf =
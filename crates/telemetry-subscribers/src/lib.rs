@@ -32,6 +32,7 @@ use tracing_subscriber::{filter, fmt, layer::SubscriberExt, reload, EnvFilter, L
 use crate::file_exporter::{CachedOpenFile, FileExporter};
 
 mod file_exporter;
+pub mod log_capture;
 pub mod span_latency_prom;
 
 /// Alias for a type-erased error type.
@@ -533,19 +534,23 @@ impl ShouldSample for SamplingFilter {
     }
 }
 
-/// Globally set a tracing subscriber suitable for testing environments
+/// Globally set a tracing subscriber suitable for testing environments. Events are also forwarded
+/// to any live [log_capture::LogCapture]s.
 pub fn init_for_testing() {
     static LOGGER: Lazy<()> = Lazy::new(|| {
-        let subscriber = ::tracing_subscriber::FmtSubscriber::builder()
-            .with_env_filter(
-                EnvFilter::builder()
-                    .with_default_directive(LevelFilter::INFO.into())
-                    .from_env_lossy(),
-            )
+        let env_filter = EnvFilter::builder()
+            .with_default_directive(LevelFilter::INFO.into())
+            .from_env_lossy();
+
+        let fmt_layer = fmt::layer()
             .with_file(true)
             .with_line_number(true)
             .with_test_writer()
-            .finish();
+            .with_filter(env_filter);
+
+        let subscriber = tracing_subscriber::registry()
+            .with(fmt_layer)
+            .with(log_capture::layer());
         ::tracing::subscriber::set_global_default(subscriber)
             .expect("unable to initialize logging for tests");
     });
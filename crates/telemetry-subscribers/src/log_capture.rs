@@ -0,0 +1,276 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Programmatic capture of tracing events, so that tests can assert on what was logged without
+//! scraping their output.
+//!
+//! The subscriber installed by [`crate::init_for_testing`] forwards events to every live
+//! [`LogCapture`] whose filter enables them, independently of the `RUST_LOG` filter used for
+//! printing logs. Captures see events from every thread and task in the process, including those
+//! of other tests running concurrently.
+
+use std::{
+    fmt::{self, Write as _},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+use tracing::{
+    callsite,
+    field::{Field, Visit},
+    metadata::LevelFilter,
+    subscriber::Interest,
+    Event, Level, Metadata, Subscriber,
+};
+use tracing_subscriber::{
+    filter::Targets,
+    layer::{Context, Filter},
+    Layer,
+};
+
+use crate::BoxError;
+
+/// Every capture that is currently live.
+static CAPTURES: Lazy<Mutex<Vec<Arc<Capture>>>> = Lazy::new(Default::default);
+
+/// A buffer of the tracing events that match a filter, from the moment it was created until it
+/// is dropped.
+pub struct LogCapture(Arc<Capture>);
+
+/// A tracing event, as recorded by a [`LogCapture`].
+#[derive(Clone, Debug)]
+pub struct CapturedEvent {
+    pub level: Level,
+    pub target: String,
+    /// The event's message, followed by its other fields, as `name=value` pairs.
+    pub message: String,
+}
+
+struct Capture {
+    filter: Targets,
+    events: Mutex<Vec<CapturedEvent>>,
+}
+
+/// Layer that forwards events to live captures.
+struct CaptureLayer;
+
+/// Per-layer filter for [`CaptureLayer`], which only enables events that some live capture is
+/// interested in, so that capturing costs nothing while there are no captures.
+struct CaptureFilter;
+
+/// Collects an event's message and its other fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl LogCapture {
+    /// Start capturing events that match `filter`, which uses the same syntax as `RUST_LOG`
+    /// target directives, e.g. `sui_core::execution_scheduler=debug,warn`.
+    pub fn new(filter: &str) -> Result<Self, BoxError> {
+        let capture = Arc::new(Capture {
+            filter: Targets::from_str(filter)?,
+            events: Mutex::new(vec![]),
+        });
+
+        CAPTURES.lock().unwrap().push(capture.clone());
+
+        // Callsites that were disabled before this capture existed need to be re-evaluated.
+        callsite::rebuild_interest_cache();
+        Ok(Self(capture))
+    }
+
+    /// The events captured so far, in the order they were emitted.
+    pub fn events(&self) -> Vec<CapturedEvent> {
+        self.0.events.lock().unwrap().clone()
+    }
+
+    /// Whether any event captured so far has a message containing `pattern`.
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.0
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| event.message.contains(pattern))
+    }
+
+    /// Forget the events captured so far.
+    pub fn clear(&self) {
+        self.0.events.lock().unwrap().clear();
+    }
+
+    /// The events captured so far, one per line, for inclusion in failure messages.
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        for event in self.0.events.lock().unwrap().iter() {
+            writeln!(dump, "{event}").unwrap();
+        }
+        dump
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        CAPTURES
+            .lock()
+            .unwrap()
+            .retain(|capture| !Arc::ptr_eq(capture, &self.0));
+        callsite::rebuild_interest_cache();
+    }
+}
+
+impl fmt::Display for CapturedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+impl Capture {
+    fn enables(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter
+            .would_enable(metadata.target(), metadata.level())
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let captures = CAPTURES.lock().unwrap();
+        let mut interested = captures
+            .iter()
+            .filter(|capture| capture.enables(metadata))
+            .peekable();
+
+        if interested.peek().is_none() {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let captured = CapturedEvent {
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            message: visitor.finish(),
+        };
+
+        for capture in interested {
+            capture.events.lock().unwrap().push(captured.clone());
+        }
+    }
+}
+
+impl CaptureFilter {
+    fn enables(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_event()
+            && CAPTURES
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|capture| capture.enables(metadata))
+    }
+}
+
+impl<S> Filter<S> for CaptureFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: &Context<'_, S>) -> bool {
+        self.enables(metadata)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        // Callsites are re-evaluated whenever a capture is created or dropped, so callsites that
+        // no live capture is interested in can be disabled outright.
+        if self.enables(metadata) {
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        let captures = CAPTURES.lock().unwrap();
+        let max = captures
+            .iter()
+            .flat_map(|capture| {
+                let targets = capture.filter.iter().map(|(_, level)| level);
+                targets.chain(capture.filter.default_level())
+            })
+            .max();
+
+        Some(max.unwrap_or(LevelFilter::OFF))
+    }
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        if self.fields.is_empty() {
+            self.message
+        } else if self.message.is_empty() {
+            self.fields
+        } else {
+            format!("{} {}", self.message, self.fields)
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.message, "{value:?}").unwrap();
+            return;
+        }
+
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        write!(self.fields, "{}={value:?}", field.name()).unwrap();
+    }
+}
+
+/// The layer that forwards events to live captures, to be installed in the global subscriber.
+pub(crate) fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    CaptureLayer.with_filter(CaptureFilter)
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::{debug, info, warn};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn test_capture_matching_events() {
+        let subscriber = tracing_subscriber::registry().with(layer());
+        let capture = LogCapture::new("log_capture_test=info").unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(target: "log_capture_test", count = 1, "first event");
+            debug!(target: "log_capture_test", "too verbose");
+            warn!(target: "log_capture_other", "wrong target");
+            warn!(target: "log_capture_test", "second event");
+        });
+
+        let events = capture.events();
+        assert_eq!(events.len(), 2, "{}", capture.dump());
+        assert_eq!(events[0].level, Level::INFO);
+        assert_eq!(events[0].message, "first event count=1");
+        assert_eq!(events[1].level, Level::WARN);
+        assert_eq!(events[1].message, "second event");
+
+        assert!(capture.contains("second"));
+        assert!(!capture.contains("too verbose"));
+
+        capture.clear();
+        assert!(capture.events().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filter() {
+        assert!(LogCapture::new("log_capture_test=loud").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use sui_types::base_types::{ObjectRef, SuiAddress};
+
+/// The number of gas coins that each named account is funded with. This is how many transactions
+/// the account can pay for concurrently, without them contending over the same gas coin.
+pub const ACCOUNT_GAS_COINS: usize = 5;
+
+/// The balance of each gas coin that named accounts are funded with, in MIST.
+pub const ACCOUNT_GAS_COIN_BALANCE: u64 = 10_000_000_000;
+
+/// An account that was added to a [`crate::TestCluster`]'s wallet under a name, by
+/// [`crate::TestCluster::account`], to make tests with several parties (e.g. a sender, a sponsor,
+/// and a recipient) easier to follow.
+///
+/// The account keeps track of its own gas coins, handing out a different coin to each
+/// transaction in flight. Clones share the same gas coins.
+#[derive(Clone, Debug)]
+pub struct TestAccount {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    name: String,
+    address: SuiAddress,
+
+    /// Gas coins that are not being used by a transaction in flight, at their latest known
+    /// versions.
+    gas: Mutex<Vec<ObjectRef>>,
+}
+
+impl TestAccount {
+    pub(crate) fn new(name: String, address: SuiAddress, gas: Vec<ObjectRef>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                name,
+                address,
+                gas: Mutex::new(gas),
+            }),
+        }
+    }
+
+    /// The name that the account was created with, which is also its alias in the wallet's
+    /// keystore.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn address(&self) -> SuiAddress {
+        self.inner.address
+    }
+
+    /// Take one of the account's gas coins, to pay for a transaction. The coin is not handed out
+    /// again until it is returned by [`Self::return_gas`], which [`crate::TestCluster`] does
+    /// when it executes a transaction whose gas is owned by this account.
+    ///
+    /// Panics if all of the account's gas coins are in use.
+    pub fn take_gas(&self) -> ObjectRef {
+        self.inner.gas.lock().unwrap().pop().unwrap_or_else(|| {
+            panic!(
+                "All gas coins of account {:?} ({}) are in use",
+                self.inner.name, self.inner.address,
+            )
+        })
+    }
+
+    /// Make a gas coin available to the account's future transactions, at its latest version.
+    /// Replaces any stale reference to the same coin.
+    pub fn return_gas(&self, gas: ObjectRef) {
+        let mut coins = self.inner.gas.lock().unwrap();
+        coins.retain(|coin| coin.0 != gas.0);
+        coins.push(gas);
+    }
+
+    /// The account's gas coins that are not being used by a transaction in flight.
+    pub fn available_gas(&self) -> Vec<ObjectRef> {
+        self.inner.gas.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deterministic fault injection for simulation tests.
+//!
+//! A [`ChaosPlan`] declares faults -- validator kills, network partitions and clock jumps -- and
+//! the checkpoint or epoch at which each of them starts. Running the plan against a
+//! [`TestCluster`] injects the faults as the cluster reaches those points, while the test drives
+//! its own workload, and [`ChaosHandle::wait_for_recovery`] then checks that every validator
+//! makes progress again once all the faults have healed.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::{rngs::OsRng, seq::SliceRandom};
+use sui_simulator::net::NetSim;
+use sui_simulator::task::NodeId;
+use sui_simulator::time::TimeHandle;
+use sui_types::base_types::{AuthorityName, ConciseableName};
+use sui_types::committee::EpochId;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use tracing::info;
+
+use crate::TestCluster;
+
+/// How often the plan checks whether the cluster has reached the trigger of its next fault.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The point in the cluster's history at which a fault is injected, as observed by the
+/// cluster's fullnode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChaosTrigger {
+    /// Once the fullnode has executed this checkpoint.
+    Checkpoint(CheckpointSequenceNumber),
+    /// Once the fullnode has entered this epoch.
+    Epoch(EpochId),
+}
+
+#[derive(Clone, Debug)]
+pub enum ChaosFault {
+    /// Stop `count` randomly chosen validators, and restart them after `restart_after`.
+    KillValidators {
+        count: usize,
+        restart_after: Duration,
+    },
+
+    /// Cut `minority` randomly chosen validators off from every other node, in both directions,
+    /// for `duration`. Nodes that restart during the partition get new simulator node IDs, and
+    /// are not partitioned.
+    Partition { minority: usize, duration: Duration },
+
+    /// Jump the simulated clock forward, as observed by every node.
+    ClockJump(Duration),
+}
+
+/// A schedule of faults to inject into a [`TestCluster`].
+///
+/// ```ignore
+/// let chaos = ChaosPlan::new()
+///     .at_checkpoint(
+///         5,
+///         ChaosFault::KillValidators { count: 1, restart_after: Duration::from_secs(5) },
+///     )
+///     .at_epoch(
+///         1,
+///         ChaosFault::Partition { minority: 1, duration: Duration::from_secs(10) },
+///     )
+///     .run(cluster.clone());
+///
+/// // ... drive the workload ...
+///
+/// chaos.wait_for_recovery(Duration::from_secs(60)).await;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChaosPlan {
+    events: Vec<(ChaosTrigger, ChaosFault)>,
+}
+
+impl ChaosPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn at_checkpoint(self, checkpoint: CheckpointSequenceNumber, fault: ChaosFault) -> Self {
+        self.at(ChaosTrigger::Checkpoint(checkpoint), fault)
+    }
+
+    pub fn at_epoch(self, epoch: EpochId, fault: ChaosFault) -> Self {
+        self.at(ChaosTrigger::Epoch(epoch), fault)
+    }
+
+    pub fn at(mut self, trigger: ChaosTrigger, fault: ChaosFault) -> Self {
+        self.events.push((trigger, fault));
+        self
+    }
+
+    /// Start injecting the plan's faults into `cluster` in the background. Faults are injected
+    /// in the order they were declared, each one once its trigger is reached and the faults
+    /// before it have been injected.
+    pub fn run(self, cluster: Arc<TestCluster>) -> ChaosHandle {
+        let task = tokio::task::spawn({
+            let cluster = cluster.clone();
+            async move {
+                let mut healing = vec![];
+                for (trigger, fault) in self.events {
+                    wait_for_trigger(&cluster, trigger).await;
+                    info!("Injecting {fault:?} at {trigger:?}");
+                    healing.extend(inject(&cluster, fault));
+                }
+
+                for heal in healing {
+                    heal.await.unwrap();
+                }
+                info!("All chaos faults have healed");
+            }
+        });
+
+        ChaosHandle { cluster, task }
+    }
+}
+
+/// A [`ChaosPlan`] being injected into a cluster. Dropping the handle stops injecting faults, but
+/// does not heal the ones that have already been injected.
+pub struct ChaosHandle {
+    cluster: Arc<TestCluster>,
+    task: JoinHandle<()>,
+}
+
+impl ChaosHandle {
+    /// Wait for every fault in the plan to be injected and healed, and then assert that the
+    /// cluster recovers: every validator has to be running, and to execute a checkpoint beyond
+    /// the highest one the fullnode had executed when the last fault healed, within `timeout_dur`.
+    pub async fn wait_for_recovery(mut self, timeout_dur: Duration) {
+        timeout(timeout_dur, &mut self.task)
+            .await
+            .expect("Timed out waiting for chaos faults to heal")
+            .expect("Chaos plan panicked");
+
+        for validator in self.cluster.swarm.validator_nodes() {
+            assert!(
+                validator.is_running(),
+                "Validator {:?} did not restart after chaos",
+                validator.name().concise()
+            );
+        }
+
+        let target = fullnode_checkpoint(&self.cluster) + 1;
+        let handles = self.cluster.all_validator_handles();
+        timeout(timeout_dur, async {
+            loop {
+                let recovered = handles.iter().all(|handle| {
+                    handle.with(|node| {
+                        node.state()
+                            .get_latest_checkpoint_sequence_number()
+                            .is_ok_and(|checkpoint| checkpoint >= target)
+                    })
+                });
+
+                if recovered {
+                    return;
+                }
+                sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| {
+            panic!(
+                "Timed out waiting for every validator to execute checkpoint {target} after chaos"
+            )
+        });
+
+        info!("Cluster recovered from chaos, all validators reached checkpoint {target}");
+    }
+}
+
+impl Drop for ChaosHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn wait_for_trigger(cluster: &TestCluster, trigger: ChaosTrigger) {
+    loop {
+        let reached = match trigger {
+            ChaosTrigger::Checkpoint(checkpoint) => fullnode_checkpoint(cluster) >= checkpoint,
+            ChaosTrigger::Epoch(epoch) => cluster
+                .fullnode_handle
+                .sui_node
+                .with(|node| node.state().epoch_store_for_testing().epoch() >= epoch),
+        };
+
+        if reached {
+            return;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn fullnode_checkpoint(cluster: &TestCluster) -> CheckpointSequenceNumber {
+    cluster
+        .fullnode_handle
+        .sui_node
+        .with(|node| node.state().get_latest_checkpoint_sequence_number())
+        .unwrap_or_default()
+}
+
+/// Inject `fault` into `cluster`, returning a task that heals it, if it needs healing.
+fn inject(cluster: &Arc<TestCluster>, fault: ChaosFault) -> Option<JoinHandle<()>> {
+    match fault {
+        ChaosFault::KillValidators {
+            count,
+            restart_after,
+        } => {
+            let victims = choose_validators(cluster, count);
+            for name in &victims {
+                info!("Killing validator {:?}", name.concise());
+                cluster.stop_node(name);
+            }
+
+            let cluster = cluster.clone();
+            Some(tokio::task::spawn(async move {
+                sleep(restart_after).await;
+                for name in &victims {
+                    info!("Restarting validator {:?}", name.concise());
+                    cluster.start_node(name).await;
+                }
+            }))
+        }
+
+        ChaosFault::Partition { minority, duration } => {
+            let minority: HashSet<_> = choose_validators(cluster, minority)
+                .iter()
+                .filter_map(|name| sim_node_id(cluster, name))
+                .collect();
+
+            let rest: Vec<_> = cluster
+                .all_node_handles()
+                .iter()
+                .map(|handle| handle.with(|node| node.get_sim_node_id()))
+                .filter(|id| !minority.contains(id))
+                .collect();
+
+            info!("Partitioning {minority:?} from {rest:?} for {duration:?}");
+            let net = sui_simulator::plugin::simulator::<NetSim>();
+            for a in &minority {
+                for b in &rest {
+                    net.clog_link(*a, *b, duration);
+                    net.clog_link(*b, *a, duration);
+                }
+            }
+
+            // Links unclog on their own once the duration has passed.
+            Some(tokio::task::spawn(sleep(duration)))
+        }
+
+        ChaosFault::ClockJump(jump) => {
+            TimeHandle::current().advance(jump);
+            None
+        }
+    }
+}
+
+/// Choose `count` of the cluster's running validators at random.
+fn choose_validators(cluster: &TestCluster, count: usize) -> Vec<AuthorityName> {
+    let running: Vec<_> = cluster
+        .swarm
+        .validator_nodes()
+        .filter(|node| node.is_running())
+        .map(|node| node.name())
+        .collect();
+
+    assert!(
+        count <= running.len(),
+        "Cannot choose {count} of {} running validators",
+        running.len()
+    );
+
+    running
+        .choose_multiple(&mut OsRng, count)
+        .copied()
+        .collect()
+}
+
+fn sim_node_id(cluster: &TestCluster, name: &AuthorityName) -> Option<NodeId> {
+    cluster
+        .swarm
+        .node(name)?
+        .get_node_handle()
+        .map(|handle| handle.with(|node| node.get_sim_node_id()))
+}
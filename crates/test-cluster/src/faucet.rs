@@ -0,0 +1,81 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use std::sync::Arc;
+
+use sui_config::{SUI_CLIENT_CONFIG, SUI_KEYSTORE_FILENAME};
+use sui_faucet::{create_wallet_context, serve_faucet, AppState, FaucetConfig, LocalFaucet};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
+use sui_sdk::sui_client_config::{SuiClientConfig, SuiEnv};
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::SuiKeyPair;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// The balance that the faucet's account is funded with when the cluster starts, in MIST.
+pub const FAUCET_BALANCE: u64 = 1_000_000_000_000_000;
+
+/// A faucet serving a [`crate::TestCluster`], started by [`crate::TestClusterBuilder::with_faucet`].
+/// The faucet stops when its handle is dropped.
+pub(crate) struct FaucetHandle {
+    pub url: String,
+    task: JoinHandle<()>,
+}
+
+impl FaucetHandle {
+    /// Start a faucet that dispenses coins from `address`, whose key is `keypair`, over the RPC at
+    /// `rpc_url`. Its wallet's config is written to `dir`, and it serves requests on a free port,
+    /// rather than the one in `config`.
+    pub(crate) async fn start(
+        dir: &Path,
+        rpc_url: String,
+        address: SuiAddress,
+        keypair: SuiKeyPair,
+        config: FaucetConfig,
+    ) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut keystore = Keystore::from(FileBasedKeystore::load_or_create(
+            &dir.join(SUI_KEYSTORE_FILENAME),
+        )?);
+        keystore.import(None, keypair).await?;
+
+        SuiClientConfig {
+            keystore,
+            external_keys: None,
+            envs: vec![SuiEnv {
+                alias: "localnet".to_string(),
+                rpc: rpc_url,
+                ws: None,
+                basic_auth: None,
+            }],
+            active_address: Some(address),
+            active_env: Some("localnet".to_string()),
+        }
+        .persisted(&dir.join(SUI_CLIENT_CONFIG))
+        .save()?;
+
+        let wallet = create_wallet_context(config.wallet_client_timeout_secs, dir.to_path_buf())?;
+        let faucet = LocalFaucet::new(wallet, config.clone()).await?;
+        let app_state = Arc::new(AppState { faucet, config });
+
+        let listener = tokio::net::TcpListener::bind((app_state.config.host_ip, 0)).await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        info!("Starting faucet at {url}");
+
+        let task = tokio::spawn(async move {
+            if let Err(e) = serve_faucet(app_state, listener).await {
+                tracing::error!("Faucet stopped: {e}");
+            }
+        });
+
+        Ok(Self { url, task })
+    }
+}
+
+impl Drop for FaucetHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
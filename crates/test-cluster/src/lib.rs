@@ -1,27 +1,32 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Context as _;
 use futures::{future::join_all, StreamExt};
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
 use mysten_common::fatal;
 use rand::{distributions::*, rngs::OsRng, seq::SliceRandom};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use sui_config::genesis::Genesis;
 use sui_config::node::{AuthorityOverloadConfig, DBCheckpointConfig, RunWithRange};
-use sui_config::{Config, ExecutionCacheConfig, SUI_CLIENT_CONFIG, SUI_NETWORK_CONFIG};
+use sui_config::{
+    Config, ExecutionCacheConfig, SUI_CLIENT_CONFIG, SUI_FULLNODE_CONFIG, SUI_NETWORK_CONFIG,
+};
 use sui_config::{NodeConfig, PersistedConfig, SUI_KEYSTORE_FILENAME};
 use sui_core::authority_aggregator::AuthorityAggregator;
 use sui_core::authority_client::NetworkAuthorityClient;
+use sui_framework::BuiltInFramework;
 use sui_json_rpc_types::{
     SuiExecutionStatus, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
     TransactionFilter,
 };
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
+use sui_move_build::BuildConfig;
 use sui_node::SuiNodeHandle;
 use sui_protocol_config::{Chain, ProtocolVersion};
 use sui_sdk::apis::QuorumDriverApi;
@@ -40,29 +45,79 @@ use sui_swarm_config::network_config_builder::{
 use sui_swarm_config::node_config_builder::{FullnodeConfigBuilder, ValidatorConfigBuilder};
 use sui_test_transaction_builder::TestTransactionBuilder;
 use sui_types::base_types::ConciseableName;
-use sui_types::base_types::{AuthorityName, ObjectID, ObjectRef, SuiAddress};
+use sui_types::base_types::{AuthorityName, ObjectID, ObjectRef, SuiAddress, TransactionDigest};
 use sui_types::committee::CommitteeTrait;
 use sui_types::committee::{Committee, EpochId};
 use sui_types::crypto::KeypairTraits;
 use sui_types::crypto::SuiKeyPair;
+use sui_types::crypto::{get_key_pair, Ed25519KeyPair};
 use sui_types::effects::{TransactionEffects, TransactionEvents};
 use sui_types::error::SuiResult;
 use sui_types::message_envelope::Message;
 use sui_types::object::Object;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::sui_system_state::epoch_start_sui_system_state::EpochStartSystemStateTrait;
 use sui_types::sui_system_state::SuiSystemState;
 use sui_types::sui_system_state::SuiSystemStateTrait;
+use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
 use sui_types::supported_protocol_versions::SupportedProtocolVersions;
 use sui_types::traffic_control::{PolicyConfig, RemoteFirewallConfig};
 use sui_types::transaction::{
-    CertifiedTransaction, Transaction, TransactionData, TransactionDataAPI, TransactionKind,
+    Argument, CertifiedTransaction, Command, ObjectArg, Transaction, TransactionData,
+    TransactionDataAPI, TransactionKind,
 };
+use sui_types::{Identifier, SUI_SYSTEM_PACKAGE_ID};
+use tempfile::TempDir;
 use tokio::time::{timeout, Instant};
 use tokio::{task::JoinHandle, time::sleep};
 use tracing::{error, info};
 
+mod accounts;
+mod faucet;
 mod test_indexer_handle;
 
+#[cfg(msim)]
+pub mod chaos;
+
+pub use accounts::{TestAccount, ACCOUNT_GAS_COINS, ACCOUNT_GAS_COIN_BALANCE};
+pub use faucet::FAUCET_BALANCE;
+pub use sui_faucet::FaucetConfig;
+pub use telemetry_subscribers::log_capture::{CapturedEvent, LogCapture};
+
+/// Assert that a [`LogCapture`] has captured an event whose message contains `pattern`.
+///
+/// ```ignore
+/// let logs = cluster.capture_logs("sui_core::execution_scheduler=debug");
+/// // ... drive the workload ...
+/// assert_logged!(logs, "Settling balances");
+/// ```
+#[macro_export]
+macro_rules! assert_logged {
+    ($capture:expr, $pattern:expr $(,)?) => {{
+        let capture = &$capture;
+        let pattern: &str = $pattern;
+        assert!(
+            capture.contains(pattern),
+            "No captured log event contains {pattern:?}, captured:\n{}",
+            capture.dump(),
+        );
+    }};
+}
+
+/// Assert that a [`LogCapture`] has not captured any event whose message contains `pattern`.
+#[macro_export]
+macro_rules! assert_not_logged {
+    ($capture:expr, $pattern:expr $(,)?) => {{
+        let capture = &$capture;
+        let pattern: &str = $pattern;
+        assert!(
+            !capture.contains(pattern),
+            "Captured a log event containing {pattern:?}, captured:\n{}",
+            capture.dump(),
+        );
+    }};
+}
+
 const NUM_VALIDATOR: usize = 4;
 
 pub struct FullNodeHandle {
@@ -94,6 +149,15 @@ pub struct TestCluster {
     pub fullnode_handle: FullNodeHandle,
     indexer_handle: Option<test_indexer_handle::IndexerHandle>,
     transaction_driver_percentage: Option<u8>,
+    /// IDs of the packages added at genesis by [TestClusterBuilder::with_genesis_packages].
+    genesis_packages: Vec<ObjectID>,
+    /// Accounts added to the wallet by [TestCluster::account], by name.
+    accounts: BTreeMap<String, TestAccount>,
+    /// The faucet started by [TestClusterBuilder::with_faucet], if any.
+    faucet: Option<faucet::FaucetHandle>,
+    // Temporary directory that a snapshot was restored into, kept alive (after the swarm) for the
+    // lifetime of the cluster.
+    _restored_dir: Option<TempDir>,
 }
 
 impl TestCluster {
@@ -134,6 +198,12 @@ impl TestCluster {
         self.wallet.get_addresses()
     }
 
+    /// IDs of the packages added at genesis by [TestClusterBuilder::with_genesis_packages], in
+    /// the order they were added.
+    pub fn genesis_packages(&self) -> &[ObjectID] {
+        &self.genesis_packages
+    }
+
     // Helper function to get the 0th address in WalletContext
     pub fn get_address_0(&self) -> SuiAddress {
         self.get_addresses()[0]
@@ -149,6 +219,107 @@ impl TestCluster {
         self.get_addresses()[2]
     }
 
+    /// The URL of the faucet serving this cluster, for requesting gas over HTTP, as in a local
+    /// network. Panics if the cluster was not built [TestClusterBuilder::with_faucet].
+    pub fn faucet_url(&self) -> &str {
+        &self
+            .faucet
+            .as_ref()
+            .expect("Cluster was built without a faucet, see TestClusterBuilder::with_faucet")
+            .url
+    }
+
+    /// Fund a new account for the faucet from one of the wallet's addresses, and start serving
+    /// the faucet with `config`.
+    async fn start_faucet(&mut self, config: FaucetConfig) -> anyhow::Result<()> {
+        let (address, keypair) = get_key_pair::<Ed25519KeyPair>();
+        let tx = self
+            .test_transaction_builder()
+            .await
+            .transfer_sui(Some(FAUCET_BALANCE), address)
+            .build();
+        self.sign_and_execute_transaction(&tx).await;
+
+        let rpc_url = self.wallet.get_active_env()?.rpc.clone();
+        self.faucet = Some(
+            faucet::FaucetHandle::start(
+                &self.swarm.dir().join("faucet"),
+                rpc_url,
+                address,
+                SuiKeyPair::Ed25519(keypair),
+                config,
+            )
+            .await?,
+        );
+
+        Ok(())
+    }
+
+    /// The account named `name`, which is created the first time it is asked for: Its key is added
+    /// to the wallet (under `name` as its alias), and it is funded with [ACCOUNT_GAS_COINS] gas
+    /// coins from the wallet's other addresses.
+    pub async fn account(&mut self, name: &str) -> TestAccount {
+        if let Some(account) = self.accounts.get(name) {
+            return account.clone();
+        }
+
+        // Fund the account from one of the wallet's original addresses, to avoid spending gas
+        // coins that another named account is tracking.
+        let mut funder = None;
+        for sender in self.get_addresses() {
+            if self.accounts.values().any(|a| a.address() == sender) {
+                continue;
+            }
+
+            if let Some(gas) = self
+                .wallet
+                .get_one_gas_object_owned_by_address(sender)
+                .await
+                .unwrap()
+            {
+                funder = Some((sender, gas));
+                break;
+            }
+        }
+
+        let (sender, gas) = funder.expect("No gas to fund a new account with");
+        let (address, keypair) = get_key_pair::<Ed25519KeyPair>();
+        self.wallet
+            .add_account(Some(name.to_string()), SuiKeyPair::Ed25519(keypair))
+            .await;
+
+        let pt = {
+            let mut builder = ProgrammableTransactionBuilder::new();
+            builder
+                .pay_sui(
+                    vec![address; ACCOUNT_GAS_COINS],
+                    vec![ACCOUNT_GAS_COIN_BALANCE; ACCOUNT_GAS_COINS],
+                )
+                .unwrap();
+            builder.finish()
+        };
+
+        let tx = self
+            .test_transaction_builder_with_gas_object(sender, gas)
+            .await
+            .programmable(pt)
+            .build();
+        let effects = self
+            .sign_and_execute_transaction(&tx)
+            .await
+            .effects
+            .unwrap();
+        let gas = effects
+            .created()
+            .iter()
+            .map(|created| created.reference.to_object_ref())
+            .collect();
+
+        let account = TestAccount::new(name.to_string(), address, gas);
+        self.accounts.insert(name.to_string(), account.clone());
+        account
+    }
+
     pub fn fullnode_config_builder(&self) -> FullnodeConfigBuilder {
         self.swarm.get_fullnode_config_builder()
     }
@@ -200,6 +371,19 @@ impl TestCluster {
         self.swarm.config().genesis.clone()
     }
 
+    /// Start buffering the tracing events that match `filter` (in `RUST_LOG` syntax, e.g.
+    /// `sui_core::execution_scheduler=debug`), from every node in the cluster, until the returned
+    /// capture is dropped. Events are captured regardless of the log level used for printing, so
+    /// tests can assert on debug output without enabling it for the whole test run. Use with
+    /// [`assert_logged!`] and [`assert_not_logged!`].
+    ///
+    /// All nodes share the process-wide subscriber, so the capture also sees events from other
+    /// clusters in the same process (e.g. from tests running concurrently).
+    pub fn capture_logs(&self, filter: &str) -> LogCapture {
+        LogCapture::new(filter)
+            .unwrap_or_else(|e| panic!("Invalid log capture filter {filter:?}: {e}"))
+    }
+
     pub fn stop_node(&self, name: &AuthorityName) {
         self.swarm.node(name).unwrap().stop();
     }
@@ -221,6 +405,56 @@ impl TestCluster {
         tokio::time::sleep(Duration::from_secs(3)).await;
     }
 
+    /// Persist the state of this cluster -- the configs and stores of its validators and fullnode
+    /// -- to `dir`, so that it can be restored by [`TestClusterBuilder::from_snapshot`]. This
+    /// allows expensive setup (publishing packages, creating objects) to be shared between tests,
+    /// including tests in other binaries.
+    ///
+    /// All nodes need to be stopped to take a consistent snapshot, so this consumes the cluster.
+    /// Clusters with an indexer-backed RPC cannot be snapshotted.
+    pub async fn snapshot_to(self, dir: &Path) -> anyhow::Result<()> {
+        let TestCluster {
+            swarm,
+            wallet,
+            fullnode_handle,
+            indexer_handle,
+            ..
+        } = self;
+
+        if indexer_handle.is_some() {
+            anyhow::bail!("Cannot snapshot a cluster with an indexer-backed RPC");
+        }
+
+        info!("Snapshotting cluster to {}", dir.display());
+
+        // Release all references to the nodes before stopping them, so that their stores are
+        // closed before they are copied.
+        drop(wallet);
+        drop(fullnode_handle);
+        swarm.all_nodes().for_each(|node| node.stop());
+
+        let fullnode = swarm
+            .fullnodes()
+            .next()
+            .context("Cluster has no fullnode")?;
+
+        let mut network_config = NetworkConfig::load(swarm.dir().join(SUI_NETWORK_CONFIG))?;
+        let mut fullnode_config = fullnode.config().clone();
+
+        // Store paths relative to the snapshot, so that it can be restored anywhere.
+        let root = Path::new("");
+        for config in &mut network_config.validator_configs {
+            rebase_node_config(config, swarm.dir(), root)?;
+        }
+        rebase_node_config(&mut fullnode_config, swarm.dir(), root)?;
+
+        copy_dir_all(swarm.dir(), dir)?;
+        network_config.save(dir.join(SUI_NETWORK_CONFIG))?;
+        fullnode_config.save(dir.join(SUI_FULLNODE_CONFIG))?;
+
+        Ok(())
+    }
+
     pub async fn start_node(&self, name: &AuthorityName) {
         let node = self.swarm.node(name).unwrap();
         if node.is_running() {
@@ -238,6 +472,107 @@ impl TestCluster {
         self.swarm.spawn_new_node(node_config).await
     }
 
+    /// Add the validator described by `validator` to the committee, through the same on-chain flow
+    /// a real validator would follow: It registers as a candidate, [ValidatorGenesisConfig::stake]
+    /// is staked with it from the wallet, and it requests to join. The cluster is then
+    /// reconfigured, and the validator is started once it is a member of the new epoch's
+    /// committee, and has caught up with the rest of the network.
+    pub async fn add_validator(&mut self, validator: ValidatorGenesisConfig) -> SuiNodeHandle {
+        let name: AuthorityName = validator.key_pair.public().into();
+        let address: SuiAddress = (&validator.account_key_pair.public()).into();
+        let rgp = self.get_reference_gas_price().await;
+
+        // The validator pays for its own registration, so it needs some gas of its own.
+        let gas = self
+            .fund_address_and_return_gas(rgp, Some(ACCOUNT_GAS_COIN_BALANCE), address)
+            .await;
+
+        let tx = TestTransactionBuilder::new(address, gas, rgp)
+            .call_request_add_validator_candidate(
+                &validator.to_validator_info_with_random_name().into(),
+            )
+            .build_and_sign(&validator.account_key_pair);
+        let effects = self.execute_transaction(tx).await.effects.unwrap();
+        let gas = effects.gas_object().reference.to_object_ref();
+
+        let pt = {
+            let mut builder = ProgrammableTransactionBuilder::new();
+            let system = builder.obj(ObjectArg::SUI_SYSTEM_MUT).unwrap();
+            let amount = builder.pure(validator.stake).unwrap();
+            let stake = builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount]));
+            let staked_with = builder.pure(address).unwrap();
+            builder.programmable_move_call(
+                SUI_SYSTEM_PACKAGE_ID,
+                SUI_SYSTEM_MODULE_NAME.to_owned(),
+                Identifier::new("request_add_stake").unwrap(),
+                vec![],
+                vec![system, stake, staked_with],
+            );
+            builder.finish()
+        };
+
+        let tx = self
+            .test_transaction_builder()
+            .await
+            .programmable(pt)
+            .build();
+        self.sign_and_execute_transaction(&tx).await;
+
+        let tx = TestTransactionBuilder::new(address, gas, rgp)
+            .call_request_add_validator()
+            .build_and_sign(&validator.account_key_pair);
+        self.execute_transaction(tx).await;
+
+        self.trigger_reconfiguration().await;
+        let committee = self.committee();
+        assert!(
+            committee.authority_exists(&name),
+            "Validator {} did not join the committee in epoch {}",
+            name.concise(),
+            committee.epoch,
+        );
+
+        let handle = self.spawn_new_validator(validator).await;
+        self.wait_for_epoch_all_nodes(committee.epoch).await;
+        handle
+    }
+
+    /// Remove the validator called `name` from the committee, by having it request to leave, and
+    /// reconfiguring the cluster. The validator's node is left running (but no longer as a member
+    /// of the committee), use [TestCluster::stop_node] to stop it.
+    pub async fn remove_validator(&self, name: &AuthorityName) {
+        let handle = self
+            .swarm
+            .node(name)
+            .and_then(|node| node.get_node_handle())
+            .unwrap_or_else(|| panic!("Validator {} is not running", name.concise()));
+
+        let address = handle.with(|node| node.get_config().sui_address());
+        let gas = self
+            .wallet
+            .get_one_gas_object_owned_by_address(address)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("Validator {} has no gas", name.concise()));
+
+        let rgp = self.get_reference_gas_price().await;
+        let tx = handle.with(|node| {
+            TestTransactionBuilder::new(address, gas, rgp)
+                .call_request_remove_validator()
+                .build_and_sign(node.get_config().account_key_pair.keypair())
+        });
+        self.execute_transaction(tx).await;
+
+        self.trigger_reconfiguration().await;
+        let committee = self.committee();
+        assert!(
+            !committee.authority_exists(name),
+            "Validator {} did not leave the committee in epoch {}",
+            name.concise(),
+            committee.epoch,
+        );
+    }
+
     pub fn random_node_restarter(self: &Arc<Self>) -> RandomNodeRestarter {
         RandomNodeRestarter::new(self.clone())
     }
@@ -575,6 +910,16 @@ impl TestCluster {
             .await
     }
 
+    /// A transaction builder for a transaction sent by `account`, which pays for gas with one of
+    /// its gas coins (see [TestAccount::take_gas]).
+    pub async fn test_transaction_builder_with_account(
+        &self,
+        account: &TestAccount,
+    ) -> TestTransactionBuilder {
+        self.test_transaction_builder_with_gas_object(account.address(), account.take_gas())
+            .await
+    }
+
     pub async fn test_transaction_builder_with_gas_object(
         &self,
         sender: SuiAddress,
@@ -600,8 +945,19 @@ impl TestCluster {
     /// Also expects the effects status to be ExecutionStatus::Success.
     /// This function is recommended for transaction execution since it most resembles the
     /// production path.
+    ///
+    /// If the gas was paid by an account created by [TestCluster::account], the gas coin is
+    /// returned to that account, at its new version.
     pub async fn execute_transaction(&self, tx: Transaction) -> SuiTransactionBlockResponse {
-        self.wallet.execute_transaction_must_succeed(tx).await
+        let gas_owner = tx.transaction_data().gas_owner();
+        let response = self.wallet.execute_transaction_must_succeed(tx).await;
+
+        if let Some(account) = self.accounts.values().find(|a| a.address() == gas_owner) {
+            let effects = response.effects.as_ref().unwrap();
+            account.return_gas(effects.gas_object().reference.to_object_ref());
+        }
+
+        response
     }
 
     /// Different from `execute_transaction` which returns RPC effects types, this function
@@ -757,6 +1113,42 @@ impl TestCluster {
             n.with(|node| node.set_safe_mode_expected(value));
         }
     }
+
+    /// Stop validators from releasing new rounds of on-chain randomness, so that tests can
+    /// control which round transactions that use randomness observe. Rounds generated while
+    /// paused are queued, and released in order by [`Self::step_randomness`] or
+    /// [`Self::resume_randomness`].
+    ///
+    /// Checkpoints stop being created while randomness is paused and rounds are pending, so
+    /// tests should not wait for checkpoints or epoch changes without releasing them. Validators
+    /// that are started or restarted after this call are not paused.
+    pub fn pause_randomness(&self) {
+        for n in self.running_validator_handles() {
+            n.with(|node| node.state().randomness_gate().pause());
+        }
+    }
+
+    /// Release the next `rounds` pending randomness rounds on every validator, leaving
+    /// randomness paused afterwards.
+    pub fn step_randomness(&self, rounds: u64) {
+        for n in self.running_validator_handles() {
+            n.with(|node| node.state().randomness_gate().step(rounds));
+        }
+    }
+
+    /// Release all pending randomness rounds, and stop pausing new ones.
+    pub fn resume_randomness(&self) {
+        for n in self.running_validator_handles() {
+            n.with(|node| node.state().randomness_gate().resume());
+        }
+    }
+
+    fn running_validator_handles(&self) -> Vec<SuiNodeHandle> {
+        self.swarm
+            .validator_nodes()
+            .filter_map(|n| n.get_node_handle())
+            .collect()
+    }
 }
 
 pub struct RandomNodeRestarter {
@@ -828,7 +1220,10 @@ impl Drop for RandomNodeRestarter {
 pub struct TestClusterBuilder {
     genesis_config: Option<GenesisConfig>,
     network_config: Option<NetworkConfig>,
+    snapshot: Option<PathBuf>,
+    restored_dir: Option<TempDir>,
     additional_objects: Vec<Object>,
+    genesis_packages: Vec<PathBuf>,
     num_validators: Option<usize>,
     validators: Option<Vec<ValidatorGenesisConfig>>,
     fullnode_rpc_port: Option<u16>,
@@ -860,6 +1255,8 @@ pub struct TestClusterBuilder {
 
     transaction_driver_percentage: Option<u8>,
 
+    faucet_config: Option<FaucetConfig>,
+
     #[cfg(msim)]
     inject_synthetic_execution_time: bool,
 }
@@ -869,8 +1266,11 @@ impl TestClusterBuilder {
         TestClusterBuilder {
             genesis_config: None,
             network_config: None,
+            snapshot: None,
+            restored_dir: None,
             chain_override: None,
             additional_objects: vec![],
+            genesis_packages: vec![],
             fullnode_rpc_port: None,
             num_validators: None,
             validators: None,
@@ -897,6 +1297,7 @@ impl TestClusterBuilder {
             ),
             indexer_backed_rpc: false,
             transaction_driver_percentage: None,
+            faucet_config: None,
             #[cfg(msim)]
             inject_synthetic_execution_time: false,
         }
@@ -924,6 +1325,18 @@ impl TestClusterBuilder {
         self
     }
 
+    /// Start a cluster from a snapshot taken by [`TestCluster::snapshot_to`]. The snapshot is
+    /// copied into the cluster's config directory (or a temporary directory) before the cluster
+    /// starts, so it is left untouched, and can be restored any number of times.
+    ///
+    /// The cluster's validators, genesis, and accounts all come from the snapshot, so they cannot
+    /// be configured separately.
+    pub fn from_snapshot(dir: impl Into<PathBuf>) -> Self {
+        let mut builder = Self::new();
+        builder.snapshot = Some(dir.into());
+        builder
+    }
+
     pub fn set_genesis_config(mut self, genesis_config: GenesisConfig) -> Self {
         assert!(self.genesis_config.is_none() && self.network_config.is_none());
         self.genesis_config = Some(genesis_config);
@@ -941,6 +1354,23 @@ impl TestClusterBuilder {
         self
     }
 
+    /// Add `objects` to genesis, e.g. parent objects or accumulator accounts that a test needs,
+    /// instead of creating them with transactions.
+    pub fn with_genesis_objects(self, objects: Vec<Object>) -> Self {
+        self.with_objects(objects)
+    }
+
+    /// Build the Move packages at `paths`, and add them to genesis, each at a fresh ID, instead of
+    /// publishing them with transactions. Their IDs are available from
+    /// [TestCluster::genesis_packages], in the same order.
+    ///
+    /// The packages can only depend on the system packages, and their `init` functions are not
+    /// run.
+    pub fn with_genesis_packages(mut self, paths: Vec<PathBuf>) -> Self {
+        self.genesis_packages.extend(paths);
+        self
+    }
+
     /// Set the number of default validators to spawn. Can be overridden by `with_validators`, if
     /// you need to provide more specific genesis configs for each validator.
     pub fn with_num_validators(mut self, num: usize) -> Self {
@@ -1116,6 +1546,14 @@ impl TestClusterBuilder {
         self
     }
 
+    /// Start a faucet alongside the cluster, dispensing coins according to `config` (its host
+    /// and port are ignored, in favour of a free port), whose URL is available from
+    /// [TestCluster::faucet_url].
+    pub fn with_faucet(mut self, config: FaucetConfig) -> Self {
+        self.faucet_config = Some(config);
+        self
+    }
+
     pub fn with_chain_override(mut self, chain: Chain) -> Self {
         self.chain_override = Some(chain);
         self
@@ -1183,6 +1621,13 @@ impl TestClusterBuilder {
             data_ingestion_path = Some(self.data_ingestion_dir.as_ref().unwrap().to_path_buf());
         }
 
+        let mut genesis_packages = vec![];
+        for path in &self.genesis_packages {
+            let package = genesis_package(path);
+            genesis_packages.push(package.id());
+            self.additional_objects.push(package);
+        }
+
         let swarm = self.start_swarm().await.unwrap();
         let working_dir = swarm.dir();
 
@@ -1223,13 +1668,23 @@ impl TestClusterBuilder {
 
         let transaction_driver_percentage = self.transaction_driver_percentage;
 
-        TestCluster {
+        let mut cluster = TestCluster {
             swarm,
             wallet,
             fullnode_handle,
             indexer_handle,
             transaction_driver_percentage,
+            genesis_packages,
+            accounts: BTreeMap::new(),
+            faucet: None,
+            _restored_dir: self.restored_dir.take(),
+        };
+
+        if let Some(config) = self.faucet_config.take() {
+            cluster.start_faucet(config).await.unwrap();
         }
+
+        cluster
     }
 
     /// Start a Swarm and set up WalletConfig
@@ -1274,6 +1729,33 @@ impl TestClusterBuilder {
             builder = builder.with_network_config(network_config);
         }
 
+        if let Some(snapshot) = self.snapshot.take() {
+            let dir = match self.config_dir.take() {
+                Some(dir) => dir,
+                None => self
+                    .restored_dir
+                    .insert(mysten_common::tempdir()?)
+                    .path()
+                    .to_path_buf(),
+            };
+
+            copy_dir_all(&snapshot, &dir)?;
+
+            let root = Path::new("");
+            let mut network_config = NetworkConfig::load(dir.join(SUI_NETWORK_CONFIG))?;
+            for config in &mut network_config.validator_configs {
+                rebase_node_config(config, root, &dir)?;
+            }
+
+            let mut fullnode_config = NodeConfig::load(dir.join(SUI_FULLNODE_CONFIG))?;
+            rebase_node_config(&mut fullnode_config, root, &dir)?;
+
+            builder = builder
+                .dir(dir)
+                .with_network_config(network_config)
+                .with_fullnode_config(fullnode_config);
+        }
+
         if let Some(authority_overload_config) = self.authority_overload_config.take() {
             builder = builder.with_authority_overload_config(authority_overload_config);
         }
@@ -1364,6 +1846,66 @@ impl TestClusterBuilder {
     }
 }
 
+/// Rewrite the paths to the stores in `config` from being under `from` to being under `to`.
+fn rebase_node_config(config: &mut NodeConfig, from: &Path, to: &Path) -> anyhow::Result<()> {
+    let rebase = |path: &mut PathBuf| -> anyhow::Result<()> {
+        let relative = path
+            .strip_prefix(from)
+            .with_context(|| format!("{} is not under {}", path.display(), from.display()))?;
+        *path = to.join(relative);
+        Ok(())
+    };
+
+    rebase(&mut config.db_path)?;
+    if let Some(consensus_config) = &mut config.consensus_config {
+        rebase(&mut consensus_config.db_path)?;
+    }
+
+    Ok(())
+}
+
+/// Recursively copy the contents of directory `src` into `dst`.
+fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(entry.path(), dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Build the Move package at `path` into a package object at a fresh ID, to add at genesis.
+fn genesis_package(path: &Path) -> Object {
+    let package_id = ObjectID::random();
+    let mut modules: Vec<_> = BuildConfig::new_for_testing()
+        .build(path)
+        .unwrap()
+        .get_modules()
+        .cloned()
+        .collect();
+
+    // The package is built as an unpublished package, at address 0x0, so it is moved to its ID
+    // by replacing that address everywhere it is referenced.
+    for module in &mut modules {
+        for address in &mut module.address_identifiers {
+            if ObjectID::from(*address) == ObjectID::ZERO {
+                *address = package_id.into();
+            }
+        }
+    }
+
+    Object::new_package_for_testing(
+        &modules,
+        TransactionDigest::genesis_marker(),
+        BuiltInFramework::genesis_move_packages(),
+    )
+    .unwrap()
+}
+
 impl Default for TestClusterBuilder {
     fn default() -> Self {
         Self::new()
@@ -1,15 +1,20 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
+use sui_faucet::{FaucetRequest, FaucetResponse, RequestStatus};
 use sui_framework::BuiltInFramework;
 use sui_json_rpc_api::ReadApiClient;
-use sui_json_rpc_types::SuiObjectResponse;
+use sui_json_rpc_types::{SuiObjectResponse, SuiTransactionBlockEffectsAPI};
 use sui_macros::sim_test;
 use sui_types::{
-    base_types::ObjectID, digests::TransactionDigest, object::Object, MOVE_STDLIB_PACKAGE_ID,
-    SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_ADDRESS, SUI_SYSTEM_PACKAGE_ID,
+    base_types::{FullObjectRef, ObjectID, SuiAddress},
+    digests::TransactionDigest,
+    object::{Object, Owner},
+    MOVE_STDLIB_PACKAGE_ID, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_ADDRESS, SUI_SYSTEM_PACKAGE_ID,
 };
-use test_cluster::TestClusterBuilder;
+use test_cluster::{FaucetConfig, TestClusterBuilder, ACCOUNT_GAS_COINS};
 
 #[sim_test]
 async fn test_additional_objects() {
@@ -25,6 +30,29 @@ async fn test_additional_objects() {
     assert!(matches!(resp, SuiObjectResponse { data: Some(_), .. }));
 }
 
+#[sim_test]
+async fn test_genesis_packages() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.extend(["..", "..", "examples", "move", "basics"]);
+
+    let cluster = TestClusterBuilder::new()
+        .with_genesis_packages(vec![path])
+        .build()
+        .await;
+
+    let [package] = cluster.genesis_packages() else {
+        panic!("Expected one genesis package");
+    };
+
+    // The package can be called into without being published first.
+    let tx = cluster
+        .test_transaction_builder()
+        .await
+        .call_counter_create(*package)
+        .build();
+    cluster.sign_and_execute_transaction(&tx).await;
+}
+
 #[sim_test]
 async fn test_package_override() {
     // `with_objects` can be used to override existing packages.
@@ -88,3 +116,83 @@ async fn test_package_override() {
 
     assert_ne!(framework_ref, modified_ref);
 }
+
+#[sim_test]
+async fn test_named_accounts() {
+    let mut cluster = TestClusterBuilder::new().build().await;
+    let alice = cluster.account("alice").await;
+    let bob = cluster.account("bob").await;
+    let carol = cluster.account("carol").await;
+
+    // Asking for an account again returns the same account.
+    assert_eq!(cluster.account("alice").await.address(), alice.address());
+    assert_eq!(alice.available_gas().len(), ACCOUNT_GAS_COINS);
+
+    // Alice pays for her own transaction, and gets her gas coin back afterwards.
+    let tx = cluster
+        .test_transaction_builder_with_account(&alice)
+        .await
+        .transfer_sui(Some(1_000), carol.address())
+        .build();
+    cluster.sign_and_execute_transaction(&tx).await;
+    assert_eq!(alice.available_gas().len(), ACCOUNT_GAS_COINS);
+
+    // Alice sends one of her coins to Carol, and Bob sponsors the transaction.
+    let coin = alice.take_gas();
+    let sponsor_gas = bob.take_gas();
+    let tx = cluster
+        .test_transaction_builder_with_gas_object(alice.address(), sponsor_gas)
+        .await
+        .with_sponsor(bob.address(), sponsor_gas)
+        .transfer(FullObjectRef::from_fastpath_ref(coin), carol.address())
+        .build();
+    let response = cluster.sign_and_execute_transaction(&tx).await;
+
+    // Bob's gas coin is returned to him, and Alice no longer has the coin she sent.
+    assert_eq!(alice.available_gas().len(), ACCOUNT_GAS_COINS - 1);
+    assert_eq!(bob.available_gas().len(), ACCOUNT_GAS_COINS);
+
+    let effects = response.effects.unwrap();
+    let transferred = effects
+        .mutated()
+        .iter()
+        .find(|o| o.object_id() == coin.0)
+        .unwrap();
+    assert_eq!(
+        transferred.owner,
+        Owner::AddressOwner(carol.address()),
+        "Coin should now be owned by Carol"
+    );
+}
+
+#[tokio::test]
+async fn test_faucet() {
+    let config = FaucetConfig::default();
+    let cluster = TestClusterBuilder::new()
+        .with_faucet(config.clone())
+        .build()
+        .await;
+
+    let recipient = SuiAddress::random_for_testing_only();
+    let response = reqwest::Client::new()
+        .post(format!("{}/v2/gas", cluster.faucet_url()))
+        .json(&FaucetRequest::new_fixed_amount_request(recipient))
+        .send()
+        .await
+        .unwrap()
+        .json::<FaucetResponse>()
+        .await
+        .unwrap();
+
+    assert!(matches!(response.status, RequestStatus::Success));
+    assert_eq!(response.coins_sent.unwrap().len(), config.num_coins);
+
+    let coins = cluster
+        .sui_client()
+        .coin_read_api()
+        .get_coins(recipient, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(coins.data.len(), config.num_coins);
+    assert!(coins.data.iter().all(|c| c.balance == config.amount));
+}
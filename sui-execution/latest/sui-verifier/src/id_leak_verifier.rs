@@ -34,6 +34,7 @@ use sui_types::{
     accumulator_event::ACCUMULATOR_MODULE_NAME,
     authenticator_state::AUTHENTICATOR_STATE_MODULE_NAME,
     clock::CLOCK_MODULE_NAME,
+    derived_object::DERIVED_OBJECT_MODULE_NAME,
     error::{ExecutionError, VMMVerifierErrorSubStatusCode},
     id::OBJECT_MODULE_NAME,
     randomness_state::RANDOMNESS_MODULE_NAME,
@@ -66,6 +67,16 @@ const OBJECT_NEW_UID_FROM_HASH: FunctionIdent = (
     OBJECT_MODULE_NAME,
     ident_str!("new_uid_from_hash"),
 );
+const DERIVED_OBJECT_CLAIM: FunctionIdent = (
+    &SUI_FRAMEWORK_ADDRESS,
+    DERIVED_OBJECT_MODULE_NAME,
+    ident_str!("claim"),
+);
+const DERIVED_OBJECT_CLAIM_FOR_EPOCH: FunctionIdent = (
+    &SUI_FRAMEWORK_ADDRESS,
+    DERIVED_OBJECT_MODULE_NAME,
+    ident_str!("claim_for_epoch"),
+);
 const TS_NEW_OBJECT: FunctionIdent = (
     &SUI_FRAMEWORK_ADDRESS,
     ident_str!(TEST_SCENARIO_MODULE_NAME),
@@ -104,7 +115,13 @@ const SUI_ACCUMULATOR_CREATE: FunctionIdent = (
     ACCUMULATOR_MODULE_NAME,
     ident_str!("create"),
 );
-const FRESH_ID_FUNCTIONS: &[FunctionIdent] = &[OBJECT_NEW, OBJECT_NEW_UID_FROM_HASH, TS_NEW_OBJECT];
+const FRESH_ID_FUNCTIONS: &[FunctionIdent] = &[
+    OBJECT_NEW,
+    OBJECT_NEW_UID_FROM_HASH,
+    DERIVED_OBJECT_CLAIM,
+    DERIVED_OBJECT_CLAIM_FOR_EPOCH,
+    TS_NEW_OBJECT,
+];
 const FUNCTIONS_TO_SKIP: &[FunctionIdent] = &[
     SUI_SYSTEM_CREATE,
     SUI_CLOCK_CREATE,